@@ -25,7 +25,8 @@ use agentic_identity::storage::{
 };
 use agentic_identity::trust::grant::TrustGrantBuilder;
 use agentic_identity::trust::revocation::{Revocation, RevocationReason};
-use agentic_identity::trust::verify::verify_trust_grant;
+use agentic_identity::trust::verify::{verify_trust_grant_with, VerifyOptions};
+use agentic_identity::trust::UsageContext;
 use agentic_identity::{
     ActionContent, ActionType, Capability, IdentityAnchor, IdentityId, ReceiptId, TrustConstraints,
     TrustId,
@@ -231,47 +232,11 @@ fn micros_to_datetime(micros: u64) -> String {
     naive.format("%Y-%m-%d %H:%M:%S UTC").to_string()
 }
 
-/// Parse a duration string like "24h", "7d", "30d", "1h30m", or plain seconds.
+/// Parse a duration string like "24h", "7d", "30d", "1h30m", or a bare
+/// number of hours; see [`agentic_identity::time::parse_duration_micros`].
 /// Returns the duration as microseconds.
 fn parse_duration_to_micros(s: &str) -> Result<u64> {
-    let s = s.trim();
-
-    // Try parsing as plain integer (hours for backward compat or as seconds)
-    if let Ok(n) = s.parse::<u64>() {
-        // Treat bare number as hours
-        return Ok(n * 3600 * 1_000_000);
-    }
-
-    let mut total_micros: u64 = 0;
-    let mut current = String::new();
-
-    for ch in s.chars() {
-        if ch.is_ascii_digit() {
-            current.push(ch);
-        } else {
-            let val: u64 = current
-                .parse()
-                .map_err(|_| anyhow!("invalid duration: {s}"))?;
-            current.clear();
-            match ch {
-                'h' => total_micros += val * 3600 * 1_000_000,
-                'd' => total_micros += val * 86400 * 1_000_000,
-                'm' => total_micros += val * 60 * 1_000_000,
-                's' => total_micros += val * 1_000_000,
-                _ => return Err(anyhow!("unknown duration unit '{}' in '{s}'", ch)),
-            }
-        }
-    }
-
-    if !current.is_empty() {
-        return Err(anyhow!("duration '{s}' is missing a unit (h/d/m/s)"));
-    }
-
-    if total_micros == 0 {
-        return Err(anyhow!("duration must be > 0"));
-    }
-
-    Ok(total_micros)
+    Ok(agentic_identity::time::parse_duration_micros(s)?)
 }
 
 // ── CLI structure ─────────────────────────────────────────────────────────────
@@ -365,6 +330,15 @@ enum Commands {
         /// Output file path (default: stdout)
         #[arg(long, short)]
         output: Option<PathBuf>,
+
+        /// Export a did:key DID document instead of the identity document
+        #[arg(long, conflicts_with = "did_web")]
+        did_key: bool,
+
+        /// Export a did:web DID document to be served from this location
+        /// (host[:port][/path], e.g. example.com/agents/alice)
+        #[arg(long, value_name = "LOCATION")]
+        did_web: Option<String>,
     },
 
     /// Query receipts and trust records by text
@@ -823,9 +797,19 @@ fn main() {
             }
         },
         Commands::Rotate { reason } => cmd_rotate(&identity_name, reason.as_deref(), verbose),
-        Commands::Export { identity, output } => {
+        Commands::Export {
+            identity,
+            output,
+            did_key,
+            did_web,
+        } => {
             let name = identity.unwrap_or(identity_name);
-            cmd_export(&name, output.as_deref(), verbose)
+            let format = match did_web {
+                Some(location) => ExportFormat::DidWeb(location),
+                None if did_key => ExportFormat::DidKey,
+                None => ExportFormat::Identity,
+            };
+            cmd_export(&name, output.as_deref(), &format, verbose)
         }
         Commands::Query { text, limit } => cmd_query_text(&text, limit),
         Commands::Ground { claim, threshold } => cmd_ground_claim(&claim, threshold),
@@ -949,6 +933,7 @@ fn main() {
 /// `aid init [--name NAME]`
 fn cmd_init(name: Option<String>, verbose: bool) -> Result<()> {
     let name = name.unwrap_or_else(|| "default".to_string());
+    agentic_identity::text::validate_text("identity name", &name)?;
     let path = identity_path(&name);
 
     if path.exists() {
@@ -1005,7 +990,7 @@ fn cmd_show(name: &str, _verbose: bool) -> Result<()> {
 
     println!("Identity: {}", name);
     println!("  ID:        {}", doc.id);
-    println!("  Algorithm: {}", doc.algorithm);
+    println!("  Algorithm: {}", doc.algorithm_summary());
     println!("  Public Key: {}", doc.public_key);
     println!("  Created:   {}", micros_to_datetime(doc.created_at));
 
@@ -1246,8 +1231,14 @@ fn cmd_verify_trust(trust_id_str: &str, capability: Option<&str>, _verbose: bool
 
     let requested_capability = capability.unwrap_or("*");
 
-    let verification = verify_trust_grant(&grant, requested_capability, 0, &revocations)
-        .context("verification failed")?;
+    let use_times = store
+        .use_times(&id)
+        .context("failed to read usage ledger")?;
+    let uses = use_times.len() as u64;
+    let options = VerifyOptions::new().context(UsageContext::new().use_times(use_times));
+    let verification =
+        verify_trust_grant_with(&grant, requested_capability, uses, &revocations, &options)
+            .context("verification failed")?;
 
     println!("Trust Grant: {}", grant.id);
     println!("  Grantor:    {}", grant.grantor);
@@ -1266,7 +1257,7 @@ fn cmd_verify_trust(trust_id_str: &str, capability: Option<&str>, _verbose: bool
         println!("  Expires:    {}", micros_to_datetime(expiry));
     }
     if let Some(max) = grant.constraints.max_uses {
-        println!("  Max Uses:   {max}");
+        println!("  Max Uses:   {max} ({uses} used)");
     }
     if grant.delegation_allowed {
         println!(
@@ -1378,7 +1369,7 @@ fn cmd_trust_grant(
     let grantee_key = anchor.public_key_base64();
 
     let mut builder = TrustGrantBuilder::new(anchor.id(), grantee_id.clone(), grantee_key)
-        .capability(Capability::new(capability_uri))
+        .capability(Capability::try_new(capability_uri)?)
         .constraints(constraints);
 
     if allow_delegation {
@@ -1646,8 +1637,23 @@ fn cmd_rotate(identity_name: &str, reason_str: Option<&str>, verbose: bool) -> R
     Ok(())
 }
 
-/// `aid export [--identity NAME] [--output FILE]`
-fn cmd_export(name: &str, output: Option<&std::path::Path>, _verbose: bool) -> Result<()> {
+/// What `aid export` writes.
+enum ExportFormat {
+    /// The identity document itself.
+    Identity,
+    /// A `did:key` DID document.
+    DidKey,
+    /// A `did:web` DID document for the given location.
+    DidWeb(String),
+}
+
+/// `aid export [--identity NAME] [--output FILE] [--did-key | --did-web LOCATION]`
+fn cmd_export(
+    name: &str,
+    output: Option<&std::path::Path>,
+    format: &ExportFormat,
+    _verbose: bool,
+) -> Result<()> {
     let path = identity_path(name);
 
     if !path.exists() {
@@ -1660,8 +1666,18 @@ fn cmd_export(name: &str, output: Option<&std::path::Path>, _verbose: bool) -> R
 
     let doc = read_public_document(&path).context("failed to read identity file")?;
 
-    let json =
-        serde_json::to_string_pretty(&doc).context("failed to serialize identity document")?;
+    let json = match format {
+        ExportFormat::Identity => serde_json::to_string_pretty(&doc),
+        ExportFormat::DidKey => serde_json::to_string_pretty(
+            &doc.to_did_key_document()
+                .context("failed to build DID document")?,
+        ),
+        ExportFormat::DidWeb(location) => serde_json::to_string_pretty(
+            &doc.to_did_web_document(location)
+                .context("failed to build DID document")?,
+        ),
+    }
+    .context("failed to serialize identity document")?;
 
     if let Some(out_path) = output {
         std::fs::write(out_path, &json)
@@ -2013,7 +2029,7 @@ fn cmd_receipt_list(
     }
 
     // Sort by timestamp descending (newest first)
-    receipts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
     // Apply limit
     let total = receipts.len();
@@ -2029,10 +2045,11 @@ fn cmd_receipt_list(
         );
         println!("  {}", "-".repeat(90));
         for receipt in &receipts {
-            let desc_preview = if receipt.action.description.len() > 35 {
-                format!("{}...", &receipt.action.description[..32])
+            let desc = &receipt.action.description;
+            let desc_preview = if desc.chars().count() > 35 {
+                format!("{}...", desc.chars().take(32).collect::<String>())
             } else {
-                receipt.action.description.clone()
+                desc.clone()
             };
             println!(
                 "  {:<25} {:<15} {:<25} {}",
@@ -2223,8 +2240,8 @@ fn cmd_spawn_create(
     let spawn_type = parse_spawn_type(type_str)?;
     let authority: Vec<Capability> = authority_str
         .split(',')
-        .map(|s| Capability::new(s.trim()))
-        .collect();
+        .map(|s| Capability::try_new(s.trim()))
+        .collect::<Result<_, _>>()?;
     let ceiling = authority.clone();
     let lifetime = parse_spawn_lifetime(lifetime_str)?;
 
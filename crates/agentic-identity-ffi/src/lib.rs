@@ -1,7 +1,12 @@
 //! AgenticIdentity C FFI bindings.
 //!
 //! Provides a C-compatible API for the core AgenticIdentity operations:
-//! identity management, action signing, receipt verification, and trust grants.
+//! identity management, action signing, receipt and bundle verification,
+//! trust grants, spawning, continuity, competence and negative proofs.
+//!
+//! Operations on the richer types take a JSON request object and write a
+//! JSON result; nested types use the same JSON form the Rust API serializes
+//! them in, so a record produced by one call can be passed to the next.
 //!
 #![allow(clippy::doc_overindented_list_items)]
 //! # Memory contract
@@ -23,18 +28,37 @@
 //! | `AID_ERR_CRYPTO`      | -3    | Cryptographic operation failed   |
 //! | `AID_ERR_IO`          | -4    | Filesystem I/O failure           |
 //! | `AID_ERR_SERIALIZATION` | -5  | JSON serialization/parse failure |
+//! | `AID_ERR_TOO_LARGE`   | -6    | Input exceeds a size limit       |
+//! | `AID_ERR_INVALID_INPUT` | -7  | An argument was invalid, e.g. a name with a control character |
+//!
+//! The code is coarse; [`aid_last_error_message`] returns the detailed reason
+//! for the most recent failure on the calling thread.
 
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
+use std::fmt;
 use std::os::raw::c_char;
 use std::path::Path;
 
 use agentic_identity::{
+    competence::{record_attempt, AttemptOutcome, CompetenceDomain},
+    continuity::{record_experience, ExperienceEvent, ExperienceType},
+    crypto::sss,
     error::IdentityError,
-    receipt::{receipt::ReceiptBuilder, verify::verify_receipt},
+    identity::RotationReason,
+    negative::{prove_cannot, prove_declared, NegativeDeclaration},
+    receipt::{receipt::ReceiptBuilder, verify::verify_receipt, verify_bundle, ReceiptBundle},
+    spawn::{
+        spawn_child, terminate_spawn, SpawnConstraints, SpawnInfo, SpawnLifetime, SpawnRecord,
+        SpawnType,
+    },
     storage::{load_identity, save_identity},
     trust::{verify::verify_trust_grant, Capability, TrustGrantBuilder},
-    ActionContent, ActionType, IdentityAnchor,
+    ActionContent, ActionType, IdentityAnchor, ReceiptId,
 };
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
 // ── Error codes ───────────────────────────────────────────────────────────────
 
@@ -50,9 +74,36 @@ pub const AID_ERR_CRYPTO: i32 = -3;
 pub const AID_ERR_IO: i32 = -4;
 /// A JSON serialization or deserialization operation failed.
 pub const AID_ERR_SERIALIZATION: i32 = -5;
+/// An input was larger than this library accepts.
+pub const AID_ERR_TOO_LARGE: i32 = -6;
+/// An argument was invalid, e.g. a name or capability URI with a control
+/// character or too few secret shares.
+pub const AID_ERR_INVALID_INPUT: i32 = -7;
+
+// ── Limits ────────────────────────────────────────────────────────────────────
+
+/// Largest `bundle_json` accepted by [`aid_bundle_verify`], in bytes.
+pub const AID_MAX_BUNDLE_BYTES: usize = 16 * 1024 * 1024;
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
 
+thread_local! {
+    /// Detail for the most recent failure on this thread, read back by
+    /// [`aid_last_error_message`].
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Record `detail` as this thread's last error and return `code`.
+fn fail(code: i32, detail: impl fmt::Display) -> i32 {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(detail.to_string()));
+    code
+}
+
+/// Record a null-pointer failure and return [`AID_ERR_NULL_PTR`].
+fn null_ptr() -> i32 {
+    fail(AID_ERR_NULL_PTR, "a required pointer argument was null")
+}
+
 /// Convert a `*const c_char` to a `&str`, returning an error code on failure.
 ///
 /// # Safety
@@ -61,11 +112,11 @@ pub const AID_ERR_SERIALIZATION: i32 = -5;
 /// null-terminated C string that remains valid for the duration of `'a`.
 unsafe fn cstr_to_str<'a>(ptr: *const c_char) -> Result<&'a str, i32> {
     if ptr.is_null() {
-        return Err(AID_ERR_NULL_PTR);
+        return Err(null_ptr());
     }
     CStr::from_ptr(ptr)
         .to_str()
-        .map_err(|_| AID_ERR_INVALID_UTF8)
+        .map_err(|e| fail(AID_ERR_INVALID_UTF8, e))
 }
 
 /// Allocate a `CString` and write it into `*out`, returning an error code on
@@ -76,24 +127,56 @@ unsafe fn cstr_to_str<'a>(ptr: *const c_char) -> Result<&'a str, i32> {
 /// `out` must be non-null.
 unsafe fn write_string_out(s: String, out: *mut *mut c_char) -> i32 {
     if out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
     match CString::new(s) {
         Ok(cs) => {
             *out = cs.into_raw();
             AID_OK
         }
-        Err(_) => AID_ERR_SERIALIZATION,
+        Err(e) => fail(AID_ERR_SERIALIZATION, e),
+    }
+}
+
+/// Parse the JSON request at `ptr` into `T`.
+///
+/// # Safety
+///
+/// As for [`cstr_to_str`].
+unsafe fn read_json<T: DeserializeOwned>(ptr: *const c_char) -> Result<T, i32> {
+    serde_json::from_str(cstr_to_str(ptr)?).map_err(|e| fail(AID_ERR_SERIALIZATION, e))
+}
+
+/// Serialize `value` and write it into `*out`.
+///
+/// # Safety
+///
+/// As for [`write_string_out`].
+unsafe fn write_json_out<T: Serialize>(value: &T, out: *mut *mut c_char) -> i32 {
+    match serde_json::to_string(value) {
+        Ok(json) => write_string_out(json, out),
+        Err(e) => fail(AID_ERR_SERIALIZATION, e),
     }
 }
 
-/// Map an [`IdentityError`] to one of the `AID_ERR_*` constants.
+/// Parse capability URIs, rejecting control characters.
+fn parse_capabilities(uris: Vec<String>) -> Result<Vec<Capability>, i32> {
+    uris.into_iter()
+        .map(Capability::try_new)
+        .collect::<Result<_, _>>()
+        .map_err(|e| map_error(&e))
+}
+
+/// Map an [`IdentityError`] to one of the `AID_ERR_*` constants, recording
+/// its message as this thread's last error.
 fn map_error(e: &IdentityError) -> i32 {
-    match e {
-        IdentityError::Io(_) => AID_ERR_IO,
-        IdentityError::SerializationError(_) | IdentityError::InvalidFileFormat(_) => {
-            AID_ERR_SERIALIZATION
-        }
+    let code = match e {
+        IdentityError::Io(_) | IdentityError::StoreLocked(_) => AID_ERR_IO,
+        IdentityError::InvalidInput(_) | IdentityError::SchemaViolation(_) => AID_ERR_INVALID_INPUT,
+        IdentityError::SerializationError(_)
+        | IdentityError::InvalidFileFormat(_)
+        | IdentityError::UnsupportedVersion { .. }
+        | IdentityError::InvalidId(_) => AID_ERR_SERIALIZATION,
         IdentityError::InvalidKey(_)
         | IdentityError::SignatureInvalid
         | IdentityError::DerivationFailed(_)
@@ -101,7 +184,8 @@ fn map_error(e: &IdentityError) -> i32 {
         | IdentityError::DecryptionFailed(_)
         | IdentityError::InvalidPassphrase => AID_ERR_CRYPTO,
         _ => AID_ERR_CRYPTO,
-    }
+    };
+    fail(code, e)
 }
 
 // ── Version ───────────────────────────────────────────────────────────────────
@@ -121,6 +205,34 @@ pub extern "C" fn aid_version() -> *const c_char {
     VERSION.as_ptr() as *const c_char
 }
 
+// ── Error details ─────────────────────────────────────────────────────────────
+
+/// Return a description of the most recent failure on the calling thread.
+///
+/// Every `aid_*` function that returns an `AID_ERR_*` code records why it
+/// failed, e.g. `"Invalid passphrase"` behind an `AID_ERR_CRYPTO`.  The detail
+/// is kept per thread and is replaced by the next failure on that thread;
+/// successful calls leave it untouched, so read it right after the failing
+/// call.
+///
+/// # Returns
+///
+/// An owned `*mut c_char` that the caller must free with [`aid_free_string`],
+/// or `NULL` if no call on this thread has failed yet.
+///
+/// # Safety
+///
+/// Always safe to call.
+#[no_mangle]
+pub extern "C" fn aid_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_deref() {
+        Some(detail) => CString::new(detail.replace('\0', ""))
+            .map(CString::into_raw)
+            .unwrap_or(std::ptr::null_mut()),
+        None => std::ptr::null_mut(),
+    })
+}
+
 // ── Identity management ───────────────────────────────────────────────────────
 
 /// Create a new identity, save it to `path` encrypted with `passphrase`, and
@@ -136,7 +248,8 @@ pub extern "C" fn aid_version() -> *const c_char {
 ///
 /// # Returns
 ///
-/// `AID_OK` on success; one of `AID_ERR_*` on failure.
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` if `name` contains a control
+/// character; one of the other `AID_ERR_*` codes on failure.
 ///
 /// # Safety
 ///
@@ -169,10 +282,13 @@ pub unsafe extern "C" fn aid_identity_create(
     };
 
     if identity_id_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
-    let anchor = IdentityAnchor::new(opt_name);
+    let anchor = match IdentityAnchor::try_new(opt_name) {
+        Ok(a) => a,
+        Err(e) => return map_error(&e),
+    };
     let id_string = anchor.id().0.clone();
 
     match save_identity(&anchor, Path::new(path_str), passphrase_str) {
@@ -215,7 +331,7 @@ pub unsafe extern "C" fn aid_identity_load(
     };
 
     if anchor_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     match load_identity(Path::new(path_str), passphrase_str) {
@@ -242,6 +358,292 @@ pub unsafe extern "C" fn aid_identity_free(anchor: *mut std::ffi::c_void) {
     }
 }
 
+/// Rotate the signing key of the identity stored at `path`, re-encrypting the
+/// file in place, and write the new identity ID into `*new_identity_id_out`.
+///
+/// A new keypair is generated and the rotation is recorded in the identity's
+/// rotation history, signed by the old key. The identity ID changes because
+/// it is derived from the key. The file is replaced atomically, so on failure
+/// it still holds the old key.
+///
+/// # Parameters
+///
+/// - `path`                — filesystem path of the `.aid` file.
+/// - `passphrase`          — passphrase the file is encrypted with; the
+///                           rotated file is encrypted with the same one.
+/// - `reason`              — `"scheduled"`, `"compromised"`, `"device_lost"`,
+///                           `"policy_required"`, or `"manual"`; pass `NULL`
+///                           for `"manual"`.
+/// - `new_identity_id_out` — on success, receives an owned `*mut c_char` that
+///                           the caller must free with [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` for an unknown `reason`; one
+/// of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// All pointer arguments (except `reason`) must be non-null, valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn aid_identity_rotate(
+    path: *const c_char,
+    passphrase: *const c_char,
+    reason: *const c_char,
+    new_identity_id_out: *mut *mut c_char,
+) -> i32 {
+    let path_str = match cstr_to_str(path) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let passphrase_str = match cstr_to_str(passphrase) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    // `reason` is nullable — treat null as "manual".
+    let reason_str = if reason.is_null() {
+        "manual"
+    } else {
+        match cstr_to_str(reason) {
+            Ok(s) => s,
+            Err(e) => return e,
+        }
+    };
+    let rotation_reason = match reason_str {
+        "scheduled" => RotationReason::Scheduled,
+        "compromised" => RotationReason::Compromised,
+        "device_lost" => RotationReason::DeviceLost,
+        "policy_required" => RotationReason::PolicyRequired,
+        "manual" => RotationReason::Manual,
+        other => {
+            return fail(
+                AID_ERR_INVALID_INPUT,
+                format!("unknown rotation reason: {other}"),
+            )
+        }
+    };
+
+    if new_identity_id_out.is_null() {
+        return null_ptr();
+    }
+
+    let path = Path::new(path_str);
+    let rotated = match load_identity(path, passphrase_str)
+        .and_then(|anchor| anchor.rotate(rotation_reason))
+    {
+        Ok(r) => r,
+        Err(e) => return map_error(&e),
+    };
+
+    match save_identity(&rotated, path, passphrase_str) {
+        Ok(()) => write_string_out(rotated.id().0.clone(), new_identity_id_out),
+        Err(e) => map_error(&e),
+    }
+}
+
+/// Split the signing key of the identity stored at `path` into `count`
+/// Shamir shares, any `threshold` of which recover the identity with
+/// [`aid_identity_recover_from_shares`].
+///
+/// `*shares_json_out` receives a JSON object:
+///
+/// ```json
+/// {"identity_id": "aid_…", "threshold": 3, "shares": ["aidss1-…", …],
+///  "name": "agent", "created_at": 1700000000000000, "rotation_history": []}
+/// ```
+///
+/// Hand each entry of `shares` to a different custodian together with the
+/// other fields, which are public and needed to rebuild the identity exactly.
+///
+/// # Parameters
+///
+/// - `path`            — filesystem path of the `.aid` file.
+/// - `passphrase`      — passphrase the file is encrypted with.
+/// - `threshold`       — shares needed to recover; at least 2.
+/// - `count`           — shares to produce; at least `threshold`.
+/// - `shares_json_out` — on success, receives an owned `*mut c_char` that the
+///                       caller must free with [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` unless
+/// `2 <= threshold <= count`; one of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null, valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn aid_identity_export_shares(
+    path: *const c_char,
+    passphrase: *const c_char,
+    threshold: u8,
+    count: u8,
+    shares_json_out: *mut *mut c_char,
+) -> i32 {
+    let path_str = match cstr_to_str(path) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let passphrase_str = match cstr_to_str(passphrase) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    if shares_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let anchor = match load_identity(Path::new(path_str), passphrase_str) {
+        Ok(a) => a,
+        Err(e) => return map_error(&e),
+    };
+    let mut key = anchor.signing_key_bytes();
+    let split = sss::split(&key, threshold, count);
+    key.zeroize();
+    let shares = match split {
+        Ok(s) => s,
+        Err(e) => return map_error(&e),
+    };
+
+    let json = serde_json::json!({
+        "identity_id": anchor.id().0,
+        "threshold": threshold,
+        "shares": shares.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        "name": anchor.name,
+        "created_at": anchor.created_at,
+        "rotation_history": anchor.rotation_history,
+    });
+    write_string_out(json.to_string(), shares_json_out)
+}
+
+/// Recover an identity from Shamir shares and save it to `path` encrypted
+/// with `passphrase`, writing the identity ID into `*identity_id_out`.
+///
+/// `shares_json` is the object produced by [`aid_identity_export_shares`]
+/// with `shares` cut down to the ones at hand, or a bare JSON array of share
+/// strings. With the object, the recovered key must derive `identity_id` and
+/// the name, creation time and rotation history are restored; with a bare
+/// array the identity has no name, a creation time of now and no history.
+///
+/// # Parameters
+///
+/// - `shares_json`     — the shares, as above.
+/// - `passphrase`      — passphrase to encrypt the recovered identity with.
+/// - `path`            — filesystem path for the new `.aid` file; must not
+///                       exist yet.
+/// - `identity_id_out` — on success, receives an owned `*mut c_char` that the
+///                       caller must free with [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` for too few, malformed or
+/// mismatched shares or an existing `path`; `AID_ERR_CRYPTO` if the shares
+/// do not recover `identity_id`; one of the other `AID_ERR_*` codes on
+/// failure.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null, valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn aid_identity_recover_from_shares(
+    shares_json: *const c_char,
+    passphrase: *const c_char,
+    path: *const c_char,
+    identity_id_out: *mut *mut c_char,
+) -> i32 {
+    let shares_str = match cstr_to_str(shares_json) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let passphrase_str = match cstr_to_str(passphrase) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let path_str = match cstr_to_str(path) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    if identity_id_out.is_null() {
+        return null_ptr();
+    }
+
+    let value: serde_json::Value = match serde_json::from_str(shares_str) {
+        Ok(v) => v,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
+    };
+    let (share_values, metadata) = match &value {
+        serde_json::Value::Array(items) => (items, None),
+        serde_json::Value::Object(fields) => match fields.get("shares") {
+            Some(serde_json::Value::Array(items)) => (items, Some(fields)),
+            _ => return fail(AID_ERR_SERIALIZATION, "missing shares array"),
+        },
+        _ => return fail(AID_ERR_SERIALIZATION, "expected a shares array or object"),
+    };
+    let shares: Result<Vec<sss::Share>, IdentityError> = share_values
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .ok_or_else(|| IdentityError::InvalidInput("share must be a string".to_string()))?
+                .parse()
+        })
+        .collect();
+    let secret = match shares.and_then(|shares| sss::combine(&shares)) {
+        Ok(s) => s,
+        Err(e) => return map_error(&e),
+    };
+    let key: Result<[u8; 32], _> = secret.as_slice().try_into();
+    drop(secret);
+    let Ok(mut key) = key else {
+        return fail(
+            AID_ERR_INVALID_INPUT,
+            "recovered secret is not a 32-byte key",
+        );
+    };
+
+    let field = |name: &str| metadata.and_then(|fields| fields.get(name));
+    let name = field("name").and_then(|v| v.as_str()).map(str::to_owned);
+    let created_at = field("created_at")
+        .and_then(|v| v.as_u64())
+        .unwrap_or_else(agentic_identity::time::now_micros);
+    let rotation_history = match field("rotation_history") {
+        Some(v) => match serde_json::from_value(v.clone()) {
+            Ok(history) => history,
+            Err(e) => return fail(AID_ERR_SERIALIZATION, e),
+        },
+        None => Vec::new(),
+    };
+    let anchor = IdentityAnchor::from_parts(&key, created_at, name, rotation_history);
+    key.zeroize();
+    let anchor = match anchor {
+        Ok(a) => a,
+        Err(e) => return map_error(&e),
+    };
+
+    if let Some(expected) = field("identity_id") {
+        if expected.as_str() != Some(anchor.id().0.as_str()) {
+            return fail(AID_ERR_CRYPTO, "recovered key does not match identity_id");
+        }
+    }
+
+    let path = Path::new(path_str);
+    if path.exists() {
+        return fail(
+            AID_ERR_INVALID_INPUT,
+            format!("{} already exists", path.display()),
+        );
+    }
+    match save_identity(&anchor, path, passphrase_str) {
+        Ok(()) => write_string_out(anchor.id().0.clone(), identity_id_out),
+        Err(e) => map_error(&e),
+    }
+}
+
 /// Retrieve the identity ID string from an opaque anchor.
 ///
 /// # Parameters
@@ -263,10 +665,10 @@ pub unsafe extern "C" fn aid_identity_get_id(
     id_out: *mut *mut c_char,
 ) -> i32 {
     if anchor.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
     if id_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let anchor_ref = &*(anchor as *const IdentityAnchor);
@@ -294,16 +696,81 @@ pub unsafe extern "C" fn aid_identity_get_public_key(
     pubkey_out: *mut *mut c_char,
 ) -> i32 {
     if anchor.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
     if pubkey_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let anchor_ref = &*(anchor as *const IdentityAnchor);
     write_string_out(anchor_ref.public_key_base64(), pubkey_out)
 }
 
+/// Retrieve the signature algorithm of an anchor's current key.
+///
+/// The name is the one identity documents use (currently always
+/// `"ed25519"`), so it can be used to pick a verifier.
+///
+/// # Parameters
+///
+/// - `anchor`        — opaque anchor from [`aid_identity_load`].
+/// - `algorithm_out` — on success, receives an owned `*mut c_char` that the
+///                     caller must free with [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; one of `AID_ERR_*` on failure.
+///
+/// # Safety
+///
+/// `anchor` and `algorithm_out` must both be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_identity_get_algorithm(
+    anchor: *const std::ffi::c_void,
+    algorithm_out: *mut *mut c_char,
+) -> i32 {
+    if anchor.is_null() {
+        return null_ptr();
+    }
+    if algorithm_out.is_null() {
+        return null_ptr();
+    }
+
+    let anchor_ref = &*(anchor as *const IdentityAnchor);
+    write_string_out(anchor_ref.algorithm().as_str().to_string(), algorithm_out)
+}
+
+/// Retrieve the public key length in bytes of an anchor's current key.
+///
+/// # Parameters
+///
+/// - `anchor`         — opaque anchor from [`aid_identity_load`].
+/// - `key_length_out` — on success, receives the key length.
+///
+/// # Returns
+///
+/// `AID_OK` on success; one of `AID_ERR_*` on failure.
+///
+/// # Safety
+///
+/// `anchor` and `key_length_out` must both be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_identity_get_key_length(
+    anchor: *const std::ffi::c_void,
+    key_length_out: *mut u32,
+) -> i32 {
+    if anchor.is_null() {
+        return null_ptr();
+    }
+    if key_length_out.is_null() {
+        return null_ptr();
+    }
+
+    let anchor_ref = &*(anchor as *const IdentityAnchor);
+    *key_length_out = anchor_ref.algorithm().public_key_len() as u32;
+    AID_OK
+}
+
 // ── Action receipts ───────────────────────────────────────────────────────────
 
 /// Sign an action and produce a JSON receipt.
@@ -338,7 +805,7 @@ pub unsafe extern "C" fn aid_action_sign(
     receipt_json_out: *mut *mut c_char,
 ) -> i32 {
     if anchor.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let action_type_str = match cstr_to_str(action_type) {
@@ -352,7 +819,7 @@ pub unsafe extern "C" fn aid_action_sign(
     };
 
     if receipt_json_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let anchor_ref = &*(anchor as *const IdentityAnchor);
@@ -378,7 +845,7 @@ pub unsafe extern "C" fn aid_action_sign(
         };
         match serde_json::from_str::<serde_json::Value>(data_str) {
             Ok(value) => ActionContent::with_data(description_str, value),
-            Err(_) => return AID_ERR_SERIALIZATION,
+            Err(e) => return fail(AID_ERR_SERIALIZATION, e),
         }
     };
 
@@ -390,7 +857,7 @@ pub unsafe extern "C" fn aid_action_sign(
 
     let json = match serde_json::to_string(&receipt) {
         Ok(j) => j,
-        Err(_) => return AID_ERR_SERIALIZATION,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
     };
 
     write_string_out(json, receipt_json_out)
@@ -427,12 +894,12 @@ pub unsafe extern "C" fn aid_receipt_verify(
     };
 
     if is_valid_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let receipt: agentic_identity::ActionReceipt = match serde_json::from_str(json_str) {
         Ok(r) => r,
-        Err(_) => return AID_ERR_SERIALIZATION,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
     };
 
     match verify_receipt(&receipt) {
@@ -444,6 +911,82 @@ pub unsafe extern "C" fn aid_receipt_verify(
     }
 }
 
+// ── Bundle verification ───────────────────────────────────────────────────────
+
+/// Verify a JSON-encoded receipt bundle — an identity document plus its
+/// receipts, oldest first — in one call.
+///
+/// The document's self-signature is checked first; if it fails, no receipt
+/// is examined and the result says so in `document_valid` /
+/// `document_error`. Otherwise every receipt's signature, actor, and link to
+/// the preceding receipt are checked and reported individually:
+///
+/// ```json
+/// {
+///   "document_valid": true,
+///   "document_error": null,
+///   "receipts": [
+///     { "id": "arec_…", "signature_valid": true, "actor_matches": true,
+///       "chain_valid": true, "error": null, "is_valid": true }
+///   ],
+///   "is_valid": true,
+///   "verified_at": 1700000000000000
+/// }
+/// ```
+///
+/// # Parameters
+///
+/// - `bundle_json`     — JSON object with `document` and `receipts` fields.
+///                       At most [`AID_MAX_BUNDLE_BYTES`] long.
+/// - `result_json_out` — on success, receives the JSON result above as an
+///                       owned `*mut c_char`.  Must be freed with
+///                       [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` if verification ran (the verdict is in the result, valid or
+/// not); `AID_ERR_TOO_LARGE` if `bundle_json` exceeds the limit; one of the
+/// other `AID_ERR_*` codes if the input could not be read or parsed.
+///
+/// # Safety
+///
+/// `bundle_json` and `result_json_out` must both be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_bundle_verify(
+    bundle_json: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if bundle_json.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    // Check the size before parsing so a huge bundle is refused rather than
+    // materialized.
+    let bytes = CStr::from_ptr(bundle_json).to_bytes();
+    if bytes.len() > AID_MAX_BUNDLE_BYTES {
+        return fail(
+            AID_ERR_TOO_LARGE,
+            format!("bundle exceeds {AID_MAX_BUNDLE_BYTES} bytes"),
+        );
+    }
+    let json_str = match std::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => return fail(AID_ERR_INVALID_UTF8, e),
+    };
+
+    let bundle: ReceiptBundle = match serde_json::from_str(json_str) {
+        Ok(b) => b,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
+    };
+
+    let json = match serde_json::to_string(&verify_bundle(&bundle)) {
+        Ok(j) => j,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
+    };
+
+    write_string_out(json, result_json_out)
+}
+
 // ── Trust grants ──────────────────────────────────────────────────────────────
 
 /// Create and sign a trust grant from `grantor_anchor` to a grantee.
@@ -463,7 +1006,10 @@ pub unsafe extern "C" fn aid_receipt_verify(
 ///
 /// # Returns
 ///
-/// `AID_OK` on success; one of `AID_ERR_*` on failure.
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` if `grantee_id` is not a
+/// well-formed identity ID or a capability URI contains a control character
+/// (e.g. an escaped `"\u0000"`); one of the other `AID_ERR_*` codes on
+/// failure.
 ///
 /// # Safety
 ///
@@ -477,7 +1023,7 @@ pub unsafe extern "C" fn aid_trust_grant(
     grant_json_out: *mut *mut c_char,
 ) -> i32 {
     if grantor_anchor.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let grantee_id_str = match cstr_to_str(grantee_id) {
@@ -496,7 +1042,7 @@ pub unsafe extern "C" fn aid_trust_grant(
     };
 
     if grant_json_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let anchor_ref = &*(grantor_anchor as *const IdentityAnchor);
@@ -504,16 +1050,23 @@ pub unsafe extern "C" fn aid_trust_grant(
     // Parse capabilities JSON array of URI strings.
     let cap_uris: Vec<String> = match serde_json::from_str(capabilities_str) {
         Ok(v) => v,
-        Err(_) => return AID_ERR_SERIALIZATION,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
     };
 
     if cap_uris.is_empty() {
-        return AID_ERR_SERIALIZATION;
+        return fail(AID_ERR_SERIALIZATION, "capabilities must not be empty");
     }
 
-    let capabilities: Vec<Capability> = cap_uris.into_iter().map(Capability::new).collect();
+    let capabilities: Vec<Capability> =
+        match cap_uris.into_iter().map(Capability::try_new).collect() {
+            Ok(c) => c,
+            Err(e) => return map_error(&e),
+        };
 
-    let grantee_identity_id = agentic_identity::IdentityId(grantee_id_str.to_owned());
+    let grantee_identity_id = match agentic_identity::IdentityId::parse(grantee_id_str) {
+        Ok(id) => id,
+        Err(e) => return fail(AID_ERR_INVALID_INPUT, e),
+    };
 
     let grant = match TrustGrantBuilder::new(
         anchor_ref.id(),
@@ -529,7 +1082,7 @@ pub unsafe extern "C" fn aid_trust_grant(
 
     let json = match serde_json::to_string(&grant) {
         Ok(j) => j,
-        Err(_) => return AID_ERR_SERIALIZATION,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
     };
 
     write_string_out(json, grant_json_out)
@@ -576,12 +1129,12 @@ pub unsafe extern "C" fn aid_trust_verify(
     };
 
     if is_valid_out.is_null() {
-        return AID_ERR_NULL_PTR;
+        return null_ptr();
     }
 
     let grant: agentic_identity::TrustGrant = match serde_json::from_str(json_str) {
         Ok(g) => g,
-        Err(_) => return AID_ERR_SERIALIZATION,
+        Err(e) => return fail(AID_ERR_SERIALIZATION, e),
     };
 
     match verify_trust_grant(&grant, capability_str, 0, &[]) {
@@ -593,11 +1146,484 @@ pub unsafe extern "C" fn aid_trust_verify(
     }
 }
 
-// ── String cleanup ────────────────────────────────────────────────────────────
+// ── Spawn ─────────────────────────────────────────────────────────────────────
+
+/// Request object for [`aid_spawn_child`].
+#[derive(Deserialize)]
+struct SpawnChildRequest {
+    #[serde(default = "default_spawn_type")]
+    spawn_type: String,
+    purpose: String,
+    authority: Vec<String>,
+    #[serde(default)]
+    ceiling: Option<Vec<String>>,
+    #[serde(default)]
+    lifetime: Option<SpawnLifetime>,
+    #[serde(default)]
+    constraints: Option<SpawnConstraints>,
+    #[serde(default)]
+    parent_spawn_info: Option<SpawnInfo>,
+    #[serde(default)]
+    existing_children: Vec<SpawnRecord>,
+}
 
-/// Free a string that was allocated by this library.
+fn default_spawn_type() -> String {
+    "worker".to_owned()
+}
+
+/// Spawn a child identity under `parent_anchor` and save it to a new `.aid`
+/// file.
 ///
-/// All `*mut c_char` values written by functions in this crate (e.g.,
+/// `request_json` is an object:
+///
+/// ```json
+/// {
+///   "spawn_type": "worker",
+///   "purpose": "index the mailbox",
+///   "authority": ["read:email"],
+///   "ceiling": ["read:*"],
+///   "lifetime": { "Duration": { "seconds": 3600 } },
+///   "constraints": null,
+///   "parent_spawn_info": null,
+///   "existing_children": []
+/// }
+/// ```
+///
+/// `spawn_type` is `"worker"` (the default), `"delegate"`, `"clone"`,
+/// `"specialist"` or any custom string. `ceiling` defaults to `authority`,
+/// `lifetime` to `"Indefinite"` and `constraints` to the library defaults.
+/// `parent_spawn_info` is needed when the parent is itself a spawned
+/// identity, and `existing_children` holds the parent's earlier spawn records
+/// so child limits are enforced. The result is
+/// `{"child_id": "aid_…", "record": {…}, "receipt": {…}}`.
+///
+/// # Parameters
+///
+/// - `parent_anchor`    — opaque anchor from [`aid_identity_load`].
+/// - `request_json`     — the request object above.
+/// - `child_path`       — filesystem path for the child's `.aid` file; must
+///                        not exist yet.
+/// - `child_passphrase` — passphrase to encrypt the child identity with.
+/// - `result_json_out`  — on success, receives the result as an owned
+///                        `*mut c_char`.  Must be freed with
+///                        [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_INVALID_INPUT` for an existing
+/// `child_path` or a capability URI with a control character; one of the
+/// other `AID_ERR_*` codes on failure, including `AID_ERR_CRYPTO` if the
+/// spawn exceeds the parent's authority or limits.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_spawn_child(
+    parent_anchor: *const std::ffi::c_void,
+    request_json: *const c_char,
+    child_path: *const c_char,
+    child_passphrase: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if parent_anchor.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let request: SpawnChildRequest = match read_json(request_json) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let path_str = match cstr_to_str(child_path) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let passphrase_str = match cstr_to_str(child_passphrase) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
+    let parent = &*(parent_anchor as *const IdentityAnchor);
+
+    let spawn_type = match request.spawn_type.as_str() {
+        "worker" => SpawnType::Worker,
+        "delegate" => SpawnType::Delegate,
+        "clone" => SpawnType::Clone,
+        "specialist" => SpawnType::Specialist,
+        other => SpawnType::Custom(other.to_owned()),
+    };
+    let ceiling_uris = request.ceiling.unwrap_or_else(|| request.authority.clone());
+    let authority = match parse_capabilities(request.authority) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+    let ceiling = match parse_capabilities(ceiling_uris) {
+        Ok(c) => c,
+        Err(e) => return e,
+    };
+
+    let path = Path::new(path_str);
+    if path.exists() {
+        return fail(
+            AID_ERR_INVALID_INPUT,
+            format!("{} already exists", path.display()),
+        );
+    }
+
+    let (child, record, receipt) = match spawn_child(
+        parent,
+        spawn_type,
+        &request.purpose,
+        authority,
+        ceiling,
+        request.lifetime.unwrap_or(SpawnLifetime::Indefinite),
+        request.constraints.unwrap_or_default(),
+        request.parent_spawn_info.as_ref(),
+        &request.existing_children,
+    ) {
+        Ok(spawned) => spawned,
+        Err(e) => return map_error(&e),
+    };
+
+    if let Err(e) = save_identity(&child, path, passphrase_str) {
+        return map_error(&e);
+    }
+
+    write_json_out(
+        &serde_json::json!({
+            "child_id": child.id(),
+            "record": record,
+            "receipt": receipt,
+        }),
+        result_json_out,
+    )
+}
+
+/// Request object for [`aid_spawn_terminate`].
+#[derive(Deserialize)]
+struct SpawnTerminateRequest {
+    record: SpawnRecord,
+    reason: String,
+    #[serde(default)]
+    cascade: bool,
+    #[serde(default)]
+    all_records: Vec<SpawnRecord>,
+}
+
+/// Terminate a spawned child of `parent_anchor`.
+///
+/// `request_json` is
+/// `{"record": {…}, "reason": "…", "cascade": false, "all_records": []}`,
+/// where `record` is the child's spawn record and `all_records` the spawn
+/// records to search for descendants when `cascade` is true. The result is
+/// `{"record": {…}, "receipt": {…}, "terminated": ["aspawn_…"],
+/// "all_records": […]}` with the records updated; store them in place of the
+/// ones passed in.
+///
+/// # Parameters
+///
+/// - `parent_anchor`   — opaque anchor of the child's parent.
+/// - `request_json`    — the request object above.
+/// - `result_json_out` — on success, receives the result as an owned
+///                       `*mut c_char`.  Must be freed with
+///                       [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_CRYPTO` if `parent_anchor` is not the
+/// record's parent; one of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_spawn_terminate(
+    parent_anchor: *const std::ffi::c_void,
+    request_json: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if parent_anchor.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let mut request: SpawnTerminateRequest = match read_json(request_json) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let parent = &*(parent_anchor as *const IdentityAnchor);
+
+    let (receipt, terminated) = match terminate_spawn(
+        parent,
+        &mut request.record,
+        &request.reason,
+        request.cascade,
+        &mut request.all_records,
+    ) {
+        Ok(r) => r,
+        Err(e) => return map_error(&e),
+    };
+
+    // Keep the caller's copy of the direct child's record in step with
+    // `record`.
+    for other in &mut request.all_records {
+        if other.id == request.record.id {
+            *other = request.record.clone();
+        }
+    }
+
+    write_json_out(
+        &serde_json::json!({
+            "record": request.record,
+            "receipt": receipt,
+            "terminated": terminated,
+            "all_records": request.all_records,
+        }),
+        result_json_out,
+    )
+}
+
+// ── Continuity ────────────────────────────────────────────────────────────────
+
+/// Request object for [`aid_continuity_record`].
+#[derive(Deserialize)]
+struct ContinuityRecordRequest {
+    experience_type: ExperienceType,
+    content_hash: String,
+    #[serde(default = "default_intensity")]
+    intensity: f32,
+    #[serde(default)]
+    previous: Option<ExperienceEvent>,
+}
+
+fn default_intensity() -> f32 {
+    0.5
+}
+
+/// Record a signed experience event in `anchor`'s continuity chain.
+///
+/// `request_json` is an object:
+///
+/// ```json
+/// {
+///   "experience_type": { "Cognition": { "cognition_type": "Thought" } },
+///   "content_hash": "sha256:…",
+///   "intensity": 0.5,
+///   "previous": null
+/// }
+/// ```
+///
+/// `intensity` (0.0–1.0) defaults to 0.5. `previous` is the chain's latest
+/// event, or null for the first; the new event continues its sequence and
+/// cumulative hash. The result is the new event.
+///
+/// # Parameters
+///
+/// - `anchor`          — opaque anchor from [`aid_identity_load`].
+/// - `request_json`    — the request object above.
+/// - `result_json_out` — on success, receives the event as an owned
+///                       `*mut c_char`.  Must be freed with
+///                       [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_CRYPTO` for an out-of-range intensity;
+/// one of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_continuity_record(
+    anchor: *const std::ffi::c_void,
+    request_json: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if anchor.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let request: ContinuityRecordRequest = match read_json(request_json) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let anchor_ref = &*(anchor as *const IdentityAnchor);
+
+    match record_experience(
+        anchor_ref,
+        request.experience_type,
+        &request.content_hash,
+        request.intensity,
+        request.previous.as_ref(),
+    ) {
+        Ok(event) => write_json_out(&event, result_json_out),
+        Err(e) => map_error(&e),
+    }
+}
+
+// ── Competence ────────────────────────────────────────────────────────────────
+
+/// Request object for [`aid_competence_record`].
+#[derive(Deserialize)]
+struct CompetenceRecordRequest {
+    domain: String,
+    outcome: AttemptOutcome,
+    receipt_id: String,
+    #[serde(default)]
+    context: Option<String>,
+}
+
+/// Record a signed competence attempt for `anchor`.
+///
+/// `request_json` is
+/// `{"domain": "deploy", "outcome": "Success", "receipt_id": "arec_…",
+/// "context": null}`, where `outcome` may also be
+/// `{"Failure": {"reason": "…"}}` or `{"Partial": {"score": 0.5}}` and
+/// `receipt_id` names the receipt of the attempted action. The result is
+/// the attempt.
+///
+/// # Parameters
+///
+/// - `anchor`           — opaque anchor from [`aid_identity_load`].
+/// - `validator_anchor` — opaque anchor of an identity that countersigns the
+///                        attempt; pass `NULL` for none.
+/// - `request_json`     — the request object above.
+/// - `result_json_out`  — on success, receives the attempt as an owned
+///                        `*mut c_char`.  Must be freed with
+///                        [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_SERIALIZATION` for a malformed
+/// `receipt_id`; one of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// `anchor`, `request_json` and `result_json_out` must be non-null.
+/// `validator_anchor` may be null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_competence_record(
+    anchor: *const std::ffi::c_void,
+    validator_anchor: *const std::ffi::c_void, // nullable
+    request_json: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if anchor.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let request: CompetenceRecordRequest = match read_json(request_json) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let receipt_id = match ReceiptId::parse(&request.receipt_id) {
+        Ok(id) => id,
+        Err(e) => return map_error(&e),
+    };
+
+    let anchor_ref = &*(anchor as *const IdentityAnchor);
+    let validator =
+        (!validator_anchor.is_null()).then(|| &*(validator_anchor as *const IdentityAnchor));
+
+    match record_attempt(
+        anchor_ref,
+        CompetenceDomain(request.domain),
+        request.outcome,
+        receipt_id,
+        request.context,
+        validator,
+    ) {
+        Ok(attempt) => write_json_out(&attempt, result_json_out),
+        Err(e) => map_error(&e),
+    }
+}
+
+// ── Negative proofs ───────────────────────────────────────────────────────────
+
+/// Request object for [`aid_negative_prove`].
+#[derive(Deserialize)]
+struct NegativeProveRequest {
+    capability: String,
+    #[serde(default)]
+    ceiling: Vec<String>,
+    #[serde(default)]
+    spawn_records: Vec<SpawnRecord>,
+    #[serde(default)]
+    declarations: Vec<NegativeDeclaration>,
+}
+
+/// Prove that `anchor` cannot exercise a capability.
+///
+/// `request_json` is
+/// `{"capability": "write:prod", "ceiling": [], "spawn_records": [],
+/// "declarations": []}`. A structural proof is tried first: the capability
+/// lies outside `ceiling` (when non-empty) or outside the authority the
+/// identity's lineage in `spawn_records` grants it. Failing that, a matching
+/// voluntary declaration from `declarations` is used. The result is the
+/// signed proof.
+///
+/// # Parameters
+///
+/// - `anchor`          — opaque anchor from [`aid_identity_load`].
+/// - `request_json`    — the request object above.
+/// - `result_json_out` — on success, receives the proof as an owned
+///                       `*mut c_char`.  Must be freed with
+///                       [`aid_free_string`].
+///
+/// # Returns
+///
+/// `AID_OK` on success; `AID_ERR_CRYPTO` if impossibility cannot be proven;
+/// one of the other `AID_ERR_*` codes on failure.
+///
+/// # Safety
+///
+/// All pointer arguments must be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn aid_negative_prove(
+    anchor: *const std::ffi::c_void,
+    request_json: *const c_char,
+    result_json_out: *mut *mut c_char,
+) -> i32 {
+    if anchor.is_null() || result_json_out.is_null() {
+        return null_ptr();
+    }
+
+    let request: NegativeProveRequest = match read_json(request_json) {
+        Ok(r) => r,
+        Err(e) => return e,
+    };
+
+    let anchor_ref = &*(anchor as *const IdentityAnchor);
+
+    let proof = prove_cannot(
+        anchor_ref,
+        &request.capability,
+        &request.ceiling,
+        &request.spawn_records,
+    )
+    .or_else(|e| {
+        if request.declarations.is_empty() {
+            Err(e)
+        } else {
+            prove_declared(anchor_ref, &request.capability, &request.declarations)
+        }
+    });
+
+    match proof {
+        Ok(p) => write_json_out(&p, result_json_out),
+        Err(e) => map_error(&e),
+    }
+}
+
+// ── String cleanup ────────────────────────────────────────────────────────────
+
+/// Free a string that was allocated by this library.
+///
+/// All `*mut c_char` values written by functions in this crate (e.g.,
 /// `identity_id_out`, `receipt_json_out`, …) must be freed through this
 /// function.  Passing `NULL` is a no-op.
 ///
@@ -692,9 +1718,172 @@ mod tests {
         let pk = unsafe { take_string(pk_out) };
         assert!(!pk.is_empty(), "public key must not be empty");
 
+        // A loaded identity reports the algorithm its key verifies with.
+        let mut alg_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe { aid_identity_get_algorithm(anchor_out as *const _, &mut alg_out) };
+        assert_eq!(rc, AID_OK, "aid_identity_get_algorithm should succeed");
+        assert_eq!(unsafe { take_string(alg_out) }, "ed25519");
+        let mut key_len = 0u32;
+        let rc = unsafe { aid_identity_get_key_length(anchor_out as *const _, &mut key_len) };
+        assert_eq!(rc, AID_OK, "aid_identity_get_key_length should succeed");
+        assert_eq!(key_len, 32);
+
         unsafe { aid_identity_free(anchor_out) };
     }
 
+    #[test]
+    fn test_identity_rotate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rotate.aid");
+        let path_cstr = cstring(path.to_str().unwrap());
+        let pass_cstr = cstring("correct-horse-battery-staple");
+
+        let mut id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_create(
+                std::ptr::null(),
+                pass_cstr.as_ptr(),
+                path_cstr.as_ptr(),
+                &mut id_out,
+            )
+        };
+        assert_eq!(rc, AID_OK);
+        let old_id = unsafe { take_string(id_out) };
+
+        let reason_cstr = cstring("scheduled");
+        let mut new_id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_rotate(
+                path_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                reason_cstr.as_ptr(),
+                &mut new_id_out,
+            )
+        };
+        assert_eq!(rc, AID_OK, "aid_identity_rotate should succeed");
+        let new_id = unsafe { take_string(new_id_out) };
+        assert_ne!(new_id, old_id);
+
+        let rotated = load_identity(&path, "correct-horse-battery-staple").unwrap();
+        assert_eq!(rotated.id().0, new_id);
+        assert_eq!(rotated.rotation_history.len(), 1);
+
+        // Unknown reasons and wrong passphrases leave the file untouched.
+        let bad_reason = cstring("bored");
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_rotate(
+                path_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                bad_reason.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+        let wrong_pass = cstring("wrong");
+        let rc = unsafe {
+            aid_identity_rotate(
+                path_cstr.as_ptr(),
+                wrong_pass.as_ptr(),
+                std::ptr::null(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_CRYPTO);
+        assert!(out.is_null());
+        let reloaded = load_identity(&path, "correct-horse-battery-staple").unwrap();
+        assert_eq!(reloaded.id().0, new_id);
+    }
+
+    #[test]
+    fn test_identity_shares_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("original.aid");
+        let path_cstr = cstring(path.to_str().unwrap());
+        let pass_cstr = cstring("pass");
+        let name_cstr = cstring("backed-up");
+
+        let mut id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_create(
+                name_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                path_cstr.as_ptr(),
+                &mut id_out,
+            )
+        };
+        assert_eq!(rc, AID_OK);
+        let id = unsafe { take_string(id_out) };
+
+        let mut json_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_export_shares(path_cstr.as_ptr(), pass_cstr.as_ptr(), 2, 3, &mut json_out)
+        };
+        assert_eq!(rc, AID_OK, "aid_identity_export_shares should succeed");
+        let mut export: serde_json::Value =
+            serde_json::from_str(&unsafe { take_string(json_out) }).unwrap();
+        assert_eq!(export["identity_id"], id.as_str());
+        assert_eq!(export["shares"].as_array().unwrap().len(), 3);
+
+        let rc = unsafe {
+            aid_identity_export_shares(path_cstr.as_ptr(), pass_cstr.as_ptr(), 4, 3, &mut json_out)
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+
+        // Recover from two of the three shares.
+        let shares = export["shares"].as_array().unwrap().clone();
+        export["shares"] = serde_json::json!([shares[2], shares[0]]);
+        let recovered = dir.path().join("recovered.aid");
+        let recovered_cstr = cstring(recovered.to_str().unwrap());
+        let export_cstr = cstring(&export.to_string());
+        let mut recovered_id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_recover_from_shares(
+                export_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                recovered_cstr.as_ptr(),
+                &mut recovered_id_out,
+            )
+        };
+        assert_eq!(
+            rc, AID_OK,
+            "aid_identity_recover_from_shares should succeed"
+        );
+        assert_eq!(unsafe { take_string(recovered_id_out) }, id);
+        let anchor = load_identity(&recovered, "pass").unwrap();
+        assert_eq!(anchor.name.as_deref(), Some("backed-up"));
+        assert_eq!(
+            anchor.signing_key_bytes(),
+            load_identity(&path, "pass").unwrap().signing_key_bytes()
+        );
+
+        // One share is not enough, and an existing file is never replaced.
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let one = cstring(&serde_json::json!([shares[1]]).to_string());
+        let other = dir.path().join("other.aid");
+        let other_cstr = cstring(other.to_str().unwrap());
+        let rc = unsafe {
+            aid_identity_recover_from_shares(
+                one.as_ptr(),
+                pass_cstr.as_ptr(),
+                other_cstr.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+        let rc = unsafe {
+            aid_identity_recover_from_shares(
+                export_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                recovered_cstr.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+        assert!(out.is_null());
+        assert!(!other.exists());
+    }
+
     #[test]
     fn test_create_identity_null_name() {
         // NULL name should be accepted (interpreted as "no name").
@@ -716,6 +1905,28 @@ mod tests {
         unsafe { aid_free_string(id_out) };
     }
 
+    #[test]
+    fn test_create_identity_rejects_control_characters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.aid");
+        let path_cstr = cstring(path.to_str().unwrap());
+        let pass_cstr = cstring("passphrase");
+        let name_cstr = cstring("line\nbreak");
+
+        let mut id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_create(
+                name_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                path_cstr.as_ptr(),
+                &mut id_out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+        assert!(id_out.is_null());
+        assert!(!path.exists());
+    }
+
     // ── action sign ───────────────────────────────────────────────────────────
 
     #[test]
@@ -869,6 +2080,82 @@ mod tests {
         unsafe { aid_identity_free(anchor_out) };
     }
 
+    // ── bundle verify ─────────────────────────────────────────────────────────
+
+    /// Serialize a bundle of `count` chained receipts signed by `anchor`.
+    fn bundle_json(anchor: &IdentityAnchor, count: usize) -> serde_json::Value {
+        let mut receipts: Vec<agentic_identity::ActionReceipt> = Vec::new();
+        for i in 0..count {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Observation,
+                ActionContent::new(format!("step {i}")),
+            );
+            if let Some(prev) = receipts.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            receipts.push(builder.sign(anchor.signing_key()).unwrap());
+        }
+        serde_json::json!({ "document": anchor.to_document(), "receipts": receipts })
+    }
+
+    unsafe fn verify_bundle_json(bundle: &serde_json::Value) -> serde_json::Value {
+        let bundle_cstr = cstring(&bundle.to_string());
+        let mut result_out: *mut c_char = std::ptr::null_mut();
+        let rc = aid_bundle_verify(bundle_cstr.as_ptr(), &mut result_out);
+        assert_eq!(rc, AID_OK, "aid_bundle_verify should succeed");
+        serde_json::from_str(&take_string(result_out)).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_verify() {
+        let anchor = IdentityAnchor::new(None);
+        let mut bundle = bundle_json(&anchor, 3);
+
+        let result = unsafe { verify_bundle_json(&bundle) };
+        assert_eq!(result["is_valid"], true);
+        assert_eq!(result["document_valid"], true);
+        assert_eq!(result["receipts"].as_array().unwrap().len(), 3);
+
+        // Break the middle link: only that receipt is reported invalid.
+        bundle["receipts"][1]["previous_receipt"] = serde_json::Value::Null;
+        let result = unsafe { verify_bundle_json(&bundle) };
+        assert_eq!(result["is_valid"], false);
+        assert_eq!(result["receipts"][0]["is_valid"], true);
+        assert_eq!(result["receipts"][1]["is_valid"], false);
+        assert_eq!(result["receipts"][2]["chain_valid"], true);
+    }
+
+    #[test]
+    fn test_bundle_verify_bad_document() {
+        let anchor = IdentityAnchor::new(None);
+        let mut bundle = bundle_json(&anchor, 2);
+        bundle["document"]["name"] = serde_json::json!("impostor");
+
+        let result = unsafe { verify_bundle_json(&bundle) };
+        assert_eq!(result["document_valid"], false);
+        assert!(result["document_error"].is_string());
+        assert!(result["receipts"].as_array().unwrap().is_empty());
+        assert_eq!(result["is_valid"], false);
+    }
+
+    #[test]
+    fn test_bundle_verify_rejects_bad_input() {
+        let mut result_out: *mut c_char = std::ptr::null_mut();
+
+        let oversized = cstring(&" ".repeat(AID_MAX_BUNDLE_BYTES + 1));
+        let rc = unsafe { aid_bundle_verify(oversized.as_ptr(), &mut result_out) };
+        assert_eq!(rc, AID_ERR_TOO_LARGE);
+        assert!(result_out.is_null());
+
+        let garbage = cstring(r#"{"receipts":[]}"#);
+        let rc = unsafe { aid_bundle_verify(garbage.as_ptr(), &mut result_out) };
+        assert_eq!(rc, AID_ERR_SERIALIZATION);
+
+        let rc = unsafe { aid_bundle_verify(std::ptr::null(), &mut result_out) };
+        assert_eq!(rc, AID_ERR_NULL_PTR);
+    }
+
     // ── trust grant & verify ──────────────────────────────────────────────────
 
     #[test]
@@ -962,11 +2249,224 @@ mod tests {
         assert_eq!(rc, AID_OK);
         assert_eq!(is_valid2, 0, "delete:everything must NOT be covered");
 
+        // A malformed grantee ID is rejected.
+        let bad_id_cstr = cstring("not-an-identity");
+        let mut bad_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_trust_grant(
+                grantor_anchor as *const _,
+                bad_id_cstr.as_ptr(),
+                grantee_pk_cstr.as_ptr(),
+                caps_cstr.as_ptr(),
+                &mut bad_out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+        assert!(bad_out.is_null());
+
         unsafe { aid_free_string(grant_out) };
         unsafe { aid_identity_free(grantor_anchor) };
         unsafe { aid_identity_free(grantee_anchor) };
     }
 
+    // ── spawn, continuity, competence, negative ──────────────────────────────
+
+    /// Create an identity in `dir` and load it, returning the opaque anchor.
+    fn load_new_identity(dir: &Path, name: &str) -> *mut std::ffi::c_void {
+        let path = cstring(dir.join(format!("{name}.aid")).to_str().unwrap());
+        let pass = cstring("pass");
+        let mut id_out: *mut c_char = std::ptr::null_mut();
+        let mut anchor: *mut std::ffi::c_void = std::ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                aid_identity_create(std::ptr::null(), pass.as_ptr(), path.as_ptr(), &mut id_out),
+                AID_OK
+            );
+            aid_free_string(id_out);
+            assert_eq!(
+                aid_identity_load(path.as_ptr(), pass.as_ptr(), &mut anchor),
+                AID_OK
+            );
+        }
+        anchor
+    }
+
+    /// Call a `(anchor, request_json, result_json_out)` function and parse
+    /// its result.
+    fn call_json(
+        f: unsafe extern "C" fn(*const std::ffi::c_void, *const c_char, *mut *mut c_char) -> i32,
+        anchor: *mut std::ffi::c_void,
+        request: serde_json::Value,
+    ) -> Result<serde_json::Value, i32> {
+        let request = cstring(&request.to_string());
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe { f(anchor as *const _, request.as_ptr(), &mut out) };
+        if rc != AID_OK {
+            return Err(rc);
+        }
+        Ok(serde_json::from_str(&unsafe { take_string(out) }).unwrap())
+    }
+
+    #[test]
+    fn test_spawn_child_and_terminate() {
+        let dir = tempfile::tempdir().unwrap();
+        let parent = load_new_identity(dir.path(), "parent");
+        let child_path = dir.path().join("child.aid");
+        let child_path_cstr = cstring(child_path.to_str().unwrap());
+        let child_pass = cstring("child-pass");
+        let request = cstring(
+            &serde_json::json!({
+                "purpose": "index the mailbox",
+                "authority": ["read:email"],
+                "lifetime": { "Duration": { "seconds": 3600 } },
+            })
+            .to_string(),
+        );
+
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_spawn_child(
+                parent as *const _,
+                request.as_ptr(),
+                child_path_cstr.as_ptr(),
+                child_pass.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_OK);
+        let spawned: serde_json::Value =
+            serde_json::from_str(&unsafe { take_string(out) }).unwrap();
+        let child = load_identity(&child_path, "child-pass").unwrap();
+        assert_eq!(spawned["child_id"], child.id().0);
+        assert_eq!(spawned["record"]["spawn_type"], "Worker");
+
+        // The child file now exists, so spawning to it again is refused.
+        let rc = unsafe {
+            aid_spawn_child(
+                parent as *const _,
+                request.as_ptr(),
+                child_path_cstr.as_ptr(),
+                child_pass.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_ERR_INVALID_INPUT);
+
+        let terminated = call_json(
+            aid_spawn_terminate,
+            parent,
+            serde_json::json!({
+                "record": spawned["record"],
+                "reason": "done",
+                "all_records": [spawned["record"]],
+            }),
+        )
+        .unwrap();
+        assert_eq!(terminated["record"]["terminated"], true);
+        assert_eq!(terminated["all_records"][0]["terminated"], true);
+        assert_eq!(terminated["terminated"].as_array().unwrap().len(), 1);
+
+        unsafe { aid_identity_free(parent) };
+    }
+
+    #[test]
+    fn test_continuity_competence_and_negative() {
+        let dir = tempfile::tempdir().unwrap();
+        let anchor = load_new_identity(dir.path(), "agent");
+
+        let first = call_json(
+            aid_continuity_record,
+            anchor,
+            serde_json::json!({
+                "experience_type": { "Cognition": { "cognition_type": "Thought" } },
+                "content_hash": "abc",
+            }),
+        )
+        .unwrap();
+        let second = call_json(
+            aid_continuity_record,
+            anchor,
+            serde_json::json!({
+                "experience_type": { "Idle": { "reason": "waiting" } },
+                "content_hash": "def",
+                "previous": first,
+            }),
+        )
+        .unwrap();
+        assert_eq!(
+            second["sequence_number"].as_u64(),
+            first["sequence_number"].as_u64().map(|n| n + 1)
+        );
+        assert_eq!(
+            call_json(
+                aid_continuity_record,
+                anchor,
+                serde_json::json!({
+                    "experience_type": { "Idle": { "reason": "waiting" } },
+                    "content_hash": "abc",
+                    "intensity": 2.0,
+                }),
+            ),
+            Err(AID_ERR_CRYPTO)
+        );
+
+        let receipt_json = {
+            let atype = cstring("decision");
+            let desc = cstring("deployed");
+            let mut out: *mut c_char = std::ptr::null_mut();
+            unsafe {
+                aid_action_sign(
+                    anchor as *const _,
+                    atype.as_ptr(),
+                    desc.as_ptr(),
+                    std::ptr::null(),
+                    &mut out,
+                );
+                take_string(out)
+            }
+        };
+        let receipt: serde_json::Value = serde_json::from_str(&receipt_json).unwrap();
+        let request = cstring(
+            &serde_json::json!({
+                "domain": "deploy",
+                "outcome": { "Partial": { "score": 0.5 } },
+                "receipt_id": receipt["id"],
+            })
+            .to_string(),
+        );
+        let mut out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_competence_record(
+                anchor as *const _,
+                std::ptr::null(),
+                request.as_ptr(),
+                &mut out,
+            )
+        };
+        assert_eq!(rc, AID_OK);
+        let attempt: serde_json::Value =
+            serde_json::from_str(&unsafe { take_string(out) }).unwrap();
+        assert_eq!(attempt["domain"], "deploy");
+
+        let proof = call_json(
+            aid_negative_prove,
+            anchor,
+            serde_json::json!({ "capability": "write:prod", "ceiling": ["read:*"] }),
+        )
+        .unwrap();
+        assert_eq!(proof["cannot_do"], "write:prod");
+        assert_eq!(
+            call_json(
+                aid_negative_prove,
+                anchor,
+                serde_json::json!({ "capability": "read:email", "ceiling": ["read:*"] }),
+            ),
+            Err(AID_ERR_CRYPTO)
+        );
+
+        unsafe { aid_identity_free(anchor) };
+    }
+
     // ── free string ───────────────────────────────────────────────────────────
 
     #[test]
@@ -997,6 +2497,48 @@ mod tests {
         unsafe { aid_free_string(std::ptr::null_mut()) };
     }
 
+    // ── last error ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_last_error_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("err.aid");
+        let path_cstr = cstring(path.to_str().unwrap());
+        let name_cstr = cstring("err-agent");
+        let pass_cstr = cstring("right-passphrase");
+        let wrong_cstr = cstring("wrong-passphrase");
+
+        let mut id_out: *mut c_char = std::ptr::null_mut();
+        let rc = unsafe {
+            aid_identity_create(
+                name_cstr.as_ptr(),
+                pass_cstr.as_ptr(),
+                path_cstr.as_ptr(),
+                &mut id_out,
+            )
+        };
+        assert_eq!(rc, AID_OK);
+        unsafe { aid_free_string(id_out) };
+
+        let mut anchor_out: *mut std::ffi::c_void = std::ptr::null_mut();
+        let rc =
+            unsafe { aid_identity_load(path_cstr.as_ptr(), wrong_cstr.as_ptr(), &mut anchor_out) };
+        assert_eq!(rc, AID_ERR_CRYPTO);
+        let detail = unsafe { take_string(aid_last_error_message()) };
+        assert_eq!(detail, "Invalid passphrase");
+
+        // The detail is per thread: a fresh thread has none.
+        let other = std::thread::spawn(|| aid_last_error_message().is_null());
+        assert!(other.join().unwrap());
+
+        // A later failure replaces it.
+        let rc =
+            unsafe { aid_identity_load(std::ptr::null(), pass_cstr.as_ptr(), &mut anchor_out) };
+        assert_eq!(rc, AID_ERR_NULL_PTR);
+        let detail = unsafe { take_string(aid_last_error_message()) };
+        assert!(detail.contains("null"), "got: {detail}");
+    }
+
     // ── null pointer handling ─────────────────────────────────────────────────
 
     #[test]
@@ -0,0 +1,274 @@
+//! Persistent audit log of MCP sessions.
+//!
+//! Every operation record the server logs is also appended to
+//! `{dir}/{session_id}.ndjson`, one JSON object per line, so a session's
+//! history outlives the process. When the session ends (`session_end`, a
+//! new `initialize` or `session_start`, or shutdown) the server seals it: the
+//! SHA-256 of the transcript file is signed into an `audit_seal` receipt and
+//! recorded, with the record count, in `{dir}/{session_id}.seal.json`. A
+//! transcript whose digest no longer matches its seal has been altered.
+//!
+//! Session IDs are `{start_secs}-{8 hex digits}`, so listing sorts sessions
+//! by start time. A session's file is only created by its first record.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use agentic_identity::storage::atomic::write_atomic;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const SEAL_VERSION: u32 = 1;
+const TRANSCRIPT_EXTENSION: &str = "ndjson";
+const SEAL_SUFFIX: &str = ".seal.json";
+
+/// One logged operation, as stored in a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AuditEntry {
+    pub(crate) tool_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) intent: Option<String>,
+    pub(crate) summary: String,
+    pub(crate) timestamp: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) elapsed_micros: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) outcome: Option<String>,
+}
+
+/// The record written when a session is sealed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct AuditSeal {
+    pub(crate) version: u32,
+    pub(crate) session_id: String,
+    pub(crate) started_at: u64,
+    pub(crate) sealed_at: u64,
+    pub(crate) record_count: usize,
+    /// Hex SHA-256 of the transcript file at sealing time.
+    pub(crate) digest: String,
+    /// The `audit_seal` receipt signing `digest`; absent when no identity
+    /// was available to sign with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) receipt_id: Option<String>,
+}
+
+/// The session records are currently appended to.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OpenSession {
+    pub(crate) id: String,
+    pub(crate) started_at: u64,
+    pub(crate) record_count: usize,
+}
+
+/// A stored session, for listing.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionSummary {
+    pub(crate) session_id: String,
+    pub(crate) started_at: u64,
+    pub(crate) record_count: usize,
+    pub(crate) seal: Option<AuditSeal>,
+}
+
+/// Session transcripts under one directory. With no directory nothing is
+/// persisted.
+pub(crate) struct AuditLog {
+    dir: Option<PathBuf>,
+    session: Option<OpenSession>,
+}
+
+impl AuditLog {
+    pub(crate) fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir, session: None }
+    }
+
+    /// Whether records are persisted at all.
+    pub(crate) fn enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// The session records are currently appended to, if one has started.
+    pub(crate) fn current(&self) -> Option<&OpenSession> {
+        self.session.as_ref()
+    }
+
+    /// Append `entry` to the open session, starting one if needed. Failures
+    /// are logged and otherwise ignored, so auditing never fails a tool call.
+    pub(crate) fn append(&mut self, entry: &AuditEntry) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+        let session = self.session.get_or_insert_with(|| OpenSession {
+            id: new_session_id(entry.timestamp),
+            started_at: entry.timestamp,
+            record_count: 0,
+        });
+        let written = std::fs::create_dir_all(dir).and_then(|()| {
+            let mut line = serde_json::to_vec(entry).map_err(std::io::Error::other)?;
+            line.push(b'\n');
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(transcript_path(dir, &session.id))?
+                .write_all(&line)
+        });
+        match written {
+            Ok(()) => session.record_count += 1,
+            Err(e) => tracing::warn!("failed to write audit record for {}: {e}", session.id),
+        }
+    }
+
+    /// Close the open session, returning it with its transcript digest for
+    /// sealing. `None` if no record was written since the last seal.
+    pub(crate) fn close(&mut self) -> Option<(OpenSession, String)> {
+        let session = self.session.take()?;
+        let dir = self.dir.as_ref()?;
+        match digest_file(&transcript_path(dir, &session.id)) {
+            Ok(digest) => Some((session, digest)),
+            Err(e) => {
+                tracing::warn!("failed to read audit transcript {}: {e}", session.id);
+                None
+            }
+        }
+    }
+
+    /// Store the seal of a closed session.
+    pub(crate) fn write_seal(&self, seal: &AuditSeal) -> std::io::Result<()> {
+        let Some(dir) = &self.dir else {
+            return Ok(());
+        };
+        let json = serde_json::to_vec_pretty(seal).map_err(std::io::Error::other)?;
+        write_atomic(&seal_path(dir, &seal.session_id), &json).map_err(std::io::Error::other)
+    }
+
+    /// Every stored session, newest first. An unreadable transcript or
+    /// seal shows up as empty or unsealed rather than failing the listing.
+    pub(crate) fn list(&self) -> std::io::Result<Vec<SessionSummary>> {
+        let Some(dir) = &self.dir else {
+            return Ok(Vec::new());
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut ids: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                name.strip_suffix(&format!(".{TRANSCRIPT_EXTENSION}"))
+                    .map(str::to_string)
+            })
+            .collect();
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(ids
+            .into_iter()
+            .map(|session_id| SessionSummary {
+                started_at: started_at(&session_id),
+                record_count: self.entries(&session_id).map_or(0, |e| e.len()),
+                seal: self.seal(&session_id).ok().flatten(),
+                session_id,
+            })
+            .collect())
+    }
+
+    /// The records of session `session_id`, oldest first.
+    pub(crate) fn entries(&self, session_id: &str) -> std::io::Result<Vec<AuditEntry>> {
+        let path = self.checked_path(session_id, transcript_path)?;
+        let text = std::fs::read_to_string(path)?;
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(std::io::Error::other))
+            .collect()
+    }
+
+    /// The seal of session `session_id`, if it has been sealed.
+    pub(crate) fn seal(&self, session_id: &str) -> std::io::Result<Option<AuditSeal>> {
+        let path = self.checked_path(session_id, seal_path)?;
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(std::io::Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Hex SHA-256 of session `session_id`'s transcript as it is now.
+    pub(crate) fn digest(&self, session_id: &str) -> std::io::Result<String> {
+        digest_file(&self.checked_path(session_id, transcript_path)?)
+    }
+
+    /// `path_of(dir, session_id)`, refusing IDs that could name a file
+    /// outside the audit directory.
+    fn checked_path(
+        &self,
+        session_id: &str,
+        path_of: fn(&Path, &str) -> PathBuf,
+    ) -> std::io::Result<PathBuf> {
+        let Some(dir) = &self.dir else {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "audit logging is disabled",
+            ));
+        };
+        if !is_valid_session_id(session_id) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid audit session id '{session_id}'"),
+            ));
+        }
+        Ok(path_of(dir, session_id))
+    }
+}
+
+/// The seal record for a closed session.
+pub(crate) fn seal_for(
+    session: &OpenSession,
+    digest: String,
+    sealed_at: u64,
+    receipt_id: Option<String>,
+) -> AuditSeal {
+    AuditSeal {
+        version: SEAL_VERSION,
+        session_id: session.id.clone(),
+        started_at: session.started_at,
+        sealed_at,
+        record_count: session.record_count,
+        digest,
+        receipt_id,
+    }
+}
+
+fn new_session_id(started_at: u64) -> String {
+    let suffix: [u8; 4] = agentic_identity::crypto::random::random_bytes();
+    format!("{started_at}-{}", hex::encode(suffix))
+}
+
+/// The start time encoded in a session ID (0 if there is none).
+fn started_at(session_id: &str) -> u64 {
+    session_id
+        .split('-')
+        .next()
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(0)
+}
+
+fn is_valid_session_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_digit() || matches!(b, b'a'..=b'f' | b'-'))
+}
+
+fn transcript_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{session_id}.{TRANSCRIPT_EXTENSION}"))
+}
+
+fn seal_path(dir: &Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{session_id}{SEAL_SUFFIX}"))
+}
+
+fn digest_file(path: &Path) -> std::io::Result<String> {
+    Ok(hex::encode(Sha256::digest(std::fs::read(path)?)))
+}
@@ -5,10 +5,11 @@
 //!
 //! Called from the stdio loop after each request (synchronous — no background thread).
 
-use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use agentic_identity::storage::atomic::write_atomic;
+
 /// Cached client directories (detected once, reused on each sync).
 pub(crate) struct GhostBridge {
     clients: Vec<ClientDir>,
@@ -51,7 +52,7 @@ impl GhostBridge {
 
         for client in &self.clients {
             let target = client.dir.join(&client.filename);
-            if let Err(e) = atomic_write(&target, markdown.as_bytes()) {
+            if let Err(e) = write_atomic(&target, markdown.as_bytes()) {
                 eprintln!("[ghost_bridge] Failed to sync to {:?}: {e}", target);
             }
         }
@@ -66,7 +67,12 @@ fn build_identity_context(server: &super::McpServer) -> String {
     md.push_str(&format!("> Auto-synced by Ghost Writer at {now}\n\n"));
 
     // Identity store summary
-    let id_count = count_files(&server.identity_dir);
+    let identity_names: Vec<String> = server
+        .identity_documents()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    let id_count = identity_names.len();
     let receipt_count = count_files(&server.receipt_dir);
     let trust_count = count_files(&server.trust_dir);
     let spawn_count = count_files(&server.spawn_dir);
@@ -77,7 +83,6 @@ fn build_identity_context(server: &super::McpServer) -> String {
     ));
 
     // List identity names
-    let identity_names = list_file_stems(&server.identity_dir);
     if !identity_names.is_empty() {
         md.push_str("## Known Identities\n\n");
         for name in &identity_names {
@@ -90,20 +95,12 @@ fn build_identity_context(server: &super::McpServer) -> String {
     if !server.operation_log.is_empty() {
         md.push_str("## Recent Operations\n\n");
         for record in server.operation_log.iter().rev().take(15) {
-            let summary = if record.summary.len() > 150 {
-                format!("{}...", &record.summary[..150])
-            } else {
-                record.summary.clone()
-            };
+            let summary = super::truncate_text(record.summary.clone(), 150);
             let intent_tag = record
                 .intent
                 .as_deref()
                 .map(|i| {
-                    let preview = if i.len() > 80 {
-                        format!("{}...", &i[..80])
-                    } else {
-                        i.to_string()
-                    };
+                    let preview = super::truncate_text(i.to_string(), 80);
                     format!(" _{preview}_")
                 })
                 .unwrap_or_default();
@@ -126,22 +123,6 @@ fn count_files(dir: &Path) -> usize {
         .unwrap_or(0)
 }
 
-fn list_file_stems(dir: &Path) -> Vec<String> {
-    std::fs::read_dir(dir)
-        .map(|entries| {
-            entries
-                .filter_map(|e| e.ok())
-                .filter_map(|e| {
-                    e.path()
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .map(|s| s.to_string())
-                })
-                .collect()
-        })
-        .unwrap_or_default()
-}
-
 fn simple_hash(s: &str) -> u64 {
     // FNV-1a hash
     let mut hash: u64 = 0xcbf29ce484222325;
@@ -235,12 +216,3 @@ fn create_if_parent_exists(memory_dir: &Path) -> bool {
     }
     false
 }
-
-fn atomic_write(target: &Path, content: &[u8]) -> Result<(), std::io::Error> {
-    let tmp = target.with_extension("tmp");
-    let mut f = std::fs::File::create(&tmp)?;
-    f.write_all(content)?;
-    f.sync_all()?;
-    std::fs::rename(&tmp, target)?;
-    Ok(())
-}
@@ -0,0 +1,113 @@
+//! Term normalization for grounding claims against evidence.
+//!
+//! Claims, capability URIs and receipt descriptions are reduced to sets of
+//! comparable terms by [`normalize`]: lowercased, split on anything that is
+//! not a letter or digit (so `:`, `_`, `-` and whitespace all separate
+//! terms), with filler words dropped. Terms are then compared whole-word, so
+//! `read:calendar` grounds "check the calendar" but "read" does not match
+//! "already" or "spread".
+//!
+//! A [`SynonymMap`] can fold related words onto one term before comparison.
+//! It is optional; the default map is empty and changes nothing.
+//!
+//! Candidates come from the stores' [`TextIndex`]es rather than from loading
+//! every record: [`candidates`] finds documents sharing a term with a claim,
+//! and [`prefix_matches`] scores documents against a free-text query.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use agentic_identity::index::TextIndex;
+use serde::Deserialize;
+
+/// Words too common to count as evidence on their own.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "can", "for", "from", "has", "have", "in",
+    "is", "it", "of", "on", "or", "the", "to", "was", "with",
+];
+
+/// Groups of interchangeable words, each folded onto a canonical term.
+///
+/// Deserialized from a JSON object mapping each canonical term to its
+/// synonyms, e.g. `{"read": ["view", "fetch"], "deploy": ["release"]}`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "HashMap<String, Vec<String>>")]
+pub(crate) struct SynonymMap {
+    canonical: HashMap<String, String>,
+}
+
+impl From<HashMap<String, Vec<String>>> for SynonymMap {
+    fn from(groups: HashMap<String, Vec<String>>) -> Self {
+        let mut canonical = HashMap::new();
+        for (term, synonyms) in groups {
+            let term = term.to_lowercase();
+            for synonym in synonyms {
+                canonical.insert(synonym.to_lowercase(), term.clone());
+            }
+        }
+        Self { canonical }
+    }
+}
+
+impl SynonymMap {
+    /// The canonical form of `term` (the term itself if it has none).
+    fn canonicalize<'a>(&'a self, term: &'a str) -> &'a str {
+        self.canonical.get(term).map(String::as_str).unwrap_or(term)
+    }
+
+    /// Every word that folds onto the canonical `term`, including itself.
+    fn variants<'a>(&'a self, term: &'a str) -> impl Iterator<Item = &'a str> {
+        std::iter::once(term).chain(
+            self.canonical
+                .iter()
+                .filter(move |(_, canonical)| canonical.as_str() == term)
+                .map(|(word, _)| word.as_str()),
+        )
+    }
+}
+
+/// Reduce `text` to its comparable terms.
+pub(crate) fn normalize(text: &str, synonyms: &SynonymMap) -> BTreeSet<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(word))
+        .map(|word| synonyms.canonicalize(word).to_string())
+        .collect()
+}
+
+/// Number of terms the two sets share.
+pub(crate) fn overlap(a: &BTreeSet<String>, b: &BTreeSet<String>) -> usize {
+    a.intersection(b).count()
+}
+
+/// IDs of the documents in `index` holding any of the normalized `terms`,
+/// in any of their synonym spellings, sorted.
+pub(crate) fn candidates(
+    index: &TextIndex,
+    terms: &BTreeSet<String>,
+    synonyms: &SynonymMap,
+) -> Vec<String> {
+    let mut ids = BTreeSet::new();
+    for term in terms {
+        for word in synonyms.variants(term) {
+            ids.extend(index.search(word));
+        }
+    }
+    ids.into_iter().map(str::to_string).collect()
+}
+
+/// Documents in `index` matching `terms` by prefix, with the fraction of
+/// `terms` each matches, best first (ties in ID order).
+pub(crate) fn prefix_matches(index: &TextIndex, terms: &BTreeSet<String>) -> Vec<(String, f32)> {
+    let mut hits: BTreeMap<&str, usize> = BTreeMap::new();
+    for term in terms {
+        for id in index.search_prefix(term) {
+            *hits.entry(id).or_default() += 1;
+        }
+    }
+    let mut scored: Vec<(String, f32)> = hits
+        .into_iter()
+        .map(|(id, n)| (id.to_string(), n as f32 / terms.len().max(1) as f32))
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored
+}
@@ -0,0 +1,241 @@
+//! In-memory summaries of the trust grants and receipts grounding reads.
+//!
+//! `identity_ground` and `identity_evidence` look at the same stores on
+//! every call. A [`GroundingCache`] keeps each store's [`TextIndex`] and a
+//! summary of every record it has loaded, and checks a [`StoreStamp`] of the
+//! store directory before each use: while the stamp is unchanged nothing is
+//! read from disk, and when it changes the index is reopened and only
+//! records not summarized yet are loaded.
+//!
+//! Grant and receipt IDs are derived from the signed content, so a summary
+//! stays correct for as long as its ID is still indexed.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use agentic_identity::index::TextIndex;
+use agentic_identity::storage::{ReceiptStore, TrustStore};
+use agentic_identity::{IdentityId, ReceiptId, TrustId};
+
+use crate::similarity::SimilarityProvider;
+use crate::{ActorScope, StoreStamp};
+
+/// What grounding needs from a trust grant.
+#[derive(Debug, Clone)]
+pub(crate) struct GrantSummary {
+    pub(crate) id: String,
+    pub(crate) grantor: IdentityId,
+    pub(crate) grantee: IdentityId,
+    pub(crate) capabilities: Vec<String>,
+    pub(crate) granted_at: u64,
+}
+
+impl GrantSummary {
+    /// Does the grant involve the scoped identity as grantor or grantee?
+    pub(crate) fn in_scope(&self, scope: &ActorScope) -> bool {
+        scope.matches(&self.grantor) || scope.matches(&self.grantee)
+    }
+}
+
+/// What grounding needs from a receipt.
+#[derive(Debug, Clone)]
+pub(crate) struct ReceiptSummary {
+    pub(crate) id: String,
+    pub(crate) actor: IdentityId,
+    pub(crate) action_type: String,
+    pub(crate) description: String,
+    pub(crate) timestamp: u64,
+}
+
+/// A record kind the cache can summarize, with the store it lives in.
+pub(crate) trait Summary: Sized {
+    type Store;
+
+    fn open(dir: &Path) -> Option<Self::Store>;
+    fn text_index(store: &Self::Store) -> Option<TextIndex>;
+    fn load(store: &Self::Store, id: &str) -> Option<Self>;
+    /// The text a query is compared against.
+    fn text(&self) -> String;
+}
+
+impl Summary for GrantSummary {
+    type Store = TrustStore;
+
+    fn open(dir: &Path) -> Option<TrustStore> {
+        TrustStore::new(dir).ok()
+    }
+
+    fn text_index(store: &TrustStore) -> Option<TextIndex> {
+        store.text_index().ok()
+    }
+
+    fn load(store: &TrustStore, id: &str) -> Option<Self> {
+        let grant = store.load_grant(&TrustId(id.to_string())).ok()?;
+        Some(Self {
+            id: grant.id.0,
+            grantor: grant.grantor,
+            grantee: grant.grantee,
+            capabilities: grant.capabilities.into_iter().map(|c| c.uri).collect(),
+            granted_at: grant.granted_at,
+        })
+    }
+
+    fn text(&self) -> String {
+        self.capabilities.join(" ")
+    }
+}
+
+impl Summary for ReceiptSummary {
+    type Store = ReceiptStore;
+
+    fn open(dir: &Path) -> Option<ReceiptStore> {
+        ReceiptStore::new(dir).ok()
+    }
+
+    fn text_index(store: &ReceiptStore) -> Option<TextIndex> {
+        store.text_index().ok()
+    }
+
+    fn load(store: &ReceiptStore, id: &str) -> Option<Self> {
+        let receipt = store.load(&ReceiptId(id.to_string())).ok()?;
+        Some(Self {
+            id: receipt.id.0,
+            actor: receipt.actor,
+            action_type: format!("{:?}", receipt.action_type),
+            description: receipt.action.description,
+            timestamp: receipt.timestamp,
+        })
+    }
+
+    fn text(&self) -> String {
+        self.description.clone()
+    }
+}
+
+/// One store's index and the summaries loaded from it so far.
+pub(crate) struct StoreCache<T: Summary> {
+    dir: PathBuf,
+    stamp: StoreStamp,
+    store: T::Store,
+    index: TextIndex,
+    summaries: HashMap<String, T>,
+}
+
+impl<T: Summary> StoreCache<T> {
+    /// Bring `slot` up to date with the store at `dir`, keeping every
+    /// summary whose record is still indexed. `None` if the store cannot be
+    /// opened.
+    fn refresh<'a>(slot: &'a mut Option<Self>, dir: &Path, enabled: bool) -> Option<&'a mut Self> {
+        // Stamped before reading, so a write racing the refresh leaves a
+        // stamp that no longer matches and is picked up next time.
+        let stamp = StoreStamp::of(dir);
+        let current = enabled
+            && slot
+                .as_ref()
+                .is_some_and(|cached| cached.dir == dir && cached.stamp == stamp);
+        if !current {
+            let store = T::open(dir)?;
+            let index = T::text_index(&store)?;
+            let mut summaries = match slot.take() {
+                Some(cached) if enabled && cached.dir == dir => cached.summaries,
+                _ => HashMap::new(),
+            };
+            summaries.retain(|id, _| index.contains(id));
+            *slot = Some(Self {
+                dir: dir.to_path_buf(),
+                stamp,
+                store,
+                index,
+                summaries,
+            });
+        }
+        slot.as_mut()
+    }
+
+    /// IDs of the records `similarity` wants scored against `claim`.
+    pub(crate) fn candidates(
+        &self,
+        similarity: &dyn SimilarityProvider,
+        claim: &str,
+    ) -> Vec<String> {
+        similarity
+            .candidates(&self.index, claim)
+            .unwrap_or_else(|| self.index.ids().map(str::to_string).collect())
+    }
+
+    /// Records matching `query` with their scores, best first (ties in ID
+    /// order). Providers that cannot rank from the index see the text of
+    /// every record.
+    pub(crate) fn rank(
+        &mut self,
+        similarity: &dyn SimilarityProvider,
+        query: &str,
+    ) -> Vec<(String, f32)> {
+        if let Some(ranked) = similarity.rank(&self.index, query) {
+            return ranked;
+        }
+        let ids: Vec<String> = self.index.ids().map(str::to_string).collect();
+        let mut ranked: Vec<(String, f32)> = ids
+            .into_iter()
+            .filter_map(|id| {
+                let score = similarity.text_score(query, &self.get(&id)?.text());
+                (score > 0.0).then_some((id, score))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        ranked
+    }
+
+    /// The summary of record `id`, loading it on first use.
+    pub(crate) fn get(&mut self, id: &str) -> Option<&T> {
+        if !self.summaries.contains_key(id) {
+            let summary = T::load(&self.store, id)?;
+            self.summaries.insert(id.to_string(), summary);
+        }
+        self.summaries.get(id)
+    }
+}
+
+/// Grant and receipt summaries shared by the grounding tools.
+///
+/// A disabled cache still serves each call, but starts from scratch every
+/// time, reading exactly what an uncached call would.
+#[derive(Default)]
+pub(crate) struct GroundingCache {
+    enabled: bool,
+    grants: Option<StoreCache<GrantSummary>>,
+    receipts: Option<StoreCache<ReceiptSummary>>,
+}
+
+impl GroundingCache {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// The trust store at `trust_dir` and the receipt store at
+    /// `receipt_dir`, each revalidated once. `None` for a store that cannot
+    /// be opened.
+    pub(crate) fn stores(
+        &mut self,
+        trust_dir: &Path,
+        receipt_dir: &Path,
+    ) -> (
+        Option<&mut StoreCache<GrantSummary>>,
+        Option<&mut StoreCache<ReceiptSummary>>,
+    ) {
+        (
+            StoreCache::refresh(&mut self.grants, trust_dir, self.enabled),
+            StoreCache::refresh(&mut self.receipts, receipt_dir, self.enabled),
+        )
+    }
+
+    /// Number of records currently summarized, across both stores.
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.grants.as_ref().map_or(0, |c| c.summaries.len())
+            + self.receipts.as_ref().map_or(0, |c| c.summaries.len())
+    }
+}
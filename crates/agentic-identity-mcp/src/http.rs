@@ -0,0 +1,627 @@
+//! HTTP transport — MCP streamable HTTP on a single `/mcp` endpoint.
+//!
+//! Built with the `net` feature and started with `--http <addr>`. Clients
+//! POST one JSON-RPC message per request. A response comes back as
+//! `application/json`, or as a one-event `text/event-stream` (SSE) when the
+//! client accepts only that; a notification is answered with `202
+//! Accepted`. The server never sends unsolicited messages, so GET (the
+//! server-to-client stream) is refused with `405`.
+//!
+//! Sessions follow the MCP spec. A successful `initialize` without an
+//! `Mcp-Session-Id` header opens a session, whose ID is returned in that
+//! header and must accompany every later request; DELETE closes it and
+//! seals its audit session, as does server shutdown for open sessions. Each
+//! session gets its own [`McpServer`], so session state (the operation log,
+//! workspaces, the session start time) is never shared between clients,
+//! while the on-disk stores are shared as they are for the socket
+//! transport. Requests within one session run one at a time. A session
+//! idle for [`SESSION_IDLE_TIMEOUT`] is closed as if deleted, so abandoned
+//! sessions do not hold the [`MAX_SESSIONS`] slots forever, and a request
+//! that is not fully received within [`REQUEST_READ_TIMEOUT`] is answered
+//! with `408` and its connection closed.
+//!
+//! When bound to a loopback address, requests must name a loopback host in
+//! `Host`, which defeats DNS-rebinding attacks from web pages. There is no
+//! authentication, so as with the socket transport a non-loopback address
+//! is refused unless `--listen-remote` opts in; expose one only behind a
+//! proxy that provides authentication.
+//!
+//! On shutdown the listener closes first, idle connections are dropped,
+//! and requests already running finish and are answered before the server
+//! exits.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+use crate::{rpc_error, McpServer, MAX_CONTENT_LENGTH_BYTES};
+
+/// The one endpoint the transport serves.
+pub(crate) const MCP_PATH: &str = "/mcp";
+
+/// Header carrying the session ID (matched case-insensitively).
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Longest request line plus headers accepted.
+const MAX_HEADER_BYTES: u64 = 64 * 1024;
+
+/// Most sessions open at once; `initialize` beyond this gets `503`.
+const MAX_SESSIONS: usize = 1024;
+
+/// How long a session may go without a request before it is closed.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How long a connection may take to send a whole request, or to start the
+/// next one on a keep-alive connection.
+const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+type Session = Arc<Mutex<McpServer>>;
+
+/// An open session and when a request last named it.
+struct OpenSession {
+    server: Session,
+    last_used: Instant,
+}
+
+/// Server factory and open sessions, shared by every connection.
+pub(crate) struct HttpState {
+    make_server: Box<dyn Fn() -> McpServer + Send + Sync>,
+    sessions: Mutex<HashMap<String, OpenSession>>,
+    /// Only accept requests whose `Host` is a loopback name.
+    loopback_only: bool,
+    session_idle_timeout: Duration,
+    read_timeout: Duration,
+}
+
+impl HttpState {
+    pub(crate) fn new<F>(make_server: F, loopback_only: bool) -> Self
+    where
+        F: Fn() -> McpServer + Send + Sync + 'static,
+    {
+        Self {
+            make_server: Box::new(make_server),
+            sessions: Mutex::new(HashMap::new()),
+            loopback_only,
+            session_idle_timeout: SESSION_IDLE_TIMEOUT,
+            read_timeout: REQUEST_READ_TIMEOUT,
+        }
+    }
+
+    /// Replace the session idle and request read timeouts.
+    #[cfg(test)]
+    pub(crate) fn with_timeouts(mut self, session_idle: Duration, read: Duration) -> Self {
+        self.session_idle_timeout = session_idle;
+        self.read_timeout = read;
+        self
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, OpenSession>> {
+        self.sessions.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Close every session idle for longer than the idle timeout.
+    fn expire_idle_sessions(&self) {
+        let now = Instant::now();
+        let expired: Vec<Session> = {
+            let mut sessions = self.sessions();
+            let stale: Vec<String> = sessions
+                .iter()
+                .filter(|(_, s)| now.duration_since(s.last_used) > self.session_idle_timeout)
+                .map(|(id, _)| id.clone())
+                .collect();
+            stale
+                .iter()
+                .filter_map(|id| sessions.remove(id))
+                .map(|s| s.server)
+                .collect()
+        };
+        for session in expired {
+            session
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .shutdown();
+        }
+    }
+
+    async fn handle(&self, request: HttpRequest) -> HttpResponse {
+        if self.loopback_only && !request.header("host").is_some_and(is_loopback_host) {
+            return HttpResponse::error(403, "Host header must name a loopback address");
+        }
+        if request.target.split('?').next() != Some(MCP_PATH) {
+            return HttpResponse::error(404, format!("not found; the MCP endpoint is {MCP_PATH}"));
+        }
+        match request.method.as_str() {
+            "POST" => self.post(request).await,
+            "DELETE" => self.delete(&request),
+            _ => HttpResponse::error(405, "only POST and DELETE are supported")
+                .with_header("Allow", "POST, DELETE"),
+        }
+    }
+
+    async fn post(&self, request: HttpRequest) -> HttpResponse {
+        let message: Value = match serde_json::from_slice(&request.body) {
+            Ok(v) => v,
+            Err(e) => {
+                let err = rpc_error(Value::Null, -32700, format!("parse error: {e}"));
+                return HttpResponse::json(400, &err);
+            }
+        };
+
+        // An existing session, or a new one for an initialize without one.
+        let (session, new_id) = match request.header(SESSION_HEADER) {
+            Some(id) => match self.sessions().get_mut(id) {
+                Some(session) => {
+                    session.last_used = Instant::now();
+                    (session.server.clone(), None)
+                }
+                None => return HttpResponse::error(404, "unknown or closed session"),
+            },
+            None if message.get("method").and_then(|m| m.as_str()) == Some("initialize") => {
+                self.expire_idle_sessions();
+                if self.sessions().len() >= MAX_SESSIONS {
+                    return HttpResponse::error(503, "too many open sessions");
+                }
+                let session = Arc::new(Mutex::new((self.make_server)()));
+                (session, Some(new_session_id()))
+            }
+            None => {
+                return HttpResponse::error(
+                    400,
+                    "missing Mcp-Session-Id header; send initialize first",
+                )
+            }
+        };
+
+        // Awaited, never aborted: the request completes even if the client
+        // has gone by the time it does.
+        let running = session.clone();
+        let response = match tokio::task::spawn_blocking(move || {
+            running
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .handle_request(message)
+        })
+        .await
+        {
+            Ok(r) => r,
+            Err(e) => {
+                let err = rpc_error(Value::Null, -32603, format!("request failed: {e}"));
+                return HttpResponse::json(500, &err);
+            }
+        };
+
+        // Notifications return Value::Null — acknowledge without a body.
+        if response.is_null() {
+            return HttpResponse::empty(202);
+        }
+        let mut http = if accepts_only_event_stream(request.header("accept")) {
+            HttpResponse::event_stream(&response)
+        } else {
+            HttpResponse::json(200, &response)
+        };
+        if let Some(id) = new_id {
+            if response.get("error").is_none() {
+                self.sessions().insert(
+                    id.clone(),
+                    OpenSession {
+                        server: session,
+                        last_used: Instant::now(),
+                    },
+                );
+                http = http.with_header("Mcp-Session-Id", id);
+            }
+        }
+        http
+    }
+
+    fn delete(&self, request: &HttpRequest) -> HttpResponse {
+        let Some(id) = request.header(SESSION_HEADER) else {
+            return HttpResponse::error(400, "missing Mcp-Session-Id header");
+        };
+        match self.sessions().remove(id) {
+            Some(session) => {
+                session
+                    .server
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .shutdown();
+                HttpResponse::empty(204)
+            }
+            None => HttpResponse::error(404, "unknown or closed session"),
+        }
+    }
+}
+
+/// Serve MCP over HTTP on the TCP `addr` until `shutdown` completes, giving
+/// each session a fresh server from `make_server`.
+///
+/// Fails if the address cannot be bound, or if it is not loopback and
+/// `allow_remote` is false.
+pub(crate) async fn serve<F, S>(
+    addr: &str,
+    allow_remote: bool,
+    make_server: F,
+    shutdown: S,
+) -> std::io::Result<()>
+where
+    F: Fn() -> McpServer + Send + Sync + 'static,
+    S: Future<Output = ()>,
+{
+    let listener = TcpListener::bind(addr).await?;
+    let local = listener.local_addr()?;
+    if !local.ip().is_loopback() {
+        if !allow_remote {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "{local} is not a loopback address and the HTTP transport has no \
+                     authentication; pass --listen-remote to serve it anyway"
+                ),
+            ));
+        }
+        eprintln!("warning: serving every tool without authentication on non-loopback {local}");
+    }
+    eprintln!("listening on http://{local}{MCP_PATH}");
+    let state = Arc::new(HttpState::new(make_server, local.ip().is_loopback()));
+    let (stop, stopped) = watch::channel(false);
+    let mut connections = JoinSet::new();
+
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut shutdown => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    let state = state.clone();
+                    let stopped = stopped.clone();
+                    connections.spawn(async move {
+                        let (read, write) = stream.into_split();
+                        if let Err(e) = serve_connection(state, read, write, stopped).await {
+                            tracing::warn!("http connection {peer}: {e}");
+                        }
+                    });
+                }
+                Err(e) => tracing::warn!("accept failed: {e}"),
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+
+    eprintln!("shutting down; waiting for running requests");
+    drop(listener);
+    let _ = stop.send(true);
+    while connections.join_next().await.is_some() {}
+    let sessions: Vec<Session> = state.sessions().drain().map(|(_, s)| s.server).collect();
+    for session in sessions {
+        session
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .shutdown();
+    }
+    Ok(())
+}
+
+/// Resolve on Ctrl-C, or on SIGTERM on Unix.
+pub(crate) async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Serve one keep-alive connection until the client closes it, it asks to
+/// close, or `stopped` turns true between requests.
+pub(crate) async fn serve_connection<R, W>(
+    state: Arc<HttpState>,
+    read: R,
+    mut write: W,
+    mut stopped: watch::Receiver<bool>,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(read);
+    loop {
+        if *stopped.borrow() {
+            return Ok(());
+        }
+        let incoming = tokio::select! {
+            _ = stopped.changed() => return Ok(()),
+            incoming = read_request(&mut reader, state.read_timeout) => incoming?,
+        };
+        let response = match incoming {
+            Incoming::Closed => return Ok(()),
+            // The rest of a malformed request cannot be trusted to frame
+            // the next one.
+            Incoming::Invalid(mut response) => {
+                response.close = true;
+                response
+            }
+            Incoming::Request(request) => {
+                let close = request.close;
+                let mut response = state.handle(request).await;
+                response.close |= close;
+                response
+            }
+        };
+        write_response(&mut write, &response).await?;
+        if response.close {
+            return Ok(());
+        }
+    }
+}
+
+// ── Requests ──────────────────────────────────────────────────────────────────
+
+struct HttpRequest {
+    method: String,
+    target: String,
+    /// Header names lowercased, in arrival order.
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// The client asked to close the connection after this request.
+    close: bool,
+}
+
+impl HttpRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+enum Incoming {
+    Request(HttpRequest),
+    /// A malformed request, answered with this response before closing.
+    Invalid(HttpResponse),
+    /// The client closed the connection between requests.
+    Closed,
+}
+
+/// Read one request, giving up after `timeout`: a connection that sent
+/// nothing is closed quietly, one part-way through a request gets `408`.
+async fn read_request<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    timeout: Duration,
+) -> std::io::Result<Incoming> {
+    let mut head = Vec::new();
+    match tokio::time::timeout(timeout, read_request_into(reader, &mut head)).await {
+        Ok(incoming) => incoming,
+        Err(_) if head.is_empty() && reader.buffer().is_empty() => Ok(Incoming::Closed),
+        Err(_) => Ok(Incoming::Invalid(HttpResponse::error(
+            408,
+            "request not received in time",
+        ))),
+    }
+}
+
+/// Read one request, collecting its head in `head` as it arrives.
+async fn read_request_into<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+    head: &mut Vec<u8>,
+) -> std::io::Result<Incoming> {
+    let mut lines = Vec::new();
+    loop {
+        let start = head.len();
+        let remaining = MAX_HEADER_BYTES.saturating_sub(start as u64);
+        let read = (&mut *reader)
+            .take(remaining)
+            .read_until(b'\n', head)
+            .await?;
+        if read == 0 {
+            return Ok(if head.is_empty() {
+                Incoming::Closed
+            } else {
+                Incoming::Invalid(HttpResponse::error(400, "incomplete request head"))
+            });
+        }
+        let line = &head[start..];
+        if line.last() != Some(&b'\n') {
+            return Ok(Incoming::Invalid(HttpResponse::error(
+                431,
+                "request head too large",
+            )));
+        }
+        let text = String::from_utf8_lossy(line).trim_end().to_string();
+        if text.is_empty() {
+            break;
+        }
+        lines.push(text);
+    }
+
+    let invalid = |message: &str| Ok(Incoming::Invalid(HttpResponse::error(400, message)));
+    let Some((request_line, header_lines)) = lines.split_first() else {
+        return invalid("empty request");
+    };
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return invalid("malformed request line");
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Ok(Incoming::Invalid(HttpResponse::error(
+            505,
+            "only HTTP/1.x is supported",
+        )));
+    }
+
+    let mut headers = Vec::with_capacity(header_lines.len());
+    for line in header_lines {
+        let Some((name, value)) = line.split_once(':') else {
+            return invalid("malformed header line");
+        };
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    let mut request = HttpRequest {
+        method: method.to_string(),
+        target: target.to_string(),
+        headers,
+        body: Vec::new(),
+        close: false,
+    };
+    let connection = request.header("connection").map(str::to_ascii_lowercase);
+    request.close = match version {
+        "HTTP/1.0" => connection.as_deref() != Some("keep-alive"),
+        _ => connection.as_deref() == Some("close"),
+    };
+
+    if request.header("transfer-encoding").is_some() {
+        return Ok(Incoming::Invalid(HttpResponse::error(
+            501,
+            "chunked request bodies are not supported; send Content-Length",
+        )));
+    }
+    let length = match request.header("content-length").map(str::parse::<usize>) {
+        None => 0,
+        Some(Ok(n)) if n <= MAX_CONTENT_LENGTH_BYTES => n,
+        Some(Ok(_)) => {
+            return Ok(Incoming::Invalid(HttpResponse::error(
+                413,
+                format!("body exceeds max size ({MAX_CONTENT_LENGTH_BYTES} bytes)"),
+            )))
+        }
+        Some(Err(_)) => return invalid("invalid Content-Length"),
+    };
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).await?;
+    Ok(Incoming::Request(request))
+}
+
+/// `localhost`, or a loopback IP, with or without a port.
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// The client takes SSE but not plain JSON.
+fn accepts_only_event_stream(accept: Option<&str>) -> bool {
+    accept.is_some_and(|accept| {
+        accept.contains("text/event-stream")
+            && !accept.contains("application/json")
+            && !accept.contains("*/*")
+    })
+}
+
+/// 128 random bits, hex-encoded.
+fn new_session_id() -> String {
+    agentic_identity::crypto::random::random_bytes::<16>()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+// ── Responses ─────────────────────────────────────────────────────────────────
+
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    /// Close the connection after sending.
+    close: bool,
+}
+
+impl HttpResponse {
+    fn empty(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            close: false,
+        }
+    }
+
+    fn json(status: u16, message: &Value) -> Self {
+        Self {
+            body: serde_json::to_vec(message).unwrap_or_default(),
+            ..Self::empty(status)
+        }
+        .with_header("Content-Type", "application/json")
+    }
+
+    /// `message` as the single event of an SSE stream.
+    fn event_stream(message: &Value) -> Self {
+        let data = serde_json::to_string(message).unwrap_or_default();
+        Self {
+            body: format!("event: message\ndata: {data}\n\n").into_bytes(),
+            ..Self::empty(200)
+        }
+        .with_header("Content-Type", "text/event-stream")
+        .with_header("Cache-Control", "no-cache")
+    }
+
+    /// A JSON-RPC error without an ID.
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, &rpc_error(Value::Null, -32600, message.into()))
+    }
+
+    fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        204 => "No Content",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        503 => "Service Unavailable",
+        505 => "HTTP Version Not Supported",
+        _ => "",
+    }
+}
+
+async fn write_response<W: AsyncWrite + Unpin>(
+    write: &mut W,
+    response: &HttpResponse,
+) -> std::io::Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    if response.close {
+        head.push_str("Connection: close\r\n");
+    }
+    head.push_str("\r\n");
+    write.write_all(head.as_bytes()).await?;
+    write.write_all(&response.body).await?;
+    write.flush().await
+}
@@ -0,0 +1,173 @@
+//! Idempotency records for MCP write tools.
+//!
+//! A caller may pass `idempotency_key` to a write tool. The first successful
+//! call stores its result under `{dir}/{key}.json`; a retry with the same key
+//! and the same arguments gets that result back instead of writing again,
+//! while reusing the key for different arguments is a conflict. Records are
+//! kept on disk so retries dedupe across restarts, and records older than the
+//! TTL are removed whenever the store is consulted.
+
+use std::path::{Path, PathBuf};
+
+use agentic_identity::storage::atomic::write_atomic;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Tools that create or change state and therefore accept `idempotency_key`.
+pub(crate) const IDEMPOTENT_TOOLS: &[&str] = &[
+    "identity_create",
+    "action_sign",
+    "receipt_add_witness",
+    "receipt_witness",
+    "trust_grant",
+    "trust_revoke",
+    "trust_use",
+    "continuity_record",
+    "continuity_anchor",
+    "continuity_heartbeat",
+    "spawn_create",
+    "spawn_terminate",
+    "competence_record",
+    "negative_declare",
+];
+
+/// Default record lifetime: one day.
+pub(crate) const DEFAULT_TTL_SECS: u64 = 86_400;
+
+const RECORD_VERSION: u32 = 1;
+const MAX_KEY_LEN: usize = 128;
+
+/// A completed write, as stored on disk.
+#[derive(Debug, Serialize, Deserialize)]
+struct IdempotencyRecord {
+    version: u32,
+    tool_name: String,
+    /// Tool name plus canonical arguments (without the key itself).
+    fingerprint: String,
+    /// The JSON-RPC `result` returned by the original call.
+    result: Value,
+    created_at: u64,
+}
+
+/// Outcome of looking up an idempotency key.
+#[derive(Debug)]
+pub(crate) enum Replay {
+    /// Key unused (or expired): perform the operation.
+    Fresh,
+    /// Key already completed with the same arguments: return this result.
+    Completed(Value),
+    /// Key already completed by a different operation.
+    Conflict { tool_name: String },
+}
+
+/// Filesystem-backed idempotency records, one file per key.
+pub(crate) struct IdempotencyStore {
+    dir: PathBuf,
+    ttl_secs: u64,
+}
+
+impl IdempotencyStore {
+    pub(crate) fn new(dir: impl Into<PathBuf>, ttl_secs: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl_secs,
+        }
+    }
+
+    /// Look up `key`, first dropping every expired record.
+    pub(crate) fn lookup(&self, key: &str, fingerprint: &str, now: u64) -> Replay {
+        self.collect_garbage(now);
+
+        let Some(record) = std::fs::read(self.record_path(key))
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<IdempotencyRecord>(&bytes).ok())
+        else {
+            return Replay::Fresh;
+        };
+
+        if record.fingerprint == fingerprint {
+            Replay::Completed(record.result)
+        } else {
+            Replay::Conflict {
+                tool_name: record.tool_name,
+            }
+        }
+    }
+
+    /// Persist the result of a completed call under `key`.
+    ///
+    /// Written to a temporary file and renamed, so a crash never leaves a
+    /// half-written record that would later be read as "fresh".
+    pub(crate) fn record(
+        &self,
+        key: &str,
+        tool_name: &str,
+        fingerprint: &str,
+        result: &Value,
+        now: u64,
+    ) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let record = IdempotencyRecord {
+            version: RECORD_VERSION,
+            tool_name: tool_name.to_string(),
+            fingerprint: fingerprint.to_string(),
+            result: result.clone(),
+            created_at: now,
+        };
+        let json = serde_json::to_vec_pretty(&record).map_err(std::io::Error::other)?;
+        write_atomic(&self.record_path(key), &json).map_err(std::io::Error::other)
+    }
+
+    fn collect_garbage(&self, now: u64) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") && self.is_expired(&path, now) {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn is_expired(&self, path: &Path, now: u64) -> bool {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<IdempotencyRecord>(&bytes).ok())
+            .is_none_or(|record| now.saturating_sub(record.created_at) > self.ttl_secs)
+    }
+
+    fn record_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Keys double as file names, so only a conservative charset is accepted.
+pub(crate) fn validate_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(format!(
+            "idempotency_key must be 1-{MAX_KEY_LEN} characters long"
+        ));
+    }
+    if !key
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        || key.starts_with('.')
+    {
+        return Err(
+            "idempotency_key may only contain ASCII letters, digits, '-', '_' and '.'".to_string(),
+        );
+    }
+    Ok(())
+}
+
+/// Identify an operation by tool name and arguments, ignoring the key.
+///
+/// `serde_json` maps are ordered, so equal arguments serialize identically.
+pub(crate) fn fingerprint(tool_name: &str, args: &Value) -> String {
+    let mut args = args.clone();
+    if let Some(obj) = args.as_object_mut() {
+        obj.remove("idempotency_key");
+    }
+    format!("{tool_name}:{args}")
+}
@@ -820,7 +820,10 @@ pub fn execute_identity_attribute_chain(server: &McpServer, id: Value, args: &Va
     };
 
     // First check the requested receipt exists.
-    let rid = ReceiptId(receipt_id.to_string());
+    let rid = match ReceiptId::parse(receipt_id) {
+        Ok(r) => r,
+        Err(e) => return tool_error(id, e.to_string()),
+    };
     if store.load(&rid).is_err() {
         return tool_error(id, format!("Receipt not found: {receipt_id}"));
     }
@@ -7,9 +7,9 @@
 
 use serde_json::{json, Value};
 
-use super::{now_secs, tool_error, tool_ok, McpServer, DEFAULT_IDENTITY, MCP_PASSPHRASE};
+use super::{now_secs, tool_error, tool_ok, McpServer, DEFAULT_IDENTITY};
 
-use agentic_identity::storage::{load_identity, SpawnStore, TrustStore};
+use agentic_identity::storage::{SpawnStore, TrustStore};
 use agentic_identity::trust::{Revocation, RevocationReason, TrustGrant, TrustId};
 
 // ── Helper: word overlap scoring ─────────────────────────────────────────────
@@ -36,7 +36,7 @@ fn word_overlap(a: &str, b: &str) -> f64 {
 
 // ── Helper: load all grants from both granted + received ─────────────────────
 
-fn load_all_grants(trust_dir: &std::path::Path) -> Vec<TrustGrant> {
+pub(crate) fn load_all_grants(trust_dir: &std::path::Path) -> Vec<TrustGrant> {
     let mut grants = Vec::new();
     if let Ok(store) = TrustStore::new(trust_dir) {
         // Load from granted/
@@ -327,7 +327,7 @@ pub fn execute_identity_trust_recommend(server: &McpServer, id: Value, args: &Va
             }
             grantors_count += 1;
             for cap in &grant.capabilities {
-                if cap.uri == capability {
+                if cap.covers(&capability) {
                     already_has = true;
                 }
                 let overlap = word_overlap(&cap.uri, &capability);
@@ -413,12 +413,10 @@ pub fn execute_identity_trust_recommend(server: &McpServer, id: Value, args: &Va
 // INVENTION 10: Revocation Cascade
 // ═════════════════════════════════════════════════════════════════════════════
 
-/// Find all grants that depend on a given trust_id (via parent_grant chain).
-fn find_dependent_grants(
-    grants: &[TrustGrant],
-    root_id: &str,
-    trust_dir: &std::path::Path,
-) -> Vec<Value> {
+/// Every grant that depends on `root_id` through the parent_grant chain.
+///
+/// A grant is listed after the grant it was delegated from.
+pub(crate) fn dependent_grants<'a>(grants: &'a [TrustGrant], root_id: &str) -> Vec<&'a TrustGrant> {
     let mut dependents = Vec::new();
     let mut queue = vec![root_id.to_string()];
     let mut seen = std::collections::HashSet::new();
@@ -429,15 +427,7 @@ fn find_dependent_grants(
             if let Some(ref parent) = grant.parent_grant {
                 if parent.0 == current_id && !seen.contains(&grant.id.0) {
                     seen.insert(grant.id.0.clone());
-                    let already_revoked = is_revoked(trust_dir, &grant.id);
-                    dependents.push(json!({
-                        "grant_id": grant.id.0,
-                        "grantor": grant.grantor.0,
-                        "grantee": grant.grantee.0,
-                        "capabilities": grant.capabilities.iter().map(|c| &c.uri).collect::<Vec<_>>(),
-                        "delegation_depth": grant.delegation_depth,
-                        "already_revoked": already_revoked,
-                    }));
+                    dependents.push(grant);
                     queue.push(grant.id.0.clone());
                 }
             }
@@ -447,6 +437,27 @@ fn find_dependent_grants(
     dependents
 }
 
+/// Find all grants that depend on a given trust_id (via parent_grant chain).
+fn find_dependent_grants(
+    grants: &[TrustGrant],
+    root_id: &str,
+    trust_dir: &std::path::Path,
+) -> Vec<Value> {
+    dependent_grants(grants, root_id)
+        .into_iter()
+        .map(|grant| {
+            json!({
+                "grant_id": grant.id.0,
+                "grantor": grant.grantor.0,
+                "grantee": grant.grantee.0,
+                "capabilities": grant.capabilities.iter().map(|c| &c.uri).collect::<Vec<_>>(),
+                "delegation_depth": grant.delegation_depth,
+                "already_revoked": is_revoked(trust_dir, &grant.id),
+            })
+        })
+        .collect()
+}
+
 // ── Tool 4: identity_revoke_cascade_preview ──────────────────────────────────
 
 pub fn definition_identity_revoke_cascade_preview() -> Value {
@@ -475,6 +486,9 @@ pub fn execute_identity_revoke_cascade_preview(
         Some(s) if !s.trim().is_empty() => s.to_string(),
         _ => return tool_error(id, "'trust_id' is required"),
     };
+    if let Err(e) = TrustId::parse(&trust_id) {
+        return tool_error(id, e.to_string());
+    }
 
     let grants = load_all_grants(&server.trust_dir);
 
@@ -551,16 +565,16 @@ pub fn execute_identity_revoke_cascade_execute(
         Some(s) if !s.trim().is_empty() => s.to_string(),
         _ => return tool_error(id, "'trust_id' is required"),
     };
+    if let Err(e) = TrustId::parse(&trust_id) {
+        return tool_error(id, e.to_string());
+    }
     let reason = args
         .get("reason")
         .and_then(|v| v.as_str())
         .unwrap_or("cascade_revocation");
 
     // Load the default identity for signing revocations
-    let identity_path = server
-        .identity_dir
-        .join(format!("{}.aid", DEFAULT_IDENTITY));
-    let anchor = match load_identity(&identity_path, MCP_PASSPHRASE) {
+    let anchor = match server.load_anchor(DEFAULT_IDENTITY) {
         Ok(a) => a,
         Err(e) => {
             return tool_error(
@@ -664,6 +678,9 @@ pub fn execute_identity_revoke_cascade_recover(
         Some(s) if !s.trim().is_empty() => s.to_string(),
         _ => return tool_error(id, "'trust_id' is required"),
     };
+    if let Err(e) = TrustId::parse(&trust_id) {
+        return tool_error(id, e.to_string());
+    }
 
     let grants = load_all_grants(&server.trust_dir);
     let dependents = find_dependent_grants(&grants, &trust_id, &server.trust_dir);
@@ -775,7 +792,7 @@ pub fn execute_identity_capability_negotiate(server: &McpServer, id: Value, args
         // Check if agent already has it
         if grant.grantee.0 == agent_id {
             for cap in &grant.capabilities {
-                if cap.uri == capability {
+                if cap.covers(&capability) {
                     has_direct = true;
                 }
                 let overlap = word_overlap(&cap.uri, &capability);
@@ -788,7 +805,7 @@ pub fn execute_identity_capability_negotiate(server: &McpServer, id: Value, args
         // Find agents that have this capability AND can delegate
         if grant.delegation_allowed {
             for cap in &grant.capabilities {
-                if cap.uri == capability {
+                if cap.covers(&capability) {
                     // Check delegation depth still allows it
                     let max_depth = grant.max_delegation_depth.unwrap_or(u32::MAX);
                     if grant.delegation_depth < max_depth {
@@ -968,7 +985,7 @@ pub fn execute_identity_capability_terms(server: &McpServer, id: Value, args: &V
     let mut terms: Vec<Value> = Vec::new();
 
     for grant in &grants {
-        let has_cap = grant.capabilities.iter().any(|c| c.uri == capability);
+        let has_cap = grant.capabilities.iter().any(|c| c.covers(&capability));
         if !has_cap {
             // Also check partial match
             let overlap = grant
@@ -7,11 +7,9 @@
 
 use serde_json::{json, Value};
 
-use super::{micros_to_rfc3339, now_secs, tool_error, tool_ok, McpServer};
+use super::{ellipsize, micros_to_rfc3339, now_secs, tool_error, tool_ok, McpServer};
 
-use agentic_identity::storage::{
-    load_identity, read_public_document, ReceiptStore, SpawnStore, TrustStore,
-};
+use agentic_identity::storage::{ReceiptStore, SpawnStore, TrustStore};
 
 // ── Helpers ──────────────────────────────────────────────────────────────────
 
@@ -65,12 +63,10 @@ pub fn execute_identity_resurrect_start(server: &McpServer, id: Value, args: &Va
         None => return tool_error(id, "required parameter 'identity_name' is missing"),
     };
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-
     // Check if identity file still exists (partially corrupted vs fully lost)
-    let identity_exists = path.exists();
+    let identity_exists = server.identity_exists(identity_name);
     let doc_readable = if identity_exists {
-        read_public_document(&path).is_ok()
+        server.read_document(identity_name).is_ok()
     } else {
         false
     };
@@ -191,8 +187,7 @@ pub fn execute_identity_resurrect_gather(server: &McpServer, id: Value, args: &V
     };
 
     // Try to read public document for identity ID
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-    let identity_id = if let Ok(doc) = read_public_document(&path) {
+    let identity_id = if let Ok(doc) = server.read_document(identity_name) {
         Some(doc.id.0.clone())
     } else {
         None
@@ -216,11 +211,7 @@ pub fn execute_identity_resurrect_gather(server: &McpServer, id: Value, args: &V
                             "receipt_id": receipt.id.0,
                             "actor": receipt.actor.0,
                             "action_type": receipt.action_type.as_tag(),
-                            "description": if receipt.action.description.len() > 100 {
-                                format!("{}...", &receipt.action.description[..97])
-                            } else {
-                                receipt.action.description.clone()
-                            },
+                            "description": ellipsize(&receipt.action.description, 100),
                             "timestamp": micros_to_rfc3339(receipt.timestamp),
                             "has_chain": receipt.previous_receipt.is_some(),
                         }));
@@ -338,11 +329,9 @@ pub fn execute_identity_resurrect_verify(server: &McpServer, id: Value, args: &V
         None => return tool_error(id, "required parameter 'identity_name' is missing"),
     };
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-
     // Check file integrity
-    let file_check = if path.exists() {
-        match read_public_document(&path) {
+    let file_check = if server.identity_exists(identity_name) {
+        match server.read_document(identity_name) {
             Ok(doc) => {
                 let sig_ok = doc.verify_signature().is_ok();
                 json!({
@@ -351,6 +340,8 @@ pub fn execute_identity_resurrect_verify(server: &McpServer, id: Value, args: &V
                     "signature_valid": sig_ok,
                     "identity_id": doc.id.0,
                     "public_key": doc.public_key,
+                    "algorithm": doc.algorithm,
+                    "key_length": doc.signature_algorithm().ok().map(|a| a.public_key_len()),
                     "created_at": micros_to_rfc3339(doc.created_at),
                 })
             }
@@ -499,15 +490,13 @@ pub fn execute_identity_resurrect_complete(server: &McpServer, id: Value, args:
         None => return tool_error(id, "required parameter 'identity_name' is missing"),
     };
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-
     // Gather recovery summary
     let mut recovered = Vec::new();
     let mut not_recovered = Vec::new();
 
     // Identity file
-    if path.exists() {
-        if let Ok(doc) = read_public_document(&path) {
+    if server.identity_exists(identity_name) {
+        if let Ok(doc) = server.read_document(identity_name) {
             recovered.push(json!({
                 "item": "identity_file",
                 "identity_id": doc.id.0,
@@ -541,11 +530,7 @@ pub fn execute_identity_resurrect_complete(server: &McpServer, id: Value, args:
                         action_history.push(json!({
                             "receipt_id": receipt.id.0,
                             "action_type": receipt.action_type.as_tag(),
-                            "description": if receipt.action.description.len() > 80 {
-                                format!("{}...", &receipt.action.description[..77])
-                            } else {
-                                receipt.action.description.clone()
-                            },
+                            "description": ellipsize(&receipt.action.description, 80),
                             "timestamp": micros_to_rfc3339(receipt.timestamp),
                         }));
                     }
@@ -700,8 +685,7 @@ pub fn execute_identity_fork_create(server: &McpServer, id: Value, args: &Value)
     };
 
     // Check fork doesn't already exist
-    let fork_path = server.identity_dir.join(format!("{fork_name}.aid"));
-    if fork_path.exists() {
+    if server.identity_exists(fork_name) {
         return tool_error(
             id,
             format!("identity '{fork_name}' already exists — choose a different fork name"),
@@ -709,8 +693,7 @@ pub fn execute_identity_fork_create(server: &McpServer, id: Value, args: &Value)
     }
 
     // Load parent identity
-    let parent_path = server.identity_dir.join(format!("{parent_name}.aid"));
-    let parent = match load_identity(&parent_path, super::MCP_PASSPHRASE) {
+    let parent = match server.load_anchor(parent_name) {
         Ok(a) => a,
         Err(e) => {
             return tool_error(
@@ -724,16 +707,20 @@ pub fn execute_identity_fork_create(server: &McpServer, id: Value, args: &Value)
     let parent_key = parent.public_key_base64();
 
     // Parse capabilities
-    let capabilities: Vec<agentic_identity::Capability> = args
+    let capabilities: Vec<agentic_identity::Capability> = match args
         .get("capabilities")
         .and_then(|v| v.as_array())
         .map(|arr| {
             arr.iter()
                 .filter_map(|v| v.as_str())
-                .map(agentic_identity::Capability::new)
+                .map(agentic_identity::Capability::try_new)
                 .collect()
         })
-        .unwrap_or_else(|| vec![agentic_identity::Capability::new("*")]);
+        .unwrap_or_else(|| Ok(vec![agentic_identity::Capability::new("*")]))
+    {
+        Ok(c) => c,
+        Err(e) => return tool_error(id, e.to_string()),
+    };
 
     // Use spawn mechanism to create the fork
     let authority = capabilities.clone();
@@ -752,11 +739,10 @@ pub fn execute_identity_fork_create(server: &McpServer, id: Value, args: &Value)
     ) {
         Ok((child, record, receipt)) => {
             // Save the forked identity with the requested name
-            if let Err(e) =
-                agentic_identity::storage::save_identity(&child, &fork_path, super::MCP_PASSPHRASE)
-            {
+            if let Err(e) = server.store_anchor(fork_name, child) {
                 return tool_error(id, format!("failed to save forked identity: {e}"));
             }
+            let fork_path = server.identity_location(fork_name);
 
             // Save spawn receipt
             if let Ok(store) = ReceiptStore::new(&server.receipt_dir) {
@@ -820,13 +806,12 @@ pub fn execute_identity_fork_merge(server: &McpServer, id: Value, args: &Value)
         None => return tool_error(id, "required parameter 'fork_name' is missing"),
     };
 
-    let fork_path = server.identity_dir.join(format!("{fork_name}.aid"));
-    if !fork_path.exists() {
+    if !server.identity_exists(fork_name) {
         return tool_error(id, format!("forked identity '{fork_name}' not found"));
     }
 
     // Read fork's public document to get its ID
-    let fork_doc = match read_public_document(&fork_path) {
+    let fork_doc = match server.read_document(fork_name) {
         Ok(d) => d,
         Err(e) => return tool_error(id, format!("failed to read fork '{fork_name}': {e}")),
     };
@@ -861,11 +846,7 @@ pub fn execute_identity_fork_merge(server: &McpServer, id: Value, args: &Value)
                         fork_receipts.push(json!({
                             "receipt_id": receipt.id.0,
                             "action_type": receipt.action_type.as_tag(),
-                            "description": if receipt.action.description.len() > 80 {
-                                format!("{}...", &receipt.action.description[..77])
-                            } else {
-                                receipt.action.description.clone()
-                            },
+                            "description": ellipsize(&receipt.action.description, 80),
                             "timestamp": micros_to_rfc3339(receipt.timestamp),
                         }));
                     }
@@ -955,13 +936,12 @@ pub fn execute_identity_fork_abandon(server: &McpServer, id: Value, args: &Value
         .and_then(|v| v.as_str())
         .unwrap_or("abandoned");
 
-    let fork_path = server.identity_dir.join(format!("{fork_name}.aid"));
-    if !fork_path.exists() {
+    if !server.identity_exists(fork_name) {
         return tool_error(id, format!("forked identity '{fork_name}' not found"));
     }
 
     // Read fork identity to get ID
-    let fork_doc = match read_public_document(&fork_path) {
+    let fork_doc = match server.read_document(fork_name) {
         Ok(d) => d,
         Err(e) => return tool_error(id, format!("failed to read fork '{fork_name}': {e}")),
     };
@@ -1039,12 +1019,11 @@ pub fn execute_identity_fork_conflicts(server: &McpServer, id: Value, args: &Val
         None => return tool_error(id, "required parameter 'fork_name' is missing"),
     };
 
-    let fork_path = server.identity_dir.join(format!("{fork_name}.aid"));
-    if !fork_path.exists() {
+    if !server.identity_exists(fork_name) {
         return tool_error(id, format!("forked identity '{fork_name}' not found"));
     }
 
-    let fork_doc = match read_public_document(&fork_path) {
+    let fork_doc = match server.read_document(fork_name) {
         Ok(d) => d,
         Err(e) => return tool_error(id, format!("failed to read fork '{fork_name}': {e}")),
     };
@@ -1223,12 +1202,11 @@ pub fn execute_identity_zk_prove(server: &McpServer, id: Value, args: &Value) ->
         .and_then(|v| v.as_str())
         .unwrap_or("default");
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-    if !path.exists() {
+    if !server.identity_exists(identity_name) {
         return tool_error(id, format!("identity '{identity_name}' not found"));
     }
 
-    let doc = match read_public_document(&path) {
+    let doc = match server.read_document(identity_name) {
         Ok(d) => d,
         Err(e) => return tool_error(id, format!("failed to read identity: {e}")),
     };
@@ -1304,7 +1282,7 @@ pub fn execute_identity_zk_prove(server: &McpServer, id: Value, args: &Value) ->
                         if let Ok(grant) = store.load_grant(gid) {
                             if grant.grantee.0 == doc.id.0 {
                                 for cap in &grant.capabilities {
-                                    if cap.uri == value || cap.uri == "*" {
+                                    if cap.covers(value) {
                                         has_cap = true;
                                         break;
                                     }
@@ -1387,22 +1365,16 @@ pub fn execute_identity_zk_verify(server: &McpServer, id: Value, args: &Value) -
     let hash_part = &proof_id[5..];
     let hash_valid = hash_part.len() == 16 && hash_part.chars().all(|c| c.is_ascii_hexdigit());
 
-    // Scan for identities that could have generated this proof
-    let mut identity_count = 0u64;
-    if let Ok(entries) = std::fs::read_dir(&server.identity_dir) {
-        for entry in entries.flatten() {
-            if entry.path().extension().and_then(|e| e.to_str()) == Some("aid") {
-                identity_count += 1;
-            }
-        }
-    }
+    // Count identities that could have generated this proof
+    let identity_count = server.identity_documents().len();
 
     let out = json!({
         "proof_id": proof_id,
         "format_valid": hash_valid,
         "verification": {
             "hash_structure": if hash_valid { "valid" } else { "invalid" },
-            "identity_store_accessible": server.identity_dir.exists(),
+            "identity_store_accessible": server.identity_dir.exists()
+                || server.identity_vault.as_deref().is_some_and(|v| v.exists()),
             "identities_available": identity_count,
         },
         "note": "ZK proof verification checks structural validity. The commitment hash can be verified against the original claim without revealing the prover's identity."
@@ -1523,8 +1495,7 @@ pub fn execute_identity_temporal_query(server: &McpServer, id: Value, args: &Val
     let query_micros = timestamp * 1_000_000;
 
     // Load identity document
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-    let identity_info = if let Ok(doc) = read_public_document(&path) {
+    let identity_info = if let Ok(doc) = server.read_document(identity_name) {
         let existed_at_time = doc.created_at <= query_micros;
         Some(json!({
             "identity_id": doc.id.0,
@@ -1538,7 +1509,7 @@ pub fn execute_identity_temporal_query(server: &McpServer, id: Value, args: &Val
         None
     };
 
-    let identity_id = if let Ok(doc) = read_public_document(&path) {
+    let identity_id = if let Ok(doc) = server.read_document(identity_name) {
         Some(doc.id.0.clone())
     } else {
         None
@@ -1559,11 +1530,7 @@ pub fn execute_identity_temporal_query(server: &McpServer, id: Value, args: &Val
                             receipts_at_time.push(json!({
                                 "receipt_id": receipt.id.0,
                                 "action_type": receipt.action_type.as_tag(),
-                                "description": if receipt.action.description.len() > 80 {
-                                    format!("{}...", &receipt.action.description[..77])
-                                } else {
-                                    receipt.action.description.clone()
-                                },
+                                "description": ellipsize(&receipt.action.description, 80),
                                 "timestamp": micros_to_rfc3339(receipt.timestamp),
                             }));
                         }
@@ -1706,8 +1673,10 @@ pub fn execute_identity_temporal_diff(server: &McpServer, id: Value, args: &Valu
         (micros_b, micros_a, time_b, time_a)
     };
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-    let identity_id = read_public_document(&path).ok().map(|d| d.id.0.clone());
+    let identity_id = server
+        .read_document(identity_name)
+        .ok()
+        .map(|d| d.id.0.clone());
 
     // Receipts between time_a and time_b
     let mut new_receipts = Vec::new();
@@ -1724,11 +1693,7 @@ pub fn execute_identity_temporal_diff(server: &McpServer, id: Value, args: &Valu
                             new_receipts.push(json!({
                                 "receipt_id": receipt.id.0,
                                 "action_type": receipt.action_type.as_tag(),
-                                "description": if receipt.action.description.len() > 80 {
-                                    format!("{}...", &receipt.action.description[..77])
-                                } else {
-                                    receipt.action.description.clone()
-                                },
+                                "description": ellipsize(&receipt.action.description, 80),
                                 "timestamp": micros_to_rfc3339(receipt.timestamp),
                             }));
                         }
@@ -1814,7 +1779,7 @@ pub fn execute_identity_temporal_diff(server: &McpServer, id: Value, args: &Valu
 
     // Key rotations in period
     let mut key_rotations = Vec::new();
-    if let Ok(doc) = read_public_document(&path) {
+    if let Ok(doc) = server.read_document(identity_name) {
         for rot in &doc.rotation_history {
             if rot.rotated_at > micros_a && rot.rotated_at <= micros_b {
                 key_rotations.push(json!({
@@ -1868,13 +1833,11 @@ pub fn execute_identity_temporal_timeline(server: &McpServer, id: Value, args: &
         .and_then(|v| v.as_str())
         .unwrap_or("default");
 
-    let path = server.identity_dir.join(format!("{identity_name}.aid"));
-
     // Collect all events with timestamps
     let mut events: Vec<(u64, Value)> = Vec::new();
 
     // Identity creation
-    if let Ok(doc) = read_public_document(&path) {
+    if let Ok(doc) = server.read_document(identity_name) {
         events.push((
             doc.created_at,
             json!({
@@ -1917,7 +1880,7 @@ pub fn execute_identity_temporal_timeline(server: &McpServer, id: Value, args: &
                 }),
             ));
         }
-    } else if !path.exists() {
+    } else if !server.identity_exists(identity_name) {
         return tool_error(id, format!("identity '{identity_name}' not found"));
     } else {
         return tool_error(
@@ -1926,7 +1889,10 @@ pub fn execute_identity_temporal_timeline(server: &McpServer, id: Value, args: &
         );
     }
 
-    let identity_id = read_public_document(&path).ok().map(|d| d.id.0.clone());
+    let identity_id = server
+        .read_document(identity_name)
+        .ok()
+        .map(|d| d.id.0.clone());
 
     // Receipts
     if let Ok(store) = ReceiptStore::new(&server.receipt_dir) {
@@ -1946,11 +1912,7 @@ pub fn execute_identity_temporal_timeline(server: &McpServer, id: Value, args: &
                                 "timestamp": micros_to_rfc3339(receipt.timestamp),
                                 "details": {
                                     "action_type": receipt.action_type.as_tag(),
-                                    "description": if receipt.action.description.len() > 80 {
-                                        format!("{}...", &receipt.action.description[..77])
-                                    } else {
-                                        receipt.action.description.clone()
-                                    },
+                                    "description": ellipsize(&receipt.action.description, 80),
                                 }
                             }),
                         ));
@@ -2069,23 +2031,13 @@ pub fn execute_identity_temporal_timeline(server: &McpServer, id: Value, args: &
 // Helpers
 // ═════════════════════════════════════════════════════════════════════════════
 
-/// Scan the identity directory to find an identity name matching a given ID.
+/// Scan the local identities to find an identity name matching a given ID.
 fn find_identity_name_by_id(server: &McpServer, target_id: &str) -> Option<String> {
-    let entries = std::fs::read_dir(&server.identity_dir).ok()?;
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) == Some("aid") {
-            if let Ok(doc) = read_public_document(&path) {
-                if doc.id.0 == target_id {
-                    return path
-                        .file_stem()
-                        .and_then(|s| s.to_str())
-                        .map(|s| s.to_string());
-                }
-            }
-        }
-    }
-    None
+    server
+        .identity_documents()
+        .into_iter()
+        .find(|(_, doc)| doc.id.0 == target_id)
+        .map(|(name, _)| name)
 }
 
 // ═════════════════════════════════════════════════════════════════════════════
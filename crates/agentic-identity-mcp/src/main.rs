@@ -9,42 +9,91 @@
 //! Reads newline-delimited JSON-RPC 2.0 requests from stdin and writes
 //! responses to stdout. Each request and response is a single line.
 //!
-//! # Default passphrase
+//! # Passphrase
 //!
-//! All operations that require a passphrase use the fixed passphrase `"agentic"`.
-//! This is intentional: agents cannot interactively enter passphrases, and the
-//! MCP server is designed for use in automated contexts where the identity file
-//! is already protected by the host environment.
-
+//! Agents cannot enter passphrases interactively, so the server reads one at
+//! startup from `AID_MCP_PASSPHRASE`, or from the file named by
+//! `AID_MCP_PASSPHRASE_FILE` (`--passphrase-env` and `--passphrase-file`
+//! override both). Identities can have passphrases of their own in a
+//! passphrase directory or the OS keyring; see [`passphrase`]. Without a
+//! server passphrase it falls back to the legacy
+//! literal `"agentic"` and warns on stderr, since anyone who has read this
+//! source can decrypt identities saved under it. Building with the
+//! `require_passphrase` feature removes the fallback: the server refuses to
+//! start without an explicit passphrase. Identities created under the legacy
+//! literal can be moved to the configured passphrase with
+//! `identity_change_passphrase`.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::io::{self, BufRead, Read, Write};
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use serde_json::{json, Value};
+use zeroize::Zeroizing;
 
+mod audit;
 mod ghost_bridge;
+mod grounding;
+mod grounding_cache;
+#[cfg(feature = "net")]
+mod http;
+mod idempotency;
 mod invention_accountability;
 mod invention_federation;
 mod invention_resilience;
 mod invention_trust_dynamics;
-
+#[cfg(feature = "net")]
+mod net;
+mod outcome;
+mod passphrase;
+mod similarity;
+
+use agentic_identity::crypto::signing::{SignatureDomain, SIGNATURE_VERSION};
+use agentic_identity::identity::{verify_genesis, IdentityDocument, RotationReason};
+use agentic_identity::index::ReceiptIndex;
+use agentic_identity::query::{holders_page, HoldersQuery, ReceiptFilter};
 use agentic_identity::receipt::receipt::ReceiptBuilder;
 use agentic_identity::receipt::verify::verify_receipt;
+use agentic_identity::receipt::{
+    witness_signing_input, ReceiptGraph, RequirementPolicy, SessionLog, SessionLogEntry,
+    WitnessSignature,
+};
+use agentic_identity::spawn::{BudgetResource, SpawnTree};
 use agentic_identity::storage::{
-    load_identity, read_public_document, save_identity, ReceiptStore, SpawnStore, TrustStore,
+    change_passphrase, load_identity, read_public_document, read_vault_documents,
+    recover_identity_files, rekey_identities, save_identity, stage_identity, CompetenceStore,
+    ContinuityStore, IdentityVault, KeyDirectory, NegativeStore, QuarantineReport,
+    ReceiptExportFilter, ReceiptStore, SpawnQuery, SpawnStore, Transaction, TrustStore,
 };
 use agentic_identity::trust::grant::TrustGrantBuilder;
 use agentic_identity::trust::revocation::{Revocation, RevocationReason};
-use agentic_identity::trust::verify::verify_trust_grant;
+use agentic_identity::trust::revocation_list::RevocationList;
+use agentic_identity::trust::verify::{
+    verify_grant_justification, verify_trust_grant, verify_trust_grant_with, VerifyOptions,
+};
+use agentic_identity::trust::{authority_diff, TrustGraph, UsageContext};
 use agentic_identity::{
-    ActionContent, ActionType, Capability, IdentityAnchor, IdentityId, ReceiptId, TrustConstraints,
-    TrustId,
+    ActionContent, ActionReceipt, ActionType, Capability, IdentityAnchor, IdentityId, ReceiptId,
+    SpawnRecord, TrustConstraints, TrustId,
+};
+
+use audit::{AuditEntry, AuditLog, AuditSeal};
+use grounding::SynonymMap;
+use grounding_cache::GroundingCache;
+use outcome::{OutcomeMessages, VerificationOutcome};
+use passphrase::{PassphraseArgs, PassphraseSources};
+use similarity::{
+    CommandVectorizer, EmbeddingSimilarity, LexicalSimilarity, SimilarityProvider,
+    DEFAULT_MIN_SIMILARITY,
 };
 
 // ── Constants ─────────────────────────────────────────────────────────────────
 
-/// Default passphrase for MCP mode. Agents cannot enter passphrases interactively.
-const MCP_PASSPHRASE: &str = "agentic";
+/// Passphrase MCP servers used before it was configurable. Still the
+/// fallback unless built with `require_passphrase`, and always accepted as
+/// the old passphrase by `identity_change_passphrase`.
+const LEGACY_MCP_PASSPHRASE: &str = "agentic";
 
 /// Default identity name when none is specified.
 const DEFAULT_IDENTITY: &str = "default";
@@ -61,11 +110,49 @@ const PROTOCOL_VERSION: &str = "2024-11-05";
 struct Cli {
     #[command(subcommand)]
     command: Option<Command>,
+
+    /// Record per-tool timings and emit a JSON trace line per call to stderr.
+    #[arg(long, global = true)]
+    trace: bool,
+
+    /// Install the identity whose key material is in this environment
+    /// variable as the default identity (see IdentityAnchor::from_key_material).
+    /// The variable is removed from the environment once read.
+    #[arg(long, global = true, value_name = "VAR")]
+    identity_from_env: Option<String>,
+
+    /// Serve JSON-RPC on a TCP host:port, or unix:<path>, instead of stdio.
+    /// There is no authentication: any client that connects can call every
+    /// tool, so a TCP address must be loopback unless --listen-remote is set.
+    #[cfg(feature = "net")]
+    #[arg(long, global = true, value_name = "ADDR", group = "network")]
+    listen: Option<String>,
+
+    /// Allow --listen or --http on a non-loopback address. Only do this
+    /// behind a proxy or network that authenticates clients.
+    #[cfg(feature = "net")]
+    #[arg(long, global = true, requires = "network")]
+    listen_remote: bool,
+
+    /// Serve MCP streamable HTTP on a TCP host:port instead of stdio. Like
+    /// --listen, the address must be loopback unless --listen-remote is set.
+    #[cfg(feature = "net")]
+    #[arg(
+        long,
+        global = true,
+        value_name = "ADDR",
+        conflicts_with = "listen",
+        group = "network"
+    )]
+    http: Option<String>,
+
+    #[command(flatten)]
+    passphrase: PassphraseArgs,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    /// Run MCP server over stdio (default).
+    /// Run MCP server over stdio (default), the --listen socket, or --http.
     Serve,
 }
 
@@ -92,6 +179,194 @@ fn spawn_dir() -> PathBuf {
     agentic_dir().join("spawn")
 }
 
+fn continuity_dir() -> PathBuf {
+    agentic_dir().join("continuity")
+}
+
+fn competence_dir() -> PathBuf {
+    agentic_dir().join("competence")
+}
+
+fn negative_dir() -> PathBuf {
+    agentic_dir().join("negative")
+}
+
+fn key_dir() -> PathBuf {
+    agentic_dir().join("keys")
+}
+
+fn idempotency_dir() -> PathBuf {
+    agentic_dir().join("idempotency")
+}
+
+fn transaction_dir() -> PathBuf {
+    agentic_dir().join("transactions")
+}
+
+fn audit_dir() -> PathBuf {
+    agentic_dir().join("audit")
+}
+
+/// The identity vault named by `AID_IDENTITY_VAULT`, which replaces the
+/// per-identity `.aid` files when set.
+fn identity_vault() -> Option<PathBuf> {
+    read_env_string_any(&["AID_IDENTITY_VAULT", "IDENTITY_VAULT"])
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Serializes read-modify-write updates of the identity vault between the
+/// servers of one process (socket and HTTP sessions).
+static VAULT_WRITE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// Requirement rules for `action_check`, read from the JSON file named by
+/// `AID_ACTION_REQUIREMENTS`. Empty (nothing required) when unset or invalid.
+fn load_action_requirements() -> RequirementPolicy {
+    let Some(path) = read_env_string_any(&["AID_ACTION_REQUIREMENTS", "ACTION_REQUIREMENTS"])
+    else {
+        return RequirementPolicy::default();
+    };
+    let loaded = std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(policy) => policy,
+        Err(e) => {
+            tracing::warn!("ignoring action requirements file '{path}': {e}");
+            RequirementPolicy::default()
+        }
+    }
+}
+
+fn load_grounding_synonyms() -> SynonymMap {
+    let Some(path) = read_env_string_any(&["AID_GROUNDING_SYNONYMS", "GROUNDING_SYNONYMS"]) else {
+        return SynonymMap::default();
+    };
+    let loaded = std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(synonyms) => synonyms,
+        Err(e) => {
+            tracing::warn!("ignoring grounding synonyms file '{path}': {e}");
+            SynonymMap::default()
+        }
+    }
+}
+
+/// The grounding similarity provider: embeddings from the program named by
+/// `AID_EMBEDDING_COMMAND` when set, lexical matching with the
+/// `AID_GROUNDING_SYNONYMS` synonyms otherwise (and as the fallback).
+fn load_similarity_provider() -> Box<dyn SimilarityProvider + Send> {
+    let lexical = LexicalSimilarity::new(load_grounding_synonyms());
+    let Some(vectorizer) = read_env_string_any(&["AID_EMBEDDING_COMMAND", "EMBEDDING_COMMAND"])
+        .and_then(|command| CommandVectorizer::parse(&command))
+    else {
+        return Box::new(lexical);
+    };
+    let min_similarity =
+        read_env_string_any(&["AID_EMBEDDING_MIN_SIMILARITY", "EMBEDDING_MIN_SIMILARITY"])
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_MIN_SIMILARITY);
+    Box::new(EmbeddingSimilarity::new(
+        Box::new(vectorizer),
+        min_similarity,
+        lexical,
+    ))
+}
+
+/// Wording for verification outcomes, read from the JSON file named by
+/// `AID_VERIFICATION_MESSAGES`. English defaults when unset or invalid.
+fn load_verification_messages() -> OutcomeMessages {
+    let Some(path) = read_env_string_any(&["AID_VERIFICATION_MESSAGES", "VERIFICATION_MESSAGES"])
+    else {
+        return OutcomeMessages::default();
+    };
+    let loaded = std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()));
+    match loaded {
+        Ok(messages) => messages,
+        Err(e) => {
+            tracing::warn!("ignoring verification messages file '{path}': {e}");
+            OutcomeMessages::default()
+        }
+    }
+}
+
+/// The server passphrase configured by `--passphrase-env`,
+/// `--passphrase-file`, `AID_MCP_PASSPHRASE` or `AID_MCP_PASSPHRASE_FILE`,
+/// in that order, if any.
+///
+/// A variable is used verbatim (unlike other settings it is not trimmed);
+/// a file has only its trailing line break removed. An empty passphrase, or
+/// a file that cannot be read or is open to other users, is an error rather
+/// than a silent fallback.
+fn configured_passphrase(args: &PassphraseArgs) -> Result<Option<String>, String> {
+    if let Some(var) = &args.passphrase_env {
+        return match std::env::var(var) {
+            Ok(passphrase) if passphrase.is_empty() => Err(format!("{var} is set but empty")),
+            Ok(passphrase) => Ok(Some(passphrase)),
+            Err(_) => Err(format!("--passphrase-env names {var}, which is not set")),
+        };
+    }
+    if let Some(path) = &args.passphrase_file {
+        return passphrase::read_passphrase_file(path).map(Some);
+    }
+
+    if let Some(passphrase) = ["AID_MCP_PASSPHRASE", "MCP_PASSPHRASE"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+    {
+        if passphrase.is_empty() {
+            return Err("AID_MCP_PASSPHRASE is set but empty".to_string());
+        }
+        return Ok(Some(passphrase));
+    }
+
+    let Some(path) = read_env_string_any(&["AID_MCP_PASSPHRASE_FILE", "MCP_PASSPHRASE_FILE"])
+    else {
+        return Ok(None);
+    };
+    passphrase::read_passphrase_file(path.as_ref()).map(Some)
+}
+
+/// Resolve the passphrase the server runs with. Every message goes to
+/// stderr: stdout carries the JSON-RPC stream.
+fn startup_passphrase(args: &PassphraseArgs) -> Result<String, String> {
+    if let Some(passphrase) = configured_passphrase(args)? {
+        return Ok(passphrase);
+    }
+
+    #[cfg(feature = "require_passphrase")]
+    {
+        Err(
+            "no passphrase configured; set AID_MCP_PASSPHRASE or AID_MCP_PASSPHRASE_FILE, \
+             or pass --passphrase-env or --passphrase-file (this build has no default \
+             passphrase)"
+                .to_string(),
+        )
+    }
+
+    #[cfg(not(feature = "require_passphrase"))]
+    {
+        eprintln!(
+            "warning: no passphrase configured; identities are encrypted with the public \
+             default passphrase. Set AID_MCP_PASSPHRASE or AID_MCP_PASSPHRASE_FILE, then \
+             migrate existing identities with identity_change_passphrase."
+        );
+        Ok(LEGACY_MCP_PASSPHRASE.to_string())
+    }
+}
+
+/// `"2 of 5"` for a grant with `max_uses`, else `"2 (unlimited)"`.
+fn uses_summary(uses: u64, max_uses: Option<u64>) -> String {
+    match max_uses {
+        Some(max) => format!("{uses} of {max}"),
+        None => format!("{uses} (unlimited)"),
+    }
+}
+
 // ── Time formatting ───────────────────────────────────────────────────────────
 
 fn micros_to_rfc3339(micros: u64) -> String {
@@ -207,6 +482,18 @@ fn tool_ok(id: Value, text: impl Into<String>) -> Value {
     )
 }
 
+/// A successful tool result that also carries the machine codes of its
+/// verification outcomes, keyed by check.
+fn tool_ok_with_outcomes(id: Value, text: impl Into<String>, outcomes: Value) -> Value {
+    ok_result(
+        id,
+        json!({
+            "content": [{"type": "text", "text": text.into()}],
+            "structuredContent": {"outcomes": outcomes}
+        }),
+    )
+}
+
 fn tool_error(id: Value, text: impl Into<String>) -> Value {
     ok_result(
         id,
@@ -228,6 +515,36 @@ fn rpc_error(id: Value, code: i64, message: impl Into<String>) -> Value {
     })
 }
 
+/// Classify a tools/call response for tracing.
+fn tool_outcome(response: &Value) -> &'static str {
+    if response.get("error").is_some() {
+        "rpc_error"
+    } else if response
+        .pointer("/result/isError")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+    {
+        "error"
+    } else {
+        "ok"
+    }
+}
+
+/// Write one structured trace line to stderr. Stdout carries JSON-RPC framing
+/// and must never receive trace output.
+fn emit_trace_line(tool_name: &str, outcome: &str, elapsed_micros: u64) {
+    eprintln!(
+        "{}",
+        json!({
+            "trace": "tool_call",
+            "tool": tool_name,
+            "outcome": outcome,
+            "elapsed_micros": elapsed_micros,
+            "timestamp": now_secs(),
+        })
+    );
+}
+
 // ── MCP Server ────────────────────────────────────────────────────────────────
 
 /// Record of an identity operation with context.
@@ -238,15 +555,67 @@ struct IdentityOperationRecord {
     intent: Option<String>,
     summary: String,
     timestamp: u64,
+    /// Wall-clock duration of the tool call (only recorded with `--trace`).
+    elapsed_micros: Option<u64>,
+    /// `ok`, `error` (tool-level) or `rpc_error` (only recorded with `--trace`).
+    outcome: Option<&'static str>,
 }
 
+/// Default number of operation records kept before the oldest are dropped.
+const DEFAULT_OPERATION_LOG_CAPACITY: usize = 1024;
+
+/// Custom action type of the receipts that seal audit sessions.
+const AUDIT_SEAL_ACTION: &str = "audit_seal";
+
+/// Default number of workspace query results kept for reuse.
+const DEFAULT_WORKSPACE_QUERY_CACHE: usize = 64;
+
 struct McpServer {
     identity_dir: PathBuf,
+    /// Vault holding every identity, used instead of `identity_dir` when set.
+    identity_vault: Option<PathBuf>,
     receipt_dir: PathBuf,
     trust_dir: PathBuf,
     spawn_dir: PathBuf,
-    /// Log of identity operations with context for this session.
-    operation_log: Vec<IdentityOperationRecord>,
+    /// Experience chains and heartbeats recorded by the continuity tools.
+    continuity_dir: PathBuf,
+    /// Attempts recorded by `competence_record`, grouped by domain.
+    competence_dir: PathBuf,
+    /// Declarations and proofs from the `negative_*` tools.
+    negative_dir: PathBuf,
+    /// Public keys of other identities, from `identity_register_key`.
+    key_dir: PathBuf,
+    /// Completed write results keyed by caller-supplied idempotency keys.
+    idempotency_dir: PathBuf,
+    /// Journal for writes that span several stores.
+    transaction_dir: PathBuf,
+    /// Passphrase identity files are saved and loaded with when
+    /// `passphrase_sources` has none for the identity.
+    passphrase: String,
+    /// Per-identity passphrases, consulted before `passphrase`.
+    passphrase_sources: PassphraseSources,
+    /// Capabilities that actions require, consulted by `action_check`.
+    action_requirements: RequirementPolicy,
+    /// Refuse to sign in `action_sign` when `action_requirements` are not
+    /// held (`AID_ENFORCE_ACTION_REQUIREMENTS`). Off by default.
+    enforce_action_requirements: bool,
+    /// Scores grants and receipts against claims in the grounding tools:
+    /// lexical with optional synonyms, or embeddings when configured.
+    similarity: Box<dyn SimilarityProvider + Send>,
+    /// Grant and receipt summaries reused across grounding calls
+    /// (`AID_GROUNDING_CACHE`, on by default).
+    grounding_cache: std::cell::RefCell<GroundingCache>,
+    /// Wording for verification outcomes in verify tool output.
+    verification_messages: OutcomeMessages,
+    /// Log of identity operations with context for this session (ring buffer).
+    operation_log: VecDeque<IdentityOperationRecord>,
+    /// Maximum number of records kept in `operation_log`.
+    operation_log_capacity: usize,
+    /// Every `operation_log` record, persisted per session and sealed when
+    /// the session ends (`AID_AUDIT_LOG`, on by default).
+    audit: AuditLog,
+    /// Whether per-tool timings are recorded and traced to stderr.
+    trace: bool,
     /// Timestamp when this session started.
     session_start_time: Option<u64>,
     /// Multi-context workspace manager for cross-identity queries.
@@ -260,11 +629,23 @@ fn now_secs() -> u64 {
         .as_secs()
 }
 
-fn truncate_text(input: String, max_len: usize) -> String {
-    if input.len() <= max_len {
-        input
+/// Keep the first `max_chars` characters (not bytes, so multibyte text never
+/// splits mid-codepoint), appending "..." only when something was cut.
+fn truncate_text(input: String, max_chars: usize) -> String {
+    match input.char_indices().nth(max_chars) {
+        Some((cut, _)) => format!("{}...", &input[..cut]),
+        None => input,
+    }
+}
+
+/// Shorten `text` to at most `max_chars` characters, the last three of which
+/// become "..." when it had to be cut.
+fn ellipsize(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
     } else {
-        format!("{}...", &input[..max_len])
+        let head: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+        format!("{head}...")
     }
 }
 
@@ -351,10 +732,16 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_core",
             "description": "Compact core identity facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "identity_create".to_string(),
+                    "identity_create_batch".to_string(),
                     "identity_show".to_string(),
+                    "identity_register_key".to_string(),
+                    "artifact_verify".to_string(),
                     "identity_health".to_string(),
+                    "identity_rekey_stores".to_string(),
+                    "identity_change_passphrase".to_string(),
+                    "identity_rotate".to_string(),
                 ],
                 "Core identity operation",
             ),
@@ -366,11 +753,22 @@ fn compact_tool_definitions() -> Vec<Value> {
                 &vec![
                     "action_sign".to_string(),
                     "action_context".to_string(),
+                    "action_check".to_string(),
                     "receipt_verify".to_string(),
+                    "receipt_request_witness".to_string(),
+                    "receipt_add_witness".to_string(),
+                    "receipt_witness".to_string(),
                     "receipt_list".to_string(),
+                    "receipt_query".to_string(),
+                    "receipt_graph".to_string(),
+                    "receipt_export".to_string(),
                     "session_start".to_string(),
                     "session_end".to_string(),
                     "identity_session_resume".to_string(),
+                    "session_trace".to_string(),
+                    "operation_log_export".to_string(),
+                    "audit_list".to_string(),
+                    "audit_show".to_string(),
                 ],
                 "Identity action operation",
             ),
@@ -379,11 +777,16 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_trust",
             "description": "Compact trust facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "trust_grant".to_string(),
                     "trust_revoke".to_string(),
+                    "trust_revoke_simulate".to_string(),
                     "trust_verify".to_string(),
+                    "trust_use".to_string(),
                     "trust_list".to_string(),
+                    "identity_authority_diff".to_string(),
+                    "capability_holders".to_string(),
+                    "trust_path".to_string(),
                 ],
                 "Trust operation",
             ),
@@ -392,12 +795,13 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_continuity",
             "description": "Compact continuity facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "continuity_record".to_string(),
                     "continuity_anchor".to_string(),
                     "continuity_heartbeat".to_string(),
                     "continuity_status".to_string(),
                     "continuity_gaps".to_string(),
+                    "continuity_confidence".to_string(),
                 ],
                 "Continuity operation",
             ),
@@ -406,12 +810,13 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_spawn",
             "description": "Compact spawn facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "spawn_create".to_string(),
                     "spawn_terminate".to_string(),
                     "spawn_list".to_string(),
                     "spawn_lineage".to_string(),
                     "spawn_authority".to_string(),
+                    "spawn_tree".to_string(),
                 ],
                 "Spawn operation",
             ),
@@ -420,7 +825,7 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_competence",
             "description": "Compact competence facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "competence_record".to_string(),
                     "competence_show".to_string(),
                     "competence_prove".to_string(),
@@ -434,7 +839,7 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_negative",
             "description": "Compact negative-capability facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "negative_prove".to_string(),
                     "negative_verify".to_string(),
                     "negative_declare".to_string(),
@@ -448,7 +853,7 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_grounding",
             "description": "Compact grounding facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "identity_ground".to_string(),
                     "identity_evidence".to_string(),
                     "identity_suggest".to_string(),
@@ -460,7 +865,7 @@ fn compact_tool_definitions() -> Vec<Value> {
             "name": "identity_workspace",
             "description": "Compact workspace facade",
             "inputSchema": compact_op_schema(
-                &vec![
+                &[
                     "identity_workspace_create".to_string(),
                     "identity_workspace_add".to_string(),
                     "identity_workspace_list".to_string(),
@@ -506,21 +911,48 @@ fn resolve_compact_tool(group: &str, operation: &str) -> Option<String> {
     let allowed = match group {
         "identity_core" => matches!(
             operation,
-            "identity_create" | "identity_show" | "identity_health"
+            "identity_create"
+                | "identity_create_batch"
+                | "identity_show"
+                | "identity_register_key"
+                | "artifact_verify"
+                | "identity_health"
+                | "identity_rekey_stores"
+                | "identity_change_passphrase"
+                | "identity_rotate"
         ),
         "identity_actions" => matches!(
             operation,
             "action_sign"
                 | "action_context"
+                | "action_check"
                 | "receipt_verify"
+                | "receipt_request_witness"
+                | "receipt_add_witness"
+                | "receipt_witness"
                 | "receipt_list"
+                | "receipt_query"
+                | "receipt_graph"
+                | "receipt_export"
                 | "session_start"
                 | "session_end"
                 | "identity_session_resume"
+                | "session_trace"
+                | "operation_log_export"
+                | "audit_list"
+                | "audit_show"
         ),
         "identity_trust" => matches!(
             operation,
-            "trust_grant" | "trust_revoke" | "trust_verify" | "trust_list"
+            "trust_grant"
+                | "trust_revoke"
+                | "trust_revoke_simulate"
+                | "trust_verify"
+                | "trust_use"
+                | "trust_list"
+                | "identity_authority_diff"
+                | "capability_holders"
+                | "trust_path"
         ),
         "identity_continuity" => matches!(
             operation,
@@ -529,10 +961,16 @@ fn resolve_compact_tool(group: &str, operation: &str) -> Option<String> {
                 | "continuity_heartbeat"
                 | "continuity_status"
                 | "continuity_gaps"
+                | "continuity_confidence"
         ),
         "identity_spawn" => matches!(
             operation,
-            "spawn_create" | "spawn_terminate" | "spawn_list" | "spawn_lineage" | "spawn_authority"
+            "spawn_create"
+                | "spawn_terminate"
+                | "spawn_list"
+                | "spawn_lineage"
+                | "spawn_authority"
+                | "spawn_tree"
         ),
         "identity_competence" => matches!(
             operation,
@@ -602,7 +1040,43 @@ fn normalize_compact_tool_call(
     Ok((resolved, params))
 }
 
-fn dir_size_bytes(path: &PathBuf) -> u64 {
+/// Actor scoping requested by a read tool via its `identity`/`actor` arguments.
+///
+/// Read tools default to [`ActorScope::All`] so that existing callers keep
+/// seeing every actor in the shared stores.
+enum ActorScope {
+    /// No scoping requested — every actor matches.
+    All,
+    /// Only this actor matches. `None` means the requested identity does not
+    /// exist, so nothing matches.
+    Only(Option<IdentityId>),
+}
+
+impl ActorScope {
+    /// Does a receipt actor (or other single identity) fall within the scope?
+    fn matches(&self, actor: &IdentityId) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(scoped) => scoped.as_ref() == Some(actor),
+        }
+    }
+
+    /// Does a trust grant involve the scoped identity as grantor or grantee?
+    fn matches_grant(&self, grant: &agentic_identity::TrustGrant) -> bool {
+        self.matches(&grant.grantor) || self.matches(&grant.grantee)
+    }
+
+    /// Human-readable label for error messages.
+    fn label(&self) -> String {
+        match self {
+            Self::All => "all identities".to_string(),
+            Self::Only(Some(actor)) => actor.to_string(),
+            Self::Only(None) => "unknown identity".to_string(),
+        }
+    }
+}
+
+fn dir_size_bytes(path: &std::path::Path) -> u64 {
     fn walk(path: &std::path::Path) -> u64 {
         let Ok(entries) = std::fs::read_dir(path) else {
             return 0;
@@ -623,22 +1097,347 @@ fn dir_size_bytes(path: &PathBuf) -> u64 {
 }
 
 impl McpServer {
-    fn new() -> Self {
+    fn new(passphrase: String) -> Self {
         Self {
+            passphrase,
+            passphrase_sources: PassphraseSources::default(),
             identity_dir: identity_dir(),
+            identity_vault: identity_vault(),
             receipt_dir: receipt_dir(),
             trust_dir: trust_dir(),
             spawn_dir: spawn_dir(),
-            operation_log: Vec::new(),
+            continuity_dir: continuity_dir(),
+            competence_dir: competence_dir(),
+            negative_dir: negative_dir(),
+            key_dir: key_dir(),
+            idempotency_dir: idempotency_dir(),
+            transaction_dir: transaction_dir(),
+            action_requirements: load_action_requirements(),
+            enforce_action_requirements: read_env_bool_any(
+                &[
+                    "AID_ENFORCE_ACTION_REQUIREMENTS",
+                    "ENFORCE_ACTION_REQUIREMENTS",
+                ],
+                false,
+            ),
+            similarity: load_similarity_provider(),
+            grounding_cache: std::cell::RefCell::new(GroundingCache::new(read_env_bool_any(
+                &["AID_GROUNDING_CACHE", "GROUNDING_CACHE"],
+                true,
+            ))),
+            verification_messages: load_verification_messages(),
+            operation_log: VecDeque::new(),
+            operation_log_capacity: read_env_usize_any(
+                &["AID_OPERATION_LOG_CAPACITY", "OPERATION_LOG_CAPACITY"],
+                DEFAULT_OPERATION_LOG_CAPACITY,
+            )
+            .max(1),
+            audit: AuditLog::new(
+                read_env_bool_any(&["AID_AUDIT_LOG", "AUDIT_LOG"], true).then(audit_dir),
+            ),
+            trace: false,
             session_start_time: None,
-            workspace_manager: IdentityWorkspaceManager::new(),
+            workspace_manager: IdentityWorkspaceManager::new(read_env_usize_any(
+                &["AID_WORKSPACE_QUERY_CACHE", "WORKSPACE_QUERY_CACHE"],
+                DEFAULT_WORKSPACE_QUERY_CACHE,
+            )),
+        }
+    }
+
+    /// Quarantine the identity, receipt, trust and spawn files a crash left
+    /// partial, logging each one. A store that cannot be scanned is logged
+    /// and skipped, so a held lock never keeps the server from starting.
+    fn recover_partial_writes(&self) -> QuarantineReport {
+        let mut scans = vec![("identity", recover_identity_files(&self.identity_dir))];
+        if self.receipt_dir.is_dir() {
+            scans.push((
+                "receipt",
+                ReceiptStore::new(&self.receipt_dir).and_then(|s| s.recover()),
+            ));
+        }
+        if self.trust_dir.is_dir() {
+            scans.push((
+                "trust",
+                TrustStore::new(&self.trust_dir).and_then(|s| s.recover()),
+            ));
+        }
+        if self.spawn_dir.is_dir() {
+            scans.push((
+                "spawn",
+                SpawnStore::new(&self.spawn_dir).and_then(|s| s.recover()),
+            ));
+        }
+
+        let mut report = QuarantineReport::default();
+        for (store, scan) in scans {
+            match scan {
+                Ok(found) => {
+                    for (path, reason) in &found.quarantined {
+                        tracing::warn!(
+                            "quarantined partial {store} file {}: {reason}",
+                            path.display()
+                        );
+                    }
+                    report.merge(found);
+                }
+                Err(e) => {
+                    tracing::warn!("could not scan the {store} store for partial writes: {e}")
+                }
+            }
+        }
+        report
+    }
+
+    /// The passphrase identity `name` is saved and loaded with: its own if a
+    /// passphrase source has one, otherwise the server passphrase.
+    fn passphrase_for(&self, name: &str) -> agentic_identity::Result<String> {
+        match self.passphrase_sources.lookup(name) {
+            Ok(Some(passphrase)) => Ok(passphrase),
+            Ok(None) => Ok(self.passphrase.clone()),
+            Err(e) => Err(agentic_identity::IdentityError::StorageError(e)),
+        }
+    }
+
+    /// Where identity `name` is kept: its `.aid` file if it has one,
+    /// otherwise the vault when one is configured. New identities go to the
+    /// vault; files (such as spawned children) are still read alongside it.
+    fn identity_location(&self, name: &str) -> PathBuf {
+        let path = self.identity_dir.join(format!("{name}.aid"));
+        match &self.identity_vault {
+            Some(vault) if !path.exists() => vault.clone(),
+            _ => path,
+        }
+    }
+
+    /// The vault's public documents, or none without a readable vault.
+    fn vault_documents(&self) -> BTreeMap<String, IdentityDocument> {
+        self.identity_vault
+            .as_deref()
+            .filter(|vault| vault.exists())
+            .and_then(|vault| read_vault_documents(vault).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether an identity named `name` exists.
+    fn identity_exists(&self, name: &str) -> bool {
+        self.identity_dir.join(format!("{name}.aid")).exists()
+            || self.vault_documents().contains_key(name)
+    }
+
+    /// Decrypt identity `name`. A `.aid` file is opened with
+    /// [`passphrase_for`](Self::passphrase_for), the vault with the server
+    /// passphrase.
+    fn load_anchor(&self, name: &str) -> agentic_identity::Result<IdentityAnchor> {
+        let path = self.identity_dir.join(format!("{name}.aid"));
+        match &self.identity_vault {
+            Some(vault) if !path.exists() => {
+                IdentityVault::open(vault, &self.passphrase)?.load(name)
+            }
+            _ => load_identity(&path, &self.passphrase_for(name)?),
+        }
+    }
+
+    /// The public document of identity `name`, read without a passphrase.
+    fn read_document(&self, name: &str) -> agentic_identity::Result<IdentityDocument> {
+        let path = self.identity_dir.join(format!("{name}.aid"));
+        match &self.identity_vault {
+            Some(vault) if !path.exists() => {
+                read_vault_documents(vault)?.remove(name).ok_or_else(|| {
+                    agentic_identity::IdentityError::NotFound(format!("identity '{name}' in vault"))
+                })
+            }
+            _ => read_public_document(&path),
+        }
+    }
+
+    /// Save `anchor` as identity `name`, replacing any identity of that name
+    /// where it is kept.
+    fn store_anchor(&self, name: &str, anchor: IdentityAnchor) -> agentic_identity::Result<()> {
+        let path = self.identity_dir.join(format!("{name}.aid"));
+        match &self.identity_vault {
+            Some(vault) if !path.exists() => {
+                let _guard = VAULT_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+                let mut opened = IdentityVault::open_or_create(vault, &self.passphrase)?;
+                if opened.contains(name) {
+                    opened.remove(name)?;
+                }
+                opened.add(name, anchor)?;
+                opened.save()
+            }
+            _ => {
+                std::fs::create_dir_all(&self.identity_dir)?;
+                save_identity(&anchor, &path, &self.passphrase_for(name)?)
+            }
+        }
+    }
+
+    /// The public documents of every identity, by name: `.aid` files first,
+    /// then vault entries without a file of the same name. Unreadable
+    /// entries are skipped.
+    fn identity_documents(&self) -> Vec<(String, IdentityDocument)> {
+        let mut documents: BTreeMap<String, IdentityDocument> =
+            std::fs::read_dir(&self.identity_dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "aid"))
+                .filter_map(|path| {
+                    let name = path.file_stem()?.to_str()?.to_string();
+                    read_public_document(&path).ok().map(|doc| (name, doc))
+                })
+                .collect();
+        for (name, doc) in self.vault_documents() {
+            documents.entry(name).or_insert(doc);
+        }
+        documents.into_iter().collect()
+    }
+
+    /// Install the identity taken from the `--identity-from-env` variable
+    /// as the default identity. It is saved under the passphrase
+    /// identity_create would use for it, so every tool loads it as it would
+    /// a file created by identity_create. Restarting with the same key
+    /// changes nothing; a default identity with a different ID is an error,
+    /// never replaced.
+    fn install_env_identity(&self, env: &EnvIdentity) -> Result<IdentityId, String> {
+        let var = &env.var;
+        let anchor =
+            IdentityAnchor::from_key_material(&env.material).map_err(|e| format!("{var}: {e}"))?;
+        let path = self.identity_location(DEFAULT_IDENTITY);
+
+        if self.identity_exists(DEFAULT_IDENTITY) {
+            let doc = self
+                .read_document(DEFAULT_IDENTITY)
+                .map_err(|e| format!("failed to read {}: {e}", path.display()))?;
+            if doc.id != anchor.id() {
+                return Err(format!(
+                    "{} holds identity {}, not {} from {var}",
+                    path.display(),
+                    doc.id,
+                    anchor.id()
+                ));
+            }
+            return Ok(doc.id);
+        }
+
+        let identity_id = anchor.id();
+        self.store_anchor(DEFAULT_IDENTITY, anchor)
+            .map_err(|e| format!("failed to save identity from {var}: {e}"))?;
+        Ok(identity_id)
+    }
+
+    /// Append a record, evicting the oldest once the log is at capacity.
+    /// Every record is also written to the audit log.
+    fn push_operation(&mut self, record: IdentityOperationRecord) {
+        self.audit.append(&AuditEntry {
+            tool_name: record.tool_name.clone(),
+            intent: record.intent.clone(),
+            summary: record.summary.clone(),
+            timestamp: record.timestamp,
+            elapsed_micros: record.elapsed_micros,
+            outcome: record.outcome.map(str::to_string),
+        });
+        while self.operation_log.len() >= self.operation_log_capacity {
+            self.operation_log.pop_front();
+        }
+        self.operation_log.push_back(record);
+    }
+
+    /// Seal the open audit session, if it has any records: sign its
+    /// transcript digest in an `audit_seal` receipt by the default identity
+    /// and store the seal. Without a usable default identity the seal is
+    /// stored unsigned.
+    fn seal_audit_session(&mut self) -> Option<AuditSeal> {
+        let (session, digest) = self.audit.close()?;
+        let sealed_at = now_secs();
+        let receipt_id = match self.sign_audit_seal(&session, &digest, sealed_at) {
+            Ok(receipt_id) => Some(receipt_id),
+            Err(e) => {
+                tracing::warn!("audit session {} sealed unsigned: {e}", session.id);
+                None
+            }
+        };
+        let seal = audit::seal_for(&session, digest, sealed_at, receipt_id);
+        if let Err(e) = self.audit.write_seal(&seal) {
+            tracing::warn!("failed to store seal of audit session {}: {e}", session.id);
+        }
+        Some(seal)
+    }
+
+    fn sign_audit_seal(
+        &self,
+        session: &audit::OpenSession,
+        digest: &str,
+        sealed_at: u64,
+    ) -> std::result::Result<String, String> {
+        if !self.identity_exists(DEFAULT_IDENTITY) {
+            return Err(format!("identity '{DEFAULT_IDENTITY}' not found"));
         }
+        let anchor = self
+            .load_anchor(DEFAULT_IDENTITY)
+            .map_err(|e| format!("failed to load identity '{DEFAULT_IDENTITY}': {e}"))?;
+        let content = ActionContent::with_data(
+            format!(
+                "Sealed audit session {} ({} operations)",
+                session.id, session.record_count
+            ),
+            json!({
+                "session_id": session.id,
+                "digest": digest,
+                "record_count": session.record_count,
+                "started_at": session.started_at,
+                "sealed_at": sealed_at,
+            }),
+        );
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Custom(AUDIT_SEAL_ACTION.to_string()),
+            content,
+        )
+        .sign(anchor.signing_key())
+        .map_err(|e| format!("failed to sign seal: {e}"))?;
+        ReceiptStore::new(&self.receipt_dir)
+            .and_then(|store| store.save(&receipt))
+            .map_err(|e| format!("failed to save seal receipt: {e}"))?;
+        Ok(receipt.id.0)
+    }
+
+    /// Seal the audit session before the server goes away.
+    fn shutdown(&mut self) {
+        self.seal_audit_session();
     }
 
     /// Route a JSON-RPC request to the appropriate handler.
+    /// Handle one JSON-RPC message, returning `Value::Null` when nothing
+    /// should be sent back.
+    ///
+    /// The response echoes the request's id exactly as sent (string, number
+    /// or null). A message without an id is a notification and never gets a
+    /// response, even if it fails. An id that is not a string, number or
+    /// null makes the request invalid, reported with a null id.
     fn handle_request(&mut self, request: Value) -> Value {
-        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        if !request.is_object() {
+            return rpc_error(Value::Null, -32600, "request must be a JSON object");
+        }
+        let id = match request.get("id") {
+            None => None,
+            Some(id @ (Value::String(_) | Value::Number(_) | Value::Null)) => Some(id.clone()),
+            Some(_) => {
+                return rpc_error(
+                    Value::Null,
+                    -32600,
+                    "request id must be a string, number, or null",
+                )
+            }
+        };
 
+        let response = self.dispatch_request(id.clone().unwrap_or(Value::Null), &request);
+        match id {
+            Some(_) => response,
+            None => Value::Null,
+        }
+    }
+
+    fn dispatch_request(&mut self, id: Value, request: &Value) -> Value {
         // ── JSON-RPC version validation ──────────────────────────────────
         if let Some(version) = request.get("jsonrpc").and_then(|v| v.as_str()) {
             if version != "2.0" {
@@ -661,14 +1460,13 @@ impl McpServer {
 
         match method.as_str() {
             "initialize" => {
+                self.seal_audit_session();
                 self.session_start_time = Some(now_secs());
                 self.operation_log.clear();
                 self.handle_initialize(id)
             }
-            "initialized" | "notifications/initialized" => {
-                // Notification — no response needed, return null sentinel
-                Value::Null
-            }
+            // Notifications — no response needed, return null sentinel
+            "initialized" | "notifications/initialized" => Value::Null,
             "tools/list" => self.handle_tools_list(id),
             "tools/call" => self.handle_tools_call(id, &params),
             "resources/list" => self.handle_resources_list(id),
@@ -709,7 +1507,7 @@ impl McpServer {
         let mut tools_list = json!([
             {
                 "name": "identity_create",
-                "description": "Create a new AgenticIdentity. Uses the default MCP passphrase",
+                "description": "Create a new AgenticIdentity, encrypted with its own passphrase if one is provisioned, otherwise the server passphrase",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
@@ -720,6 +1518,21 @@ impl McpServer {
                     }
                 }
             },
+            {
+                "name": "identity_create_batch",
+                "description": "Create many identities and return a manifest of their IDs, public keys and fingerprints. Names that already exist are skipped, so an interrupted batch can be re-run",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["names"],
+                    "properties": {
+                        "names": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Identity names to create"
+                        }
+                    }
+                }
+            },
             {
                 "name": "identity_show",
                 "description": "Show identity information (public document, no passphrase required)",
@@ -733,6 +1546,41 @@ impl McpServer {
                     }
                 }
             },
+            {
+                "name": "identity_register_key",
+                "description": "Record another identity's public key so trust can be granted to it. Give either its public key (filed under the ID derived from it) or its identity document (required for identities that have rotated keys)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "public_key": {
+                            "type": "string",
+                            "description": "Base64 Ed25519 public key"
+                        },
+                        "document": {
+                            "type": "object",
+                            "description": "Identity document, as returned by identity_show or aid export; must be self-signed"
+                        },
+                        "label": {
+                            "type": "string",
+                            "description": "Optional label (defaults to the document's name)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "artifact_verify",
+                "description": "Verify the stored identity, receipt or trust grant an aid:// URI points to. Read-only; returns {kind, id, valid, reasons}",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["uri"],
+                    "properties": {
+                        "uri": {
+                            "type": "string",
+                            "description": "aid://identity/<name>, aid://receipt/<receipt_id> or aid://trust/<trust_id>"
+                        }
+                    }
+                }
+            },
             {
                 "name": "action_sign",
                 "description": "Sign an action and create a verifiable receipt",
@@ -755,7 +1603,19 @@ impl McpServer {
                         },
                         "chain_to": {
                             "type": "string",
-                            "description": "Previous receipt ID to chain to (arec_...)"
+                            "description": "Previous receipt ID to chain to (arec_...); must exist and be signed by the same identity"
+                        },
+                        "chain_force": {
+                            "type": "boolean",
+                            "description": "Chain to a receipt not in the local store, e.g. one signed on another machine (default: false)"
+                        },
+                        "intent": {
+                            "type": "string",
+                            "description": "Why the action is taken (signed; defaults to the latest action_context intent)"
+                        },
+                        "cost": {
+                            "type": "integer",
+                            "description": "Cost of the action (e.g. tokens), metered against the signer's spawn budget"
                         },
                         "identity": {
                             "type": "string",
@@ -765,23 +1625,124 @@ impl McpServer {
                 }
             },
             {
-                "name": "receipt_verify",
-                "description": "Verify the cryptographic signature on a receipt",
+                "name": "action_check",
+                "description": "Pre-flight check: which capabilities a proposed action requires and whether the identity holds them (never signs)",
                 "inputSchema": {
                     "type": "object",
-                    "required": ["receipt_id"],
+                    "required": ["action"],
                     "properties": {
-                        "receipt_id": {
+                        "action": {
                             "type": "string",
-                            "description": "Receipt ID (arec_...)"
+                            "description": "Human-readable description of the proposed action"
+                        },
+                        "action_type": {
+                            "type": "string",
+                            "description": "Action type: decision, observation, mutation, delegation, revocation, identity_operation, or custom string",
+                            "default": "decision"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        },
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Identity ID (aid_...) — no identity file needed"
                         }
                     }
                 }
             },
             {
-                "name": "trust_grant",
-                "description": "Grant trust (capabilities) to another identity",
-                "inputSchema": {
+                "name": "receipt_verify",
+                "description": "Verify the cryptographic signature on a receipt",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["receipt_id"],
+                    "properties": {
+                        "receipt_id": {
+                            "type": "string",
+                            "description": "Receipt ID (arec_...)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Only accept receipts signed by this identity name (default: any actor)"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only accept receipts signed by this identity ID (aid_...)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "receipt_request_witness",
+                "description": "Get the exact message a witness must sign to co-sign a stored receipt",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["receipt_id"],
+                    "properties": {
+                        "receipt_id": {
+                            "type": "string",
+                            "description": "Receipt ID (arec_...)"
+                        },
+                        "witness_id": {
+                            "type": "string",
+                            "description": "Witness identity ID (aid_...); fills it into the message"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "receipt_add_witness",
+                "description": "Verify a witness signature and attach it to a stored receipt",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["receipt_id", "witness_id", "witness_key", "witnessed_at", "signature"],
+                    "properties": {
+                        "receipt_id": {
+                            "type": "string",
+                            "description": "Receipt ID (arec_...)"
+                        },
+                        "witness_id": {
+                            "type": "string",
+                            "description": "Witness identity ID (aid_...)"
+                        },
+                        "witness_key": {
+                            "type": "string",
+                            "description": "Witness Ed25519 public key (base64)"
+                        },
+                        "witnessed_at": {
+                            "type": "integer",
+                            "description": "Timestamp used in the signed message (microseconds since epoch)"
+                        },
+                        "signature": {
+                            "type": "string",
+                            "description": "Witness signature over the message from receipt_request_witness (base64)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "receipt_witness",
+                "description": "Co-sign a stored receipt as a second local identity",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["receipt_id", "witness"],
+                    "properties": {
+                        "receipt_id": {
+                            "type": "string",
+                            "description": "Receipt ID (arec_...)"
+                        },
+                        "witness": {
+                            "type": "string",
+                            "description": "Name of the witnessing identity (not the receipt's actor)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "trust_grant",
+                "description": "Grant trust (capabilities) to another identity",
+                "inputSchema": {
                     "type": "object",
                     "required": ["grantee", "capabilities"],
                     "properties": {
@@ -807,6 +1768,18 @@ impl McpServer {
                             "description": "Whether the grantee can delegate trust to others",
                             "default": false
                         },
+                        "purpose": {
+                            "type": "string",
+                            "description": "Why the grant is issued (signed into the grant)"
+                        },
+                        "justification_receipt": {
+                            "type": "string",
+                            "description": "Receipt ID (arec_...) signed by the grantor that justifies this grant"
+                        },
+                        "grantee_key": {
+                            "type": "string",
+                            "description": "Grantee's base64 public key (e.g. from an identity_create_batch manifest); must derive the grantee ID. Defaults to the key registered with identity_register_key, or that of a local identity"
+                        },
                         "identity": {
                             "type": "string",
                             "description": "Grantor identity name (default: \"default\")"
@@ -837,6 +1810,20 @@ impl McpServer {
                     }
                 }
             },
+            {
+                "name": "trust_revoke_simulate",
+                "description": "Show what revoking a trust grant would invalidate, without revoking it: every dependent delegated grant, whether it loses all or only some capabilities, and the capabilities each grantee would lose",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["trust_id"],
+                    "properties": {
+                        "trust_id": {
+                            "type": "string",
+                            "description": "Trust grant ID (atrust_...)"
+                        }
+                    }
+                }
+            },
             {
                 "name": "trust_verify",
                 "description": "Verify whether a trust grant is currently valid for a capability",
@@ -851,6 +1838,40 @@ impl McpServer {
                         "capability": {
                             "type": "string",
                             "description": "Capability URI to check (default: \"*\" checks overall validity)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Only accept grants where this identity name is grantor or grantee (default: any)"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only accept grants where this identity ID (aid_...) is grantor or grantee"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "trust_use",
+                "description": "Exercise a trust grant for a capability: verify it, then record the use so max_uses is enforced. Fails once every allowed use has been recorded",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["trust_id", "capability"],
+                    "properties": {
+                        "trust_id": {
+                            "type": "string",
+                            "description": "Trust grant ID (atrust_...)"
+                        },
+                        "capability": {
+                            "type": "string",
+                            "description": "Capability URI being exercised"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Only accept grants where this identity name is grantor or grantee (default: any)"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only accept grants where this identity ID (aid_...) is grantor or grantee"
                         }
                     }
                 }
@@ -869,212 +1890,508 @@ impl McpServer {
                         "valid_only": {
                             "type": "boolean",
                             "description": "Only show non-revoked grants (default: false)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Only show grants where this identity name is grantor or grantee (default: all)"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only show grants where this identity ID (aid_...) is grantor or grantee"
                         }
                     }
                 }
             },
             {
-                "name": "receipt_list",
-                "description": "List action receipts with optional filters",
+                "name": "capability_holders",
+                "description": "List the identities that currently hold a capability through a valid trust grant or spawn authority, wildcards included. Paged: pass next_cursor back as cursor",
                 "inputSchema": {
                     "type": "object",
+                    "required": ["capability"],
                     "properties": {
-                        "actor": {
+                        "capability": {
                             "type": "string",
-                            "description": "Filter by actor identity ID (aid_...)"
+                            "description": "Capability URI to look up, e.g. \"deploy:prod\""
                         },
-                        "action_type": {
-                            "type": "string",
-                            "description": "Filter by action type"
+                        "at": {
+                            "type": "integer",
+                            "description": "Timestamp to evaluate at (microseconds since epoch, default: now)"
                         },
                         "limit": {
                             "type": "integer",
-                            "description": "Maximum number of receipts to return (default: 20)"
+                            "description": "Maximum grant and spawn records examined per page (default: 500)"
+                        },
+                        "cursor": {
+                            "type": "string",
+                            "description": "next_cursor from the previous page"
                         }
                     }
                 }
             },
             {
-                "name": "identity_health",
-                "description": "Check system health: identity files, receipt store, trust store",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {}
-                }
-            },
-            {
-                "name": "continuity_record",
-                "description": "Record an experience event in the continuity chain",
+                "name": "trust_path",
+                "description": "Find a delegation path through which one identity grants another a capability, across this server's trust store and any other agents' stores, with each hop's constraints",
                 "inputSchema": {
                     "type": "object",
-                    "required": ["content_hash"],
+                    "required": ["from", "to", "capability"],
                     "properties": {
-                        "experience_type": {
+                        "from": {
                             "type": "string",
-                            "description": "Experience type: perception, cognition, action, memory, learning, planning, emotion, idle, system (default: cognition)"
+                            "description": "Granting identity: ID (aid_...) or identity name"
                         },
-                        "content_hash": {
+                        "to": {
                             "type": "string",
-                            "description": "Hash of the experience content"
-                        },
-                        "intensity": {
-                            "type": "number",
-                            "description": "Intensity of the experience (0.0 - 1.0, default: 0.5)"
+                            "description": "Receiving identity: ID (aid_...) or identity name"
                         },
-                        "identity": {
+                        "capability": {
                             "type": "string",
-                            "description": "Identity name (default: \"default\")"
+                            "description": "Capability URI the path must grant, e.g. \"deploy:prod\""
+                        },
+                        "stores": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Trust store directories of other agents to search as well"
+                        },
+                        "at": {
+                            "type": "integer",
+                            "description": "Timestamp to evaluate at (microseconds since epoch, default: now)"
                         }
                     }
                 }
             },
             {
-                "name": "continuity_anchor",
-                "description": "Create a continuity anchor (checkpoint) at the current state",
+                "name": "identity_authority_diff",
+                "description": "Compare an identity's effective authority at two timestamps: capabilities gained and lost through grants, expiry, revocation and spawn lifetime",
                 "inputSchema": {
                     "type": "object",
+                    "required": ["from"],
                     "properties": {
-                        "anchor_type": {
-                            "type": "string",
-                            "description": "Anchor type: genesis, manual, time_based, experience_count (default: manual)"
+                        "from": {
+                            "type": "integer",
+                            "description": "Earlier timestamp (microseconds since epoch)"
+                        },
+                        "to": {
+                            "type": "integer",
+                            "description": "Later timestamp (microseconds since epoch, default: now)"
                         },
                         "identity": {
                             "type": "string",
                             "description": "Identity name (default: \"default\")"
+                        },
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Identity ID (aid_...) — no identity file needed"
+                        },
+                        "spawn_id": {
+                            "type": "string",
+                            "description": "Spawn record ID — inspects the record's child"
                         }
                     }
                 }
             },
             {
-                "name": "continuity_heartbeat",
-                "description": "Create a heartbeat record indicating the agent is alive",
+                "name": "receipt_list",
+                "description": "List action receipts with optional filters",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "status": {
+                        "actor": {
                             "type": "string",
-                            "description": "Heartbeat status: active, idle, suspended, degraded (default: active)"
+                            "description": "Filter by actor identity ID (aid_...)"
                         },
                         "identity": {
                             "type": "string",
-                            "description": "Identity name (default: \"default\")"
+                            "description": "Filter by actor identity name (default: all actors)"
+                        },
+                        "action_type": {
+                            "type": "string",
+                            "description": "Filter by action type"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of receipts to return (default: 20)"
                         }
                     }
                 }
             },
             {
-                "name": "continuity_status",
-                "description": "Get the continuity status for an identity",
+                "name": "receipt_query",
+                "description": "Query receipts with a structured filter (actor, action type, time range, data fields, chain membership). Returns the matches and the index plan used to find them",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "identity": {
-                            "type": "string",
-                            "description": "Identity name (default: \"default\")"
+                        "filter": {
+                            "type": "object",
+                            "description": "Receipt filter; all fields optional and combined with AND",
+                            "properties": {
+                                "actor": { "type": "string", "description": "Actor identity ID (aid_...)" },
+                                "action_type": { "type": "string", "description": "Action type tag, e.g. decision or a custom tag" },
+                                "since": { "type": "integer", "description": "At or after this time (microseconds since epoch)" },
+                                "until": { "type": "integer", "description": "At or before this time (microseconds since epoch)" },
+                                "data": { "type": "object", "description": "Values the action data must hold, keyed by dotted path, e.g. {\"target.env\": \"prod\"}" },
+                                "chained_to": { "type": "string", "description": "Only receipts chaining from this receipt ID, directly or transitively" },
+                                "order": { "type": "string", "enum": ["newest_first", "oldest_first"], "description": "Result order (default: newest_first)" },
+                                "limit": { "type": "integer", "description": "Maximum number of receipts to return (default: 20)" }
+                            },
+                            "additionalProperties": false
                         }
                     }
                 }
             },
             {
-                "name": "continuity_gaps",
-                "description": "Detect gaps in the experience chain",
+                "name": "receipt_graph",
+                "description": "Render how receipts link together (chains, spawns, grant exercises) as a Graphviz DOT or Mermaid diagram",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "grace_period_seconds": {
-                            "type": "integer",
-                            "description": "Grace period in seconds (gaps shorter are ignored, default: 300)"
+                        "format": {
+                            "type": "string",
+                            "enum": ["mermaid", "dot", "json"],
+                            "description": "Output format (default: mermaid)"
+                        },
+                        "tip": {
+                            "type": "string",
+                            "description": "Only the chain ending at this receipt ID"
+                        },
+                        "actor": {
+                            "type": "string",
+                            "description": "Only receipts by this actor identity ID (aid_...)"
                         },
                         "identity": {
                             "type": "string",
-                            "description": "Identity name (default: \"default\")"
+                            "description": "Only receipts by this actor identity name (default: all actors)"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum number of receipts to draw, newest kept (default: 100)"
                         }
                     }
                 }
             },
             {
-                "name": "spawn_create",
-                "description": "Spawn a child identity with bounded authority",
+                "name": "receipt_export",
+                "description": "Export every receipt as NDJSON, oldest first, ending with a summary line (count and chain tip) that lets an importer detect truncation",
                 "inputSchema": {
                     "type": "object",
-                    "required": ["purpose", "authority"],
                     "properties": {
-                        "spawn_type": {
+                        "actor": {
                             "type": "string",
-                            "description": "Spawn type: worker, delegate, clone, specialist (default: worker)"
+                            "description": "Only receipts by this identity ID (aid_...)"
                         },
-                        "purpose": {
+                        "identity": {
                             "type": "string",
-                            "description": "Purpose of the spawned identity"
+                            "description": "Only receipts by this identity name (default: all actors)"
                         },
-                        "authority": {
-                            "type": "array",
-                            "items": {"type": "string"},
-                            "description": "Capability URIs to grant to the child"
-                        },
-                        "lifetime": {
-                            "type": "string",
-                            "description": "Lifetime: indefinite, parent_termination, or duration in seconds (default: indefinite)"
+                        "since": {
+                            "type": "integer",
+                            "description": "Only receipts at or after this time (microseconds since epoch)"
                         },
-                        "identity": {
-                            "type": "string",
-                            "description": "Parent identity name (default: \"default\")"
+                        "until": {
+                            "type": "integer",
+                            "description": "Only receipts at or before this time (microseconds since epoch)"
                         }
                     }
                 }
             },
             {
-                "name": "spawn_terminate",
-                "description": "Terminate a spawned child identity",
+                "name": "identity_health",
+                "description": "Check system health: identity files, receipt store, trust store, and grants expiring soon or already expired",
                 "inputSchema": {
                     "type": "object",
-                    "required": ["spawn_id"],
                     "properties": {
-                        "spawn_id": {
-                            "type": "string",
-                            "description": "Spawn record ID (aspawn_...)"
-                        },
-                        "reason": {
-                            "type": "string",
-                            "description": "Reason for termination"
-                        },
-                        "cascade": {
-                            "type": "boolean",
-                            "description": "Whether to cascade termination to descendants (default: false)"
-                        },
-                        "identity": {
+                        "expiry_horizon": {
                             "type": "string",
-                            "description": "Parent identity name (default: \"default\")"
+                            "description": "Report grants expiring within this duration, e.g. \"24h\", \"7d\" (default: \"24h\")"
                         }
                     }
                 }
             },
             {
-                "name": "spawn_list",
-                "description": "List spawned child identities",
+                "name": "identity_rekey_stores",
+                "description": "Re-encrypt every local identity file under a new passphrase, all or nothing. Resumes an interrupted rekey",
                 "inputSchema": {
                     "type": "object",
+                    "required": ["new_passphrase"],
                     "properties": {
-                        "active_only": {
-                            "type": "boolean",
-                            "description": "Only show active (non-terminated) spawns (default: false)"
+                        "old_passphrase": {
+                            "type": "string",
+                            "description": "Passphrase the files are encrypted with now (default: the MCP passphrase)"
                         },
-                        "identity": {
+                        "new_passphrase": {
                             "type": "string",
-                            "description": "Parent identity name (default: \"default\")"
+                            "description": "Passphrase to re-encrypt the files with"
                         }
                     }
                 }
             },
             {
-                "name": "spawn_lineage",
-                "description": "Get lineage information for an identity",
+                "name": "identity_change_passphrase",
+                "description": "Re-encrypt one identity file under a new passphrase, e.g. to move an identity off the legacy default onto the server's configured passphrase",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        },
+                        "old_passphrase": {
+                            "type": "string",
+                            "description": "Passphrase the file is encrypted with now (default: the legacy default passphrase)"
+                        },
+                        "new_passphrase": {
+                            "type": "string",
+                            "description": "Passphrase to re-encrypt the file with (default: the identity's own passphrase if one is provisioned, else the server's)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "identity_rotate",
+                "description": "Rotate an identity's signing key: generate a new keypair, record the rotation signed by the old key, and re-encrypt the .aid file in place. The identity ID changes; the old key stays in rotation_history",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        },
+                        "reason": {
+                            "type": "string",
+                            "enum": ["scheduled", "compromised", "device_lost", "policy_required", "manual"],
+                            "description": "Why the key is being rotated (default: manual)"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_record",
+                "description": "Record an experience event in the continuity chain",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["content_hash"],
+                    "properties": {
+                        "experience_type": {
+                            "type": "string",
+                            "description": "Experience type: perception, cognition, action, memory, learning, planning, emotion, idle, system (default: cognition)"
+                        },
+                        "content_hash": {
+                            "type": "string",
+                            "description": "Hash of the experience content"
+                        },
+                        "intensity": {
+                            "type": "number",
+                            "description": "Intensity of the experience (0.0 - 1.0, default: 0.5)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_anchor",
+                "description": "Create a continuity anchor (checkpoint) at the current state",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "anchor_type": {
+                            "type": "string",
+                            "description": "Anchor type: genesis, manual, time_based, experience_count (default: manual)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_heartbeat",
+                "description": "Create a heartbeat record indicating the agent is alive",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "status": {
+                            "type": "string",
+                            "description": "Heartbeat status: active, idle, suspended, degraded (default: active)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_status",
+                "description": "Get the continuity status for an identity",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_gaps",
+                "description": "Detect gaps in the experience chain, with a probable cause for each (planned_suspension, crash, maintenance, unknown)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "grace_period_seconds": {
+                            "type": "integer",
+                            "description": "Grace period in seconds (gaps shorter are ignored, default: 300)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "continuity_confidence",
+                "description": "Score from 0 to 1 how confidently an identity maintained continuity over a window, from its gaps, anchors and heartbeats",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "from": {
+                            "type": "integer",
+                            "description": "Window start (microseconds since epoch, default: first experience)"
+                        },
+                        "to": {
+                            "type": "integer",
+                            "description": "Window end (microseconds since epoch, default: now)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "spawn_create",
+                "description": "Spawn a child identity with bounded authority",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["purpose", "authority"],
+                    "properties": {
+                        "spawn_type": {
+                            "type": "string",
+                            "description": "Spawn type: worker, delegate, clone, specialist (default: worker)"
+                        },
+                        "purpose": {
+                            "type": "string",
+                            "description": "Purpose of the spawned identity"
+                        },
+                        "authority": {
+                            "type": "array",
+                            "items": {"type": "string"},
+                            "description": "Capability URIs to grant to the child"
+                        },
+                        "lifetime": {
+                            "type": "string",
+                            "description": "Lifetime: indefinite, parent_termination, or duration in seconds (default: indefinite)"
+                        },
+                        "budget": {
+                            "type": "object",
+                            "description": "Resource budgets for the child (all optional, default: unlimited). Once one is spent, operations needing it are refused",
+                            "properties": {
+                                "max_receipts": { "type": "integer", "description": "Receipts the child may sign" },
+                                "max_sub_spawns": { "type": "integer", "description": "Spawns the child may make" },
+                                "max_cost": { "type": "integer", "description": "Total cost the child may report through action_sign" }
+                            },
+                            "additionalProperties": false
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Parent identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "spawn_terminate",
+                "description": "Terminate a spawned child identity",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["spawn_id"],
+                    "properties": {
+                        "spawn_id": {
+                            "type": "string",
+                            "description": "Spawn record ID (aspawn_...)"
+                        },
+                        "reason": {
+                            "type": "string",
+                            "description": "Reason for termination"
+                        },
+                        "cascade": {
+                            "type": "boolean",
+                            "description": "Whether to cascade termination to descendants (default: false)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Parent identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "spawn_list",
+                "description": "List spawned child identities",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "active_only": {
+                            "type": "boolean",
+                            "description": "Only show active spawns: not terminated, not expired, and with an active lineage (default: false)"
+                        },
+                        "parent": {
+                            "type": "string",
+                            "description": "Only direct children of this identity ID (aid_...)"
+                        },
+                        "spawn_type": {
+                            "type": "string",
+                            "description": "Only this spawn type: worker, delegate, clone, specialist, or a custom tag"
+                        },
+                        "limit": {
+                            "type": "integer",
+                            "description": "Maximum results per page (default: 20)",
+                            "default": 20
+                        },
+                        "cursor": {
+                            "type": "string",
+                            "description": "next_cursor from a previous page"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Parent identity name (default: \"default\")"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "spawn_lineage",
+                "description": "Get lineage information for an identity",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
                         "identity": {
                             "type": "string",
                             "description": "Identity name (default: \"default\")"
+                        },
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Identity ID (aid_...) — no identity file needed"
+                        },
+                        "spawn_id": {
+                            "type": "string",
+                            "description": "Spawn record ID — inspects the record's child"
                         }
                     }
                 }
@@ -1088,6 +2405,40 @@ impl McpServer {
                         "identity": {
                             "type": "string",
                             "description": "Identity name (default: \"default\")"
+                        },
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Identity ID (aid_...) — no identity file needed"
+                        },
+                        "spawn_id": {
+                            "type": "string",
+                            "description": "Spawn record ID — inspects the record's child"
+                        }
+                    }
+                }
+            },
+            {
+                "name": "spawn_tree",
+                "description": "Show the spawn hierarchy as a tree, with each identity's effective authority and status",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "format": {
+                            "type": "string",
+                            "enum": ["text", "json", "mermaid"],
+                            "description": "Output format (default: text)"
+                        },
+                        "identity": {
+                            "type": "string",
+                            "description": "Only the tree below this identity name (default: every lineage)"
+                        },
+                        "identity_id": {
+                            "type": "string",
+                            "description": "Only the tree below this identity ID (aid_...)"
+                        },
+                        "spawn_id": {
+                            "type": "string",
+                            "description": "Only the tree below this spawn record's child"
                         }
                     }
                 }
@@ -1109,17 +2460,18 @@ impl McpServer {
             },
             {
                 "name": "competence_show",
-                "description": "Get competence record for a domain",
+                "description": "Get competence record for a domain, computed from recorded attempts",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
-                        "domain": { "type": "string", "description": "Competence domain" }
+                        "domain": { "type": "string", "description": "Competence domain (omit to list every domain)" },
+                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" }
                     }
                 }
             },
             {
                 "name": "competence_prove",
-                "description": "Generate a competence proof for a domain",
+                "description": "Generate a competence proof for a domain from recorded attempts",
                 "inputSchema": {
                     "type": "object",
                     "properties": {
@@ -1146,7 +2498,9 @@ impl McpServer {
                 "description": "List all competence domains for the identity",
                 "inputSchema": {
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" }
+                    }
                 }
             },
             {
@@ -1189,7 +2543,9 @@ impl McpServer {
                 "description": "List all negative declarations for the identity",
                 "inputSchema": {
                     "type": "object",
-                    "properties": {}
+                    "properties": {
+                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" }
+                    }
                 }
             },
             {
@@ -1261,16 +2617,60 @@ impl McpServer {
                     }
                 }
             },
-            // ── V2: Grounding (anti-hallucination) ─────────────────────────
             {
-                "name": "identity_ground",
+                "name": "session_trace",
+                "description": "Per-tool timings and outcomes collected this session (requires --trace)",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "limit": { "type": "integer", "description": "Return only the most recent N entries" }
+                    }
+                }
+            },
+            {
+                "name": "operation_log_export",
+                "description": "Export this session's operation log, including logged intents, as a session log signed by an identity",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "identity": { "type": "string", "description": "Identity name to sign with (default: \"default\")" },
+                        "include_summaries": { "type": "boolean", "description": "Keep argument summaries, which may contain sensitive data", "default": false }
+                    }
+                }
+            },
+            {
+                "name": "audit_list",
+                "description": "List persisted audit sessions, newest first, with record counts and seal status",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": {
+                        "limit": { "type": "integer", "description": "Maximum number of sessions", "default": 20 }
+                    }
+                }
+            },
+            {
+                "name": "audit_show",
+                "description": "Show an audit session's records and check its transcript against the signed seal",
+                "inputSchema": {
+                    "type": "object",
+                    "required": ["session_id"],
+                    "properties": {
+                        "session_id": { "type": "string", "description": "Session ID from audit_list" },
+                        "limit": { "type": "integer", "description": "Return only the most recent N records" }
+                    }
+                }
+            },
+            // ── V2: Grounding (anti-hallucination) ─────────────────────────
+            {
+                "name": "identity_ground",
                 "description": "Verify an authority/action claim has backing in trust grants, receipts, or competence records. Prevents hallucination about permissions",
                 "inputSchema": {
                     "type": "object",
                     "required": ["claim"],
                     "properties": {
                         "claim": { "type": "string", "description": "The claim to verify (e.g., 'agent has deploy permission')" },
-                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" }
+                        "identity": { "type": "string", "description": "Only use evidence involving this identity name (default: all identities)" },
+                        "actor": { "type": "string", "description": "Only use evidence involving this identity ID (aid_...)" }
                     }
                 }
             },
@@ -1282,7 +2682,8 @@ impl McpServer {
                     "required": ["query"],
                     "properties": {
                         "query": { "type": "string", "description": "The query to search evidence for" },
-                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" },
+                        "identity": { "type": "string", "description": "Only use evidence involving this identity name (default: all identities)" },
+                        "actor": { "type": "string", "description": "Only use evidence involving this identity ID (aid_...)" },
                         "max_results": { "type": "integer", "default": 10 }
                     }
                 }
@@ -1295,7 +2696,8 @@ impl McpServer {
                     "required": ["query"],
                     "properties": {
                         "query": { "type": "string", "description": "The query to find suggestions for" },
-                        "identity": { "type": "string", "description": "Identity name (default: \"default\")" },
+                        "identity": { "type": "string", "description": "Only suggest from records involving this identity name (default: all identities)" },
+                        "actor": { "type": "string", "description": "Only suggest from records involving this identity ID (aid_...)" },
                         "limit": { "type": "integer", "default": 5 }
                     }
                 }
@@ -1382,6 +2784,24 @@ impl McpServer {
             arr.extend(invention_accountability::all_definitions());
             arr.extend(invention_federation::all_definitions());
             arr.extend(invention_resilience::all_definitions());
+
+            for tool in arr.iter_mut() {
+                let is_write = tool
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .is_some_and(|n| idempotency::IDEMPOTENT_TOOLS.contains(&n));
+                if let (true, Some(Value::Object(props))) =
+                    (is_write, tool.pointer_mut("/inputSchema/properties"))
+                {
+                    props.insert(
+                        "idempotency_key".to_string(),
+                        json!({
+                            "type": "string",
+                            "description": "Caller-chosen key; retries with the same key and arguments return the original result instead of writing again"
+                        }),
+                    );
+                }
+            }
         }
         ok_result(id, json!({ "tools": tools_list }))
     }
@@ -1399,31 +2819,85 @@ impl McpServer {
             Err(message) => return rpc_error(id, -32602, message),
         };
 
+        // Replay or reject retried writes before doing any work.
+        let idempotency = match args.get("idempotency_key").and_then(|v| v.as_str()) {
+            Some(key) if idempotency::IDEMPOTENT_TOOLS.contains(&tool_name.as_str()) => {
+                if let Err(message) = idempotency::validate_key(key) {
+                    return tool_error(id, message);
+                }
+                let store = idempotency::IdempotencyStore::new(
+                    &self.idempotency_dir,
+                    read_env_u64_any(
+                        &["AID_IDEMPOTENCY_TTL_SECS", "IDEMPOTENCY_TTL_SECS"],
+                        idempotency::DEFAULT_TTL_SECS,
+                    ),
+                );
+                let fingerprint = idempotency::fingerprint(&tool_name, &args);
+                match store.lookup(key, &fingerprint, now_secs()) {
+                    idempotency::Replay::Completed(result) => return ok_result(id, result),
+                    idempotency::Replay::Conflict {
+                        tool_name: original,
+                    } => {
+                        return tool_error(
+                            id,
+                            format!(
+                                "idempotency key '{key}' was already used for a different \
+                                 {original} call; choose a new key for a new operation"
+                            ),
+                        )
+                    }
+                    idempotency::Replay::Fresh => Some((store, key.to_string(), fingerprint)),
+                }
+            }
+            _ => None,
+        };
+
         // Handle action_context separately (it mutates operation_log directly).
         if tool_name == "action_context" {
             return self.tool_action_context(id, &args);
         }
 
+        let started = std::time::Instant::now();
         let result = match tool_name.as_str() {
             "identity_create" => self.tool_identity_create(id.clone(), &args),
+            "identity_create_batch" => self.tool_identity_create_batch(id.clone(), &args),
             "identity_show" => self.tool_identity_show(id.clone(), &args),
+            "identity_register_key" => self.tool_identity_register_key(id.clone(), &args),
+            "artifact_verify" => self.tool_artifact_verify(id.clone(), &args),
             "action_sign" => self.tool_action_sign(id.clone(), &args),
+            "action_check" => self.tool_action_check(id.clone(), &args),
             "receipt_verify" => self.tool_receipt_verify(id.clone(), &args),
+            "receipt_request_witness" => self.tool_receipt_request_witness(id.clone(), &args),
+            "receipt_add_witness" => self.tool_receipt_add_witness(id.clone(), &args),
+            "receipt_witness" => self.tool_receipt_witness(id.clone(), &args),
             "trust_grant" => self.tool_trust_grant(id.clone(), &args),
             "trust_revoke" => self.tool_trust_revoke(id.clone(), &args),
+            "trust_revoke_simulate" => self.tool_trust_revoke_simulate(id.clone(), &args),
             "trust_verify" => self.tool_trust_verify(id.clone(), &args),
+            "trust_use" => self.tool_trust_use(id.clone(), &args),
             "trust_list" => self.tool_trust_list(id.clone(), &args),
+            "identity_authority_diff" => self.tool_identity_authority_diff(id.clone(), &args),
+            "capability_holders" => self.tool_capability_holders(id.clone(), &args),
+            "trust_path" => self.tool_trust_path(id.clone(), &args),
             "receipt_list" => self.tool_receipt_list(id.clone(), &args),
+            "receipt_query" => self.tool_receipt_query(id.clone(), &args),
+            "receipt_graph" => self.tool_receipt_graph(id.clone(), &args),
+            "receipt_export" => self.tool_receipt_export(id.clone(), &args),
             "identity_health" => self.tool_identity_health(id.clone(), &args),
+            "identity_rekey_stores" => self.tool_identity_rekey_stores(id.clone(), &args),
+            "identity_change_passphrase" => self.tool_identity_change_passphrase(id.clone(), &args),
+            "identity_rotate" => self.tool_identity_rotate(id.clone(), &args),
             "continuity_record" => self.tool_continuity_record(id.clone(), &args),
             "continuity_anchor" => self.tool_continuity_anchor(id.clone(), &args),
             "continuity_heartbeat" => self.tool_continuity_heartbeat(id.clone(), &args),
             "continuity_status" => self.tool_continuity_status(id.clone(), &args),
             "continuity_gaps" => self.tool_continuity_gaps(id.clone(), &args),
+            "continuity_confidence" => self.tool_continuity_confidence(id.clone(), &args),
             "spawn_create" => self.tool_spawn_create(id.clone(), &args),
             "spawn_terminate" => self.tool_spawn_terminate(id.clone(), &args),
             "spawn_list" => self.tool_spawn_list(id.clone(), &args),
             "spawn_lineage" => self.tool_spawn_lineage(id.clone(), &args),
+            "spawn_tree" => self.tool_spawn_tree(id.clone(), &args),
             "spawn_authority" => self.tool_spawn_authority(id.clone(), &args),
             "competence_record" => self.tool_competence_record(id.clone(), &args),
             "competence_show" => self.tool_competence_show(id.clone(), &args),
@@ -1438,6 +2912,10 @@ impl McpServer {
             "session_start" => self.tool_session_start(id.clone(), &args),
             "session_end" => self.tool_session_end(id.clone(), &args),
             "identity_session_resume" => self.tool_identity_session_resume(id.clone(), &args),
+            "session_trace" => self.tool_session_trace(id.clone(), &args),
+            "operation_log_export" => self.tool_operation_log_export(id.clone(), &args),
+            "audit_list" => self.tool_audit_list(id.clone(), &args),
+            "audit_show" => self.tool_audit_show(id.clone(), &args),
             // V2: Grounding
             "identity_ground" => self.tool_identity_ground(id.clone(), &args),
             "identity_evidence" => self.tool_identity_evidence(id.clone(), &args),
@@ -1468,15 +2946,23 @@ impl McpServer {
                 {
                     r
                 } else {
-                    return rpc_error(id, -32803, format!("Tool not found: {tool_name}"));
+                    rpc_error(id.clone(), -32803, format!("Tool not found: {tool_name}"))
                 }
             }
         };
 
+        // Timings are taken for every outcome so slow failures are visible too.
+        let timing = self.trace.then(|| {
+            let elapsed_micros = started.elapsed().as_micros() as u64;
+            let outcome = tool_outcome(&result);
+            emit_trace_line(&tool_name, outcome, elapsed_micros);
+            (elapsed_micros, outcome)
+        });
+
         // Auto-log the tool call.
         let capture_mode = read_env_string_any(&["AID_AUTO_CAPTURE_MODE", "AUTO_CAPTURE_MODE"])
             .unwrap_or_else(|| "summary".to_string());
-        if !capture_mode.eq_ignore_ascii_case("off") {
+        if self.trace || !capture_mode.eq_ignore_ascii_case("off") {
             let summary =
                 if read_env_bool_any(&["AID_AUTO_CAPTURE_REDACT", "AUTO_CAPTURE_REDACT"], true) {
                     "<redacted>".to_string()
@@ -1488,15 +2974,33 @@ impl McpServer {
                     .max(64);
                     truncate_text(args.to_string(), max_chars)
                 };
-            self.operation_log.push(IdentityOperationRecord {
-                tool_name,
+            self.push_operation(IdentityOperationRecord {
+                tool_name: tool_name.clone(),
                 intent: None,
                 summary,
                 timestamp: now_secs(),
+                elapsed_micros: timing.map(|(elapsed, _)| elapsed),
+                outcome: timing.map(|(_, outcome)| outcome),
             });
         }
+        if tool_name == "session_end" {
+            self.seal_audit_session();
+        }
         self.maybe_emit_storage_budget_warning();
 
+        // Only successful writes are remembered, so a failed call can be retried.
+        if let Some((store, key, fingerprint)) = idempotency {
+            if tool_outcome(&result) == "ok" {
+                if let Some(tool_result) = result.get("result") {
+                    if let Err(e) =
+                        store.record(&key, &tool_name, &fingerprint, tool_result, now_secs())
+                    {
+                        tracing::warn!("failed to persist idempotency key '{key}': {e}");
+                    }
+                }
+            }
+        }
+
         result
     }
 
@@ -1510,6 +3014,7 @@ impl McpServer {
             .unwrap_or(now);
         let metadata = args.get("metadata").cloned().unwrap_or_else(|| json!({}));
 
+        self.seal_audit_session();
         self.session_start_time = Some(now);
         self.operation_log.clear();
 
@@ -1532,6 +3037,8 @@ impl McpServer {
             .unwrap_or(now);
         let started_at = self.session_start_time.take();
         let duration_seconds = started_at.map(|start| now.saturating_sub(start));
+        // Sealed once this call is logged, so it ends the transcript.
+        let audit_session = self.audit.current().map(|session| session.id.clone());
 
         tool_ok(
             id,
@@ -1541,6 +3048,7 @@ impl McpServer {
                 "started_at": started_at,
                 "duration_seconds": duration_seconds,
                 "operation_count": self.operation_log.len(),
+                "audit_session": audit_session,
             }))
             .unwrap_or_default(),
         )
@@ -1574,6 +3082,188 @@ impl McpServer {
         )
     }
 
+    fn tool_session_trace(&self, id: Value, args: &Value) -> Value {
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|l| l as usize);
+        let traced: Vec<&IdentityOperationRecord> = self
+            .operation_log
+            .iter()
+            .filter(|record| record.elapsed_micros.is_some())
+            .collect();
+        let skip = limit.map_or(0, |l| traced.len().saturating_sub(l));
+        let total_micros: u64 = traced.iter().filter_map(|r| r.elapsed_micros).sum();
+        let errors = traced
+            .iter()
+            .filter(|r| r.outcome.is_some_and(|o| o != "ok"))
+            .count();
+        let slowest = traced
+            .iter()
+            .max_by_key(|r| r.elapsed_micros)
+            .map(|r| json!({ "tool_name": r.tool_name, "elapsed_micros": r.elapsed_micros }));
+        let entries: Vec<Value> = traced
+            .iter()
+            .skip(skip)
+            .map(|record| {
+                json!({
+                    "tool_name": record.tool_name,
+                    "outcome": record.outcome,
+                    "elapsed_micros": record.elapsed_micros,
+                    "timestamp": record.timestamp
+                })
+            })
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "trace_enabled": self.trace,
+                "capacity": self.operation_log_capacity,
+                "call_count": traced.len(),
+                "error_count": errors,
+                "total_micros": total_micros,
+                "slowest": slowest,
+                "entries": entries
+            }))
+            .unwrap_or_default(),
+        )
+    }
+
+    fn tool_operation_log_export(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+        let include_summaries = args
+            .get("include_summaries")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let anchor = match self.load_anchor(name) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
+        };
+
+        let entries = self
+            .operation_log
+            .iter()
+            .map(|record| SessionLogEntry {
+                tool_name: record.tool_name.clone(),
+                intent: record.intent.clone(),
+                summary: Some(record.summary.clone()),
+                timestamp: record.timestamp,
+                outcome: record.outcome.map(str::to_string),
+            })
+            .collect();
+        match SessionLog::sign(
+            &anchor,
+            self.session_start_time,
+            entries,
+            !include_summaries,
+        ) {
+            Ok(log) => tool_ok(id, serde_json::to_string_pretty(&log).unwrap_or_default()),
+            Err(e) => tool_error(id, format!("failed to sign session log: {e}")),
+        }
+    }
+
+    // ── Tool: audit_list / audit_show ─────────────────────────────────────────
+
+    fn tool_audit_list(&self, id: Value, args: &Value) -> Value {
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let sessions = match self.audit.list() {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to list audit sessions: {e}")),
+        };
+        let current = self.audit.current().map(|session| session.id.as_str());
+        let values: Vec<Value> = sessions
+            .iter()
+            .take(limit.max(1))
+            .map(|session| {
+                json!({
+                    "session_id": session.session_id,
+                    "started_at": session.started_at,
+                    "record_count": session.record_count,
+                    "current": current == Some(session.session_id.as_str()),
+                    "sealed": session.seal.is_some(),
+                    "sealed_at": session.seal.as_ref().map(|s| s.sealed_at),
+                    "receipt_id": session.seal.as_ref().and_then(|s| s.receipt_id.as_ref()),
+                })
+            })
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "enabled": self.audit.enabled(),
+                "total": sessions.len(),
+                "sessions": values,
+            }))
+            .unwrap_or_default(),
+        )
+    }
+
+    fn tool_audit_show(&self, id: Value, args: &Value) -> Value {
+        let session_id = match args.get("session_id").and_then(|v| v.as_str()) {
+            Some(s) if !s.trim().is_empty() => s,
+            _ => return tool_error(id, "'session_id' is required"),
+        };
+        let limit = args
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|l| l as usize);
+
+        let entries = match self.audit.entries(session_id) {
+            Ok(e) => e,
+            Err(e) => {
+                return tool_error(
+                    id,
+                    format!("failed to read audit session '{session_id}': {e}"),
+                )
+            }
+        };
+        let seal = match self.audit.seal(session_id) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to read audit seal: {e}")),
+        };
+
+        // An unsealed session has nothing to check against yet.
+        let digest_matches = seal.as_ref().map(|seal| {
+            self.audit
+                .digest(session_id)
+                .is_ok_and(|digest| digest == seal.digest)
+        });
+        let receipt_valid = seal
+            .as_ref()
+            .and_then(|seal| seal.receipt_id.as_ref().map(|rid| (seal, rid)))
+            .map(|(seal, rid)| {
+                ReceiptStore::new(&self.receipt_dir)
+                    .and_then(|store| store.load(&ReceiptId(rid.clone())))
+                    .is_ok_and(|receipt| {
+                        let data = receipt.action.data.as_ref();
+                        verify_receipt(&receipt).is_ok_and(|v| v.is_valid)
+                            && receipt.action_type.as_tag() == AUDIT_SEAL_ACTION
+                            && data.and_then(|d| d.get("digest")) == Some(&json!(seal.digest))
+                            && data.and_then(|d| d.get("session_id")) == Some(&json!(session_id))
+                    })
+            });
+
+        let skip = limit.map_or(0, |l| entries.len().saturating_sub(l));
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "session_id": session_id,
+                "record_count": entries.len(),
+                "records": &entries[skip..],
+                "sealed": seal.is_some(),
+                "seal": seal,
+                "digest_matches": digest_matches,
+                "receipt_valid": receipt_valid,
+            }))
+            .unwrap_or_default(),
+        )
+    }
+
     // ── Tool: action_context ───────────────────────────────────────────────────
 
     fn tool_action_context(&mut self, id: Value, args: &Value) -> Value {
@@ -1602,10 +3292,15 @@ impl McpServer {
             intent: Some(intent),
             summary: summary_parts.join(" | "),
             timestamp: now_secs(),
+            elapsed_micros: None,
+            outcome: None,
         };
 
-        let index = self.operation_log.len();
-        self.operation_log.push(record);
+        let index = self
+            .operation_log
+            .len()
+            .min(self.operation_log_capacity - 1);
+        self.push_operation(record);
 
         tool_ok(
             id,
@@ -1617,6 +3312,80 @@ impl McpServer {
         )
     }
 
+    // ── Tool: action_check ────────────────────────────────────────────────────
+
+    /// Read-only: compares required capabilities with effective authority.
+    fn tool_action_check(&self, id: Value, args: &Value) -> Value {
+        let description = match args.get("action").and_then(|v| v.as_str()) {
+            Some(a) if !a.trim().is_empty() => a,
+            _ => return tool_error(id, "'action' is required and must not be empty"),
+        };
+        let action_type = parse_action_type(
+            args.get("action_type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("decision"),
+        );
+
+        let required = self
+            .action_requirements
+            .required_capabilities(&ActionContent::new(description), &action_type);
+        if required.is_empty() {
+            return tool_ok(
+                id,
+                format!(
+                    "Action check ({}): no capability required\n  Result: OK",
+                    action_type.as_tag()
+                ),
+            );
+        }
+
+        let records = SpawnStore::new(&self.spawn_dir)
+            .ok()
+            .and_then(|s| s.load_all().ok())
+            .unwrap_or_default();
+        let (label, identity_id) = match self.spawn_subject(args, &records) {
+            Ok(subject) => subject,
+            Err(e) => return tool_error(id, e),
+        };
+        let authority = match agentic_identity::spawn::authority_for(&identity_id, &records) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to compute authority: {e}")),
+        };
+
+        let (held, missing): (Vec<&Capability>, Vec<&Capability>) =
+            required.iter().partition(|cap| {
+                agentic_identity::trust::capabilities_cover(
+                    &authority.effective_authority,
+                    &cap.uri,
+                )
+            });
+        let join = |caps: &[&Capability]| {
+            if caps.is_empty() {
+                "(none)".to_string()
+            } else {
+                caps.iter()
+                    .map(|c| c.uri.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+
+        let out = format!(
+            "Action check for identity '{}' ({})\n  Required: {}\n  Held:     {}\n  Missing:  {}\n  Result: {}",
+            label,
+            action_type.as_tag(),
+            join(&required.iter().collect::<Vec<_>>()),
+            join(&held),
+            join(&missing),
+            if missing.is_empty() {
+                "OK"
+            } else {
+                "MISSING CAPABILITIES — do not sign"
+            }
+        );
+        tool_ok(id, out)
+    }
+
     fn maybe_emit_storage_budget_warning(&self) {
         let mode = read_env_string_any(&["AID_STORAGE_BUDGET_MODE", "STORAGE_BUDGET_MODE"])
             .unwrap_or_else(|| "auto-rollup".to_string());
@@ -1657,6 +3426,33 @@ impl McpServer {
         }
     }
 
+    /// Resolve the `identity` (local name) and `actor` (`aid_...`) scoping
+    /// arguments shared by the read tools.
+    ///
+    /// The identity name is resolved through the plaintext public document, so
+    /// no passphrase is needed. A name with no `.aid` file, or an `identity`
+    /// and `actor` pair that disagree, yields a scope that matches nothing.
+    /// A malformed `actor` is an error rather than an empty scope.
+    fn actor_scope(&self, args: &Value) -> std::result::Result<ActorScope, String> {
+        let actor = match args.get("actor").and_then(|v| v.as_str()) {
+            Some(s) => Some(IdentityId::parse(s).map_err(|e| format!("invalid actor: {e}"))?),
+            None => None,
+        };
+        let named = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .map(|name| self.read_document(name).ok().map(|doc| doc.id));
+
+        Ok(match (named, actor) {
+            (None, None) => ActorScope::All,
+            (None, Some(actor)) => ActorScope::Only(Some(actor)),
+            (Some(resolved), None) => ActorScope::Only(resolved),
+            (Some(resolved), Some(actor)) => {
+                ActorScope::Only(resolved.filter(|resolved| *resolved == actor))
+            }
+        })
+    }
+
     // ── Tool: identity_create ─────────────────────────────────────────────────
 
     fn tool_identity_create(&self, id: Value, args: &Value) -> Value {
@@ -1666,33 +3462,33 @@ impl McpServer {
             .unwrap_or(DEFAULT_IDENTITY)
             .to_string();
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-
-        if path.exists() {
+        if self.identity_exists(&name) {
             return tool_error(
                 id,
                 format!("identity '{name}' already exists — use identity_show to inspect it"),
             );
         }
 
-        if let Err(e) = std::fs::create_dir_all(&self.identity_dir) {
-            return tool_error(id, format!("failed to create identity directory: {e}"));
-        }
-
-        let anchor = IdentityAnchor::new(Some(name.clone()));
+        let anchor = match IdentityAnchor::try_new(Some(name.clone())) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
         let identity_id = anchor.id();
         let pub_key = anchor.public_key_base64();
+        let algorithm = anchor.algorithm().summary();
         let created_at = anchor.created_at;
 
-        if let Err(e) = save_identity(&anchor, &path, MCP_PASSPHRASE) {
+        if let Err(e) = self.store_anchor(&name, anchor) {
             return tool_error(id, format!("failed to save identity: {e}"));
         }
+        let path = self.identity_location(&name);
 
         tool_ok(
             id,
             format!(
                 "Created identity '{name}'\n\
                  ID:         {identity_id}\n\
+                 Algorithm:  {algorithm}\n\
                  Public Key: {pub_key}\n\
                  Created:    {}\n\
                  File:       {}",
@@ -1702,6 +3498,82 @@ impl McpServer {
         )
     }
 
+    // ── Tool: identity_create_batch ───────────────────────────────────────────
+
+    fn tool_identity_create_batch(&self, id: Value, args: &Value) -> Value {
+        let Some(requested) = args.get("names").and_then(|v| v.as_array()) else {
+            return tool_error(id, "required parameter 'names' is missing");
+        };
+        let mut seen = HashSet::new();
+        let names: Vec<&str> = requested
+            .iter()
+            .filter_map(|v| v.as_str())
+            .filter(|name| seen.insert(*name))
+            .collect();
+        if names.is_empty() {
+            return tool_error(id, "names must list at least one identity name");
+        }
+
+        let mut manifest = Vec::new();
+        let mut results = Vec::new();
+        let (mut created, mut skipped, mut failed) = (0, 0, 0);
+        for name in names {
+            // An existing identity is left alone, so re-running a batch resumes it.
+            let existing = self.identity_exists(name);
+            let document = if existing {
+                self.read_document(name)
+            } else {
+                IdentityAnchor::try_new(Some(name.to_string())).and_then(|anchor| {
+                    let document = anchor.to_document();
+                    self.store_anchor(name, anchor)?;
+                    Ok(document)
+                })
+            };
+            let entry = document.and_then(|doc| {
+                Ok(json!({
+                    "name": name,
+                    "id": doc.id.0,
+                    "public_key": doc.public_key,
+                    "fingerprint": doc.key_fingerprint()?,
+                }))
+            });
+
+            match entry {
+                Ok(entry) => {
+                    let status = if existing {
+                        skipped += 1;
+                        "skipped"
+                    } else {
+                        created += 1;
+                        "created"
+                    };
+                    results.push(json!({ "name": name, "status": status }));
+                    manifest.push(entry);
+                }
+                Err(e) => {
+                    failed += 1;
+                    results.push(json!({
+                        "name": name,
+                        "status": "failed",
+                        "error": e.to_string(),
+                    }));
+                }
+            }
+        }
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "created": created,
+                "skipped": skipped,
+                "failed": failed,
+                "results": results,
+                "manifest": manifest,
+            }))
+            .unwrap(),
+        )
+    }
+
     // ── Tool: identity_show ───────────────────────────────────────────────────
 
     fn tool_identity_show(&self, id: Value, args: &Value) -> Value {
@@ -1710,25 +3582,19 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or(DEFAULT_IDENTITY);
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-
-        if !path.exists() {
+        if !self.identity_exists(name) {
             return tool_error(
                 id,
                 format!("identity '{name}' not found — use identity_create to create it"),
             );
         }
 
-        let doc = match read_public_document(&path) {
+        let doc = match self.read_document(name) {
             Ok(d) => d,
             Err(e) => return tool_error(id, format!("failed to read identity file: {e}")),
         };
 
-        let sig_status = if doc.verify_signature().is_ok() {
-            "valid"
-        } else {
-            "INVALID"
-        };
+        let signature = VerificationOutcome::signature(doc.verify_signature().is_ok());
 
         let mut out = format!(
             "Identity: {name}\n\
@@ -1738,10 +3604,10 @@ impl McpServer {
              Created:    {}\n\
              Signature:  {}",
             doc.id,
-            doc.algorithm,
+            doc.algorithm_summary(),
             doc.public_key,
             micros_to_rfc3339(doc.created_at),
-            sig_status,
+            self.verification_messages.render(signature),
         );
 
         if let Some(ref n) = doc.name {
@@ -1766,44 +3632,244 @@ impl McpServer {
             out.push_str(&format!("\nAttestations: {}", doc.attestations.len()));
         }
 
-        tool_ok(id, out)
+        tool_ok_with_outcomes(id, out, json!({"signature": signature.code()}))
     }
 
-    // ── Tool: action_sign ─────────────────────────────────────────────────────
-
-    fn tool_action_sign(&self, id: Value, args: &Value) -> Value {
-        let action_desc = match args.get("action").and_then(|v| v.as_str()) {
-            Some(a) => a.to_string(),
-            None => return tool_error(id, "required parameter 'action' is missing"),
-        };
+    // ── Tool: identity_register_key ───────────────────────────────────────────
 
-        let identity_name = args
-            .get("identity")
+    fn tool_identity_register_key(&self, id: Value, args: &Value) -> Value {
+        let label = args
+            .get("label")
             .and_then(|v| v.as_str())
-            .unwrap_or(DEFAULT_IDENTITY);
+            .map(str::to_string);
 
-        let path = self.identity_dir.join(format!("{identity_name}.aid"));
+        let directory = match KeyDirectory::new(&self.key_dir) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to open key directory: {e}")),
+        };
 
-        if !path.exists() {
-            return tool_error(
+        let registered = match (args.get("public_key"), args.get("document")) {
+            (Some(key), None) => match key.as_str() {
+                Some(key) => directory.register_key(key, label),
+                None => return tool_error(id, "'public_key' must be a string"),
+            },
+            (None, Some(doc)) => {
+                match serde_json::from_value::<agentic_identity::identity::IdentityDocument>(
+                    doc.clone(),
+                ) {
+                    Ok(doc) => directory.register_document(&doc, label),
+                    Err(e) => return tool_error(id, format!("invalid identity document: {e}")),
+                }
+            }
+            _ => return tool_error(id, "exactly one of 'public_key' or 'document' is required"),
+        };
+
+        match registered {
+            Ok(entry) => tool_ok(
                 id,
-                format!("identity '{identity_name}' not found — use identity_create first"),
-            );
+                format!(
+                    "Key registered\n\
+                     Identity:   {}\n\
+                     Public Key: {}\n\
+                     Label:      {}",
+                    entry.identity,
+                    entry.public_key,
+                    entry.label.as_deref().unwrap_or("(none)"),
+                ),
+            ),
+            Err(e) => tool_error(id, format!("failed to register key: {e}")),
         }
+    }
 
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
-            Ok(a) => a,
-            Err(e) => {
-                return tool_error(
-                    id,
-                    format!(
-                        "failed to load identity '{identity_name}': {e}. \
-                         Note: MCP server uses passphrase 'agentic'. \
-                         If this identity was created with a different passphrase, \
-                         use 'identity_create' to create a new MCP-compatible identity."
-                    ),
+    /// The public key of a trust grantee: the one in the key directory, else
+    /// that of a local identity with the grantee's ID.
+    fn resolve_grantee_key(&self, grantee: &IdentityId) -> Result<String, String> {
+        let registered = KeyDirectory::new(&self.key_dir).and_then(|d| d.resolve(grantee));
+        match registered {
+            Ok(key) => return Ok(key),
+            Err(agentic_identity::IdentityError::NotFound(_)) => {}
+            Err(e) => return Err(format!("failed to look up key of grantee '{grantee}': {e}")),
+        }
+
+        self.identity_documents()
+            .into_iter()
+            .map(|(_, doc)| doc)
+            .find(|doc| &doc.id == grantee && doc.verify_signature().is_ok())
+            .map(|doc| doc.public_key)
+            .ok_or_else(|| {
+                format!(
+                    "no public key known for grantee '{grantee}' — register it with \
+                     identity_register_key or pass grantee_key"
                 )
-            }
+            })
+    }
+
+    // ── Tool: artifact_verify ─────────────────────────────────────────────────
+
+    fn tool_artifact_verify(&self, id: Value, args: &Value) -> Value {
+        let uri = match args.get("uri").and_then(|v| v.as_str()) {
+            Some(u) => u,
+            None => return tool_error(id, "required parameter 'uri' is missing"),
+        };
+
+        let verified = if let Some(name) = uri.strip_prefix("aid://identity/") {
+            self.verify_identity_artifact(name)
+        } else if let Some(receipt_id) = uri.strip_prefix("aid://receipt/") {
+            self.verify_receipt_artifact(receipt_id)
+        } else if let Some(trust_id) = uri.strip_prefix("aid://trust/") {
+            self.verify_trust_artifact(trust_id)
+        } else {
+            Err(format!(
+                "unknown artifact URI '{uri}' — expected aid://identity/<name>, \
+                 aid://receipt/<receipt_id> or aid://trust/<trust_id>"
+            ))
+        };
+
+        match verified {
+            Ok((kind, artifact_id, reasons)) => tool_ok(
+                id,
+                serde_json::to_string_pretty(&json!({
+                    "kind": kind,
+                    "id": artifact_id,
+                    "valid": reasons.is_empty(),
+                    "reasons": reasons,
+                }))
+                .unwrap(),
+            ),
+            Err(e) => tool_error(id, e),
+        }
+    }
+
+    /// Check an identity document's self-signature and rotation chain.
+    fn verify_identity_artifact(
+        &self,
+        name: &str,
+    ) -> Result<(&'static str, String, Vec<String>), String> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(format!("invalid identity name '{name}'"));
+        }
+        if !self.identity_exists(name) {
+            return Err(format!("identity '{name}' not found"));
+        }
+        let doc = self
+            .read_document(name)
+            .map_err(|e| format!("failed to read identity '{name}': {e}"))?;
+
+        let mut reasons = Vec::new();
+        if let Err(e) = doc.verify_signature() {
+            reasons.push(format!("self-signature invalid: {e}"));
+        }
+        if !verify_genesis(&doc) {
+            reasons.push("rotation chain does not lead back to the genesis key".to_string());
+        }
+        Ok(("identity", doc.id.0, reasons))
+    }
+
+    /// Check a stored receipt's signature and witnesses.
+    fn verify_receipt_artifact(
+        &self,
+        receipt_id: &str,
+    ) -> Result<(&'static str, String, Vec<String>), String> {
+        let receipt_id = ReceiptId::parse(receipt_id).map_err(|e| e.to_string())?;
+        // Opening a store creates its directory; never write for a lookup.
+        if !self.receipt_dir.is_dir() {
+            return Err(format!("receipt '{receipt_id}' not found"));
+        }
+        let receipt = ReceiptStore::new(&self.receipt_dir)
+            .and_then(|store| store.load(&receipt_id))
+            .map_err(|e| format!("receipt '{receipt_id}' not found: {e}"))?;
+        let verification =
+            verify_receipt(&receipt).map_err(|e| format!("verification error: {e}"))?;
+
+        let mut reasons = Vec::new();
+        if !verification.signature_valid {
+            reasons.push("signature invalid".to_string());
+        }
+        for (witness, valid) in receipt.witnesses.iter().zip(&verification.witnesses_valid) {
+            if !valid {
+                reasons.push(format!("witness {} signature invalid", witness.witness));
+            }
+        }
+        Ok(("receipt", receipt.id.0, reasons))
+    }
+
+    /// Check a stored trust grant's signature, validity window and
+    /// revocation status.
+    fn verify_trust_artifact(
+        &self,
+        trust_id: &str,
+    ) -> Result<(&'static str, String, Vec<String>), String> {
+        let trust_id = TrustId::parse(trust_id).map_err(|e| e.to_string())?;
+        if !self.trust_dir.is_dir() {
+            return Err(format!("trust grant '{trust_id}' not found"));
+        }
+        let store = TrustStore::new(&self.trust_dir)
+            .map_err(|e| format!("failed to open trust store: {e}"))?;
+        let grant = store
+            .load_grant(&trust_id)
+            .map_err(|e| format!("trust grant '{trust_id}' not found: {e}"))?;
+
+        let mut reasons = Vec::new();
+        let revocations = if store.is_revoked(&trust_id) {
+            match store.load_revocation(&trust_id) {
+                Ok(rev) => vec![rev],
+                Err(e) => {
+                    // A revocation we cannot read still revokes.
+                    reasons.push(format!("revoked (revocation record unreadable: {e})"));
+                    vec![]
+                }
+            }
+        } else {
+            vec![]
+        };
+
+        // The grant as a whole, not any one capability, is being verified.
+        let verification = verify_trust_grant(&grant, "*", 0, &revocations)
+            .map_err(|e| format!("verification error: {e}"))?;
+        if !verification.signature_valid {
+            reasons.push("signature invalid".to_string());
+        }
+        if !verification.time_valid {
+            reasons.push("outside its validity window".to_string());
+        }
+        if !verification.not_revoked {
+            reasons.push("revoked".to_string());
+        }
+        Ok(("trust", grant.id.0, reasons))
+    }
+
+    // ── Tool: action_sign ─────────────────────────────────────────────────────
+
+    fn tool_action_sign(&self, id: Value, args: &Value) -> Value {
+        let action_desc = match args.get("action").and_then(|v| v.as_str()) {
+            Some(a) => a.to_string(),
+            None => return tool_error(id, "required parameter 'action' is missing"),
+        };
+
+        let identity_name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+
+        if !self.identity_exists(identity_name) {
+            return tool_error(
+                id,
+                format!("identity '{identity_name}' not found — use identity_create first"),
+            );
+        }
+
+        let anchor = match self.load_anchor(identity_name) {
+            Ok(a) => a,
+            Err(e) => {
+                return tool_error(
+                    id,
+                    format!(
+                        "failed to load identity '{identity_name}': {e}. \
+                         If this identity was created with a different passphrase, \
+                         move it to the server's with 'identity_change_passphrase'."
+                    ),
+                )
+            }
         };
 
         let action_type_str = args
@@ -1818,23 +3884,102 @@ impl McpServer {
             ActionContent::new(action_desc.clone())
         };
 
+        let receipt_store = match ReceiptStore::new(&self.receipt_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
+        };
+
         let mut builder = ReceiptBuilder::new(anchor.id(), action_type.clone(), action_content);
 
         if let Some(prev_id_str) = args.get("chain_to").and_then(|v| v.as_str()) {
-            builder = builder.chain_to(ReceiptId(prev_id_str.to_string()));
+            let prev_id = match ReceiptId::parse(prev_id_str) {
+                Ok(prev_id) => prev_id,
+                Err(e) => return tool_error(id, format!("invalid chain_to '{prev_id_str}': {e}")),
+            };
+            // A typo would otherwise chain to nothing; `chain_force` is for
+            // predecessors that have not been imported to this machine yet.
+            let force = args
+                .get("chain_force")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            if !force {
+                match receipt_store.load(&prev_id) {
+                    Ok(prev) if prev.actor == anchor.id() => {}
+                    Ok(prev) => {
+                        return tool_error(
+                            id,
+                            format!(
+                                "chain_to receipt '{prev_id}' was signed by {}, not by identity '{identity_name}'",
+                                prev.actor
+                            ),
+                        )
+                    }
+                    Err(_) => {
+                        return tool_error(
+                            id,
+                            format!(
+                                "chain_to receipt '{prev_id}' not found — check the ID, or set chain_force to chain to a receipt stored elsewhere"
+                            ),
+                        )
+                    }
+                }
+            }
+            builder = builder.chain_to(prev_id);
+        }
+
+        // Explicit intent wins; otherwise carry the latest action_context intent.
+        let intent = args
+            .get("intent")
+            .and_then(|v| v.as_str())
+            .filter(|i| !i.trim().is_empty())
+            .map(str::to_string)
+            .or_else(|| {
+                self.operation_log
+                    .iter()
+                    .rev()
+                    .find_map(|record| record.intent.clone())
+            });
+        if let Some(intent) = intent {
+            builder = builder.intent(intent);
         }
 
-        let receipt = match builder.sign(anchor.signing_key()) {
+        let signed = if self.enforce_action_requirements {
+            let records = SpawnStore::new(&self.spawn_dir)
+                .ok()
+                .and_then(|s| s.load_all().ok())
+                .unwrap_or_default();
+            let authority = match agentic_identity::spawn::authority_for(&anchor.id(), &records) {
+                Ok(a) => a,
+                Err(e) => return tool_error(id, format!("failed to compute authority: {e}")),
+            };
+            builder
+                .requirements(self.action_requirements.clone())
+                .sign_guarded(anchor.signing_key(), &authority.effective_authority)
+        } else {
+            builder.sign(anchor.signing_key())
+        };
+        let receipt = match signed {
             Ok(r) => r,
             Err(e) => return tool_error(id, format!("failed to sign receipt: {e}")),
         };
 
-        let receipt_store = match ReceiptStore::new(&self.receipt_dir) {
-            Ok(s) => s,
-            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
-        };
+        let mut usage = vec![(BudgetResource::Receipts, 1)];
+        if let Some(cost) = args.get("cost").and_then(|v| v.as_u64()) {
+            usage.push((BudgetResource::Cost, cost));
+        }
 
-        if let Err(e) = receipt_store.save(&receipt) {
+        // The receipt and the budget it uses are saved together or not at all.
+        let mut txn = match Transaction::begin(&self.transaction_dir) {
+            Ok(t) => t,
+            Err(e) => return tool_error(id, format!("failed to start transaction: {e}")),
+        };
+        if let Err(e) = self.stage_spawn_budget(&mut txn, &anchor.id(), &usage) {
+            return tool_error(id, e);
+        }
+        if let Err(e) = receipt_store
+            .stage(&mut txn, &receipt)
+            .and_then(|()| txn.commit())
+        {
             return tool_error(id, format!("failed to save receipt: {e}"));
         }
 
@@ -1855,6 +4000,9 @@ impl McpServer {
         if let Some(ref prev) = receipt.previous_receipt {
             out.push_str(&format!("\nChained to: {prev}"));
         }
+        if let Some(ref intent) = receipt.intent {
+            out.push_str(&format!("\nIntent:    {intent}"));
+        }
 
         tool_ok(id, out)
     }
@@ -1872,27 +4020,37 @@ impl McpServer {
             Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
         };
 
-        let receipt_id = ReceiptId(receipt_id_str.clone());
+        let receipt_id = match ReceiptId::parse(&receipt_id_str) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
         let receipt = match store.load(&receipt_id) {
             Ok(r) => r,
             Err(e) => return tool_error(id, format!("receipt '{receipt_id_str}' not found: {e}")),
         };
 
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
+        if !scope.matches(&receipt.actor) {
+            return tool_error(
+                id,
+                format!(
+                    "receipt '{receipt_id_str}' was not signed by {}",
+                    scope.label()
+                ),
+            );
+        }
+
         let verification = match verify_receipt(&receipt) {
             Ok(v) => v,
             Err(e) => return tool_error(id, format!("verification error: {e}")),
         };
 
-        let result_str = if verification.is_valid {
-            "VALID"
-        } else {
-            "INVALID"
-        };
-        let sig_str = if verification.signature_valid {
-            "valid"
-        } else {
-            "INVALID"
-        };
+        let messages = &self.verification_messages;
+        let result = VerificationOutcome::result(verification.is_valid);
+        let signature = VerificationOutcome::signature(verification.signature_valid);
 
         let mut out = format!(
             "Receipt: {}\n\
@@ -1908,17 +4066,26 @@ impl McpServer {
             receipt.action_type.as_tag(),
             micros_to_rfc3339(receipt.timestamp),
             receipt.action.description,
-            sig_str,
-            result_str,
+            messages.render(signature),
+            messages.render(result),
         );
 
+        let mut witness_codes = Vec::new();
         if !receipt.witnesses.is_empty() {
             out.push_str(&format!("\nWitnesses ({}):", receipt.witnesses.len()));
-            for (i, valid) in verification.witnesses_valid.iter().enumerate() {
+            for (i, (witness, valid)) in receipt
+                .witnesses
+                .iter()
+                .zip(&verification.witnesses_valid)
+                .enumerate()
+            {
+                let outcome = VerificationOutcome::witness(*valid);
+                witness_codes.push(outcome.code());
                 out.push_str(&format!(
-                    "\n  [{}] {}",
+                    "\n  [{}] {} {}",
                     i + 1,
-                    if *valid { "valid" } else { "INVALID" }
+                    witness.witness,
+                    messages.render(outcome)
                 ));
             }
         }
@@ -1926,10 +4093,202 @@ impl McpServer {
         if let Some(ref prev) = receipt.previous_receipt {
             out.push_str(&format!("\nChained to: {prev}"));
         }
+        if let Some(ref intent) = receipt.intent {
+            out.push_str(&format!("\nIntent:    {intent}"));
+        }
+
+        tool_ok_with_outcomes(
+            id,
+            out,
+            json!({
+                "result": result.code(),
+                "signature": signature.code(),
+                "witnesses": witness_codes,
+            }),
+        )
+    }
+
+    // ── Tool: receipt_request_witness ─────────────────────────────────────────
+
+    fn tool_receipt_request_witness(&self, id: Value, args: &Value) -> Value {
+        let receipt = match self.load_receipt_arg(args) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, e),
+        };
+        let witness = match args.get("witness_id").and_then(|v| v.as_str()) {
+            Some(w) => match IdentityId::parse(w) {
+                Ok(w) => Some(w),
+                Err(e) => return tool_error(id, e.to_string()),
+            },
+            None => None,
+        };
+
+        let witnessed_at = agentic_identity::time::now_micros();
+        let message = match &witness {
+            Some(w) => witness_signing_input(w, &receipt.receipt_hash, witnessed_at),
+            None => format!(
+                "{}:witness:<witness_id>:{}:{witnessed_at}",
+                SignatureDomain::Witness.tag(),
+                receipt.receipt_hash
+            ),
+        };
+
+        let out = format!(
+            "Witness request for receipt: {}\n  \
+             Receipt hash: {}\n  \
+             Witnesses:    {}\n\n\
+             Sign this UTF-8 message with the witness's Ed25519 key (base64 signature):\n  \
+             {}\n\n\
+             Then call receipt_add_witness with witnessed_at: {}",
+            receipt.id,
+            receipt.receipt_hash,
+            receipt.witnesses.len(),
+            message,
+            witnessed_at,
+        );
+        tool_ok(id, out)
+    }
+
+    // ── Tool: receipt_add_witness ─────────────────────────────────────────────
+
+    fn tool_receipt_add_witness(&self, id: Value, args: &Value) -> Value {
+        let mut receipt = match self.load_receipt_arg(args) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, e),
+        };
+
+        let str_arg = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| format!("required parameter '{name}' is missing"))
+        };
+        let (witness_id, witness_key, signature) = match (
+            str_arg("witness_id"),
+            str_arg("witness_key"),
+            str_arg("signature"),
+        ) {
+            (Ok(w), Ok(k), Ok(s)) => (w, k, s),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return tool_error(id, e),
+        };
+        let Some(witnessed_at) = args.get("witnessed_at").and_then(|v| v.as_u64()) else {
+            return tool_error(id, "required parameter 'witnessed_at' is missing");
+        };
+        let witness = match IdentityId::parse(&witness_id) {
+            Ok(w) => w,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
+
+        let ws = WitnessSignature {
+            witness,
+            witness_key,
+            witnessed_at,
+            signature,
+            signature_version: SIGNATURE_VERSION,
+        };
+        match ws.verifying_key() {
+            Ok(key) if IdentityId::from_verifying_key(&key) == ws.witness => {}
+            Ok(_) => {
+                return tool_error(
+                    id,
+                    format!("witness_key does not belong to witness '{}'", ws.witness),
+                )
+            }
+            Err(e) => return tool_error(id, format!("invalid witness_key: {e}")),
+        }
+        if let Err(e) = ws.verify(&receipt.receipt_hash) {
+            return tool_error(id, format!("witness signature rejected: {e}"));
+        }
+        if receipt.witnesses.iter().any(|w| w.witness == ws.witness) {
+            return tool_error(
+                id,
+                format!(
+                    "receipt '{}' is already witnessed by '{}'",
+                    receipt.id, ws.witness
+                ),
+            );
+        }
+
+        let witness_label = ws.witness.clone();
+        receipt.add_witness(ws);
+        let store = match ReceiptStore::new(&self.receipt_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
+        };
+        if let Err(e) = store.save(&receipt) {
+            return tool_error(id, format!("failed to save receipt: {e}"));
+        }
+
+        let out = format!(
+            "Witness added to receipt: {}\n  Witness:      {}\n  Witnessed at: {}\n  Witnesses:    {}",
+            receipt.id,
+            witness_label,
+            micros_to_rfc3339(witnessed_at),
+            receipt.witnesses.len(),
+        );
+        tool_ok(id, out)
+    }
+
+    // ── Tool: receipt_witness ─────────────────────────────────────────────────
+
+    fn tool_receipt_witness(&self, id: Value, args: &Value) -> Value {
+        let mut receipt = match self.load_receipt_arg(args) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, e),
+        };
+        let Some(witness_name) = args.get("witness").and_then(|v| v.as_str()) else {
+            return tool_error(id, "required parameter 'witness' is missing");
+        };
+
+        if !self.identity_exists(witness_name) {
+            return tool_error(
+                id,
+                format!("identity '{witness_name}' not found — use identity_create first"),
+            );
+        }
+        let anchor = match self.load_anchor(witness_name) {
+            Ok(a) => a,
+            Err(e) => {
+                return tool_error(id, format!("failed to load identity '{witness_name}': {e}"))
+            }
+        };
+
+        let witnessed_at = match receipt.sign_as_witness(&anchor) {
+            Ok(ws) => ws.witnessed_at,
+            Err(e) => return tool_error(id, format!("cannot witness receipt: {e}")),
+        };
+        let store = match ReceiptStore::new(&self.receipt_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
+        };
+        if let Err(e) = store.save(&receipt) {
+            return tool_error(id, format!("failed to save receipt: {e}"));
+        }
 
+        let out = format!(
+            "Witness added to receipt: {}\n  Witness:      {} ({witness_name})\n  Witnessed at: {}\n  Witnesses:    {}",
+            receipt.id,
+            anchor.id(),
+            micros_to_rfc3339(witnessed_at),
+            receipt.witnesses.len(),
+        );
         tool_ok(id, out)
     }
 
+    /// Load the receipt named by the `receipt_id` argument.
+    fn load_receipt_arg(&self, args: &Value) -> std::result::Result<ActionReceipt, String> {
+        let receipt_id_str = args
+            .get("receipt_id")
+            .and_then(|v| v.as_str())
+            .ok_or("required parameter 'receipt_id' is missing")?;
+        let receipt_id = ReceiptId::parse(receipt_id_str).map_err(|e| e.to_string())?;
+        let store = ReceiptStore::new(&self.receipt_dir)
+            .map_err(|e| format!("failed to open receipt store: {e}"))?;
+        store
+            .load(&receipt_id)
+            .map_err(|e| format!("receipt '{receipt_id_str}' not found: {e}"))
+    }
+
     // ── Tool: trust_grant ─────────────────────────────────────────────────────
 
     fn tool_trust_grant(&self, id: Value, args: &Value) -> Value {
@@ -1957,16 +4316,14 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or(DEFAULT_IDENTITY);
 
-        let path = self.identity_dir.join(format!("{identity_name}.aid"));
-
-        if !path.exists() {
+        if !self.identity_exists(identity_name) {
             return tool_error(
                 id,
                 format!("identity '{identity_name}' not found — use identity_create first"),
             );
         }
 
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(identity_name) {
             Ok(a) => a,
             Err(e) => {
                 return tool_error(
@@ -1977,14 +4334,32 @@ impl McpServer {
         };
 
         let grantee_id = IdentityId(grantee_str.clone());
-        // Use grantor's own key as grantee key placeholder (no key registry).
-        let grantee_key = anchor.public_key_base64();
+        let grantee_key = match args.get("grantee_key").and_then(|v| v.as_str()) {
+            Some(key) => match IdentityId::from_public_key_base64(key) {
+                Ok(derived) if derived == grantee_id => key.to_string(),
+                Ok(_) => {
+                    return tool_error(
+                        id,
+                        format!("grantee_key does not belong to grantee '{grantee_id}'"),
+                    )
+                }
+                Err(e) => return tool_error(id, format!("invalid grantee_key: {e}")),
+            },
+            None => match self.resolve_grantee_key(&grantee_id) {
+                Ok(key) => key,
+                Err(e) => return tool_error(id, e),
+            },
+        };
 
-        let capabilities: Vec<Capability> = caps_arr
+        let capabilities: Vec<Capability> = match caps_arr
             .iter()
             .filter_map(|v| v.as_str())
-            .map(Capability::new)
-            .collect();
+            .map(Capability::try_new)
+            .collect()
+        {
+            Ok(c) => c,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
 
         let now_micros = agentic_identity::time::now_micros();
         let mut constraints = TrustConstraints::open();
@@ -2013,17 +4388,46 @@ impl McpServer {
             builder = builder.allow_delegation(1);
         }
 
+        if let Some(purpose) = args.get("purpose").and_then(|v| v.as_str()) {
+            builder = builder.purpose(purpose);
+        }
+
+        let mut justification = Vec::new();
+        if let Some(receipt_str) = args.get("justification_receipt").and_then(|v| v.as_str()) {
+            let receipt_id = match ReceiptId::parse(receipt_str) {
+                Ok(r) => r,
+                Err(e) => return tool_error(id, format!("invalid justification_receipt: {e}")),
+            };
+            if let Ok(receipt) =
+                ReceiptStore::new(&self.receipt_dir).and_then(|store| store.load(&receipt_id))
+            {
+                justification.push(receipt);
+            }
+            builder = builder.justification_receipt(receipt_id);
+        }
+
         let grant = match builder.sign(anchor.signing_key()) {
             Ok(g) => g,
             Err(e) => return tool_error(id, format!("failed to sign trust grant: {e}")),
         };
 
+        if let Err(e) = verify_grant_justification(&grant, &justification) {
+            return tool_error(
+                id,
+                format!("justification receipt must exist and be signed by the grantor: {e}"),
+            );
+        }
+
         let store = match TrustStore::new(&self.trust_dir) {
             Ok(s) => s,
             Err(e) => return tool_error(id, format!("failed to open trust store: {e}")),
         };
 
-        if let Err(e) = store.save_granted(&grant) {
+        let saved = Transaction::begin(&self.transaction_dir).and_then(|mut txn| {
+            store.stage_granted(&mut txn, &grant)?;
+            txn.commit()
+        });
+        if let Err(e) = saved {
             return tool_error(id, format!("failed to save trust grant: {e}"));
         }
 
@@ -2046,26 +4450,31 @@ impl McpServer {
             .map(|m| m.to_string())
             .unwrap_or_else(|| "unlimited".to_string());
 
-        tool_ok(
-            id,
-            format!(
-                "Trust grant created\n\
-                 Trust ID:    {}\n\
-                 Grantor:     {}\n\
-                 Grantee:     {}\n\
-                 Capabilities: {}\n\
-                 Expires:     {}\n\
-                 Max Uses:    {}\n\
-                 Delegation:  {}",
-                grant.id,
-                grant.grantor,
-                grant.grantee,
-                cap_uris.join(", "),
-                expiry_str,
-                max_uses_str,
-                delegation_str,
-            ),
-        )
+        let mut out = format!(
+            "Trust grant created\n\
+             Trust ID:    {}\n\
+             Grantor:     {}\n\
+             Grantee:     {}\n\
+             Capabilities: {}\n\
+             Expires:     {}\n\
+             Max Uses:    {}\n\
+             Delegation:  {}",
+            grant.id,
+            grant.grantor,
+            grant.grantee,
+            cap_uris.join(", "),
+            expiry_str,
+            max_uses_str,
+            delegation_str,
+        );
+        if let Some(ref purpose) = grant.purpose {
+            out.push_str(&format!("\nPurpose:     {purpose}"));
+        }
+        if let Some(ref receipt) = grant.justification_receipt {
+            out.push_str(&format!("\nJustified by: {receipt}"));
+        }
+
+        tool_ok(id, out)
     }
 
     // ── Tool: trust_revoke ────────────────────────────────────────────────────
@@ -2081,16 +4490,14 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or(DEFAULT_IDENTITY);
 
-        let path = self.identity_dir.join(format!("{identity_name}.aid"));
-
-        if !path.exists() {
+        if !self.identity_exists(identity_name) {
             return tool_error(
                 id,
                 format!("identity '{identity_name}' not found — use identity_create first"),
             );
         }
 
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(identity_name) {
             Ok(a) => a,
             Err(e) => {
                 return tool_error(
@@ -2100,7 +4507,10 @@ impl McpServer {
             }
         };
 
-        let trust_id = TrustId(trust_id_str.clone());
+        let trust_id = match TrustId::parse(&trust_id_str) {
+            Ok(t) => t,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
 
         let store = match TrustStore::new(&self.trust_dir) {
             Ok(s) => s,
@@ -2144,11 +4554,140 @@ impl McpServer {
         )
     }
 
-    // ── Tool: trust_verify ────────────────────────────────────────────────────
+    // ── Tool: trust_revoke_simulate ───────────────────────────────────────────
 
-    fn tool_trust_verify(&self, id: Value, args: &Value) -> Value {
-        let trust_id_str = match args.get("trust_id").and_then(|v| v.as_str()) {
-            Some(s) => s.to_string(),
+    fn tool_trust_revoke_simulate(&self, id: Value, args: &Value) -> Value {
+        let trust_id = match args.get("trust_id").and_then(|v| v.as_str()) {
+            Some(s) => match TrustId::parse(s) {
+                Ok(t) => t,
+                Err(e) => return tool_error(id, e.to_string()),
+            },
+            None => return tool_error(id, "required parameter 'trust_id' is missing"),
+        };
+
+        let store = match TrustStore::new(&self.trust_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open trust store: {e}")),
+        };
+        let grants = invention_federation::load_all_grants(&self.trust_dir);
+        let Some(root) = grants.iter().find(|g| g.id == trust_id) else {
+            return tool_error(id, format!("trust grant '{trust_id}' not found"));
+        };
+        if store.is_revoked(&trust_id) {
+            return tool_error(id, format!("trust grant '{trust_id}' is already revoked"));
+        }
+
+        // The same dependency walk identity_revoke_cascade_execute revokes,
+        // root first and every grant after the one it was delegated from.
+        let walked: Vec<&agentic_identity::TrustGrant> = std::iter::once(root)
+            .chain(invention_federation::dependent_grants(&grants, &trust_id.0))
+            .collect();
+        let in_cascade: HashSet<&str> = walked.iter().map(|g| g.id.0.as_str()).collect();
+        let (cascade, already_revoked): (Vec<_>, Vec<_>) =
+            walked.into_iter().partition(|g| !store.is_revoked(&g.id));
+
+        // What an identity still holds through live grants outside the cascade.
+        let held_outside = |identity: &IdentityId| -> Vec<Capability> {
+            grants
+                .iter()
+                .filter(|g| g.grantee == *identity && !in_cascade.contains(g.id.0.as_str()))
+                .filter(|g| !store.is_revoked(&g.id))
+                .flat_map(|g| g.capabilities.iter().cloned())
+                .collect()
+        };
+
+        // A delegated capability survives if its grantor can still back it,
+        // either from outside the cascade or from what an earlier grant in
+        // the cascade kept. The revoked grant itself keeps nothing.
+        let mut kept: Vec<(&IdentityId, Vec<Capability>)> = Vec::new();
+        let mut lost_by_grantee: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut impacts = Vec::new();
+        let mut fully = 0usize;
+        let mut partially = 0usize;
+        for (i, grant) in cascade.iter().enumerate() {
+            let (lost, retained): (Vec<Capability>, Vec<Capability>) =
+                if i == 0 {
+                    (grant.capabilities.clone(), Vec::new())
+                } else {
+                    let mut backing = held_outside(&grant.grantor);
+                    for (grantee, caps) in &kept {
+                        if **grantee == grant.grantor {
+                            backing.extend(caps.iter().cloned());
+                        }
+                    }
+                    grant.capabilities.iter().cloned().partition(|c| {
+                        !agentic_identity::trust::capabilities_cover(&backing, &c.uri)
+                    })
+                };
+
+            let impact = if retained.is_empty() {
+                fully += 1;
+                "fully_invalidated"
+            } else if !lost.is_empty() {
+                partially += 1;
+                "partially_invalidated"
+            } else {
+                "still_backed"
+            };
+            lost_by_grantee
+                .entry(grant.grantee.0.clone())
+                .or_default()
+                .extend(lost.iter().map(|c| c.uri.clone()));
+
+            let uris = |caps: &[Capability]| caps.iter().map(|c| c.uri.clone()).collect::<Vec<_>>();
+            impacts.push(json!({
+                "grant_id": grant.id.0,
+                "grantor": grant.grantor.0,
+                "grantee": grant.grantee.0,
+                "delegation_depth": grant.delegation_depth,
+                "impact": impact,
+                "capabilities_lost": uris(&lost),
+                "capabilities_kept": uris(&retained),
+            }));
+            kept.push((&grant.grantee, retained));
+        }
+
+        // A grantee only loses what no other live grant still gives it.
+        let capabilities_lost: Vec<Value> = lost_by_grantee
+            .into_iter()
+            .filter_map(|(grantee, uris)| {
+                let identity = IdentityId(grantee.clone());
+                let mut still = held_outside(&identity);
+                for (g, caps) in &kept {
+                    if **g == identity {
+                        still.extend(caps.iter().cloned());
+                    }
+                }
+                let lost: Vec<String> = uris
+                    .into_iter()
+                    .filter(|uri| !agentic_identity::trust::capabilities_cover(&still, uri))
+                    .collect();
+                (!lost.is_empty()).then(|| json!({ "identity": grantee, "capabilities": lost }))
+            })
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "trust_id": trust_id.0,
+                "simulated": true,
+                "grants_affected": cascade.len(),
+                "fully_invalidated": fully,
+                "partially_invalidated": partially,
+                "grants": impacts,
+                "already_revoked": already_revoked.iter().map(|g| &g.id.0).collect::<Vec<_>>(),
+                "capabilities_lost": capabilities_lost,
+                "note": "Nothing was revoked. Use trust_revoke or identity_revoke_cascade_execute to apply.",
+            }))
+            .unwrap(),
+        )
+    }
+
+    // ── Tool: trust_verify ────────────────────────────────────────────────────
+
+    fn tool_trust_verify(&self, id: Value, args: &Value) -> Value {
+        let trust_id_str = match args.get("trust_id").and_then(|v| v.as_str()) {
+            Some(s) => s.to_string(),
             None => return tool_error(id, "required parameter 'trust_id' is missing"),
         };
 
@@ -2162,7 +4701,10 @@ impl McpServer {
             Err(e) => return tool_error(id, format!("failed to open trust store: {e}")),
         };
 
-        let trust_id = TrustId(trust_id_str.clone());
+        let trust_id = match TrustId::parse(&trust_id_str) {
+            Ok(t) => t,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
         let grant = match store.load_grant(&trust_id) {
             Ok(g) => g,
             Err(e) => {
@@ -2170,6 +4712,20 @@ impl McpServer {
             }
         };
 
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
+        if !scope.matches_grant(&grant) {
+            return tool_error(
+                id,
+                format!(
+                    "trust grant '{trust_id_str}' does not involve {}",
+                    scope.label()
+                ),
+            );
+        }
+
         let revocations = if store.is_revoked(&trust_id) {
             match store.load_revocation(&trust_id) {
                 Ok(rev) => vec![rev],
@@ -2179,16 +4735,53 @@ impl McpServer {
             vec![]
         };
 
-        let verification = match verify_trust_grant(&grant, capability, 0, &revocations) {
-            Ok(v) => v,
-            Err(e) => return tool_error(id, format!("verification error: {e}")),
+        let use_times = match store.use_times(&trust_id) {
+            Ok(times) => times,
+            Err(e) => return tool_error(id, format!("failed to read usage ledger: {e}")),
         };
+        let uses = use_times.len() as u64;
+        let options = VerifyOptions::new().context(UsageContext::new().use_times(use_times));
+        let verification =
+            match verify_trust_grant_with(&grant, capability, uses, &revocations, &options) {
+                Ok(v) => v,
+                Err(e) => return tool_error(id, format!("verification error: {e}")),
+            };
 
-        let result_str = if verification.is_valid {
-            "VALID"
-        } else {
-            "INVALID"
+        // Deep audit of the rationale link; it does not affect validity.
+        let justification_str = match grant.justification_receipt {
+            None => "none".to_string(),
+            Some(ref receipt_id) => {
+                let linked: Vec<_> = ReceiptStore::new(&self.receipt_dir)
+                    .and_then(|store| store.load(receipt_id))
+                    .into_iter()
+                    .collect();
+                match verify_grant_justification(&grant, &linked) {
+                    Ok(()) => format!("{receipt_id} (verified)"),
+                    Err(e) => format!("{receipt_id} (UNVERIFIED: {e})"),
+                }
+            }
         };
+
+        let messages = &self.verification_messages;
+        let outcomes = [
+            (
+                "signature",
+                VerificationOutcome::signature(verification.signature_valid),
+            ),
+            ("time", VerificationOutcome::time(verification.time_valid)),
+            (
+                "revocation",
+                VerificationOutcome::revocation(!verification.not_revoked),
+            ),
+            ("uses", VerificationOutcome::uses(verification.uses_valid)),
+            (
+                "capability",
+                VerificationOutcome::capability(verification.capability_granted),
+            ),
+            ("result", VerificationOutcome::result(verification.is_valid)),
+        ];
+        let [signature, time, revocation, uses_check, capability_outcome, result] =
+            outcomes.map(|(_, outcome)| messages.render(outcome));
         let cap_uris: Vec<&str> = grant.capabilities.iter().map(|c| c.uri.as_str()).collect();
         let expiry_str = grant
             .constraints
@@ -2196,7 +4789,12 @@ impl McpServer {
             .map(micros_to_rfc3339)
             .unwrap_or_else(|| "never".to_string());
 
-        tool_ok(
+        let codes: serde_json::Map<String, Value> = outcomes
+            .iter()
+            .map(|(check, outcome)| (check.to_string(), json!(outcome.code())))
+            .collect();
+
+        tool_ok_with_outcomes(
             id,
             format!(
                 "Trust Grant: {}\n\
@@ -2204,7 +4802,10 @@ impl McpServer {
                  Grantee:      {}\n\
                  Granted At:   {}\n\
                  Capabilities: {}\n\
-                 Expires:      {}\n\n\
+                 Expires:      {}\n\
+                 Used:         {}\n\
+                 Purpose:      {}\n\
+                 Justified by: {}\n\n\
                  Verification (capability: {capability}):\n\
                  Signature:    {}\n\
                  Time:         {}\n\
@@ -2218,32 +4819,125 @@ impl McpServer {
                 micros_to_rfc3339(grant.granted_at),
                 cap_uris.join(", "),
                 expiry_str,
-                if verification.signature_valid {
-                    "valid"
-                } else {
-                    "INVALID"
-                },
-                if verification.time_valid {
-                    "valid"
-                } else {
-                    "expired/not-yet-valid"
-                },
-                if verification.not_revoked {
-                    "yes"
-                } else {
-                    "REVOKED"
-                },
-                if verification.uses_valid {
-                    "within limit"
-                } else {
-                    "exceeded"
-                },
-                if verification.capability_granted {
-                    "granted"
-                } else {
-                    "not granted"
-                },
-                result_str,
+                uses_summary(uses, grant.constraints.max_uses),
+                grant.purpose.as_deref().unwrap_or("none"),
+                justification_str,
+                signature,
+                time,
+                revocation,
+                uses_check,
+                capability_outcome,
+                result,
+            ),
+            Value::Object(codes),
+        )
+    }
+
+    // ── Tool: trust_use ───────────────────────────────────────────────────────
+
+    fn tool_trust_use(&self, id: Value, args: &Value) -> Value {
+        let trust_id = match args.get("trust_id").and_then(|v| v.as_str()) {
+            Some(s) => match TrustId::parse(s) {
+                Ok(t) => t,
+                Err(e) => return tool_error(id, e.to_string()),
+            },
+            None => return tool_error(id, "required parameter 'trust_id' is missing"),
+        };
+        let capability = match args.get("capability").and_then(|v| v.as_str()) {
+            Some(c) => c,
+            None => return tool_error(id, "required parameter 'capability' is missing"),
+        };
+
+        let store = match TrustStore::new(&self.trust_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open trust store: {e}")),
+        };
+        let grant = match store.load_grant(&trust_id) {
+            Ok(g) => g,
+            Err(e) => return tool_error(id, format!("trust grant '{trust_id}' not found: {e}")),
+        };
+
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
+        if !scope.matches_grant(&grant) {
+            return tool_error(
+                id,
+                format!(
+                    "trust grant '{trust_id}' does not involve {}",
+                    scope.label()
+                ),
+            );
+        }
+
+        let revocations: Vec<_> = store.load_revocation(&trust_id).into_iter().collect();
+        let use_times = match store.use_times(&trust_id) {
+            Ok(times) => times,
+            Err(e) => return tool_error(id, format!("failed to read usage ledger: {e}")),
+        };
+        let uses = use_times.len() as u64;
+        let options = VerifyOptions::new().context(UsageContext::new().use_times(use_times));
+        let verification =
+            match verify_trust_grant_with(&grant, capability, uses, &revocations, &options) {
+                Ok(v) => v,
+                Err(e) => return tool_error(id, format!("verification error: {e}")),
+            };
+
+        let max_uses = grant.constraints.max_uses;
+        let mut reasons = Vec::new();
+        if !verification.signature_valid {
+            reasons.push("signature invalid".to_string());
+        }
+        if !verification.time_valid {
+            reasons.push("outside its validity window".to_string());
+        }
+        if !verification.not_revoked || store.is_revoked(&trust_id) {
+            reasons.push("revoked".to_string());
+        }
+        if !verification.uses_valid {
+            reasons.push(format!("all {} uses consumed", max_uses.unwrap_or(0)));
+        }
+        if !verification.capability_granted {
+            reasons.push(format!("capability '{capability}' not granted"));
+        }
+        reasons.extend(verification.context_violations.iter().cloned());
+        if !reasons.is_empty() {
+            return tool_error(
+                id,
+                format!(
+                    "trust grant '{trust_id}' cannot be used: {}",
+                    reasons.join(", ")
+                ),
+            );
+        }
+
+        // Claiming the use is atomic, so a concurrent use can still win the
+        // last slot after verification passed.
+        let used = match store.record_use_within(&trust_id, max_uses) {
+            Ok(n) => n,
+            Err(agentic_identity::IdentityError::MaxUsesExceeded) => {
+                return tool_error(
+                    id,
+                    format!(
+                        "trust grant '{trust_id}' cannot be used: all {} uses consumed",
+                        max_uses.unwrap_or(0)
+                    ),
+                )
+            }
+            Err(e) => return tool_error(id, format!("failed to record use: {e}")),
+        };
+
+        tool_ok(
+            id,
+            format!(
+                "Trust grant used\n\
+                 Trust ID:    {trust_id}\n\
+                 Grantee:     {}\n\
+                 Capability:  {capability}\n\
+                 Use:         {}",
+                grant.grantee,
+                uses_summary(used, max_uses),
             ),
         )
     }
@@ -2267,6 +4961,10 @@ impl McpServer {
 
         let show_granted = matches!(direction, "granted" | "both");
         let show_received = matches!(direction, "received" | "both");
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
 
         let mut out = String::new();
 
@@ -2285,6 +4983,9 @@ impl McpServer {
                     continue;
                 }
                 if let Ok(grant) = store.load_grant(gid) {
+                    if !scope.matches_grant(&grant) {
+                        continue;
+                    }
                     let caps: Vec<&str> =
                         grant.capabilities.iter().map(|c| c.uri.as_str()).collect();
                     let status = if revoked { " [REVOKED]" } else { "" };
@@ -2325,6 +5026,9 @@ impl McpServer {
                     continue;
                 }
                 if let Ok(grant) = store.load_grant(gid) {
+                    if !scope.matches_grant(&grant) {
+                        continue;
+                    }
                     let caps: Vec<&str> =
                         grant.capabilities.iter().map(|c| c.uri.as_str()).collect();
                     let status = if revoked { " [REVOKED]" } else { "" };
@@ -2356,13 +5060,190 @@ impl McpServer {
         tool_ok(id, out.trim_end().to_string())
     }
 
+    // ── Tool: identity_authority_diff ─────────────────────────────────────────
+
+    fn tool_identity_authority_diff(&self, id: Value, args: &Value) -> Value {
+        let from = match args.get("from").and_then(|v| v.as_u64()) {
+            Some(t) => t,
+            None => return tool_error(id, "required parameter 'from' is missing"),
+        };
+        let to = args
+            .get("to")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(agentic_identity::time::now_micros);
+
+        let records = SpawnStore::new(&self.spawn_dir)
+            .ok()
+            .and_then(|s| s.load_all().ok())
+            .unwrap_or_default();
+        let (label, identity_id) = match self.spawn_subject(args, &records) {
+            Ok(subject) => subject,
+            Err(e) => return tool_error(id, e),
+        };
+
+        let store = match TrustStore::new(&self.trust_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open trust store: {e}")),
+        };
+        let mut grant_ids = store.list_granted().unwrap_or_default();
+        grant_ids.extend(store.list_received().unwrap_or_default());
+        grant_ids.sort_by(|a, b| a.0.cmp(&b.0));
+        grant_ids.dedup();
+        let grants: Vec<_> = grant_ids
+            .iter()
+            .filter_map(|gid| store.load_grant(gid).ok())
+            .collect();
+        let revocations: Vec<_> = store
+            .list_revocations()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tid| store.load_revocation(tid).ok())
+            .collect();
+
+        let diff = authority_diff(&identity_id, from, to, &grants, &revocations, &records);
+        let uris = |caps: &[Capability]| caps.iter().map(|c| c.uri.clone()).collect::<Vec<_>>();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "identity": label,
+                "identity_id": identity_id.0,
+                "from": micros_to_rfc3339(from),
+                "to": micros_to_rfc3339(to),
+                "changed": !diff.is_empty(),
+                "added": uris(&diff.added),
+                "removed": uris(&diff.removed),
+            }))
+            .unwrap(),
+        )
+    }
+
+    // ── Tool: capability_holders ──────────────────────────────────────────────
+
+    fn tool_capability_holders(&self, id: Value, args: &Value) -> Value {
+        let capability = match args.get("capability").and_then(|v| v.as_str()) {
+            Some(c) => c,
+            None => return tool_error(id, "required parameter 'capability' is missing"),
+        };
+        let at = args
+            .get("at")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(agentic_identity::time::now_micros);
+
+        let mut query = HoldersQuery::new();
+        if let Some(limit) = args.get("limit").and_then(|v| v.as_u64()) {
+            query = query.limit(limit as usize);
+        }
+        if let Some(cursor) = args.get("cursor").and_then(|v| v.as_str()) {
+            query = query.cursor(cursor);
+        }
+
+        // trust/ and spawn/ live side by side in the data directory.
+        let base_dir = self.trust_dir.parent().unwrap_or(std::path::Path::new("."));
+        let page = match holders_page(capability, base_dir, at, &query) {
+            Ok(p) => p,
+            Err(e) => return tool_error(id, format!("failed to scan holders: {e}")),
+        };
+        let holders: Vec<Value> = page
+            .holders
+            .iter()
+            .map(|(identity, source)| json!({"identity": identity.0, "source": source}))
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "capability": capability,
+                "at": micros_to_rfc3339(at),
+                "holders": holders,
+                "negated": page.negated,
+                "scanned": page.scanned,
+                "next_cursor": page.next_cursor,
+            }))
+            .unwrap(),
+        )
+    }
+
+    // ── Tool: trust_path ──────────────────────────────────────────────────────
+
+    fn tool_trust_path(&self, id: Value, args: &Value) -> Value {
+        let (from, to, capability) = match (
+            args.get("from").and_then(|v| v.as_str()),
+            args.get("to").and_then(|v| v.as_str()),
+            args.get("capability").and_then(|v| v.as_str()),
+        ) {
+            (Some(f), Some(t), Some(c)) => (f, t, c),
+            _ => return tool_error(id, "'from', 'to' and 'capability' are required"),
+        };
+        let at = args
+            .get("at")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(agentic_identity::time::now_micros);
+        let (from_id, to_id) = match (self.path_endpoint(from), self.path_endpoint(to)) {
+            (Ok(f), Ok(t)) => (f, t),
+            (Err(e), _) | (_, Err(e)) => return tool_error(id, e),
+        };
+
+        let mut dirs = vec![self.trust_dir.clone()];
+        for dir in args
+            .get("stores")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+        {
+            let Some(dir) = dir.as_str() else {
+                return tool_error(id, "'stores' must be an array of directory paths");
+            };
+            // TrustStore::new would create a mistyped directory; refuse instead.
+            if !std::path::Path::new(dir).is_dir() {
+                return tool_error(id, format!("trust store '{dir}' is not a directory"));
+            }
+            dirs.push(PathBuf::from(dir));
+        }
+        let mut graph = TrustGraph::new();
+        for dir in &dirs {
+            if let Err(e) = TrustStore::new(dir).and_then(|s| graph.ingest_store(&s)) {
+                return tool_error(
+                    id,
+                    format!("failed to read trust store {}: {e}", dir.display()),
+                );
+            }
+        }
+
+        let path = graph.find_path(&from_id, &to_id, capability, at);
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "found": path.is_some(),
+                "from": from_id.0,
+                "to": to_id.0,
+                "capability": capability,
+                "at": micros_to_rfc3339(at),
+                "stores": dirs.len(),
+                "grants_scanned": graph.len(),
+                "hops": path.map(|p| p.hops).unwrap_or_default(),
+            }))
+            .unwrap(),
+        )
+    }
+
+    /// An identity ID, or the ID of a local identity by name.
+    fn path_endpoint(&self, value: &str) -> std::result::Result<IdentityId, String> {
+        if value.starts_with("aid_") {
+            return IdentityId::parse(value).map_err(|e| e.to_string());
+        }
+        self.read_document(value)
+            .map(|doc| doc.id)
+            .map_err(|e| format!("failed to read identity '{value}': {e}"))
+    }
+
     // ── Tool: receipt_list ────────────────────────────────────────────────────
 
     fn tool_receipt_list(&self, id: Value, args: &Value) -> Value {
-        let actor_filter = args
-            .get("actor")
-            .and_then(|v| v.as_str())
-            .map(|s| s.to_string());
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
         let type_filter = args
             .get("action_type")
             .and_then(|v| v.as_str())
@@ -2374,30 +5255,23 @@ impl McpServer {
             Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
         };
 
-        let all_ids = match store.list() {
-            Ok(i) => i,
+        let all = match store.load_all() {
+            Ok(r) => r,
             Err(e) => return tool_error(id, format!("failed to list receipts: {e}")),
         };
 
-        let mut receipts = Vec::new();
-        for rid in &all_ids {
-            if let Ok(receipt) = store.load(rid) {
-                if let Some(ref actor) = actor_filter {
-                    if receipt.actor.0 != *actor {
-                        continue;
-                    }
-                }
-                if let Some(ref type_str) = type_filter {
-                    if receipt.action_type.as_tag() != type_str.as_str() {
-                        continue;
-                    }
-                }
-                receipts.push(receipt);
-            }
-        }
+        let mut receipts: Vec<_> = all
+            .into_iter()
+            .filter(|receipt| scope.matches(&receipt.actor))
+            .filter(|receipt| {
+                type_filter
+                    .as_deref()
+                    .is_none_or(|t| receipt.action_type.as_tag() == t)
+            })
+            .collect();
 
         // Sort by timestamp descending (newest first).
-        receipts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
         let total = receipts.len();
         receipts.truncate(limit);
@@ -2408,11 +5282,7 @@ impl McpServer {
             out.push_str("  (none match filters)");
         } else {
             for receipt in &receipts {
-                let desc = if receipt.action.description.len() > 60 {
-                    format!("{}...", &receipt.action.description[..57])
-                } else {
-                    receipt.action.description.clone()
-                };
+                let desc = ellipsize(&receipt.action.description, 60);
                 out.push_str(&format!(
                     "  {} [{}] {} — {}\n",
                     receipt.id,
@@ -2426,38 +5296,181 @@ impl McpServer {
         tool_ok(id, out.trim_end().to_string())
     }
 
-    // ── Tool: identity_health ─────────────────────────────────────────────────
+    // ── Tool: receipt_query ───────────────────────────────────────────────────
 
-    fn tool_identity_health(&self, id: Value, _args: &Value) -> Value {
-        let mut out = String::from("AgenticIdentity Health Check\n\n");
+    fn tool_receipt_query(&self, id: Value, args: &Value) -> Value {
+        let mut filter =
+            match ReceiptFilter::from_json(args.get("filter").cloned().unwrap_or(json!({}))) {
+                Ok(f) => f,
+                Err(e) => return tool_error(id, e.to_string()),
+            };
+        let limit = filter.limit.take().unwrap_or(20);
+        // Match the tags action_sign writes, e.g. `Decision` → `decision`.
+        filter.action_type = filter
+            .action_type
+            .map(|t| parse_action_type(&t).as_tag().to_string());
 
-        // Identity directory
-        let id_dir_exists = self.identity_dir.exists();
-        out.push_str(&format!(
-            "Identity Directory: {}\n  Path: {}\n",
-            if id_dir_exists { "OK" } else { "MISSING" },
-            self.identity_dir.display()
-        ));
+        let store = match ReceiptStore::new(&self.receipt_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
+        };
+        let all = match store.load_all() {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, format!("failed to list receipts: {e}")),
+        };
+        let mut index = ReceiptIndex::new();
+        for receipt in all {
+            index.insert(receipt);
+        }
 
-        // Count identity files
-        let identity_count = if id_dir_exists {
-            std::fs::read_dir(&self.identity_dir)
-                .ok()
-                .map(|entries| {
-                    entries
-                        .filter_map(|e| e.ok())
-                        .filter(|e| e.path().extension().map(|x| x == "aid").unwrap_or(false))
-                        .count()
+        let (plan, mut receipts) = filter.execute_with_plan(&index);
+        let total = receipts.len();
+        receipts.truncate(limit);
+        let receipts: Vec<Value> = receipts
+            .iter()
+            .map(|r| {
+                json!({
+                    "receipt_id": r.id.0,
+                    "actor": r.actor.0,
+                    "action_type": r.action_type.as_tag(),
+                    "timestamp": micros_to_rfc3339(r.timestamp),
+                    "description": r.action.description,
+                    "data": r.action.data,
+                    "previous_receipt": r.previous_receipt.as_ref().map(|p| &p.0),
                 })
-                .unwrap_or(0)
-        } else {
-            0
-        };
-        out.push_str(&format!("  Identities: {identity_count}\n"));
+            })
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "plan": plan,
+                "explain": plan.to_string(),
+                "total": total,
+                "receipts": receipts,
+            }))
+            .unwrap(),
+        )
+    }
+
+    // ── Tool: receipt_graph ───────────────────────────────────────────────────
+
+    fn tool_receipt_graph(&self, id: Value, args: &Value) -> Value {
+        let format = args
+            .get("format")
+            .and_then(|v| v.as_str())
+            .unwrap_or("mermaid");
+        if !matches!(format, "mermaid" | "dot" | "json") {
+            return tool_error(
+                id,
+                format!("unknown format '{format}' (expected mermaid, dot or json)"),
+            );
+        }
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(100) as usize;
+
+        let store = match ReceiptStore::new(&self.receipt_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open receipt store: {e}")),
+        };
+        let loaded = match args.get("tip").and_then(|v| v.as_str()) {
+            Some(tip) => match ReceiptId::parse(tip) {
+                // A chain whose start has been pruned ends at the first
+                // receipt that cannot be loaded; the graph marks the gap.
+                Ok(tip) => Ok(store.walk_chain(&tip).map_while(|r| r.ok()).collect()),
+                Err(e) => return tool_error(id, e.to_string()),
+            },
+            None => store.load_all(),
+        };
+        let mut receipts: Vec<ActionReceipt> = match loaded {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, format!("failed to list receipts: {e}")),
+        };
+        receipts.retain(|receipt| scope.matches(&receipt.actor));
+        receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
+        receipts.truncate(limit);
+        if receipts.is_empty() {
+            return tool_error(id, "no receipts match");
+        }
+
+        let graph = ReceiptGraph::from_receipts(&receipts);
+        match format {
+            "dot" => tool_ok(id, graph.to_dot()),
+            "json" => tool_ok(id, serde_json::to_string_pretty(&graph).unwrap()),
+            _ => tool_ok(id, graph.to_mermaid()),
+        }
+    }
+
+    // ── Tool: receipt_export ──────────────────────────────────────────────────
+
+    fn tool_receipt_export(&self, id: Value, args: &Value) -> Value {
+        let actor = match self.actor_scope(args) {
+            Err(e) => return tool_error(id, e),
+            Ok(ActorScope::All) => None,
+            Ok(ActorScope::Only(Some(actor))) => Some(actor),
+            Ok(ActorScope::Only(None)) => {
+                return tool_error(id, "no identity matches the requested actor")
+            }
+        };
+        let filter = ReceiptExportFilter {
+            actor,
+            since: args.get("since").and_then(|v| v.as_u64()),
+            until: args.get("until").and_then(|v| v.as_u64()),
+        };
+
+        match self.export_receipts_ndjson(&filter) {
+            Ok(ndjson) => tool_ok(id, ndjson),
+            Err(e) => tool_error(id, format!("receipt export failed: {e}")),
+        }
+    }
+
+    /// Stream matching receipts from the store straight into an NDJSON
+    /// buffer, one receipt at a time.
+    fn export_receipts_ndjson(
+        &self,
+        filter: &ReceiptExportFilter,
+    ) -> agentic_identity::Result<String> {
+        let store = ReceiptStore::new(&self.receipt_dir)?;
+        let mut out = Vec::new();
+        store.export_ndjson(filter, &mut out)?;
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    // ── Tool: identity_health ─────────────────────────────────────────────────
+
+    fn tool_identity_health(&self, id: Value, args: &Value) -> Value {
+        let horizon_str = args
+            .get("expiry_horizon")
+            .and_then(|v| v.as_str())
+            .unwrap_or("24h");
+        let horizon = match parse_duration_to_micros(horizon_str) {
+            Ok(h) => h,
+            Err(e) => return tool_error(id, format!("invalid expiry_horizon: {e}")),
+        };
+
+        let mut out = String::from("AgenticIdentity Health Check\n\n");
+
+        // Identity directory, or the vault that replaces it
+        let (id_store, id_path) = match &self.identity_vault {
+            Some(vault) => ("Identity Vault", vault.as_path()),
+            None => ("Identity Directory", self.identity_dir.as_path()),
+        };
+        let id_dir_exists = id_path.exists();
+        out.push_str(&format!(
+            "{id_store}: {}\n  Path: {}\n",
+            if id_dir_exists { "OK" } else { "MISSING" },
+            id_path.display()
+        ));
+
+        // Count identities
+        let identity_count = self.identity_documents().len();
+        out.push_str(&format!("  Identities: {identity_count}\n"));
 
         // Default identity
-        let default_path = self.identity_dir.join(format!("{DEFAULT_IDENTITY}.aid"));
-        let default_exists = default_path.exists();
+        let default_exists = self.identity_exists(DEFAULT_IDENTITY);
         out.push_str(&format!(
             "  Default identity: {}\n",
             if default_exists {
@@ -2513,6 +5526,67 @@ impl McpServer {
         out.push_str(&format!("  Received:    {received_count}\n"));
         out.push_str(&format!("  Revocations: {revocation_count}\n"));
 
+        // Grant expiry, judged at the same instant a verification made now
+        // would use.
+        let expiry_store = if trust_dir_exists {
+            TrustStore::new(&self.trust_dir).ok()
+        } else {
+            None
+        };
+        if let Some(store) = expiry_store {
+            let mut grant_ids = store.list_granted().unwrap_or_default();
+            grant_ids.extend(store.list_received().unwrap_or_default());
+            grant_ids.sort_by(|a, b| a.0.cmp(&b.0));
+            grant_ids.dedup();
+            let grants: Vec<_> = grant_ids
+                .iter()
+                .filter_map(|gid| store.load_grant(gid).ok())
+                .collect();
+            let revocations: Vec<_> = store
+                .list_revocations()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|tid| store.load_revocation(tid).ok())
+                .collect();
+
+            let now = agentic_identity::time::now_micros();
+            let sweep = agentic_identity::trust::expiry_sweep(now, horizon, &grants, &revocations);
+            let describe = |g: &agentic_identity::TrustGrant| {
+                let caps: Vec<&str> = g.capabilities.iter().map(|c| c.uri.as_str()).collect();
+                format!(
+                    "{} ({} -> {}: {})",
+                    g.id,
+                    g.grantor,
+                    g.grantee,
+                    caps.join(", ")
+                )
+            };
+            out.push_str(&format!(
+                "  Expiring within {}: {}\n",
+                agentic_identity::time::format_duration_micros(horizon),
+                sweep.expiring.len()
+            ));
+            for g in &sweep.expiring {
+                let not_after = g.constraints.not_after.unwrap_or(now);
+                out.push_str(&format!(
+                    "    {} — in {} ({})\n",
+                    describe(g),
+                    agentic_identity::time::format_duration_micros(not_after - now),
+                    micros_to_rfc3339(not_after)
+                ));
+            }
+            out.push_str(&format!("  Expired:     {}\n", sweep.expired.len()));
+            for g in &sweep.expired {
+                let not_after = g.constraints.not_after.unwrap_or(now);
+                out.push_str(&format!(
+                    "    {} — {} ago ({})\n",
+                    describe(g),
+                    agentic_identity::time::format_duration_micros(now - not_after),
+                    micros_to_rfc3339(not_after)
+                ));
+            }
+        }
+
         // Overall status
         out.push('\n');
         let ok = id_dir_exists && default_exists;
@@ -2528,6 +5602,159 @@ impl McpServer {
         tool_ok(id, out)
     }
 
+    // ── Tool: identity_rekey_stores ───────────────────────────────────────────
+
+    /// Moves every `.aid` file from one passphrase to another through a
+    /// staging directory and manifest, so a crash never leaves a mixture of
+    /// keys: calling again finishes (or restarts) the interrupted run.
+    fn tool_identity_rekey_stores(&self, id: Value, args: &Value) -> Value {
+        let new_passphrase = match args.get("new_passphrase").and_then(|v| v.as_str()) {
+            Some(p) if !p.is_empty() => p,
+            _ => return tool_error(id, "'new_passphrase' is required"),
+        };
+        let old_passphrase = args
+            .get("old_passphrase")
+            .and_then(|v| v.as_str())
+            .unwrap_or(&self.passphrase);
+
+        if let Some(vault) = &self.identity_vault {
+            let _guard = VAULT_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let rekeyed = IdentityVault::open(vault, old_passphrase).and_then(|mut opened| {
+                opened.change_passphrase(new_passphrase)?;
+                opened.save()?;
+                Ok(opened.list().len())
+            });
+            return match rekeyed {
+                Ok(count) => tool_ok(
+                    id,
+                    serde_json::to_string_pretty(&json!({
+                        "status": "rekeyed",
+                        "vault": vault.display().to_string(),
+                        "identities": count,
+                    }))
+                    .unwrap(),
+                ),
+                Err(e) => tool_error(id, format!("failed to rekey identity vault: {e}")),
+            };
+        }
+
+        match rekey_identities(&self.identity_dir, old_passphrase, new_passphrase) {
+            Ok(report) => tool_ok(
+                id,
+                serde_json::to_string_pretty(&json!({
+                    "status": "rekeyed",
+                    "examined": report.examined,
+                    "rekeyed": report.rekeyed,
+                    "already_rekeyed": report.already_rekeyed,
+                    "resumed": report.resumed,
+                }))
+                .unwrap(),
+            ),
+            Err(e) => tool_error(id, format!("rekey failed: {e}")),
+        }
+    }
+
+    // ── Tool: identity_change_passphrase ──────────────────────────────────────
+
+    fn tool_identity_change_passphrase(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+        let old_passphrase = args
+            .get("old_passphrase")
+            .and_then(|v| v.as_str())
+            .unwrap_or(LEGACY_MCP_PASSPHRASE);
+        // By default the identity moves onto the passphrase it would be
+        // loaded with: its own if one is provisioned, else the server's.
+        let new_passphrase = match args.get("new_passphrase").and_then(|v| v.as_str()) {
+            Some(p) => p.to_string(),
+            None => match self.passphrase_for(name) {
+                Ok(p) => p,
+                Err(e) => return tool_error(id, e.to_string()),
+            },
+        };
+        if new_passphrase.is_empty() {
+            return tool_error(id, "'new_passphrase' must not be empty");
+        }
+
+        if self.identity_vault.is_some() {
+            return tool_error(
+                id,
+                "identities in a vault share its passphrase and cannot be re-encrypted one by one",
+            );
+        }
+        let path = self.identity_dir.join(format!("{name}.aid"));
+        if !path.exists() {
+            return tool_error(id, format!("Identity '{name}' not found"));
+        }
+
+        match change_passphrase(&path, old_passphrase, &new_passphrase) {
+            Ok(()) => tool_ok(
+                id,
+                serde_json::to_string_pretty(&json!({
+                    "status": "passphrase_changed",
+                    "name": name,
+                    "file": path.display().to_string(),
+                }))
+                .unwrap(),
+            ),
+            Err(e) => tool_error(id, format!("Failed to change passphrase: {e}")),
+        }
+    }
+
+    // ── Tool: identity_rotate ─────────────────────────────────────────────────
+
+    fn tool_identity_rotate(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+        let reason = match args
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("manual")
+        {
+            "scheduled" => RotationReason::Scheduled,
+            "compromised" => RotationReason::Compromised,
+            "device_lost" => RotationReason::DeviceLost,
+            "policy_required" => RotationReason::PolicyRequired,
+            "manual" => RotationReason::Manual,
+            other => return tool_error(id, format!("unknown rotation reason '{other}'")),
+        };
+
+        if !self.identity_exists(name) {
+            return tool_error(id, format!("Identity '{name}' not found"));
+        }
+        let anchor = match self.load_anchor(name) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
+        };
+
+        let rotated = match anchor.rotate(reason.clone()) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, format!("failed to rotate identity keys: {e}")),
+        };
+        let summary = json!({
+            "status": "rotated",
+            "name": name,
+            "reason": reason.as_str(),
+            "old_id": anchor.id().0,
+            "new_id": rotated.id().0,
+            "old_key": anchor.public_key_base64(),
+            "new_key": rotated.public_key_base64(),
+            "rotations": rotated.rotation_history.len(),
+            "file": self.identity_location(name).display().to_string(),
+        });
+        // Identity files and the vault are both replaced atomically, so a
+        // failed write leaves the old key in place.
+        if let Err(e) = self.store_anchor(name, rotated) {
+            return tool_error(id, format!("failed to save rotated identity: {e}"));
+        }
+
+        tool_ok(id, serde_json::to_string_pretty(&summary).unwrap())
+    }
+
     // ── Tool: continuity_record ──────────────────────────────────────────────
 
     fn tool_continuity_record(&self, id: Value, args: &Value) -> Value {
@@ -2548,8 +5775,7 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or("cognition");
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -2581,14 +5807,26 @@ impl McpServer {
             },
         };
 
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let previous = match store.load_experiences(&anchor.id()) {
+            Ok(mut exps) => exps.pop(),
+            Err(e) => return tool_error(id, format!("failed to load experiences: {e}")),
+        };
+
         match agentic_identity::continuity::record_experience(
             &anchor,
             event_type,
             content_hash,
             intensity,
-            None,
+            previous.as_ref(),
         ) {
             Ok(exp) => {
+                if let Err(e) = store.append_experience(&exp) {
+                    return tool_error(id, format!("failed to store experience: {e}"));
+                }
                 let out = format!(
                     "Experience recorded\n  ID: {}\n  Type: {}\n  Sequence: {}\n  Timestamp: {}\n  Intensity: {:.1}\n  Hash: {}",
                     exp.id, exp.event_type.as_tag(), exp.sequence_number, micros_to_rfc3339(exp.timestamp), exp.intensity, exp.cumulative_hash
@@ -2611,8 +5849,7 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or("manual");
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -2628,6 +5865,20 @@ impl McpServer {
             _ => agentic_identity::continuity::AnchorType::Manual,
         };
 
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let (previous, previous_anchor) = match (
+            store.load_experiences(&anchor.id()),
+            store.load_anchors(&anchor.id()),
+        ) {
+            (Ok(mut exps), Ok(mut anchors)) => (exps.pop(), anchors.pop()),
+            (Err(e), _) | (_, Err(e)) => {
+                return tool_error(id, format!("failed to load continuity chain: {e}"))
+            }
+        };
+
         // Create a checkpoint experience to anchor to
         let exp = match agentic_identity::continuity::record_experience(
             &anchor,
@@ -2636,14 +5887,26 @@ impl McpServer {
             },
             &format!("anchor_{anchor_type_str}"),
             1.0,
-            None,
+            previous.as_ref(),
         ) {
             Ok(e) => e,
             Err(e) => return tool_error(id, format!("failed to create experience: {e}")),
         };
+        if let Err(e) = store.append_experience(&exp) {
+            return tool_error(id, format!("failed to store experience: {e}"));
+        }
 
-        match agentic_identity::continuity::create_anchor(&anchor, anchor_type, &exp, None, None) {
+        match agentic_identity::continuity::create_anchor(
+            &anchor,
+            anchor_type,
+            &exp,
+            previous_anchor.as_ref(),
+            None,
+        ) {
             Ok(ca) => {
+                if let Err(e) = store.save_anchor(&ca) {
+                    return tool_error(id, format!("failed to store anchor: {e}"));
+                }
                 let out = format!(
                     "Continuity anchor created\n  ID: {}\n  Type: {}\n  Experience: {}\n  Count: {}\n  Hash: {}",
                     ca.id, ca.anchor_type.as_tag(), ca.experience_id, ca.experience_count, ca.cumulative_hash
@@ -2666,8 +5929,7 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or("active");
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -2686,16 +5948,40 @@ impl McpServer {
             latency_ms: 0,
         };
 
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let (experiences, heartbeats) = match (
+            store.load_experiences(&anchor.id()),
+            store.load_heartbeats(&anchor.id()),
+        ) {
+            (Ok(exps), Ok(hbs)) => (exps, hbs),
+            (Err(e), _) | (_, Err(e)) => {
+                return tool_error(id, format!("failed to load continuity chain: {e}"))
+            }
+        };
+        let continuity_hash = experiences
+            .last()
+            .map(|e| e.cumulative_hash.as_str())
+            .unwrap_or("mcp_heartbeat");
+        let since_last = experiences
+            .len()
+            .saturating_sub(heartbeats.last().map_or(0, |h| h.experience_count as usize));
+
         match agentic_identity::continuity::create_heartbeat(
             &anchor,
-            0,
-            "mcp_heartbeat",
-            0,
-            0,
+            heartbeats.len() as u64,
+            continuity_hash,
+            experiences.len() as u64,
+            since_last as u64,
             status,
             health,
         ) {
             Ok(hb) => {
+                if let Err(e) = store.append_heartbeat(&hb) {
+                    return tool_error(id, format!("failed to store heartbeat: {e}"));
+                }
                 let out = format!(
                     "Heartbeat created\n  ID: {}\n  Status: {}\n  Timestamp: {}",
                     hb.id,
@@ -2715,10 +6001,56 @@ impl McpServer {
             .get("identity")
             .and_then(|v| v.as_str())
             .unwrap_or(DEFAULT_IDENTITY);
-        let out = format!(
-            "Continuity status for identity '{}'\n  No experiences recorded yet (use continuity_record to start)",
-            name
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let (replay, heartbeats) = match (store.replay(&doc.id), store.load_heartbeats(&doc.id)) {
+            (Ok(r), Ok(hbs)) => (r, hbs),
+            (Err(e), _) | (_, Err(e)) => {
+                return tool_error(id, format!("failed to load continuity chain: {e}"))
+            }
+        };
+        let Some(state) = &replay.state else {
+            let out = format!(
+                "Continuity status for identity '{}'\n  No experiences recorded yet (use continuity_record to start)",
+                name
+            );
+            return tool_ok(id, out);
+        };
+
+        let chain = match replay.broken_at {
+            None => format!("intact ({} experiences replayed)", replay.verified),
+            Some(seq) => format!(
+                "BROKEN at sequence {seq} ({} experiences verified before it)",
+                replay.verified
+            ),
+        };
+        let mut out = format!(
+            "Continuity status for identity '{}'\n  Experiences: {}\n  Genesis: {} ({})\n  Latest: {} ({})\n  Latest hash: {}\n  Chain: {}\n  Anchors: {} ({} verified)\n  Heartbeats: {}",
+            name,
+            state.total_experiences,
+            state.genesis_experience_id,
+            micros_to_rfc3339(state.genesis_timestamp),
+            state.latest_experience_id,
+            micros_to_rfc3339(state.latest_timestamp),
+            state.latest_hash,
+            chain,
+            replay.anchors,
+            replay.anchors_verified,
+            heartbeats.len()
         );
+        if let Some(last) = heartbeats.last() {
+            out.push_str(&format!(
+                "\n  Last heartbeat: {}",
+                micros_to_rfc3339(last.timestamp)
+            ));
+        }
         tool_ok(id, out)
     }
 
@@ -2733,11 +6065,120 @@ impl McpServer {
             .get("grace_period_seconds")
             .and_then(|v| v.as_u64())
             .unwrap_or(300);
-        let out = format!(
-            "Gap analysis for identity '{}' (grace: {}s)\n  No experiences recorded yet",
-            name, grace
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let (experiences, heartbeats, gaps) = match (
+            store.load_experiences(&doc.id),
+            store.load_heartbeats(&doc.id),
+            store.gaps(&doc.id, grace),
+        ) {
+            (Ok(exps), Ok(hbs), Ok(gaps)) => (exps, hbs, gaps),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return tool_error(id, format!("failed to load continuity chain: {e}"))
+            }
+        };
+        if experiences.is_empty() {
+            let out = format!(
+                "Gap analysis for identity '{}' (grace: {}s)\n  No experiences recorded yet",
+                name, grace
+            );
+            return tool_ok(id, out);
+        }
+
+        let gaps: Vec<Value> = gaps
+            .iter()
+            .map(|g| {
+                json!({
+                    "start": micros_to_rfc3339(g.start),
+                    "end": micros_to_rfc3339(g.end),
+                    "duration_seconds": g.end.saturating_sub(g.start) / 1_000_000,
+                    "gap_type": format!("{:?}", g.gap_type).to_lowercase(),
+                    "severity": format!("{:?}", g.severity).to_lowercase(),
+                    "probable_cause": g.cause.as_tag(),
+                    "impact": g.impact,
+                })
+            })
+            .collect();
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "identity": name,
+                "grace_period_seconds": grace,
+                "experiences": experiences.len(),
+                "heartbeats": heartbeats.len(),
+                "gap_count": gaps.len(),
+                "gaps": gaps,
+            }))
+            .unwrap(),
+        )
+    }
+
+    // ── Tool: continuity_confidence ───────────────────────────────────────────
+
+    fn tool_continuity_confidence(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let store = match ContinuityStore::new(&self.continuity_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open continuity store: {e}")),
+        };
+        let (experiences, anchors, heartbeats) = match (
+            store.load_experiences(&doc.id),
+            store.load_anchors(&doc.id),
+            store.load_heartbeats(&doc.id),
+        ) {
+            (Ok(exps), Ok(anchors), Ok(hbs)) => (exps, anchors, hbs),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                return tool_error(id, format!("failed to load continuity chain: {e}"))
+            }
+        };
+
+        let to = args
+            .get("to")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_else(agentic_identity::time::now_micros);
+        let from = args
+            .get("from")
+            .and_then(|v| v.as_u64())
+            .or_else(|| experiences.first().map(|e| e.timestamp))
+            .unwrap_or(to);
+
+        let score = agentic_identity::continuity::confidence_score(
+            &experiences,
+            &anchors,
+            &heartbeats,
+            (from, to),
         );
-        tool_ok(id, out)
+        let inside = |t: u64| t >= from && t <= to;
+
+        tool_ok(
+            id,
+            serde_json::to_string_pretty(&json!({
+                "identity": name,
+                "from": micros_to_rfc3339(from),
+                "to": micros_to_rfc3339(to),
+                "score": score,
+                "experiences": experiences.iter().filter(|e| inside(e.timestamp)).count(),
+                "anchors": anchors.iter().filter(|a| inside(a.timestamp)).count(),
+                "heartbeats": heartbeats.iter().filter(|h| inside(h.timestamp)).count(),
+            }))
+            .unwrap(),
+        )
     }
 
     // ── Tool: spawn_create ────────────────────────────────────────────────────
@@ -2760,8 +6201,7 @@ impl McpServer {
             .and_then(|v| v.as_str())
             .unwrap_or("worker");
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let parent = match load_identity(&path, MCP_PASSPHRASE) {
+        let parent = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -2773,13 +6213,26 @@ impl McpServer {
             _ => agentic_identity::spawn::SpawnType::Worker,
         };
 
-        let authority: Vec<Capability> = authority_arr
+        let authority: Vec<Capability> = match authority_arr
             .iter()
             .filter_map(|v| v.as_str())
-            .map(Capability::new)
-            .collect();
+            .map(Capability::try_new)
+            .collect()
+        {
+            Ok(c) => c,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
         let ceiling = authority.clone();
 
+        let budget = args.get("budget");
+        let limit = |key: &str| budget.and_then(|b| b.get(key)).and_then(|v| v.as_u64());
+        let constraints = agentic_identity::spawn::SpawnConstraints {
+            max_receipts: limit("max_receipts"),
+            max_sub_spawns: limit("max_sub_spawns"),
+            max_cost: limit("max_cost"),
+            ..Default::default()
+        };
+
         match agentic_identity::spawn::spawn_child(
             &parent,
             spawn_type,
@@ -2787,26 +6240,41 @@ impl McpServer {
             authority,
             ceiling,
             agentic_identity::spawn::SpawnLifetime::Indefinite,
-            agentic_identity::spawn::SpawnConstraints::default(),
+            constraints,
             None,
             &[],
         ) {
-            Ok((child, record, receipt)) => {
-                // Save child identity
-                let child_name = format!("{}-{}", name, record.spawn_type.as_tag());
-                let child_path = self.identity_dir.join(format!("{child_name}.aid"));
-                if let Err(e) = save_identity(&child, &child_path, MCP_PASSPHRASE) {
-                    return tool_error(id, format!("failed to save child identity: {e}"));
-                }
-
-                // Save spawn receipt
-                if let Ok(store) = ReceiptStore::new(&self.receipt_dir) {
-                    let _ = store.save(&receipt);
-                }
-
-                // Save spawn record for terminate/list/lineage
-                if let Ok(store) = SpawnStore::new(&self.spawn_dir) {
-                    let _ = store.save(&record);
+            Ok((child, mut record, receipt)) => {
+                // The child identity, its receipt, its spawn record and the
+                // parent's sub-spawn budget are saved together or not at
+                // all. The file name is chosen inside the transaction, so no
+                // concurrent spawn can take it.
+                let mut txn = match Transaction::begin(&self.transaction_dir) {
+                    Ok(t) => t,
+                    Err(e) => return tool_error(id, format!("failed to start transaction: {e}")),
+                };
+                if let Err(e) = self.stage_spawn_budget(
+                    &mut txn,
+                    &parent.id(),
+                    &[(BudgetResource::SubSpawns, 1)],
+                ) {
+                    return tool_error(id, e);
+                }
+                let child_file = self.free_child_file(name, &record);
+                let child_path = self.identity_dir.join(&child_file);
+                let child_name = child_file.trim_end_matches(".aid").to_string();
+                record.child_file = Some(child_file);
+
+                let staged = self
+                    .passphrase_for(&child_name)
+                    .and_then(|passphrase| {
+                        stage_identity(&mut txn, &child, &child_path, &passphrase)
+                    })
+                    .and_then(|()| ReceiptStore::new(&self.receipt_dir)?.stage(&mut txn, &receipt))
+                    .and_then(|()| SpawnStore::new(&self.spawn_dir)?.stage(&mut txn, &record))
+                    .and_then(|()| txn.commit());
+                if let Err(e) = staged {
+                    return tool_error(id, format!("failed to save spawned child: {e}"));
                 }
 
                 let caps: Vec<&str> = record
@@ -2825,6 +6293,47 @@ impl McpServer {
         }
     }
 
+    /// File name for a new child of `parent_name`. The first child of each
+    /// type keeps the original `{parent}-{type}.aid` name; later ones add
+    /// part of the child ID so they never overwrite an earlier child.
+    fn free_child_file(&self, parent_name: &str, record: &SpawnRecord) -> String {
+        let base = format!("{parent_name}-{}", record.spawn_type.as_tag());
+        let suffix = record
+            .child_id
+            .0
+            .strip_prefix("aid_")
+            .unwrap_or(&record.child_id.0);
+        let candidates = std::iter::once(format!("{base}.aid"))
+            .chain((8..=suffix.len()).map(|n| format!("{base}-{}.aid", &suffix[..n])));
+        for candidate in candidates {
+            if !self.identity_dir.join(&candidate).exists() {
+                return candidate;
+            }
+        }
+        format!("{base}-{}.aid", record.id.0)
+    }
+
+    /// Locate a spawned child's identity file. Records written before the
+    /// file name was recorded are matched by the child ID in each file's
+    /// public document.
+    fn child_file_path(&self, record: &SpawnRecord) -> Option<PathBuf> {
+        if let Some(file) = &record.child_file {
+            let path = self.identity_dir.join(file);
+            return path.exists().then_some(path);
+        }
+        std::fs::read_dir(&self.identity_dir)
+            .ok()?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "aid"))
+            .find(|path| read_public_document(path).is_ok_and(|doc| doc.id == record.child_id))
+    }
+
+    fn child_file_label(&self, record: &SpawnRecord) -> String {
+        self.child_file_path(record)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "(not found)".to_string())
+    }
+
     // ── Tool: spawn_terminate ─────────────────────────────────────────────────
 
     fn tool_spawn_terminate(&self, id: Value, args: &Value) -> Value {
@@ -2846,8 +6355,7 @@ impl McpServer {
             .unwrap_or(DEFAULT_IDENTITY);
 
         // Load parent identity
-        let parent_path = self.identity_dir.join(format!("{parent_name}.aid"));
-        let parent = match load_identity(&parent_path, MCP_PASSPHRASE) {
+        let parent = match self.load_anchor(parent_name) {
             Ok(a) => a,
             Err(e) => {
                 return tool_error(id, format!("failed to load identity '{parent_name}': {e}"))
@@ -2861,7 +6369,10 @@ impl McpServer {
         };
 
         // Load the target spawn record
-        let spawn_id = agentic_identity::spawn::SpawnId(spawn_id_str.to_string());
+        let spawn_id = match agentic_identity::spawn::SpawnId::parse(spawn_id_str) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
         let mut record = match store.load(&spawn_id) {
             Ok(r) => r,
             Err(e) => {
@@ -2908,9 +6419,10 @@ impl McpServer {
                 }
 
                 let out = format!(
-                    "Spawn terminated\n  Spawn ID: {}\n  Child ID: {}\n  Reason: {}\n  Cascade: {}\n  Records terminated: {}\n  Receipt: {}",
+                    "Spawn terminated\n  Spawn ID: {}\n  Child ID: {}\n  Child file: {}\n  Reason: {}\n  Cascade: {}\n  Records terminated: {}\n  Receipt: {}",
                     record.id,
                     record.child_id,
+                    self.child_file_label(&record),
                     record.termination_reason.as_deref().unwrap_or("unknown"),
                     cascade,
                     terminated_ids.len(),
@@ -2925,28 +6437,39 @@ impl McpServer {
     // ── Tool: spawn_list ──────────────────────────────────────────────────────
 
     fn tool_spawn_list(&self, id: Value, args: &Value) -> Value {
-        let active_only = args
-            .get("active_only")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
+        let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(20) as usize;
+        let mut query = SpawnQuery::new()
+            .active_only(
+                args.get("active_only")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+            )
+            .limit(limit.max(1));
+
+        if let Some(parent_str) = args.get("parent").and_then(|v| v.as_str()) {
+            match IdentityId::parse(parent_str) {
+                Ok(parent) => query = query.parent(parent),
+                Err(e) => return tool_error(id, format!("invalid parent: {e}")),
+            }
+        }
+        if let Some(spawn_type) = args.get("spawn_type").and_then(|v| v.as_str()) {
+            query = query.spawn_type(spawn_type);
+        }
+        if let Some(cursor) = args.get("cursor").and_then(|v| v.as_str()) {
+            query = query.cursor(cursor);
+        }
+
         let store = match SpawnStore::new(&self.spawn_dir) {
             Ok(s) => s,
             Err(e) => return tool_error(id, format!("failed to open spawn store: {e}")),
         };
 
-        let records = match store.load_all() {
-            Ok(r) => r,
-            Err(e) => return tool_error(id, format!("failed to load spawn records: {e}")),
-        };
-
-        let filtered: Vec<&agentic_identity::spawn::SpawnRecord> = if active_only {
-            records.iter().filter(|r| !r.terminated).collect()
-        } else {
-            records.iter().collect()
+        let page = match store.query(&query) {
+            Ok(p) => p,
+            Err(e) => return tool_error(id, format!("failed to query spawn records: {e}")),
         };
 
-        if filtered.is_empty() {
+        if page.total == 0 {
             return tool_ok(
                 id,
                 "No spawned identities found (use spawn_create to spawn a child)".to_string(),
@@ -2954,8 +6477,12 @@ impl McpServer {
         }
 
         let mut lines = Vec::new();
-        lines.push(format!("Spawned identities ({}):", filtered.len()));
-        for r in &filtered {
+        lines.push(format!(
+            "Spawned identities ({} shown, {} total):",
+            page.records.len(),
+            page.total
+        ));
+        for r in &page.records {
             let status = if r.terminated { "terminated" } else { "active" };
             let caps: Vec<&str> = r.authority_granted.iter().map(|c| c.uri.as_str()).collect();
             lines.push(format!(
@@ -2967,107 +6494,188 @@ impl McpServer {
                 caps.join(", ")
             ));
         }
+        if let Some(cursor) = page.next_cursor {
+            lines.push(format!("Next cursor: {cursor}"));
+        }
         tool_ok(id, lines.join("\n"))
     }
 
-    // ── Tool: spawn_lineage ───────────────────────────────────────────────────
+    /// Stage `usage` against the budgets of the spawn that created
+    /// `identity` in `txn`, all or nothing: if any budget would be exceeded
+    /// nothing is staged. Identities not spawned here have no budgets.
+    ///
+    /// The spawn store stays locked until `txn` finishes, so a concurrent
+    /// writer can neither consume the same budget nor have its consumption
+    /// overwritten, and nothing is used up unless the work commits with it.
+    fn stage_spawn_budget(
+        &self,
+        txn: &mut Transaction,
+        identity: &IdentityId,
+        usage: &[(BudgetResource, u64)],
+    ) -> std::result::Result<(), String> {
+        // Without a spawn directory there are no spawn records to meter.
+        if !self.spawn_dir.is_dir() {
+            return Ok(());
+        }
+        txn.lock_store(&self.spawn_dir)
+            .map_err(|e| format!("failed to lock spawn store: {e}"))?;
+        let store = SpawnStore::new(&self.spawn_dir)
+            .map_err(|e| format!("failed to open spawn store: {e}"))?;
+        let records = store
+            .load_all()
+            .map_err(|e| format!("failed to load spawn records: {e}"))?;
+        let Some(mut record) = records.into_iter().find(|r| r.child_id == *identity) else {
+            return Ok(());
+        };
+        for (resource, amount) in usage {
+            agentic_identity::spawn::consume_budget(&mut record, *resource, *amount)
+                .map_err(|e| format!("{e} (spawn {})", record.id))?;
+        }
+        store
+            .stage(txn, &record)
+            .map_err(|e| format!("failed to record spawn budget use: {e}"))
+    }
+
+    /// Resolve which identity a spawn query is about, without decrypting any
+    /// key. `spawn_id` takes the record's `child_id`, `identity_id` is used
+    /// as-is, and a named identity is resolved from its public document.
+    ///
+    /// Returns a display label and the identity ID.
+    fn spawn_subject(
+        &self,
+        args: &Value,
+        records: &[SpawnRecord],
+    ) -> std::result::Result<(String, IdentityId), String> {
+        if let Some(spawn_id) = args.get("spawn_id").and_then(|v| v.as_str()) {
+            return records
+                .iter()
+                .find(|r| r.id.0 == spawn_id)
+                .map(|r| (r.child_id.0.clone(), r.child_id.clone()))
+                .ok_or_else(|| format!("spawn record '{spawn_id}' not found"));
+        }
+        if let Some(aid) = args.get("identity_id").and_then(|v| v.as_str()) {
+            return Ok((aid.to_string(), IdentityId(aid.to_string())));
+        }
 
-    fn tool_spawn_lineage(&self, id: Value, args: &Value) -> Value {
         let name = args
             .get("identity")
             .and_then(|v| v.as_str())
             .unwrap_or(DEFAULT_IDENTITY);
+        self.read_document(name)
+            .map(|doc| (name.to_string(), doc.id))
+            .map_err(|e| format!("failed to read identity '{name}': {e}"))
+    }
 
-        // Load all spawn records
-        let store = match SpawnStore::new(&self.spawn_dir) {
-            Ok(s) => s,
-            Err(_) => {
-                // No spawn store means root identity
-                let out = format!(
-                    "Lineage for identity '{}'\n  Root (no spawn record — this is a root identity)\n  Depth: 0\n  Authority: * (full)",
-                    name
-                );
-                return tool_ok(id, out);
-            }
-        };
+    // ── Tool: spawn_lineage ───────────────────────────────────────────────────
 
-        let records = store.load_all().unwrap_or_default();
+    fn tool_spawn_lineage(&self, id: Value, args: &Value) -> Value {
+        let records = SpawnStore::new(&self.spawn_dir)
+            .ok()
+            .and_then(|s| s.load_all().ok())
+            .unwrap_or_default();
 
-        // Load the identity to get its ID
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
-            Ok(a) => a,
-            Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
-        };
+        // With no spawn records at all, every identity is a root.
+        if records.is_empty() && args.get("spawn_id").is_none() {
+            let label = args
+                .get("identity_id")
+                .or_else(|| args.get("identity"))
+                .and_then(|v| v.as_str())
+                .unwrap_or(DEFAULT_IDENTITY);
+            let out = format!(
+                "Lineage for identity '{}'\n  Root (no spawn record — this is a root identity)\n  Depth: 0\n  Authority: * (full)",
+                label
+            );
+            return tool_ok(id, out);
+        }
 
-        let identity_id = anchor.id();
+        let (label, identity_id) = match self.spawn_subject(args, &records) {
+            Ok(subject) => subject,
+            Err(e) => return tool_error(id, e),
+        };
 
-        // Check if this identity appears as a child in any spawn record
-        let as_child = records.iter().find(|r| r.child_id == identity_id);
+        let authority = match agentic_identity::spawn::authority_for(&identity_id, &records) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to compute authority: {e}")),
+        };
 
-        match as_child {
+        let record = match authority
+            .spawn_id
+            .as_ref()
+            .and_then(|sid| records.iter().find(|r| r.id == *sid))
+        {
+            Some(r) => r,
             None => {
                 // Root identity
                 let out = format!(
                     "Lineage for identity '{}'\n  Root (no spawn record — this is a root identity)\n  Depth: 0\n  Authority: * (full)",
-                    name
+                    label
                 );
-                tool_ok(id, out)
+                return tool_ok(id, out);
             }
-            Some(record) => {
-                let ancestors = agentic_identity::spawn::get_ancestors(&identity_id, &records)
-                    .unwrap_or_default();
-                let authority =
-                    agentic_identity::spawn::get_effective_authority(&identity_id, &records)
-                        .unwrap_or_default();
-                let caps: Vec<&str> = authority.iter().map(|c| c.uri.as_str()).collect();
-                let status = if record.terminated {
-                    "TERMINATED"
-                } else {
-                    "active"
-                };
+        };
 
-                let out = format!(
-                    "Lineage for identity '{}'\n  Status: {}\n  Parent: {}\n  Spawn ID: {}\n  Type: {}\n  Depth: {}\n  Authority: {}",
-                    name,
-                    status,
-                    record.parent_id,
-                    record.id,
-                    record.spawn_type.as_tag(),
-                    ancestors.len(),
-                    caps.join(", ")
-                );
-                tool_ok(id, out)
-            }
+        let caps: Vec<&str> = authority
+            .effective_authority
+            .iter()
+            .map(|c| c.uri.as_str())
+            .collect();
+        let status = if record.terminated {
+            "TERMINATED"
+        } else if !authority.active {
+            "inactive (ancestor terminated or expired)"
+        } else {
+            "active"
+        };
+
+        let mut out = format!(
+            "Lineage for identity '{}'\n  Status: {}\n  Parent: {}\n  Spawn ID: {}\n  Type: {}\n  Child file: {}\n  Depth: {}\n  Authority: {}",
+            label,
+            status,
+            record.parent_id,
+            record.id,
+            record.spawn_type.as_tag(),
+            self.child_file_label(record),
+            authority.spawn_depth,
+            caps.join(", ")
+        );
+        if record.constraints.has_budget() {
+            let budgets: Vec<String> = BudgetResource::ALL
+                .iter()
+                .filter_map(|&resource| {
+                    let limit = record.constraints.budget(resource)?;
+                    let used = record.consumption.used(resource);
+                    Some(format!("{} {used}/{limit}", resource.as_tag()))
+                })
+                .collect();
+            out.push_str(&format!("\n  Budget: {}", budgets.join(", ")));
         }
+        tool_ok(id, out)
     }
 
     // ── Tool: spawn_authority ─────────────────────────────────────────────────
 
     fn tool_spawn_authority(&self, id: Value, args: &Value) -> Value {
-        let name = args
-            .get("identity")
-            .and_then(|v| v.as_str())
-            .unwrap_or(DEFAULT_IDENTITY);
-
-        // Load the identity
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
-            Ok(a) => a,
-            Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
-        };
-
-        let identity_id = anchor.id();
         let records = SpawnStore::new(&self.spawn_dir)
             .ok()
             .and_then(|s| s.load_all().ok())
             .unwrap_or_default();
 
-        let authority = agentic_identity::spawn::get_effective_authority(&identity_id, &records)
-            .unwrap_or_default();
+        let (label, identity_id) = match self.spawn_subject(args, &records) {
+            Ok(subject) => subject,
+            Err(e) => return tool_error(id, e),
+        };
+
+        let authority = match agentic_identity::spawn::authority_for(&identity_id, &records) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to compute authority: {e}")),
+        };
 
-        let caps: Vec<&str> = authority.iter().map(|c| c.uri.as_str()).collect();
-        let label = if caps.len() == 1 && caps[0] == "*" {
+        let caps: Vec<&str> = authority
+            .effective_authority
+            .iter()
+            .map(|c| c.uri.as_str())
+            .collect();
+        let label_caps = if authority.is_root {
             format!("{} (root identity — full authority)", caps.join(", "))
         } else if caps.is_empty() {
             "none (terminated or expired)".to_string()
@@ -3075,10 +6683,59 @@ impl McpServer {
             caps.join(", ")
         };
 
-        let out = format!("Effective authority for identity '{}'\n  {}", name, label);
+        let mut out = format!(
+            "Effective authority for identity '{}'\n  {}",
+            label, label_caps
+        );
+        if !authority.is_root {
+            let ceiling: Vec<&str> = authority
+                .authority_ceiling
+                .iter()
+                .map(|c| c.uri.as_str())
+                .collect();
+            out.push_str(&format!("\n  Ceiling: {}", ceiling.join(", ")));
+        }
         tool_ok(id, out)
     }
 
+    // ── Tool: spawn_tree ──────────────────────────────────────────────────────
+
+    fn tool_spawn_tree(&self, id: Value, args: &Value) -> Value {
+        let format = args
+            .get("format")
+            .and_then(|v| v.as_str())
+            .unwrap_or("text");
+        if !matches!(format, "text" | "json" | "mermaid") {
+            return tool_error(
+                id,
+                format!("unknown format '{format}' (expected text, json or mermaid)"),
+            );
+        }
+        let records = match SpawnStore::new(&self.spawn_dir).and_then(|s| s.load_all()) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, format!("failed to load spawn records: {e}")),
+        };
+
+        let subtree = ["identity", "identity_id", "spawn_id"]
+            .iter()
+            .any(|key| args.get(key).is_some());
+        let tree = if subtree {
+            match self.spawn_subject(args, &records) {
+                Ok((_, identity)) => SpawnTree::rooted_at(&identity, &records),
+                Err(e) => return tool_error(id, e),
+            }
+        } else {
+            SpawnTree::build(&records)
+        };
+
+        match format {
+            "json" => tool_ok(id, serde_json::to_string_pretty(&tree).unwrap()),
+            "mermaid" => tool_ok(id, tree.to_mermaid()),
+            _ if tree.is_empty() => tool_ok(id, "No spawned identities"),
+            _ => tool_ok(id, tree.render_text().trim_end().to_string()),
+        }
+    }
+
     // ── Tool: competence_record ─────────────────────────────────────────────
 
     fn tool_competence_record(&self, id: Value, args: &Value) -> Value {
@@ -3099,8 +6756,7 @@ impl McpServer {
             None => return tool_error(id, "receipt_id is required"),
         };
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -3122,7 +6778,10 @@ impl McpServer {
             }
             _ => return tool_error(id, "outcome must be 'success', 'failure', or 'partial'"),
         };
-        let receipt_id = ReceiptId(receipt_id_str.to_string());
+        let receipt_id = match ReceiptId::parse(receipt_id_str) {
+            Ok(r) => r,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
 
         match agentic_identity::competence::record_attempt(
             &anchor,
@@ -3133,6 +6792,11 @@ impl McpServer {
             None,
         ) {
             Ok(attempt) => {
+                if let Err(e) =
+                    CompetenceStore::new(&self.competence_dir).and_then(|s| s.save(&attempt))
+                {
+                    return tool_error(id, format!("failed to save attempt: {e}"));
+                }
                 let out = format!(
                     "Competence attempt recorded\n  Attempt ID: {}\n  Domain: {}\n  Outcome: {:?}\n  Receipt: {}\n  Timestamp: {}",
                     attempt.attempt_id.0, attempt.domain.0, attempt.outcome, receipt_id.0, micros_to_rfc3339(attempt.timestamp)
@@ -3146,10 +6810,48 @@ impl McpServer {
     // ── Tool: competence_show ────────────────────────────────────────────────
 
     fn tool_competence_show(&self, id: Value, args: &Value) -> Value {
-        let domain_str = args.get("domain").and_then(|v| v.as_str()).unwrap_or("*");
+        let name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+        let domain_str = match args.get("domain").and_then(|v| v.as_str()) {
+            Some(d) => d,
+            None => return self.tool_competence_list(id, args),
+        };
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let domain = agentic_identity::competence::CompetenceDomain::new(domain_str);
+        let record = match CompetenceStore::new(&self.competence_dir)
+            .and_then(|s| s.competence(&doc.id, &domain))
+        {
+            Ok(Some(r)) => r,
+            Ok(None) => {
+                return tool_ok(
+                    id,
+                    format!(
+                        "Competence record for domain '{domain_str}'\n  No attempts recorded yet\n  (Use competence_record to track outcomes)"
+                    ),
+                )
+            }
+            Err(e) => return tool_error(id, format!("failed to load competence history: {e}")),
+        };
+
         let out = format!(
-            "Competence record for domain '{}'\n  No attempts recorded yet\n  (Use competence_record to track outcomes)",
-            domain_str
+            "Competence record for domain '{}'\n  Identity: {}\n  Attempts: {} ({} succeeded, {} failed, {} partial)\n  Success rate: {:.1}%\n  Current streak: {}\n  Best streak: {}\n  First attempt: {}\n  Last attempt: {}",
+            record.domain.0,
+            record.identity,
+            record.total_attempts,
+            record.successes,
+            record.failures,
+            record.partial_count,
+            record.success_rate * 100.0,
+            record.streak_current,
+            record.streak_best,
+            micros_to_rfc3339(record.first_attempt),
+            micros_to_rfc3339(record.last_attempt)
         );
         tool_ok(id, out)
     }
@@ -3169,29 +6871,46 @@ impl McpServer {
         let min_attempts = args
             .get("min_attempts")
             .and_then(|v| v.as_u64())
-            .unwrap_or(3) as usize;
+            .unwrap_or(3);
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
 
         let domain = agentic_identity::competence::CompetenceDomain::new(domain_str);
-        let _requirement = agentic_identity::competence::CompetenceRequirement {
-            domain: domain.clone(),
-            min_success_rate: min_rate,
-            min_attempts: min_attempts as u64,
-            min_streak: None,
-            max_age_seconds: None,
-        };
+        let attempts =
+            match CompetenceStore::new(&self.competence_dir).and_then(|s| s.load_domain(&domain)) {
+                Ok(a) => a,
+                Err(e) => return tool_error(id, format!("failed to load competence history: {e}")),
+            };
 
-        // For now, we have no persistent attempt store yet — return a descriptive message
-        let out = format!(
-            "Competence proof generation for domain '{}'\n  Identity: {}\n  Required success rate: {:.0}%\n  Min attempts: {}\n  Status: No attempts recorded yet — use competence_record first",
-            domain_str, anchor.id(), min_rate * 100.0, min_attempts
-        );
-        tool_ok(id, out)
+        match agentic_identity::competence::generate_proof(
+            &anchor,
+            domain,
+            min_attempts,
+            min_rate,
+            None,
+            None,
+            &attempts,
+        ) {
+            Ok(proof) => {
+                let out = format!(
+                    "Competence proof generated\n  Proof ID: {}\n  Identity: {}\n  Domain: {}\n  Attempts: {} (required {})\n  Success rate: {:.1}% (required {:.0}%)\n  Evidence sampled: {}\n  Generated: {}",
+                    proof.proof_id.0,
+                    proof.identity,
+                    proof.domain.0,
+                    proof.claim.actual_attempts,
+                    min_attempts,
+                    proof.claim.actual_success_rate * 100.0,
+                    min_rate * 100.0,
+                    proof.evidence_sample.len(),
+                    micros_to_rfc3339(proof.generated_at)
+                );
+                tool_ok(id, out)
+            }
+            Err(e) => tool_error(id, format!("competence not proven for '{domain_str}': {e}")),
+        }
     }
 
     // ── Tool: competence_verify ──────────────────────────────────────────────
@@ -3201,17 +6920,52 @@ impl McpServer {
             Some(p) => p,
             None => return tool_error(id, "proof_id is required"),
         };
+        let status = VerificationOutcome::ProofNotFound;
         let out = format!(
-            "Competence proof verification\n  Proof ID: {}\n  Status: Proof not found (competence proofs are not yet persisted to disk)",
-            proof_id
+            "Competence proof verification\n  Proof ID: {}\n  Status: {} (competence proofs are not yet persisted to disk)",
+            proof_id,
+            self.verification_messages.render(status)
         );
-        tool_ok(id, out)
+        tool_ok_with_outcomes(id, out, json!({"result": status.code()}))
     }
 
     // ── Tool: competence_list ────────────────────────────────────────────────
 
-    fn tool_competence_list(&self, id: Value, _args: &Value) -> Value {
-        tool_ok(id, "Competence domains: (none recorded yet)\n  Use competence_record to begin tracking outcomes.".to_string())
+    fn tool_competence_list(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let attempts = match CompetenceStore::new(&self.competence_dir).and_then(|s| s.load_all()) {
+            Ok(a) => a,
+            Err(e) => return tool_error(id, format!("failed to load competence history: {e}")),
+        };
+
+        let mut records = agentic_identity::competence::list_competences(&doc.id, &attempts);
+        if records.is_empty() {
+            return tool_ok(
+                id,
+                "Competence domains: (none recorded yet)\n  Use competence_record to begin tracking outcomes.".to_string(),
+            );
+        }
+        records.sort_by(|a, b| a.domain.0.cmp(&b.domain.0));
+
+        let mut out = format!("Competence domains for '{name}': {}", records.len());
+        for record in &records {
+            out.push_str(&format!(
+                "\n  {}: {} attempts, {:.1}% success, best streak {}",
+                record.domain.0,
+                record.total_attempts,
+                record.success_rate * 100.0,
+                record.streak_best
+            ));
+        }
+        tool_ok(id, out)
     }
 
     // ── Tool: negative_prove ─────────────────────────────────────────────────
@@ -3226,23 +6980,34 @@ impl McpServer {
             None => return tool_error(id, "capability is required"),
         };
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
+        let store = match NegativeStore::new(&self.negative_dir) {
+            Ok(s) => s,
+            Err(e) => return tool_error(id, format!("failed to open negative store: {e}")),
+        };
+        let declarations = match store.declarations_for(&anchor.id()) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to load declarations: {e}")),
+        };
 
         // Root identities have full authority — check against ceiling (which for root is everything)
         let ceiling: Vec<String> = vec![];
         let spawn_records: Vec<agentic_identity::spawn::SpawnRecord> = vec![];
 
-        match agentic_identity::negative::prove_cannot(
-            &anchor,
-            capability,
-            &ceiling,
-            &spawn_records,
-        ) {
+        let proof =
+            agentic_identity::negative::prove_cannot(&anchor, capability, &ceiling, &spawn_records)
+                .or_else(|e| {
+                    agentic_identity::negative::prove_declared(&anchor, capability, &declarations)
+                        .map_err(|_| e)
+                });
+        match proof {
             Ok(proof) => {
+                if let Err(e) = store.save_proof(&proof) {
+                    return tool_error(id, format!("failed to save proof: {e}"));
+                }
                 let out = format!(
                     "Negative capability proof generated\n  Proof ID: {}\n  Capability: {}\n  Reason: {:?}\n  Timestamp: {}",
                     proof.proof_id.0, proof.cannot_do, proof.reason, micros_to_rfc3339(proof.generated_at)
@@ -3256,15 +7021,78 @@ impl McpServer {
     // ── Tool: negative_verify ────────────────────────────────────────────────
 
     fn tool_negative_verify(&self, id: Value, args: &Value) -> Value {
-        let proof_id = match args.get("proof_id").and_then(|v| v.as_str()) {
+        let proof_id_str = match args.get("proof_id").and_then(|v| v.as_str()) {
             Some(p) => p,
             None => return tool_error(id, "proof_id is required"),
         };
-        let out = format!(
-            "Negative proof verification\n  Proof ID: {}\n  Status: Proof not found (negative proofs are not yet persisted to disk)",
-            proof_id
+        let proof_id = match agentic_identity::negative::NegativeProofId::parse(proof_id_str) {
+            Ok(p) => p,
+            Err(e) => return tool_error(id, e.to_string()),
+        };
+
+        let messages = &self.verification_messages;
+        let proof =
+            match NegativeStore::new(&self.negative_dir).and_then(|s| s.load_proof(&proof_id)) {
+                Ok(p) => p,
+                Err(agentic_identity::IdentityError::NotFound(_)) => {
+                    let status = VerificationOutcome::ProofNotFound;
+                    let out = format!(
+                        "Negative proof verification\n  Proof ID: {}\n  Status: {}",
+                        proof_id,
+                        messages.render(status)
+                    );
+                    return tool_ok_with_outcomes(id, out, json!({"result": status.code()}));
+                }
+                Err(e) => return tool_error(id, format!("failed to load proof: {e}")),
+            };
+
+        // The proof carries only the prover's ID; its key comes from the
+        // matching local identity.
+        let key = self
+            .identity_documents()
+            .into_iter()
+            .map(|(_, doc)| doc)
+            .find(|doc| doc.id == proof.identity)
+            .map(|doc| doc.verifying_key());
+        let key = match key {
+            Some(Ok(k)) => k,
+            Some(Err(e)) => {
+                return tool_error(id, format!("invalid key for {}: {e}", proof.identity))
+            }
+            None => {
+                return tool_error(
+                    id,
+                    format!(
+                        "no local identity {} to verify the proof against",
+                        proof.identity
+                    ),
+                )
+            }
+        };
+        let verification = match agentic_identity::negative::verify_negative_proof(&proof, &key) {
+            Ok(v) => v,
+            Err(e) => return tool_error(id, format!("verification error: {e}")),
+        };
+
+        let signature = VerificationOutcome::signature(verification.signature_valid);
+        let result = VerificationOutcome::result(verification.is_valid);
+        let mut out = format!(
+            "Negative proof verification\n  Proof ID: {}\n  Identity: {}\n  Capability: {}\n  Reason: {:?}\n  Signature: {}\n  Status: {}",
+            proof.proof_id,
+            proof.identity,
+            proof.cannot_do,
+            proof.reason,
+            messages.render(signature),
+            messages.render(result)
         );
-        tool_ok(id, out)
+        for error in &verification.errors {
+            out.push_str(&format!("\n  Error: {error}"));
+        }
+        tool_ok_with_outcomes(
+            id,
+            out,
+            json!({"signature": signature.code(), "result": result.code()}),
+        )
     }
 
     // ── Tool: negative_declare ───────────────────────────────────────────────
@@ -3287,8 +7115,7 @@ impl McpServer {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
+        let anchor = match self.load_anchor(name) {
             Ok(a) => a,
             Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
         };
@@ -3303,6 +7130,11 @@ impl McpServer {
             vec![],
         ) {
             Ok(declaration) => {
+                if let Err(e) = NegativeStore::new(&self.negative_dir)
+                    .and_then(|s| s.save_declaration(&declaration))
+                {
+                    return tool_error(id, format!("failed to save declaration: {e}"));
+                }
                 let out = format!(
                     "Negative declaration created\n  Declaration ID: {}\n  Capabilities: {}\n  Reason: {}\n  Permanent: {}\n  Timestamp: {}",
                     declaration.declaration_id.0, capabilities.join(", "), reason, permanent, micros_to_rfc3339(declaration.declared_at)
@@ -3315,8 +7147,42 @@ impl McpServer {
 
     // ── Tool: negative_list ──────────────────────────────────────────────────
 
-    fn tool_negative_list(&self, id: Value, _args: &Value) -> Value {
-        tool_ok(id, "Negative declarations: (none recorded yet)\n  Use negative_declare to add self-imposed restrictions.".to_string())
+    fn tool_negative_list(&self, id: Value, args: &Value) -> Value {
+        let name = args
+            .get("identity")
+            .and_then(|v| v.as_str())
+            .unwrap_or(DEFAULT_IDENTITY);
+
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let declarations = match NegativeStore::new(&self.negative_dir)
+            .and_then(|s| s.declarations_for(&doc.id))
+        {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to load declarations: {e}")),
+        };
+
+        if declarations.is_empty() {
+            return tool_ok(id, "Negative declarations: (none recorded yet)\n  Use negative_declare to add self-imposed restrictions.".to_string());
+        }
+        let mut out = format!("Negative declarations for '{name}': {}", declarations.len());
+        for declaration in &declarations {
+            out.push_str(&format!(
+                "\n  {} [{}]{}\n    Reason: {}\n    Declared: {}",
+                declaration.declaration_id,
+                declaration.cannot_do.join(", "),
+                if declaration.permanent {
+                    " (permanent)"
+                } else {
+                    ""
+                },
+                declaration.reason,
+                micros_to_rfc3339(declaration.declared_at)
+            ));
+        }
+        tool_ok(id, out)
     }
 
     // ── Tool: negative_check ─────────────────────────────────────────────────
@@ -3331,18 +7197,22 @@ impl McpServer {
             None => return tool_error(id, "capability is required"),
         };
 
-        let path = self.identity_dir.join(format!("{name}.aid"));
-        let anchor = match load_identity(&path, MCP_PASSPHRASE) {
-            Ok(a) => a,
-            Err(e) => return tool_error(id, format!("failed to load identity '{name}': {e}")),
+        let doc = match self.read_document(name) {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to read identity '{name}': {e}")),
+        };
+        let declarations = match NegativeStore::new(&self.negative_dir)
+            .and_then(|s| s.declarations_for(&doc.id))
+        {
+            Ok(d) => d,
+            Err(e) => return tool_error(id, format!("failed to load declarations: {e}")),
         };
 
         let ceiling: Vec<String> = vec![];
-        let declarations: Vec<agentic_identity::negative::NegativeDeclaration> = vec![];
         let spawn_records: Vec<agentic_identity::spawn::SpawnRecord> = vec![];
 
         let result = agentic_identity::negative::is_impossible(
-            &anchor.id(),
+            &doc.id,
             capability,
             &ceiling,
             &spawn_records,
@@ -3386,6 +7256,12 @@ impl McpServer {
                         "description": "Most recent action receipts (up to 20)",
                         "mimeType": "application/json"
                     },
+                    {
+                        "uri": "aid://receipts/all",
+                        "name": "All Receipts",
+                        "description": "Every action receipt as NDJSON, ending with a summary line",
+                        "mimeType": "application/x-ndjson"
+                    },
                     {
                         "uri": "aid://trust/granted",
                         "name": "Granted Trust",
@@ -3397,6 +7273,12 @@ impl McpServer {
                         "name": "Received Trust",
                         "description": "Trust grants received by this identity",
                         "mimeType": "application/json"
+                    },
+                    {
+                        "uri": "aid://trust/revocations",
+                        "name": "Revocation List",
+                        "description": "Signed list of every revocation issued by the default identity",
+                        "mimeType": "application/json"
                     }
                 ]
             }),
@@ -3420,23 +7302,36 @@ impl McpServer {
             match rest {
                 "granted" => self.resource_trust_list(id, "granted"),
                 "received" => self.resource_trust_list(id, "received"),
+                "revocations" => self.resource_revocation_list(id),
                 trust_id => self.resource_trust_grant(id, trust_id),
             }
         } else if uri == "aid://receipts/recent" {
             self.resource_receipts_recent(id)
+        } else if uri == "aid://receipts/all" {
+            match self.export_receipts_ndjson(&ReceiptExportFilter::default()) {
+                Ok(text) => ok_result(
+                    id,
+                    json!({
+                        "contents": [{
+                            "uri": "aid://receipts/all",
+                            "mimeType": "application/x-ndjson",
+                            "text": text
+                        }]
+                    }),
+                ),
+                Err(e) => rpc_error(id, -32602, format!("receipt export failed: {e}")),
+            }
         } else {
             rpc_error(id, -32602, format!("unknown resource URI: {uri}"))
         }
     }
 
     fn resource_identity(&self, id: Value, name: &str) -> Value {
-        let path = self.identity_dir.join(format!("{name}.aid"));
-
-        if !path.exists() {
+        if !self.identity_exists(name) {
             return rpc_error(id, -32602, format!("identity '{name}' not found"));
         }
 
-        match read_public_document(&path) {
+        match self.read_document(name) {
             Ok(doc) => {
                 let text = serde_json::to_string_pretty(&doc)
                     .unwrap_or_else(|e| format!("serialization error: {e}"));
@@ -3461,8 +7356,11 @@ impl McpServer {
             Err(e) => return rpc_error(id, -32602, format!("receipt store error: {e}")),
         };
 
-        let rid = ReceiptId(receipt_id.to_string());
-        match store.load(&rid) {
+        let rid = match ReceiptId::parse(receipt_id) {
+            Ok(r) => r,
+            Err(e) => return rpc_error(id, -32602, e.to_string()),
+        };
+        match store.load(&rid) {
             Ok(receipt) => {
                 let text = serde_json::to_string_pretty(&receipt)
                     .unwrap_or_else(|e| format!("serialization error: {e}"));
@@ -3487,7 +7385,10 @@ impl McpServer {
             Err(e) => return rpc_error(id, -32602, format!("trust store error: {e}")),
         };
 
-        let tid = TrustId(trust_id.to_string());
+        let tid = match TrustId::parse(trust_id) {
+            Ok(t) => t,
+            Err(e) => return rpc_error(id, -32602, e.to_string()),
+        };
         match store.load_grant(&tid) {
             Ok(grant) => {
                 let text = serde_json::to_string_pretty(&grant)
@@ -3545,23 +7446,57 @@ impl McpServer {
         )
     }
 
+    fn resource_revocation_list(&self, id: Value) -> Value {
+        if !self.identity_exists(DEFAULT_IDENTITY) {
+            return rpc_error(
+                id,
+                -32602,
+                format!("identity '{DEFAULT_IDENTITY}' not found"),
+            );
+        }
+        let anchor = match self.load_anchor(DEFAULT_IDENTITY) {
+            Ok(a) => a,
+            Err(e) => return rpc_error(id, -32602, format!("failed to load identity: {e}")),
+        };
+
+        let store = match TrustStore::new(&self.trust_dir) {
+            Ok(s) => s,
+            Err(e) => return rpc_error(id, -32602, format!("trust store error: {e}")),
+        };
+        let list = match store
+            .revocations_by(&anchor.id())
+            .and_then(|revocations| RevocationList::sign(&anchor, revocations))
+        {
+            Ok(l) => l,
+            Err(e) => return rpc_error(id, -32602, format!("failed to sign revocation list: {e}")),
+        };
+
+        let text = serde_json::to_string_pretty(&list)
+            .unwrap_or_else(|e| format!("serialization error: {e}"));
+        ok_result(
+            id,
+            json!({
+                "contents": [{
+                    "uri": "aid://trust/revocations",
+                    "mimeType": "application/json",
+                    "text": text
+                }]
+            }),
+        )
+    }
+
     fn resource_receipts_recent(&self, id: Value) -> Value {
         let store = match ReceiptStore::new(&self.receipt_dir) {
             Ok(s) => s,
             Err(e) => return rpc_error(id, -32602, format!("receipt store error: {e}")),
         };
 
-        let all_ids = match store.list() {
-            Ok(i) => i,
+        let mut receipts = match store.load_all() {
+            Ok(r) => r,
             Err(e) => return rpc_error(id, -32602, format!("failed to list receipts: {e}")),
         };
 
-        let mut receipts: Vec<_> = all_ids
-            .iter()
-            .filter_map(|rid| store.load(rid).ok())
-            .collect();
-
-        receipts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+        receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
         receipts.truncate(20);
 
         let values: Vec<Value> = receipts
@@ -3592,74 +7527,61 @@ impl McpServer {
             _ => return tool_error(id, "'claim' is required"),
         };
 
-        let claim_lower = claim.to_lowercase();
-        let claim_words: Vec<&str> = claim_lower.split_whitespace().collect();
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
+        let similarity = &*self.similarity;
         let mut evidence = Vec::new();
 
-        // Search trust grants
-        if let Ok(store) = TrustStore::new(&self.trust_dir) {
-            for grant_ids in [store.list_granted().ok(), store.list_received().ok()]
-                .into_iter()
-                .flatten()
-            {
-                for gid in grant_ids.iter().take(100) {
-                    if let Ok(grant) = store.load_grant(gid) {
-                        let mut score = 0.0f32;
-                        for cap in &grant.capabilities {
-                            if claim_lower.contains(&cap.uri.to_lowercase()) {
-                                score += 1.0;
-                            }
-                            let cap_lower = cap.uri.to_lowercase();
-                            let cap_words: Vec<&str> = cap_lower
-                                .split(':')
-                                .flat_map(|s| s.split_whitespace())
-                                .collect();
-                            let overlap = claim_words
-                                .iter()
-                                .filter(|w| {
-                                    cap_words
-                                        .iter()
-                                        .any(|cw| cw.contains(**w) || w.contains(cw))
-                                })
-                                .count();
-                            score += overlap as f32 * 0.3;
-                        }
-                        if score > 0.0 {
-                            evidence.push(json!({
-                                "type": "trust_grant",
-                                "id": grant.id.0,
-                                "capabilities": grant.capabilities.iter().map(|c| &c.uri).collect::<Vec<_>>(),
-                                "grantor": grant.grantor.0,
-                                "grantee": grant.grantee.0,
-                                "score": score,
-                            }));
-                        }
-                    }
+        let mut cache = self.grounding_cache.borrow_mut();
+        let (grants, receipts) = cache.stores(&self.trust_dir, &self.receipt_dir);
+
+        // Search trust grants the provider considers candidates
+        if let Some(grants) = grants {
+            for gid in grants.candidates(similarity, claim) {
+                let Some(grant) = grants.get(&gid) else {
+                    continue;
+                };
+                if !grant.in_scope(&scope) {
+                    continue;
+                }
+                let score: f32 = grant
+                    .capabilities
+                    .iter()
+                    .map(|cap| similarity.capability_score(claim, cap))
+                    .sum();
+                if score > 0.0 {
+                    evidence.push(json!({
+                        "type": "trust_grant",
+                        "id": grant.id,
+                        "capabilities": grant.capabilities,
+                        "grantor": grant.grantor.0,
+                        "grantee": grant.grantee.0,
+                        "score": score,
+                    }));
                 }
             }
         }
 
-        // Search receipts
-        if let Ok(store) = ReceiptStore::new(&self.receipt_dir) {
-            if let Ok(receipt_ids) = store.list() {
-                for rid in receipt_ids.iter().take(100) {
-                    if let Ok(receipt) = store.load(rid) {
-                        let action_lower = receipt.action.description.to_lowercase();
-                        let overlap = claim_words
-                            .iter()
-                            .filter(|w| action_lower.contains(**w))
-                            .count();
-                        if overlap > 0 {
-                            let score = overlap as f32 / claim_words.len().max(1) as f32;
-                            evidence.push(json!({
-                                "type": "receipt",
-                                "id": receipt.id.0,
-                                "action_type": format!("{:?}", receipt.action_type),
-                                "action": receipt.action.description,
-                                "score": score,
-                            }));
-                        }
-                    }
+        // Search receipts the provider considers candidates
+        if let Some(receipts) = receipts {
+            for rid in receipts.candidates(similarity, claim) {
+                let Some(receipt) = receipts.get(&rid) else {
+                    continue;
+                };
+                if !scope.matches(&receipt.actor) {
+                    continue;
+                }
+                let score = similarity.text_score(claim, &receipt.description);
+                if score > 0.0 {
+                    evidence.push(json!({
+                        "type": "receipt",
+                        "id": receipt.id,
+                        "action_type": receipt.action_type,
+                        "action": receipt.description,
+                        "score": score,
+                    }));
                 }
             }
         }
@@ -3699,75 +7621,70 @@ impl McpServer {
             .and_then(|v| v.as_u64())
             .unwrap_or(10) as usize;
 
-        let query_lower = query.to_lowercase();
-        let query_words: Vec<&str> = query_lower.split_whitespace().collect();
-        let mut evidence: Vec<(f32, Value)> = Vec::new();
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
 
-        // Search trust grants
-        if let Ok(store) = TrustStore::new(&self.trust_dir) {
-            for grant_ids in [store.list_granted().ok(), store.list_received().ok()]
-                .into_iter()
-                .flatten()
-            {
-                for gid in grant_ids.iter().take(100) {
-                    if let Ok(grant) = store.load_grant(gid) {
-                        let cap_str: String = grant
-                            .capabilities
-                            .iter()
-                            .map(|c| c.uri.to_lowercase())
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                        let overlap = query_words.iter().filter(|w| cap_str.contains(**w)).count();
-                        if overlap > 0 {
-                            let score = overlap as f32 / query_words.len().max(1) as f32;
-                            evidence.push((score, json!({
-                                "type": "trust_grant",
-                                "id": grant.id.0,
-                                "grantor": grant.grantor.0,
-                                "grantee": grant.grantee.0,
-                                "capabilities": grant.capabilities.iter().map(|c| &c.uri).collect::<Vec<_>>(),
-                                "granted_at": micros_to_rfc3339(grant.granted_at),
-                                "score": score,
-                            })));
-                        }
-                    }
-                }
+        // Lexical matching scores from the text indexes alone, so only the
+        // best matches are summarized, skipping any outside the requested
+        // scope.
+        let mut cache = self.grounding_cache.borrow_mut();
+        let (mut grants, mut receipts) = cache.stores(&self.trust_dir, &self.receipt_dir);
+        let mut ranked: Vec<(f32, bool, String)> = Vec::new();
+        if let Some(grants) = grants.as_deref_mut() {
+            for (gid, score) in grants.rank(&*self.similarity, query) {
+                ranked.push((score, true, gid));
             }
         }
-
-        // Search receipts
-        if let Ok(store) = ReceiptStore::new(&self.receipt_dir) {
-            if let Ok(receipt_ids) = store.list() {
-                for rid in receipt_ids.iter().take(200) {
-                    if let Ok(receipt) = store.load(rid) {
-                        let action_lower = receipt.action.description.to_lowercase();
-                        let overlap = query_words
-                            .iter()
-                            .filter(|w| action_lower.contains(**w))
-                            .count();
-                        if overlap > 0 {
-                            let score = overlap as f32 / query_words.len().max(1) as f32;
-                            evidence.push((
-                                score,
-                                json!({
-                                    "type": "receipt",
-                                    "id": receipt.id.0,
-                                    "actor": receipt.actor.0,
-                                    "action_type": format!("{:?}", receipt.action_type),
-                                    "action": receipt.action.description,
-                                    "timestamp": micros_to_rfc3339(receipt.timestamp),
-                                    "score": score,
-                                }),
-                            ));
-                        }
-                    }
-                }
+        if let Some(receipts) = receipts.as_deref_mut() {
+            for (rid, score) in receipts.rank(&*self.similarity, query) {
+                ranked.push((score, false, rid));
             }
         }
+        // Stable, so grants stay ahead of receipts with the same score.
+        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
 
-        evidence.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
-        evidence.truncate(max_results);
-        let items: Vec<Value> = evidence.into_iter().map(|(_, v)| v).collect();
+        let mut items: Vec<Value> = Vec::new();
+        for (score, is_grant, doc_id) in ranked {
+            if items.len() >= max_results {
+                break;
+            }
+            let item = if is_grant {
+                let Some(grant) = grants.as_deref_mut().and_then(|g| g.get(&doc_id)) else {
+                    continue;
+                };
+                if !grant.in_scope(&scope) {
+                    continue;
+                }
+                json!({
+                    "type": "trust_grant",
+                    "id": grant.id,
+                    "grantor": grant.grantor.0,
+                    "grantee": grant.grantee.0,
+                    "capabilities": grant.capabilities,
+                    "granted_at": micros_to_rfc3339(grant.granted_at),
+                    "score": score,
+                })
+            } else {
+                let Some(receipt) = receipts.as_deref_mut().and_then(|r| r.get(&doc_id)) else {
+                    continue;
+                };
+                if !scope.matches(&receipt.actor) {
+                    continue;
+                }
+                json!({
+                    "type": "receipt",
+                    "id": receipt.id,
+                    "actor": receipt.actor.0,
+                    "action_type": receipt.action_type,
+                    "action": receipt.description,
+                    "timestamp": micros_to_rfc3339(receipt.timestamp),
+                    "score": score,
+                })
+            };
+            items.push(item);
+        }
 
         tool_ok(
             id,
@@ -3787,6 +7704,10 @@ impl McpServer {
         };
         let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
 
+        let scope = match self.actor_scope(args) {
+            Ok(scope) => scope,
+            Err(e) => return tool_error(id, e),
+        };
         let query_lower = query.to_lowercase();
         let mut suggestions: Vec<Value> = Vec::new();
 
@@ -3798,6 +7719,9 @@ impl McpServer {
             {
                 for gid in grant_ids.iter().take(50) {
                     if let Ok(grant) = store.load_grant(gid) {
+                        if !scope.matches_grant(&grant) {
+                            continue;
+                        }
                         for cap in &grant.capabilities {
                             if cap.uri.to_lowercase().contains(&query_lower)
                                 || query_lower.contains(&cap.uri.to_lowercase())
@@ -3819,6 +7743,9 @@ impl McpServer {
             if let Ok(receipt_ids) = store.list() {
                 for rid in receipt_ids.iter().take(50) {
                     if let Ok(receipt) = store.load(rid) {
+                        if !scope.matches(&receipt.actor) {
+                            continue;
+                        }
                         let desc_lower = receipt.action.description.to_lowercase();
                         if desc_lower.contains(&query_lower) || query_lower.contains(&desc_lower) {
                             suggestions.push(json!({
@@ -3993,16 +7920,118 @@ struct IdentityWorkspace {
     contexts: Vec<IdentityWorkspaceContext>,
 }
 
+/// Size and newest modification time of a store directory and the
+/// directories directly inside it. Adding, removing or rewriting a record
+/// changes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StoreStamp {
+    entries: usize,
+    newest: Option<std::time::SystemTime>,
+}
+
+impl StoreStamp {
+    fn of(dir: &std::path::Path) -> Self {
+        let mut stamp = Self {
+            entries: 0,
+            newest: None,
+        };
+        stamp.add_dir(dir, true);
+        stamp
+    }
+
+    fn add_dir(&mut self, dir: &std::path::Path, descend: bool) {
+        self.add_modified(std::fs::metadata(dir).ok());
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            self.entries += 1;
+            match entry.metadata() {
+                Ok(meta) if meta.is_dir() && descend => self.add_dir(&entry.path(), false),
+                meta => self.add_modified(meta.ok()),
+            }
+        }
+    }
+
+    fn add_modified(&mut self, meta: Option<std::fs::Metadata>) {
+        if let Some(modified) = meta.and_then(|m| m.modified().ok()) {
+            self.newest = self.newest.max(Some(modified));
+        }
+    }
+}
+
+/// One cached [`IdentityWorkspaceManager::query_all`] result.
+struct CachedQuery {
+    workspace_id: String,
+    query: String,
+    max_per_context: usize,
+    /// Per context: trust store stamp, receipt store stamp.
+    stamps: Vec<(StoreStamp, StoreStamp)>,
+    results: Vec<Value>,
+}
+
+/// Least-recently-used cache of workspace query results.
+///
+/// An entry is reused only while every store it scanned is unchanged, so a
+/// hit returns exactly what a fresh scan would. A capacity of 0 disables
+/// caching.
+struct WorkspaceQueryCache {
+    capacity: usize,
+    /// Most recently used first.
+    entries: VecDeque<CachedQuery>,
+}
+
+impl WorkspaceQueryCache {
+    fn get(
+        &mut self,
+        workspace_id: &str,
+        query: &str,
+        max_per_context: usize,
+        stamps: &[(StoreStamp, StoreStamp)],
+    ) -> Option<Vec<Value>> {
+        let pos = self.entries.iter().position(|e| {
+            e.workspace_id == workspace_id
+                && e.query == query
+                && e.max_per_context == max_per_context
+        })?;
+        let entry = self.entries.remove(pos)?;
+        if entry.stamps != stamps {
+            return None;
+        }
+        let results = entry.results.clone();
+        self.entries.push_front(entry);
+        Some(results)
+    }
+
+    fn insert(&mut self, entry: CachedQuery) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.truncate(self.capacity - 1);
+        self.entries.push_front(entry);
+    }
+
+    fn invalidate(&mut self, workspace_id: &str) {
+        self.entries.retain(|e| e.workspace_id != workspace_id);
+    }
+}
+
 struct IdentityWorkspaceManager {
     workspaces: std::collections::HashMap<String, IdentityWorkspace>,
     next_id: u64,
+    query_cache: std::cell::RefCell<WorkspaceQueryCache>,
 }
 
 impl IdentityWorkspaceManager {
-    fn new() -> Self {
+    /// A manager caching up to `query_cache_capacity` query results.
+    fn new(query_cache_capacity: usize) -> Self {
         Self {
             workspaces: std::collections::HashMap::new(),
             next_id: 1,
+            query_cache: std::cell::RefCell::new(WorkspaceQueryCache {
+                capacity: query_cache_capacity,
+                entries: VecDeque::new(),
+            }),
         }
     }
 
@@ -4050,6 +8079,7 @@ impl IdentityWorkspaceManager {
             trust_dir: dir.join("trust"),
             receipt_dir: dir.join("receipts"),
         });
+        self.query_cache.borrow_mut().invalidate(workspace_id);
 
         Ok(ctx_id)
     }
@@ -4075,6 +8105,8 @@ impl IdentityWorkspaceManager {
             .collect())
     }
 
+    /// Search every context's stores, reusing a cached result while none of
+    /// the stores has changed since it was computed.
     fn query_all(
         &self,
         workspace_id: &str,
@@ -4085,7 +8117,38 @@ impl IdentityWorkspaceManager {
             .workspaces
             .get(workspace_id)
             .ok_or_else(|| format!("Workspace not found: {workspace_id}"))?;
+        if self.query_cache.borrow().capacity == 0 {
+            return Ok(Self::scan(workspace, query, max_per_context));
+        }
+
+        // Stamped before scanning, so a write racing the scan leaves a
+        // stale stamp and forces a rescan next time.
+        let stamps: Vec<_> = workspace
+            .contexts
+            .iter()
+            .map(|ctx| {
+                (
+                    StoreStamp::of(&ctx.trust_dir),
+                    StoreStamp::of(&ctx.receipt_dir),
+                )
+            })
+            .collect();
+        let mut cache = self.query_cache.borrow_mut();
+        if let Some(results) = cache.get(workspace_id, query, max_per_context, &stamps) {
+            return Ok(results);
+        }
+        let results = Self::scan(workspace, query, max_per_context);
+        cache.insert(CachedQuery {
+            workspace_id: workspace_id.to_string(),
+            query: query.to_string(),
+            max_per_context,
+            stamps,
+            results: results.clone(),
+        });
+        Ok(results)
+    }
 
+    fn scan(workspace: &IdentityWorkspace, query: &str, max_per_context: usize) -> Vec<Value> {
         let query_lower = query.to_lowercase();
         let query_words: Vec<&str> = query_lower.split_whitespace().collect();
         let mut results = Vec::new();
@@ -4156,7 +8219,7 @@ impl IdentityWorkspaceManager {
             }));
         }
 
-        Ok(results)
+        results
     }
 
     fn compare(
@@ -4206,46 +8269,11 @@ impl IdentityWorkspaceManager {
 
 // ── Duration parsing ──────────────────────────────────────────────────────────
 
-/// Parse a duration string like "24h", "7d", "30d", "1h30m".
+/// Parse a duration string like "24h", "7d", "30d", "1h30m"; see
+/// [`agentic_identity::time::parse_duration_micros`].
 /// Returns duration as microseconds.
 fn parse_duration_to_micros(s: &str) -> Result<u64, String> {
-    let s = s.trim();
-
-    // Bare integer treated as hours.
-    if let Ok(n) = s.parse::<u64>() {
-        return Ok(n * 3600 * 1_000_000);
-    }
-
-    let mut total_micros: u64 = 0;
-    let mut current = String::new();
-
-    for ch in s.chars() {
-        if ch.is_ascii_digit() {
-            current.push(ch);
-        } else {
-            let val: u64 = current
-                .parse()
-                .map_err(|_| format!("invalid number in duration: {s}"))?;
-            current.clear();
-            match ch {
-                'h' => total_micros += val * 3600 * 1_000_000,
-                'd' => total_micros += val * 86400 * 1_000_000,
-                'm' => total_micros += val * 60 * 1_000_000,
-                's' => total_micros += val * 1_000_000,
-                _ => return Err(format!("unknown duration unit '{ch}' in '{s}'")),
-            }
-        }
-    }
-
-    if !current.is_empty() {
-        return Err(format!("duration '{s}' is missing a unit (h/d/m/s)"));
-    }
-
-    if total_micros == 0 {
-        return Err("duration must be > 0".to_string());
-    }
-
-    Ok(total_micros)
+    agentic_identity::time::parse_duration_micros(s).map_err(|e| e.to_string())
 }
 
 // ── main ──────────────────────────────────────────────────────────────────────
@@ -4253,7 +8281,36 @@ fn parse_duration_to_micros(s: &str) -> Result<u64, String> {
 /// Hard limit for framed stdio payloads (8 MiB).
 const MAX_CONTENT_LENGTH_BYTES: usize = 8 * 1024 * 1024;
 
-fn run_stdio_server() {
+/// Key material read from the `--identity-from-env` variable, wiped when
+/// dropped.
+struct EnvIdentity {
+    var: String,
+    material: Zeroizing<String>,
+}
+
+impl EnvIdentity {
+    /// Read `var` and remove it from the environment. Call this only while
+    /// the process is single-threaded.
+    fn take(var: String) -> Result<Self, String> {
+        let material = std::env::var(&var).map_err(|e| match e {
+            std::env::VarError::NotPresent => format!("environment variable {var} is not set"),
+            std::env::VarError::NotUnicode(_) => format!("{var} is not valid UTF-8"),
+        })?;
+        std::env::remove_var(&var);
+        Ok(Self {
+            var,
+            material: Zeroizing::new(material),
+        })
+    }
+}
+
+/// Set up logging and build the configured server, exiting on a
+/// configuration error.
+fn start_server(
+    trace: bool,
+    identity_from_env: Option<EnvIdentity>,
+    passphrase_args: &PassphraseArgs,
+) -> McpServer {
     // Log to stderr (stdout is reserved for JSON-RPC responses).
     // Use a minimal subscriber without the env-filter feature (not enabled in workspace).
     tracing_subscriber::fmt()
@@ -4261,7 +8318,32 @@ fn run_stdio_server() {
         .with_max_level(tracing::Level::WARN)
         .init();
 
-    let mut server = McpServer::new();
+    let passphrase = match startup_passphrase(passphrase_args) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    };
+    let mut server = McpServer::new(passphrase);
+    server.passphrase_sources = PassphraseSources::from_args(passphrase_args);
+    server.trace = trace;
+    server.recover_partial_writes();
+    if let Some(env) = identity_from_env {
+        if let Err(e) = server.install_env_identity(&env) {
+            eprintln!("error: {e}");
+            std::process::exit(2);
+        }
+    }
+    server
+}
+
+fn run_stdio_server(
+    trace: bool,
+    identity_from_env: Option<EnvIdentity>,
+    passphrase_args: &PassphraseArgs,
+) {
+    let mut server = start_server(trace, identity_from_env, passphrase_args);
 
     // Ghost Writer: sync identity context to Claude, Cursor, Windsurf, Cody
     let mut ghost = ghost_bridge::GhostBridge::new();
@@ -4370,6 +8452,8 @@ fn run_stdio_server() {
             write_response_framed(&stdout, &resp, framed_output);
         }
     }
+
+    server.shutdown();
 }
 
 /// Process a single JSON-RPC request through the server and ghost writer.
@@ -4420,10 +8504,116 @@ fn write_response_framed(stdout: &io::Stdout, response: &Value, framed: bool) {
     let _ = out.flush();
 }
 
+/// Serve every connection on `listen` with its own server (see [`net`]).
+#[cfg(feature = "net")]
+fn run_socket_server(
+    listen: &str,
+    allow_remote: bool,
+    trace: bool,
+    identity_from_env: Option<EnvIdentity>,
+    passphrase_args: &PassphraseArgs,
+) {
+    let addr = match net::ListenAddr::parse(listen) {
+        Ok(a) => a,
+        Err(e) => {
+            eprintln!("error: invalid --listen address: {e}");
+            std::process::exit(2);
+        }
+    };
+    let configured = start_server(trace, identity_from_env, passphrase_args);
+    let (passphrase, sources) = (configured.passphrase, configured.passphrase_sources);
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("error: failed to start async runtime: {e}");
+            std::process::exit(1);
+        }
+    };
+    let served = runtime.block_on(net::serve(addr, allow_remote, move || {
+        let mut server = McpServer::new(passphrase.clone());
+        server.passphrase_sources = sources.clone();
+        server.trace = trace;
+        server
+    }));
+    if let Err(e) = served {
+        eprintln!("error: failed to listen on {listen}: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Serve MCP over HTTP on `addr` until Ctrl-C or SIGTERM (see [`http`]).
+#[cfg(feature = "net")]
+fn run_http_server(
+    addr: &str,
+    allow_remote: bool,
+    trace: bool,
+    identity_from_env: Option<EnvIdentity>,
+    passphrase_args: &PassphraseArgs,
+) {
+    let configured = start_server(trace, identity_from_env, passphrase_args);
+    let (passphrase, sources) = (configured.passphrase, configured.passphrase_sources);
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("error: failed to start async runtime: {e}");
+            std::process::exit(1);
+        }
+    };
+    let make_server = move || {
+        let mut server = McpServer::new(passphrase.clone());
+        server.passphrase_sources = sources.clone();
+        server.trace = trace;
+        server
+    };
+    let served = runtime.block_on(http::serve(
+        addr,
+        allow_remote,
+        make_server,
+        http::shutdown_signal(),
+    ));
+    if let Err(e) = served {
+        eprintln!("error: failed to listen on {addr}: {e}");
+        std::process::exit(1);
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
+    // Taken before any thread starts: removing the variable races with
+    // other threads reading the environment.
+    let identity_from_env = cli
+        .identity_from_env
+        .map(|var| match EnvIdentity::take(var) {
+            Ok(env) => env,
+            Err(e) => {
+                eprintln!("error: {e}");
+                std::process::exit(2);
+            }
+        });
     match cli.command.unwrap_or(Command::Serve) {
-        Command::Serve => run_stdio_server(),
+        Command::Serve => {
+            #[cfg(feature = "net")]
+            if let Some(addr) = cli.http {
+                return run_http_server(
+                    &addr,
+                    cli.listen_remote,
+                    cli.trace,
+                    identity_from_env,
+                    &cli.passphrase,
+                );
+            }
+            #[cfg(feature = "net")]
+            if let Some(listen) = cli.listen {
+                return run_socket_server(
+                    &listen,
+                    cli.listen_remote,
+                    cli.trace,
+                    identity_from_env,
+                    &cli.passphrase,
+                );
+            }
+            run_stdio_server(cli.trace, identity_from_env, &cli.passphrase)
+        }
     }
 }
 
@@ -4446,18 +8636,49 @@ mod tests {
     /// Create a temporary McpServer wired to temp directories.
     fn test_server() -> (McpServer, tempfile::TempDir) {
         let tmp = tempfile::tempdir().unwrap();
-        let server = McpServer {
-            identity_dir: tmp.path().join("identity"),
-            receipt_dir: tmp.path().join("receipts"),
-            trust_dir: tmp.path().join("trust"),
-            spawn_dir: tmp.path().join("spawn"),
-            operation_log: Vec::new(),
-            session_start_time: None,
-            workspace_manager: IdentityWorkspaceManager::new(),
-        };
+        let server = server_in(tmp.path());
         (server, tmp)
     }
 
+    /// An McpServer wired to directories under `dir`.
+    fn server_in(dir: &std::path::Path) -> McpServer {
+        McpServer {
+            passphrase: LEGACY_MCP_PASSPHRASE.to_string(),
+            passphrase_sources: PassphraseSources::default(),
+            identity_dir: dir.join("identity"),
+            identity_vault: None,
+            receipt_dir: dir.join("receipts"),
+            trust_dir: dir.join("trust"),
+            spawn_dir: dir.join("spawn"),
+            continuity_dir: dir.join("continuity"),
+            competence_dir: dir.join("competence"),
+            negative_dir: dir.join("negative"),
+            key_dir: dir.join("keys"),
+            idempotency_dir: dir.join("idempotency"),
+            transaction_dir: dir.join("transactions"),
+            action_requirements: RequirementPolicy::default(),
+            enforce_action_requirements: false,
+            similarity: Box::new(LexicalSimilarity::default()),
+            grounding_cache: std::cell::RefCell::new(GroundingCache::new(true)),
+            verification_messages: OutcomeMessages::default(),
+            operation_log: VecDeque::new(),
+            operation_log_capacity: DEFAULT_OPERATION_LOG_CAPACITY,
+            audit: AuditLog::new(Some(dir.join("audit"))),
+            trace: false,
+            session_start_time: None,
+            workspace_manager: IdentityWorkspaceManager::new(DEFAULT_WORKSPACE_QUERY_CACHE),
+        }
+    }
+
+    /// Helper: register a fresh key so its identity can be a trust grantee.
+    fn registered_grantee(server: &McpServer) -> String {
+        let anchor = IdentityAnchor::new(None);
+        KeyDirectory::new(&server.key_dir)
+            .and_then(|d| d.register_key(&anchor.public_key_base64(), None))
+            .unwrap();
+        anchor.id().0
+    }
+
     // ── JSON-RPC helpers ──────────────────────────────────────────────────────
 
     fn is_ok(resp: &Value) -> bool {
@@ -4569,8 +8790,30 @@ mod tests {
         assert!(names.contains(&"identity_workspace_query"));
         assert!(names.contains(&"identity_workspace_compare"));
         assert!(names.contains(&"identity_workspace_xref"));
-        // 30 original + 1 action_context + 3 session + 3 grounding + 6 workspace + 58 inventions = 101
-        assert_eq!(tools.len(), 101);
+        assert!(names.contains(&"session_trace"));
+        assert!(names.contains(&"action_check"));
+        assert!(names.contains(&"receipt_request_witness"));
+        assert!(names.contains(&"receipt_add_witness"));
+        assert!(names.contains(&"receipt_witness"));
+        assert!(names.contains(&"identity_rekey_stores"));
+        assert!(names.contains(&"identity_change_passphrase"));
+        assert!(names.contains(&"identity_authority_diff"));
+        assert!(names.contains(&"receipt_export"));
+        assert!(names.contains(&"trust_revoke_simulate"));
+        assert!(names.contains(&"identity_create_batch"));
+        assert!(names.contains(&"operation_log_export"));
+        assert!(names.contains(&"artifact_verify"));
+        assert!(names.contains(&"capability_holders"));
+        assert!(names.contains(&"continuity_confidence"));
+        assert!(names.contains(&"identity_rotate"));
+        assert!(names.contains(&"receipt_query"));
+        assert!(names.contains(&"audit_list"));
+        assert!(names.contains(&"audit_show"));
+        assert!(names.contains(&"receipt_graph"));
+        assert!(names.contains(&"spawn_tree"));
+        assert!(names.contains(&"trust_path"));
+        // 43 original + 2 action (context, check) + 3 witness + 5 session + 2 audit + 3 grounding + 6 workspace + 1 trust (path) + 60 inventions = 125
+        assert_eq!(tools.len(), 125);
     }
 
     #[test]
@@ -4649,6 +8892,7 @@ mod tests {
         let uris: Vec<&str> = resources.iter().filter_map(|r| r["uri"].as_str()).collect();
         assert!(uris.contains(&"aid://identity/default"));
         assert!(uris.contains(&"aid://receipts/recent"));
+        assert!(uris.contains(&"aid://trust/revocations"));
     }
 
     // ── identity_create ───────────────────────────────────────────────────────
@@ -4686,6 +8930,53 @@ mod tests {
         assert!(text.contains("test-agent"));
     }
 
+    #[test]
+    fn test_install_env_identity_matches_key() {
+        init();
+        let (server, _tmp) = test_server();
+        let original = IdentityAnchor::new(None);
+        let key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            original.signing_key_bytes(),
+        );
+
+        let env = |material: String| EnvIdentity {
+            var: "AID_MCP_TEST_IDENTITY_FROM_ENV".to_string(),
+            material: Zeroizing::new(material),
+        };
+        assert_eq!(
+            server.install_env_identity(&env(key.clone())).unwrap(),
+            original.id()
+        );
+        let path = server.identity_dir.join("default.aid");
+        let loaded = load_identity(&path, &server.passphrase).unwrap();
+        assert_eq!(loaded.id(), original.id());
+
+        // Restarting with the same key is a no-op; another key is refused.
+        assert!(server.install_env_identity(&env(key)).is_ok());
+        let other = IdentityAnchor::new(None);
+        let other_key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            other.signing_key_bytes(),
+        );
+        assert!(server.install_env_identity(&env(other_key)).is_err());
+        assert_eq!(read_public_document(&path).unwrap().id, original.id());
+    }
+
+    #[test]
+    fn test_identity_create_rejects_control_characters() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let req = json!({
+            "jsonrpc":"2.0","id":7,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{"name":"bad\0name"}}
+        });
+        let resp = server.handle_request(req);
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("control character U+0000"));
+    }
+
     #[test]
     fn test_identity_create_duplicate_fails() {
         init();
@@ -4711,10 +9002,147 @@ mod tests {
         assert!(text.contains("already exists"));
     }
 
-    // ── identity_show ─────────────────────────────────────────────────────────
-
     #[test]
-    fn test_identity_show_after_create() {
+    fn test_identity_create_batch_reports_per_name_and_resumes() {
+        init();
+        let (mut server, _tmp, _id) = setup_identity();
+        let batch = |server: &mut McpServer| {
+            tool_json(&server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"identity_create_batch",
+                    "arguments":{"names":["default", "worker-1", "bad\0name", "worker-1"]}
+                }
+            })))
+        };
+
+        let first = batch(&mut server);
+        assert_eq!(first["created"], 1);
+        assert_eq!(first["skipped"], 1);
+        assert_eq!(first["failed"], 1);
+        let statuses: Vec<&str> = first["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, ["skipped", "created", "failed"]);
+        let manifest = first["manifest"].as_array().unwrap();
+        assert_eq!(manifest.len(), 2);
+        let worker = manifest[1].clone();
+        assert_eq!(worker["name"], "worker-1");
+        assert!(worker["fingerprint"].as_str().unwrap().contains(':'));
+
+        // Re-running creates nothing and reports the same keys.
+        let again = batch(&mut server);
+        assert_eq!(again["created"], 0);
+        assert_eq!(again["skipped"], 2);
+        assert_eq!(again["manifest"][1], worker);
+
+        // The manifest's key can be bound into a grant directly.
+        let grant = |server: &mut McpServer, key: &Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"trust_grant",
+                    "arguments":{
+                        "grantee": worker["id"],
+                        "grantee_key": key,
+                        "capabilities":["read:calendar"]
+                    }
+                }
+            }))
+        };
+        let resp = grant(&mut server, &worker["public_key"]);
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        assert!(is_tool_error(&grant(
+            &mut server,
+            &manifest[0]["public_key"]
+        )));
+    }
+
+    // ── identity_register_key ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_trust_grant_uses_registered_grantee_key() {
+        init();
+        let (mut server, _tmp, _id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        let bob = IdentityAnchor::new(Some("bob".into()));
+        let grant_to_bob = json!({"grantee": bob.id().0, "capabilities":["read:calendar"]});
+
+        // No key known for bob: refused rather than signed with a wrong key.
+        let resp = call("trust_grant", grant_to_bob.clone());
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("identity_register_key"));
+
+        let resp = call(
+            "identity_register_key",
+            json!({"public_key": bob.public_key_base64(), "label": "bob"}),
+        );
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        assert!(tool_text(&resp).contains(&bob.id().0));
+
+        let resp = call("trust_grant", grant_to_bob);
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        let trust_id = TrustId::parse(&extract_trust_id(&tool_text(&resp))).unwrap();
+        let grant = TrustStore::new(&server.trust_dir)
+            .unwrap()
+            .load_grant(&trust_id)
+            .unwrap();
+        assert_eq!(grant.grantee_key, bob.public_key_base64());
+    }
+
+    #[test]
+    fn test_identity_register_key_checks_documents() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        // A rotated identity's ID no longer derives from its key, so only its
+        // document can register it.
+        let carol = IdentityAnchor::new(Some("carol".into()))
+            .rotate(RotationReason::Scheduled)
+            .unwrap();
+        let document = serde_json::to_value(carol.to_document()).unwrap();
+        let resp = call("identity_register_key", json!({"document": document}));
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        assert!(tool_text(&resp).contains("carol"));
+
+        let mut forged = carol.to_document();
+        forged.public_key = IdentityAnchor::new(None).public_key_base64();
+        let resp = call(
+            "identity_register_key",
+            json!({"document": serde_json::to_value(forged).unwrap()}),
+        );
+        assert!(is_tool_error(&resp));
+
+        assert!(is_tool_error(&call("identity_register_key", json!({}))));
+        assert!(is_tool_error(&call(
+            "identity_register_key",
+            json!({"public_key": "not-a-key"})
+        )));
+    }
+
+    // ── identity_show ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_identity_show_after_create() {
         init();
         let (mut server, _tmp) = test_server();
 
@@ -4836,6 +9264,46 @@ mod tests {
         assert!(text2.contains("Chained to:"));
     }
 
+    #[test]
+    fn test_action_sign_rejects_unknown_chain_to_unless_forced() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let sign = |server: &mut McpServer, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":args}
+            }))
+        };
+
+        let malformed = sign(&mut server, json!({"action":"a","chain_to":"arec_0Ol"}));
+        assert!(is_tool_error(&malformed));
+        assert!(tool_text(&malformed).contains("arec_0Ol"));
+
+        let missing = sign(&mut server, json!({"action":"a","chain_to":"arec_Missing"}));
+        assert!(is_tool_error(&missing));
+        assert!(tool_text(&missing).contains("'arec_Missing' not found"));
+
+        let forced = sign(
+            &mut server,
+            json!({"action":"a","chain_to":"arec_Missing","chain_force":true}),
+        );
+        assert!(!is_tool_error(&forced), "{}", tool_text(&forced));
+        assert!(tool_text(&forced).contains("Chained to: arec_Missing"));
+
+        // A predecessor signed by another identity is refused.
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{"name":"other"}}
+        }));
+        let other = sign(&mut server, json!({"action":"b","identity":"other"}));
+        let other_id = extract_receipt_id(&tool_text(&other));
+        let cross = sign(&mut server, json!({"action":"c","chain_to":other_id}));
+        assert!(is_tool_error(&cross));
+        assert!(tool_text(&cross).contains("was signed by"));
+    }
+
     #[test]
     fn test_action_sign_no_action_fails() {
         init();
@@ -4851,6 +9319,90 @@ mod tests {
         assert!(text.contains("action"));
     }
 
+    #[test]
+    fn test_action_sign_carries_action_context_intent() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":18,
+            "method":"tools/call",
+            "params":{"name":"action_context","arguments":{"intent":"unblock release"}}
+        }));
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":19,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"Approved hotfix"}}
+        }));
+        assert!(!is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("Intent:    unblock release"));
+
+        // An explicit intent overrides the session context.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":20,
+            "method":"tools/call",
+            "params":{
+                "name":"action_sign",
+                "arguments":{"action":"Approved rollback","intent":"restore service"}
+            }
+        }));
+        assert!(tool_text(&resp).contains("Intent:    restore service"));
+    }
+
+    #[test]
+    fn test_trust_grant_purpose_and_justification_audit() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let grantee = registered_grantee(&server);
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":21,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"Approved access request"}}
+        }));
+        let receipt_id = extract_receipt_id(&tool_text(&resp));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":22,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{
+                    "grantee": grantee,
+                    "capabilities":["read:calendar"],
+                    "purpose":"calendar sync",
+                    "justification_receipt": receipt_id
+                }
+            }
+        }));
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        let trust_id = extract_trust_id(&tool_text(&resp));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":23,
+            "method":"tools/call",
+            "params":{"name":"trust_verify","arguments":{"trust_id": trust_id}}
+        }));
+        let text = tool_text(&resp);
+        assert!(text.contains("Purpose:      calendar sync"));
+        assert!(text.contains(&format!("{receipt_id} (verified)")));
+
+        // A dangling justification link is refused at issue time.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":24,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{
+                    "grantee": grantee,
+                    "capabilities":["read:calendar"],
+                    "justification_receipt":"arec_missing"
+                }
+            }
+        }));
+        assert!(is_tool_error(&resp));
+    }
+
     // ── receipt_verify ────────────────────────────────────────────────────────
 
     #[test]
@@ -4914,12 +9466,29 @@ mod tests {
         assert!(text.contains("not found"));
     }
 
+    #[test]
+    fn test_receipt_verify_rejects_wrong_id_kind() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":22,
+            "method":"tools/call",
+            "params":{
+                "name":"receipt_verify",
+                "arguments":{"receipt_id":"atrust_3yZe7d"}
+            }
+        }));
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("expected a receipt ID"));
+    }
+
     // ── trust_grant / trust_verify / trust_revoke ─────────────────────────────
 
     #[test]
     fn test_trust_grant_and_verify() {
         init();
         let (mut server, _tmp) = test_server();
+        let grantee = registered_grantee(&server);
 
         // Create identity.
         let _ = server.handle_request(json!({
@@ -4935,7 +9504,7 @@ mod tests {
             "params":{
                 "name":"trust_grant",
                 "arguments":{
-                    "grantee":"aid_testgrantee",
+                    "grantee": grantee,
                     "capabilities":["read:calendar","write:notes"]
                 }
             }
@@ -4974,6 +9543,7 @@ mod tests {
     fn test_trust_revoke() {
         init();
         let (mut server, _tmp) = test_server();
+        let grantee = registered_grantee(&server);
 
         // Create identity.
         let _ = server.handle_request(json!({
@@ -4989,7 +9559,7 @@ mod tests {
             "params":{
                 "name":"trust_grant",
                 "arguments":{
-                    "grantee":"aid_toberevoked",
+                    "grantee": grantee,
                     "capabilities":["execute:deploy"]
                 }
             }
@@ -5028,130 +9598,2067 @@ mod tests {
         assert!(verify_text.contains("INVALID") || verify_text.contains("REVOKED"));
     }
 
-    // ── trust_list ────────────────────────────────────────────────────────────
+    #[test]
+    fn test_artifact_verify_by_uri() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+        let grantee = registered_grantee(&server);
+        let call = |server: &mut McpServer, name: &str, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": args}
+            }))
+        };
+
+        let resp = call(
+            &mut server,
+            "artifact_verify",
+            json!({"uri":"aid://identity/default"}),
+        );
+        let result = tool_json(&resp);
+        assert_eq!(result["kind"], "identity");
+        assert_eq!(result["id"], identity_id);
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["reasons"], json!([]));
+
+        let signed = call(&mut server, "action_sign", json!({"action":"Deployed v2"}));
+        let receipt_id = extract_receipt_id(&tool_text(&signed));
+        let uri = format!("aid://receipt/{receipt_id}");
+        let result = tool_json(&call(&mut server, "artifact_verify", json!({"uri": uri})));
+        assert_eq!(result["kind"], "receipt");
+        assert_eq!(result["valid"], true);
+
+        let granted = call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": grantee,"capabilities":["read:calendar"]}),
+        );
+        let trust_id = extract_trust_id(&tool_text(&granted));
+        let uri = format!("aid://trust/{trust_id}");
+        let result = tool_json(&call(&mut server, "artifact_verify", json!({"uri": uri})));
+        assert_eq!(result["kind"], "trust");
+        assert_eq!(result["valid"], true);
+
+        call(&mut server, "trust_revoke", json!({"trust_id": trust_id}));
+        let result = tool_json(&call(&mut server, "artifact_verify", json!({"uri": uri})));
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["reasons"], json!(["revoked"]));
+
+        let resp = call(
+            &mut server,
+            "artifact_verify",
+            json!({"uri":"https://example.com/x"}),
+        );
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("unknown artifact URI"));
+        let resp = call(
+            &mut server,
+            "artifact_verify",
+            json!({"uri":"aid://identity/../x"}),
+        );
+        assert!(is_tool_error(&resp));
+    }
 
     #[test]
-    fn test_trust_list() {
+    fn test_capability_holders_pages_through_grants() {
         init();
-        let (mut server, _tmp) = test_server();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": args}
+            }))
+        };
+        let holders: Vec<String> = (0..3).map(|_| registered_grantee(&server)).collect();
+        call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": holders[0], "capabilities": ["deploy:*"]}),
+        );
+        call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": holders[1], "capabilities": ["deploy:prod"]}),
+        );
+        call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": holders[2], "capabilities": ["read:logs"]}),
+        );
 
-        // Create identity.
-        let _ = server.handle_request(json!({
-            "jsonrpc":"2.0","id":29,
-            "method":"tools/call",
-            "params":{"name":"identity_create","arguments":{}}
-        }));
+        let first = tool_json(&call(
+            &mut server,
+            "capability_holders",
+            json!({"capability": "deploy:prod", "limit": 2}),
+        ));
+        assert_eq!(first["scanned"], 2);
+        let cursor = first["next_cursor"].as_str().unwrap();
+        let second = tool_json(&call(
+            &mut server,
+            "capability_holders",
+            json!({"capability": "deploy:prod", "limit": 2, "cursor": cursor}),
+        ));
+        assert!(second["next_cursor"].is_null());
 
-        // Grant trust to two different grantees.
-        let _ = server.handle_request(json!({
-            "jsonrpc":"2.0","id":30,
-            "method":"tools/call",
-            "params":{
-                "name":"trust_grant",
-                "arguments":{"grantee":"aid_alice","capabilities":["read:*"]}
-            }
-        }));
-        let _ = server.handle_request(json!({
-            "jsonrpc":"2.0","id":31,
-            "method":"tools/call",
-            "params":{
-                "name":"trust_grant",
-                "arguments":{"grantee":"aid_bob","capabilities":["write:notes"]}
-            }
-        }));
+        let mut found: Vec<&str> = first["holders"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(second["holders"].as_array().unwrap())
+            .map(|h| {
+                assert_eq!(h["source"]["kind"], "grant");
+                h["identity"].as_str().unwrap()
+            })
+            .collect();
+        found.sort();
+        let mut expected = vec![holders[0].as_str(), holders[1].as_str()];
+        expected.sort();
+        assert_eq!(found, expected);
 
-        // List granted.
-        let list_resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":32,
-            "method":"tools/call",
-            "params":{
-                "name":"trust_list",
-                "arguments":{"direction":"granted"}
-            }
-        }));
-        assert!(!is_tool_error(&list_resp));
-        let text = tool_text(&list_resp);
-        assert!(text.contains("Granted (2)"));
+        let resp = call(&mut server, "capability_holders", json!({}));
+        assert!(is_tool_error(&resp));
     }
 
-    // ── receipt_list ──────────────────────────────────────────────────────────
+    #[test]
+    fn test_trust_path_follows_delegation_into_other_stores() {
+        init();
+        let (mut server, tmp, identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": args}
+            }))
+        };
+        let bob = IdentityAnchor::new(None);
+        let carol = IdentityAnchor::new(None);
+        let granted = call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": bob.id().0, "grantee_key": bob.public_key_base64(),
+                "capabilities": ["deploy:*"], "allow_delegation": true}),
+        );
+        let trust_id = extract_trust_id(&tool_text(&granted));
+
+        // Bob's own store holds his delegation to Carol.
+        let bob_dir = tmp.path().join("bob-trust");
+        let delegated = TrustGrantBuilder::new(bob.id(), carol.id(), carol.public_key_base64())
+            .capability(Capability::new("deploy:prod"))
+            .delegated_from(TrustId(trust_id.clone()), 1)
+            .sign(bob.signing_key())
+            .unwrap();
+        TrustStore::new(&bob_dir)
+            .unwrap()
+            .save_granted(&delegated)
+            .unwrap();
+
+        let args = json!({"from": "default", "to": carol.id().0, "capability": "deploy:prod"});
+        let local = tool_json(&call(&mut server, "trust_path", args.clone()));
+        assert_eq!(local["found"], false);
+
+        let mut args = args;
+        args["stores"] = json!([bob_dir.to_str().unwrap()]);
+        let result = tool_json(&call(&mut server, "trust_path", args.clone()));
+        assert_eq!(result["found"], true);
+        assert_eq!(result["from"], identity_id);
+        let hops = result["hops"].as_array().unwrap();
+        assert_eq!(hops.len(), 2);
+        assert_eq!(hops[0]["grant_id"], trust_id);
+        assert_eq!(hops[1]["grantee"], carol.id().0);
+        assert_eq!(hops[1]["capabilities"][0]["uri"], "deploy:prod");
+
+        args["capability"] = json!("deploy:staging");
+        let result = tool_json(&call(&mut server, "trust_path", args));
+        assert_eq!(result["found"], false);
+
+        let missing = json!({"from": "default", "to": carol.id().0, "capability": "deploy:prod",
+            "stores": [tmp.path().join("nowhere").to_str().unwrap()]});
+        assert!(is_tool_error(&call(&mut server, "trust_path", missing)));
+        assert!(is_tool_error(&call(
+            &mut server,
+            "trust_path",
+            json!({"from": "default"})
+        )));
+        let malformed =
+            json!({"from": "default", "to": "aid_../carol", "capability": "deploy:prod"});
+        assert!(is_tool_error(&call(&mut server, "trust_path", malformed)));
+    }
 
     #[test]
-    fn test_receipt_list() {
+    fn test_trust_use_enforces_max_uses() {
         init();
-        let (mut server, _tmp) = test_server();
+        let (mut server, _tmp, _id) = setup_identity();
+        let grantee = registered_grantee(&server);
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
 
-        // Create identity.
-        let _ = server.handle_request(json!({
-            "jsonrpc":"2.0","id":33,
-            "method":"tools/call",
-            "params":{"name":"identity_create","arguments":{}}
-        }));
+        let granted = call(
+            "trust_grant",
+            json!({"grantee": grantee, "capabilities":["read:calendar"], "max_uses": 2}),
+        );
+        let trust_id = extract_trust_id(&tool_text(&granted));
+        let use_once = json!({"trust_id": trust_id, "capability": "read:calendar"});
 
-        // Sign three actions.
-        for i in 0..3 {
-            let _ = server.handle_request(json!({
-                "jsonrpc":"2.0","id": 34 + i,
-                "method":"tools/call",
-                "params":{
-                    "name":"action_sign",
-                    "arguments":{
-                        "action": format!("Action {i}"),
-                        "action_type":"decision"
-                    }
-                }
-            }));
-        }
+        let resp = call("trust_use", use_once.clone());
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        assert!(tool_text(&resp).contains("1 of 2"));
 
-        // List receipts.
-        let list_resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":37,
-            "method":"tools/call",
-            "params":{"name":"receipt_list","arguments":{}}
-        }));
-        assert!(!is_tool_error(&list_resp));
+        // A retry with the same idempotency key is not a second use.
+        let mut keyed = use_once.clone();
+        keyed["idempotency_key"] = json!("use-2");
+        assert!(tool_text(&call("trust_use", keyed.clone())).contains("2 of 2"));
+        assert!(tool_text(&call("trust_use", keyed)).contains("2 of 2"));
+
+        let resp = call("trust_use", use_once);
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("all 2 uses consumed"));
+
+        let resp = call("trust_verify", json!({"trust_id": trust_id}));
+        assert!(tool_text(&resp).contains("Used:         2 of 2"));
+        assert_eq!(
+            resp["result"]["structuredContent"]["outcomes"]["uses"],
+            "uses_exceeded"
+        );
+
+        let resp = call(
+            "trust_use",
+            json!({"trust_id": trust_id, "capability": "write:calendar"}),
+        );
+        assert!(tool_text(&resp).contains("capability 'write:calendar' not granted"));
+    }
+
+    #[test]
+    fn test_trust_use_enforces_rate_limit_from_ledger() {
+        use agentic_identity::trust::Predicate;
+
+        init();
+        let (mut server, _tmp, _id) = setup_identity();
+        let grantor =
+            load_identity(&server.identity_dir.join("default.aid"), &server.passphrase).unwrap();
+        let grantee = IdentityAnchor::new(None);
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capability(Capability::new("spend:usd"))
+            .constraints(TrustConstraints::open().with_predicate(
+                "spend:*",
+                Predicate::RateLimit {
+                    max: 2,
+                    window_secs: 3600,
+                },
+            ))
+            .sign(grantor.signing_key())
+            .unwrap();
+        TrustStore::new(&server.trust_dir)
+            .unwrap()
+            .save_granted(&grant)
+            .unwrap();
+
+        let use_once = json!({"trust_id": grant.id.0, "capability": "spend:usd"});
+        let mut call = |arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"trust_use","arguments":arguments}
+            }))
+        };
+        for _ in 0..2 {
+            let resp = call(use_once.clone());
+            assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        }
+        let resp = call(use_once);
+        assert!(is_tool_error(&resp));
+        assert!(
+            tool_text(&resp).contains("2 uses in the last 3600s"),
+            "{}",
+            tool_text(&resp)
+        );
+    }
+
+    #[test]
+    fn test_trust_verify_outcome_codes_and_messages() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let grantee = registered_grantee(&server);
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":1,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+        let grant_resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{"grantee": grantee,"capabilities":["read:calendar"]}
+            }
+        }));
+        let trust_id = tool_text(&grant_resp)
+            .split_whitespace()
+            .find(|w| w.starts_with("atrust_"))
+            .unwrap()
+            .to_string();
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"trust_revoke","arguments":{"trust_id": trust_id}}
+        }));
+        let verify = |server: &mut McpServer| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"trust_verify",
+                    "arguments":{"trust_id": trust_id, "capability":"read:calendar"}
+                }
+            }))
+        };
+
+        // Default wording is the English text clients already match.
+        let resp = verify(&mut server);
+        let text = tool_text(&resp);
+        assert!(text.contains("Not Revoked:  REVOKED"), "{text}");
+        assert!(text.contains("Result:       INVALID"), "{text}");
+        let outcomes = &resp["result"]["structuredContent"]["outcomes"];
+        assert_eq!(outcomes["revocation"], "revoked");
+        assert_eq!(outcomes["signature"], "signature_valid");
+        assert_eq!(outcomes["result"], "invalid");
+
+        // Overridden wording changes the text but never the codes.
+        server.verification_messages =
+            serde_json::from_str(r#"{"revoked": "RÉVOQUÉ", "invalid": "INVALIDE"}"#).unwrap();
+        let resp = verify(&mut server);
+        let text = tool_text(&resp);
+        assert!(text.contains("Not Revoked:  RÉVOQUÉ"), "{text}");
+        assert!(text.contains("Result:       INVALIDE"), "{text}");
+        assert_eq!(
+            resp["result"]["structuredContent"]["outcomes"]["revocation"],
+            "revoked"
+        );
+
+        assert!(serde_json::from_str::<OutcomeMessages>(r#"{"revokd": "x"}"#).is_err());
+    }
+
+    // ── identity_authority_diff ───────────────────────────────────────────────
+
+    #[test]
+    fn test_trust_revoke_simulate_separates_full_and_partial_loss() {
+        init();
+        let (mut server, tmp) = test_server();
+        let [alice, bob, carol, dave, other] = std::array::from_fn(|_| IdentityAnchor::new(None));
+        let grant = |from: &IdentityAnchor, to: &IdentityAnchor, caps: &[&str]| {
+            TrustGrantBuilder::new(from.id(), to.id(), to.public_key_base64())
+                .capabilities(caps.iter().map(|c| Capability::new(*c)).collect())
+                .allow_delegation(3)
+        };
+        let sign =
+            |b: TrustGrantBuilder, from: &IdentityAnchor| b.sign(from.signing_key()).unwrap();
+
+        let root = sign(
+            grant(&alice, &bob, &["read:calendar", "write:notes"]),
+            &alice,
+        );
+        // Bob also holds write:notes from someone else, so Carol keeps it.
+        let side = sign(grant(&other, &bob, &["write:notes"]), &other);
+        let to_carol = sign(
+            grant(&bob, &carol, &["read:calendar", "write:notes"])
+                .delegated_from(root.id.clone(), 1),
+            &bob,
+        );
+        let to_dave = sign(
+            grant(&carol, &dave, &["read:calendar"]).delegated_from(to_carol.id.clone(), 2),
+            &carol,
+        );
+        let store = TrustStore::new(tmp.path().join("trust")).unwrap();
+        for g in [&root, &side, &to_carol, &to_dave] {
+            store.save_granted(g).unwrap();
+        }
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"trust_revoke_simulate","arguments":{"trust_id": root.id.0}}
+        }));
+        let j = tool_json(&resp);
+        assert_eq!(j["grants_affected"], 3);
+        assert_eq!(j["fully_invalidated"], 2);
+        assert_eq!(j["partially_invalidated"], 1);
+        let impact = |gid: &TrustId| {
+            j["grants"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|g| g["grant_id"] == gid.0)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(impact(&root.id)["impact"], "fully_invalidated");
+        let carol_grant = impact(&to_carol.id);
+        assert_eq!(carol_grant["impact"], "partially_invalidated");
+        assert_eq!(carol_grant["capabilities_lost"], json!(["read:calendar"]));
+        assert_eq!(carol_grant["capabilities_kept"], json!(["write:notes"]));
+        assert_eq!(impact(&to_dave.id)["impact"], "fully_invalidated");
+
+        let lost = j["capabilities_lost"].as_array().unwrap();
+        assert_eq!(lost.len(), 3);
+        assert!(lost
+            .iter()
+            .all(|entry| entry["capabilities"] == json!(["read:calendar"])));
+
+        // Nothing was revoked.
+        assert!(store.list_revocations().unwrap().is_empty());
+        assert!(!store.is_revoked(&root.id));
+    }
+
+    #[test]
+    fn test_identity_authority_diff_reports_revocation() {
+        init();
+        let (mut server, _tmp, _id) = setup_identity();
+        let auditee = registered_grantee(&server);
+        let call = |server: &mut McpServer, name: &str, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":args}
+            }))
+        };
+
+        let grant_text = tool_text(&call(
+            &mut server,
+            "trust_grant",
+            json!({"grantee": auditee,"capabilities":["execute:deploy"]}),
+        ));
+        let trust_id = grant_text
+            .split_whitespace()
+            .find(|w| w.starts_with("atrust_"))
+            .unwrap()
+            .to_string();
+        let granted = agentic_identity::time::now_micros();
+        let _ = call(&mut server, "trust_revoke", json!({"trust_id": trust_id}));
+        let revoked = agentic_identity::time::now_micros();
+
+        let j = tool_json(&call(
+            &mut server,
+            "identity_authority_diff",
+            json!({"identity_id": auditee,"from":granted,"to":revoked}),
+        ));
+        assert_eq!(j["changed"], true);
+        assert_eq!(j["removed"], json!(["execute:deploy"]));
+        assert_eq!(j["added"], json!([]));
+
+        // The same timestamp on both sides never reports a change.
+        let j = tool_json(&call(
+            &mut server,
+            "identity_authority_diff",
+            json!({"identity_id": auditee,"from":granted,"to":granted}),
+        ));
+        assert_eq!(j["changed"], false);
+
+        let resp = call(&mut server, "identity_authority_diff", json!({}));
+        assert!(is_tool_error(&resp));
+    }
+
+    // ── trust_list ────────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_trust_list() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let (alice, bob) = (registered_grantee(&server), registered_grantee(&server));
+
+        // Create identity.
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":29,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+
+        // Grant trust to two different grantees.
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":30,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{"grantee": alice,"capabilities":["read:*"]}
+            }
+        }));
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":31,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{"grantee": bob,"capabilities":["write:notes"]}
+            }
+        }));
+
+        // List granted.
+        let list_resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":32,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_list",
+                "arguments":{"direction":"granted"}
+            }
+        }));
+        assert!(!is_tool_error(&list_resp));
+        let text = tool_text(&list_resp);
+        assert!(text.contains("Granted (2)"));
+    }
+
+    // ── receipt_list ──────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_receipt_list() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        // Create identity.
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":33,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+
+        // Sign three actions.
+        for i in 0..3 {
+            let _ = server.handle_request(json!({
+                "jsonrpc":"2.0","id": 34 + i,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{
+                        "action": format!("Action {i}"),
+                        "action_type":"decision"
+                    }
+                }
+            }));
+        }
+
+        // List receipts.
+        let list_resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":37,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{}}
+        }));
+        assert!(!is_tool_error(&list_resp));
         let text = tool_text(&list_resp);
         assert!(text.contains("3 total"));
     }
 
-    // ── identity_health ───────────────────────────────────────────────────────
+    #[test]
+    fn test_receipt_query_filters_by_data_and_chain() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+        let mut ids = Vec::new();
+        for (i, env) in ["staging", "prod", "prod"].into_iter().enumerate() {
+            let mut args = json!({
+                "action": format!("Deploy {i}"),
+                "action_type": "deploy",
+                "data": {"target": {"env": env}},
+            });
+            if let Some(previous) = ids.last() {
+                args["chain_to"] = json!(previous);
+            }
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":args}
+            }));
+            assert!(!is_tool_error(&resp));
+            ids.push(extract_receipt_id(&tool_text(&resp)));
+        }
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"receipt_query","arguments":{"filter":{
+                "actor": identity_id,
+                "chained_to": ids[0],
+                "data": {"target.env": "prod"},
+                "order": "oldest_first",
+            }}}
+        }));
+        assert!(!is_tool_error(&resp));
+        let out = tool_json(&resp);
+        assert_eq!(out["total"], 2);
+        assert_eq!(out["receipts"][0]["receipt_id"], ids[1]);
+        assert_eq!(out["receipts"][1]["receipt_id"], ids[2]);
+        assert_eq!(out["plan"]["seed"], "chain");
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_query","arguments":{"filter":{"actr": identity_id}}}
+        }));
+        assert!(is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_receipt_graph_renders_chain() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+        let mut ids: Vec<String> = Vec::new();
+        for i in 0..3 {
+            let mut args = json!({ "action": format!("Step \"{i}\""), "action_type": "decision" });
+            if let Some(previous) = ids.last() {
+                args["chain_to"] = json!(previous);
+            }
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":args}
+            }));
+            assert!(!is_tool_error(&resp));
+            ids.push(extract_receipt_id(&tool_text(&resp)));
+        }
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"receipt_graph","arguments":{"tip": ids[1], "format": "json"}}
+        }));
+        assert!(!is_tool_error(&resp));
+        let graph = tool_json(&resp);
+        let mut node_ids: Vec<&str> = graph["nodes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|n| n["id"].as_str().unwrap())
+            .collect();
+        node_ids.sort_unstable();
+        let mut expected = vec![ids[0].as_str(), ids[1].as_str(), identity_id.as_str()];
+        expected.sort_unstable();
+        assert_eq!(node_ids, expected);
+        assert!(graph["edges"].as_array().unwrap().contains(&json!({
+            "from": ids[0], "to": ids[1], "kind": "chain"
+        })));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"receipt_graph","arguments":{"format": "dot"}}
+        }));
+        let dot = tool_text(&resp);
+        assert!(dot.starts_with("digraph receipts {"));
+        assert!(dot.contains(&format!("\"{}\" -> \"{}\";", ids[1], ids[2])));
+        assert!(dot.contains("Step \\\"2\\\""));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_graph","arguments":{}}
+        }));
+        assert!(tool_text(&resp).starts_with("flowchart LR"));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":6,
+            "method":"tools/call",
+            "params":{"name":"receipt_graph","arguments":{"format": "svg"}}
+        }));
+        assert!(is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_receipt_export_ndjson_round_trips() {
+        init();
+        let (mut server, tmp, identity_id) = setup_identity();
+        for action in ["first", "second"] {
+            let _ = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":{"action":action}}
+            }));
+        }
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"receipt_export","arguments":{"actor":identity_id}}
+        }));
+        assert!(!is_tool_error(&resp));
+        let ndjson = tool_text(&resp);
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let summary: Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(summary["summary"]["count"], 2);
+
+        let imported = ReceiptStore::new(tmp.path().join("imported")).unwrap();
+        let read = agentic_identity::storage::read_ndjson(ndjson.as_bytes(), |r| imported.save(&r))
+            .unwrap();
+        assert_eq!(read.count, 2);
+        assert_eq!(imported.list().unwrap().len(), 2);
+
+        // The resource serves the same stream.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"resources/read",
+            "params":{"uri":"aid://receipts/all"}
+        }));
+        let contents = &resp["result"]["contents"][0];
+        assert_eq!(contents["mimeType"], "application/x-ndjson");
+        assert_eq!(contents["text"].as_str().unwrap(), ndjson);
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_export","arguments":{"identity":"nobody"}}
+        }));
+        assert!(is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_receipt_list_truncates_multibyte_descriptions() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":1,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+
+        // 56 ASCII bytes put byte 57 inside the first 4-byte emoji.
+        let long = format!("{}{}", "a".repeat(56), "🚀".repeat(10));
+        let exact = "日本語".repeat(20);
+        for action in [&long, &exact] {
+            let _ = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":{"action": action}}
+            }));
+        }
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{}}
+        }));
+        assert!(!is_tool_error(&resp));
+        let text = tool_text(&resp);
+        assert!(text.contains(&format!("{}🚀...", "a".repeat(56))));
+        // Exactly 60 characters: shown whole, no ellipsis.
+        assert!(text.contains(&format!("{exact}\n")));
+    }
+
+    #[test]
+    fn test_truncate_text_respects_char_boundaries() {
+        assert_eq!(truncate_text("héllo".to_string(), 2), "hé...");
+        assert_eq!(truncate_text("héllo".to_string(), 5), "héllo");
+        assert_eq!(truncate_text("🚀🚀".to_string(), 1), "🚀...");
+    }
+
+    #[test]
+    fn test_receipt_witness_round_trip() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        let signed = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"Approved release"}}
+        }));
+        let receipt_id = extract_receipt_id(&tool_text(&signed));
+        let store = ReceiptStore::new(tmp.path().join("receipts")).unwrap();
+        let original = store.load(&ReceiptId(receipt_id.clone())).unwrap();
+
+        let witness = IdentityAnchor::new(None);
+        let request = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{
+                "name":"receipt_request_witness",
+                "arguments":{"receipt_id": receipt_id, "witness_id": witness.id().0}
+            }
+        }));
+        let text = tool_text(&request);
+        assert!(!is_tool_error(&request), "{text}");
+        let message = text
+            .lines()
+            .map(str::trim)
+            .find(|l| l.starts_with("aid:witness:"))
+            .unwrap()
+            .to_string();
+        let witnessed_at: u64 = message.rsplit(':').next().unwrap().parse().unwrap();
+        let signature = agentic_identity::crypto::signing::sign_to_base64(
+            witness.signing_key(),
+            message.as_bytes(),
+        );
+
+        let add = |server: &mut McpServer, signature: &str| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":4,
+                "method":"tools/call",
+                "params":{
+                    "name":"receipt_add_witness",
+                    "arguments":{
+                        "receipt_id": receipt_id,
+                        "witness_id": witness.id().0,
+                        "witness_key": witness.public_key_base64(),
+                        "witnessed_at": witnessed_at,
+                        "signature": signature
+                    }
+                }
+            }))
+        };
+
+        // A signature over the wrong message is rejected.
+        let forged = agentic_identity::crypto::signing::sign_to_base64(
+            witness.signing_key(),
+            b"something else",
+        );
+        let rejected = add(&mut server, &forged);
+        assert!(is_tool_error(&rejected));
+        assert!(tool_text(&rejected).contains("witness signature rejected"));
+
+        let added = add(&mut server, &signature);
+        assert!(!is_tool_error(&added), "{}", tool_text(&added));
+        assert!(is_tool_error(&add(&mut server, &signature)));
+
+        let updated = store.load(&original.id).unwrap();
+        assert_eq!(updated.witnesses.len(), 1);
+        assert_eq!(updated.receipt_hash, original.receipt_hash);
+        assert_eq!(updated.signature, original.signature);
+
+        let verified = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_verify","arguments":{"receipt_id": receipt_id}}
+        }));
+        let text = tool_text(&verified);
+        assert!(text.contains("Result:    VALID"), "{text}");
+        assert!(text.contains(&format!("[1] {} valid", witness.id())));
+    }
+
+    #[test]
+    fn test_receipt_witness_with_local_identity() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let signed = call("action_sign", json!({"action": "Approved release"}));
+        let receipt_id = extract_receipt_id(&tool_text(&signed));
+        let created = call("identity_create", json!({"name": "auditor"}));
+        let auditor = extract_identity_id(&tool_text(&created));
+
+        // The actor cannot witness its own receipt.
+        let own = call(
+            "receipt_witness",
+            json!({"receipt_id": receipt_id, "witness": "default"}),
+        );
+        assert!(is_tool_error(&own));
+
+        let witnessed = call(
+            "receipt_witness",
+            json!({"receipt_id": receipt_id, "witness": "auditor"}),
+        );
+        let text = tool_text(&witnessed);
+        assert!(!is_tool_error(&witnessed), "{text}");
+        assert!(text.contains("Witnesses:    1"), "{text}");
+        assert!(is_tool_error(&call(
+            "receipt_witness",
+            json!({"receipt_id": receipt_id, "witness": "auditor"}),
+        )));
+
+        let verified = call("receipt_verify", json!({"receipt_id": receipt_id}));
+        let text = tool_text(&verified);
+        assert!(text.contains("Result:    VALID"), "{text}");
+        assert!(text.contains(&format!("[1] {auditor} valid")), "{text}");
+    }
+
+    /// Create two named identities and sign one receipt with each.
+    fn setup_two_actors(server: &mut McpServer) -> (String, String) {
+        let mut ids = Vec::new();
+        for name in ["alice", "bob"] {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":"identity_create","arguments":{"name": name}}
+            }));
+            ids.push(extract_identity_id(&tool_text(&resp)));
+            let _ = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{"action": format!("Deployed service for {name}"), "identity": name}
+                }
+            }));
+        }
+        (ids[0].clone(), ids[1].clone())
+    }
+
+    #[test]
+    fn test_receipt_list_scoped_by_identity() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let (alice_id, _bob_id) = setup_two_actors(&mut server);
+
+        let all = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{}}
+        }));
+        assert!(tool_text(&all).contains("2 total"));
+
+        let scoped = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{"identity":"alice"}}
+        }));
+        let text = tool_text(&scoped);
+        assert!(text.contains("1 total"));
+        assert!(text.contains("alice"));
+        assert!(!text.contains("bob"));
+
+        let by_actor = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{"actor": alice_id}}
+        }));
+        assert!(tool_text(&by_actor).contains("1 total"));
+
+        // An explicit identity that does not exist matches nothing, not everything.
+        let missing = server.handle_request(json!({
+            "jsonrpc":"2.0","id":6,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{"identity":"ghost"}}
+        }));
+        assert!(tool_text(&missing).contains("0 total"));
+    }
+
+    #[test]
+    fn test_read_tools_reject_malformed_actor() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let _ = setup_two_actors(&mut server);
+
+        for actor in ["", "alice", "aid_!!"] {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{"name":"receipt_list","arguments":{"actor": actor}}
+            }));
+            assert!(is_tool_error(&resp), "actor {actor:?} should be rejected");
+            assert!(tool_text(&resp).contains("invalid actor"));
+        }
+    }
+
+    #[test]
+    fn test_receipt_verify_scoped_rejects_other_actor() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let _ = setup_two_actors(&mut server);
+
+        let list = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"receipt_list","arguments":{"identity":"alice"}}
+        }));
+        let receipt_id = extract_receipt_id(&tool_text(&list));
+
+        let own = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"receipt_verify","arguments":{"receipt_id": receipt_id, "identity":"alice"}}
+        }));
+        assert!(!is_tool_error(&own));
+
+        let other = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"receipt_verify","arguments":{"receipt_id": receipt_id, "identity":"bob"}}
+        }));
+        assert!(is_tool_error(&other));
+    }
+
+    #[test]
+    fn test_grounding_scoped_excludes_other_identity_grants() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let external = registered_grantee(&server);
+        let (_alice_id, _bob_id) = setup_two_actors(&mut server);
+
+        let grant = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{
+                    "grantee": external,
+                    "capabilities":["read:files"],
+                    "identity":"alice"
+                }
+            }
+        }));
+        assert!(!is_tool_error(&grant));
+
+        let as_alice = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"identity_ground","arguments":{"claim":"can read files","identity":"alice"}}
+        }));
+        assert_eq!(tool_json(&as_alice)["status"], "verified");
+
+        let as_bob = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"identity_ground","arguments":{"claim":"can read files","identity":"bob"}}
+        }));
+        assert_eq!(tool_json(&as_bob)["status"], "ungrounded");
+    }
+
+    // ── spawn_authority / spawn_lineage ──────────────────────────────────────
+
+    #[test]
+    fn test_action_check_without_requirements() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"action_check","arguments":{"action":"deploy to prod"}}
+        }));
+        assert!(!is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("no capability required"));
+    }
+
+    #[test]
+    fn test_action_check_reports_missing_capabilities_without_signing() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        server.action_requirements = RequirementPolicy::new()
+            .for_keyword("deploy to prod", "deploy:prod")
+            .for_type("mutation", "write:docs");
+
+        let spawned = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"spawn_create",
+                "arguments":{"purpose":"edit docs","authority":["write:docs"]}
+            }
+        }));
+        let child_id = tool_text(&spawned)
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("Child ID:"))
+            .unwrap()
+            .trim()
+            .to_string();
+        let receipts_before = std::fs::read_dir(tmp.path().join("receipts"))
+            .map(|d| d.count())
+            .unwrap_or(0);
+
+        let child = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{
+                "name":"action_check",
+                "arguments":{
+                    "action":"Deploy to prod",
+                    "action_type":"mutation",
+                    "identity_id": child_id
+                }
+            }
+        }));
+        let text = tool_text(&child);
+        assert!(!is_tool_error(&child), "{text}");
+        assert!(text.contains("Held:     write:docs"));
+        assert!(text.contains("Missing:  deploy:prod"));
+        assert!(text.contains("do not sign"));
+
+        // The root identity holds everything.
+        let root = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{
+                "name":"action_check",
+                "arguments":{"action":"Deploy to prod","action_type":"mutation"}
+            }
+        }));
+        assert!(tool_text(&root).contains("Result: OK"));
+
+        let receipts_after = std::fs::read_dir(tmp.path().join("receipts"))
+            .map(|d| d.count())
+            .unwrap_or(0);
+        assert_eq!(receipts_before, receipts_after);
+    }
+
+    #[test]
+    fn test_action_sign_enforces_requirements_when_enabled() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        server.action_requirements = RequirementPolicy::new().for_keyword("deploy", "deploy:*");
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"spawn_create",
+                "arguments":{"purpose":"edit docs","authority":["write:docs"]}
+            }
+        }));
+        let sign = |server: &mut McpServer, identity: &str| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{"action":"Deploy v2","action_type":"mutation","identity":identity}
+                }
+            }))
+        };
+
+        // Off by default: signing is unrestricted.
+        assert!(!is_tool_error(&sign(&mut server, "default-worker")));
+
+        server.enforce_action_requirements = true;
+        let resp = sign(&mut server, "default-worker");
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("Authority escalation"));
+        assert!(!is_tool_error(&sign(&mut server, "default")));
+    }
+
+    #[test]
+    fn test_spawn_authority_from_records_without_child_file() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+
+        let root = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"spawn_authority","arguments":{}}
+        }));
+        assert!(tool_text(&root).contains("root identity"));
+
+        let spawned = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{
+                "name":"spawn_create",
+                "arguments":{"purpose":"index docs","authority":["read:docs"]}
+            }
+        }));
+        let text = tool_text(&spawned);
+        let field = |label: &str| {
+            text.lines()
+                .find_map(|l| l.trim().strip_prefix(label))
+                .unwrap()
+                .trim()
+                .to_string()
+        };
+        let child_id = field("Child ID:");
+        let spawn_id = field("Spawn ID:");
+
+        // The child's key file is not needed.
+        std::fs::remove_file(tmp.path().join("identity").join("default-worker.aid")).unwrap();
+
+        let by_id = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"spawn_authority","arguments":{"identity_id": child_id}}
+        }));
+        let text = tool_text(&by_id);
+        assert!(!is_tool_error(&by_id));
+        assert!(text.contains("read:docs"));
+        assert!(!text.contains("root identity"));
+
+        let lineage = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"spawn_lineage","arguments":{"spawn_id": spawn_id}}
+        }));
+        let text = tool_text(&lineage);
+        assert!(text.contains("Status: active"));
+        assert!(text.contains("Depth: 1"));
+        assert!(text.contains("read:docs"));
+    }
+
+    #[test]
+    fn test_spawn_tree_shows_nested_authority() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let resp = call(&mut server, "spawn_tree", json!({}));
+        assert_eq!(tool_text(&resp), "No spawned identities");
+
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({"purpose": "index docs", "authority": ["read:docs", "write:docs"]}),
+        );
+        assert!(!is_tool_error(&resp));
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({
+                "identity": "default-worker",
+                "spawn_type": "delegate",
+                "purpose": "fetch pages",
+                "authority": ["read:docs"],
+            }),
+        );
+        assert!(!is_tool_error(&resp));
+
+        let resp = call(&mut server, "spawn_tree", json!({}));
+        let text = tool_text(&resp);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3, "{text}");
+        assert!(lines[0].starts_with(&identity_id));
+        assert!(lines[1].contains("[worker] index docs — read:docs, write:docs"));
+        assert!(lines[2].contains("[delegate] fetch pages — read:docs"));
+
+        let resp = call(
+            &mut server,
+            "spawn_tree",
+            json!({"identity": "default-worker", "format": "json"}),
+        );
+        let tree = tool_json(&resp);
+        assert_eq!(tree["roots"][0]["depth"], 1);
+        assert_eq!(tree["roots"][0]["children"][0]["depth"], 2);
+
+        let resp = call(&mut server, "spawn_tree", json!({"format": "mermaid"}));
+        assert!(tool_text(&resp).contains("n1 -->|delegate| n2"));
+    }
+
+    #[test]
+    fn test_spawn_budget_refuses_once_spent() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({
+                "purpose": "summarize",
+                "authority": ["read:docs"],
+                "budget": {"max_receipts": 1, "max_sub_spawns": 0, "max_cost": 100},
+            }),
+        );
+        assert!(!is_tool_error(&resp));
+        let spawn_id = tool_text(&resp)
+            .lines()
+            .find_map(|l| {
+                l.trim()
+                    .strip_prefix("Spawn ID:")
+                    .map(|v| v.trim().to_string())
+            })
+            .unwrap();
+
+        let sign = json!({"identity": "default-worker", "action": "Summarized", "cost": 40});
+        let resp = call(&mut server, "action_sign", sign.clone());
+        assert!(!is_tool_error(&resp));
+        let resp = call(&mut server, "action_sign", sign);
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("budget exhausted: receipts"));
+
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({"identity": "default-worker", "purpose": "helper", "authority": ["read:docs"]}),
+        );
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("sub_spawns"));
+
+        let resp = call(&mut server, "spawn_lineage", json!({"spawn_id": spawn_id}));
+        assert!(tool_text(&resp).contains("Budget: receipts 1/1, sub_spawns 0/0, cost 40/100"));
+
+        // The root identity was not spawned and has no budget.
+        let resp = call(&mut server, "action_sign", json!({"action": "Reviewed"}));
+        assert!(!is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_failed_spawn_leaves_budget_unspent() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({
+                "purpose": "summarize",
+                "authority": ["read:docs"],
+                "budget": {"max_sub_spawns": 2},
+            }),
+        );
+        assert!(!is_tool_error(&resp));
+        let spawn_id = tool_text(&resp)
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("Spawn ID:").map(str::trim))
+            .map(|v| agentic_identity::spawn::SpawnId(v.to_string()))
+            .unwrap();
+
+        // A file where the receipt store expects a directory fails the
+        // grandchild's save after its budget was checked.
+        let receipts = tmp.path().join("receipts");
+        std::fs::rename(&receipts, tmp.path().join("receipts-aside")).unwrap();
+        std::fs::write(&receipts, b"not a directory").unwrap();
+        let resp = call(
+            &mut server,
+            "spawn_create",
+            json!({"identity": "default-worker", "purpose": "helper", "authority": ["read:docs"]}),
+        );
+        assert!(is_tool_error(&resp));
+
+        let record = SpawnStore::new(tmp.path().join("spawn"))
+            .unwrap()
+            .load(&spawn_id)
+            .unwrap();
+        assert_eq!(record.consumption.used(BudgetResource::SubSpawns), 0);
+    }
+
+    #[test]
+    fn test_startup_quarantines_partial_writes() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action": "Deployed"}}
+        }));
+        assert!(!is_tool_error(&resp));
+        assert!(server.recover_partial_writes().is_clean());
+
+        // A crash mid-write, from before saves were atomic.
+        std::fs::write(
+            tmp.path().join("receipts").join("arec_cut.json"),
+            b"{\"vers",
+        )
+        .unwrap();
+        std::fs::write(tmp.path().join("identity").join("default.aid.tmp"), b"{").unwrap();
+
+        let report = server.recover_partial_writes();
+        assert_eq!(report.quarantined.len(), 2);
+        assert!(tmp
+            .path()
+            .join("receipts/quarantine/arec_cut.json")
+            .exists());
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action": "Verified"}}
+        }));
+        assert!(!is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_spawn_create_failed_save_leaves_no_child() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        // A file where the spawn store expects a directory fails the last
+        // staged write, after the child identity and receipt were staged.
+        std::fs::write(tmp.path().join("spawn"), b"not a directory").unwrap();
+        let receipts = ReceiptStore::new(tmp.path().join("receipts")).unwrap();
+        let receipts_before = receipts.list().unwrap().len();
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"spawn_create",
+                "arguments":{"purpose":"index docs","authority":["read:docs"]}
+            }
+        }));
+        assert!(is_tool_error(&resp));
+
+        let identities: Vec<_> = std::fs::read_dir(tmp.path().join("identity"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        assert_eq!(identities, ["default.aid"]);
+        assert_eq!(receipts.list().unwrap().len(), receipts_before);
+        let journal: Vec<_> = std::fs::read_dir(tmp.path().join("transactions"))
+            .unwrap()
+            .collect();
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn test_spawn_create_same_type_twice_keeps_both_children() {
+        init();
+        let (mut server, tmp, _identity_id) = setup_identity();
+        let field = |resp: &Value, label: &str| {
+            tool_text(resp)
+                .lines()
+                .find_map(|l| l.trim().strip_prefix(label).map(|v| v.trim().to_string()))
+                .unwrap()
+        };
+
+        let mut children = Vec::new();
+        for n in 0..2 {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":n + 2,
+                "method":"tools/call",
+                "params":{
+                    "name":"spawn_create",
+                    "arguments":{"purpose":"index docs","authority":["read:docs"]}
+                }
+            }));
+            assert!(!is_tool_error(&resp));
+            children.push((
+                field(&resp, "Spawn ID:"),
+                field(&resp, "Child ID:"),
+                field(&resp, "Child file:"),
+            ));
+        }
+
+        // The first child keeps the original name; the second gets its own.
+        let first = tmp.path().join("identity").join("default-worker.aid");
+        assert_eq!(children[0].2, first.display().to_string());
+        assert_ne!(children[0].2, children[1].2);
+        for (_, child_id, file) in &children {
+            let doc = read_public_document(std::path::Path::new(file)).unwrap();
+            assert_eq!(&doc.id.0, child_id);
+        }
+
+        let lineage = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"spawn_lineage","arguments":{"spawn_id": children[1].0}}
+        }));
+        assert_eq!(field(&lineage, "Child file:"), children[1].2);
+
+        let terminated = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"spawn_terminate","arguments":{"spawn_id": children[0].0}}
+        }));
+        assert_eq!(field(&terminated, "Child file:"), children[0].2);
+    }
+
+    #[test]
+    fn test_spawn_list_filters_and_paginates() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+
+        for (i, spawn_type) in ["worker", "worker", "specialist"].iter().enumerate() {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":10 + i,
+                "method":"tools/call",
+                "params":{
+                    "name":"spawn_create",
+                    "arguments":{
+                        "purpose": format!("task {i}"),
+                        "authority":["read:docs"],
+                        "spawn_type": spawn_type
+                    }
+                }
+            }));
+            assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        }
+
+        let list = |server: &mut McpServer, args: Value| {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":20,
+                "method":"tools/call",
+                "params":{"name":"spawn_list","arguments": args}
+            }));
+            tool_text(&resp)
+        };
+
+        let first = list(&mut server, json!({"parent": identity_id, "limit": 2}));
+        assert!(first.contains("(2 shown, 3 total)"));
+        let cursor = first
+            .lines()
+            .find_map(|l| l.strip_prefix("Next cursor: "))
+            .unwrap()
+            .to_string();
+        let second = list(&mut server, json!({"limit": 2, "cursor": cursor}));
+        assert!(second.contains("(1 shown, 3 total)"));
+        assert!(!second.contains("Next cursor"));
+
+        let specialists = list(&mut server, json!({"spawn_type": "specialist"}));
+        assert!(specialists.contains("(1 shown, 1 total)"));
+
+        let childless = list(&mut server, json!({"parent": "aid_NoKids"}));
+        assert!(childless.contains("No spawned identities found"));
+    }
+
+    // ── idempotency ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_idempotent_action_sign_replays_across_restart() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let sign = |server: &mut McpServer, action: &str| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":30,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{"action": action, "idempotency_key":"retry-1"}
+                }
+            }))
+        };
+
+        let first = sign(&mut server, "Deployed v2");
+        assert!(!is_tool_error(&first));
+        let receipt_id = extract_receipt_id(&tool_text(&first));
+
+        // Same process retry.
+        let retry = sign(&mut server, "Deployed v2");
+        assert_eq!(extract_receipt_id(&tool_text(&retry)), receipt_id);
+
+        // Retry from a fresh process over the same directories.
+        let (mut restarted, _tmp2) = test_server();
+        restarted.identity_dir = server.identity_dir.clone();
+        restarted.receipt_dir = server.receipt_dir.clone();
+        restarted.idempotency_dir = server.idempotency_dir.clone();
+        let retry = sign(&mut restarted, "Deployed v2");
+        assert_eq!(extract_receipt_id(&tool_text(&retry)), receipt_id);
+        assert_eq!(
+            ReceiptStore::new(&server.receipt_dir)
+                .unwrap()
+                .list()
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Reusing the key for a different operation is a conflict.
+        let conflict = sign(&mut server, "Deployed v3");
+        assert!(is_tool_error(&conflict));
+        assert!(tool_text(&conflict).contains("different"));
+    }
+
+    #[test]
+    fn test_idempotency_failed_call_is_not_recorded() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let sign = |server: &mut McpServer| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":31,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{"action":"Deployed", "idempotency_key":"retry-2"}
+                }
+            }))
+        };
+
+        // No identity yet: the call fails and may be retried.
+        assert!(is_tool_error(&sign(&mut server)));
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":32,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+        assert!(!is_tool_error(&sign(&mut server)));
+
+        let bad_key = server.handle_request(json!({
+            "jsonrpc":"2.0","id":33,
+            "method":"tools/call",
+            "params":{
+                "name":"action_sign",
+                "arguments":{"action":"Deployed", "idempotency_key":"../escape"}
+            }
+        }));
+        assert!(is_tool_error(&bad_key));
+    }
+
+    #[test]
+    fn test_idempotency_records_expire_after_ttl() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = idempotency::IdempotencyStore::new(tmp.path(), 60);
+        let fingerprint = idempotency::fingerprint("action_sign", &json!({"action":"x"}));
+
+        store
+            .record(
+                "k",
+                "action_sign",
+                &fingerprint,
+                &json!({"ok": true}),
+                1_000,
+            )
+            .unwrap();
+        assert!(matches!(
+            store.lookup("k", &fingerprint, 1_060),
+            idempotency::Replay::Completed(_)
+        ));
+        assert!(matches!(
+            store.lookup("k", &fingerprint, 1_061),
+            idempotency::Replay::Fresh
+        ));
+        assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    // ── identity_health ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_identity_health_before_setup() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let req = json!({
+            "jsonrpc":"2.0","id":38,
+            "method":"tools/call",
+            "params":{"name":"identity_health","arguments":{}}
+        });
+        let resp = server.handle_request(req);
+        assert!(!is_tool_error(&resp));
+        let text = tool_text(&resp);
+        assert!(text.contains("NEEDS SETUP") || text.contains("HEALTHY"));
+    }
+
+    #[test]
+    fn test_identity_health_after_setup() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        // Create default identity.
+        let _ = server.handle_request(json!({
+            "jsonrpc":"2.0","id":39,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+
+        let req = json!({
+            "jsonrpc":"2.0","id":40,
+            "method":"tools/call",
+            "params":{"name":"identity_health","arguments":{}}
+        });
+        let resp = server.handle_request(req);
+        assert!(!is_tool_error(&resp));
+        let text = tool_text(&resp);
+        assert!(text.contains("HEALTHY"));
+    }
+
+    #[test]
+    fn test_identity_health_reports_expiring_grants() {
+        init();
+        let (mut server, _tmp, identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        for (cap, expires) in [
+            ("read:soon", Some("2h")),
+            ("read:later", Some("30d")),
+            ("read:forever", None),
+        ] {
+            let mut args = json!({"grantee": identity_id, "capabilities": [cap]});
+            if let Some(e) = expires {
+                args["expires"] = json!(e);
+            }
+            assert!(!is_tool_error(&call("trust_grant", args)));
+        }
+
+        let text = tool_text(&call("identity_health", json!({})));
+        assert!(text.contains("Expiring within 1d: 1"), "{text}");
+        assert!(text.contains("read:soon"));
+        assert!(!text.contains("read:later"));
+        assert!(!text.contains("read:forever"));
+        assert!(text.contains("Expired:     0"));
+
+        let text = tool_text(&call("identity_health", json!({"expiry_horizon":"60d"})));
+        assert!(text.contains("Expiring within 60d: 2"), "{text}");
+        let soon = text.find("read:soon").unwrap();
+        let later = text.find("read:later").unwrap();
+        assert!(soon < later);
+
+        assert!(is_tool_error(&call(
+            "identity_health",
+            json!({"expiry_horizon":"soon"})
+        )));
+    }
+
+    // ── continuity_gaps ───────────────────────────────────────────────────────
+
+    #[test]
+    fn test_continuity_gaps_report_probable_cause() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        let text = tool_text(&call("continuity_gaps", json!({"grace_period_seconds":0})));
+        assert!(text.contains("No experiences recorded yet"));
+
+        assert!(!is_tool_error(&call(
+            "continuity_record",
+            json!({"content_hash":"before"})
+        )));
+        assert!(!is_tool_error(&call(
+            "continuity_heartbeat",
+            json!({"status":"suspended"})
+        )));
+        assert!(!is_tool_error(&call(
+            "continuity_record",
+            json!({"content_hash":"after"})
+        )));
+
+        let j = tool_json(&call("continuity_gaps", json!({"grace_period_seconds":0})));
+        assert_eq!(j["experiences"], 2);
+        assert_eq!(j["heartbeats"], 1);
+        assert_eq!(j["gap_count"], 1);
+        assert_eq!(j["gaps"][0]["gap_type"], "temporal");
+        assert_eq!(j["gaps"][0]["probable_cause"], "planned_suspension");
+    }
+
+    #[test]
+    fn test_continuity_status_replays_stored_chain() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        let text = tool_text(&call("continuity_status", json!({})));
+        assert!(text.contains("No experiences recorded yet"));
+
+        for content_hash in ["one", "two", "three"] {
+            assert!(!is_tool_error(&call(
+                "continuity_record",
+                json!({ "content_hash": content_hash })
+            )));
+        }
+        assert!(!is_tool_error(&call("continuity_anchor", json!({}))));
+
+        let text = tool_text(&call("continuity_status", json!({})));
+        assert!(text.contains("Experiences: 4"), "{text}");
+        assert!(text.contains("Chain: intact (4 experiences replayed)"));
+        assert!(text.contains("Anchors: 1 (1 verified)"));
+    }
+
+    #[test]
+    fn test_continuity_confidence_scores_window() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        let j = tool_json(&call("continuity_confidence", json!({})));
+        assert_eq!(j["score"], 0.0);
+        assert_eq!(j["experiences"], 0);
+
+        assert!(!is_tool_error(&call(
+            "continuity_record",
+            json!({"content_hash":"first"})
+        )));
+        assert!(!is_tool_error(&call("continuity_heartbeat", json!({}))));
+        assert!(!is_tool_error(&call("continuity_anchor", json!({}))));
+
+        // The anchor is taken on a checkpoint experience of its own.
+        let j = tool_json(&call("continuity_confidence", json!({"from":0})));
+        assert_eq!(j["experiences"], 2);
+        assert_eq!(j["anchors"], 1);
+        assert_eq!(j["heartbeats"], 1);
+        let score = j["score"].as_f64().unwrap();
+        assert!(score > 0.0 && score <= 1.0);
+    }
+
+    // ── identity_rekey_stores ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_identity_rekey_stores() {
+        init();
+        let (mut server, _tmp) = test_server();
+        for name in ["default", "worker"] {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":"identity_create","arguments":{"name":name}}
+            }));
+        }
+
+        let rekey = |server: &mut McpServer, args: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"identity_rekey_stores","arguments":args}
+            }))
+        };
+
+        let resp = rekey(&mut server, json!({}));
+        assert!(is_tool_error(&resp));
+
+        let j = tool_json(&rekey(&mut server, json!({"new_passphrase":"rotated"})));
+        assert_eq!(j["examined"], 2);
+        assert_eq!(j["rekeyed"], 2);
+        assert_eq!(j["resumed"], false);
+        let path = server.identity_dir.join("worker.aid");
+        assert!(load_identity(&path, "rotated").is_ok());
+        assert!(load_identity(&path, LEGACY_MCP_PASSPHRASE).is_err());
+
+        // The files are no longer under the MCP passphrase.
+        let resp = rekey(&mut server, json!({"new_passphrase":"again"}));
+        assert!(is_tool_error(&resp));
+        assert!(load_identity(&path, "rotated").is_ok());
+
+        let j = tool_json(&rekey(
+            &mut server,
+            json!({"old_passphrase":"rotated","new_passphrase":LEGACY_MCP_PASSPHRASE}),
+        ));
+        assert_eq!(j["rekeyed"], 2);
+        assert!(load_identity(&path, LEGACY_MCP_PASSPHRASE).is_ok());
+    }
+
+    // ── identity_change_passphrase ────────────────────────────────────────────
+
+    #[test]
+    fn test_identity_change_passphrase_migrates_legacy_identity() {
+        let (mut server, _tmp, _id) = setup_identity();
+        server.passphrase = "configured".to_string();
+        let path = server.identity_dir.join("default.aid");
+
+        // Under the configured passphrase the legacy identity is unreadable.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"before migration"}}
+        }));
+        assert!(is_tool_error(&resp));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"identity_change_passphrase","arguments":{}}
+        }));
+        assert!(!is_tool_error(&resp));
+        assert_eq!(tool_json(&resp)["status"], "passphrase_changed");
+        assert!(load_identity(&path, "configured").is_ok());
+        assert!(load_identity(&path, LEGACY_MCP_PASSPHRASE).is_err());
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"after migration"}}
+        }));
+        assert!(!is_tool_error(&resp));
+
+        // Running it again fails: the file is no longer under the legacy passphrase.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":5,
+            "method":"tools/call",
+            "params":{"name":"identity_change_passphrase","arguments":{}}
+        }));
+        assert!(is_tool_error(&resp));
+        assert!(load_identity(&path, "configured").is_ok());
+    }
+
+    #[test]
+    fn test_identity_rotate_reencrypts_in_place() {
+        init();
+        let (mut server, tmp, old_id) = setup_identity();
+        let path = tmp.path().join("identity").join("default.aid");
+        let old_key = read_public_document(&path).unwrap().public_key;
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{"name":"identity_rotate","arguments":{"reason":"scheduled"}}
+        }));
+        assert!(!is_tool_error(&resp));
+        let j = tool_json(&resp);
+        assert_eq!(j["status"], "rotated");
+        assert_eq!(j["old_id"], old_id.as_str());
+        assert_ne!(j["new_id"], j["old_id"]);
+
+        let rotated = load_identity(&path, &server.passphrase).unwrap();
+        assert_eq!(rotated.id().0, j["new_id"].as_str().unwrap());
+        assert_eq!(rotated.rotation_history.len(), 1);
+        assert_eq!(rotated.rotation_history[0].previous_key, old_key);
+        assert!(read_public_document(&path)
+            .unwrap()
+            .verify_signature()
+            .is_ok());
+
+        // Signing keeps working with the new key.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":3,
+            "method":"tools/call",
+            "params":{"name":"action_sign","arguments":{"action":"after rotation"}}
+        }));
+        assert!(!is_tool_error(&resp));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":4,
+            "method":"tools/call",
+            "params":{"name":"identity_rotate","arguments":{"reason":"bored"}}
+        }));
+        assert!(is_tool_error(&resp));
+        assert_eq!(
+            load_identity(&path, &server.passphrase).unwrap().id(),
+            rotated.id()
+        );
+    }
+
+    #[test]
+    fn test_identity_vault_replaces_identity_files() {
+        init();
+        let (mut server, tmp) = test_server();
+        let vault = tmp.path().join("identities.vault");
+        server.identity_vault = Some(vault.clone());
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,"method":"tools/call",
+                "params":{"name":name,"arguments":arguments}
+            }))
+        };
+
+        for name in ["default", "alice"] {
+            let resp = call("identity_create", json!({"name": name}));
+            assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        }
+        assert!(!tmp.path().join("identity").join("alice.aid").exists());
+        let documents = read_vault_documents(&vault).unwrap();
+        assert_eq!(
+            documents.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["alice", "default"]
+        );
+        assert!(is_tool_error(&call(
+            "identity_create",
+            json!({"name": "alice"})
+        )));
+
+        let resp = call("action_sign", json!({"action": "from the vault"}));
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+
+        let resp = call("identity_rotate", json!({"name": "alice"}));
+        assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        let rotated = IdentityVault::open(&vault, LEGACY_MCP_PASSPHRASE)
+            .unwrap()
+            .load("alice")
+            .unwrap();
+        assert_eq!(rotated.rotation_history.len(), 1);
+        assert_ne!(rotated.id(), documents["alice"].id);
+
+        let resp = call(
+            "identity_change_passphrase",
+            json!({"name": "alice", "new_passphrase": "other"}),
+        );
+        assert!(is_tool_error(&resp));
+    }
+
+    // ── per-identity passphrases ──────────────────────────────────────────────
+
+    /// Write a passphrase file readable only by its owner.
+    fn write_passphrase_file(path: &std::path::Path, passphrase: &str) {
+        std::fs::write(path, format!("{passphrase}\n")).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        }
+    }
 
     #[test]
-    fn test_identity_health_before_setup() {
+    fn test_per_identity_passphrase_file_used_for_create_and_load() {
         init();
-        let (mut server, _tmp) = test_server();
-        let req = json!({
-            "jsonrpc":"2.0","id":38,
-            "method":"tools/call",
-            "params":{"name":"identity_health","arguments":{}}
-        });
-        let resp = server.handle_request(req);
-        assert!(!is_tool_error(&resp));
-        let text = tool_text(&resp);
-        assert!(text.contains("NEEDS SETUP") || text.contains("HEALTHY"));
+        let (mut server, tmp) = test_server();
+        let dir = tmp.path().join("passphrases");
+        std::fs::create_dir_all(&dir).unwrap();
+        write_passphrase_file(&dir.join("alice.passphrase"), "alice-secret");
+        server.passphrase_sources.dir = Some(dir);
+
+        for name in ["alice", "bob"] {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":1,
+                "method":"tools/call",
+                "params":{"name":"identity_create","arguments":{"name":name}}
+            }));
+            assert!(!is_tool_error(&resp));
+        }
+
+        // alice has her own passphrase; bob falls back to the server's.
+        let alice = server.identity_dir.join("alice.aid");
+        assert!(load_identity(&alice, "alice-secret").is_ok());
+        assert!(load_identity(&alice, &server.passphrase).is_err());
+        let bob = server.identity_dir.join("bob.aid");
+        assert!(load_identity(&bob, &server.passphrase).is_ok());
+
+        for name in ["alice", "bob"] {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name":"action_sign","arguments":{"identity":name,"action":"signed"}}
+            }));
+            assert!(!is_tool_error(&resp));
+        }
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_identity_health_after_setup() {
-        init();
-        let (mut server, _tmp) = test_server();
+    fn test_passphrase_file_open_to_others_is_refused() {
+        use std::os::unix::fs::PermissionsExt;
 
-        // Create default identity.
-        let _ = server.handle_request(json!({
-            "jsonrpc":"2.0","id":39,
+        init();
+        let (mut server, tmp) = test_server();
+        let dir = tmp.path().join("passphrases");
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("alice.passphrase");
+        write_passphrase_file(&file, "alice-secret");
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o644)).unwrap();
+        server.passphrase_sources.dir = Some(dir);
+
+        // Never silently falls back to the server passphrase.
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":1,
             "method":"tools/call",
-            "params":{"name":"identity_create","arguments":{}}
+            "params":{"name":"identity_create","arguments":{"name":"alice"}}
         }));
+        assert!(is_tool_error(&resp));
+        assert!(tool_text(&resp).contains("chmod 600"));
+        assert!(!server.identity_dir.join("alice.aid").exists());
 
-        let req = json!({
-            "jsonrpc":"2.0","id":40,
-            "method":"tools/call",
-            "params":{"name":"identity_health","arguments":{}}
-        });
-        let resp = server.handle_request(req);
-        assert!(!is_tool_error(&resp));
-        let text = tool_text(&resp);
-        assert!(text.contains("HEALTHY"));
+        let args = PassphraseArgs {
+            passphrase_file: Some(file.clone()),
+            ..Default::default()
+        };
+        assert!(configured_passphrase(&args).is_err());
+        std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(
+            configured_passphrase(&args).unwrap().as_deref(),
+            Some("alice-secret")
+        );
     }
 
     // ── unknown method ────────────────────────────────────────────────────────
@@ -5177,6 +11684,65 @@ mod tests {
         assert!(resp.is_null());
     }
 
+    #[test]
+    fn test_notifications_never_get_a_response() {
+        init();
+        let (mut server, _tmp) = test_server();
+        for req in [
+            json!({"jsonrpc":"2.0","method":"no_such_method"}),
+            json!({"jsonrpc":"1.0","method":"ping"}),
+            json!({"jsonrpc":"2.0"}),
+            json!({"jsonrpc":"2.0","method":"tools/call","params":{"name":"nope"}}),
+        ] {
+            assert!(server.handle_request(req).is_null());
+        }
+
+        // A null id is a request, not a notification.
+        let resp = server.handle_request(json!({"jsonrpc":"2.0","id":null,"method":"ping"}));
+        assert!(is_ok(&resp));
+        assert!(resp["id"].is_null());
+    }
+
+    // ── request ids ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_request_ids_round_trip_exactly() {
+        init();
+        let (mut server, _tmp) = test_server();
+        for raw_id in [
+            "7",
+            "-3",
+            "1.50",
+            "123456789012345678901234567890",
+            "\"req-1\"",
+            "\"ключ-🔑\"",
+            "\"42\"",
+        ] {
+            let req: Value = serde_json::from_str(&format!(
+                r#"{{"jsonrpc":"2.0","id":{raw_id},"method":"no_such_method"}}"#
+            ))
+            .unwrap();
+            let resp = server.handle_request(req);
+            assert_eq!(resp["error"]["code"], -32601);
+            assert_eq!(serde_json::to_string(&resp["id"]).unwrap(), raw_id);
+        }
+    }
+
+    #[test]
+    fn test_structured_request_id_is_invalid() {
+        init();
+        let (mut server, _tmp) = test_server();
+        for id in [json!({"n": 1}), json!([1])] {
+            let resp = server.handle_request(json!({"jsonrpc":"2.0","id":id,"method":"ping"}));
+            assert_eq!(resp["error"]["code"], -32600);
+            assert!(resp["id"].is_null());
+        }
+
+        let resp = server.handle_request(json!([{"jsonrpc":"2.0","id":1,"method":"ping"}]));
+        assert_eq!(resp["error"]["code"], -32600);
+        assert!(resp["id"].is_null());
+    }
+
     // ── resources/read ────────────────────────────────────────────────────────
 
     #[test]
@@ -5238,6 +11804,49 @@ mod tests {
         assert!(arr.as_array().map(|a| !a.is_empty()).unwrap_or(false));
     }
 
+    #[test]
+    fn test_resource_revocation_list() {
+        init();
+        let (mut server, _tmp) = test_server();
+        let grantee = registered_grantee(&server);
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":47,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let _ = call("identity_create", json!({}));
+        let grant = call(
+            "trust_grant",
+            json!({"grantee": grantee, "capabilities": ["read:calendar"]}),
+        );
+        let trust_id = extract_trust_id(&tool_text(&grant));
+        let _ = call(
+            "trust_grant",
+            json!({"grantee": grantee, "capabilities": ["write:notes"]}),
+        );
+        assert!(!is_tool_error(&call(
+            "trust_revoke",
+            json!({"trust_id": trust_id})
+        )));
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":48,
+            "method":"resources/read",
+            "params":{"uri":"aid://trust/revocations"}
+        }));
+        assert!(is_ok(&resp));
+        let text = resp["result"]["contents"][0]["text"].as_str().unwrap();
+        let list: RevocationList = serde_json::from_str(text).unwrap();
+
+        let doc = read_public_document(&server.identity_dir.join("default.aid")).unwrap();
+        assert!(agentic_identity::trust::verify_revocation_list(&list, &doc.public_key).is_ok());
+        assert_eq!(list.revocations.len(), 1);
+        assert!(list.is_revoked(&TrustId(trust_id)));
+    }
+
     // ── duration parser ───────────────────────────────────────────────────────
 
     #[test]
@@ -5272,6 +11881,9 @@ mod tests {
     fn test_parse_duration_invalid() {
         assert!(parse_duration_to_micros("invalid").is_err());
         assert!(parse_duration_to_micros("5x").is_err());
+        assert!(parse_duration_to_micros("1h1h")
+            .unwrap_err()
+            .contains("'h'"));
     }
 
     // ── datetime formatter ────────────────────────────────────────────────────
@@ -5489,6 +12101,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_competence_prove_uses_recorded_attempts() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let signed = call("action_sign", json!({"action": "deploy service"}));
+        let receipt_id = extract_receipt_id(&tool_text(&signed));
+
+        let prove = json!({"domain": "deploy", "min_attempts": 3, "min_rate": 0.7});
+        assert!(is_tool_error(&call("competence_prove", prove.clone())));
+
+        for outcome in ["success", "success", "failure", "success"] {
+            let resp = call(
+                "competence_record",
+                json!({"domain": "deploy", "outcome": outcome, "receipt_id": receipt_id}),
+            );
+            assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+        }
+
+        let shown = tool_text(&call("competence_show", json!({"domain": "deploy"})));
+        assert!(shown.contains("Attempts: 4 (3 succeeded, 1 failed, 0 partial)"));
+        assert!(shown.contains("Success rate: 75.0%"));
+
+        let listed = tool_text(&call("competence_list", json!({})));
+        assert!(listed.contains("deploy: 4 attempts, 75.0% success"));
+
+        let proved = call("competence_prove", prove);
+        assert!(!is_tool_error(&proved), "{}", tool_text(&proved));
+        assert!(tool_text(&proved).contains("aprf_"));
+
+        let too_strict = call(
+            "competence_prove",
+            json!({"domain": "deploy", "min_attempts": 3, "min_rate": 0.9}),
+        );
+        assert!(is_tool_error(&too_strict));
+    }
+
+    #[test]
+    fn test_negative_declaration_binds_check_and_proof() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let mut call = |name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+
+        let check = json!({"capability": "deploy:prod"});
+        assert!(tool_text(&call("negative_check", check.clone())).contains("POSSIBLE"));
+        assert!(is_tool_error(&call("negative_prove", check.clone())));
+
+        let declared = call(
+            "negative_declare",
+            json!({"capabilities": "deploy:*", "reason": "change freeze"}),
+        );
+        assert!(!is_tool_error(&declared), "{}", tool_text(&declared));
+
+        assert!(tool_text(&call("negative_check", check.clone())).contains("IMPOSSIBLE"));
+        assert!(tool_text(&call("negative_list", json!({}))).contains("[deploy:*]"));
+
+        let proved = call("negative_prove", check);
+        assert!(!is_tool_error(&proved), "{}", tool_text(&proved));
+        let proof_id = tool_text(&proved)
+            .split_whitespace()
+            .find(|w| w.starts_with("aneg_"))
+            .unwrap()
+            .to_string();
+
+        let verified = call("negative_verify", json!({"proof_id": proof_id}));
+        assert_eq!(
+            verified["result"]["structuredContent"]["outcomes"]["result"],
+            "valid"
+        );
+
+        let missing = call("negative_verify", json!({"proof_id": "aneg_Missing"}));
+        assert_eq!(
+            missing["result"]["structuredContent"]["outcomes"]["result"],
+            "proof_not_found"
+        );
+        assert!(is_tool_error(&call(
+            "negative_verify",
+            json!({"proof_id": "../declarations/x"})
+        )));
+    }
+
     // ── scale tests ─────────────────────────────────────────────────────
 
     #[test]
@@ -5561,106 +12267,708 @@ mod tests {
             "Expected at least 11 log entries, got {}",
             server.operation_log.len()
         );
-    }
+    }
+
+    // ── edge cases ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_action_context_unicode() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":400,
+            "method":"tools/call",
+            "params":{
+                "name":"action_context",
+                "arguments":{
+                    "intent": "建立与新合作者的信任",
+                    "decision": "付与読み取り専用アクセス",
+                    "topic": "국제화-테스트"
+                }
+            }
+        }));
+        assert!(!is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_action_context_special_chars() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":401,
+            "method":"tools/call",
+            "params":{
+                "name":"action_context",
+                "arguments":{
+                    "intent": "Check \"quotes\" and 'apostrophes' & <angle> brackets",
+                    "decision": "Found: \\ backslash, \t tab"
+                }
+            }
+        }));
+        assert!(!is_tool_error(&resp));
+    }
+
+    #[test]
+    fn test_action_context_long_intent() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        let long_intent = "Y".repeat(10_000);
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":402,
+            "method":"tools/call",
+            "params":{
+                "name":"action_context",
+                "arguments":{
+                    "intent": long_intent
+                }
+            }
+        }));
+        assert!(!is_tool_error(&resp));
+    }
+
+    // ── regression ──────────────────────────────────────────────────────
+
+    #[test]
+    fn test_tools_list_includes_action_context() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        let resp = server.handle_request(json!({
+            "jsonrpc":"2.0","id":500,
+            "method":"tools/list",
+            "params":{}
+        }));
+
+        let tools = resp["result"]["tools"].as_array().unwrap();
+        let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
+        assert!(
+            names.contains(&"action_context"),
+            "Tool list must include action_context, found: {:?}",
+            names
+        );
+    }
+
+    #[test]
+    fn test_operation_log_timestamps_nonzero() {
+        init();
+        let (mut server, _tmp) = test_server();
+
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":501,
+            "method":"tools/call",
+            "params":{"name":"identity_create","arguments":{}}
+        }));
+
+        assert!(!server.operation_log.is_empty());
+        assert!(
+            server.operation_log[0].timestamp > 0,
+            "Timestamp should be non-zero"
+        );
+    }
+
+    #[test]
+    fn test_audit_log_persists_and_seals_sessions() {
+        init();
+        let (mut server, tmp, identity_id) = setup_identity();
+        let call = |server: &mut McpServer, name: &str, arguments: Value| {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":520,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            }))
+        };
+        call(
+            &mut server,
+            "action_context",
+            json!({"intent":"review access"}),
+        );
+        let session = server.audit.current().unwrap().id.clone();
+        let ended = tool_json(&call(&mut server, "session_end", json!({})));
+        assert_eq!(ended["audit_session"], session.as_str());
+        assert!(server.audit.current().is_none());
+
+        let listed = tool_json(&call(&mut server, "audit_list", json!({})));
+        assert_eq!(listed["total"], 1);
+        assert_eq!(listed["sessions"][0]["session_id"], session.as_str());
+        assert_eq!(listed["sessions"][0]["record_count"], 3);
+        assert_eq!(listed["sessions"][0]["sealed"], true);
+        // Logging the audit_list call opened the next session.
+        assert!(server.audit.current().is_some());
+
+        let shown = tool_json(&call(
+            &mut server,
+            "audit_show",
+            json!({"session_id": &session}),
+        ));
+        let tools: Vec<&str> = shown["records"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["tool_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(tools, ["identity_create", "action_context", "session_end"]);
+        assert_eq!(shown["records"][1]["intent"], "review access");
+        assert_eq!(shown["digest_matches"], true);
+        assert_eq!(shown["receipt_valid"], true);
+        let receipt_id = ReceiptId(shown["seal"]["receipt_id"].as_str().unwrap().to_string());
+        let receipt = ReceiptStore::new(&server.receipt_dir)
+            .unwrap()
+            .load(&receipt_id)
+            .unwrap();
+        assert_eq!(receipt.actor.0, identity_id);
+        assert_eq!(receipt.action_type.as_tag(), AUDIT_SEAL_ACTION);
+
+        // Editing the transcript after sealing is detected.
+        let transcript = tmp.path().join("audit").join(format!("{session}.ndjson"));
+        let mut text = std::fs::read_to_string(&transcript).unwrap();
+        text = text.replace("review access", "nothing to see");
+        std::fs::write(&transcript, text).unwrap();
+        let shown = tool_json(&call(
+            &mut server,
+            "audit_show",
+            json!({"session_id": &session}),
+        ));
+        assert_eq!(shown["digest_matches"], false);
+
+        let bad = call(
+            &mut server,
+            "audit_show",
+            json!({"session_id": "../receipts/x"}),
+        );
+        assert!(is_tool_error(&bad));
 
-    // ── edge cases ──────────────────────────────────────────────────────
+        // Without an identity to sign with, shutdown still seals, unsigned.
+        let (mut bare, _bare_tmp) = test_server();
+        call(&mut bare, "session_trace", json!({}));
+        let open = bare.audit.current().unwrap().id.clone();
+        bare.shutdown();
+        let seal = bare.audit.seal(&open).unwrap().unwrap();
+        assert_eq!(seal.record_count, 1);
+        assert!(seal.receipt_id.is_none());
+    }
 
     #[test]
-    fn test_action_context_unicode() {
+    fn test_session_trace_records_timings_including_errors() {
         init();
         let (mut server, _tmp) = test_server();
+        server.trace = true;
+
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":510,
+            "method":"tools/call",
+            "params":{"name":"identity_show","arguments":{"name":"missing"}}
+        }));
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":511,
+            "method":"tools/call",
+            "params":{"name":"no_such_tool","arguments":{}}
+        }));
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":512,
+            "method":"tools/call",
+            "params":{"name":"trust_list","arguments":{}}
+        }));
 
         let resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":400,
+            "jsonrpc":"2.0","id":513,
             "method":"tools/call",
-            "params":{
-                "name":"action_context",
-                "arguments":{
-                    "intent": "建立与新合作者的信任",
-                    "decision": "付与読み取り専用アクセス",
-                    "topic": "국제화-테스트"
-                }
-            }
+            "params":{"name":"session_trace","arguments":{}}
         }));
-        assert!(!is_tool_error(&resp));
+        let trace = tool_json(&resp);
+        assert_eq!(trace["trace_enabled"], true);
+        assert_eq!(trace["call_count"], 3);
+        assert_eq!(trace["error_count"], 2);
+        let outcomes: Vec<&str> = trace["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["outcome"].as_str().unwrap())
+            .collect();
+        assert_eq!(outcomes, vec!["error", "rpc_error", "ok"]);
+        assert!(trace["entries"][0]["elapsed_micros"].is_u64());
     }
 
+    #[cfg(feature = "net")]
     #[test]
-    fn test_action_context_special_chars() {
+    fn test_socket_connection_uses_newline_framing() {
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
         init();
-        let (mut server, _tmp) = test_server();
+        let (server, _tmp) = test_server();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let lines = runtime.block_on(async {
+            let (client, remote) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(remote);
+            let connection = tokio::spawn(net::serve_connection(server, read, write));
+
+            let (client_read, mut client_write) = tokio::io::split(client);
+            client_write
+                .write_all(
+                    concat!(
+                        r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+                        "\n",
+                        r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+                        "\n\nnot json\n",
+                        r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"identity_create","arguments":{}}}"#,
+                        "\n",
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            client_write.shutdown().await.unwrap();
 
-        let resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":401,
-            "method":"tools/call",
-            "params":{
-                "name":"action_context",
-                "arguments":{
-                    "intent": "Check \"quotes\" and 'apostrophes' & <angle> brackets",
-                    "decision": "Found: \\ backslash, \t tab"
-                }
+            let mut lines = Vec::new();
+            let mut reader = BufReader::new(client_read).lines();
+            while let Some(line) = reader.next_line().await.unwrap() {
+                lines.push(serde_json::from_str::<Value>(&line).unwrap());
             }
-        }));
-        assert!(!is_tool_error(&resp));
+            connection.await.unwrap().unwrap();
+            lines
+        });
+
+        // No response for the notification or the blank line.
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["id"], 1);
+        assert_eq!(lines[1]["error"]["code"], -32700);
+        assert_eq!(lines[2]["id"], 2);
+        assert!(!is_tool_error(&lines[2]), "{}", tool_text(&lines[2]));
+
+        assert!(matches!(
+            net::ListenAddr::parse("127.0.0.1:7411"),
+            Ok(net::ListenAddr::Tcp(_))
+        ));
+        assert!(net::ListenAddr::parse("unix:").is_err());
     }
 
+    #[cfg(feature = "net")]
     #[test]
-    fn test_action_context_long_intent() {
+    fn test_socket_transport_refuses_remote_and_http() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
         init();
-        let (mut server, _tmp) = test_server();
+        let (server, tmp) = test_server();
+        let dir = tmp.path().to_path_buf();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let (refused, output) = runtime.block_on(async {
+            let refused = net::serve(
+                net::ListenAddr::Tcp("0.0.0.0:0".to_string()),
+                false,
+                move || server_in(&dir),
+            )
+            .await
+            .unwrap_err();
+
+            // A browser form POST whose body is a JSON-RPC line.
+            let (client, remote) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(remote);
+            let connection = tokio::spawn(net::serve_connection(server, read, write));
+            let (mut client_read, mut client_write) = tokio::io::split(client);
+            client_write
+                .write_all(
+                    concat!(
+                        "POST / HTTP/1.1\r\n",
+                        "Host: 127.0.0.1:7411\r\n",
+                        "Content-Type: text/plain\r\n",
+                        "\r\n",
+                        r#"{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{"name":"identity_create","arguments":{}}}"#,
+                        "\n",
+                    )
+                    .as_bytes(),
+                )
+                .await
+                .unwrap();
+            connection.await.unwrap().unwrap();
+            let mut output = Vec::new();
+            client_read.read_to_end(&mut output).await.unwrap();
+            (refused, output)
+        });
 
-        let long_intent = "Y".repeat(10_000);
-        let resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":402,
+        assert_eq!(refused.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(refused.to_string().contains("--listen-remote"));
+        assert!(output.is_empty());
+        assert!(!tmp.path().join("identity").join("default.aid").exists());
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_http_transport_refuses_remote() {
+        init();
+        let (_server, tmp) = test_server();
+        let dir = tmp.path().to_path_buf();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let refused = runtime
+            .block_on(http::serve(
+                "0.0.0.0:0",
+                false,
+                move || server_in(&dir),
+                std::future::pending(),
+            ))
+            .unwrap_err();
+        assert_eq!(refused.kind(), std::io::ErrorKind::PermissionDenied);
+        assert!(refused.to_string().contains("--listen-remote"));
+
+        // The opt-in applies to either network transport, but only to one.
+        let parses = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("agentic-identity-mcp").chain(args.iter().copied()))
+                .is_ok()
+        };
+        assert!(parses(&["--http", "0.0.0.0:7411", "--listen-remote"]));
+        assert!(parses(&["--listen", "0.0.0.0:7411", "--listen-remote"]));
+        assert!(!parses(&["--listen-remote"]));
+    }
+
+    /// Send one HTTP request and read back its status, headers and body.
+    #[cfg(feature = "net")]
+    async fn http_exchange<R, W>(
+        reader: &mut R,
+        writer: &mut W,
+        request: String,
+    ) -> (u16, Vec<(String, String)>, String)
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+        writer.write_all(request.as_bytes()).await.unwrap();
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line).await.unwrap();
+        let status = status_line.split(' ').nth(1).unwrap().parse().unwrap();
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap();
+            let Some((name, value)) = line.trim_end().split_once(':') else {
+                break;
+            };
+            headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+        }
+        let length: usize = headers
+            .iter()
+            .find(|(n, _)| n == "content-length")
+            .map(|(_, v)| v.parse().unwrap())
+            .unwrap();
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).await.unwrap();
+        (status, headers, String::from_utf8(body).unwrap())
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_http_transport_sessions() {
+        use tokio::io::AsyncWriteExt;
+
+        init();
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let state = std::sync::Arc::new(http::HttpState::new(move || server_in(&dir), true));
+
+        let request = |method: &str, host: &str, headers: &[(&str, &str)], body: &Value| {
+            let body = if body.is_null() {
+                String::new()
+            } else {
+                body.to_string()
+            };
+            let mut out = format!(
+                "{method} {} HTTP/1.1\r\nHost: {host}\r\nContent-Length: {}\r\n",
+                http::MCP_PATH,
+                body.len()
+            );
+            for (name, value) in headers {
+                out.push_str(&format!("{name}: {value}\r\n"));
+            }
+            out + "\r\n" + &body
+        };
+        let header = |headers: &[(String, String)], name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.clone())
+        };
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let (client, remote) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(remote);
+            let (_stop, stopped) = tokio::sync::watch::channel(false);
+            let connection = tokio::spawn(http::serve_connection(state, read, write, stopped));
+            let (client_read, mut writer) = tokio::io::split(client);
+            let mut reader = tokio::io::BufReader::new(client_read);
+            let ping = json!({"jsonrpc":"2.0","id":1,"method":"ping"});
+
+            // Everything but initialize needs a session.
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("POST", "localhost", &[], &ping),
+            )
+            .await;
+            assert_eq!(status, 400);
+
+            let initialize = json!({
+                "jsonrpc":"2.0","id":1,"method":"initialize",
+                "params":{"protocolVersion":"2025-03-26","capabilities":{},
+                          "clientInfo":{"name":"test","version":"0"}}
+            });
+            let (status, headers, body) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("POST", "127.0.0.1:7412", &[], &initialize),
+            )
+            .await;
+            assert_eq!(status, 200, "{body}");
+            let session = header(&headers, "mcp-session-id").unwrap();
+            let session_header = [("Mcp-Session-Id", session.as_str())];
+
+            let initialized = json!({"jsonrpc":"2.0","method":"notifications/initialized"});
+            let (status, _, body) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("POST", "localhost", &session_header, &initialized),
+            )
+            .await;
+            assert_eq!((status, body.as_str()), (202, ""));
+
+            // A client that takes only SSE gets the response as one event.
+            let create = json!({
+                "jsonrpc":"2.0","id":2,"method":"tools/call",
+                "params":{"name":"identity_create","arguments":{}}
+            });
+            let (status, headers, body) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request(
+                    "POST",
+                    "localhost",
+                    &[session_header[0], ("Accept", "text/event-stream")],
+                    &create,
+                ),
+            )
+            .await;
+            assert_eq!(status, 200);
+            assert_eq!(
+                header(&headers, "content-type").as_deref(),
+                Some("text/event-stream")
+            );
+            let data = body
+                .strip_prefix("event: message\ndata: ")
+                .and_then(|rest| rest.strip_suffix("\n\n"))
+                .unwrap();
+            let response: Value = serde_json::from_str(data).unwrap();
+            assert!(!is_tool_error(&response), "{}", tool_text(&response));
+
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("GET", "localhost", &session_header, &Value::Null),
+            )
+            .await;
+            assert_eq!(status, 405);
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("POST", "evil.example", &session_header, &ping),
+            )
+            .await;
+            assert_eq!(status, 403);
+
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("DELETE", "localhost", &session_header, &Value::Null),
+            )
+            .await;
+            assert_eq!(status, 204);
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                request("POST", "localhost", &session_header, &ping),
+            )
+            .await;
+            assert_eq!(status, 404);
+
+            // Dropping one half of the split stream does not close it.
+            writer.shutdown().await.unwrap();
+            connection.await.unwrap().unwrap();
+        });
+    }
+
+    #[cfg(feature = "net")]
+    #[test]
+    fn test_http_transport_expires_idle_sessions_and_slow_requests() {
+        use std::time::Duration;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        init();
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let state = std::sync::Arc::new(
+            http::HttpState::new(move || server_in(&dir), true)
+                .with_timeouts(Duration::from_millis(50), Duration::from_millis(200)),
+        );
+        let post = |headers: &str, body: &Value| {
+            let body = body.to_string();
+            format!(
+                "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n{headers}\r\n{body}",
+                http::MCP_PATH,
+                body.len()
+            )
+        };
+        let initialize = json!({
+            "jsonrpc":"2.0","id":1,"method":"initialize",
+            "params":{"protocolVersion":"2025-03-26","capabilities":{},
+                      "clientInfo":{"name":"test","version":"0"}}
+        });
+        let ping = json!({"jsonrpc":"2.0","id":2,"method":"ping"});
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let (client, remote) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(remote);
+            let (_stop, stopped) = tokio::sync::watch::channel(false);
+            let connection =
+                tokio::spawn(http::serve_connection(state.clone(), read, write, stopped));
+            let (client_read, mut writer) = tokio::io::split(client);
+            let mut reader = tokio::io::BufReader::new(client_read);
+
+            let (status, headers, _) =
+                http_exchange(&mut reader, &mut writer, post("", &initialize)).await;
+            assert_eq!(status, 200);
+            let (_, stale) = headers
+                .into_iter()
+                .find(|(n, _)| n == "mcp-session-id")
+                .unwrap();
+
+            // The next initialize closes the session left idle.
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let (status, _, _) =
+                http_exchange(&mut reader, &mut writer, post("", &initialize)).await;
+            assert_eq!(status, 200);
+            let (status, _, _) = http_exchange(
+                &mut reader,
+                &mut writer,
+                post(&format!("Mcp-Session-Id: {stale}\r\n"), &ping),
+            )
+            .await;
+            assert_eq!(status, 404);
+
+            // A request that stalls part-way is answered with 408 and closed.
+            writer
+                .write_all(format!("POST {} HTTP/1.1\r\n", http::MCP_PATH).as_bytes())
+                .await
+                .unwrap();
+            connection.await.unwrap().unwrap();
+            let mut output = String::new();
+            reader.read_to_string(&mut output).await.unwrap();
+            assert!(output.starts_with("HTTP/1.1 408"), "{output}");
+
+            // An idle keep-alive connection is closed without a response.
+            let (client, remote) = tokio::io::duplex(64 * 1024);
+            let (read, write) = tokio::io::split(remote);
+            let (_stop, stopped) = tokio::sync::watch::channel(false);
+            http::serve_connection(state, read, write, stopped)
+                .await
+                .unwrap();
+            let (mut client_read, _client_write) = tokio::io::split(client);
+            let mut output = Vec::new();
+            client_read.read_to_end(&mut output).await.unwrap();
+            assert!(output.is_empty());
+        });
+    }
+
+    #[test]
+    fn test_operation_log_export_is_signed_and_redacted() {
+        init();
+        let (mut server, _tmp, _id) = setup_identity();
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
             "method":"tools/call",
             "params":{
                 "name":"action_context",
-                "arguments":{
-                    "intent": long_intent
-                }
+                "arguments":{"intent":"audit the calendar grants","decision":"token=hunter2"}
             }
         }));
-        assert!(!is_tool_error(&resp));
-    }
+        let export = |server: &mut McpServer, include_summaries: bool| {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{
+                    "name":"operation_log_export",
+                    "arguments":{"include_summaries": include_summaries}
+                }
+            }));
+            assert!(!is_tool_error(&resp), "{}", tool_text(&resp));
+            serde_json::from_str::<SessionLog>(&tool_text(&resp)).unwrap()
+        };
 
-    // ── regression ──────────────────────────────────────────────────────
+        let log = export(&mut server, false);
+        assert!(log.redacted);
+        assert!(!serde_json::to_string(&log).unwrap().contains("hunter2"));
+        let context = log
+            .entries
+            .iter()
+            .find(|e| e.tool_name == "action_context")
+            .unwrap();
+        assert_eq!(context.intent.as_deref(), Some("audit the calendar grants"));
+
+        let key = log.signer_key.clone();
+        assert!(agentic_identity::receipt::verify_session_log(&log, &key).is_ok());
+        let mut tampered = log;
+        tampered.entries.pop();
+        assert!(agentic_identity::receipt::verify_session_log(&tampered, &key).is_err());
+
+        let full = export(&mut server, true);
+        assert!(!full.redacted);
+        assert!(serde_json::to_string(&full).unwrap().contains("hunter2"));
+    }
 
     #[test]
-    fn test_tools_list_includes_action_context() {
+    fn test_session_trace_without_flag_is_empty() {
         init();
         let (mut server, _tmp) = test_server();
 
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":520,
+            "method":"tools/call",
+            "params":{"name":"trust_list","arguments":{}}
+        }));
         let resp = server.handle_request(json!({
-            "jsonrpc":"2.0","id":500,
-            "method":"tools/list",
-            "params":{}
+            "jsonrpc":"2.0","id":521,
+            "method":"tools/call",
+            "params":{"name":"session_trace","arguments":{}}
         }));
-
-        let tools = resp["result"]["tools"].as_array().unwrap();
-        let names: Vec<&str> = tools.iter().filter_map(|t| t["name"].as_str()).collect();
-        assert!(
-            names.contains(&"action_context"),
-            "Tool list must include action_context, found: {:?}",
-            names
-        );
+        let trace = tool_json(&resp);
+        assert_eq!(trace["trace_enabled"], false);
+        assert_eq!(trace["call_count"], 0);
+        assert!(server.operation_log[0].elapsed_micros.is_none());
     }
 
     #[test]
-    fn test_operation_log_timestamps_nonzero() {
+    fn test_operation_log_is_bounded() {
         init();
         let (mut server, _tmp) = test_server();
+        server.operation_log_capacity = 3;
+        server.trace = true;
 
+        for i in 0..5 {
+            server.handle_request(json!({
+                "jsonrpc":"2.0","id":530 + i,
+                "method":"tools/call",
+                "params":{"name":"trust_list","arguments":{}}
+            }));
+        }
         server.handle_request(json!({
-            "jsonrpc":"2.0","id":501,
+            "jsonrpc":"2.0","id":540,
             "method":"tools/call",
-            "params":{"name":"identity_create","arguments":{}}
+            "params":{"name":"receipt_list","arguments":{}}
         }));
 
-        assert!(!server.operation_log.is_empty());
-        assert!(
-            server.operation_log[0].timestamp > 0,
-            "Timestamp should be non-zero"
-        );
+        assert_eq!(server.operation_log.len(), 3);
+        assert_eq!(server.operation_log[2].tool_name, "receipt_list");
     }
 
     // ════════════════════════════════════════════════════════════════════════
@@ -5956,6 +13264,7 @@ mod tests {
     fn test_v2_grounding_multiple_evidence() {
         init();
         let (mut server, _tmp, identity_id) = setup_identity();
+        let other = registered_grantee(&server);
 
         // Multiple grants.
         server.handle_request(json!({
@@ -5975,7 +13284,7 @@ mod tests {
             "params":{
                 "name":"trust_grant",
                 "arguments":{
-                    "grantee":"aid_other",
+                    "grantee": other,
                     "capabilities": ["deploy:staging"]
                 }
             }
@@ -6013,6 +13322,185 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_grounding_normalize_matches_whole_words() {
+        let none = SynonymMap::default();
+        let cap = grounding::normalize("read:calendar", &none);
+        let claim = grounding::normalize("Check the CALENDAR.", &none);
+        assert_eq!(grounding::overlap(&cap, &claim), 1);
+        let claim = grounding::normalize("already spread the news", &none);
+        assert_eq!(grounding::overlap(&cap, &claim), 0);
+        assert_eq!(
+            grounding::normalize("deploy-prod_eu west", &none),
+            grounding::normalize("deploy prod eu west", &none)
+        );
+        assert!(grounding::normalize("the and to", &none).is_empty());
+
+        let synonyms: SynonymMap = serde_json::from_str(r#"{"read": ["View", "fetch"]}"#).unwrap();
+        let cap = grounding::normalize("read:calendar", &synonyms);
+        let claim = grounding::normalize("can view calendar", &synonyms);
+        assert_eq!(grounding::overlap(&cap, &claim), 2);
+    }
+
+    #[test]
+    fn test_v2_grounding_ignores_substring_matches() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"action_sign",
+                "arguments":{"action":"Already spread the update","action_type":"mutation"}
+            }
+        }));
+
+        let ground = |server: &mut McpServer| {
+            tool_json(&server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{"name":"identity_ground","arguments":{"claim":"read update"}}
+            })))
+        };
+        let j = ground(&mut server);
+        let evidence = j["evidence"].as_array().unwrap();
+        assert_eq!(evidence.len(), 1);
+        // Only "update" matches; "read" is not a word of the receipt.
+        assert_eq!(evidence[0]["score"], 0.5);
+
+        server.similarity = Box::new(LexicalSimilarity::new(
+            serde_json::from_str(r#"{"read": ["spread"]}"#).unwrap(),
+        ));
+        let j = ground(&mut server);
+        assert_eq!(j["evidence"][0]["score"], 1.0);
+    }
+
+    /// Puts texts about releasing and deploying on one axis, everything
+    /// else on the other; "unembeddable" has no vector.
+    struct TopicVectorizer;
+
+    impl similarity::Vectorizer for TopicVectorizer {
+        fn embed(&self, text: &str) -> Option<Vec<f32>> {
+            let text = text.to_lowercase();
+            if text.contains("unembeddable") {
+                None
+            } else if text.contains("deploy") || text.contains("release") {
+                Some(vec![1.0, 0.1])
+            } else {
+                Some(vec![0.0, 1.0])
+            }
+        }
+    }
+
+    #[test]
+    fn test_grounding_with_embedding_similarity() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let grantee = registered_grantee(&server);
+        server.handle_request(json!({
+            "jsonrpc":"2.0","id":2,
+            "method":"tools/call",
+            "params":{
+                "name":"trust_grant",
+                "arguments":{"grantee": grantee, "capabilities": ["deploy:production"]}
+            }
+        }));
+        let call = |server: &mut McpServer, name: &str, arguments: Value| {
+            tool_json(&server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{"name": name, "arguments": arguments}
+            })))
+        };
+
+        // No shared word, so lexical matching finds nothing.
+        let j = call(
+            &mut server,
+            "identity_ground",
+            json!({"claim":"release the build"}),
+        );
+        assert_eq!(j["status"], "ungrounded");
+
+        server.similarity = Box::new(EmbeddingSimilarity::new(
+            Box::new(TopicVectorizer),
+            DEFAULT_MIN_SIMILARITY,
+            LexicalSimilarity::default(),
+        ));
+        let j = call(
+            &mut server,
+            "identity_ground",
+            json!({"claim":"release the build"}),
+        );
+        assert_eq!(j["status"], "verified");
+        assert_eq!(j["evidence"][0]["capabilities"][0], "deploy:production");
+        let j = call(
+            &mut server,
+            "identity_ground",
+            json!({"claim":"read the calendar"}),
+        );
+        assert_eq!(j["status"], "ungrounded");
+
+        let j = call(&mut server, "identity_evidence", json!({"query":"release"}));
+        assert_eq!(j["count"], 1);
+        assert_eq!(j["evidence"][0]["type"], "trust_grant");
+
+        // Without a vector for the claim, scoring falls back to words.
+        let j = call(
+            &mut server,
+            "identity_ground",
+            json!({"claim":"unembeddable production"}),
+        );
+        assert_eq!(j["status"], "verified");
+        assert!((j["evidence"][0]["score"].as_f64().unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_grounding_cache_tracks_store_changes() {
+        init();
+        let (mut server, _tmp, _identity_id) = setup_identity();
+        let sign = |server: &mut McpServer, action: &str| {
+            let resp = server.handle_request(json!({
+                "jsonrpc":"2.0","id":2,
+                "method":"tools/call",
+                "params":{
+                    "name":"action_sign",
+                    "arguments":{"action": action, "action_type":"mutation"}
+                }
+            }));
+            extract_receipt_id(&tool_text(&resp))
+        };
+        let ground = |server: &mut McpServer| {
+            tool_json(&server.handle_request(json!({
+                "jsonrpc":"2.0","id":3,
+                "method":"tools/call",
+                "params":{"name":"identity_ground","arguments":{"claim":"deploy"}}
+            })))
+        };
+
+        let first = sign(&mut server, "Deploy api");
+        assert_eq!(ground(&mut server)["evidence_count"], 1);
+        assert_eq!(server.grounding_cache.borrow().len(), 1);
+
+        // A new receipt changes the store stamp and is summarized on demand.
+        sign(&mut server, "Deploy worker");
+        assert_eq!(ground(&mut server)["evidence_count"], 2);
+        assert_eq!(server.grounding_cache.borrow().len(), 2);
+
+        // A deleted receipt drops out of the index and the cache.
+        ReceiptStore::new(&server.receipt_dir)
+            .unwrap()
+            .delete(&ReceiptId(first))
+            .unwrap();
+        let cached = ground(&mut server);
+        assert_eq!(cached["evidence_count"], 1);
+        assert_eq!(server.grounding_cache.borrow().len(), 1);
+
+        // A disabled cache reads the stores afresh and agrees.
+        server.grounding_cache = std::cell::RefCell::new(GroundingCache::new(false));
+        assert_eq!(ground(&mut server), cached);
+    }
+
     #[test]
     fn test_v2_evidence_basic() {
         init();
@@ -6059,16 +13547,17 @@ mod tests {
     fn test_v2_evidence_max_results() {
         init();
         let (mut server, _tmp, _identity_id) = setup_identity();
+        let agents: Vec<String> = (0..5).map(|_| registered_grantee(&server)).collect();
 
         // Create multiple grants that all match "deploy".
-        for i in 0..5 {
+        for (i, agent) in agents.iter().enumerate() {
             server.handle_request(json!({
                 "jsonrpc":"2.0","id": 10 + i,
                 "method":"tools/call",
                 "params":{
                     "name":"trust_grant",
                     "arguments":{
-                        "grantee": format!("aid_agent_{i}"),
+                        "grantee": agent,
                         "capabilities": [format!("deploy:env_{i}")]
                     }
                 }
@@ -6133,16 +13622,17 @@ mod tests {
     fn test_v2_suggest_limit() {
         init();
         let (mut server, _tmp, _identity_id) = setup_identity();
+        let agents: Vec<String> = (0..5).map(|_| registered_grantee(&server)).collect();
 
         // Create multiple grants.
-        for i in 0..5 {
+        for (i, agent) in agents.iter().enumerate() {
             server.handle_request(json!({
                 "jsonrpc":"2.0","id": 10 + i,
                 "method":"tools/call",
                 "params":{
                     "name":"trust_grant",
                     "arguments":{
-                        "grantee": format!("aid_agent_{i}"),
+                        "grantee": agent,
                         "capabilities": [format!("deploy:{i}")]
                     }
                 }
@@ -6429,6 +13919,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_workspace_query_cache_tracks_store_changes() {
+        init();
+        let ctx_tmp = tempfile::tempdir().unwrap();
+        let dir = setup_context_dir(ctx_tmp.path(), "agent-a");
+        let store = ReceiptStore::new(dir.join("receipts")).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let sign = |text: &str| {
+            let receipt =
+                ReceiptBuilder::new(anchor.id(), ActionType::Mutation, ActionContent::new(text))
+                    .sign(anchor.signing_key())
+                    .unwrap();
+            store.save(&receipt).unwrap();
+        };
+        sign("deploy api");
+
+        let path = dir.to_str().unwrap();
+        let mut cached = IdentityWorkspaceManager::new(4);
+        let ws = cached.create("cached");
+        cached.add_context(&ws, path, "primary", None).unwrap();
+        let mut uncached = IdentityWorkspaceManager::new(0);
+        let ws_uncached = uncached.create("uncached");
+        uncached
+            .add_context(&ws_uncached, path, "primary", None)
+            .unwrap();
+
+        let first = cached.query_all(&ws, "deploy", 5).unwrap();
+        let second = cached.query_all(&ws, "deploy", 5).unwrap();
+        assert_eq!(
+            serde_json::to_vec(&first).unwrap(),
+            serde_json::to_vec(&second).unwrap()
+        );
+        assert_eq!(first[0]["match_count"], 1);
+        assert_eq!(cached.query_cache.borrow().entries.len(), 1);
+
+        // A new receipt changes the store, so the cached result is dropped.
+        sign("deploy worker");
+        let after = cached.query_all(&ws, "deploy", 5).unwrap();
+        assert_eq!(after[0]["match_count"], 2);
+        assert_eq!(
+            after,
+            uncached.query_all(&ws_uncached, "deploy", 5).unwrap()
+        );
+        assert!(uncached.query_cache.borrow().entries.is_empty());
+
+        // Adding a context invalidates the workspace's entries.
+        let other = setup_context_dir(ctx_tmp.path(), "agent-b");
+        cached
+            .add_context(&ws, other.to_str().unwrap(), "secondary", None)
+            .unwrap();
+        assert!(cached.query_cache.borrow().entries.is_empty());
+        assert_eq!(cached.query_all(&ws, "deploy", 5).unwrap().len(), 2);
+    }
+
     #[test]
     fn test_v2_workspace_query_across() {
         init();
@@ -6961,6 +14505,7 @@ mod tests {
     fn test_v2_grounding_with_many_grants() {
         init();
         let (mut server, _tmp, identity_id) = setup_identity();
+        let other = registered_grantee(&server);
 
         // Create 12 grants with distinct capabilities.
         for i in 0..12 {
@@ -6970,7 +14515,7 @@ mod tests {
                 "params":{
                     "name":"trust_grant",
                     "arguments":{
-                        "grantee": if i % 2 == 0 { identity_id.clone() } else { format!("aid_agent_{i}") },
+                        "grantee": if i % 2 == 0 { identity_id.clone() } else { other.clone() },
                         "capabilities": [format!("action_{}:{}", ["read","write","deploy","admin","execute","manage"][i % 6], ["files","logs","services","config","keys","db"][i % 6])]
                     }
                 }
@@ -0,0 +1,221 @@
+//! Socket transport — JSON-RPC over TCP or a Unix-domain socket.
+//!
+//! Built with the `net` feature and started with `--listen <addr>`. The
+//! framing is the stdio contract: one JSON-RPC message per line in, one
+//! response per line out, and nothing for notifications. Content-Length
+//! framing remains stdio-only.
+//!
+//! Every connection gets its own [`McpServer`], so session state (the
+//! operation log, workspaces, the session start time) is never shared
+//! between clients. The on-disk stores are shared the same way they are
+//! between several stdio servers on one machine: each record is its own
+//! file, and identity files are replaced atomically.
+//!
+//! A request runs on a blocking thread that its connection waits for but
+//! never cancels. If the client disconnects mid-request, the request still
+//! finishes its writes; only then are the connection and its server
+//! dropped. A line longer than the frame limit closes the connection.
+//! When the client closes the connection, its audit session is sealed.
+//!
+//! There is no authentication: anyone who can connect can call every tool,
+//! including `action_sign`, `trust_grant` and `spawn_create`. A TCP address
+//! must therefore be loopback unless `--listen-remote` opts in, and then it
+//! belongs only behind something that authenticates. A connection whose
+//! first line is an HTTP request line is closed unanswered, so a web page
+//! cannot reach a loopback port with a cross-protocol form POST whose body
+//! happens to parse as JSON-RPC.
+
+#[cfg(unix)]
+use std::path::PathBuf;
+
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::{rpc_error, McpServer, MAX_CONTENT_LENGTH_BYTES};
+
+/// Where `--listen` accepts connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ListenAddr {
+    /// A TCP `host:port`.
+    Tcp(String),
+    /// A Unix-domain socket path.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl ListenAddr {
+    /// `unix:<path>` names a Unix-domain socket; anything else is a TCP
+    /// `host:port`.
+    pub(crate) fn parse(s: &str) -> Result<Self, String> {
+        match s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            Some("") => Err("unix: needs a socket path".to_string()),
+            #[cfg(unix)]
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            #[cfg(not(unix))]
+            Some(_) => Err("Unix-domain sockets are not supported on this platform".to_string()),
+            None => Ok(Self::Tcp(s.to_string())),
+        }
+    }
+}
+
+/// Accept connections on `addr` until the process stops, serving each with
+/// a fresh server from `make_server`.
+///
+/// Fails if the address cannot be bound, or if a TCP address is not
+/// loopback and `allow_remote` is false. An existing Unix socket file is
+/// never removed; delete a stale one before restarting.
+pub(crate) async fn serve<F>(
+    addr: ListenAddr,
+    allow_remote: bool,
+    make_server: F,
+) -> std::io::Result<()>
+where
+    F: Fn() -> McpServer,
+{
+    match addr {
+        ListenAddr::Tcp(addr) => {
+            let listener = TcpListener::bind(&addr).await?;
+            let local = listener.local_addr()?;
+            if !local.ip().is_loopback() {
+                if !allow_remote {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::PermissionDenied,
+                        format!(
+                            "{local} is not a loopback address and the socket transport has \
+                             no authentication; pass --listen-remote to serve it anyway"
+                        ),
+                    ));
+                }
+                eprintln!(
+                    "warning: serving every tool without authentication on non-loopback {local}"
+                );
+            }
+            eprintln!("listening on {local}");
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        let server = make_server();
+                        tokio::spawn(async move {
+                            let (read, write) = stream.into_split();
+                            if let Err(e) = serve_connection(server, read, write).await {
+                                tracing::warn!("connection {peer}: {e}");
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("accept failed: {e}"),
+                }
+            }
+        }
+        #[cfg(unix)]
+        ListenAddr::Unix(path) => {
+            let listener = tokio::net::UnixListener::bind(&path)?;
+            eprintln!("listening on unix:{}", path.display());
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        let server = make_server();
+                        tokio::spawn(async move {
+                            let (read, write) = stream.into_split();
+                            if let Err(e) = serve_connection(server, read, write).await {
+                                tracing::warn!("unix connection: {e}");
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("accept failed: {e}"),
+                }
+            }
+        }
+    }
+}
+
+/// Serve one connection until the client closes it.
+pub(crate) async fn serve_connection<R, W>(
+    mut server: McpServer,
+    read: R,
+    mut write: W,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(read);
+    let limit = MAX_CONTENT_LENGTH_BYTES as u64 + 1;
+    let mut line = Vec::new();
+    let mut first = true;
+
+    loop {
+        line.clear();
+        let read = (&mut reader)
+            .take(limit)
+            .read_until(b'\n', &mut line)
+            .await?;
+        if read == 0 {
+            // Sealing the audit session signs and writes, so it runs on a
+            // blocking thread like a request.
+            let _ = tokio::task::spawn_blocking(move || server.shutdown()).await;
+            return Ok(());
+        }
+        if read as u64 == limit && line.last() != Some(&b'\n') {
+            let err = rpc_error(
+                Value::Null,
+                -32700,
+                format!("message exceeds max frame size ({MAX_CONTENT_LENGTH_BYTES} bytes)"),
+            );
+            return write_line(&mut write, &err).await;
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if std::mem::take(&mut first) && is_http_request_line(trimmed) {
+            tracing::warn!("closing connection that sent an HTTP request line");
+            let _ = tokio::task::spawn_blocking(move || server.shutdown()).await;
+            return Ok(());
+        }
+        let request: Value = match serde_json::from_str(trimmed) {
+            Ok(v) => v,
+            Err(e) => {
+                let err = rpc_error(Value::Null, -32700, format!("parse error: {e}"));
+                write_line(&mut write, &err).await?;
+                continue;
+            }
+        };
+
+        // Awaited, never aborted: the request completes even if the client
+        // has gone by the time it does.
+        let (returned, response) = tokio::task::spawn_blocking(move || {
+            let response = server.handle_request(request);
+            (server, response)
+        })
+        .await
+        .map_err(std::io::Error::other)?;
+        server = returned;
+
+        // Notifications return Value::Null — don't write a response.
+        if !response.is_null() {
+            write_line(&mut write, &response).await?;
+        }
+    }
+}
+
+/// `METHOD target HTTP/x.y`, the start of an HTTP request.
+fn is_http_request_line(line: &str) -> bool {
+    let mut parts = line.split_ascii_whitespace();
+    let (Some(method), Some(_target), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    method.bytes().all(|b| b.is_ascii_uppercase()) && version.starts_with("HTTP/")
+}
+
+async fn write_line<W: AsyncWrite + Unpin>(write: &mut W, response: &Value) -> std::io::Result<()> {
+    let mut bytes = serde_json::to_vec(response).map_err(std::io::Error::other)?;
+    bytes.push(b'\n');
+    write.write_all(&bytes).await?;
+    write.flush().await
+}
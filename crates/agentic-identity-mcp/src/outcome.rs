@@ -0,0 +1,193 @@
+//! Verification outcomes — stable machine codes and their human rendering.
+//!
+//! Every verify tool reports each check it makes as a
+//! [`VerificationOutcome`]. The outcome's [`code`](VerificationOutcome::code)
+//! is part of the tool contract: clients branch on it, so a code is never
+//! renamed or reused once released. New outcomes get new codes.
+//!
+//! The text a person reads comes from [`OutcomeMessages`]. It starts from
+//! the English wording the tools have always printed, so existing output is
+//! unchanged, and any code can be given other wording through a JSON file
+//! mapping codes to text, e.g. `{"revoked": "RÉVOQUÉ"}`. Tools also return
+//! the codes in `structuredContent.outcomes`, which clients should prefer
+//! over matching the text.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// The result of one verification check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum VerificationOutcome {
+    Valid,
+    Invalid,
+    SignatureValid,
+    SignatureInvalid,
+    WitnessValid,
+    WitnessInvalid,
+    TimeValid,
+    TimeInvalid,
+    NotRevoked,
+    Revoked,
+    UsesWithinLimit,
+    UsesExceeded,
+    CapabilityGranted,
+    CapabilityNotGranted,
+    ProofNotFound,
+}
+
+impl VerificationOutcome {
+    /// Every outcome, for looking codes up.
+    const ALL: [Self; 15] = [
+        Self::Valid,
+        Self::Invalid,
+        Self::SignatureValid,
+        Self::SignatureInvalid,
+        Self::WitnessValid,
+        Self::WitnessInvalid,
+        Self::TimeValid,
+        Self::TimeInvalid,
+        Self::NotRevoked,
+        Self::Revoked,
+        Self::UsesWithinLimit,
+        Self::UsesExceeded,
+        Self::CapabilityGranted,
+        Self::CapabilityNotGranted,
+        Self::ProofNotFound,
+    ];
+
+    /// The stable machine code. Never change an existing one.
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Invalid => "invalid",
+            Self::SignatureValid => "signature_valid",
+            Self::SignatureInvalid => "signature_invalid",
+            Self::WitnessValid => "witness_valid",
+            Self::WitnessInvalid => "witness_invalid",
+            Self::TimeValid => "time_valid",
+            Self::TimeInvalid => "time_invalid",
+            Self::NotRevoked => "not_revoked",
+            Self::Revoked => "revoked",
+            Self::UsesWithinLimit => "uses_within_limit",
+            Self::UsesExceeded => "uses_exceeded",
+            Self::CapabilityGranted => "capability_granted",
+            Self::CapabilityNotGranted => "capability_not_granted",
+            Self::ProofNotFound => "proof_not_found",
+        }
+    }
+
+    /// The English text the tools print when no override is configured.
+    fn default_message(self) -> &'static str {
+        match self {
+            Self::Valid => "VALID",
+            Self::Invalid => "INVALID",
+            Self::SignatureValid | Self::WitnessValid | Self::TimeValid => "valid",
+            Self::SignatureInvalid | Self::WitnessInvalid => "INVALID",
+            Self::TimeInvalid => "expired/not-yet-valid",
+            Self::NotRevoked => "yes",
+            Self::Revoked => "REVOKED",
+            Self::UsesWithinLimit => "within limit",
+            Self::UsesExceeded => "exceeded",
+            Self::CapabilityGranted => "granted",
+            Self::CapabilityNotGranted => "not granted",
+            Self::ProofNotFound => "Proof not found",
+        }
+    }
+
+    fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|o| o.code() == code)
+    }
+
+    /// Overall result of a verification.
+    pub(crate) fn result(valid: bool) -> Self {
+        if valid {
+            Self::Valid
+        } else {
+            Self::Invalid
+        }
+    }
+
+    pub(crate) fn signature(valid: bool) -> Self {
+        if valid {
+            Self::SignatureValid
+        } else {
+            Self::SignatureInvalid
+        }
+    }
+
+    pub(crate) fn witness(valid: bool) -> Self {
+        if valid {
+            Self::WitnessValid
+        } else {
+            Self::WitnessInvalid
+        }
+    }
+
+    pub(crate) fn time(valid: bool) -> Self {
+        if valid {
+            Self::TimeValid
+        } else {
+            Self::TimeInvalid
+        }
+    }
+
+    pub(crate) fn revocation(revoked: bool) -> Self {
+        if revoked {
+            Self::Revoked
+        } else {
+            Self::NotRevoked
+        }
+    }
+
+    pub(crate) fn uses(within_limit: bool) -> Self {
+        if within_limit {
+            Self::UsesWithinLimit
+        } else {
+            Self::UsesExceeded
+        }
+    }
+
+    pub(crate) fn capability(granted: bool) -> Self {
+        if granted {
+            Self::CapabilityGranted
+        } else {
+            Self::CapabilityNotGranted
+        }
+    }
+}
+
+/// Human wording for verification outcomes.
+///
+/// Deserialized from a JSON object mapping outcome codes to text. Codes not
+/// in the object keep their English default; an unknown code is an error,
+/// so a misspelled override is not silently ignored.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(try_from = "HashMap<String, String>")]
+pub(crate) struct OutcomeMessages {
+    overrides: HashMap<VerificationOutcome, String>,
+}
+
+impl TryFrom<HashMap<String, String>> for OutcomeMessages {
+    type Error = String;
+
+    fn try_from(messages: HashMap<String, String>) -> Result<Self, String> {
+        let mut overrides = HashMap::new();
+        for (code, text) in messages {
+            let outcome = VerificationOutcome::from_code(&code)
+                .ok_or_else(|| format!("unknown verification outcome code '{code}'"))?;
+            overrides.insert(outcome, text);
+        }
+        Ok(Self { overrides })
+    }
+}
+
+impl OutcomeMessages {
+    /// The text shown for `outcome`.
+    pub(crate) fn render(&self, outcome: VerificationOutcome) -> &str {
+        self.overrides
+            .get(&outcome)
+            .map(String::as_str)
+            .unwrap_or(outcome.default_message())
+    }
+}
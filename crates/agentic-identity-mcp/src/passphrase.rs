@@ -0,0 +1,157 @@
+//! Where identity passphrases come from.
+//!
+//! The server always has a passphrase of its own, read at startup from
+//! `--passphrase-env`, `--passphrase-file`, `AID_MCP_PASSPHRASE` or
+//! `AID_MCP_PASSPHRASE_FILE`. Identities can also have their own, so that
+//! identities served by one process need not share a secret. For the
+//! identity `name`, the first of these that exists is used:
+//!
+//! 1. the file `<dir>/<name>.passphrase`, with `--passphrase-dir` or
+//!    `AID_MCP_PASSPHRASE_DIR`;
+//! 2. the OS keyring entry for user `name` under the service given with
+//!    `--passphrase-keyring` or `AID_MCP_PASSPHRASE_KEYRING` (`keyring`
+//!    feature only);
+//! 3. the server passphrase.
+//!
+//! Creating an identity uses the same lookup, so provisioning a per-identity
+//! passphrase before `identity_create` is all it takes to encrypt the new
+//! file under it.
+//!
+//! On Unix every passphrase file, including the server's, must not be
+//! readable or writable by group or others; a file that is is refused
+//! rather than used.
+
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+
+/// File extension of per-identity passphrase files.
+const PASSPHRASE_EXTENSION: &str = "passphrase";
+
+/// Command-line flags that configure passphrase sources.
+#[derive(Args, Debug, Default)]
+pub(crate) struct PassphraseArgs {
+    /// Read the server passphrase from this environment variable instead of
+    /// AID_MCP_PASSPHRASE.
+    #[arg(long, global = true, value_name = "VAR")]
+    pub(crate) passphrase_env: Option<String>,
+
+    /// Read the server passphrase from this file instead of
+    /// AID_MCP_PASSPHRASE_FILE.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub(crate) passphrase_file: Option<PathBuf>,
+
+    /// Directory of per-identity passphrase files named <identity>.passphrase.
+    #[arg(long, global = true, value_name = "DIR")]
+    pub(crate) passphrase_dir: Option<PathBuf>,
+
+    /// Keyring service holding per-identity passphrases, one entry per
+    /// identity name.
+    #[cfg(feature = "keyring")]
+    #[arg(long, global = true, value_name = "SERVICE")]
+    pub(crate) passphrase_keyring: Option<String>,
+}
+
+/// Per-identity passphrase sources. The default has none, so every identity
+/// uses the server passphrase.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PassphraseSources {
+    /// Directory holding `<name>.passphrase` files.
+    pub(crate) dir: Option<PathBuf>,
+    /// Keyring service whose entries are keyed by identity name.
+    #[cfg(feature = "keyring")]
+    pub(crate) keyring_service: Option<String>,
+}
+
+impl PassphraseSources {
+    /// Sources from the command line, falling back to the environment.
+    pub(crate) fn from_args(args: &PassphraseArgs) -> Self {
+        Self {
+            dir: args.passphrase_dir.clone().or_else(|| {
+                crate::read_env_string_any(&["AID_MCP_PASSPHRASE_DIR", "MCP_PASSPHRASE_DIR"])
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from)
+            }),
+            #[cfg(feature = "keyring")]
+            keyring_service: args.passphrase_keyring.clone().or_else(|| {
+                crate::read_env_string_any(&[
+                    "AID_MCP_PASSPHRASE_KEYRING",
+                    "MCP_PASSPHRASE_KEYRING",
+                ])
+                .filter(|service| !service.is_empty())
+            }),
+        }
+    }
+
+    /// The passphrase provisioned for identity `name`, or `None` if no
+    /// source has one and the server passphrase applies.
+    ///
+    /// A source that has an entry for `name` but cannot be read is an error,
+    /// never a fallback: silently using the server passphrase instead would
+    /// encrypt a new identity under the wrong secret.
+    pub(crate) fn lookup(&self, name: &str) -> Result<Option<String>, String> {
+        if let Some(dir) = &self.dir {
+            let path = dir.join(format!("{name}.{PASSPHRASE_EXTENSION}"));
+            if path.exists() {
+                return read_passphrase_file(&path).map(Some);
+            }
+        }
+
+        #[cfg(feature = "keyring")]
+        if let Some(service) = &self.keyring_service {
+            let entry = keyring::Entry::new(service, name)
+                .map_err(|e| format!("cannot open keyring entry '{service}/{name}': {e}"))?;
+            match entry.get_password() {
+                Ok(passphrase) if passphrase.is_empty() => {
+                    return Err(format!("keyring entry '{service}/{name}' is empty"));
+                }
+                Ok(passphrase) => return Ok(Some(passphrase)),
+                Err(keyring::Error::NoEntry) => {}
+                Err(e) => {
+                    return Err(format!("cannot read keyring entry '{service}/{name}': {e}"));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Read a passphrase file, removing only its trailing line break.
+///
+/// Fails if the file cannot be read, is empty, or (on Unix) grants any
+/// access to group or others.
+pub(crate) fn read_passphrase_file(path: &Path) -> Result<String, String> {
+    check_private(path)?;
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("cannot read passphrase file '{}': {e}", path.display()))?;
+    let passphrase = contents.trim_end_matches(['\n', '\r']);
+    if passphrase.is_empty() {
+        return Err(format!("passphrase file '{}' is empty", path.display()));
+    }
+    Ok(passphrase.to_string())
+}
+
+#[cfg(unix)]
+fn check_private(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = std::fs::metadata(path)
+        .map_err(|e| format!("cannot read passphrase file '{}': {e}", path.display()))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(format!(
+            "passphrase file '{}' is accessible to other users (mode {:03o}); \
+             restrict it with 'chmod 600'",
+            path.display(),
+            mode & 0o777
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_private(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! How grounding decides that a record backs a claim.
+//!
+//! `identity_ground` and `identity_evidence` score trust grants and receipts
+//! through a [`SimilarityProvider`]. The default, [`LexicalSimilarity`],
+//! compares normalized terms (see [`crate::grounding`]) and narrows
+//! candidates through the stores' text indexes. [`EmbeddingSimilarity`]
+//! compares vectors from a [`Vectorizer`] instead, so "release" can ground a
+//! `deploy:*` grant, and falls back to lexical scoring for any text the
+//! vectorizer cannot embed. Tool inputs and outputs are the same either way.
+//!
+//! The server uses embeddings when `AID_EMBEDDING_COMMAND` names a program:
+//! it is run once per text, reads the text on stdin and prints a JSON array
+//! of numbers.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use agentic_identity::index::{tokenize, TextIndex};
+
+use crate::grounding::{self, SynonymMap};
+
+/// Cosine similarity an embedding match needs to count as evidence, unless
+/// `AID_EMBEDDING_MIN_SIMILARITY` says otherwise.
+pub(crate) const DEFAULT_MIN_SIMILARITY: f32 = 0.5;
+
+/// Most texts whose vectors an [`EmbeddingSimilarity`] remembers.
+const MAX_CACHED_VECTORS: usize = 10_000;
+
+/// Scores records against a claim or query.
+pub(crate) trait SimilarityProvider {
+    /// IDs in `index` worth scoring against `claim`, or `None` to score
+    /// every indexed record.
+    fn candidates(&self, index: &TextIndex, claim: &str) -> Option<Vec<String>>;
+
+    /// Records in `index` ranked against `query` from the index alone, best
+    /// first, or `None` to score each record's text with [`text_score`].
+    ///
+    /// [`text_score`]: SimilarityProvider::text_score
+    fn rank(&self, _index: &TextIndex, _query: &str) -> Option<Vec<(String, f32)>> {
+        None
+    }
+
+    /// How strongly a granted capability URI backs `claim`; 0 if not at all.
+    fn capability_score(&self, claim: &str, capability: &str) -> f32;
+
+    /// How strongly free text, such as a receipt description, backs
+    /// `claim`, from 0 to 1.
+    fn text_score(&self, claim: &str, text: &str) -> f32;
+}
+
+/// Whole-word term overlap, with optional synonyms.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LexicalSimilarity {
+    synonyms: SynonymMap,
+}
+
+impl LexicalSimilarity {
+    pub(crate) fn new(synonyms: SynonymMap) -> Self {
+        Self { synonyms }
+    }
+}
+
+impl SimilarityProvider for LexicalSimilarity {
+    fn candidates(&self, index: &TextIndex, claim: &str) -> Option<Vec<String>> {
+        let terms = grounding::normalize(claim, &self.synonyms);
+        Some(grounding::candidates(index, &terms, &self.synonyms))
+    }
+
+    fn rank(&self, index: &TextIndex, query: &str) -> Option<Vec<(String, f32)>> {
+        Some(grounding::prefix_matches(index, &tokenize(query)))
+    }
+
+    /// 1 when every term of the capability appears in the claim, plus 0.3
+    /// per shared term.
+    fn capability_score(&self, claim: &str, capability: &str) -> f32 {
+        let claim_terms = grounding::normalize(claim, &self.synonyms);
+        let cap_terms = grounding::normalize(capability, &self.synonyms);
+        let overlap = grounding::overlap(&claim_terms, &cap_terms);
+        let mut score = overlap as f32 * 0.3;
+        if overlap > 0 && overlap == cap_terms.len() {
+            score += 1.0;
+        }
+        score
+    }
+
+    /// The fraction of the claim's terms found in the text.
+    fn text_score(&self, claim: &str, text: &str) -> f32 {
+        let claim_terms = grounding::normalize(claim, &self.synonyms);
+        let text_terms = grounding::normalize(text, &self.synonyms);
+        grounding::overlap(&claim_terms, &text_terms) as f32 / claim_terms.len().max(1) as f32
+    }
+}
+
+/// Turns text into a vector; texts with similar meaning should get vectors
+/// with a high cosine similarity.
+pub(crate) trait Vectorizer {
+    /// The vector for `text`, or `None` if it cannot be embedded right now.
+    fn embed(&self, text: &str) -> Option<Vec<f32>>;
+}
+
+/// Runs an external program per text: the text goes to its stdin and a JSON
+/// array of numbers is read from its stdout.
+#[derive(Debug, Clone)]
+pub(crate) struct CommandVectorizer {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandVectorizer {
+    /// A vectorizer for `command`, split on whitespace into a program and
+    /// its arguments. `None` if the command is blank.
+    pub(crate) fn parse(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace().map(str::to_string);
+        Some(Self {
+            program: words.next()?,
+            args: words.collect(),
+        })
+    }
+}
+
+impl Vectorizer for CommandVectorizer {
+    fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| tracing::warn!("embedding command '{}' failed: {e}", self.program))
+            .ok()?;
+        // Dropped at the end of the statement, closing stdin.
+        child.stdin.take()?.write_all(text.as_bytes()).ok()?;
+        let output = child.wait_with_output().ok()?;
+        if !output.status.success() {
+            tracing::warn!(
+                "embedding command '{}' exited with {}",
+                self.program,
+                output.status
+            );
+            return None;
+        }
+        serde_json::from_slice(&output.stdout).ok()
+    }
+}
+
+/// Cosine similarity of embeddings, falling back to [`LexicalSimilarity`]
+/// for any text without a vector.
+pub(crate) struct EmbeddingSimilarity {
+    vectorizer: Box<dyn Vectorizer + Send>,
+    /// Scores below this count as no match.
+    min_similarity: f32,
+    fallback: LexicalSimilarity,
+    /// Vectors by text, including failures, so each text is embedded once.
+    vectors: RefCell<HashMap<String, Option<Vec<f32>>>>,
+}
+
+impl EmbeddingSimilarity {
+    pub(crate) fn new(
+        vectorizer: Box<dyn Vectorizer + Send>,
+        min_similarity: f32,
+        fallback: LexicalSimilarity,
+    ) -> Self {
+        Self {
+            vectorizer,
+            min_similarity,
+            fallback,
+            vectors: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn vector(&self, text: &str) -> Option<Vec<f32>> {
+        if let Some(vector) = self.vectors.borrow().get(text) {
+            return vector.clone();
+        }
+        let vector = self.vectorizer.embed(text);
+        let mut vectors = self.vectors.borrow_mut();
+        if vectors.len() >= MAX_CACHED_VECTORS {
+            vectors.clear();
+        }
+        vectors.insert(text.to_string(), vector.clone());
+        vector
+    }
+
+    /// Thresholded cosine similarity, or `None` if either text has no
+    /// usable vector.
+    fn similarity(&self, a: &str, b: &str) -> Option<f32> {
+        let cosine = cosine(&self.vector(a)?, &self.vector(b)?)?;
+        Some(if cosine >= self.min_similarity {
+            cosine
+        } else {
+            0.0
+        })
+    }
+}
+
+impl SimilarityProvider for EmbeddingSimilarity {
+    fn candidates(&self, _index: &TextIndex, _claim: &str) -> Option<Vec<String>> {
+        None
+    }
+
+    fn capability_score(&self, claim: &str, capability: &str) -> f32 {
+        self.similarity(claim, capability)
+            .unwrap_or_else(|| self.fallback.capability_score(claim, capability))
+    }
+
+    fn text_score(&self, claim: &str, text: &str) -> f32 {
+        self.similarity(claim, text)
+            .unwrap_or_else(|| self.fallback.text_score(claim, text))
+    }
+}
+
+/// Cosine similarity of two vectors of the same, non-zero length.
+fn cosine(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    (denominator > 0.0).then(|| dot / denominator)
+}
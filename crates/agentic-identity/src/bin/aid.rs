@@ -72,47 +72,11 @@ fn micros_to_datetime(micros: u64) -> String {
     naive.format("%Y-%m-%d %H:%M:%S UTC").to_string()
 }
 
-/// Parse a duration string like "24h", "7d", "30d", "1h30m", or plain seconds.
+/// Parse a duration string like "24h", "7d", "30d", "1h30m", or a bare
+/// number of hours; see [`agentic_identity::time::parse_duration_micros`].
 /// Returns the duration as microseconds.
 fn parse_duration_to_micros(s: &str) -> Result<u64> {
-    let s = s.trim();
-
-    // Try parsing as plain integer (hours for backward compat or as seconds)
-    if let Ok(n) = s.parse::<u64>() {
-        // Treat bare number as hours
-        return Ok(n * 3600 * 1_000_000);
-    }
-
-    let mut total_micros: u64 = 0;
-    let mut current = String::new();
-
-    for ch in s.chars() {
-        if ch.is_ascii_digit() {
-            current.push(ch);
-        } else {
-            let val: u64 = current
-                .parse()
-                .map_err(|_| anyhow!("invalid duration: {s}"))?;
-            current.clear();
-            match ch {
-                'h' => total_micros += val * 3600 * 1_000_000,
-                'd' => total_micros += val * 86400 * 1_000_000,
-                'm' => total_micros += val * 60 * 1_000_000,
-                's' => total_micros += val * 1_000_000,
-                _ => return Err(anyhow!("unknown duration unit '{}' in '{s}'", ch)),
-            }
-        }
-    }
-
-    if !current.is_empty() {
-        return Err(anyhow!("duration '{s}' is missing a unit (h/d/m/s)"));
-    }
-
-    if total_micros == 0 {
-        return Err(anyhow!("duration must be > 0"));
-    }
-
-    Ok(total_micros)
+    Ok(agentic_identity::time::parse_duration_micros(s)?)
 }
 
 // ── CLI structure ─────────────────────────────────────────────────────────────
@@ -751,7 +715,7 @@ fn cmd_show(name: &str, _verbose: bool) -> Result<()> {
 
     println!("Identity: {}", name);
     println!("  ID:        {}", doc.id);
-    println!("  Algorithm: {}", doc.algorithm);
+    println!("  Algorithm: {}", doc.algorithm_summary());
     println!("  Public Key: {}", doc.public_key);
     println!("  Created:   {}", micros_to_datetime(doc.created_at));
 
@@ -1464,7 +1428,7 @@ fn cmd_receipt_list(
     }
 
     // Sort by timestamp descending (newest first)
-    receipts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
     // Apply limit
     let total = receipts.len();
@@ -1480,10 +1444,11 @@ fn cmd_receipt_list(
         );
         println!("  {}", "-".repeat(90));
         for receipt in &receipts {
-            let desc_preview = if receipt.action.description.len() > 35 {
-                format!("{}...", &receipt.action.description[..32])
+            let desc = &receipt.action.description;
+            let desc_preview = if desc.chars().count() > 35 {
+                format!("{}...", desc.chars().take(32).collect::<String>())
             } else {
-                receipt.action.description.clone()
+                desc.clone()
             };
             println!(
                 "  {:<25} {:<15} {:<25} {}",
@@ -0,0 +1,539 @@
+//! Aggregate competence proofs — statistics without the attempt history.
+//!
+//! A [`CompetenceProof`](super::CompetenceProof) names the attempts behind
+//! it. An [`AggregateCompetenceProof`] discloses only how many attempts
+//! there were, which ten-point success rate bucket they fall in, and the
+//! time window they span. It also signs one salted hash commitment per
+//! attempt, in time order, so a verifier can later ask for specific
+//! attempts and check each [`AttemptOpening`] against the proof without
+//! having received the rest.
+//!
+//! A commitment is the hex SHA-256 of the canonical JSON (RFC 8785) of
+//! `[salt, attempt]`; the salt keeps an attempt from being confirmed by
+//! anyone who merely guesses it.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::canonical::canonicalize;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityAnchor;
+use crate::identity::IdentityId;
+
+use super::engine::verify_attempt;
+#[cfg(feature = "signing")]
+use super::types::CompetenceRecord;
+use super::types::{
+    AttemptId, CompetenceAttempt, CompetenceDomain, CompetenceRequirement, CompetenceVerification,
+    ProofId,
+};
+
+/// Width of a success rate bucket, in percentage points.
+pub const RATE_BUCKET_PERCENT: u8 = 10;
+
+/// Competence proof disclosing only aggregate statistics.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregateCompetenceProof {
+    pub proof_id: ProofId,
+    pub identity: IdentityId,
+    pub domain: CompetenceDomain,
+    pub attempt_count: u64,
+    /// The success rate is at least this many percent...
+    pub success_rate_min_percent: u8,
+    /// ...and below this many (or exactly 100).
+    pub success_rate_max_percent: u8,
+    /// Timestamp of the first attempt counted.
+    pub window_start: u64,
+    /// Timestamp of the last attempt counted.
+    pub window_end: u64,
+    /// Hex commitment to each attempt, in time order.
+    pub attempt_commitments: Vec<String>,
+    pub generated_at: u64,
+    pub valid_until: Option<u64>,
+    pub proof_hash: String,
+    pub signature: String,
+    pub signature_version: u32,
+}
+
+/// What an actor reveals of one attempt to let it be audited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptOpening {
+    /// Position of the attempt's commitment in the proof.
+    pub index: u64,
+    /// Hex-encoded random salt.
+    pub salt: String,
+    pub attempt: CompetenceAttempt,
+}
+
+impl AttemptOpening {
+    /// The commitment to this opening.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if the attempt cannot be
+    /// canonicalized.
+    pub fn commitment(&self) -> Result<String> {
+        let attempt = serde_json::to_value(&self.attempt)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        let json = canonicalize(&serde_json::json!([self.salt, attempt]))?;
+        Ok(hex::encode(Sha256::digest(json.as_bytes())))
+    }
+}
+
+/// Every attempt opening of an aggregate proof, kept by the prover.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttemptOpenings {
+    pub openings: Vec<AttemptOpening>,
+}
+
+impl AttemptOpenings {
+    /// The openings of the attempts with IDs `attempt_ids`, for an auditor.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if an attempt is not in the proof.
+    pub fn open(&self, attempt_ids: &[AttemptId]) -> Result<Vec<AttemptOpening>> {
+        attempt_ids
+            .iter()
+            .map(|id| {
+                self.openings
+                    .iter()
+                    .find(|o| &o.attempt.attempt_id == id)
+                    .cloned()
+                    .ok_or_else(|| {
+                        IdentityError::NotFound(format!("attempt {id} is not in this proof"))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Generate an aggregate competence proof over the identity's attempts in
+/// `domain`, restricted to the last `window_seconds` if given.
+///
+/// Returns the proof, to hand out, and the openings, to keep.
+///
+/// # Errors
+///
+/// Returns `IdentityError::NotFound` if there are no attempts to count, and
+/// `IdentityError::InvalidInput` if `valid_duration_seconds` runs past the
+/// end of time.
+#[cfg(feature = "signing")]
+pub fn generate_aggregate_proof(
+    identity: &IdentityAnchor,
+    domain: CompetenceDomain,
+    window_seconds: Option<u64>,
+    valid_duration_seconds: Option<u64>,
+    attempts: &[CompetenceAttempt],
+) -> Result<(AggregateCompetenceProof, AttemptOpenings)> {
+    let now = crate::time::now_micros();
+    let cutoff = window_seconds.map_or(0, |w| now.saturating_sub(w.saturating_mul(1_000_000)));
+    let valid_until = valid_duration_seconds
+        .map(|d| {
+            d.checked_mul(1_000_000)
+                .and_then(|micros| now.checked_add(micros))
+                .ok_or_else(|| {
+                    IdentityError::InvalidInput(format!("validity of {d} seconds is too long"))
+                })
+        })
+        .transpose()?;
+    let identity_id = identity.id();
+
+    let mut relevant: Vec<&CompetenceAttempt> = attempts
+        .iter()
+        .filter(|a| a.identity == identity_id && a.domain == domain && a.timestamp >= cutoff)
+        .collect();
+    relevant.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.attempt_id.0.cmp(&b.attempt_id.0))
+    });
+    let (Some(first), Some(last)) = (relevant.first(), relevant.last()) else {
+        return Err(IdentityError::NotFound(format!(
+            "No competence attempts for domain '{}'",
+            domain.0
+        )));
+    };
+    let (window_start, window_end) = (first.timestamp, last.timestamp);
+
+    let mut record = CompetenceRecord::new(identity_id.clone(), domain.clone());
+    let mut openings = AttemptOpenings::default();
+    let mut attempt_commitments = Vec::with_capacity(relevant.len());
+    for (index, attempt) in relevant.into_iter().enumerate() {
+        record.record_attempt(attempt);
+        let opening = AttemptOpening {
+            index: index as u64,
+            salt: hex::encode(crate::crypto::random::random_bytes::<16>()),
+            attempt: attempt.clone(),
+        };
+        attempt_commitments.push(opening.commitment()?);
+        openings.openings.push(opening);
+    }
+    let (success_rate_min_percent, success_rate_max_percent) = rate_bucket(record.success_rate);
+
+    let mut proof = AggregateCompetenceProof {
+        proof_id: ProofId(String::new()),
+        identity: identity_id,
+        domain,
+        attempt_count: record.total_attempts,
+        success_rate_min_percent,
+        success_rate_max_percent,
+        window_start,
+        window_end,
+        attempt_commitments,
+        generated_at: now,
+        valid_until,
+        proof_hash: String::new(),
+        signature: String::new(),
+        signature_version: signing::SIGNATURE_VERSION,
+    };
+    proof.proof_hash = proof.compute_hash()?;
+    let id_hash = Sha256::digest(proof.proof_hash.as_bytes());
+    proof.proof_id = ProofId(format!(
+        "aagg_{}",
+        bs58::encode(&id_hash[..16]).into_string()
+    ));
+    proof.signature = signing::sign_in_domain(
+        identity.signing_key(),
+        SignatureDomain::CompetenceAggregate,
+        proof.proof_hash.as_bytes(),
+    );
+    Ok((proof, openings))
+}
+
+impl AggregateCompetenceProof {
+    /// Hash of the canonical JSON of every signed field.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if the proof cannot be
+    /// canonicalized.
+    pub fn compute_hash(&self) -> Result<String> {
+        let payload = serde_json::json!({
+            "identity": self.identity.0,
+            "domain": self.domain.0,
+            "attempt_count": self.attempt_count,
+            "success_rate_min_percent": self.success_rate_min_percent,
+            "success_rate_max_percent": self.success_rate_max_percent,
+            "window_start": self.window_start,
+            "window_end": self.window_end,
+            "attempt_commitments": self.attempt_commitments,
+            "generated_at": self.generated_at,
+            "valid_until": self.valid_until,
+        });
+        Ok(hex::encode(Sha256::digest(
+            canonicalize(&payload)?.as_bytes(),
+        )))
+    }
+
+    /// Whether the disclosed statistics meet `requirement`.
+    ///
+    /// The success rate counts at its bucket's lower bound, the age limit
+    /// applies to the window's first attempt, and a streak requirement is
+    /// never met, since streaks are not disclosed.
+    pub fn satisfies(&self, requirement: &CompetenceRequirement) -> bool {
+        let recent = match requirement.max_age_seconds {
+            Some(age) => {
+                self.window_start
+                    >= crate::time::now_micros().saturating_sub(age.saturating_mul(1_000_000))
+            }
+            None => true,
+        };
+        requirement.domain == self.domain
+            && self.attempt_count >= requirement.min_attempts
+            && f32::from(self.success_rate_min_percent) >= requirement.min_success_rate * 100.0
+            && requirement.min_streak.is_none()
+            && recent
+    }
+}
+
+/// Verify an aggregate proof's signature and expiry, and that its
+/// statistics are consistent with each other.
+pub fn verify_aggregate_proof(
+    proof: &AggregateCompetenceProof,
+    verifying_key: &ed25519_dalek::VerifyingKey,
+) -> Result<CompetenceVerification> {
+    let now = crate::time::now_micros();
+    let mut errors = Vec::new();
+
+    let hash_valid = proof.compute_hash()? == proof.proof_hash;
+    let sig_valid = hash_valid
+        && signing::verify_versioned(
+            verifying_key,
+            SignatureDomain::CompetenceAggregate,
+            proof.signature_version,
+            proof.proof_hash.as_bytes(),
+            &proof.signature,
+        )
+        .is_ok();
+    if !sig_valid {
+        errors.push("Signature verification failed".to_string());
+    }
+
+    let not_expired = match proof.valid_until {
+        Some(until) => now <= until,
+        None => true,
+    };
+    if !not_expired {
+        errors.push("Competence proof expired".to_string());
+    }
+
+    let meets_attempts =
+        proof.attempt_count > 0 && proof.attempt_commitments.len() as u64 == proof.attempt_count;
+    if !meets_attempts {
+        errors.push(format!(
+            "Attempt count {} does not match {} commitments",
+            proof.attempt_count,
+            proof.attempt_commitments.len()
+        ));
+    }
+
+    let meets_rate = proof.success_rate_max_percent <= 100
+        && proof.success_rate_min_percent <= proof.success_rate_max_percent;
+    if !meets_rate {
+        errors.push("Success rate bucket is malformed".to_string());
+    }
+
+    let is_valid = sig_valid && not_expired && meets_attempts && meets_rate;
+    Ok(CompetenceVerification {
+        identity: proof.identity.clone(),
+        domain: proof.domain.clone(),
+        meets_attempts,
+        meets_rate,
+        meets_streak: true,
+        meets_recency: not_expired,
+        is_valid,
+        verified_at: now,
+        errors,
+    })
+}
+
+/// Audit one attempt of a verified aggregate proof: the opening matches
+/// its commitment, and the attempt is a genuine, signed attempt of the
+/// proof's identity, domain and window.
+///
+/// # Errors
+///
+/// Returns `IdentityError::NotFound` if the index is past the proof's
+/// commitments, `IdentityError::InvalidInput` if the opening or the attempt
+/// does not match the proof, and `IdentityError::SignatureInvalid` if the
+/// attempt's signature does not verify.
+pub fn audit_attempt(
+    proof: &AggregateCompetenceProof,
+    opening: &AttemptOpening,
+    verifying_key: &ed25519_dalek::VerifyingKey,
+) -> Result<()> {
+    let commitment = usize::try_from(opening.index)
+        .ok()
+        .and_then(|i| proof.attempt_commitments.get(i))
+        .ok_or_else(|| {
+            IdentityError::NotFound(format!("proof has no attempt {}", opening.index))
+        })?;
+    if *commitment != opening.commitment()? {
+        return Err(IdentityError::InvalidInput(format!(
+            "attempt {} does not match its commitment",
+            opening.index
+        )));
+    }
+    let attempt = &opening.attempt;
+    if attempt.identity != proof.identity
+        || attempt.domain != proof.domain
+        || attempt.timestamp < proof.window_start
+        || attempt.timestamp > proof.window_end
+    {
+        return Err(IdentityError::InvalidInput(format!(
+            "attempt {} is outside the proof's identity, domain or window",
+            attempt.attempt_id
+        )));
+    }
+    verify_attempt(attempt, verifying_key)
+}
+
+/// The ten-point bucket `[min, max)` holding `rate`; a perfect rate is the
+/// bucket `[100, 100]`.
+#[cfg(feature = "signing")]
+fn rate_bucket(rate: f32) -> (u8, u8) {
+    let percent = (rate.clamp(0.0, 1.0) * 100.0).floor() as u8;
+    let min = percent - percent % RATE_BUCKET_PERCENT;
+    (min, min.saturating_add(RATE_BUCKET_PERCENT).min(100))
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::competence::engine::record_attempt;
+    use crate::competence::AttemptOutcome;
+    use crate::receipt::ReceiptId;
+
+    fn history(
+        identity: &IdentityAnchor,
+        successes: usize,
+        failures: usize,
+    ) -> Vec<CompetenceAttempt> {
+        let domain = CompetenceDomain::new("deploy");
+        (0..successes + failures)
+            .map(|i| {
+                let outcome = if i < successes {
+                    AttemptOutcome::Success
+                } else {
+                    AttemptOutcome::Failure {
+                        reason: "rollback".into(),
+                    }
+                };
+                record_attempt(
+                    identity,
+                    domain.clone(),
+                    outcome,
+                    ReceiptId(format!("arec_{i}")),
+                    Some(format!("customer-{i}")),
+                    None,
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aggregate_proof_discloses_only_statistics() {
+        let identity = IdentityAnchor::new(None);
+        let attempts = history(&identity, 8, 2);
+        let (proof, openings) = generate_aggregate_proof(
+            &identity,
+            CompetenceDomain::new("deploy"),
+            None,
+            Some(3600),
+            &attempts,
+        )
+        .unwrap();
+
+        assert_eq!(proof.attempt_count, 10);
+        assert_eq!(
+            (
+                proof.success_rate_min_percent,
+                proof.success_rate_max_percent
+            ),
+            (80, 90)
+        );
+        let json = serde_json::to_string(&proof).unwrap();
+        assert!(!json.contains("customer-") && !json.contains("aatt_"));
+        assert!(
+            verify_aggregate_proof(&proof, identity.verifying_key())
+                .unwrap()
+                .is_valid
+        );
+
+        let requirement = CompetenceRequirement {
+            domain: CompetenceDomain::new("deploy"),
+            min_attempts: 10,
+            min_success_rate: 0.8,
+            min_streak: None,
+            max_age_seconds: Some(3600),
+        };
+        assert!(proof.satisfies(&requirement));
+        assert!(!proof.satisfies(&CompetenceRequirement {
+            min_success_rate: 0.85,
+            ..requirement
+        }));
+
+        // Audit two attempts without the other eight.
+        let audited = openings
+            .open(&[
+                attempts[1].attempt_id.clone(),
+                attempts[9].attempt_id.clone(),
+            ])
+            .unwrap();
+        for opening in &audited {
+            audit_attempt(&proof, opening, identity.verifying_key()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_huge_windows_do_not_overflow() {
+        let identity = IdentityAnchor::new(None);
+        let attempts = history(&identity, 3, 0);
+        let (proof, _) = generate_aggregate_proof(
+            &identity,
+            CompetenceDomain::new("deploy"),
+            Some(u64::MAX),
+            None,
+            &attempts,
+        )
+        .unwrap();
+        assert_eq!(proof.attempt_count, 3);
+
+        assert!(proof.satisfies(&CompetenceRequirement {
+            domain: CompetenceDomain::new("deploy"),
+            min_attempts: 3,
+            min_success_rate: 1.0,
+            min_streak: None,
+            max_age_seconds: Some(u64::MAX),
+        }));
+
+        let too_long = generate_aggregate_proof(
+            &identity,
+            CompetenceDomain::new("deploy"),
+            None,
+            Some(u64::MAX),
+            &attempts,
+        );
+        assert!(matches!(too_long, Err(IdentityError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_tampered_aggregate_and_openings_fail() {
+        let identity = IdentityAnchor::new(None);
+        let attempts = history(&identity, 3, 3);
+        let (proof, openings) = generate_aggregate_proof(
+            &identity,
+            CompetenceDomain::new("deploy"),
+            None,
+            None,
+            &attempts,
+        )
+        .unwrap();
+
+        let mut inflated = proof.clone();
+        inflated.success_rate_min_percent = 90;
+        inflated.success_rate_max_percent = 100;
+        assert!(
+            !verify_aggregate_proof(&inflated, identity.verifying_key())
+                .unwrap()
+                .is_valid
+        );
+
+        let mut opening = openings
+            .open(&[attempts[4].attempt_id.clone()])
+            .unwrap()
+            .remove(0);
+        opening.attempt.outcome = AttemptOutcome::Success;
+        assert!(matches!(
+            audit_attempt(&proof, &opening, identity.verifying_key()),
+            Err(IdentityError::InvalidInput(_))
+        ));
+
+        let outsider = IdentityAnchor::new(None);
+        let honest = openings
+            .open(&[attempts[0].attempt_id.clone()])
+            .unwrap()
+            .remove(0);
+        assert!(audit_attempt(&proof, &honest, outsider.verifying_key()).is_err());
+        assert!(generate_aggregate_proof(
+            &identity,
+            CompetenceDomain::new("planning"),
+            None,
+            None,
+            &attempts,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rate_buckets() {
+        assert_eq!(rate_bucket(0.0), (0, 10));
+        assert_eq!(rate_bucket(0.799), (70, 80));
+        assert_eq!(rate_bucket(0.8), (80, 90));
+        assert_eq!(rate_bucket(1.0), (100, 100));
+    }
+}
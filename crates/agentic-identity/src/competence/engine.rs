@@ -2,7 +2,7 @@
 
 use sha2::{Digest, Sha256};
 
-use crate::crypto::signing;
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::error::{IdentityError, Result};
 use crate::identity::{IdentityAnchor, IdentityId};
 use crate::receipt::ReceiptId;
@@ -44,21 +44,13 @@ pub fn record_attempt(
     let attempt_id = AttemptId(format!("aatt_{id_encoded}"));
 
     // Sign the attempt
-    let outcome_tag = match &outcome {
-        AttemptOutcome::Success => "success".to_string(),
-        AttemptOutcome::Failure { reason } => format!("failure:{}", reason),
-        AttemptOutcome::Partial { score } => format!("partial:{}", score),
-    };
-
-    let sign_input = format!(
-        "attempt:{}:{}:{}:{}:{}",
-        attempt_id.0,
-        identity.id().0,
-        domain.0,
-        outcome_tag,
-        now
+    let outcome_tag = outcome_tag(&outcome);
+    let sign_input = attempt_sign_input(&attempt_id, &identity.id(), &domain, &outcome_tag, now);
+    let signature = signing::sign_in_domain(
+        identity.signing_key(),
+        SignatureDomain::CompetenceAttempt,
+        sign_input.as_bytes(),
     );
-    let signature = signing::sign_to_base64(identity.signing_key(), sign_input.as_bytes());
 
     // Validator co-signature
     let (validator_id, validator_sig) = if let Some(val) = validator {
@@ -69,7 +61,11 @@ pub fn record_attempt(
             outcome_tag,
             now
         );
-        let val_sig = signing::sign_to_base64(val.signing_key(), val_sign_input.as_bytes());
+        let val_sig = signing::sign_in_domain(
+            val.signing_key(),
+            SignatureDomain::CompetenceValidation,
+            val_sign_input.as_bytes(),
+        );
         (Some(val.id()), Some(val_sig))
     } else {
         (None, None)
@@ -86,9 +82,57 @@ pub fn record_attempt(
         validator: validator_id,
         validator_signature: validator_sig,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
+/// Verify the identity's signature on a recorded attempt.
+///
+/// # Errors
+///
+/// Returns `IdentityError::SignatureInvalid` if the signature does not
+/// verify under `verifying_key`.
+pub fn verify_attempt(
+    attempt: &CompetenceAttempt,
+    verifying_key: &ed25519_dalek::VerifyingKey,
+) -> Result<()> {
+    let sign_input = attempt_sign_input(
+        &attempt.attempt_id,
+        &attempt.identity,
+        &attempt.domain,
+        &outcome_tag(&attempt.outcome),
+        attempt.timestamp,
+    );
+    signing::verify_versioned(
+        verifying_key,
+        SignatureDomain::CompetenceAttempt,
+        attempt.signature_version,
+        sign_input.as_bytes(),
+        &attempt.signature,
+    )
+}
+
+fn outcome_tag(outcome: &AttemptOutcome) -> String {
+    match outcome {
+        AttemptOutcome::Success => "success".to_string(),
+        AttemptOutcome::Failure { reason } => format!("failure:{}", reason),
+        AttemptOutcome::Partial { score } => format!("partial:{}", score),
+    }
+}
+
+fn attempt_sign_input(
+    attempt_id: &AttemptId,
+    identity: &IdentityId,
+    domain: &CompetenceDomain,
+    outcome_tag: &str,
+    timestamp: u64,
+) -> String {
+    format!(
+        "attempt:{}:{}:{}:{}:{}",
+        attempt_id.0, identity.0, domain.0, outcome_tag, timestamp
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Get competence (from a list of attempts)
 // ---------------------------------------------------------------------------
@@ -226,7 +270,11 @@ pub fn generate_proof(
     let proof_id = ProofId(format!("aprf_{id_encoded}"));
 
     // Sign the proof
-    let signature = signing::sign_to_base64(identity.signing_key(), proof_hash.as_bytes());
+    let signature = signing::sign_in_domain(
+        identity.signing_key(),
+        SignatureDomain::CompetenceProof,
+        proof_hash.as_bytes(),
+    );
 
     Ok(CompetenceProof {
         proof_id,
@@ -239,6 +287,7 @@ pub fn generate_proof(
         valid_until,
         proof_hash,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
@@ -255,9 +304,14 @@ pub fn verify_proof(
     let mut errors = Vec::new();
 
     // Verify signature
-    let sig_valid =
-        signing::verify_from_base64(verifying_key, proof.proof_hash.as_bytes(), &proof.signature)
-            .is_ok();
+    let sig_valid = signing::verify_versioned(
+        verifying_key,
+        SignatureDomain::CompetenceProof,
+        proof.signature_version,
+        proof.proof_hash.as_bytes(),
+        &proof.signature,
+    )
+    .is_ok();
 
     if !sig_valid {
         errors.push("Signature verification failed".to_string());
@@ -265,15 +319,11 @@ pub fn verify_proof(
 
     // Check expiration
     let not_expired = match proof.valid_until {
-        Some(until) => {
-            if now > until {
-                errors.push("Competence proof expired".to_string());
-                false
-            } else {
-                true
-            }
+        Some(until) if now > until => {
+            errors.push("Competence proof expired".to_string());
+            false
         }
-        None => true,
+        _ => true,
     };
 
     // Check claim validity
@@ -424,7 +474,7 @@ pub fn check_competence(
 // Tests (12 scenarios)
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -7,7 +7,9 @@
 //! - Competence proof generation with evidence
 //! - Proof verification and expiration
 //! - Competence requirements for trust grants
+//! - Aggregate proofs disclosing statistics, with auditable attempt commitments
 
+pub mod aggregate;
 pub mod engine;
 pub mod types;
 
@@ -16,7 +18,14 @@ pub use types::{
     CompetenceProof, CompetenceRecord, CompetenceRequirement, CompetenceVerification, ProofId,
 };
 
+#[cfg(feature = "signing")]
+pub use aggregate::generate_aggregate_proof;
+pub use aggregate::{
+    audit_attempt, verify_aggregate_proof, AggregateCompetenceProof, AttemptOpening,
+    AttemptOpenings,
+};
+
 pub use engine::{
     check_competence, generate_proof, get_competence, list_competences, record_attempt,
-    verify_proof,
+    verify_attempt, verify_proof,
 };
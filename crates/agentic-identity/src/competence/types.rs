@@ -72,6 +72,13 @@ pub struct CompetenceAttempt {
     pub validator: Option<IdentityId>,
     pub validator_signature: Option<String>,
     pub signature: String,
+    /// Signature scheme of `signature` and `validator_signature`; absent
+    /// on attempts signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -213,6 +220,12 @@ pub struct CompetenceProof {
     pub valid_until: Option<u64>,
     pub proof_hash: String,
     pub signature: String,
+    /// Absent on proofs signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -2,7 +2,8 @@
 
 use sha2::{Digest, Sha256};
 
-use crate::crypto::signing;
+use crate::crypto::signer::{sign_in_domain_with, Signer};
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::error::{IdentityError, Result};
 use crate::identity::{IdentityAnchor, IdentityId};
 use crate::receipt::witness::WitnessSignature;
@@ -16,8 +17,8 @@ use super::types::*;
 /// Record an experience event, linking it to the previous event in the chain.
 ///
 /// If `previous` is `None` this becomes the genesis event (sequence 0).
-pub fn record_experience(
-    identity: &IdentityAnchor,
+pub fn record_experience<S: Signer + ?Sized>(
+    identity: &S,
     event_type: ExperienceType,
     content_hash: &str,
     intensity: f32,
@@ -29,7 +30,7 @@ pub fn record_experience(
     }
 
     let now = crate::time::now_micros();
-    let identity_id = identity.id();
+    let identity_id = IdentityId::from_verifying_key(&identity.verifying_key());
 
     let (prev_id, prev_hash, seq) = match previous {
         Some(prev) => (
@@ -40,15 +41,7 @@ pub fn record_experience(
         None => (None, None, 0),
     };
 
-    // Compute cumulative hash = SHA-256(prev_cumulative_hash || content_hash || seq || timestamp)
-    let cumulative_input = format!(
-        "{}:{}:{}:{}",
-        prev_hash.as_deref().unwrap_or("genesis"),
-        content_hash,
-        seq,
-        now,
-    );
-    let cumulative_hash = hex::encode(Sha256::digest(cumulative_input.as_bytes()));
+    let cumulative_hash = compute_cumulative_hash(prev_hash.as_deref(), content_hash, seq, now);
 
     // Generate experience ID
     let id_input = format!("exp:{}:{}:{}", identity_id.0, seq, now);
@@ -57,7 +50,11 @@ pub fn record_experience(
     let id = ExperienceId(format!("aexp_{id_encoded}"));
 
     // Sign the cumulative hash
-    let signature = signing::sign_to_base64(identity.signing_key(), cumulative_hash.as_bytes());
+    let signature = sign_in_domain_with(
+        identity,
+        SignatureDomain::Experience,
+        cumulative_hash.as_bytes(),
+    )?;
 
     Ok(ExperienceEvent {
         id,
@@ -72,23 +69,42 @@ pub fn record_experience(
         sequence_number: seq,
         cumulative_hash,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
+/// Compute an experience's cumulative hash:
+/// SHA-256(prev_cumulative_hash || content_hash || seq || timestamp).
+pub(crate) fn compute_cumulative_hash(
+    previous_hash: Option<&str>,
+    content_hash: &str,
+    sequence_number: u64,
+    timestamp: u64,
+) -> String {
+    let cumulative_input = format!(
+        "{}:{}:{}:{}",
+        previous_hash.unwrap_or("genesis"),
+        content_hash,
+        sequence_number,
+        timestamp,
+    );
+    hex::encode(Sha256::digest(cumulative_input.as_bytes()))
+}
+
 // ---------------------------------------------------------------------------
 // Continuity anchor
 // ---------------------------------------------------------------------------
 
 /// Create a continuity anchor (checkpoint) at the latest experience.
-pub fn create_anchor(
-    identity: &IdentityAnchor,
+pub fn create_anchor<S: Signer + ?Sized>(
+    identity: &S,
     anchor_type: AnchorType,
     latest_experience: &ExperienceEvent,
     previous_anchor: Option<&ContinuityAnchor>,
     external_witness: Option<&IdentityAnchor>,
 ) -> Result<ContinuityAnchor> {
     let now = crate::time::now_micros();
-    let identity_id = identity.id();
+    let identity_id = IdentityId::from_verifying_key(&identity.verifying_key());
 
     let prev_anchor_id = previous_anchor.map(|a| a.id.clone());
 
@@ -109,15 +125,18 @@ pub fn create_anchor(
     });
 
     // Sign the anchor
-    let sign_input = format!(
-        "anchor:{}:{}:{}:{}:{}",
-        id.0,
-        anchor_type.as_tag(),
-        latest_experience.cumulative_hash,
+    let sign_input = anchor_signing_input(
+        &id,
+        &anchor_type,
+        &latest_experience.cumulative_hash,
         latest_experience.sequence_number + 1,
         now,
     );
-    let signature = signing::sign_to_base64(identity.signing_key(), sign_input.as_bytes());
+    let signature = sign_in_domain_with(
+        identity,
+        SignatureDomain::ContinuityAnchor,
+        sign_input.as_bytes(),
+    )?;
 
     Ok(ContinuityAnchor {
         id,
@@ -130,16 +149,35 @@ pub fn create_anchor(
         previous_anchor: prev_anchor_id,
         external_witness: witness_sig,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
+/// The message a continuity anchor's signature covers.
+pub(crate) fn anchor_signing_input(
+    id: &AnchorId,
+    anchor_type: &AnchorType,
+    cumulative_hash: &str,
+    experience_count: u64,
+    timestamp: u64,
+) -> String {
+    format!(
+        "anchor:{}:{}:{}:{}:{}",
+        id.0,
+        anchor_type.as_tag(),
+        cumulative_hash,
+        experience_count,
+        timestamp,
+    )
+}
+
 // ---------------------------------------------------------------------------
 // Heartbeat
 // ---------------------------------------------------------------------------
 
 /// Create a heartbeat record.
-pub fn create_heartbeat(
-    identity: &IdentityAnchor,
+pub fn create_heartbeat<S: Signer + ?Sized>(
+    identity: &S,
     sequence_number: u64,
     continuity_hash: &str,
     experience_count: u64,
@@ -148,7 +186,7 @@ pub fn create_heartbeat(
     health: HealthMetrics,
 ) -> Result<HeartbeatRecord> {
     let now = crate::time::now_micros();
-    let identity_id = identity.id();
+    let identity_id = IdentityId::from_verifying_key(&identity.verifying_key());
 
     // Generate heartbeat ID
     let id_input = format!("hb:{}:{}:{}", identity_id.0, sequence_number, now);
@@ -165,7 +203,8 @@ pub fn create_heartbeat(
         status.as_tag(),
         now,
     );
-    let signature = signing::sign_to_base64(identity.signing_key(), sign_input.as_bytes());
+    let signature =
+        sign_in_domain_with(identity, SignatureDomain::Heartbeat, sign_input.as_bytes())?;
 
     Ok(HeartbeatRecord {
         id,
@@ -178,6 +217,7 @@ pub fn create_heartbeat(
         status,
         health,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
@@ -186,8 +226,8 @@ pub fn create_heartbeat(
 // ---------------------------------------------------------------------------
 
 /// Create a continuity claim over a range of experiences.
-pub fn create_continuity_claim(
-    identity: &IdentityAnchor,
+pub fn create_continuity_claim<S: Signer + ?Sized>(
+    identity: &S,
     claim_type: ClaimType,
     experiences: &[ExperienceEvent],
     anchors: &[ContinuityAnchor],
@@ -198,7 +238,7 @@ pub fn create_continuity_claim(
     }
 
     let now = crate::time::now_micros();
-    let identity_id = identity.id();
+    let identity_id = IdentityId::from_verifying_key(&identity.verifying_key());
 
     let first = &experiences[0];
     let last = experiences.last().unwrap();
@@ -247,7 +287,11 @@ pub fn create_continuity_claim(
         experiences.len(),
         max_gap_seconds,
     );
-    let signature = signing::sign_to_base64(identity.signing_key(), sign_input.as_bytes());
+    let signature = sign_in_domain_with(
+        identity,
+        SignatureDomain::ContinuityClaim,
+        sign_input.as_bytes(),
+    )?;
 
     Ok(ContinuityClaim {
         id,
@@ -262,6 +306,7 @@ pub fn create_continuity_claim(
         experience_count: experiences.len() as u64,
         max_gap_seconds,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
@@ -447,6 +492,7 @@ pub fn detect_gaps(experiences: &[ExperienceEvent], grace_period_seconds: u64) -
                     "{}s gap between seq {} and {}",
                     gap_seconds, prev.sequence_number, curr.sequence_number
                 ),
+                cause: GapCause::Unknown,
             });
         }
 
@@ -462,6 +508,7 @@ pub fn detect_gaps(experiences: &[ExperienceEvent], grace_period_seconds: u64) -
                     prev.sequence_number + 1,
                     curr.sequence_number.saturating_sub(1)
                 ),
+                cause: GapCause::Unknown,
             });
         }
 
@@ -474,6 +521,7 @@ pub fn detect_gaps(experiences: &[ExperienceEvent], grace_period_seconds: u64) -
                     gap_type: GapType::Hash,
                     severity: GapSeverity::Critical,
                     impact: format!("Hash chain broken at seq {}", curr.sequence_number),
+                    cause: GapCause::Unknown,
                 });
             }
         }
@@ -482,6 +530,190 @@ pub fn detect_gaps(experiences: &[ExperienceEvent], grace_period_seconds: u64) -
     gaps
 }
 
+// ---------------------------------------------------------------------------
+// Gap classification
+// ---------------------------------------------------------------------------
+
+/// Fill in the probable [`GapCause`] of every gap from the experience chain
+/// and heartbeat history around it. See [`classify_gap`] for the rules.
+pub fn classify_gaps(
+    gaps: &mut [Gap],
+    experiences: &[ExperienceEvent],
+    heartbeats: &[HeartbeatRecord],
+) {
+    for gap in gaps {
+        gap.cause = classify_gap(gap, experiences, heartbeats);
+    }
+}
+
+/// Infer the probable cause of a single gap.
+///
+/// Only temporal gaps are classified; sequence and hash gaps are integrity
+/// failures rather than downtime and are always [`GapCause::Unknown`]. For a
+/// temporal gap, the experience that opens it (the last one at or before
+/// `gap.start`), the experience that closes it (the first one at or after
+/// `gap.end`) and the last heartbeat before `gap.end` are examined, first
+/// matching rule wins:
+///
+/// 1. Both the opening and closing experiences are `System::Checkpoint` →
+///    [`GapCause::Maintenance`].
+/// 2. The opening experience is `System::Shutdown`, or the last heartbeat is
+///    `Suspended` → [`GapCause::PlannedSuspension`].
+/// 3. The opening experience is `System::Error` → [`GapCause::Crash`].
+/// 4. The last heartbeat is `Active` or `Degraded` and came no later than
+///    `gap.start` — the identity was running, then stopped both recording
+///    and beating without saying so → [`GapCause::Crash`].
+/// 5. Anything else (no heartbeats, an `Idle` heartbeat, heartbeats that
+///    continued through the gap) → [`GapCause::Unknown`].
+pub fn classify_gap(
+    gap: &Gap,
+    experiences: &[ExperienceEvent],
+    heartbeats: &[HeartbeatRecord],
+) -> GapCause {
+    if gap.gap_type != GapType::Temporal {
+        return GapCause::Unknown;
+    }
+
+    let system_event = |e: &ExperienceEvent| match &e.event_type {
+        ExperienceType::System { event } => Some(event.clone()),
+        _ => None,
+    };
+    let opening = experiences
+        .iter()
+        .filter(|e| e.timestamp <= gap.start)
+        .max_by_key(|e| e.timestamp)
+        .and_then(system_event);
+    let closing = experiences
+        .iter()
+        .filter(|e| e.timestamp >= gap.end)
+        .min_by_key(|e| e.timestamp)
+        .and_then(system_event);
+    let last_heartbeat = heartbeats
+        .iter()
+        .filter(|h| h.timestamp < gap.end)
+        .max_by_key(|h| h.timestamp);
+
+    if opening == Some(SystemEvent::Checkpoint) && closing == Some(SystemEvent::Checkpoint) {
+        return GapCause::Maintenance;
+    }
+    if opening == Some(SystemEvent::Shutdown)
+        || last_heartbeat.is_some_and(|h| h.status == HeartbeatStatus::Suspended)
+    {
+        return GapCause::PlannedSuspension;
+    }
+    if matches!(opening, Some(SystemEvent::Error { .. })) {
+        return GapCause::Crash;
+    }
+    match last_heartbeat {
+        Some(h)
+            if h.timestamp <= gap.start
+                && matches!(
+                    h.status,
+                    HeartbeatStatus::Active | HeartbeatStatus::Degraded
+                ) =>
+        {
+            GapCause::Crash
+        }
+        _ => GapCause::Unknown,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Confidence score
+// ---------------------------------------------------------------------------
+
+/// Score returned for a window with no experiences, anchors or heartbeats in
+/// it: nothing supports continuity, so confidence is the lowest possible.
+pub const NO_DATA_CONFIDENCE: f32 = 0.0;
+
+/// Temporal gaps shorter than this are not penalized by [`confidence_score`].
+pub const CONFIDENCE_GRACE_SECONDS: u64 = 300;
+
+/// Summarize how confidently continuity was maintained over `window`
+/// (`(start, end)` in microseconds since epoch) as a score in `0.0..=1.0`.
+///
+/// The score is
+///
+/// ```text
+/// integrity × (0.6 × coverage + 0.2 × anchoring + 0.2 × liveness)
+/// ```
+///
+/// - **coverage** is `1 − Σ(weight × gap duration) / window duration`,
+///   floored at 0, over the temporal gaps of `chain` found with
+///   [`CONFIDENCE_GRACE_SECONDS`] and classified by [`classify_gaps`]. Each
+///   gap is clipped to the window. Crashes and unexplained gaps weigh 1.0;
+///   planned suspensions and maintenance weigh 0.25.
+/// - **integrity** is `0.5ⁿ`, where `n` counts sequence and hash gaps that
+///   overlap the window.
+/// - **anchoring** is `1 − longest stretch of the window without an anchor /
+///   window duration`; evenly spaced anchors score highest.
+/// - **liveness** is the same measure over heartbeats.
+///
+/// An empty or inverted window, or one with no experience, anchor or
+/// heartbeat inside it, scores [`NO_DATA_CONFIDENCE`]. The score depends
+/// only on the inputs (anchors and heartbeats are sorted first), so the same
+/// inputs always give the same score.
+pub fn confidence_score(
+    chain: &[ExperienceEvent],
+    anchors: &[ContinuityAnchor],
+    heartbeats: &[HeartbeatRecord],
+    window: (u64, u64),
+) -> f32 {
+    let (start, end) = window;
+    if end <= start {
+        return NO_DATA_CONFIDENCE;
+    }
+    let inside = |t: u64| t >= start && t <= end;
+    if !chain.iter().any(|e| inside(e.timestamp))
+        && !anchors.iter().any(|a| inside(a.timestamp))
+        && !heartbeats.iter().any(|h| inside(h.timestamp))
+    {
+        return NO_DATA_CONFIDENCE;
+    }
+    let duration = (end - start) as f64;
+
+    let mut gaps = detect_gaps(chain, CONFIDENCE_GRACE_SECONDS);
+    classify_gaps(&mut gaps, chain, heartbeats);
+    let mut penalty = 0.0;
+    let mut integrity = 1.0;
+    for gap in &gaps {
+        let (gap_start, gap_end) = (gap.start.max(start), gap.end.min(end));
+        if gap_start > gap_end {
+            continue;
+        }
+        if gap.gap_type != GapType::Temporal {
+            integrity *= 0.5;
+            continue;
+        }
+        let weight = match gap.cause {
+            GapCause::PlannedSuspension | GapCause::Maintenance => 0.25,
+            GapCause::Crash | GapCause::Unknown => 1.0,
+        };
+        penalty += weight * (gap_end - gap_start) as f64;
+    }
+    let coverage = (1.0 - penalty / duration).max(0.0);
+
+    let anchoring = spread(anchors.iter().map(|a| a.timestamp), start, end);
+    let liveness = spread(heartbeats.iter().map(|h| h.timestamp), start, end);
+
+    let score = integrity * (0.6 * coverage + 0.2 * anchoring + 0.2 * liveness);
+    score.clamp(0.0, 1.0) as f32
+}
+
+/// `1 − (longest stretch of [start, end] containing none of timestamps) /
+/// (end − start)`. Requires `end > start`.
+fn spread(timestamps: impl Iterator<Item = u64>, start: u64, end: u64) -> f64 {
+    let mut points: Vec<u64> = timestamps.filter(|&t| t >= start && t <= end).collect();
+    points.sort_unstable();
+    let mut longest = 0;
+    let mut previous = start;
+    for t in points.into_iter().chain(std::iter::once(end)) {
+        longest = longest.max(t - previous);
+        previous = t;
+    }
+    1.0 - longest as f64 / (end - start) as f64
+}
+
 /// Compute continuity state from a set of experiences.
 pub fn get_continuity_state(
     identity: &IdentityId,
@@ -512,7 +744,7 @@ pub fn get_continuity_state(
 // Tests
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -1093,4 +1325,237 @@ mod tests {
             .collect();
         assert!(!hash_gaps.is_empty());
     }
+
+    /// Build a chain of `(seconds, type)` events with synthetic timestamps.
+    fn synthetic_chain(
+        anchor: &IdentityAnchor,
+        events: Vec<(u64, ExperienceType)>,
+    ) -> Vec<ExperienceEvent> {
+        let mut chain: Vec<ExperienceEvent> = Vec::new();
+        for (secs, event_type) in events {
+            let mut e = record_experience(anchor, event_type, "h", 0.5, chain.last()).unwrap();
+            e.timestamp = secs * 1_000_000;
+            chain.push(e);
+        }
+        chain
+    }
+
+    fn synthetic_heartbeat(
+        anchor: &IdentityAnchor,
+        secs: u64,
+        status: HeartbeatStatus,
+    ) -> HeartbeatRecord {
+        let health = HealthMetrics {
+            memory_usage_bytes: 0,
+            experience_rate_per_hour: 0.0,
+            error_count: 0,
+            latency_ms: 0,
+        };
+        let mut hb = create_heartbeat(anchor, 0, "h", 0, 0, status, health).unwrap();
+        hb.timestamp = secs * 1_000_000;
+        hb
+    }
+
+    fn thought() -> ExperienceType {
+        ExperienceType::Cognition {
+            cognition_type: CognitionType::Thought,
+        }
+    }
+
+    fn system(event: SystemEvent) -> ExperienceType {
+        ExperienceType::System { event }
+    }
+
+    /// Cause of the single temporal gap between 1000s and 5000s.
+    fn cause_of(chain: &[ExperienceEvent], heartbeats: &[HeartbeatRecord]) -> GapCause {
+        let mut gaps = detect_gaps(chain, 60);
+        classify_gaps(&mut gaps, chain, heartbeats);
+        let temporal: Vec<_> = gaps
+            .iter()
+            .filter(|g| g.gap_type == GapType::Temporal)
+            .collect();
+        assert_eq!(temporal.len(), 1);
+        temporal[0].cause.clone()
+    }
+
+    // 17. Gap cause: suspended heartbeat before the gap
+    #[test]
+    fn test_gap_cause_planned_suspension() {
+        let anchor = make_identity();
+        let chain = synthetic_chain(&anchor, vec![(1000, thought()), (5000, thought())]);
+        let heartbeats = [
+            synthetic_heartbeat(&anchor, 900, HeartbeatStatus::Active),
+            synthetic_heartbeat(&anchor, 1010, HeartbeatStatus::Suspended),
+        ];
+        assert_eq!(cause_of(&chain, &heartbeats), GapCause::PlannedSuspension);
+
+        let chain = synthetic_chain(
+            &anchor,
+            vec![(1000, system(SystemEvent::Shutdown)), (5000, thought())],
+        );
+        assert_eq!(cause_of(&chain, &[]), GapCause::PlannedSuspension);
+    }
+
+    // 18. Gap cause: heartbeat stopped without closing
+    #[test]
+    fn test_gap_cause_crash() {
+        let anchor = make_identity();
+        let chain = synthetic_chain(&anchor, vec![(1000, thought()), (5000, thought())]);
+        let heartbeats = [synthetic_heartbeat(&anchor, 990, HeartbeatStatus::Active)];
+        assert_eq!(cause_of(&chain, &heartbeats), GapCause::Crash);
+
+        let chain = synthetic_chain(
+            &anchor,
+            vec![
+                (
+                    1000,
+                    system(SystemEvent::Error {
+                        message: "oom".into(),
+                    }),
+                ),
+                (5000, thought()),
+            ],
+        );
+        assert_eq!(cause_of(&chain, &[]), GapCause::Crash);
+    }
+
+    // 19. Gap cause: bracketed by checkpoints
+    #[test]
+    fn test_gap_cause_maintenance() {
+        let anchor = make_identity();
+        let chain = synthetic_chain(
+            &anchor,
+            vec![
+                (980, thought()),
+                (1000, system(SystemEvent::Checkpoint)),
+                (5000, system(SystemEvent::Checkpoint)),
+            ],
+        );
+        // Checkpoints win over a heartbeat that would otherwise mean a crash.
+        let heartbeats = [synthetic_heartbeat(&anchor, 990, HeartbeatStatus::Active)];
+        assert_eq!(cause_of(&chain, &heartbeats), GapCause::Maintenance);
+    }
+
+    // 20. Gap cause: no surrounding signal is not guessed
+    #[test]
+    fn test_gap_cause_unknown_without_signal() {
+        let anchor = make_identity();
+        let chain = synthetic_chain(&anchor, vec![(1000, thought()), (5000, thought())]);
+        assert_eq!(cause_of(&chain, &[]), GapCause::Unknown);
+
+        // One checkpoint on a single side is not a bracket.
+        let chain = synthetic_chain(
+            &anchor,
+            vec![(1000, system(SystemEvent::Checkpoint)), (5000, thought())],
+        );
+        assert_eq!(cause_of(&chain, &[]), GapCause::Unknown);
+
+        // Idle before the gap, or heartbeats that kept going through it.
+        let chain = synthetic_chain(&anchor, vec![(1000, thought()), (5000, thought())]);
+        let idle = [synthetic_heartbeat(&anchor, 990, HeartbeatStatus::Idle)];
+        assert_eq!(cause_of(&chain, &idle), GapCause::Unknown);
+        let alive = [
+            synthetic_heartbeat(&anchor, 990, HeartbeatStatus::Active),
+            synthetic_heartbeat(&anchor, 3000, HeartbeatStatus::Active),
+        ];
+        assert_eq!(cause_of(&chain, &alive), GapCause::Unknown);
+    }
+
+    // 21. Only temporal gaps are classified
+    #[test]
+    fn test_gap_cause_ignores_integrity_gaps() {
+        let anchor = make_identity();
+        let mut chain = synthetic_chain(
+            &anchor,
+            vec![(1000, system(SystemEvent::Shutdown)), (1001, thought())],
+        );
+        chain[1].sequence_number = 5;
+        let mut gaps = detect_gaps(&chain, 60);
+        classify_gaps(&mut gaps, &chain, &[]);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].gap_type, GapType::Sequence);
+        assert_eq!(gaps[0].cause, GapCause::Unknown);
+    }
+
+    /// Experiences and heartbeats every 250s over 0..=10000s, silent from
+    /// 1000s to 5000s; the last heartbeat before the silence has `status`.
+    fn chain_with_silence(
+        anchor: &IdentityAnchor,
+        status: HeartbeatStatus,
+    ) -> (Vec<ExperienceEvent>, Vec<HeartbeatRecord>) {
+        let times: Vec<u64> = (0..=40)
+            .map(|i| i * 250)
+            .filter(|&t| t <= 1000 || t >= 5000)
+            .collect();
+        let chain = synthetic_chain(anchor, times.iter().map(|&t| (t, thought())).collect());
+        let heartbeats = times
+            .iter()
+            .map(|&t| {
+                let status = if t == 1000 {
+                    status.clone()
+                } else {
+                    HeartbeatStatus::Active
+                };
+                synthetic_heartbeat(anchor, t, status)
+            })
+            .collect();
+        (chain, heartbeats)
+    }
+
+    // 22. Confidence: a window without data scores low, never NaN
+    #[test]
+    fn test_confidence_score_without_data() {
+        let anchor = make_identity();
+        let (chain, heartbeats) = chain_with_silence(&anchor, HeartbeatStatus::Active);
+        let secs = |s: u64| s * 1_000_000;
+
+        for window in [
+            (secs(20_000), secs(30_000)),
+            (secs(10), secs(10)),
+            (secs(500), secs(100)),
+        ] {
+            let score = confidence_score(&chain, &[], &heartbeats, window);
+            assert_eq!(score, NO_DATA_CONFIDENCE);
+        }
+        assert_eq!(
+            confidence_score(&[], &[], &[], (0, secs(10))),
+            NO_DATA_CONFIDENCE
+        );
+    }
+
+    // 23. Confidence: suspensions cost less than crashes, deterministically
+    #[test]
+    fn test_confidence_score_weighs_gap_causes() {
+        let anchor = make_identity();
+        let window = (0, 10_000 * 1_000_000);
+
+        let (chain, heartbeats) = chain_with_silence(&anchor, HeartbeatStatus::Active);
+        let crash = confidence_score(&chain, &[], &heartbeats, window);
+        assert_eq!(crash, confidence_score(&chain, &[], &heartbeats, window));
+
+        let (chain, heartbeats) = chain_with_silence(&anchor, HeartbeatStatus::Suspended);
+        let suspended = confidence_score(&chain, &[], &heartbeats, window);
+
+        assert!(crash > NO_DATA_CONFIDENCE);
+        assert!(suspended > crash);
+        assert!(suspended < 1.0);
+
+        // Regular anchors raise the score; a broken hash chain halves it.
+        let anchors: Vec<_> = chain
+            .iter()
+            .step_by(4)
+            .map(|e| {
+                let mut a = create_anchor(&anchor, AnchorType::Manual, e, None, None).unwrap();
+                a.timestamp = e.timestamp;
+                a
+            })
+            .collect();
+        let anchored = confidence_score(&chain, &anchors, &heartbeats, window);
+        assert!(anchored > suspended);
+
+        let mut broken = chain.clone();
+        broken[2].previous_experience_hash = Some("wrong_hash".into());
+        let halved = confidence_score(&broken, &anchors, &heartbeats, window);
+        assert!((halved - anchored / 2.0).abs() < 1e-6);
+    }
 }
@@ -6,7 +6,8 @@
 //! - Continuity anchors (time-based, count-based, manual, external)
 //! - Heartbeat monitoring
 //! - Continuity claims and verification
-//! - Gap detection (temporal, sequence, hash, heartbeat)
+//! - Gap detection (temporal, sequence, hash, heartbeat) and probable-cause
+//!   classification
 
 pub mod engine;
 pub mod types;
@@ -14,12 +15,13 @@ pub mod types;
 pub use types::{
     AnchorId, AnchorType, ClaimId, ClaimType, CognitionType, CommunicationDirection,
     ContinuityAnchor, ContinuityClaim, ContinuityResult, ContinuityState, ContinuityVerification,
-    ExperienceEvent, ExperienceId, ExperienceType, Gap, GapSeverity, GapType, HealthMetrics,
-    HeartbeatId, HeartbeatRecord, HeartbeatStatus, LearningType, MemoryOpType, PerceptionSource,
-    PlanningType, SystemEvent,
+    ExperienceEvent, ExperienceId, ExperienceType, Gap, GapCause, GapSeverity, GapType,
+    HealthMetrics, HeartbeatId, HeartbeatRecord, HeartbeatStatus, LearningType, MemoryOpType,
+    PerceptionSource, PlanningType, SystemEvent,
 };
 
 pub use engine::{
-    create_anchor, create_continuity_claim, create_heartbeat, detect_gaps, get_continuity_state,
-    record_experience, verify_continuity,
+    classify_gap, classify_gaps, confidence_score, create_anchor, create_continuity_claim,
+    create_heartbeat, detect_gaps, get_continuity_state, record_experience, verify_continuity,
+    CONFIDENCE_GRACE_SECONDS, NO_DATA_CONFIDENCE,
 };
@@ -38,6 +38,12 @@ pub struct ExperienceEvent {
     pub cumulative_hash: String,
 
     pub signature: String,
+    /// Absent on events signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -196,6 +202,11 @@ pub struct ContinuityAnchor {
     pub previous_anchor: Option<AnchorId>,
     pub external_witness: Option<String>,
     pub signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 /// Type of continuity anchor.
@@ -248,6 +259,11 @@ pub struct HeartbeatRecord {
     pub status: HeartbeatStatus,
     pub health: HealthMetrics,
     pub signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 /// Heartbeat status.
@@ -309,6 +325,11 @@ pub struct ContinuityClaim {
     pub experience_count: u64,
     pub max_gap_seconds: u64,
     pub signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 /// Type of continuity claim.
@@ -342,6 +363,9 @@ pub struct Gap {
     pub gap_type: GapType,
     pub severity: GapSeverity,
     pub impact: String,
+    /// Heuristic explanation of the gap, filled in by `classify_gaps`.
+    #[serde(default)]
+    pub cause: GapCause,
 }
 
 /// Type of continuity gap.
@@ -353,6 +377,33 @@ pub enum GapType {
     Heartbeat,
 }
 
+/// Probable cause of a temporal gap, inferred from the surrounding
+/// experiences and heartbeats. See `classify_gap` for the rules.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GapCause {
+    /// The identity announced it was stopping (suspended heartbeat or shutdown).
+    PlannedSuspension,
+    /// The identity was running and stopped without announcing it.
+    Crash,
+    /// The gap is bracketed by checkpoints.
+    Maintenance,
+    /// Nothing around the gap says why it happened.
+    #[default]
+    Unknown,
+}
+
+impl GapCause {
+    /// Return a stable string tag.
+    pub fn as_tag(&self) -> &str {
+        match self {
+            Self::PlannedSuspension => "planned_suspension",
+            Self::Crash => "crash",
+            Self::Maintenance => "maintenance",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
 /// Severity of a gap.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum GapSeverity {
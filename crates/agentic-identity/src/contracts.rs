@@ -3,6 +3,11 @@
 //! Implements: Sister, SessionManagement, Grounding, Queryable
 //! Does NOT implement: FileFormatReader/Writer (directory-based JSON storage),
 //!                     WorkspaceManagement (sessions, not workspaces)
+//!
+//! The [`policy`] submodule holds the issuance policies an identity installs
+//! on its own trust grants and spawns.
+
+pub mod policy;
 
 use std::path::PathBuf;
 use std::time::Instant;
@@ -27,6 +32,7 @@ impl From<IdentityError> for SisterError {
                 SisterError::not_found(format!("identity item not found: {name}"))
             }
             IdentityError::InvalidKey(msg) => SisterError::invalid_input(msg.clone()),
+            IdentityError::InvalidId(msg) => SisterError::invalid_input(msg.clone()),
             IdentityError::InvalidPassphrase => SisterError::new(
                 ErrorCode::PermissionDenied,
                 "Invalid passphrase".to_string(),
@@ -51,6 +57,9 @@ impl From<IdentityError> for SisterError {
             IdentityError::MaxUsesExceeded => {
                 SisterError::new(ErrorCode::InvalidState, "Max uses exceeded".to_string())
             }
+            IdentityError::BudgetExhausted { .. } => {
+                SisterError::new(ErrorCode::InvalidState, e.to_string())
+            }
             IdentityError::DelegationNotAllowed => SisterError::new(
                 ErrorCode::PermissionDenied,
                 "Delegation not allowed".to_string(),
@@ -62,17 +71,42 @@ impl From<IdentityError> for SisterError {
             IdentityError::InvalidChain => {
                 SisterError::new(ErrorCode::InvalidState, "Invalid receipt chain".to_string())
             }
+            IdentityError::AuthorityEscalation(msg) => SisterError::new(
+                ErrorCode::PermissionDenied,
+                format!("Authority escalation: {msg}"),
+            ),
+            IdentityError::PolicyViolation(violation) => SisterError::new(
+                ErrorCode::PermissionDenied,
+                format!("Policy violation: {violation}"),
+            ),
+            IdentityError::IssuerNotTrusted(msg) => SisterError::new(
+                ErrorCode::PermissionDenied,
+                format!("Issuer not trusted: {msg}"),
+            ),
+            IdentityError::UntrustedTime(msg) => SisterError::new(
+                ErrorCode::PermissionDenied,
+                format!("Untrusted time source: {msg}"),
+            ),
             IdentityError::StorageError(msg) => {
                 SisterError::new(ErrorCode::StorageError, format!("Storage error: {msg}"))
             }
+            IdentityError::StoreLocked(msg) => {
+                SisterError::new(ErrorCode::StorageError, format!("Store locked: {msg}"))
+            }
             IdentityError::SerializationError(msg) => SisterError::new(
                 ErrorCode::StorageError,
                 format!("Serialization error: {msg}"),
             ),
+            IdentityError::SchemaViolation(msg) => {
+                SisterError::invalid_input(format!("Schema violation: {msg}"))
+            }
             IdentityError::InvalidFileFormat(msg) => SisterError::new(
                 ErrorCode::VersionMismatch,
                 format!("Invalid file format: {msg}"),
             ),
+            IdentityError::UnsupportedVersion { .. } => {
+                SisterError::new(ErrorCode::VersionMismatch, e.to_string())
+            }
             IdentityError::Io(err) => {
                 SisterError::new(ErrorCode::StorageError, format!("IO error: {err}"))
             }
@@ -847,7 +881,7 @@ impl Queryable for IdentitySister {
             "recent" => {
                 let limit = query.limit.unwrap_or(10);
                 let mut receipts = self.load_all_receipts()?;
-                receipts.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+                receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp));
 
                 let total = receipts.len();
                 let results: Vec<serde_json::Value> = receipts
@@ -920,7 +954,7 @@ impl Queryable for IdentitySister {
 // TESTS
 // ═══════════════════════════════════════════════════════════════════
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -0,0 +1,555 @@
+//! Issuance policies — rules an identity installs on its own authority.
+//!
+//! An identity signs a [`Policy`] for each rule it wants to hold itself to,
+//! such as "never grant `write:prod`", "every grant expires within 7 days"
+//! or "spawned children never get `network:*`", and installs it in a
+//! [`PolicySet`]. The set is checked before issuance:
+//! [`TrustGrantBuilder::policies`](crate::trust::TrustGrantBuilder::policies)
+//! makes signing a grant fail, and
+//! [`spawn_child_with_policy`](crate::spawn::spawn_child_with_policy) makes a
+//! spawn fail, with [`IdentityError::PolicyViolation`] before anything is
+//! signed.
+//!
+//! The error carries the [`PolicyViolation`], whose
+//! [`receipt`](PolicyViolation::receipt) records the refusal in the
+//! identity's receipt chain.
+//!
+//! Policies only constrain issuance by the identity that signed them, and
+//! only where the caller supplies the set; they are a guard against the
+//! identity's own mistakes, not something a verifier can enforce.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "signing")]
+use crate::crypto::signer::{sign_in_domain_with, Signer};
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+use crate::identity::anchor::decode_public_key;
+use crate::identity::IdentityId;
+#[cfg(feature = "signing")]
+use crate::receipt::action::{ActionContent, ActionType};
+#[cfg(feature = "signing")]
+use crate::receipt::receipt::ReceiptBuilder;
+#[cfg(feature = "signing")]
+use crate::receipt::ActionReceipt;
+use crate::trust::capability::{capability_uri_covers, Capability};
+use crate::trust::TrustGrant;
+
+/// Unique identifier for an installed policy.
+///
+/// Format: `apolicy_` + base58 of the first 16 bytes of SHA-256 of the
+/// signed content.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PolicyId(pub String);
+
+impl std::fmt::Display for PolicyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What a policy forbids.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum PolicyRule {
+    /// Never grant a capability overlapping `capability`, so `write:prod`
+    /// also blocks grants of `write:*` and `*`.
+    DenyGrant { capability: String },
+    /// Every grant must expire no later than `max_secs` after it is issued.
+    MaxGrantLifetime { max_secs: u64 },
+    /// A spawned child's granted authority and ceiling must not overlap
+    /// `capability`.
+    DenySpawnAuthority { capability: String },
+}
+
+impl PolicyRule {
+    #[cfg(feature = "signing")]
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::DenyGrant { capability } | Self::DenySpawnAuthority { capability } => {
+                Capability::try_new(capability.as_str()).map(|_| ())
+            }
+            Self::MaxGrantLifetime { max_secs: 0 } => Err(IdentityError::InvalidInput(
+                "max grant lifetime must be positive".into(),
+            )),
+            Self::MaxGrantLifetime { .. } => Ok(()),
+        }
+    }
+}
+
+/// The issuance a policy was checked against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    TrustGrant,
+    Spawn,
+}
+
+impl PolicyAction {
+    /// Return a stable string tag.
+    pub fn as_tag(&self) -> &'static str {
+        match self {
+            Self::TrustGrant => "trust_grant",
+            Self::Spawn => "spawn",
+        }
+    }
+}
+
+/// A rule signed by the identity it constrains.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    pub id: PolicyId,
+    pub identity: IdentityId,
+    /// Base64 key that signed the policy.
+    pub identity_key: String,
+    pub rule: PolicyRule,
+    /// When the policy was signed (microseconds since epoch).
+    pub installed_at: u64,
+    pub signature: String,
+    /// Signature scheme version; see [`signing::SIGNATURE_VERSION`].
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// Everything in a [`Policy`] except its ID and signature.
+#[derive(Serialize)]
+struct PolicyPayload<'a> {
+    identity: &'a IdentityId,
+    identity_key: &'a str,
+    rule: &'a PolicyRule,
+    installed_at: u64,
+}
+
+impl Policy {
+    /// Sign `rule` as a policy on `signer`'s own issuance.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` for a capability pattern with a
+    /// control character or a zero lifetime.
+    #[cfg(feature = "signing")]
+    pub fn sign<S: Signer + ?Sized>(signer: &S, rule: PolicyRule) -> Result<Self> {
+        rule.validate()?;
+        let mut policy = Self {
+            id: PolicyId(String::new()),
+            identity: IdentityId::from_verifying_key(&signer.verifying_key()),
+            identity_key: signer.public_key_base64(),
+            rule,
+            installed_at: crate::time::now_micros(),
+            signature: String::new(),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        let input = policy.signing_input()?;
+        let hash = Sha256::digest(input.as_bytes());
+        policy.id = PolicyId(format!(
+            "apolicy_{}",
+            bs58::encode(&hash[..16]).into_string()
+        ));
+        policy.signature = sign_in_domain_with(signer, SignatureDomain::Policy, input.as_bytes())?;
+        Ok(policy)
+    }
+
+    /// Verify that the policy was signed by `identity_key`, from which
+    /// `identity` derives, and that `id` matches its content.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if the key does not decode or
+    /// `identity` does not derive from it, `IdentityError::InvalidId` for a
+    /// mismatched ID, and `IdentityError::SignatureInvalid` if the policy was
+    /// altered.
+    pub fn verify(&self) -> Result<()> {
+        let key = decode_public_key(&self.identity_key)?;
+        if IdentityId::from_verifying_key(&key) != self.identity {
+            return Err(IdentityError::InvalidKey(format!(
+                "policy of {} was not signed by its key",
+                self.identity
+            )));
+        }
+        let input = self.signing_input()?;
+        let hash = Sha256::digest(input.as_bytes());
+        if self.id.0 != format!("apolicy_{}", bs58::encode(&hash[..16]).into_string()) {
+            return Err(IdentityError::InvalidId(format!(
+                "policy ID {} does not match its content",
+                self.id
+            )));
+        }
+        signing::verify_versioned(
+            &key,
+            SignatureDomain::Policy,
+            self.signature_version,
+            input.as_bytes(),
+            &self.signature,
+        )
+    }
+
+    fn signing_input(&self) -> Result<String> {
+        serde_json::to_string(&PolicyPayload {
+            identity: &self.identity,
+            identity_key: &self.identity_key,
+            rule: &self.rule,
+            installed_at: self.installed_at,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+    }
+
+    fn violation(&self, action: PolicyAction, detail: String) -> PolicyViolation {
+        PolicyViolation {
+            policy: self.id.clone(),
+            identity: self.identity.clone(),
+            rule: self.rule.clone(),
+            action,
+            detail,
+        }
+    }
+}
+
+/// Do two capability patterns share any capability?
+fn overlaps(a: &str, b: &str) -> bool {
+    capability_uri_covers(a, b) || capability_uri_covers(b, a)
+}
+
+/// The policies an identity has installed.
+///
+/// Only verified policies get in: [`install`](Self::install) checks each
+/// signature, and so does deserialization.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "Vec<Policy>", into = "Vec<Policy>")]
+pub struct PolicySet {
+    policies: Vec<Policy>,
+}
+
+impl TryFrom<Vec<Policy>> for PolicySet {
+    type Error = IdentityError;
+
+    fn try_from(policies: Vec<Policy>) -> Result<Self> {
+        let mut set = Self::new();
+        for policy in policies {
+            set.install(policy)?;
+        }
+        Ok(set)
+    }
+}
+
+impl From<PolicySet> for Vec<Policy> {
+    fn from(set: PolicySet) -> Self {
+        set.policies
+    }
+}
+
+impl PolicySet {
+    /// Create an empty set (no restrictions).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify `policy` and add it. Installing a policy twice is a no-op.
+    pub fn install(&mut self, policy: Policy) -> Result<()> {
+        policy.verify()?;
+        if !self.policies.iter().any(|p| p.id == policy.id) {
+            self.policies.push(policy);
+        }
+        Ok(())
+    }
+
+    /// Remove the policy with `id`, returning it if it was installed.
+    pub fn remove(&mut self, id: &PolicyId) -> Option<Policy> {
+        let index = self.policies.iter().position(|p| &p.id == id)?;
+        Some(self.policies.remove(index))
+    }
+
+    /// The installed policies, in installation order.
+    pub fn policies(&self) -> &[Policy] {
+        &self.policies
+    }
+
+    /// Is this set empty (no restrictions)?
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    fn of<'a>(&'a self, identity: &'a IdentityId) -> impl Iterator<Item = &'a Policy> {
+        self.policies
+            .iter()
+            .filter(move |p| &p.identity == identity)
+    }
+
+    /// Check a grant against its grantor's policies.
+    ///
+    /// Returns the first violation, if any.
+    pub fn check_grant(&self, grant: &TrustGrant) -> Option<PolicyViolation> {
+        self.of(&grant.grantor)
+            .find_map(|policy| match &policy.rule {
+                PolicyRule::DenyGrant { capability } => grant
+                    .capabilities
+                    .iter()
+                    .find(|cap| overlaps(capability, &cap.uri))
+                    .map(|cap| {
+                        policy.violation(
+                            PolicyAction::TrustGrant,
+                            format!("grant of '{}' overlaps denied '{capability}'", cap.uri),
+                        )
+                    }),
+                PolicyRule::MaxGrantLifetime { max_secs } => {
+                    let limit = grant
+                        .granted_at
+                        .saturating_add(max_secs.saturating_mul(1_000_000));
+                    match grant.constraints.not_after {
+                        Some(not_after) if not_after <= limit => None,
+                        Some(not_after) => Some(policy.violation(
+                            PolicyAction::TrustGrant,
+                            format!(
+                                "grant expires {}s after issue; at most {max_secs}s allowed",
+                                not_after.saturating_sub(grant.granted_at) / 1_000_000
+                            ),
+                        )),
+                        None => Some(policy.violation(
+                            PolicyAction::TrustGrant,
+                            format!("grant never expires; at most {max_secs}s allowed"),
+                        )),
+                    }
+                }
+                PolicyRule::DenySpawnAuthority { .. } => None,
+            })
+    }
+
+    /// Check a spawn by `parent` against its policies.
+    ///
+    /// Returns the first violation, if any.
+    pub fn check_spawn(
+        &self,
+        parent: &IdentityId,
+        authority_granted: &[Capability],
+        authority_ceiling: &[Capability],
+    ) -> Option<PolicyViolation> {
+        self.of(parent).find_map(|policy| match &policy.rule {
+            PolicyRule::DenySpawnAuthority { capability } => authority_granted
+                .iter()
+                .chain(authority_ceiling)
+                .find(|cap| overlaps(capability, &cap.uri))
+                .map(|cap| {
+                    policy.violation(
+                        PolicyAction::Spawn,
+                        format!(
+                            "child authority '{}' overlaps denied '{capability}'",
+                            cap.uri
+                        ),
+                    )
+                }),
+            PolicyRule::DenyGrant { .. } | PolicyRule::MaxGrantLifetime { .. } => None,
+        })
+    }
+}
+
+/// An issuance refused by an installed policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyViolation {
+    pub policy: PolicyId,
+    /// The identity whose issuance was refused.
+    pub identity: IdentityId,
+    pub rule: PolicyRule,
+    pub action: PolicyAction,
+    /// What about the issuance broke the rule.
+    pub detail: String,
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} refused by policy {}: {}",
+            self.action.as_tag(),
+            self.policy,
+            self.detail
+        )
+    }
+}
+
+impl From<PolicyViolation> for IdentityError {
+    fn from(violation: PolicyViolation) -> Self {
+        IdentityError::PolicyViolation(Box::new(violation))
+    }
+}
+
+impl PolicyViolation {
+    /// Record the refusal as a `policy_violation` receipt signed by `signer`,
+    /// normally the identity whose issuance was refused.
+    #[cfg(feature = "signing")]
+    pub fn receipt<S: Signer + ?Sized>(&self, signer: &S) -> Result<ActionReceipt> {
+        let data = serde_json::to_value(self)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        ReceiptBuilder::new(
+            IdentityId::from_verifying_key(&signer.verifying_key()),
+            ActionType::Custom("policy_violation".into()),
+            ActionContent::with_data(self.to_string(), data),
+        )
+        .sign(signer)
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::spawn::{spawn_child_with_policy, SpawnConstraints, SpawnLifetime, SpawnType};
+    use crate::trust::{TrustConstraints, TrustGrantBuilder};
+
+    fn grant_builder(grantor: &IdentityAnchor, caps: &[&str]) -> TrustGrantBuilder {
+        let grantee = IdentityAnchor::new(None);
+        TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capabilities(caps.iter().map(|c| Capability::new(*c)).collect())
+    }
+
+    #[test]
+    fn test_policy_sign_verify_and_tamper() {
+        let anchor = IdentityAnchor::new(None);
+        let policy = Policy::sign(
+            &anchor,
+            PolicyRule::DenyGrant {
+                capability: "write:prod".into(),
+            },
+        )
+        .unwrap();
+        assert!(policy.id.0.starts_with("apolicy_"));
+        policy.verify().unwrap();
+
+        let mut tampered = policy.clone();
+        tampered.rule = PolicyRule::DenyGrant {
+            capability: "write:dev".into(),
+        };
+        assert!(tampered.verify().is_err());
+        assert!(PolicySet::new().install(tampered.clone()).is_err());
+
+        let json = serde_json::to_string(&[tampered]).unwrap();
+        assert!(serde_json::from_str::<PolicySet>(&json).is_err());
+
+        assert!(Policy::sign(&anchor, PolicyRule::MaxGrantLifetime { max_secs: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_deny_grant_blocks_overlapping_capabilities() {
+        let anchor = IdentityAnchor::new(None);
+        let mut set = PolicySet::new();
+        set.install(
+            Policy::sign(
+                &anchor,
+                PolicyRule::DenyGrant {
+                    capability: "write:prod".into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        for caps in [&["write:prod"][..], &["write:*"][..], &["read:x", "*"][..]] {
+            let err = grant_builder(&anchor, caps)
+                .policies(set.clone())
+                .sign(&anchor)
+                .unwrap_err();
+            let IdentityError::PolicyViolation(violation) = err else {
+                panic!("expected a policy violation");
+            };
+            assert_eq!(violation.action, PolicyAction::TrustGrant);
+            assert_eq!(violation.policy, set.policies()[0].id);
+
+            let receipt = violation.receipt(&anchor).unwrap();
+            assert_eq!(
+                receipt.action_type,
+                ActionType::Custom("policy_violation".into())
+            );
+            crate::receipt::verify::verify_receipt(&receipt).unwrap();
+        }
+
+        grant_builder(&anchor, &["write:dev", "read:prod"])
+            .policies(set.clone())
+            .sign(&anchor)
+            .unwrap();
+
+        // Another identity's policies do not constrain this grantor.
+        let other = IdentityAnchor::new(None);
+        grant_builder(&other, &["write:prod"])
+            .policies(set)
+            .sign(&other)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_max_grant_lifetime() {
+        let anchor = IdentityAnchor::new(None);
+        let mut set = PolicySet::new();
+        set.install(
+            Policy::sign(
+                &anchor,
+                PolicyRule::MaxGrantLifetime {
+                    max_secs: 7 * 24 * 3600,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let err = grant_builder(&anchor, &["read:x"])
+            .policies(set.clone())
+            .sign(&anchor)
+            .unwrap_err();
+        assert!(err.to_string().contains("never expires"), "got: {err}");
+
+        let now = crate::time::now_micros();
+        let mut long = TrustConstraints::open();
+        long.not_after = Some(now + 30 * 24 * 3600 * 1_000_000);
+        assert!(grant_builder(&anchor, &["read:x"])
+            .constraints(long)
+            .policies(set.clone())
+            .sign(&anchor)
+            .is_err());
+
+        let mut short = TrustConstraints::open();
+        short.not_after = Some(now + 3600 * 1_000_000);
+        grant_builder(&anchor, &["read:x"])
+            .constraints(short)
+            .policies(set)
+            .sign(&anchor)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_deny_spawn_authority() {
+        let parent = IdentityAnchor::new(None);
+        let mut set = PolicySet::new();
+        set.install(
+            Policy::sign(
+                &parent,
+                PolicyRule::DenySpawnAuthority {
+                    capability: "network:*".into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let spawn = |caps: &[&str]| {
+            spawn_child_with_policy(
+                &parent,
+                SpawnType::Worker,
+                "fetch",
+                caps.iter().map(|c| Capability::new(*c)).collect(),
+                caps.iter().map(|c| Capability::new(*c)).collect(),
+                SpawnLifetime::Indefinite,
+                SpawnConstraints::default(),
+                None,
+                &[],
+                &set,
+            )
+        };
+
+        let Err(IdentityError::PolicyViolation(violation)) = spawn(&["network:http"]) else {
+            panic!("expected a policy violation");
+        };
+        assert_eq!(violation.action, PolicyAction::Spawn);
+        spawn(&["read:files"]).unwrap();
+    }
+}
@@ -0,0 +1,259 @@
+//! JSON Canonicalization Scheme (RFC 8785).
+//!
+//! Signed artifacts at [`CANONICAL_SIGNATURE_VERSION`] sign a JCS payload:
+//! the same value always canonicalizes to the same bytes, whatever order
+//! its fields were written in or which JSON library produced them, so a
+//! verifier in another language can rebuild the signed bytes exactly.
+//!
+//! The canonical form is compact JSON with:
+//! - object members sorted by the UTF-16 code units of their keys;
+//! - strings escaped as ECMAScript `JSON.stringify` does: `"`, `\`, and
+//!   control characters only, using the short escapes where they exist;
+//! - numbers in ECMAScript `Number` form (shortest round-trip digits,
+//!   `-0` written as `0`).
+//!
+//! Integers beyond ±2^53 cannot be represented exactly as an IEEE double
+//! and are rejected rather than silently rounded.
+//!
+//! [`CANONICAL_SIGNATURE_VERSION`]: super::signing::CANONICAL_SIGNATURE_VERSION
+
+use serde::Serialize;
+use serde_json::{Number, Value};
+
+use crate::error::{IdentityError, Result};
+
+/// Largest integer magnitude an IEEE double holds exactly.
+pub(crate) const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Serialize `value` and return its canonical JSON text.
+pub fn to_canonical_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    canonicalize(&value)
+}
+
+/// Serialize `value` and return its canonical JSON as UTF-8 bytes.
+pub fn to_canonical_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    to_canonical_string(value).map(String::into_bytes)
+}
+
+/// Canonical JSON text of an already-parsed value.
+pub fn canonicalize(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_value(value, &mut out)?;
+    Ok(out)
+}
+
+fn write_value(value: &Value, out: &mut String) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(n, out)?,
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(key, out);
+                out.push(':');
+                write_value(item, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn write_number(n: &Number, out: &mut String) -> Result<()> {
+    if let Some(u) = n.as_u64() {
+        if u > MAX_SAFE_INTEGER {
+            return Err(unsafe_integer(n));
+        }
+        out.push_str(&u.to_string());
+    } else if let Some(i) = n.as_i64() {
+        if i.unsigned_abs() > MAX_SAFE_INTEGER {
+            return Err(unsafe_integer(n));
+        }
+        out.push_str(&i.to_string());
+    } else {
+        let f = n.as_f64().ok_or_else(|| {
+            IdentityError::SerializationError(format!("number {n} is not representable"))
+        })?;
+        write_double(f, out)?;
+    }
+    Ok(())
+}
+
+fn unsafe_integer(n: &Number) -> IdentityError {
+    IdentityError::SerializationError(format!(
+        "integer {n} exceeds 2^53 and cannot be canonicalized exactly"
+    ))
+}
+
+/// Write a double the way ECMAScript's `Number::toString` does.
+fn write_double(f: f64, out: &mut String) -> Result<()> {
+    if !f.is_finite() {
+        return Err(IdentityError::SerializationError(format!(
+            "{f} is not a JSON number"
+        )));
+    }
+    if f == 0.0 {
+        out.push('0');
+        return Ok(());
+    }
+    if f < 0.0 {
+        out.push('-');
+    }
+
+    // Rust's `{:e}` gives the shortest round-trip digits, e.g. `1.2345e-7`.
+    let sci = format!("{:e}", f.abs());
+    let (mantissa, exponent) = sci.split_once('e').expect("`{:e}` always has an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("`{:e}` exponent is an integer");
+    let k = digits.len() as i32;
+    // Decimal point position relative to the first digit.
+    let n = exponent + 1;
+
+    if (k..=21).contains(&n) {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if (1..=21).contains(&n) {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if (-5..=0).contains(&n) {
+        out.push_str("0.");
+        out.push_str(&"0".repeat((-n) as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n - 1 < 0 { '-' } else { '+' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn double(f: f64) -> String {
+        let mut out = String::new();
+        write_double(f, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_sorts_keys_and_strips_whitespace() {
+        let value: Value = serde_json::from_str(
+            r#"{ "b": [1, {"z": null, "a": true}], "a": "x", "\u20ac": 1, "\r": 2, "1": 3 }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            canonicalize(&value).unwrap(),
+            "{\"\\r\":2,\"1\":3,\"a\":\"x\",\"b\":[1,{\"a\":true,\"z\":null}],\"\u{20ac}\":1}"
+        );
+    }
+
+    #[test]
+    fn test_keys_sort_by_utf16_code_units() {
+        // U+1F600 is a surrogate pair (0xD83D...), which sorts before U+FB33
+        // in UTF-16 even though it is the larger code point.
+        let value = json!({ "\u{fb33}": 1, "\u{1f600}": 2 });
+        assert_eq!(
+            canonicalize(&value).unwrap(),
+            "{\"\u{1f600}\":2,\"\u{fb33}\":1}"
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let value = json!("\u{0}\u{8}\t\n\u{b}\u{c}\r\u{1f}\"\\/\u{7f}\u{20ac}");
+        assert_eq!(
+            canonicalize(&value).unwrap(),
+            "\"\\u0000\\b\\t\\n\\u000b\\f\\r\\u001f\\\"\\\\/\u{7f}\u{20ac}\""
+        );
+    }
+
+    #[test]
+    fn test_number_formatting() {
+        // Examples from RFC 8785, Appendix B.
+        assert_eq!(double(-0.0), "0");
+        assert_eq!(double(1e21), "1e+21");
+        assert_eq!(double(1e20), "100000000000000000000");
+        assert_eq!(double(1e-7), "1e-7");
+        assert_eq!(double(1e-6), "0.000001");
+        assert_eq!(double(333333333.3333333), "333333333.3333333");
+        assert_eq!(double(9007199254740994.0), "9007199254740994");
+        assert_eq!(double(5e-324), "5e-324");
+        assert_eq!(double(f64::MAX), "1.7976931348623157e+308");
+        assert_eq!(double(-1.5), "-1.5");
+        assert_eq!(
+            canonicalize(&json!([1, -2, 0.5, 4.5e-7])).unwrap(),
+            "[1,-2,0.5,4.5e-7]"
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsafe_integers() {
+        assert!(canonicalize(&json!(MAX_SAFE_INTEGER)).is_ok());
+        assert!(canonicalize(&json!(MAX_SAFE_INTEGER + 1)).is_err());
+        assert!(canonicalize(&json!(-(MAX_SAFE_INTEGER as i64) - 1)).is_err());
+    }
+
+    #[test]
+    fn test_struct_field_order_is_irrelevant() {
+        #[derive(Serialize)]
+        struct Ab {
+            a: u32,
+            b: &'static str,
+        }
+        #[derive(Serialize)]
+        struct Ba {
+            b: &'static str,
+            a: u32,
+        }
+        assert_eq!(
+            to_canonical_string(&Ab { a: 1, b: "x" }).unwrap(),
+            to_canonical_string(&Ba { b: "x", a: 1 }).unwrap()
+        );
+    }
+}
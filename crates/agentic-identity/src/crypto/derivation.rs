@@ -53,7 +53,7 @@ pub fn revocation_context(trust_id: &str) -> String {
     format!("agentic-identity/revocation/{trust_id}")
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::crypto::keys::Ed25519KeyPair;
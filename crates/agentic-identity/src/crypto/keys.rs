@@ -4,6 +4,7 @@
 //! X25519 is used for Diffie-Hellman key exchange (encrypted channels).
 
 use ed25519_dalek::{SigningKey, VerifyingKey};
+#[cfg(feature = "signing")]
 use x25519_dalek::{EphemeralSecret, PublicKey as X25519PublicKey, StaticSecret};
 use zeroize::Zeroize;
 
@@ -19,6 +20,7 @@ pub struct Ed25519KeyPair {
 
 impl Ed25519KeyPair {
     /// Generate a new random Ed25519 key pair.
+    #[cfg(feature = "signing")]
     pub fn generate() -> Self {
         let signing_key = SigningKey::generate(&mut rand::thread_rng());
         let verifying_key = signing_key.verifying_key();
@@ -74,11 +76,13 @@ impl Drop for Ed25519KeyPair {
 }
 
 /// An X25519 static key pair for Diffie-Hellman key exchange.
+#[cfg(feature = "signing")]
 pub struct X25519KeyPair {
     secret: StaticSecret,
     public: X25519PublicKey,
 }
 
+#[cfg(feature = "signing")]
 impl X25519KeyPair {
     /// Generate a new random X25519 key pair.
     pub fn generate() -> Self {
@@ -94,6 +98,16 @@ impl X25519KeyPair {
         Self { secret, public }
     }
 
+    /// The X25519 key pair birationally equivalent to an Ed25519 signing
+    /// key, whose public half is [`x25519_public_from_ed25519`] of the
+    /// verifying key. Lets a key that only signs also receive.
+    pub fn from_ed25519(signing_key: &SigningKey) -> Self {
+        let mut scalar = signing_key.to_scalar_bytes();
+        let pair = Self::from_secret_bytes(scalar);
+        scalar.zeroize();
+        pair
+    }
+
     /// Perform Diffie-Hellman key exchange with a peer's public key.
     ///
     /// Returns the shared secret (32 bytes).
@@ -112,14 +126,22 @@ impl X25519KeyPair {
     }
 }
 
+/// The X25519 public key of an Ed25519 verifying key, for encrypting to an
+/// identity known only by its signing key.
+#[cfg(feature = "signing")]
+pub fn x25519_public_from_ed25519(verifying_key: &VerifyingKey) -> X25519PublicKey {
+    X25519PublicKey::from(verifying_key.to_montgomery().to_bytes())
+}
+
 /// Generate an ephemeral X25519 key pair for one-time use.
+#[cfg(feature = "signing")]
 pub fn ephemeral_x25519() -> (EphemeralSecret, X25519PublicKey) {
     let secret = EphemeralSecret::random_from_rng(rand::thread_rng());
     let public = X25519PublicKey::from(&secret);
     (secret, public)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
 
@@ -172,4 +194,19 @@ mod tests {
         let ac = alice.diffie_hellman(charlie.public_key());
         assert_ne!(ab, ac);
     }
+
+    #[test]
+    fn test_x25519_from_ed25519_agrees_with_public_conversion() {
+        let alice = Ed25519KeyPair::generate();
+        let bob = X25519KeyPair::generate();
+        let alice_x = X25519KeyPair::from_ed25519(alice.signing_key());
+        assert_eq!(
+            alice_x.public_key_bytes(),
+            *x25519_public_from_ed25519(alice.verifying_key()).as_bytes()
+        );
+        assert_eq!(
+            bob.diffie_hellman(&x25519_public_from_ed25519(alice.verifying_key())),
+            alice_x.diffie_hellman(bob.public_key())
+        );
+    }
 }
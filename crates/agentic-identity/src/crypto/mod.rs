@@ -2,14 +2,28 @@
 //!
 //! This module provides:
 //! - Ed25519 key generation, signing, and verification
+//! - JSON canonicalization (RFC 8785) for signed payloads
+//! - A [`Signer`](signer::Signer) trait for keys held outside the process
 //! - X25519 Diffie-Hellman key exchange
 //! - HKDF-SHA256 key derivation
 //! - Argon2id passphrase-based key derivation
 //! - ChaCha20-Poly1305 authenticated encryption
+//! - Shamir secret sharing for key backups
 //! - Cryptographically secure random number generation
+//!
+//! Key generation, X25519, encryption, randomness and splitting secrets need
+//! the `signing` feature; signature verification, canonicalization, key
+//! derivation and combining secret shares are always built.
 
+pub mod canonical;
 pub mod derivation;
+#[cfg(feature = "signing")]
 pub mod encryption;
 pub mod keys;
+#[cfg(feature = "signing")]
 pub mod random;
+pub mod signer;
 pub mod signing;
+pub mod sss;
+
+pub use signer::{sign_in_domain_with, sign_with, Signer};
@@ -0,0 +1,251 @@
+//! Pluggable signers.
+//!
+//! Everything that signs an artifact — receipts, trust grants, spawn
+//! records, continuity events — takes a [`Signer`] rather than a raw
+//! Ed25519 key, so the private key can live in an HSM, a TPM, or an OS
+//! keychain and never enter process memory. A signer only has to expose its
+//! public key and sign bytes it is handed; domain separation and encoding
+//! are done here, so every backend produces identical signatures.
+//!
+//! [`SigningKey`] and [`IdentityAnchor`](crate::identity::IdentityAnchor)
+//! implement the trait, so in-memory keys work unchanged.
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+
+use crate::error::Result;
+
+use super::signing::SignatureDomain;
+
+/// A source of Ed25519 signatures whose private key may be held elsewhere.
+///
+/// Implementations for hardware or remote keys return an error from
+/// [`sign_message`](Signer::sign_message) when the device is unavailable or
+/// the user declines; callers surface it unchanged.
+pub trait Signer {
+    /// The public key signatures verify against.
+    fn verifying_key(&self) -> VerifyingKey;
+
+    /// Sign `message` exactly as given, with no domain tag.
+    fn sign_message(&self, message: &[u8]) -> Result<Signature>;
+
+    /// The public key as base64, as stored in artifacts.
+    fn public_key_base64(&self) -> String {
+        base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            self.verifying_key().to_bytes(),
+        )
+    }
+}
+
+impl Signer for SigningKey {
+    fn verifying_key(&self) -> VerifyingKey {
+        SigningKey::verifying_key(self)
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        Ok(ed25519_dalek::Signer::sign(self, message))
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for &S {
+    fn verifying_key(&self) -> VerifyingKey {
+        (**self).verifying_key()
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        (**self).sign_message(message)
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn verifying_key(&self) -> VerifyingKey {
+        (**self).verifying_key()
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+        (**self).sign_message(message)
+    }
+}
+
+/// Sign `message` with `signer` and return the base64 signature.
+///
+/// The counterpart of [`sign_to_base64`](super::signing::sign_to_base64).
+/// The signature is checked against the signer's public key before it is
+/// returned, so a faulty backend cannot emit an artifact that never
+/// verifies.
+pub fn sign_with<S: Signer + ?Sized>(signer: &S, message: &[u8]) -> Result<String> {
+    let signature = signer.sign_message(message)?;
+    super::signing::verify(&signer.verifying_key(), message, &signature)?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        signature.to_bytes(),
+    ))
+}
+
+/// Sign `message` in `domain` with `signer` and return the base64 signature.
+///
+/// Produces the same signature as
+/// [`sign_in_domain`](super::signing::sign_in_domain) for the same key.
+pub fn sign_in_domain_with<S: Signer + ?Sized>(
+    signer: &S,
+    domain: SignatureDomain,
+    message: &[u8],
+) -> Result<String> {
+    sign_with(signer, &domain.separate(message))
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::crypto::keys::Ed25519KeyPair;
+    use crate::crypto::signing::{sign_in_domain, verify_in_domain};
+    use crate::error::IdentityError;
+
+    /// A signer that holds its key behind an opaque handle, like an HSM.
+    struct Enclave {
+        key: SigningKey,
+        online: bool,
+    }
+
+    impl Signer for Enclave {
+        fn verifying_key(&self) -> VerifyingKey {
+            self.key.verifying_key()
+        }
+
+        fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+            if !self.online {
+                return Err(IdentityError::StorageError("enclave offline".into()));
+            }
+            Ok(ed25519_dalek::Signer::sign(&self.key, message))
+        }
+    }
+
+    #[test]
+    fn test_external_signer_matches_in_memory_key() {
+        let kp = Ed25519KeyPair::generate();
+        let enclave = Enclave {
+            key: kp.signing_key().clone(),
+            online: true,
+        };
+        let message = b"receipt-hash";
+
+        let external = sign_in_domain_with(&enclave, SignatureDomain::Receipt, message).unwrap();
+        let local = sign_in_domain(kp.signing_key(), SignatureDomain::Receipt, message);
+        assert_eq!(external, local);
+        assert!(verify_in_domain(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            message,
+            &external
+        )
+        .is_ok());
+        assert_eq!(
+            enclave.public_key_base64(),
+            kp.signing_key().public_key_base64()
+        );
+
+        // A boxed trait object works too.
+        let boxed: Box<dyn Signer> = Box::new(enclave);
+        assert!(sign_in_domain_with(&boxed, SignatureDomain::Receipt, message).is_ok());
+    }
+
+    #[test]
+    fn test_signer_errors_and_bad_signatures_are_rejected() {
+        let kp = Ed25519KeyPair::generate();
+        let offline = Enclave {
+            key: kp.signing_key().clone(),
+            online: false,
+        };
+        assert!(sign_in_domain_with(&offline, SignatureDomain::Receipt, b"m").is_err());
+
+        /// Claims one key but signs with another.
+        struct Mismatched(SigningKey, SigningKey);
+        impl Signer for Mismatched {
+            fn verifying_key(&self) -> VerifyingKey {
+                self.0.verifying_key()
+            }
+            fn sign_message(&self, message: &[u8]) -> Result<Signature> {
+                Ok(ed25519_dalek::Signer::sign(&self.1, message))
+            }
+        }
+        let other = Ed25519KeyPair::generate();
+        let bad = Mismatched(kp.signing_key().clone(), other.signing_key().clone());
+        assert!(matches!(
+            sign_in_domain_with(&bad, SignatureDomain::Receipt, b"m"),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+
+    #[cfg(feature = "signing")]
+    #[test]
+    fn test_builders_and_engines_accept_external_signer() {
+        use crate::continuity::{record_experience, ExperienceType, SystemEvent};
+        use crate::identity::IdentityId;
+        use crate::receipt::receipt::ReceiptBuilder;
+        use crate::receipt::verify::verify_receipt;
+        use crate::receipt::{ActionContent, ActionType};
+        use crate::trust::{verify_trust_grant, Capability, TrustGrantBuilder};
+
+        let kp = Ed25519KeyPair::generate();
+        let enclave = Enclave {
+            key: kp.signing_key().clone(),
+            online: true,
+        };
+        let actor = IdentityId::from_verifying_key(&enclave.verifying_key());
+
+        let receipt = ReceiptBuilder::new(
+            actor.clone(),
+            ActionType::Decision,
+            ActionContent::new("signed by hardware"),
+        )
+        .sign(&enclave)
+        .unwrap();
+        assert!(verify_receipt(&receipt).unwrap().is_valid);
+
+        let grantee = Ed25519KeyPair::generate();
+        let grant = TrustGrantBuilder::new(
+            actor,
+            IdentityId::from_verifying_key(grantee.verifying_key()),
+            grantee.signing_key().public_key_base64(),
+        )
+        .capability(Capability::new("read:calendar"))
+        .sign(&enclave)
+        .unwrap();
+        assert!(
+            verify_trust_grant(&grant, "read:calendar", 0, &[])
+                .unwrap()
+                .is_valid
+        );
+
+        let event = record_experience(
+            &enclave,
+            ExperienceType::System {
+                event: SystemEvent::Checkpoint,
+            },
+            "hash",
+            0.5,
+            None,
+        )
+        .unwrap();
+        assert!(verify_in_domain(
+            kp.verifying_key(),
+            SignatureDomain::Experience,
+            event.cumulative_hash.as_bytes(),
+            &event.signature,
+        )
+        .is_ok());
+
+        // A signer that fails surfaces its error instead of a bad receipt.
+        let offline = Enclave {
+            key: kp.signing_key().clone(),
+            online: false,
+        };
+        let result = ReceiptBuilder::new(
+            IdentityId::from_verifying_key(&offline.verifying_key()),
+            ActionType::Decision,
+            ActionContent::new("never signed"),
+        )
+        .sign(&offline);
+        assert!(matches!(result, Err(IdentityError::StorageError(_))));
+    }
+}
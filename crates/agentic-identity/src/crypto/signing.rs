@@ -2,11 +2,160 @@
 //!
 //! Provides a simple API for signing arbitrary messages and verifying
 //! signatures against known public keys.
+//!
+//! Artifacts sign in a [`SignatureDomain`]: the signed bytes are the
+//! domain's tag, a `:`, then the message, so a signature made for one
+//! artifact type never verifies as another. Every tag has the form
+//! `aid:<type>:v<n>`, which keeps the set prefix-free.
+//!
+//! Artifacts written before domain separation carry no
+//! `signature_version` and signed the bare message. They load as
+//! [`LEGACY_SIGNATURE_VERSION`] and verify through the legacy path in
+//! [`verify_versioned`]; everything signed now is
+//! [`SIGNATURE_VERSION`]. The version is not itself signed: stripping it
+//! only asks the verifier to check a domain-tagged signature against the
+//! bare message, which fails.
+//!
+//! Receipts, trust grants, spawn records and capability declarations sign
+//! at [`CANONICAL_SIGNATURE_VERSION`]: the signature is made the same way
+//! as [`SIGNATURE_VERSION`], but the message is the artifact's RFC 8785
+//! canonical JSON (see [`canonical`](super::canonical)) rather than an
+//! ad-hoc string, so other implementations can rebuild it byte for byte.
+//! Each artifact still rebuilds its older message format for lower versions.
 
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 
 use crate::error::{IdentityError, Result};
 
+/// Signature version of artifacts signed before domain separation.
+pub const LEGACY_SIGNATURE_VERSION: u32 = 0;
+
+/// Signature version written on every artifact signed by this version.
+pub const SIGNATURE_VERSION: u32 = 1;
+
+/// Signature version of artifacts whose signed message is a JCS-canonical
+/// payload. Verified exactly like [`SIGNATURE_VERSION`]; the version tells
+/// the artifact which message to rebuild.
+pub const CANONICAL_SIGNATURE_VERSION: u32 = 2;
+
+/// The kind of artifact a signature is made for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignatureDomain {
+    /// An action receipt's signature over its receipt hash.
+    Receipt,
+    /// A witness countersignature on a receipt.
+    Witness,
+    /// A multi-signature member's signature over a receipt or grant hash.
+    Cosignature,
+    /// A grantor's signature over a trust grant hash.
+    TrustGrant,
+    /// A grantee's acknowledgment of a trust grant.
+    TrustAcknowledgment,
+    /// A revocation of a trust grant.
+    Revocation,
+    /// A time token from a trusted time source.
+    TimeToken,
+    /// A parent's signature over a spawn record.
+    Spawn,
+    /// A child's acknowledgment of its spawn record.
+    SpawnAcknowledgment,
+    /// A child's signature over a lineage proof.
+    LineageProof,
+    /// A continuity experience event.
+    Experience,
+    /// A continuity anchor.
+    ContinuityAnchor,
+    /// A continuity heartbeat.
+    Heartbeat,
+    /// A continuity claim.
+    ContinuityClaim,
+    /// A signed continuity export.
+    ContinuityExport,
+    /// A recorded competence attempt.
+    CompetenceAttempt,
+    /// A validator's countersignature on a competence attempt.
+    CompetenceValidation,
+    /// A competence proof.
+    CompetenceProof,
+    /// A competence proof disclosing only aggregate statistics.
+    CompetenceAggregate,
+    /// A negative capability proof.
+    NegativeProof,
+    /// A voluntary negative declaration.
+    NegativeDeclaration,
+    /// A signed identity document.
+    IdentityDocument,
+    /// The old key's authorization of a key rotation.
+    KeyRotation,
+    /// An exported session log.
+    SessionLog,
+    /// An identity's signed revocation list.
+    RevocationList,
+    /// The Merkle root of a receipt batch.
+    ReceiptBatch,
+    /// An identity's nomination of its recovery guardians.
+    GuardianSet,
+    /// A guardian's approval of a recovery request.
+    RecoveryApproval,
+    /// An identity's installed issuance policy.
+    Policy,
+}
+
+impl SignatureDomain {
+    /// The tag prepended to the signed message. Never change a released
+    /// tag; a new signing scheme gets a new version suffix.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::Receipt => "aid:receipt:v1",
+            Self::Witness => "aid:witness:v1",
+            Self::Cosignature => "aid:cosignature:v1",
+            Self::TrustGrant => "aid:trust-grant:v1",
+            Self::TrustAcknowledgment => "aid:trust-ack:v1",
+            Self::Revocation => "aid:revocation:v1",
+            Self::TimeToken => "aid:time-token:v1",
+            Self::Spawn => "aid:spawn:v1",
+            Self::SpawnAcknowledgment => "aid:spawn-ack:v1",
+            Self::LineageProof => "aid:lineage-proof:v1",
+            Self::Experience => "aid:experience:v1",
+            Self::ContinuityAnchor => "aid:continuity-anchor:v1",
+            Self::Heartbeat => "aid:heartbeat:v1",
+            Self::ContinuityClaim => "aid:continuity-claim:v1",
+            Self::ContinuityExport => "aid:continuity-export:v1",
+            Self::CompetenceAttempt => "aid:competence-attempt:v1",
+            Self::CompetenceValidation => "aid:competence-validation:v1",
+            Self::CompetenceProof => "aid:competence-proof:v1",
+            Self::CompetenceAggregate => "aid:competence-aggregate:v1",
+            Self::NegativeProof => "aid:negative-proof:v1",
+            Self::NegativeDeclaration => "aid:negative-declaration:v1",
+            Self::IdentityDocument => "aid:identity-document:v1",
+            Self::KeyRotation => "aid:key-rotation:v1",
+            Self::SessionLog => "aid:session-log:v1",
+            Self::RevocationList => "aid:revocation-list:v1",
+            Self::ReceiptBatch => "aid:receipt-batch:v1",
+            Self::GuardianSet => "aid:guardian-set:v1",
+            Self::RecoveryApproval => "aid:recovery-approval:v1",
+            Self::Policy => "aid:policy:v1",
+        }
+    }
+
+    /// The bytes signed for `message` in this domain: the tag, a `:`, then
+    /// the message.
+    pub fn separate(self, message: &[u8]) -> Vec<u8> {
+        let tag = self.tag().as_bytes();
+        let mut bytes = Vec::with_capacity(tag.len() + 1 + message.len());
+        bytes.extend_from_slice(tag);
+        bytes.push(b':');
+        bytes.extend_from_slice(message);
+        bytes
+    }
+}
+
+/// `serde` helper: true for artifacts signed before domain separation,
+/// whose JSON has no `signature_version`.
+pub fn is_legacy_signature(version: &u32) -> bool {
+    *version == LEGACY_SIGNATURE_VERSION
+}
+
 /// Sign a message with an Ed25519 signing key.
 ///
 /// Returns the signature as 64 bytes.
@@ -45,7 +194,44 @@ pub fn verify_from_base64(
     verify(verifying_key, message, &signature)
 }
 
-#[cfg(test)]
+/// Sign `message` in `domain` and return the base64 signature.
+pub fn sign_in_domain(signing_key: &SigningKey, domain: SignatureDomain, message: &[u8]) -> String {
+    sign_to_base64(signing_key, &domain.separate(message))
+}
+
+/// Verify a base64 signature made by [`sign_in_domain`] in `domain`.
+pub fn verify_in_domain(
+    verifying_key: &VerifyingKey,
+    domain: SignatureDomain,
+    message: &[u8],
+    signature_b64: &str,
+) -> Result<()> {
+    verify_from_base64(verifying_key, &domain.separate(message), signature_b64)
+}
+
+/// Verify a base64 signature made under `version`.
+///
+/// A [`LEGACY_SIGNATURE_VERSION`] signature covers the bare message; a
+/// [`SIGNATURE_VERSION`] or [`CANONICAL_SIGNATURE_VERSION`] one covers the
+/// message in `domain`. Any other version fails with
+/// `IdentityError::SignatureInvalid`.
+pub fn verify_versioned(
+    verifying_key: &VerifyingKey,
+    domain: SignatureDomain,
+    version: u32,
+    message: &[u8],
+    signature_b64: &str,
+) -> Result<()> {
+    match version {
+        LEGACY_SIGNATURE_VERSION => verify_from_base64(verifying_key, message, signature_b64),
+        SIGNATURE_VERSION | CANONICAL_SIGNATURE_VERSION => {
+            verify_in_domain(verifying_key, domain, message, signature_b64)
+        }
+        _ => Err(IdentityError::SignatureInvalid),
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::crypto::keys::Ed25519KeyPair;
@@ -91,6 +277,125 @@ mod tests {
         assert!(verify_from_base64(kp.verifying_key(), message, "not-valid-base64!!!").is_err());
     }
 
+    #[test]
+    fn test_domain_signature_does_not_cross_domains() {
+        let kp = Ed25519KeyPair::generate();
+        let message = b"3f2a9c";
+        let sig = sign_in_domain(kp.signing_key(), SignatureDomain::Receipt, message);
+        assert!(
+            verify_in_domain(kp.verifying_key(), SignatureDomain::Receipt, message, &sig).is_ok()
+        );
+        assert!(matches!(
+            verify_in_domain(
+                kp.verifying_key(),
+                SignatureDomain::TrustGrant,
+                message,
+                &sig
+            ),
+            Err(IdentityError::SignatureInvalid)
+        ));
+        // Nor does it pass as a legacy signature over the bare message.
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            LEGACY_SIGNATURE_VERSION,
+            message,
+            &sig
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_versioned_legacy_fallback() {
+        let kp = Ed25519KeyPair::generate();
+        let message = b"3f2a9c";
+        let legacy = sign_to_base64(kp.signing_key(), message);
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            LEGACY_SIGNATURE_VERSION,
+            message,
+            &legacy
+        )
+        .is_ok());
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            SIGNATURE_VERSION,
+            message,
+            &legacy
+        )
+        .is_err());
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            99,
+            message,
+            &legacy
+        )
+        .is_err());
+
+        // The canonical version verifies domain signatures, not bare ones.
+        let domain = sign_in_domain(kp.signing_key(), SignatureDomain::Receipt, message);
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            CANONICAL_SIGNATURE_VERSION,
+            message,
+            &domain
+        )
+        .is_ok());
+        assert!(verify_versioned(
+            kp.verifying_key(),
+            SignatureDomain::Receipt,
+            CANONICAL_SIGNATURE_VERSION,
+            message,
+            &legacy
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_domain_tags_are_distinct() {
+        use SignatureDomain::*;
+        let all = [
+            Receipt,
+            Witness,
+            Cosignature,
+            TrustGrant,
+            TrustAcknowledgment,
+            Revocation,
+            TimeToken,
+            Spawn,
+            SpawnAcknowledgment,
+            LineageProof,
+            Experience,
+            ContinuityAnchor,
+            Heartbeat,
+            ContinuityClaim,
+            ContinuityExport,
+            CompetenceAttempt,
+            CompetenceValidation,
+            CompetenceProof,
+            CompetenceAggregate,
+            NegativeProof,
+            NegativeDeclaration,
+            IdentityDocument,
+            KeyRotation,
+            SessionLog,
+            RevocationList,
+            ReceiptBatch,
+            GuardianSet,
+            RecoveryApproval,
+            Policy,
+        ];
+        let tags: std::collections::HashSet<_> = all.iter().map(|d| d.tag()).collect();
+        assert_eq!(tags.len(), all.len());
+        assert!(all
+            .iter()
+            .all(|d| d.tag().starts_with("aid:") && d.tag().ends_with(":v1")));
+    }
+
     #[test]
     fn test_deterministic_signature() {
         // Ed25519 signatures are deterministic for the same key + message
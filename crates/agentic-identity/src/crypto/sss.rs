@@ -0,0 +1,293 @@
+//! Shamir secret sharing over GF(2^8).
+//!
+//! [`split`] turns a secret into `count` shares of which any `threshold`
+//! reconstruct it with [`combine`]; fewer reveal nothing about it. Each byte
+//! of the secret is the constant term of its own random polynomial of degree
+//! `threshold - 1`, and share `x` holds every polynomial evaluated at `x`.
+//!
+//! Shares from one split carry the same random set ID, so shares of two
+//! different splits are rejected instead of combining into a wrong secret.
+//! A share's text form is `aidss1-{set}-{threshold}-{index}-{value}`, with
+//! the set ID and value in hex.
+//!
+//! Field arithmetic avoids lookup tables and data-dependent branches, so
+//! timing does not depend on the secret.
+
+use std::fmt;
+use std::str::FromStr;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{IdentityError, Result};
+
+/// Prefix of a share's text form.
+const SHARE_PREFIX: &str = "aidss1";
+
+/// One share of a split secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    /// Random ID shared by every share of one split.
+    pub set: [u8; 4],
+    /// Shares needed to reconstruct the secret.
+    pub threshold: u8,
+    /// The x coordinate of this share (1..=255).
+    pub index: u8,
+    /// One polynomial evaluation per secret byte.
+    pub value: Vec<u8>,
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("set", &hex::encode(self.set))
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{SHARE_PREFIX}-{}-{}-{}-{}",
+            hex::encode(self.set),
+            self.threshold,
+            self.index,
+            hex::encode(&self.value)
+        )
+    }
+}
+
+impl FromStr for Share {
+    type Err = IdentityError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let malformed = || IdentityError::InvalidInput("malformed secret share".to_string());
+        let mut parts = s.trim().split('-');
+        if parts.next() != Some(SHARE_PREFIX) {
+            return Err(malformed());
+        }
+        let set = parts
+            .next()
+            .and_then(|p| hex::decode(p).ok())
+            .and_then(|bytes| <[u8; 4]>::try_from(bytes).ok())
+            .ok_or_else(malformed)?;
+        let threshold = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(malformed)?;
+        let index = parts
+            .next()
+            .and_then(|p| p.parse().ok())
+            .ok_or_else(malformed)?;
+        let value = parts
+            .next()
+            .and_then(|p| hex::decode(p).ok())
+            .ok_or_else(malformed)?;
+        if parts.next().is_some() || threshold < 2 || index == 0 || value.is_empty() {
+            return Err(malformed());
+        }
+        Ok(Self {
+            set,
+            threshold,
+            index,
+            value,
+        })
+    }
+}
+
+/// Split `secret` into `count` shares, any `threshold` of which reconstruct it.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidInput` if `secret` is empty or unless
+/// `2 <= threshold <= count`.
+#[cfg(feature = "signing")]
+pub fn split(secret: &[u8], threshold: u8, count: u8) -> Result<Vec<Share>> {
+    if secret.is_empty() {
+        return Err(IdentityError::InvalidInput(
+            "cannot split an empty secret".to_string(),
+        ));
+    }
+    if threshold < 2 || threshold > count {
+        return Err(IdentityError::InvalidInput(format!(
+            "threshold must be between 2 and the share count ({count}), got {threshold}"
+        )));
+    }
+
+    let set = crate::crypto::random::random_bytes::<4>();
+    let mut shares: Vec<Share> = (1..=count)
+        .map(|index| Share {
+            set,
+            threshold,
+            index,
+            value: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    // coefficients[0] is the secret byte; the rest are random.
+    let mut coefficients = vec![0u8; usize::from(threshold)];
+    for &byte in secret {
+        coefficients[0] = byte;
+        crate::crypto::random::fill_random(&mut coefficients[1..]);
+        for share in &mut shares {
+            share.value.push(evaluate(&coefficients, share.index));
+        }
+    }
+    coefficients.zeroize();
+    Ok(shares)
+}
+
+/// Reconstruct the secret from `shares`. Only the first `threshold` shares
+/// are used; any beyond that must still belong to the same split. The
+/// secret is wiped from memory when the returned buffer is dropped.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidInput` if there are fewer shares than the
+/// threshold, or the shares come from different splits, repeat an index or
+/// differ in length.
+pub fn combine(shares: &[Share]) -> Result<Zeroizing<Vec<u8>>> {
+    let Some(first) = shares.first() else {
+        return Err(IdentityError::InvalidInput(
+            "no secret shares given".to_string(),
+        ));
+    };
+    for (n, share) in shares.iter().enumerate() {
+        if share.set != first.set
+            || share.threshold != first.threshold
+            || share.value.len() != first.value.len()
+        {
+            return Err(IdentityError::InvalidInput(
+                "secret shares come from different splits".to_string(),
+            ));
+        }
+        if share.index == 0 || shares[..n].iter().any(|s| s.index == share.index) {
+            return Err(IdentityError::InvalidInput(format!(
+                "secret share index {} is invalid or repeated",
+                share.index
+            )));
+        }
+    }
+    let threshold = usize::from(first.threshold);
+    if shares.len() < threshold {
+        return Err(IdentityError::InvalidInput(format!(
+            "{threshold} secret shares are needed, got {}",
+            shares.len()
+        )));
+    }
+    let used = &shares[..threshold];
+
+    // Lagrange basis polynomials evaluated at x = 0.
+    let mut basis: Vec<u8> = used
+        .iter()
+        .map(|share| {
+            used.iter()
+                .filter(|other| other.index != share.index)
+                .fold(1, |acc, other| {
+                    mul(acc, div(other.index, other.index ^ share.index))
+                })
+        })
+        .collect();
+
+    let secret = (0..first.value.len())
+        .map(|i| {
+            used.iter()
+                .zip(&basis)
+                .fold(0, |acc, (share, &l)| acc ^ mul(share.value[i], l))
+        })
+        .collect();
+    basis.zeroize();
+    Ok(Zeroizing::new(secret))
+}
+
+/// Evaluate the polynomial with `coefficients` (lowest degree first) at `x`.
+#[cfg(feature = "signing")]
+fn evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0, |acc, &coefficient| mul(acc, x) ^ coefficient)
+}
+
+/// Multiply in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, in constant time.
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Divide in GF(2^8); `b` must be non-zero. The inverse of `b` is
+/// `b^254 = b^2 · b^4 · … · b^128`.
+fn div(a: u8, b: u8) -> u8 {
+    let mut inverse = 1u8;
+    let mut power = b;
+    for _ in 1..8 {
+        power = mul(power, power);
+        inverse = mul(inverse, power);
+    }
+    mul(a, inverse)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_inverse() {
+        for b in 1..=255u8 {
+            assert_eq!(mul(b, div(1, b)), 1, "inverse of {b}");
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine() {
+        let secret = b"correct horse battery staple 32b";
+        let shares = split(secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        // Any three shares, in any order, reconstruct the secret.
+        let pick = |indices: &[usize]| -> Vec<Share> {
+            indices.iter().map(|&i| shares[i].clone()).collect()
+        };
+        assert_eq!(*combine(&pick(&[0, 1, 2])).unwrap(), secret);
+        assert_eq!(*combine(&pick(&[4, 2, 0])).unwrap(), secret);
+        assert_eq!(*combine(&pick(&[1, 3, 4, 0])).unwrap(), secret);
+
+        // Two are not enough.
+        assert!(matches!(
+            combine(&pick(&[0, 1])),
+            Err(IdentityError::InvalidInput(_))
+        ));
+        // Text form round-trips.
+        let parsed: Vec<Share> = shares
+            .iter()
+            .map(|s| s.to_string().parse().unwrap())
+            .collect();
+        assert_eq!(*combine(&parsed[2..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_combine_rejects_bad_shares() {
+        let a = split(b"secret", 2, 3).unwrap();
+        let b = split(b"secret", 2, 3).unwrap();
+        assert!(combine(&[a[0].clone(), b[1].clone()]).is_err());
+        assert!(combine(&[a[0].clone(), a[0].clone()]).is_err());
+        assert!(split(b"secret", 1, 3).is_err());
+        assert!(split(b"secret", 4, 3).is_err());
+        assert!("aidss1-00-2-1-ab".parse::<Share>().is_err());
+        assert!("sss-00000000-2-1-ab".parse::<Share>().is_err());
+    }
+}
@@ -15,6 +15,9 @@ pub enum IdentityError {
     #[error("Identity not found: {0}")]
     NotFound(String),
 
+    #[error("Invalid ID: {0}")]
+    InvalidId(String),
+
     #[error("Key derivation failed: {0}")]
     DerivationFailed(String),
 
@@ -48,15 +51,39 @@ pub enum IdentityError {
     #[error("Delegation depth exceeded")]
     DelegationDepthExceeded,
 
+    #[error("Spawn budget exhausted: {resource} limit is {limit}")]
+    BudgetExhausted { resource: String, limit: u64 },
+
     #[error("Invalid receipt chain")]
     InvalidChain,
 
+    #[error("Continuity conflict: {0}")]
+    ContinuityConflict(String),
+
+    #[error("Authority escalation: {0}")]
+    AuthorityEscalation(String),
+
+    #[error("Issuer not trusted: {0}")]
+    IssuerNotTrusted(String),
+
+    #[error("Untrusted time source: {0}")]
+    UntrustedTime(String),
+
     #[error("Storage error: {0}")]
     StorageError(String),
 
+    #[error("Store locked: {0}")]
+    StoreLocked(String),
+
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Schema violation: {0}")]
+    SchemaViolation(String),
+
     #[error("Invalid file format: {0}")]
     InvalidFileFormat(String),
 
@@ -84,6 +111,16 @@ pub enum IdentityError {
 
     #[error("Permanent declaration cannot be revoked")]
     PermanentDeclaration,
+
+    #[error("Policy violation: {0}")]
+    PolicyViolation(Box<crate::contracts::policy::PolicyViolation>),
+
+    #[error("Unsupported {kind} schema version {found} (this version reads up to {supported})")]
+    UnsupportedVersion {
+        kind: String,
+        found: u32,
+        supported: u32,
+    },
 }
 
 /// Convenience Result alias.
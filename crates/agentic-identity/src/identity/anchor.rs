@@ -7,12 +7,16 @@
 use ed25519_dalek::{SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::crypto::derivation;
 use crate::crypto::keys::Ed25519KeyPair;
+use crate::crypto::signer::Signer;
+use crate::crypto::signing::SignatureDomain;
 use crate::error::{IdentityError, Result};
 
+use super::recovery::RecoveryProof;
+
 /// Unique identifier for an identity.
 ///
 /// Format: `aid_` + base58 of first 16 bytes of SHA-256(public_key).
@@ -20,6 +24,12 @@ use crate::error::{IdentityError, Result};
 pub struct IdentityId(pub String);
 
 impl IdentityId {
+    /// Parse an identity ID from untrusted input, checking its prefix and charset.
+    pub fn parse(s: &str) -> Result<Self> {
+        validate_prefixed_id(s, "aid_")?;
+        Ok(Self(s.to_string()))
+    }
+
     /// Compute an identity ID from a verifying (public) key.
     pub fn from_verifying_key(key: &VerifyingKey) -> Self {
         let hash = Sha256::digest(key.as_bytes());
@@ -27,6 +37,13 @@ impl IdentityId {
         let encoded = bs58::encode(truncated).into_string();
         Self(format!("aid_{encoded}"))
     }
+
+    /// Compute an identity ID from a base64-encoded public key.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] if the key does not decode.
+    pub fn from_public_key_base64(key_b64: &str) -> Result<Self> {
+        Ok(Self::from_verifying_key(&decode_public_key(key_b64)?))
+    }
 }
 
 impl std::fmt::Display for IdentityId {
@@ -35,6 +52,112 @@ impl std::fmt::Display for IdentityId {
     }
 }
 
+/// Signature algorithm of an identity's current key.
+///
+/// Documents carry it as the `algorithm` string. A document without one is
+/// `Ed25519`, the only algorithm identities have used so far.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureAlgorithm {
+    #[default]
+    Ed25519,
+}
+
+impl SignatureAlgorithm {
+    /// The name used in identity documents.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ed25519 => "ed25519",
+        }
+    }
+
+    /// Parse a document's `algorithm` string. Empty means `Ed25519`.
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "" | "ed25519" => Ok(Self::Ed25519),
+            other => Err(IdentityError::InvalidKey(format!(
+                "unsupported signature algorithm '{other}'"
+            ))),
+        }
+    }
+
+    /// Public key length in bytes.
+    pub fn public_key_len(&self) -> usize {
+        match self {
+            Self::Ed25519 => 32,
+        }
+    }
+
+    /// Name and key length for display, e.g. `Ed25519 (32-byte key)`.
+    pub fn summary(&self) -> String {
+        format!("{self} ({}-byte key)", self.public_key_len())
+    }
+}
+
+impl std::fmt::Display for SignatureAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ed25519 => write!(f, "Ed25519"),
+        }
+    }
+}
+
+fn default_algorithm() -> String {
+    SignatureAlgorithm::default().as_str().to_string()
+}
+
+/// ID prefixes and the artifact each one names, used for error messages.
+const ID_KINDS: &[(&str, &str)] = &[
+    ("aid_", "identity"),
+    ("arec_", "receipt"),
+    ("atrust_", "trust grant"),
+    ("aspawn_", "spawn"),
+    ("aneg_", "negative proof"),
+];
+
+/// Check that `s` is `prefix` followed by a non-empty base58 body.
+///
+/// Shared by the `parse` constructors of the ID newtypes. Does not allocate
+/// unless the ID is rejected.
+pub(crate) fn validate_prefixed_id(s: &str, prefix: &str) -> Result<()> {
+    let kind = |p: &str| {
+        ID_KINDS
+            .iter()
+            .find(|(k, _)| *k == p)
+            .map(|(_, kind)| *kind)
+            .unwrap_or("unknown")
+    };
+
+    let Some(body) = s.strip_prefix(prefix) else {
+        let expected = kind(prefix);
+        return Err(match ID_KINDS.iter().find(|(p, _)| s.starts_with(p)) {
+            Some((other, other_kind)) => IdentityError::InvalidId(format!(
+                "expected a {expected} ID ({prefix}...), got a {other_kind} ID ({other}...): '{s}'"
+            )),
+            None => IdentityError::InvalidId(format!(
+                "expected a {expected} ID starting with '{prefix}': '{s}'"
+            )),
+        });
+    };
+
+    if body.is_empty() {
+        return Err(IdentityError::InvalidId(format!(
+            "{} ID '{s}' has no content after the prefix",
+            kind(prefix)
+        )));
+    }
+    // Bitcoin base58 alphabet: alphanumerics without 0, O, I and l.
+    if let Some(bad) = body
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() || matches!(c, '0' | 'O' | 'I' | 'l'))
+    {
+        return Err(IdentityError::InvalidId(format!(
+            "{} ID '{s}' contains invalid character '{bad}'",
+            kind(prefix)
+        )));
+    }
+    Ok(())
+}
+
 /// The root identity anchor containing key material.
 ///
 /// The signing key is zeroized on drop to prevent leakage.
@@ -51,6 +174,7 @@ pub struct IdentityAnchor {
 
 impl IdentityAnchor {
     /// Create a new identity anchor with a fresh key pair.
+    #[cfg(feature = "signing")]
     pub fn new(name: Option<String>) -> Self {
         let now = crate::time::now_micros();
         Self {
@@ -61,6 +185,19 @@ impl IdentityAnchor {
         }
     }
 
+    /// Create a new identity anchor, rejecting names that contain control
+    /// characters (including NUL).
+    ///
+    /// Use this for names that come from outside the program; see
+    /// [`validate_text`](crate::text::validate_text).
+    #[cfg(feature = "signing")]
+    pub fn try_new(name: Option<String>) -> Result<Self> {
+        if let Some(name) = &name {
+            crate::text::validate_text("identity name", name)?;
+        }
+        Ok(Self::new(name))
+    }
+
     /// Reconstruct from existing key bytes and metadata.
     pub fn from_parts(
         signing_key_bytes: &[u8; 32],
@@ -77,6 +214,89 @@ impl IdentityAnchor {
         })
     }
 
+    /// Load an identity from key material, such as the value of an
+    /// environment variable a deployment injects.
+    ///
+    /// The material is either the base64 signing key on its own, or a JSON
+    /// object `{"signing_key": "<base64>", "name": "...", "created_at": 0}`
+    /// in which `name` and `created_at` (microseconds) are optional;
+    /// `created_at` defaults to now. Surrounding whitespace is ignored. The
+    /// ID derives from the key bytes alone, so it matches that of a
+    /// file-loaded identity with the same key.
+    ///
+    /// Every copy of the key made here is zeroized; `material` itself is
+    /// the caller's to wipe. Reading the material from the environment, and
+    /// removing it there, is left to the caller: a binary should do both once
+    /// at startup, before it spawns threads.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] if the material is malformed
+    /// or the key is not 32 bytes.
+    pub fn from_key_material(material: &str) -> Result<Self> {
+        let material = material.trim();
+        let mut material: EnvKeyMaterial = if material.starts_with('{') {
+            serde_json::from_str(material).map_err(|e| IdentityError::InvalidKey(e.to_string()))?
+        } else {
+            EnvKeyMaterial {
+                signing_key: material.to_string(),
+                name: None,
+                created_at: None,
+            }
+        };
+        let decoded = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            material.signing_key.trim(),
+        );
+        material.signing_key.zeroize();
+
+        let mut bytes =
+            decoded.map_err(|e| IdentityError::InvalidKey(format!("key is not base64: {e}")))?;
+        let key: std::result::Result<[u8; 32], _> = bytes.as_slice().try_into();
+        let len = bytes.len();
+        bytes.zeroize();
+        let mut key = key.map_err(|_| {
+            IdentityError::InvalidKey(format!(
+                "expected a 32-byte Ed25519 signing key, got {len} bytes"
+            ))
+        })?;
+
+        if let Some(name) = &material.name {
+            crate::text::validate_text("identity name", name)?;
+        }
+        let anchor = Self::from_parts(
+            &key,
+            material.created_at.unwrap_or_else(crate::time::now_micros),
+            material.name,
+            Vec::new(),
+        );
+        key.zeroize();
+        anchor
+    }
+
+    /// Load an identity from the key material in the environment variable
+    /// `var`; see [`IdentityAnchor::from_key_material`].
+    ///
+    /// The variable is only read. Removing it is the caller's job, done
+    /// while the process is still single-threaded.
+    ///
+    /// Fails with [`IdentityError::NotFound`] if `var` is unset, and with
+    /// [`IdentityError::InvalidKey`] if it is not UTF-8 or its material is
+    /// rejected.
+    pub fn from_key_env(var: &str) -> Result<Self> {
+        let value = std::env::var(var).map_err(|e| match e {
+            std::env::VarError::NotPresent => {
+                IdentityError::NotFound(format!("environment variable {var} is not set"))
+            }
+            std::env::VarError::NotUnicode(_) => {
+                IdentityError::InvalidKey(format!("{var} is not valid UTF-8"))
+            }
+        })?;
+        let value = Zeroizing::new(value);
+        Self::from_key_material(&value).map_err(|e| match e {
+            IdentityError::InvalidKey(msg) => IdentityError::InvalidKey(format!("{var}: {msg}")),
+            other => other,
+        })
+    }
+
     /// Return the identity ID (derived from public key).
     pub fn id(&self) -> IdentityId {
         IdentityId::from_verifying_key(self.key_pair.verifying_key())
@@ -87,6 +307,11 @@ impl IdentityAnchor {
         self.key_pair.signing_key()
     }
 
+    /// Return the signature algorithm of the current key.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::Ed25519
+    }
+
     /// Return the verifying (public) key.
     pub fn verifying_key(&self) -> &VerifyingKey {
         self.key_pair.verifying_key()
@@ -142,6 +367,7 @@ impl IdentityAnchor {
 
     /// Rotate the root key. Returns the new anchor with the old key
     /// recorded in rotation history.
+    #[cfg(feature = "signing")]
     pub fn rotate(&self, reason: RotationReason) -> Result<Self> {
         let old_pub_b64 = self.public_key_base64();
         let new_kp = Ed25519KeyPair::generate();
@@ -156,8 +382,11 @@ impl IdentityAnchor {
             "rotate:{old_pub_b64}:{new_pub_b64}:{now}:{}",
             reason.as_str()
         );
-        let auth_sig =
-            crate::crypto::signing::sign_to_base64(self.signing_key(), auth_message.as_bytes());
+        let auth_sig = crate::crypto::signing::sign_in_domain(
+            self.signing_key(),
+            SignatureDomain::KeyRotation,
+            auth_message.as_bytes(),
+        );
 
         let rotation = KeyRotation {
             previous_key: old_pub_b64,
@@ -165,6 +394,8 @@ impl IdentityAnchor {
             rotated_at: now,
             reason: reason.clone(),
             authorization_signature: auth_sig,
+            signature_version: crate::crypto::signing::SIGNATURE_VERSION,
+            recovery: None,
         };
 
         let mut history = self.rotation_history.clone();
@@ -191,6 +422,8 @@ impl IdentityAnchor {
                 rotated_at: r.rotated_at,
                 reason: r.reason.clone(),
                 authorization_signature: r.authorization_signature.clone(),
+                signature_version: r.signature_version,
+                recovery: r.recovery.clone(),
             })
             .collect();
 
@@ -198,23 +431,40 @@ impl IdentityAnchor {
         let mut doc = IdentityDocument {
             id,
             public_key: pub_key_b64,
-            algorithm: "ed25519".to_string(),
+            algorithm: self.algorithm().as_str().to_string(),
             created_at: self.created_at,
             name: self.name.clone(),
             rotation_history: public_rotations,
             attestations: Vec::new(),
             signature: String::new(),
+            signature_version: crate::crypto::signing::SIGNATURE_VERSION,
+            schema_version: crate::storage::migrate::SCHEMA_VERSION,
         };
 
         // Self-sign the document
         let to_sign = serde_json::to_string(&DocumentSignPayload::from(&doc)).unwrap_or_default();
-        doc.signature =
-            crate::crypto::signing::sign_to_base64(self.signing_key(), to_sign.as_bytes());
+        doc.signature = crate::crypto::signing::sign_in_domain(
+            self.signing_key(),
+            SignatureDomain::IdentityDocument,
+            to_sign.as_bytes(),
+        );
 
         doc
     }
 }
 
+/// An anchor signs with its in-memory root key. Code that accepts a
+/// [`Signer`] therefore takes an anchor or a hardware-backed key alike.
+impl Signer for IdentityAnchor {
+    fn verifying_key(&self) -> VerifyingKey {
+        *self.key_pair.verifying_key()
+    }
+
+    fn sign_message(&self, message: &[u8]) -> Result<ed25519_dalek::Signature> {
+        self.signing_key().sign_message(message)
+    }
+}
+
 /// Payload used for document self-signature (excludes the signature field).
 #[derive(Serialize)]
 struct DocumentSignPayload {
@@ -242,41 +492,168 @@ impl From<&IdentityDocument> for DocumentSignPayload {
 pub struct IdentityDocument {
     pub id: IdentityId,
     pub public_key: String,
+    #[serde(default = "default_algorithm")]
     pub algorithm: String,
     pub created_at: u64,
     pub name: Option<String>,
     pub rotation_history: Vec<PublicKeyRotation>,
     pub attestations: Vec<Attestation>,
     pub signature: String,
+    /// Absent on documents signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    /// Layout version; see [`migrate`](crate::storage::migrate). Not signed.
+    #[serde(default)]
+    pub schema_version: u32,
 }
 
 impl IdentityDocument {
-    /// Verify the self-signature on this document.
-    pub fn verify_signature(&self) -> Result<()> {
-        let pub_bytes =
-            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &self.public_key)
-                .map_err(|e| {
-                    IdentityError::InvalidKey(format!("invalid base64 public key: {e}"))
-                })?;
+    /// The signature algorithm of the document's key.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] for an algorithm this
+    /// version cannot verify.
+    pub fn signature_algorithm(&self) -> Result<SignatureAlgorithm> {
+        SignatureAlgorithm::parse(&self.algorithm)
+    }
+
+    /// [`SignatureAlgorithm::summary`], or the raw name if unsupported.
+    pub fn algorithm_summary(&self) -> String {
+        match self.signature_algorithm() {
+            Ok(alg) => alg.summary(),
+            Err(_) => format!("{} (unsupported)", self.algorithm),
+        }
+    }
+
+    /// A short digest of the current public key for comparing keys by eye:
+    /// the first 16 bytes of its SHA-256 (the digest [`IdentityId`] encodes
+    /// in base58), as colon-separated hex.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] if the key does not decode.
+    pub fn key_fingerprint(&self) -> Result<String> {
+        let key = decode_public_key(&self.public_key)?;
+        let digest = Sha256::digest(key.as_bytes());
+        Ok(digest[..16]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(":"))
+    }
 
-        let key_bytes: [u8; 32] = pub_bytes
-            .try_into()
-            .map_err(|_| IdentityError::InvalidKey("public key must be 32 bytes".into()))?;
+    /// The current public key, decoded for verifying signatures made with it.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] if the key does not decode.
+    pub fn verifying_key(&self) -> Result<VerifyingKey> {
+        decode_public_key(&self.public_key)
+    }
 
-        let verifying_key = Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)?;
+    /// Verify the self-signature on this document.
+    ///
+    /// Fails without checking the signature if the algorithm is not one
+    /// this version supports.
+    pub fn verify_signature(&self) -> Result<()> {
+        self.signature_algorithm()?;
+        let verifying_key = decode_public_key(&self.public_key)?;
 
         let payload = DocumentSignPayload::from(self);
         let to_verify = serde_json::to_string(&payload)
             .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
 
-        crate::crypto::signing::verify_from_base64(
+        crate::crypto::signing::verify_versioned(
             &verifying_key,
+            SignatureDomain::IdentityDocument,
+            self.signature_version,
             to_verify.as_bytes(),
             &self.signature,
         )
     }
 }
 
+pub(crate) fn decode_public_key(key_b64: &str) -> Result<VerifyingKey> {
+    let pub_bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, key_b64)
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid base64 public key: {e}")))?;
+
+    let key_bytes: [u8; 32] = pub_bytes
+        .try_into()
+        .map_err(|_| IdentityError::InvalidKey("public key must be 32 bytes".into()))?;
+
+    Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)
+}
+
+/// Check that a document's identity is self-certifying: that its ID was
+/// derived from its genesis key, with no parent vouching for it.
+///
+/// The genesis key is the first rotation's `previous_key`, or the document's
+/// key if it never rotated. Every rotation must be authorized by the key
+/// before it, or for a recovery by that key's guardians, ending at the
+/// document's current key, so a forger cannot claim someone else's ID by
+/// listing their key as a past one.
+/// [`IdentityAnchor::rotate`] derives a fresh ID from the new key, so the ID
+/// of a rotated document may also match its current key; any other ID fails.
+///
+/// This costs one hash per candidate key plus one signature check per
+/// rotation, cheap enough to run on every lineage verification. It does not
+/// check the document's own signature; see
+/// [`IdentityDocument::verify_signature`].
+pub fn verify_genesis(doc: &IdentityDocument) -> bool {
+    let genesis_key = doc
+        .rotation_history
+        .first()
+        .map_or(doc.public_key.as_str(), |r| r.previous_key.as_str());
+
+    let mut current = genesis_key;
+    for rotation in &doc.rotation_history {
+        if rotation.previous_key != current {
+            return false;
+        }
+        if let Some(recovery) = &rotation.recovery {
+            if recovery
+                .authorizes(
+                    &rotation.previous_key,
+                    &rotation.new_key,
+                    rotation.rotated_at,
+                    &rotation.reason,
+                )
+                .is_err()
+            {
+                return false;
+            }
+            current = &rotation.new_key;
+            continue;
+        }
+        let message = format!(
+            "rotate:{}:{}:{}:{}",
+            rotation.previous_key,
+            rotation.new_key,
+            rotation.rotated_at,
+            rotation.reason.as_str()
+        );
+        let authorized = decode_public_key(current).and_then(|key| {
+            crate::crypto::signing::verify_versioned(
+                &key,
+                SignatureDomain::KeyRotation,
+                rotation.signature_version,
+                message.as_bytes(),
+                &rotation.authorization_signature,
+            )
+        });
+        if authorized.is_err() {
+            return false;
+        }
+        current = &rotation.new_key;
+    }
+    if current != doc.public_key {
+        return false;
+    }
+
+    let derives_id = |key_b64: &str| {
+        decode_public_key(key_b64).is_ok_and(|key| IdentityId::from_verifying_key(&key) == doc.id)
+    };
+    derives_id(genesis_key) || derives_id(&doc.public_key)
+}
+
 /// Record of a key rotation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyRotation {
@@ -285,6 +662,16 @@ pub struct KeyRotation {
     pub rotated_at: u64,
     pub reason: RotationReason,
     pub authorization_signature: String,
+    /// Absent on rotations authorized before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    /// Guardian approval standing in for `authorization_signature` when
+    /// the old key was lost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryProof>,
 }
 
 impl Zeroize for KeyRotation {
@@ -293,6 +680,16 @@ impl Zeroize for KeyRotation {
     }
 }
 
+/// The JSON form read by [`IdentityAnchor::from_key_material`].
+#[derive(Deserialize)]
+struct EnvKeyMaterial {
+    signing_key: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    created_at: Option<u64>,
+}
+
 /// Public view of key rotation (no private data).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicKeyRotation {
@@ -301,6 +698,13 @@ pub struct PublicKeyRotation {
     pub rotated_at: u64,
     pub reason: RotationReason,
     pub authorization_signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryProof>,
 }
 
 /// Reason for key rotation.
@@ -352,7 +756,7 @@ pub enum AttestationClaim {
     },
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
 
@@ -365,6 +769,53 @@ mod tests {
         assert_eq!(anchor.name.as_deref(), Some("test-agent"));
     }
 
+    #[test]
+    fn test_identity_try_new_rejects_control_characters() {
+        let err = IdentityAnchor::try_new(Some("agent\0x".to_string()));
+        assert!(matches!(err, Err(IdentityError::InvalidInput(_))));
+        assert!(IdentityAnchor::try_new(Some("tab\there".to_string())).is_err());
+
+        let anchor = IdentityAnchor::try_new(Some("agent-ü-東京".to_string())).unwrap();
+        assert_eq!(anchor.name.as_deref(), Some("agent-ü-東京"));
+        assert!(IdentityAnchor::try_new(None).is_ok());
+    }
+
+    #[test]
+    fn test_from_key_material_matches_key_bytes() {
+        let original = IdentityAnchor::new(Some("ci-agent".to_string()));
+        let key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            original.signing_key_bytes(),
+        );
+
+        let loaded = IdentityAnchor::from_key_material(&format!("{key}\n")).unwrap();
+        assert_eq!(loaded.id(), original.id());
+
+        let json = serde_json::json!({
+            "signing_key": key,
+            "name": "ci-agent",
+            "created_at": original.created_at,
+        });
+        let loaded = IdentityAnchor::from_key_material(&json.to_string()).unwrap();
+        let (a, b) = (loaded.to_document(), original.to_document());
+        assert_eq!(a.id, b.id);
+        assert_eq!(a.public_key, b.public_key);
+        assert_eq!(a.name, b.name);
+        assert_eq!(a.created_at, b.created_at);
+        assert!(a.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn test_from_key_material_rejects_bad_material() {
+        for value in ["", "AAAA", "not base64!", "{\"name\": \"x\"}"] {
+            let result = IdentityAnchor::from_key_material(value);
+            assert!(
+                matches!(result, Err(IdentityError::InvalidKey(_))),
+                "{value}"
+            );
+        }
+    }
+
     #[test]
     fn test_identity_id_from_key() {
         let anchor = IdentityAnchor::new(None);
@@ -380,6 +831,97 @@ mod tests {
         assert!(doc.verify_signature().is_ok());
     }
 
+    #[test]
+    fn test_identity_algorithm_reported_and_checked() {
+        let anchor = IdentityAnchor::new(None);
+        let rotated = anchor.rotate(RotationReason::Manual).unwrap();
+        assert_eq!(rotated.algorithm(), SignatureAlgorithm::Ed25519);
+        assert_eq!(rotated.algorithm().public_key_len(), 32);
+
+        // A document serialized without the field reads back as Ed25519.
+        let doc = anchor.to_document();
+        let mut json = serde_json::to_value(&doc).unwrap();
+        json.as_object_mut().unwrap().remove("algorithm");
+        let legacy: IdentityDocument = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            legacy.signature_algorithm().unwrap(),
+            SignatureAlgorithm::Ed25519
+        );
+        assert!(legacy.verify_signature().is_ok());
+
+        let mut unknown = doc;
+        unknown.algorithm = "secp256k1".to_string();
+        assert!(unknown.signature_algorithm().is_err());
+        assert!(unknown.verify_signature().is_err());
+    }
+
+    #[test]
+    fn test_key_fingerprint() {
+        let anchor = IdentityAnchor::new(None);
+        let fingerprint = anchor.to_document().key_fingerprint().unwrap();
+        assert_eq!(fingerprint.len(), 16 * 3 - 1);
+        assert_eq!(fingerprint, anchor.to_document().key_fingerprint().unwrap());
+        let rotated = anchor.rotate(RotationReason::Manual).unwrap();
+        assert_ne!(
+            fingerprint,
+            rotated.to_document().key_fingerprint().unwrap()
+        );
+        assert_eq!(
+            IdentityId::from_public_key_base64(&anchor.public_key_base64()).unwrap(),
+            anchor.id()
+        );
+        assert!(IdentityId::from_public_key_base64("not a key").is_err());
+    }
+
+    #[test]
+    fn test_verify_genesis() {
+        let anchor = IdentityAnchor::new(None);
+        assert!(verify_genesis(&anchor.to_document()));
+
+        let rotated = anchor
+            .rotate(RotationReason::Scheduled)
+            .unwrap()
+            .rotate(RotationReason::Manual)
+            .unwrap();
+        assert!(verify_genesis(&rotated.to_document()));
+
+        // An ID not derived from any key in the document.
+        let mut forged = anchor.to_document();
+        forged.id = IdentityAnchor::new(None).id();
+        assert!(!verify_genesis(&forged));
+    }
+
+    #[test]
+    fn test_verify_genesis_rejects_borrowed_genesis_key() {
+        // A forger claims the victim's ID by listing the victim's key as a
+        // genesis key it "rotated" away from, and self-signs the document.
+        let victim = IdentityAnchor::new(None);
+        let forger = IdentityAnchor::new(None);
+        let mut doc = forger.to_document();
+        doc.id = victim.id();
+        doc.rotation_history.push(PublicKeyRotation {
+            previous_key: victim.public_key_base64(),
+            new_key: forger.public_key_base64(),
+            rotated_at: crate::time::now_micros(),
+            reason: RotationReason::Manual,
+            authorization_signature: crate::crypto::signing::sign_to_base64(
+                forger.signing_key(),
+                b"rotate",
+            ),
+            signature_version: crate::crypto::signing::SIGNATURE_VERSION,
+            recovery: None,
+        });
+        let payload = serde_json::to_string(&DocumentSignPayload::from(&doc)).unwrap();
+        doc.signature = crate::crypto::signing::sign_in_domain(
+            forger.signing_key(),
+            SignatureDomain::IdentityDocument,
+            payload.as_bytes(),
+        );
+
+        assert!(doc.verify_signature().is_ok());
+        assert!(!verify_genesis(&doc));
+    }
+
     #[test]
     fn test_identity_derive_session_key() {
         let anchor = IdentityAnchor::new(None);
@@ -441,4 +983,27 @@ mod tests {
         let b = IdentityAnchor::new(None);
         assert_ne!(a.id(), b.id());
     }
+
+    #[test]
+    fn test_identity_id_parse() {
+        let anchor = IdentityAnchor::new(None);
+        let id = anchor.id();
+        assert_eq!(IdentityId::parse(&id.0).unwrap(), id);
+
+        assert!(matches!(
+            IdentityId::parse("aid_"),
+            Err(IdentityError::InvalidId(_))
+        ));
+        assert!(IdentityId::parse("aid_abc0").is_err());
+        assert!(IdentityId::parse("aid_abc-def").is_err());
+        assert!(IdentityId::parse("did:aid_abc").is_err());
+    }
+
+    #[test]
+    fn test_validate_prefixed_id_wrong_kind() {
+        let err = validate_prefixed_id("atrust_3yZe7d", "arec_").unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("expected a receipt ID"));
+        assert!(msg.contains("got a trust grant ID"));
+    }
 }
@@ -0,0 +1,282 @@
+//! W3C DID documents for identities.
+//!
+//! An identity can be presented to DID resolvers and wallets in two ways:
+//!
+//! - `did:key` — the DID is the current public key itself
+//!   (`did:key:z6Mk...`). It needs no hosting, but changes whenever the key
+//!   is rotated.
+//! - `did:web` — the DID names a location (`did:web:example.com:agents:alice`)
+//!   where the DID document is served as `did.json`. It stays the same across
+//!   rotations; the served document must be re-exported after each one.
+//!
+//! Both documents carry the current key as an `Ed25519VerificationKey2020`
+//! verification method, usable for authentication, assertions and
+//! capabilities, and list the AID identity ID (as `urn:aid:<id>`) in
+//! `alsoKnownAs`.
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::keys::Ed25519KeyPair;
+use crate::error::{IdentityError, Result};
+
+use super::anchor::IdentityDocument;
+
+/// JSON-LD context of DID core v1.
+pub const DID_V1_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+
+/// JSON-LD context of the Ed25519 2020 verification suite.
+pub const ED25519_2020_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2020/v1";
+
+/// Verification method type of an Ed25519 key in multibase form.
+pub const ED25519_VERIFICATION_KEY_2020: &str = "Ed25519VerificationKey2020";
+
+/// Multicodec prefix of an Ed25519 public key.
+const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+// ── DID document ─────────────────────────────────────────────────────────────
+
+/// A W3C DID document (DID core v1.0).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_known_as: Vec<String>,
+    pub verification_method: Vec<VerificationMethod>,
+    #[serde(default)]
+    pub authentication: Vec<String>,
+    #[serde(default)]
+    pub assertion_method: Vec<String>,
+    #[serde(default)]
+    pub capability_invocation: Vec<String>,
+    #[serde(default)]
+    pub capability_delegation: Vec<String>,
+}
+
+/// A public key listed in a [`DidDocument`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    /// DID URL of the method: `<did>#<fragment>`.
+    pub id: String,
+    #[serde(rename = "type")]
+    pub method_type: String,
+    pub controller: String,
+    /// The key as multibase base58btc of its multicodec encoding.
+    pub public_key_multibase: String,
+}
+
+impl VerificationMethod {
+    /// Decode the method's Ed25519 public key.
+    ///
+    /// Fails with [`IdentityError::InvalidKey`] for other method types or
+    /// keys.
+    pub fn verifying_key(&self) -> Result<VerifyingKey> {
+        if self.method_type != ED25519_VERIFICATION_KEY_2020 {
+            return Err(IdentityError::InvalidKey(format!(
+                "unsupported verification method type '{}'",
+                self.method_type
+            )));
+        }
+        decode_multibase_key(&self.public_key_multibase)
+    }
+}
+
+impl IdentityDocument {
+    /// The `did:key` DID of the document's current key.
+    pub fn did_key(&self) -> Result<String> {
+        Ok(did_key(&self.verifying_key()?))
+    }
+
+    /// Render the document as a `did:key` DID document.
+    ///
+    /// Fails if the document's self-signature does not verify.
+    pub fn to_did_key_document(&self) -> Result<DidDocument> {
+        self.verify_signature()?;
+        let key = self.verifying_key()?;
+        Ok(self.did_document(did_key(&key), &key, Vec::new()))
+    }
+
+    /// Render the document as a `did:web` DID document to be served from
+    /// `location` (see [`did_web`]). The document's `did:key` is listed in
+    /// `alsoKnownAs`.
+    ///
+    /// Fails if `location` is invalid or the document's self-signature does
+    /// not verify.
+    pub fn to_did_web_document(&self, location: &str) -> Result<DidDocument> {
+        let did = did_web(location)?;
+        self.verify_signature()?;
+        let key = self.verifying_key()?;
+        Ok(self.did_document(did, &key, vec![did_key(&key)]))
+    }
+
+    fn did_document(
+        &self,
+        did: String,
+        key: &VerifyingKey,
+        mut also_known_as: Vec<String>,
+    ) -> DidDocument {
+        let multibase = encode_multibase_key(key);
+        let method_id = format!("{did}#{multibase}");
+        also_known_as.insert(0, format!("urn:aid:{}", self.id));
+
+        DidDocument {
+            context: vec![DID_V1_CONTEXT.into(), ED25519_2020_CONTEXT.into()],
+            id: did.clone(),
+            also_known_as,
+            verification_method: vec![VerificationMethod {
+                id: method_id.clone(),
+                method_type: ED25519_VERIFICATION_KEY_2020.into(),
+                controller: did,
+                public_key_multibase: multibase,
+            }],
+            authentication: vec![method_id.clone()],
+            assertion_method: vec![method_id.clone()],
+            capability_invocation: vec![method_id.clone()],
+            capability_delegation: vec![method_id],
+        }
+    }
+}
+
+// ── DIDs ─────────────────────────────────────────────────────────────────────
+
+/// The `did:key` DID of an Ed25519 public key (`did:key:z6Mk...`).
+pub fn did_key(key: &VerifyingKey) -> String {
+    format!("did:key:{}", encode_multibase_key(key))
+}
+
+/// Decode the Ed25519 public key named by a `did:key` DID or DID URL.
+///
+/// Any `#fragment` is ignored. Fails with [`IdentityError::InvalidKey`] for
+/// other DID methods and for keys that are not Ed25519.
+pub fn verifying_key_from_did_key(did: &str) -> Result<VerifyingKey> {
+    let did = did.split('#').next().unwrap_or(did);
+    let multibase = did
+        .strip_prefix("did:key:")
+        .ok_or_else(|| IdentityError::InvalidKey(format!("not a did:key: '{did}'")))?;
+    decode_multibase_key(multibase)
+}
+
+/// The `did:web` DID for a document served from `location`.
+///
+/// `location` is a host with an optional port and path, without a scheme:
+/// `example.com` (served at `https://example.com/.well-known/did.json`) or
+/// `example.com:8443/agents/alice` (served at
+/// `https://example.com:8443/agents/alice/did.json`).
+pub fn did_web(location: &str) -> Result<String> {
+    let invalid = |reason: &str| {
+        IdentityError::InvalidInput(format!("invalid did:web location '{location}': {reason}"))
+    };
+    if location.contains("://") {
+        return Err(invalid("omit the URL scheme"));
+    }
+    let mut segments = location.trim_end_matches('/').split('/');
+    let host = segments.next().unwrap_or_default();
+    if host.is_empty() {
+        return Err(invalid("no host"));
+    }
+
+    let mut did = format!("did:web:{}", host.replace(':', "%3A"));
+    for segment in segments {
+        if segment.is_empty() || segment.contains(':') {
+            return Err(invalid("empty or ':' path segment"));
+        }
+        did.push(':');
+        did.push_str(segment);
+    }
+    Ok(did)
+}
+
+// ── Multibase keys ───────────────────────────────────────────────────────────
+
+/// `z` + base58btc of the multicodec Ed25519 key.
+fn encode_multibase_key(key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_MULTICODEC.to_vec();
+    bytes.extend_from_slice(key.as_bytes());
+    format!("z{}", bs58::encode(bytes).into_string())
+}
+
+fn decode_multibase_key(multibase: &str) -> Result<VerifyingKey> {
+    let encoded = multibase.strip_prefix('z').ok_or_else(|| {
+        IdentityError::InvalidKey(format!("key is not base58btc multibase: '{multibase}'"))
+    })?;
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid multibase key: {e}")))?;
+    let key: [u8; 32] = bytes
+        .strip_prefix(&ED25519_MULTICODEC)
+        .and_then(|key| key.try_into().ok())
+        .ok_or_else(|| IdentityError::InvalidKey(format!("key is not Ed25519: '{multibase}'")))?;
+    Ed25519KeyPair::verifying_key_from_bytes(&key)
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+
+    #[test]
+    fn test_did_key_document() {
+        let anchor = IdentityAnchor::new(Some("alice".into()));
+        let doc = anchor.to_document().to_did_key_document().unwrap();
+
+        assert!(doc.id.starts_with("did:key:z6Mk"));
+        assert_eq!(doc.context[0], DID_V1_CONTEXT);
+        assert_eq!(doc.also_known_as, vec![format!("urn:aid:{}", anchor.id())]);
+        let method = &doc.verification_method[0];
+        assert_eq!(method.controller, doc.id);
+        assert!(method.id.starts_with(&format!("{}#z6Mk", doc.id)));
+        assert_eq!(&method.verifying_key().unwrap(), anchor.verifying_key());
+        assert_eq!(doc.authentication, vec![method.id.clone()]);
+
+        let json = serde_json::to_value(&doc).unwrap();
+        assert_eq!(
+            json["verificationMethod"][0]["type"],
+            ED25519_VERIFICATION_KEY_2020
+        );
+        assert!(json["@context"].is_array());
+    }
+
+    #[test]
+    fn test_did_key_round_trip() {
+        let anchor = IdentityAnchor::new(None);
+        let did = did_key(anchor.verifying_key());
+        assert_eq!(
+            &verifying_key_from_did_key(&format!("{did}#key-1")).unwrap(),
+            anchor.verifying_key()
+        );
+        assert!(verifying_key_from_did_key("did:web:example.com").is_err());
+        assert!(verifying_key_from_did_key("did:key:z111").is_err());
+    }
+
+    #[test]
+    fn test_did_web_document() {
+        let anchor = IdentityAnchor::new(None);
+        let document = anchor.to_document();
+        let doc = document
+            .to_did_web_document("example.com:8443/agents/alice/")
+            .unwrap();
+
+        assert_eq!(doc.id, "did:web:example.com%3A8443:agents:alice");
+        assert_eq!(doc.also_known_as[1], document.did_key().unwrap());
+        assert_eq!(doc.verification_method[0].controller, doc.id);
+
+        assert_eq!(did_web("example.com").unwrap(), "did:web:example.com");
+        assert!(did_web("https://example.com").is_err());
+        assert!(did_web("example.com//alice").is_err());
+        assert!(did_web("").is_err());
+    }
+
+    #[test]
+    fn test_tampered_document_is_not_exported() {
+        let anchor = IdentityAnchor::new(None);
+        let mut document = anchor.to_document();
+        document.public_key = IdentityAnchor::new(None).public_key_base64();
+        assert!(document.to_did_key_document().is_err());
+    }
+}
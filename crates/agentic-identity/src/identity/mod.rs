@@ -1,11 +1,22 @@
 //! Identity management — creation, derivation, rotation.
 //!
 //! The identity module provides the core `IdentityAnchor` type
-//! which is the root of an agent's cryptographic identity.
+//! which is the root of an agent's cryptographic identity, and
+//! `MultisigAnchor` for identities controlled by M-of-N keys. Identity
+//! documents can be exported as W3C DID documents (`did`), and a lost key
+//! can be replaced with the approval of nominated guardians (`recovery`).
 
 pub mod anchor;
+pub mod did;
+pub mod multisig;
+pub mod recovery;
 
 pub use anchor::{
-    Attestation, AttestationClaim, IdentityAnchor, IdentityDocument, IdentityId, KeyRotation,
-    PublicKeyRotation, RotationReason,
+    verify_genesis, Attestation, AttestationClaim, IdentityAnchor, IdentityDocument, IdentityId,
+    KeyRotation, PublicKeyRotation, RotationReason, SignatureAlgorithm,
 };
+pub use did::{did_key, did_web, DidDocument, VerificationMethod};
+pub use multisig::{cosign_signing_input, Cosignature, MultisigAnchor, MultisigDocument};
+#[cfg(feature = "signing")]
+pub use recovery::recover_identity;
+pub use recovery::{Guardian, GuardianApproval, GuardianSet, RecoveryProof, RecoveryRequest};
@@ -0,0 +1,392 @@
+//! Multi-signature identities — M-of-N keys acting as one identity.
+//!
+//! A [`MultisigAnchor`] is a set of member keys and a threshold. Its
+//! identity ID is derived from the sorted keys and the threshold, so the
+//! same configuration always yields the same ID and any change to it (a key
+//! swapped, the threshold lowered) yields a different one.
+//!
+//! Receipts and grants issued by a multisig identity are finalized with
+//! [`MultisigAnchor::prepare_receipt`] / [`MultisigAnchor::prepare_grant`],
+//! which bind the configuration into the signed content, and then collect
+//! one [`Cosignature`] per member. They verify only with at least
+//! `threshold` valid signatures from distinct members; see
+//! [`verify_multisig_receipt`](crate::receipt::verify::verify_multisig_receipt)
+//! and [`verify_multisig_trust_grant`](crate::trust::verify::verify_multisig_trust_grant).
+
+use std::collections::BTreeSet;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::keys::Ed25519KeyPair;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::receipt::receipt::ReceiptBuilder;
+use crate::receipt::ActionReceipt;
+use crate::trust::grant::TrustGrant;
+#[cfg(feature = "signing")]
+use crate::trust::grant::TrustGrantBuilder;
+
+use super::anchor::IdentityId;
+
+/// Domain separator for multisig identity IDs.
+const MULTISIG_ID_DOMAIN: &[u8] = b"agentic-identity/multisig/v1";
+
+/// The message a member signs: `aid:cosignature:v1:cosign:{multisig_id}:{hash}`.
+///
+/// `hash` is the receipt hash or grant hash. Exposed so a member holding its
+/// key elsewhere can produce a [`Cosignature`] without this crate.
+pub fn cosign_signing_input(multisig: &IdentityId, hash: &str) -> String {
+    format!(
+        "{}:{}",
+        SignatureDomain::Cosignature.tag(),
+        cosign_message(multisig, hash)
+    )
+}
+
+/// The cosign message without its domain tag, as signed before domain
+/// separation.
+fn cosign_message(multisig: &IdentityId, hash: &str) -> String {
+    format!("cosign:{}:{hash}", multisig.0)
+}
+
+/// One member's signature on a multisig receipt or grant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cosignature {
+    /// Base64-encoded public key of the signing member.
+    pub key: String,
+    /// Signature over [`cosign_signing_input`].
+    pub signature: String,
+    /// Absent on cosignatures made before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+impl Cosignature {
+    /// Sign `hash` on behalf of `multisig` with a member key.
+    pub fn create(multisig: &IdentityId, signing_key: &SigningKey, hash: &str) -> Self {
+        let to_sign = cosign_message(multisig, hash);
+        Self {
+            key: encode_key(&signing_key.verifying_key()),
+            signature: signing::sign_in_domain(
+                signing_key,
+                SignatureDomain::Cosignature,
+                to_sign.as_bytes(),
+            ),
+            signature_version: signing::SIGNATURE_VERSION,
+        }
+    }
+
+    /// Verify this signature over `hash` on behalf of `multisig`.
+    pub fn verify(&self, multisig: &IdentityId, hash: &str) -> Result<()> {
+        let verifying_key = decode_key(&self.key)?;
+        let to_verify = cosign_message(multisig, hash);
+        signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::Cosignature,
+            self.signature_version,
+            to_verify.as_bytes(),
+            &self.signature,
+        )
+    }
+}
+
+/// An identity controlled by `threshold` of `keys`.
+///
+/// Holds public keys only; each member signs with its own key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigAnchor {
+    /// Member keys, sorted by their bytes.
+    pub keys: Vec<VerifyingKey>,
+    /// Number of distinct members that must sign.
+    pub threshold: usize,
+}
+
+impl MultisigAnchor {
+    /// Create a `threshold`-of-`keys` identity.
+    ///
+    /// Keys are sorted, so the order they are given in does not matter.
+    /// Fails if `keys` is empty or contains a duplicate, or if `threshold`
+    /// is zero or exceeds the number of keys.
+    pub fn new(mut keys: Vec<VerifyingKey>, threshold: usize) -> Result<Self> {
+        if keys.is_empty() {
+            return Err(IdentityError::InvalidKey(
+                "multisig identity needs at least one key".into(),
+            ));
+        }
+        if threshold == 0 || threshold > keys.len() {
+            return Err(IdentityError::InvalidKey(format!(
+                "multisig threshold must be between 1 and {}, got {threshold}",
+                keys.len()
+            )));
+        }
+        keys.sort_by_key(|k| k.to_bytes());
+        if keys.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(IdentityError::InvalidKey(
+                "multisig identity lists the same key twice".into(),
+            ));
+        }
+        Ok(Self { keys, threshold })
+    }
+
+    /// The identity ID: `aid_` + base58 of the first 16 bytes of
+    /// SHA-256(domain || threshold || sorted keys).
+    pub fn id(&self) -> IdentityId {
+        let mut hasher = Sha256::new();
+        hasher.update(MULTISIG_ID_DOMAIN);
+        hasher.update((self.threshold as u64).to_be_bytes());
+        for key in self.sorted_keys() {
+            hasher.update(key);
+        }
+        let hash = hasher.finalize();
+        IdentityId(format!("aid_{}", bs58::encode(&hash[..16]).into_string()))
+    }
+
+    /// The configuration as it is recorded in `actor_key` / `grantor_key`:
+    /// `multisig:{threshold}:{key},{key},...` with sorted base64 keys.
+    pub fn key_descriptor(&self) -> String {
+        let keys: Vec<String> = self.sorted_keys().iter().map(encode_bytes).collect();
+        format!("multisig:{}:{}", self.threshold, keys.join(","))
+    }
+
+    /// Is `key` one of the members?
+    pub fn is_member(&self, key: &VerifyingKey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// The shareable document for this identity.
+    pub fn to_document(&self) -> MultisigDocument {
+        MultisigDocument {
+            id: self.id(),
+            keys: self.sorted_keys().iter().map(encode_bytes).collect(),
+            threshold: self.threshold,
+        }
+    }
+
+    /// Finalize a receipt for cosigning. The builder's actor must be [`id`](Self::id).
+    #[cfg(feature = "signing")]
+    pub fn prepare_receipt(&self, builder: ReceiptBuilder) -> Result<ActionReceipt> {
+        let receipt = builder.build(self.key_descriptor())?;
+        self.check_issuer(&receipt.actor, &receipt.actor_key)?;
+        Ok(receipt)
+    }
+
+    /// Finalize a grant for cosigning. The builder's grantor must be [`id`](Self::id).
+    #[cfg(feature = "signing")]
+    pub fn prepare_grant(&self, builder: TrustGrantBuilder) -> Result<TrustGrant> {
+        let grant = builder.build(self.key_descriptor())?;
+        self.check_issuer(&grant.grantor, &grant.grantor_key)?;
+        Ok(grant)
+    }
+
+    /// Add a member's signature to a prepared receipt.
+    ///
+    /// Signing again with a key that already signed is a no-op.
+    pub fn cosign_receipt(&self, receipt: &mut ActionReceipt, member: &SigningKey) -> Result<()> {
+        self.check_issuer(&receipt.actor, &receipt.actor_key)?;
+        self.cosign(&mut receipt.cosignatures, &receipt.receipt_hash, member)
+    }
+
+    /// Add a member's signature to a prepared grant.
+    ///
+    /// Signing again with a key that already signed is a no-op.
+    pub fn cosign_grant(&self, grant: &mut TrustGrant, member: &SigningKey) -> Result<()> {
+        self.check_issuer(&grant.grantor, &grant.grantor_key)?;
+        self.cosign(&mut grant.cosignatures, &grant.grant_hash, member)
+    }
+
+    /// Number of distinct members with a valid signature over `hash`.
+    ///
+    /// Signatures from non-members, invalid signatures, and repeat
+    /// signatures from the same key are not counted.
+    pub fn valid_signers(&self, hash: &str, cosignatures: &[Cosignature]) -> usize {
+        let id = self.id();
+        let mut signers: BTreeSet<[u8; 32]> = BTreeSet::new();
+        for cosig in cosignatures {
+            let Ok(key) = decode_key(&cosig.key) else {
+                continue;
+            };
+            if self.is_member(&key) && cosig.verify(&id, hash).is_ok() {
+                signers.insert(key.to_bytes());
+            }
+        }
+        signers.len()
+    }
+
+    /// Do `cosignatures` over `hash` reach the threshold?
+    pub fn is_satisfied(&self, hash: &str, cosignatures: &[Cosignature]) -> bool {
+        self.valid_signers(hash, cosignatures) >= self.threshold
+    }
+
+    /// Was an artifact issued as this identity, under this configuration?
+    pub(crate) fn issued(&self, issuer: &IdentityId, issuer_key: &str) -> bool {
+        *issuer == self.id() && issuer_key == self.key_descriptor()
+    }
+
+    fn check_issuer(&self, issuer: &IdentityId, issuer_key: &str) -> Result<()> {
+        if self.issued(issuer, issuer_key) {
+            Ok(())
+        } else {
+            Err(IdentityError::InvalidId(format!(
+                "issuer {issuer} is not multisig identity {}",
+                self.id()
+            )))
+        }
+    }
+
+    fn cosign(
+        &self,
+        cosignatures: &mut Vec<Cosignature>,
+        hash: &str,
+        member: &SigningKey,
+    ) -> Result<()> {
+        let key = member.verifying_key();
+        if !self.is_member(&key) {
+            return Err(IdentityError::InvalidKey(format!(
+                "key {} is not a member of multisig identity {}",
+                encode_key(&key),
+                self.id()
+            )));
+        }
+        let encoded = encode_key(&key);
+        if !cosignatures.iter().any(|c| c.key == encoded) {
+            cosignatures.push(Cosignature::create(&self.id(), member, hash));
+        }
+        Ok(())
+    }
+
+    fn sorted_keys(&self) -> Vec<[u8; 32]> {
+        let mut keys: Vec<[u8; 32]> = self.keys.iter().map(|k| k.to_bytes()).collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// Public document for a multisig identity (shareable).
+///
+/// Its `id` is recomputed from `keys` and `threshold` by
+/// [`anchor`](Self::anchor), so an edited document is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigDocument {
+    pub id: IdentityId,
+    /// Base64-encoded member keys, sorted.
+    pub keys: Vec<String>,
+    pub threshold: usize,
+}
+
+impl MultisigDocument {
+    /// Rebuild the anchor, checking that `id` matches the configuration.
+    pub fn anchor(&self) -> Result<MultisigAnchor> {
+        let keys = self
+            .keys
+            .iter()
+            .map(|k| decode_key(k))
+            .collect::<Result<Vec<_>>>()?;
+        let anchor = MultisigAnchor::new(keys, self.threshold)?;
+        if anchor.id() != self.id {
+            return Err(IdentityError::InvalidId(format!(
+                "multisig document {} does not match its keys and threshold (expected {})",
+                self.id,
+                anchor.id()
+            )));
+        }
+        Ok(anchor)
+    }
+}
+
+fn encode_key(key: &VerifyingKey) -> String {
+    encode_bytes(&key.to_bytes())
+}
+
+fn encode_bytes(bytes: &[u8; 32]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+}
+
+fn decode_key(encoded: &str) -> Result<VerifyingKey> {
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid multisig member key: {e}")))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| IdentityError::InvalidKey("multisig member key must be 32 bytes".into()))?;
+    Ed25519KeyPair::verifying_key_from_bytes(&bytes)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+
+    fn members(n: usize) -> Vec<IdentityAnchor> {
+        (0..n).map(|_| IdentityAnchor::new(None)).collect()
+    }
+
+    fn keys(members: &[IdentityAnchor]) -> Vec<VerifyingKey> {
+        members.iter().map(|m| *m.verifying_key()).collect()
+    }
+
+    #[test]
+    fn test_id_is_stable_and_binds_configuration() {
+        let members = members(3);
+        let a = MultisigAnchor::new(keys(&members), 2).unwrap();
+        let mut reversed = keys(&members);
+        reversed.reverse();
+        let b = MultisigAnchor::new(reversed, 2).unwrap();
+        assert_eq!(a.id(), b.id());
+        assert_eq!(a.key_descriptor(), b.key_descriptor());
+
+        let lower = MultisigAnchor::new(keys(&members), 1).unwrap();
+        assert_ne!(a.id(), lower.id());
+        let fewer = MultisigAnchor::new(keys(&members[..2]), 2).unwrap();
+        assert_ne!(a.id(), fewer.id());
+    }
+
+    #[test]
+    fn test_new_rejects_bad_configuration() {
+        let members = members(2);
+        assert!(MultisigAnchor::new(Vec::new(), 1).is_err());
+        assert!(MultisigAnchor::new(keys(&members), 0).is_err());
+        assert!(MultisigAnchor::new(keys(&members), 3).is_err());
+        let dup = vec![*members[0].verifying_key(), *members[0].verifying_key()];
+        assert!(MultisigAnchor::new(dup, 1).is_err());
+    }
+
+    #[test]
+    fn test_document_round_trip_and_tamper() {
+        let members = members(3);
+        let anchor = MultisigAnchor::new(keys(&members), 2).unwrap();
+        let doc = anchor.to_document();
+        assert_eq!(doc.anchor().unwrap(), anchor);
+
+        let mut lowered = doc.clone();
+        lowered.threshold = 1;
+        assert!(lowered.anchor().is_err());
+
+        let mut swapped = doc;
+        swapped.keys[0] = encode_key(IdentityAnchor::new(None).verifying_key());
+        assert!(swapped.anchor().is_err());
+    }
+
+    #[test]
+    fn test_duplicate_and_foreign_signatures_do_not_count() {
+        let members = members(3);
+        let anchor = MultisigAnchor::new(keys(&members), 2).unwrap();
+        let id = anchor.id();
+        let hash = "abc";
+
+        let first = Cosignature::create(&id, members[0].signing_key(), hash);
+        let outsider = IdentityAnchor::new(None);
+        let foreign = Cosignature::create(&id, outsider.signing_key(), hash);
+        let cosigs = vec![first.clone(), first.clone(), foreign];
+        assert_eq!(anchor.valid_signers(hash, &cosigs), 1);
+        assert!(!anchor.is_satisfied(hash, &cosigs));
+
+        let second = Cosignature::create(&id, members[1].signing_key(), hash);
+        assert!(anchor.is_satisfied(hash, &[first, second.clone()]));
+        assert_eq!(anchor.valid_signers("other", &[second]), 0);
+    }
+}
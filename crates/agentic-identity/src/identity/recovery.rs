@@ -0,0 +1,616 @@
+//! Guardian recovery — replacing a lost key with K-of-N guardian approval.
+//!
+//! An identity nominates guardians with [`GuardianSet::nominate`], best done
+//! right after it is created, and hands the signed set to each guardian. If
+//! the key is later lost, its holder generates a fresh key and opens a
+//! [`RecoveryRequest`] for it; each guardian checks the request out of band
+//! and adds a [`GuardianApproval`]. Once `threshold` guardians have
+//! approved, [`recover_identity`] performs the rotation to the new key
+//! without the old one and records it in an `IdentityOperation` receipt
+//! signed by the new key.
+//!
+//! The rotation carries the set and the approved request as a
+//! [`RecoveryProof`] in place of the old key's authorization, so
+//! [`verify_genesis`](super::anchor::verify_genesis) accepts the recovered
+//! identity's document offline. A set authorizes recovery only of the key
+//! that signed it; nominate again after rotating.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::receipt::action::{ActionContent, ActionType};
+#[cfg(feature = "signing")]
+use crate::receipt::receipt::ReceiptBuilder;
+#[cfg(feature = "signing")]
+use crate::receipt::ActionReceipt;
+
+use super::anchor::{decode_public_key, IdentityId, RotationReason};
+#[cfg(feature = "signing")]
+use super::anchor::{IdentityAnchor, IdentityDocument, KeyRotation};
+
+/// A nominated guardian.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guardian {
+    pub id: IdentityId,
+    /// Base64 public key the guardian approves with.
+    pub key: String,
+}
+
+/// An identity's signed nomination of its guardians.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardianSet {
+    pub identity: IdentityId,
+    /// Base64 key that signed the set; the only key it can recover.
+    pub identity_key: String,
+    /// Guardians, sorted by key.
+    pub guardians: Vec<Guardian>,
+    /// Number of distinct guardians that must approve a recovery.
+    pub threshold: usize,
+    /// When the set was signed (microseconds since epoch).
+    pub nominated_at: u64,
+    pub signature: String,
+    /// Absent on sets signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// Everything in a [`GuardianSet`] except its signature.
+#[derive(Serialize)]
+struct GuardianSetPayload<'a> {
+    identity: &'a IdentityId,
+    identity_key: &'a str,
+    guardians: &'a [Guardian],
+    threshold: usize,
+    nominated_at: u64,
+}
+
+impl GuardianSet {
+    /// Nominate the identities behind `guardians` as `anchor`'s guardians,
+    /// any `threshold` of which can later approve a recovery.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if `guardians` is empty, names
+    /// an identity twice or includes `anchor` itself, or unless
+    /// `1 <= threshold <= guardians.len()`, and
+    /// `IdentityError::SignatureInvalid` if a guardian document does not
+    /// verify.
+    #[cfg(feature = "signing")]
+    pub fn nominate(
+        anchor: &IdentityAnchor,
+        guardians: &[IdentityDocument],
+        threshold: usize,
+    ) -> Result<Self> {
+        if guardians.is_empty() {
+            return Err(IdentityError::InvalidInput(
+                "a guardian set needs at least one guardian".into(),
+            ));
+        }
+        if threshold == 0 || threshold > guardians.len() {
+            return Err(IdentityError::InvalidInput(format!(
+                "guardian threshold must be between 1 and {}, got {threshold}",
+                guardians.len()
+            )));
+        }
+        let identity_key = anchor.public_key_base64();
+        let mut nominated = Vec::with_capacity(guardians.len());
+        for doc in guardians {
+            doc.verify_signature()?;
+            if doc.public_key == identity_key {
+                return Err(IdentityError::InvalidInput(
+                    "an identity cannot be its own guardian".into(),
+                ));
+            }
+            nominated.push(Guardian {
+                id: doc.id.clone(),
+                key: doc.public_key.clone(),
+            });
+        }
+        nominated.sort_by(|a, b| a.key.cmp(&b.key));
+        if nominated.windows(2).any(|pair| pair[0].key == pair[1].key) {
+            return Err(IdentityError::InvalidInput(
+                "guardian set lists the same guardian twice".into(),
+            ));
+        }
+
+        let mut set = Self {
+            identity: anchor.id(),
+            identity_key,
+            guardians: nominated,
+            threshold,
+            nominated_at: crate::time::now_micros(),
+            signature: String::new(),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        set.signature = signing::sign_in_domain(
+            anchor.signing_key(),
+            SignatureDomain::GuardianSet,
+            set.signing_input()?.as_bytes(),
+        );
+        Ok(set)
+    }
+
+    /// Hex SHA-256 of the signed content, which recovery requests refer to.
+    pub fn hash(&self) -> Result<String> {
+        Ok(hex::encode(Sha256::digest(self.signing_input()?)))
+    }
+
+    /// Verify that the set was signed by `identity_key`, from which
+    /// `identity` derives.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if the key does not decode or
+    /// `identity` does not derive from it, and
+    /// `IdentityError::SignatureInvalid` if the set was altered.
+    pub fn verify(&self) -> Result<()> {
+        let key = decode_public_key(&self.identity_key)?;
+        if IdentityId::from_verifying_key(&key) != self.identity {
+            return Err(IdentityError::InvalidKey(format!(
+                "guardian set of {} was not signed by its key",
+                self.identity
+            )));
+        }
+        signing::verify_versioned(
+            &key,
+            SignatureDomain::GuardianSet,
+            self.signature_version,
+            self.signing_input()?.as_bytes(),
+            &self.signature,
+        )
+    }
+
+    /// The guardian approving with `key` (base64), if any.
+    pub fn guardian(&self, key: &str) -> Option<&Guardian> {
+        self.guardians.iter().find(|g| g.key == key)
+    }
+
+    fn signing_input(&self) -> Result<String> {
+        serde_json::to_string(&GuardianSetPayload {
+            identity: &self.identity,
+            identity_key: &self.identity_key,
+            guardians: &self.guardians,
+            threshold: self.threshold,
+            nominated_at: self.nominated_at,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+    }
+}
+
+/// One guardian's signature on a recovery request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardianApproval {
+    pub guardian: IdentityId,
+    /// Base64 public key of the approving guardian.
+    pub key: String,
+    /// Signature over the request hash.
+    pub signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// A request to replace an identity's lost key with `new_key`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryRequest {
+    pub identity: IdentityId,
+    /// [`GuardianSet::hash`] of the set the request is made under.
+    pub guardian_set_hash: String,
+    /// Base64 key being replaced.
+    pub previous_key: String,
+    /// Base64 key to recover to.
+    pub new_key: String,
+    pub reason: RotationReason,
+    /// When the request was opened (microseconds since epoch); becomes the
+    /// rotation time.
+    pub requested_at: u64,
+    /// Guardian approvals collected so far.
+    #[serde(default)]
+    pub approvals: Vec<GuardianApproval>,
+}
+
+/// Everything in a [`RecoveryRequest`] that guardians approve.
+#[derive(Serialize)]
+struct RecoveryRequestPayload<'a> {
+    identity: &'a IdentityId,
+    guardian_set_hash: &'a str,
+    previous_key: &'a str,
+    new_key: &'a str,
+    reason: &'a str,
+    requested_at: u64,
+}
+
+impl RecoveryRequest {
+    /// Open a request to recover `set`'s identity to `new_key` (base64).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if `new_key` does not decode or
+    /// is the key being replaced.
+    pub fn new(set: &GuardianSet, new_key: &str, reason: RotationReason) -> Result<Self> {
+        decode_public_key(new_key)?;
+        if new_key == set.identity_key {
+            return Err(IdentityError::InvalidKey(
+                "recovery must move to a new key".into(),
+            ));
+        }
+        Ok(Self {
+            identity: set.identity.clone(),
+            guardian_set_hash: set.hash()?,
+            previous_key: set.identity_key.clone(),
+            new_key: new_key.to_string(),
+            reason,
+            requested_at: crate::time::now_micros(),
+            approvals: Vec::new(),
+        })
+    }
+
+    /// Hex SHA-256 of the approved content, excluding the approvals.
+    pub fn hash(&self) -> Result<String> {
+        let payload = serde_json::to_string(&RecoveryRequestPayload {
+            identity: &self.identity,
+            guardian_set_hash: &self.guardian_set_hash,
+            previous_key: &self.previous_key,
+            new_key: &self.new_key,
+            reason: self.reason.as_str(),
+            requested_at: self.requested_at,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        Ok(hex::encode(Sha256::digest(payload)))
+    }
+
+    /// Add `guardian`'s approval. Approving twice is a no-op.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if the request was not made
+    /// under `set`, and `IdentityError::InvalidKey` if `guardian` is not one
+    /// of its guardians.
+    #[cfg(feature = "signing")]
+    pub fn approve(&mut self, set: &GuardianSet, guardian: &IdentityAnchor) -> Result<()> {
+        self.check_set(set)?;
+        let key = guardian.public_key_base64();
+        let Some(nominated) = set.guardian(&key) else {
+            return Err(IdentityError::InvalidKey(format!(
+                "{} is not a guardian of {}",
+                guardian.id(),
+                set.identity
+            )));
+        };
+        if self.approvals.iter().any(|a| a.key == key) {
+            return Ok(());
+        }
+        let signature = signing::sign_in_domain(
+            guardian.signing_key(),
+            SignatureDomain::RecoveryApproval,
+            self.hash()?.as_bytes(),
+        );
+        self.approvals.push(GuardianApproval {
+            guardian: nominated.id.clone(),
+            key,
+            signature,
+            signature_version: signing::SIGNATURE_VERSION,
+        });
+        Ok(())
+    }
+
+    /// Number of distinct guardians of `set` with a valid approval.
+    ///
+    /// Approvals from non-guardians, invalid signatures and repeat approvals
+    /// are not counted.
+    pub fn valid_approvals(&self, set: &GuardianSet) -> Result<usize> {
+        let hash = self.hash()?;
+        let mut approvers = BTreeSet::new();
+        for approval in &self.approvals {
+            if set.guardian(&approval.key).is_none() {
+                continue;
+            }
+            let valid = decode_public_key(&approval.key).and_then(|key| {
+                signing::verify_versioned(
+                    &key,
+                    SignatureDomain::RecoveryApproval,
+                    approval.signature_version,
+                    hash.as_bytes(),
+                    &approval.signature,
+                )
+            });
+            if valid.is_ok() {
+                approvers.insert(approval.key.as_str());
+            }
+        }
+        Ok(approvers.len())
+    }
+
+    /// Verify the set and that enough of its guardians approved this request.
+    ///
+    /// # Errors
+    ///
+    /// As for [`GuardianSet::verify`]; in addition
+    /// `IdentityError::InvalidInput` if the request was not made under `set`,
+    /// and `IdentityError::SignatureInvalid` if fewer than `threshold`
+    /// guardians approved it.
+    pub fn verify(&self, set: &GuardianSet) -> Result<()> {
+        set.verify()?;
+        self.check_set(set)?;
+        let approvals = self.valid_approvals(set)?;
+        if approvals < set.threshold {
+            return Err(IdentityError::SignatureInvalid);
+        }
+        Ok(())
+    }
+
+    fn check_set(&self, set: &GuardianSet) -> Result<()> {
+        if self.guardian_set_hash != set.hash()?
+            || self.identity != set.identity
+            || self.previous_key != set.identity_key
+        {
+            return Err(IdentityError::InvalidInput(format!(
+                "recovery request for {} was not made under this guardian set",
+                self.identity
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The guardian authorization recorded on a recovery rotation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecoveryProof {
+    pub guardian_set: GuardianSet,
+    pub request: RecoveryRequest,
+}
+
+impl RecoveryProof {
+    /// Does this proof authorize rotating `previous_key` to `new_key` at
+    /// `rotated_at` for `reason`?
+    ///
+    /// # Errors
+    ///
+    /// As for [`RecoveryRequest::verify`]; in addition
+    /// `IdentityError::InvalidChain` if the rotation is not the one the
+    /// request asked for.
+    pub fn authorizes(
+        &self,
+        previous_key: &str,
+        new_key: &str,
+        rotated_at: u64,
+        reason: &RotationReason,
+    ) -> Result<()> {
+        self.request.verify(&self.guardian_set)?;
+        let request = &self.request;
+        if request.previous_key != previous_key
+            || request.new_key != new_key
+            || request.requested_at != rotated_at
+            || request.reason != *reason
+        {
+            return Err(IdentityError::InvalidChain);
+        }
+        Ok(())
+    }
+}
+
+/// Recover the identity described by `previous` to `new_signing_key`.
+///
+/// `previous` is the identity's last public document, whose current key must
+/// be the one `request` replaces, and `new_signing_key` the secret half of
+/// `request.new_key`. Returns the recovered anchor, whose ID derives from the
+/// new key as after any rotation, and an `IdentityOperation` receipt signed
+/// by it that records the recovery.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidChain` if `previous` does not verify or
+/// does not end at the replaced key, `IdentityError::InvalidKey` if
+/// `new_signing_key` is not the requested key, and any error from
+/// [`RecoveryRequest::verify`].
+#[cfg(feature = "signing")]
+pub fn recover_identity(
+    previous: &IdentityDocument,
+    set: &GuardianSet,
+    request: RecoveryRequest,
+    new_signing_key: &[u8; 32],
+) -> Result<(IdentityAnchor, ActionReceipt)> {
+    if previous.verify_signature().is_err()
+        || !super::anchor::verify_genesis(previous)
+        || previous.public_key != request.previous_key
+    {
+        return Err(IdentityError::InvalidChain);
+    }
+    request.verify(set)?;
+    let mut anchor = IdentityAnchor::from_parts(
+        new_signing_key,
+        previous.created_at,
+        previous.name.clone(),
+        Vec::new(),
+    )?;
+    if anchor.public_key_base64() != request.new_key {
+        return Err(IdentityError::InvalidKey(
+            "signing key is not the key the recovery request names".into(),
+        ));
+    }
+
+    let mut history: Vec<KeyRotation> = previous
+        .rotation_history
+        .iter()
+        .map(|r| KeyRotation {
+            previous_key: r.previous_key.clone(),
+            new_key: r.new_key.clone(),
+            rotated_at: r.rotated_at,
+            reason: r.reason.clone(),
+            authorization_signature: r.authorization_signature.clone(),
+            signature_version: r.signature_version,
+            recovery: r.recovery.clone(),
+        })
+        .collect();
+    let approvals = request.valid_approvals(set)?;
+    let request_hash = request.hash()?;
+    history.push(KeyRotation {
+        previous_key: request.previous_key.clone(),
+        new_key: request.new_key.clone(),
+        rotated_at: request.requested_at,
+        reason: request.reason.clone(),
+        authorization_signature: String::new(),
+        signature_version: signing::SIGNATURE_VERSION,
+        recovery: Some(RecoveryProof {
+            guardian_set: set.clone(),
+            request,
+        }),
+    });
+
+    anchor.rotation_history = history;
+
+    let rotation = &anchor.rotation_history[anchor.rotation_history.len() - 1];
+    let receipt = ReceiptBuilder::new(
+        anchor.id(),
+        ActionType::IdentityOperation,
+        ActionContent::with_data(
+            format!(
+                "Recovered identity {} with {approvals} of {} guardian approvals",
+                previous.id,
+                set.guardians.len()
+            ),
+            serde_json::json!({
+                "operation": "recovery",
+                "previous_id": previous.id,
+                "previous_key": rotation.previous_key,
+                "new_key": rotation.new_key,
+                "reason": rotation.reason.as_str(),
+                "guardian_set_hash": set.hash()?,
+                "request_hash": request_hash,
+                "approvals": approvals,
+                "threshold": set.threshold,
+            }),
+        ),
+    )
+    .sign(&anchor)?;
+    Ok((anchor, receipt))
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::anchor::verify_genesis;
+
+    fn guardians(n: usize) -> Vec<IdentityAnchor> {
+        (0..n).map(|_| IdentityAnchor::new(None)).collect()
+    }
+
+    fn documents(anchors: &[IdentityAnchor]) -> Vec<IdentityDocument> {
+        anchors.iter().map(IdentityAnchor::to_document).collect()
+    }
+
+    #[test]
+    fn test_recovery_with_threshold_approvals() {
+        let lost = IdentityAnchor::new(Some("agent".into()))
+            .rotate(RotationReason::Scheduled)
+            .unwrap();
+        let guardians = guardians(3);
+        let set = GuardianSet::nominate(&lost, &documents(&guardians), 2).unwrap();
+        set.verify().unwrap();
+        let previous = lost.to_document();
+        drop(lost);
+
+        let replacement = IdentityAnchor::new(None);
+        let mut request = RecoveryRequest::new(
+            &set,
+            &replacement.public_key_base64(),
+            RotationReason::DeviceLost,
+        )
+        .unwrap();
+        request.approve(&set, &guardians[0]).unwrap();
+        request.approve(&set, &guardians[0]).unwrap();
+        assert_eq!(request.valid_approvals(&set).unwrap(), 1);
+        assert!(request.verify(&set).is_err());
+
+        request.approve(&set, &guardians[2]).unwrap();
+        request.verify(&set).unwrap();
+
+        let (recovered, receipt) =
+            recover_identity(&previous, &set, request, &replacement.signing_key_bytes()).unwrap();
+        assert_eq!(recovered.name.as_deref(), Some("agent"));
+        assert_eq!(recovered.created_at, previous.created_at);
+        assert_eq!(recovered.rotation_history.len(), 2);
+        assert_eq!(receipt.actor, recovered.id());
+        assert_eq!(receipt.action_type, ActionType::IdentityOperation);
+
+        let doc = recovered.to_document();
+        assert!(verify_genesis(&doc));
+        let reloaded: IdentityDocument =
+            serde_json::from_str(&serde_json::to_string(&doc).unwrap()).unwrap();
+        assert!(verify_genesis(&reloaded));
+    }
+
+    #[test]
+    fn test_recovery_rejects_outsiders_and_tampering() {
+        let lost = IdentityAnchor::new(None);
+        let guardians = guardians(2);
+        let set = GuardianSet::nominate(&lost, &documents(&guardians), 2).unwrap();
+        let replacement = IdentityAnchor::new(None);
+        let mut request = RecoveryRequest::new(
+            &set,
+            &replacement.public_key_base64(),
+            RotationReason::Compromised,
+        )
+        .unwrap();
+
+        let outsider = IdentityAnchor::new(None);
+        assert!(request.approve(&set, &outsider).is_err());
+        request.approve(&set, &guardians[0]).unwrap();
+        request.approve(&set, &guardians[1]).unwrap();
+
+        // Redirecting the approved request to another key voids the approvals.
+        let mut redirected = request.clone();
+        redirected.new_key = outsider.public_key_base64();
+        assert!(redirected.verify(&set).is_err());
+
+        // A lowered threshold breaks the set's signature.
+        let mut lowered = set.clone();
+        lowered.threshold = 1;
+        assert!(lowered.verify().is_err());
+
+        // The secret key must match the approved request.
+        assert!(recover_identity(
+            &lost.to_document(),
+            &set,
+            request.clone(),
+            &outsider.signing_key_bytes()
+        )
+        .is_err());
+
+        // A forged recovery rotation does not pass genesis verification.
+        let (recovered, _) = recover_identity(
+            &lost.to_document(),
+            &set,
+            request,
+            &replacement.signing_key_bytes(),
+        )
+        .unwrap();
+        let mut forged = recovered.to_document();
+        if let Some(proof) = forged.rotation_history[0].recovery.as_mut() {
+            proof.request.approvals.pop();
+        }
+        assert!(!verify_genesis(&forged));
+    }
+
+    #[test]
+    fn test_nominate_rejects_bad_sets() {
+        let anchor = IdentityAnchor::new(None);
+        let guardians = documents(&guardians(2));
+        assert!(GuardianSet::nominate(&anchor, &[], 1).is_err());
+        assert!(GuardianSet::nominate(&anchor, &guardians, 0).is_err());
+        assert!(GuardianSet::nominate(&anchor, &guardians, 3).is_err());
+        let twice = vec![guardians[0].clone(), guardians[0].clone()];
+        assert!(GuardianSet::nominate(&anchor, &twice, 1).is_err());
+        assert!(GuardianSet::nominate(&anchor, &[anchor.to_document()], 1).is_err());
+    }
+}
@@ -3,14 +3,22 @@
 //! This module provides two in-memory index structures:
 //!
 //! - [`ReceiptIndex`] — indexes [`ActionReceipt`] records by ID, actor,
-//!   action type, and timestamp.
+//!   action type, timestamp, and the receipt they chain from.
 //! - [`TrustIndex`] — indexes [`TrustGrant`] and [`Revocation`] records by
 //!   ID, grantor, and grantee.
 //!
 //! Both indexes hold owned copies of the records and support O(1) lookups
 //! by primary key as well as set lookups by the secondary keys.
+//!
+//! [`TextIndex`] is different: a persisted inverted index from terms to
+//! receipt and grant IDs, kept by the receipt and trust stores so text
+//! search need not load every record.
+
+mod text;
 
-use std::collections::{BTreeMap, HashMap};
+pub use text::{grant_terms, receipt_terms, tokenize, TextIndex};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::identity::IdentityId;
 use crate::receipt::{ActionReceipt, ActionType, ReceiptId};
@@ -20,9 +28,9 @@ use crate::trust::{Revocation, TrustGrant, TrustId};
 
 /// In-memory index over [`ActionReceipt`] records.
 ///
-/// Maintains four secondary indexes so that receipts can be found
-/// efficiently by primary ID, by actor identity, by action-type tag,
-/// or by a timestamp range.
+/// Maintains secondary indexes so that receipts can be found efficiently
+/// by primary ID, by actor identity, by action-type tag, by a timestamp
+/// range, or by the receipt they follow in a chain.
 pub struct ReceiptIndex {
     /// Primary store: receipt ID → receipt.
     by_id: HashMap<ReceiptId, ActionReceipt>,
@@ -34,6 +42,8 @@ pub struct ReceiptIndex {
     ///
     /// Using a `BTreeMap` gives us cheap range queries without sorting.
     by_time: BTreeMap<u64, Vec<ReceiptId>>,
+    /// Secondary index: previous receipt → receipts that chain from it.
+    by_previous: HashMap<ReceiptId, Vec<ReceiptId>>,
 }
 
 impl ReceiptIndex {
@@ -44,10 +54,11 @@ impl ReceiptIndex {
             by_actor: HashMap::new(),
             by_type: HashMap::new(),
             by_time: BTreeMap::new(),
+            by_previous: HashMap::new(),
         }
     }
 
-    /// Insert a receipt into all four indexes.
+    /// Insert a receipt into every index.
     ///
     /// If a receipt with the same ID already exists it is replaced in
     /// `by_id`, but **not** removed from the secondary indexes (the old
@@ -58,8 +69,15 @@ impl ReceiptIndex {
         let actor = receipt.actor.clone();
         let type_tag = receipt.action_type.as_tag().to_string();
         let ts = receipt.timestamp;
+        let previous = receipt.previous_receipt.clone();
 
         self.by_id.insert(id.clone(), receipt);
+        if let Some(previous) = previous {
+            self.by_previous
+                .entry(previous)
+                .or_default()
+                .push(id.clone());
+        }
         self.by_actor.entry(actor).or_default().push(id.clone());
         self.by_type.entry(type_tag).or_default().push(id.clone());
         self.by_time.entry(ts).or_default().push(id);
@@ -83,7 +101,11 @@ impl ReceiptIndex {
 
     /// Return all receipts whose action type matches `action_type`.
     pub fn by_type(&self, action_type: &ActionType) -> Vec<&ActionReceipt> {
-        let tag = action_type.as_tag();
+        self.by_tag(action_type.as_tag())
+    }
+
+    /// Return all receipts whose action-type tag is `tag`.
+    pub fn by_tag(&self, tag: &str) -> Vec<&ActionReceipt> {
         self.by_type
             .get(tag)
             .map(|ids| ids.iter().filter_map(|id| self.by_id.get(id)).collect())
@@ -98,6 +120,51 @@ impl ReceiptIndex {
             .collect()
     }
 
+    /// Return every receipt that chains from `root`, directly or
+    /// transitively, in breadth-first order. `root` itself is not included.
+    pub fn chained_to(&self, root: &ReceiptId) -> Vec<&ActionReceipt> {
+        let mut seen = HashSet::new();
+        let mut out = Vec::new();
+        let mut next = vec![root];
+        while !next.is_empty() {
+            let mut following = Vec::new();
+            for id in next {
+                for successor in self.by_previous.get(id).into_iter().flatten() {
+                    if !seen.insert(successor) {
+                        continue;
+                    }
+                    if let Some(receipt) = self.by_id.get(successor) {
+                        out.push(receipt);
+                    }
+                    following.push(successor);
+                }
+            }
+            next = following;
+        }
+        out
+    }
+
+    /// Number of receipts recorded for `actor`, without collecting them.
+    pub fn count_by_actor(&self, actor: &IdentityId) -> usize {
+        self.by_actor.get(actor).map_or(0, Vec::len)
+    }
+
+    /// Number of receipts with action-type tag `tag`.
+    pub fn count_by_tag(&self, tag: &str) -> usize {
+        self.by_type.get(tag).map_or(0, Vec::len)
+    }
+
+    /// Number of receipts whose timestamp falls within `[from, to]`.
+    pub fn count_in_time_range(&self, from: u64, to: u64) -> usize {
+        if from > to {
+            return 0;
+        }
+        self.by_time
+            .range(from..=to)
+            .map(|(_ts, ids)| ids.len())
+            .sum()
+    }
+
     /// Return the total number of receipts stored.
     pub fn len(&self) -> usize {
         self.by_id.len()
@@ -220,7 +287,7 @@ impl Default for TrustIndex {
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -348,6 +415,46 @@ mod tests {
         assert!(none.is_empty());
     }
 
+    #[test]
+    fn test_receipt_index_chained_to() {
+        let anchor = IdentityAnchor::new(None);
+        let mut idx = ReceiptIndex::new();
+
+        let root = make_receipt(&anchor, ActionType::Decision, "root");
+        let child = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("child"),
+        )
+        .chain_to(root.id.clone())
+        .sign(anchor.signing_key())
+        .unwrap();
+        let grandchild = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Mutation,
+            ActionContent::new("grandchild"),
+        )
+        .chain_to(child.id.clone())
+        .sign(anchor.signing_key())
+        .unwrap();
+        let unrelated = make_receipt(&anchor, ActionType::Decision, "unrelated");
+
+        let root_id = root.id.clone();
+        let child_id = child.id.clone();
+        for r in [root, child, grandchild.clone(), unrelated] {
+            idx.insert(r);
+        }
+
+        let chain = idx.chained_to(&root_id);
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].id, child_id);
+        assert_eq!(chain[1].id, grandchild.id);
+        assert!(idx.chained_to(&grandchild.id).is_empty());
+        assert_eq!(idx.count_by_actor(&anchor.id()), 4);
+        assert_eq!(idx.count_by_tag("mutation"), 1);
+        assert_eq!(idx.count_in_time_range(1, 0), 0);
+    }
+
     #[test]
     fn test_receipt_index_empty() {
         let idx = ReceiptIndex::new();
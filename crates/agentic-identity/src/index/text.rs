@@ -0,0 +1,454 @@
+//! Full-text inverted index over receipts and trust grants.
+//!
+//! [`TextIndex`] maps each term to the documents containing it. A
+//! receipt's terms come from its action description and its `data`
+//! payload (keys and scalar values); a grant's from its capability URIs.
+//! Terms are produced by [`tokenize`]: lowercased and split on anything
+//! that is not a letter or digit, so `deploy:staging` yields `deploy` and
+//! `staging`.
+//!
+//! On disk the index is an append-only NDJSON journal of `put` and
+//! `remove` entries, replayed on [`TextIndex::load`]. Stores append one
+//! line per save, so keeping the index current costs nothing proportional
+//! to its size; [`TextIndex::compact`] rewrites the journal as one `put`
+//! per document. A torn final line (a crash mid-append) is ignored.
+//!
+//! The journal can fall behind files written another way, such as
+//! transactions or a copy into the store directory; the stores reconcile it
+//! against their directory listing when they open it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::ops::Bound;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{IdentityError, Result};
+use crate::receipt::ActionReceipt;
+use crate::storage::atomic::write_atomic;
+use crate::trust::TrustGrant;
+
+/// One journal line.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    /// Index `id` under `terms`, replacing any earlier entry.
+    Put { id: String, terms: BTreeSet<String> },
+    /// Drop `id` from the index.
+    Remove { id: String },
+}
+
+/// Inverted index from terms to document IDs.
+#[derive(Debug, Clone, Default)]
+pub struct TextIndex {
+    /// Document ID → its terms, so a replaced document can be unindexed.
+    docs: BTreeMap<String, BTreeSet<String>>,
+    /// Term → IDs of the documents containing it.
+    postings: BTreeMap<String, BTreeSet<String>>,
+    /// Journal lines replayed by `load`, used to decide when to compact.
+    journal_len: usize,
+}
+
+impl TextIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index document `id` under `terms`, replacing any earlier terms.
+    pub fn insert(&mut self, id: impl Into<String>, terms: BTreeSet<String>) {
+        let id = id.into();
+        self.remove(&id);
+        for term in &terms {
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .insert(id.clone());
+        }
+        self.docs.insert(id, terms);
+    }
+
+    /// Drop document `id`. Returns whether it was indexed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let Some(terms) = self.docs.remove(id) else {
+            return false;
+        };
+        for term in terms {
+            if let Some(ids) = self.postings.get_mut(&term) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    /// The terms document `id` is indexed under.
+    pub fn terms(&self, id: &str) -> Option<&BTreeSet<String>> {
+        self.docs.get(id)
+    }
+
+    /// Whether document `id` is indexed.
+    pub fn contains(&self, id: &str) -> bool {
+        self.docs.contains_key(id)
+    }
+
+    /// IDs of every indexed document, sorted.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.docs.keys().map(String::as_str)
+    }
+
+    /// Documents containing exactly `term`.
+    pub fn search(&self, term: &str) -> BTreeSet<&str> {
+        self.postings
+            .get(term)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Documents containing a term that starts with `prefix`.
+    pub fn search_prefix(&self, prefix: &str) -> BTreeSet<&str> {
+        self.postings
+            .range::<str, _>((Bound::Included(prefix), Bound::Unbounded))
+            .take_while(|(term, _)| term.starts_with(prefix))
+            .flat_map(|(_, ids)| ids.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// Number of indexed documents.
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    /// Return `true` when no documents are indexed.
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Whether the journal this index was loaded from has grown well past
+    /// one line per document and is worth compacting.
+    pub fn needs_compaction(&self) -> bool {
+        self.journal_len > 2 * self.docs.len() + 64
+    }
+
+    // ── Persistence ───────────────────────────────────────────────────────────
+
+    /// Replay the journal at `path`. A missing file is an empty index.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if a line other than the
+    /// last cannot be parsed, or `IdentityError::Io` for filesystem errors.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut index = Self::new();
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        for (n, line) in lines.iter().enumerate() {
+            let entry = match serde_json::from_str::<JournalEntry>(line) {
+                Ok(entry) => entry,
+                Err(_) if n + 1 == lines.len() && !text.ends_with('\n') => break,
+                Err(e) => {
+                    return Err(IdentityError::InvalidFileFormat(format!(
+                        "failed to parse text index {} line {}: {e}",
+                        path.display(),
+                        n + 1
+                    )))
+                }
+            };
+            match entry {
+                JournalEntry::Put { id, terms } => index.insert(id, terms),
+                JournalEntry::Remove { id } => {
+                    index.remove(&id);
+                }
+            }
+            index.journal_len += 1;
+        }
+        Ok(index)
+    }
+
+    /// Append a `put` of `id` under `terms` to the journal at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors.
+    pub fn append_put(path: &Path, id: &str, terms: &BTreeSet<String>) -> Result<()> {
+        append(
+            path,
+            &JournalEntry::Put {
+                id: id.to_string(),
+                terms: terms.clone(),
+            },
+        )
+    }
+
+    /// Append a `remove` of `id` to the journal at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors.
+    pub fn append_remove(path: &Path, id: &str) -> Result<()> {
+        append(path, &JournalEntry::Remove { id: id.to_string() })
+    }
+
+    /// Load the journal at `path` and reconcile it with `stored`, the IDs
+    /// actually in the store: documents no longer stored are removed, and
+    /// stored documents missing from the journal are indexed under
+    /// `terms_of` (skipped if it returns `None`, e.g. for an unreadable
+    /// file). A journal that cannot be parsed is rebuilt from scratch.
+    pub(crate) fn open_synced<F>(
+        path: &Path,
+        stored: BTreeSet<String>,
+        mut terms_of: F,
+    ) -> Result<Self>
+    where
+        F: FnMut(&str) -> Option<BTreeSet<String>>,
+    {
+        let (mut index, rebuild) = match Self::load(path) {
+            Ok(index) => (index, false),
+            Err(IdentityError::InvalidFileFormat(_)) => (Self::new(), true),
+            Err(e) => return Err(e),
+        };
+
+        let gone: Vec<String> = index
+            .docs
+            .keys()
+            .filter(|id| !stored.contains(*id))
+            .cloned()
+            .collect();
+        for id in gone {
+            index.remove(&id);
+            if !rebuild {
+                Self::append_remove(path, &id)?;
+                index.journal_len += 1;
+            }
+        }
+        for id in stored {
+            if index.contains(&id) {
+                continue;
+            }
+            let Some(terms) = terms_of(&id) else {
+                continue;
+            };
+            if !rebuild {
+                Self::append_put(path, &id, &terms)?;
+                index.journal_len += 1;
+            }
+            index.insert(id, terms);
+        }
+
+        if rebuild || index.needs_compaction() {
+            index.compact(path)?;
+        }
+        Ok(index)
+    }
+
+    /// Rewrite the journal at `path` as one `put` per document.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors.
+    pub fn compact(&mut self, path: &Path) -> Result<()> {
+        let mut out = String::new();
+        for (id, terms) in &self.docs {
+            let entry = JournalEntry::Put {
+                id: id.clone(),
+                terms: terms.clone(),
+            };
+            out.push_str(&encode(&entry)?);
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(path, out.as_bytes())?;
+        self.journal_len = self.docs.len();
+        Ok(())
+    }
+}
+
+fn encode(entry: &JournalEntry) -> Result<String> {
+    let mut line = serde_json::to_string(entry)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    line.push('\n');
+    Ok(line)
+}
+
+fn append(path: &Path, entry: &JournalEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // One `write` per line, so concurrent appenders do not interleave.
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(encode(entry)?.as_bytes())?;
+    Ok(())
+}
+
+// ── Terms ─────────────────────────────────────────────────────────────────────
+
+/// Reduce `text` to lowercase terms, split on anything that is not a
+/// letter or digit.
+pub fn tokenize(text: &str) -> BTreeSet<String> {
+    let mut terms = BTreeSet::new();
+    add_terms(text, &mut terms);
+    terms
+}
+
+/// Terms of a receipt: its action description and `data` payload.
+pub fn receipt_terms(receipt: &ActionReceipt) -> BTreeSet<String> {
+    let mut terms = tokenize(&receipt.action.description);
+    if let Some(data) = &receipt.action.data {
+        add_value_terms(data, &mut terms);
+    }
+    terms
+}
+
+/// Terms of a trust grant: its capability URIs.
+pub fn grant_terms(grant: &TrustGrant) -> BTreeSet<String> {
+    let mut terms = BTreeSet::new();
+    for capability in &grant.capabilities {
+        add_terms(&capability.uri, &mut terms);
+    }
+    terms
+}
+
+fn add_terms(text: &str, terms: &mut BTreeSet<String>) {
+    terms.extend(
+        text.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_string),
+    );
+}
+
+fn add_value_terms(value: &Value, terms: &mut BTreeSet<String>) {
+    match value {
+        Value::Null => {}
+        Value::Bool(b) => add_terms(&b.to_string(), terms),
+        Value::Number(n) => add_terms(&n.to_string(), terms),
+        Value::String(s) => add_terms(s, terms),
+        Value::Array(items) => items.iter().for_each(|v| add_value_terms(v, terms)),
+        Value::Object(map) => {
+            for (key, v) in map {
+                add_terms(key, terms);
+                add_value_terms(v, terms);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::action::{ActionContent, ActionType};
+    use crate::receipt::receipt::ReceiptBuilder;
+    use serde_json::json;
+
+    fn terms(words: &[&str]) -> BTreeSet<String> {
+        words.iter().map(|w| w.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tokenize_splits_on_punctuation() {
+        assert_eq!(
+            tokenize("Deploy:Staging — read_only, v2"),
+            terms(&["deploy", "only", "read", "staging", "v2"])
+        );
+    }
+
+    #[test]
+    fn test_receipt_terms_include_data_payload() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Mutation,
+            ActionContent::with_data(
+                "Rolled out release",
+                json!({"target": {"env": "prod"}, "hosts": ["web-1"], "replicas": 3}),
+            ),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        let found = receipt_terms(&receipt);
+        for term in [
+            "rolled", "release", "target", "env", "prod", "web", "1", "3",
+        ] {
+            assert!(found.contains(term), "missing {term}");
+        }
+    }
+
+    #[test]
+    fn test_insert_replace_remove_and_search() {
+        let mut index = TextIndex::new();
+        index.insert("a", terms(&["deploy", "staging"]));
+        index.insert("b", terms(&["deploy", "prod"]));
+        assert_eq!(index.search("deploy").len(), 2);
+        assert_eq!(
+            index.search_prefix("st").into_iter().collect::<Vec<_>>(),
+            vec!["a"]
+        );
+
+        index.insert("a", terms(&["read"]));
+        assert!(index.search("staging").is_empty());
+        assert_eq!(index.search("deploy").len(), 1);
+
+        assert!(index.remove("b"));
+        assert!(!index.remove("b"));
+        assert!(index.search("deploy").is_empty());
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_journal_replays_and_compacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index").join("text.ndjson");
+
+        TextIndex::append_put(&path, "a", &terms(&["deploy"])).unwrap();
+        TextIndex::append_put(&path, "b", &terms(&["read"])).unwrap();
+        TextIndex::append_put(&path, "a", &terms(&["write"])).unwrap();
+        TextIndex::append_remove(&path, "b").unwrap();
+
+        let mut index = TextIndex::load(&path).unwrap();
+        assert_eq!(index.ids().collect::<Vec<_>>(), vec!["a"]);
+        assert_eq!(index.terms("a"), Some(&terms(&["write"])));
+
+        index.compact(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert_eq!(TextIndex::load(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_torn_final_line_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("text.ndjson");
+        TextIndex::append_put(&path, "a", &terms(&["deploy"])).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(br#"{"op":"put","id":"b","ter"#).unwrap();
+
+        let index = TextIndex::load(&path).unwrap();
+        assert_eq!(index.len(), 1);
+        assert!(TextIndex::load(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+
+        std::fs::write(&path, "garbage\n{\"op\":\"remove\",\"id\":\"a\"}\n").unwrap();
+        assert!(TextIndex::load(&path).is_err());
+    }
+}
@@ -4,6 +4,11 @@
 //! trust relationships, temporal continuity, identity inheritance,
 //! competence proofs, and negative capability proofs
 //! for AI agents operating via MCP.
+//!
+//! Key generation, `.aid` file encryption and the receipt/grant builders
+//! sit behind the default `signing` feature. Verifier-only consumers build
+//! with `default-features = false, features = ["verify-only"]` and keep the
+//! types, deserialization and every verify function.
 
 pub mod competence;
 pub mod continuity;
@@ -17,25 +22,32 @@ pub mod query;
 pub mod receipt;
 pub mod spawn;
 pub mod storage;
+pub mod text;
 pub mod time;
 pub mod trust;
 
 // Re-export primary types
 pub use error::{IdentityError, Result};
-pub use identity::{IdentityAnchor, IdentityDocument, IdentityId};
+pub use identity::{
+    IdentityAnchor, IdentityDocument, IdentityId, MultisigAnchor, MultisigDocument,
+    SignatureAlgorithm,
+};
 pub use receipt::{ActionContent, ActionReceipt, ActionType, ReceiptId, ReceiptVerification};
-pub use trust::{Capability, TrustConstraints, TrustGrant, TrustId, TrustVerification};
+pub use trust::{
+    Capability, DelegatedTrustGrant, ImplicationPolicy, IssuerAllowlist, TimeSource, TimeToken,
+    TrustConstraints, TrustGrant, TrustId, TrustVerification, UsageContext,
+};
 
 // Re-export continuity types
 pub use continuity::{
     ContinuityAnchor, ContinuityClaim, ContinuityResult, ContinuityState, ContinuityVerification,
-    ExperienceEvent, ExperienceId, ExperienceType, Gap, GapType,
+    ExperienceEvent, ExperienceId, ExperienceType, Gap, GapCause, GapType,
 };
 
 // Re-export spawn types
 pub use spawn::{
-    Lineage, LineageVerification, SpawnConstraints, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord,
-    SpawnType,
+    BudgetResource, Lineage, LineageProof, LineageVerification, SpawnAuthority, SpawnConstraints,
+    SpawnConsumption, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord, SpawnType,
 };
 
 // Re-export competence types
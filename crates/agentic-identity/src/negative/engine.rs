@@ -1,9 +1,11 @@
 //! Negative capability engine — impossibility proofs, declarations, verification.
 
 use ed25519_dalek::VerifyingKey;
+use serde::Serialize;
 use sha2::{Digest, Sha256};
 
-use crate::crypto::signing;
+use crate::crypto::canonical;
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::error::{IdentityError, Result};
 use crate::identity::{IdentityAnchor, IdentityId};
 use crate::spawn::SpawnRecord;
@@ -86,7 +88,7 @@ pub fn prove_cannot(
                     !spawn_record
                         .authority_granted
                         .iter()
-                        .any(|g| g.uri == c.uri)
+                        .any(|g| capability_uri_covers(&g.uri, &c.uri))
                 })
                 .map(|c| c.uri.clone())
                 .collect();
@@ -179,6 +181,49 @@ pub fn prove_cannot(
     )))
 }
 
+// ---------------------------------------------------------------------------
+// prove_declared
+// ---------------------------------------------------------------------------
+
+/// Generate a negative capability proof from the identity's own voluntary
+/// declarations.
+///
+/// The first declaration by `identity` whose restrictions cover `capability`
+/// becomes the evidence. Declarations by other identities are ignored.
+pub fn prove_declared(
+    identity: &IdentityAnchor,
+    capability: &str,
+    declarations: &[NegativeDeclaration],
+) -> Result<NegativeCapabilityProof> {
+    let identity_id = identity.id();
+    let declaration = declarations
+        .iter()
+        .find(|d| {
+            d.identity == identity_id
+                && d.cannot_do
+                    .iter()
+                    .any(|c| capability_uri_covers(c, capability))
+        })
+        .ok_or_else(|| {
+            IdentityError::TrustNotGranted(format!(
+                "Cannot prove impossibility: no declaration excludes '{}'",
+                capability
+            ))
+        })?;
+
+    build_proof(
+        identity,
+        capability,
+        ImpossibilityReason::VoluntaryDeclaration {
+            declaration_id: declaration.declaration_id.clone(),
+        },
+        NegativeEvidence::Declaration {
+            declaration_id: declaration.declaration_id.clone(),
+        },
+        crate::time::now_micros(),
+    )
+}
+
 /// Helper: build a signed negative proof.
 fn build_proof(
     identity: &IdentityAnchor,
@@ -200,7 +245,11 @@ fn build_proof(
     let id_encoded = bs58::encode(&id_hash[..16]).into_string();
     let proof_id = NegativeProofId(format!("aneg_{id_encoded}"));
 
-    let signature = signing::sign_to_base64(identity.signing_key(), proof_hash.as_bytes());
+    let signature = signing::sign_in_domain(
+        identity.signing_key(),
+        SignatureDomain::NegativeProof,
+        proof_hash.as_bytes(),
+    );
 
     Ok(NegativeCapabilityProof {
         proof_id,
@@ -212,6 +261,7 @@ fn build_proof(
         valid_until: None,
         proof_hash,
         signature,
+        signature_version: signing::SIGNATURE_VERSION,
     })
 }
 
@@ -228,9 +278,14 @@ pub fn verify_negative_proof(
     let mut errors = Vec::new();
 
     // Verify signature
-    let sig_valid =
-        signing::verify_from_base64(verifying_key, proof.proof_hash.as_bytes(), &proof.signature)
-            .is_ok();
+    let sig_valid = signing::verify_versioned(
+        verifying_key,
+        SignatureDomain::NegativeProof,
+        proof.signature_version,
+        proof.proof_hash.as_bytes(),
+        &proof.signature,
+    )
+    .is_ok();
 
     if !sig_valid {
         errors.push("Signature verification failed".to_string());
@@ -375,17 +430,6 @@ pub fn declare_cannot(
     let id_encoded = bs58::encode(&id_hash[..16]).into_string();
     let declaration_id = DeclarationId(format!("adecl_{id_encoded}"));
 
-    // Sign the declaration
-    let sign_input = format!(
-        "negdecl:{}:{}:{}:{}:{}",
-        declaration_id.0,
-        identity.id().0,
-        capabilities.join(","),
-        reason,
-        permanent
-    );
-    let signature = signing::sign_to_base64(identity.signing_key(), sign_input.as_bytes());
-
     // Collect witness signatures
     let witness_sigs: Vec<crate::receipt::witness::WitnessSignature> = witnesses
         .iter()
@@ -398,7 +442,7 @@ pub fn declare_cannot(
         })
         .collect();
 
-    Ok(NegativeDeclaration {
+    let mut declaration = NegativeDeclaration {
         declaration_id,
         identity: identity.id(),
         cannot_do: capabilities,
@@ -406,8 +450,74 @@ pub fn declare_cannot(
         declared_at: now,
         permanent,
         witnesses: witness_sigs,
-        signature,
-    })
+        signature: String::new(),
+        signature_version: signing::CANONICAL_SIGNATURE_VERSION,
+    };
+
+    // Sign the declaration
+    let sign_input = declaration_signing_input(&declaration)?;
+    declaration.signature = signing::sign_in_domain(
+        identity.signing_key(),
+        SignatureDomain::NegativeDeclaration,
+        sign_input.as_bytes(),
+    );
+
+    Ok(declaration)
+}
+
+/// Fields of a declaration signed at
+/// [`CANONICAL_SIGNATURE_VERSION`](signing::CANONICAL_SIGNATURE_VERSION).
+#[derive(Serialize)]
+struct DeclarationPayload<'a> {
+    declaration_id: &'a DeclarationId,
+    identity: &'a IdentityId,
+    cannot_do: &'a [String],
+    reason: &'a str,
+    declared_at: u64,
+    permanent: bool,
+}
+
+/// The message the declaring identity signs. Older declarations did not
+/// cover `declared_at`.
+fn declaration_signing_input(declaration: &NegativeDeclaration) -> Result<String> {
+    if declaration.signature_version >= signing::CANONICAL_SIGNATURE_VERSION {
+        return canonical::to_canonical_string(&DeclarationPayload {
+            declaration_id: &declaration.declaration_id,
+            identity: &declaration.identity,
+            cannot_do: &declaration.cannot_do,
+            reason: &declaration.reason,
+            declared_at: declaration.declared_at,
+            permanent: declaration.permanent,
+        });
+    }
+    Ok(format!(
+        "negdecl:{}:{}:{}:{}:{}",
+        declaration.declaration_id.0,
+        declaration.identity.0,
+        declaration.cannot_do.join(","),
+        declaration.reason,
+        declaration.permanent
+    ))
+}
+
+/// Verify a declaration's signature with the declaring identity's public
+/// key (base64), which must be the key `declaration.identity` derives from.
+pub fn verify_declaration(declaration: &NegativeDeclaration, public_key: &str) -> Result<()> {
+    let key = crate::identity::anchor::decode_public_key(public_key)?;
+    if IdentityId::from_verifying_key(&key) != declaration.identity {
+        return Err(IdentityError::InvalidKey(format!(
+            "key does not belong to {}",
+            declaration.identity
+        )));
+    }
+    let input = declaration_signing_input(declaration)?;
+    signing::verify_versioned(
+        &key,
+        SignatureDomain::NegativeDeclaration,
+        declaration.signature_version,
+        input.as_bytes(),
+        &declaration.signature,
+    )
 }
 
 // ---------------------------------------------------------------------------
@@ -460,7 +570,10 @@ pub fn get_impossibilities(
 
     if let Some(record) = my_spawn {
         for cap in &record.authority_ceiling {
-            let in_granted = record.authority_granted.iter().any(|g| g.uri == cap.uri);
+            let in_granted = record
+                .authority_granted
+                .iter()
+                .any(|g| capability_uri_covers(&g.uri, &cap.uri));
             if !in_granted {
                 impossibilities.push((
                     cap.uri.clone(),
@@ -476,10 +589,10 @@ pub fn get_impossibilities(
 }
 
 // ---------------------------------------------------------------------------
-// Tests (12 scenarios)
+// Tests (13 scenarios)
 // ---------------------------------------------------------------------------
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -520,12 +633,19 @@ mod tests {
                 max_descendants: None,
                 can_spawn: true,
                 authority_decay: None,
+                max_receipts: None,
+                max_sub_spawns: None,
+                max_cost: None,
             },
             parent_signature: "test_sig".to_string(),
             child_acknowledgment: None,
+            signature_version: crate::crypto::signing::SIGNATURE_VERSION,
+            schema_version: crate::storage::migrate::SCHEMA_VERSION,
             terminated: false,
             terminated_at: None,
             termination_reason: None,
+            child_file: None,
+            consumption: Default::default(),
         }
     }
 
@@ -679,6 +799,34 @@ mod tests {
         assert!(decl.permanent);
     }
 
+    #[test]
+    fn test_verify_declaration() {
+        let identity = test_identity();
+        let mut decl = declare_cannot(
+            &identity,
+            vec!["deploy:*".to_string()],
+            "read-only agent",
+            false,
+            vec![],
+        )
+        .unwrap();
+        assert!(verify_declaration(&decl, &identity.public_key_base64()).is_ok());
+
+        // The declaration time is signed.
+        let mut backdated = decl.clone();
+        backdated.declared_at -= 1;
+        assert!(verify_declaration(&backdated, &identity.public_key_base64()).is_err());
+
+        let other = IdentityAnchor::new(None);
+        assert!(matches!(
+            verify_declaration(&decl, &other.public_key_base64()),
+            Err(IdentityError::InvalidKey(_))
+        ));
+
+        decl.cannot_do.push("admin:*".to_string());
+        assert!(verify_declaration(&decl, &identity.public_key_base64()).is_err());
+    }
+
     // 10. Lineage proof walks entire ancestry
     #[test]
     fn test_lineage_proof_walks_ancestry() {
@@ -753,4 +901,33 @@ mod tests {
         let has_email = impossibilities.iter().any(|(cap, _)| cap == "email:*");
         assert!(has_email, "Expected email:* in impossibilities");
     }
+
+    // 13. Declarations prove impossibility for their own identity only
+    #[test]
+    fn test_prove_declared() {
+        let identity = test_identity();
+        let other = test_identity();
+        let declaration = declare_cannot(
+            &identity,
+            vec!["deploy:*".to_string()],
+            "change freeze",
+            false,
+            vec![],
+        )
+        .unwrap();
+        let declarations = vec![declaration.clone()];
+
+        let proof = prove_declared(&identity, "deploy:production", &declarations).unwrap();
+        assert_eq!(
+            proof.reason,
+            ImpossibilityReason::VoluntaryDeclaration {
+                declaration_id: declaration.declaration_id.clone()
+            }
+        );
+        let verification = verify_negative_proof(&proof, identity.verifying_key()).unwrap();
+        assert!(verification.is_valid);
+
+        assert!(prove_declared(&identity, "email:send", &declarations).is_err());
+        assert!(prove_declared(&other, "deploy:production", &declarations).is_err());
+    }
 }
@@ -18,5 +18,5 @@ pub use types::{
 
 pub use engine::{
     declare_cannot, get_impossibilities, is_impossible, list_declarations, prove_cannot,
-    verify_negative_proof,
+    prove_declared, verify_declaration, verify_negative_proof,
 };
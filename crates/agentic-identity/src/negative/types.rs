@@ -37,6 +37,15 @@ pub enum ImpossibilityReason {
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NegativeProofId(pub String);
 
+impl NegativeProofId {
+    /// Parse a negative proof ID from untrusted input, checking its prefix
+    /// and charset.
+    pub fn parse(s: &str) -> crate::error::Result<Self> {
+        crate::identity::anchor::validate_prefixed_id(s, "aneg_")?;
+        Ok(Self(s.to_string()))
+    }
+}
+
 impl std::fmt::Display for NegativeProofId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -55,6 +64,12 @@ pub struct NegativeCapabilityProof {
     pub valid_until: Option<u64>,
     pub proof_hash: String,
     pub signature: String,
+    /// Absent on proofs signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -113,6 +128,12 @@ pub struct NegativeDeclaration {
     pub permanent: bool,
     pub witnesses: Vec<WitnessSignature>,
     pub signature: String,
+    /// Absent on declarations signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,403 @@
+//! Structured receipt filters with an index-backed execution plan.
+//!
+//! A [`ReceiptFilter`] is the JSON-friendly form of a receipt query: it
+//! filters by actor, action-type tag, time range, values inside the
+//! action's `data`, and membership in the chain following a receipt.
+//! Unknown fields are rejected, so a misspelt filter fails instead of
+//! silently matching everything.
+//!
+//! ```json
+//! {
+//!   "actor": "aid_...",
+//!   "action_type": "deploy",
+//!   "since": 1700000000000000,
+//!   "data": { "target.env": "prod", "replicas": 3 },
+//!   "chained_to": "arec_...",
+//!   "order": "oldest_first",
+//!   "limit": 50
+//! }
+//! ```
+//!
+//! [`ReceiptFilter::plan`] sizes every index the filter can use and seeds
+//! from the smallest candidate set; the remaining conditions are checked
+//! against those candidates only. [`QueryPlan`] reports the choice, so a
+//! caller can see why a query was slow.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::SortOrder;
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+use crate::index::ReceiptIndex;
+use crate::receipt::{ActionReceipt, ReceiptId};
+
+/// A structured receipt query. Unset fields impose no restriction; set
+/// fields are combined with logical AND.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReceiptFilter {
+    /// Only receipts recorded by this identity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actor: Option<IdentityId>,
+    /// Only receipts with this action-type tag, e.g. `decision` or a
+    /// custom tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action_type: Option<String>,
+    /// Only receipts at or after this time (microseconds since epoch).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<u64>,
+    /// Only receipts at or before this time (microseconds since epoch).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+    /// Values the action's `data` must hold, keyed by dotted path
+    /// (`target.id`, `items.0.name`). Values compare as JSON, so `3` and
+    /// `"3"` differ; a receipt without `data` matches no path.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub data: BTreeMap<String, Value>,
+    /// Only receipts that chain from this receipt, directly or through
+    /// later links. The receipt itself is not included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chained_to: Option<ReceiptId>,
+    /// Result order by timestamp.
+    pub order: SortOrder,
+    /// Maximum number of receipts to return, applied after sorting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// The index a [`QueryPlan`] draws its first candidates from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexSeed {
+    /// The chain following `chained_to`.
+    Chain,
+    /// The actor index.
+    Actor,
+    /// The action-type index.
+    ActionType,
+    /// The timestamp index, over `[since, until]`.
+    TimeRange,
+    /// Every receipt; the filter names nothing indexed.
+    FullScan,
+}
+
+impl IndexSeed {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Chain => "chained_to",
+            Self::Actor => "actor",
+            Self::ActionType => "action_type",
+            Self::TimeRange => "time_range",
+            Self::FullScan => "full_scan",
+        }
+    }
+}
+
+/// How a [`ReceiptFilter`] runs against a [`ReceiptIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryPlan {
+    /// Index the candidates come from.
+    pub seed: IndexSeed,
+    /// Receipts drawn from that index.
+    pub candidates: usize,
+    /// Conditions checked against each candidate afterwards, in order.
+    pub residual: Vec<String>,
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} candidates)", self.seed.as_str(), self.candidates)?;
+        if !self.residual.is_empty() {
+            write!(f, ", then {}", self.residual.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl ReceiptFilter {
+    /// An empty filter, matching every receipt.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a filter from a JSON object.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` for unknown fields or
+    /// values of the wrong type.
+    pub fn from_json(value: Value) -> Result<Self> {
+        serde_json::from_value(value)
+            .map_err(|e| IdentityError::SerializationError(format!("invalid receipt filter: {e}")))
+    }
+
+    /// Choose the index to seed from: whichever yields the fewest
+    /// candidates, preferring the chain, then actor, then action type,
+    /// then time range when sizes tie.
+    pub fn plan(&self, index: &ReceiptIndex) -> QueryPlan {
+        let (from, to) = self.time_bounds();
+        let mut options = Vec::new();
+        if let Some(root) = &self.chained_to {
+            options.push((IndexSeed::Chain, index.chained_to(root).len()));
+        }
+        if let Some(actor) = &self.actor {
+            options.push((IndexSeed::Actor, index.count_by_actor(actor)));
+        }
+        if let Some(tag) = &self.action_type {
+            options.push((IndexSeed::ActionType, index.count_by_tag(tag)));
+        }
+        if self.since.is_some() || self.until.is_some() {
+            options.push((IndexSeed::TimeRange, index.count_in_time_range(from, to)));
+        }
+        // `min_by_key` keeps the first of equal elements.
+        let (seed, candidates) = options
+            .into_iter()
+            .min_by_key(|(_, n)| *n)
+            .unwrap_or((IndexSeed::FullScan, index.len()));
+
+        let mut residual = Vec::new();
+        if self.chained_to.is_some() && seed != IndexSeed::Chain {
+            residual.push("chained_to".to_string());
+        }
+        if self.actor.is_some() && seed != IndexSeed::Actor {
+            residual.push("actor".to_string());
+        }
+        if self.action_type.is_some() && seed != IndexSeed::ActionType {
+            residual.push("action_type".to_string());
+        }
+        if (self.since.is_some() || self.until.is_some()) && seed != IndexSeed::TimeRange {
+            residual.push("time_range".to_string());
+        }
+        residual.extend(self.data.keys().map(|path| format!("data.{path}")));
+
+        QueryPlan {
+            seed,
+            candidates,
+            residual,
+        }
+    }
+
+    /// Run the filter against `index`, sorted by [`order`](Self::order) and
+    /// capped at [`limit`](Self::limit).
+    pub fn execute<'a>(&self, index: &'a ReceiptIndex) -> Vec<&'a ActionReceipt> {
+        self.execute_with_plan(index).1
+    }
+
+    /// As [`execute`](Self::execute), also returning the plan it followed.
+    pub fn execute_with_plan<'a>(
+        &self,
+        index: &'a ReceiptIndex,
+    ) -> (QueryPlan, Vec<&'a ActionReceipt>) {
+        let plan = self.plan(index);
+        let (from, to) = self.time_bounds();
+
+        let mut receipts = match plan.seed {
+            IndexSeed::Chain => {
+                let root = self.chained_to.as_ref().expect("planned from chained_to");
+                index.chained_to(root)
+            }
+            IndexSeed::Actor => index.by_actor(self.actor.as_ref().expect("planned from actor")),
+            IndexSeed::ActionType => {
+                index.by_tag(self.action_type.as_deref().expect("planned from type"))
+            }
+            IndexSeed::TimeRange if from > to => Vec::new(),
+            IndexSeed::TimeRange => index.by_time_range(from, to),
+            IndexSeed::FullScan => index.by_time_range(0, u64::MAX),
+        };
+
+        if plan.seed != IndexSeed::Chain {
+            if let Some(root) = &self.chained_to {
+                let chain: HashSet<&ReceiptId> =
+                    index.chained_to(root).into_iter().map(|r| &r.id).collect();
+                receipts.retain(|r| chain.contains(&r.id));
+            }
+        }
+        receipts.retain(|r| self.matches_fields(r, from, to));
+
+        match self.order {
+            SortOrder::NewestFirst => receipts.sort_by_key(|r| std::cmp::Reverse(r.timestamp)),
+            SortOrder::OldestFirst => receipts.sort_by_key(|r| r.timestamp),
+        }
+        if let Some(limit) = self.limit {
+            receipts.truncate(limit);
+        }
+        (plan, receipts)
+    }
+
+    /// Whether `receipt` passes every condition except `chained_to`, which
+    /// depends on the other receipts in the index.
+    pub fn matches(&self, receipt: &ActionReceipt) -> bool {
+        let (from, to) = self.time_bounds();
+        self.matches_fields(receipt, from, to)
+    }
+
+    fn matches_fields(&self, receipt: &ActionReceipt, from: u64, to: u64) -> bool {
+        self.actor.as_ref().is_none_or(|a| &receipt.actor == a)
+            && self
+                .action_type
+                .as_deref()
+                .is_none_or(|t| receipt.action_type.as_tag() == t)
+            && (from..=to).contains(&receipt.timestamp)
+            && self.data.iter().all(|(path, expected)| {
+                receipt
+                    .action
+                    .data
+                    .as_ref()
+                    .and_then(|data| lookup(data, path))
+                    .is_some_and(|found| found == expected)
+            })
+    }
+
+    fn time_bounds(&self) -> (u64, u64) {
+        (self.since.unwrap_or(0), self.until.unwrap_or(u64::MAX))
+    }
+}
+
+/// Follow a dotted path into `value`; numeric segments index arrays.
+fn lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::action::{ActionContent, ActionType};
+    use crate::receipt::receipt::ReceiptBuilder;
+    use serde_json::json;
+
+    fn receipt(
+        anchor: &IdentityAnchor,
+        tag: &str,
+        data: Value,
+        previous: Option<&ActionReceipt>,
+    ) -> ActionReceipt {
+        let mut builder = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Custom(tag.into()),
+            ActionContent::with_data(tag, data),
+        );
+        if let Some(previous) = previous {
+            builder = builder.chain_to(previous.id.clone());
+        }
+        builder.sign(anchor.signing_key()).unwrap()
+    }
+
+    #[test]
+    fn test_filter_from_json_rejects_unknown_fields() {
+        let filter = ReceiptFilter::from_json(json!({
+            "action_type": "deploy",
+            "data": {"target.env": "prod"},
+            "order": "oldest_first",
+        }))
+        .unwrap();
+        assert_eq!(filter.action_type.as_deref(), Some("deploy"));
+        assert_eq!(filter.order, SortOrder::OldestFirst);
+
+        assert!(ReceiptFilter::from_json(json!({"acter": "aid_x"})).is_err());
+        assert!(ReceiptFilter::from_json(json!({"since": "yesterday"})).is_err());
+    }
+
+    #[test]
+    fn test_filter_matches_data_paths() {
+        let anchor = IdentityAnchor::new(None);
+        let r = receipt(
+            &anchor,
+            "deploy",
+            json!({"target": {"env": "prod"}, "hosts": ["a", "b"], "replicas": 3}),
+            None,
+        );
+
+        let mut filter = ReceiptFilter::new();
+        filter.data.insert("target.env".into(), json!("prod"));
+        filter.data.insert("hosts.1".into(), json!("b"));
+        filter.data.insert("replicas".into(), json!(3));
+        assert!(filter.matches(&r));
+
+        filter.data.insert("replicas".into(), json!("3"));
+        assert!(!filter.matches(&r));
+
+        let mut missing = ReceiptFilter::new();
+        missing.data.insert("target.region".into(), Value::Null);
+        assert!(!missing.matches(&r));
+    }
+
+    #[test]
+    fn test_plan_seeds_from_smallest_index() {
+        let a = IdentityAnchor::new(None);
+        let b = IdentityAnchor::new(None);
+        let mut index = ReceiptIndex::new();
+        for i in 0..5 {
+            index.insert(receipt(&a, "deploy", json!({"n": i}), None));
+        }
+        index.insert(receipt(&b, "deploy", json!({"n": 9}), None));
+
+        let filter = ReceiptFilter {
+            actor: Some(b.id()),
+            action_type: Some("deploy".into()),
+            ..ReceiptFilter::default()
+        };
+        let (plan, found) = filter.execute_with_plan(&index);
+        assert_eq!(plan.seed, IndexSeed::Actor);
+        assert_eq!(plan.candidates, 1);
+        assert_eq!(plan.residual, vec!["action_type".to_string()]);
+        assert_eq!(plan.to_string(), "actor (1 candidates), then action_type");
+        assert_eq!(found.len(), 1);
+
+        let (plan, found) = ReceiptFilter::new().execute_with_plan(&index);
+        assert_eq!(plan.seed, IndexSeed::FullScan);
+        assert_eq!(found.len(), 6);
+    }
+
+    #[test]
+    fn test_chained_to_follows_the_whole_chain() {
+        let anchor = IdentityAnchor::new(None);
+        let root = receipt(&anchor, "start", json!({}), None);
+        let step = receipt(&anchor, "step", json!({"ok": true}), Some(&root));
+        let end = receipt(&anchor, "step", json!({"ok": false}), Some(&step));
+        let other = receipt(&anchor, "step", json!({"ok": true}), None);
+
+        let mut index = ReceiptIndex::new();
+        for r in [&root, &step, &end, &other] {
+            index.insert(r.clone());
+        }
+
+        let mut filter = ReceiptFilter {
+            chained_to: Some(root.id.clone()),
+            order: SortOrder::OldestFirst,
+            ..ReceiptFilter::default()
+        };
+        let ids: Vec<_> = filter.execute(&index).iter().map(|r| &r.id).collect();
+        assert_eq!(ids, vec![&step.id, &end.id]);
+
+        filter.data.insert("ok".into(), json!(true));
+        let (plan, found) = filter.execute_with_plan(&index);
+        assert_eq!(plan.seed, IndexSeed::Chain);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, step.id);
+    }
+
+    #[test]
+    fn test_inverted_time_range_matches_nothing() {
+        let anchor = IdentityAnchor::new(None);
+        let mut index = ReceiptIndex::new();
+        index.insert(receipt(&anchor, "x", json!({}), None));
+
+        let filter = ReceiptFilter {
+            since: Some(10),
+            until: Some(5),
+            ..ReceiptFilter::default()
+        };
+        assert!(filter.execute(&index).is_empty());
+    }
+}
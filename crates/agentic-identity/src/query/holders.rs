@@ -0,0 +1,414 @@
+//! Reverse capability queries — which identities hold a capability.
+//!
+//! [`holders_page`] answers "who can `deploy:prod`?" over a data directory
+//! (`trust/` and `spawn/` under `base_dir`). An identity holds a capability
+//! through a trust grant issued to it that verifies, is in its validity
+//! window, is not revoked and covers the capability, or through a spawn
+//! whose effective authority (narrowed by every ancestor, empty once any
+//! of them is terminated or expired) covers it. Wildcards match both ways
+//! a grant can be written, so `deploy:*` and `*` hold `deploy:prod`.
+//!
+//! Records are examined in a fixed order, at most `limit` per page, so a
+//! large fleet is walked a page at a time instead of in one blocking scan.
+//! Spawn lineage is the exception: a page that examines any spawn record
+//! loads the whole spawn store once to resolve ancestors.
+//!
+//! Negative declarations are not stored in the data directory, so callers
+//! pass the ones they hold. A holder whose own signed declaration rules the
+//! capability out is reported in [`HoldersPage::negated`] rather than
+//! dropped, since an audit must still see the authority on record.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::identity::IdentityId;
+use crate::negative::{verify_declaration, DeclarationId, NegativeDeclaration};
+use crate::spawn::{authority_for, SpawnId, SpawnRecord};
+use crate::storage::{SpawnStore, TrustStore};
+use crate::trust::capability::capability_uri_covers;
+use crate::trust::verify::{verify_trust_grant_with, TimeSource, VerifyOptions};
+use crate::trust::{capabilities_cover, TrustId};
+
+/// Records examined per page when no limit is set.
+pub const DEFAULT_HOLDERS_PAGE: usize = 500;
+
+/// How an identity came to hold a capability.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuthoritySource {
+    /// A trust grant issued to the identity.
+    Grant {
+        trust_id: TrustId,
+        grantor: IdentityId,
+    },
+    /// The authority the identity was spawned with.
+    Spawn {
+        spawn_id: SpawnId,
+        parent: IdentityId,
+    },
+}
+
+/// A holder whose own negative declaration rules the capability out.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NegatedHolder {
+    pub identity: IdentityId,
+    pub source: AuthoritySource,
+    pub declaration: DeclarationId,
+}
+
+/// Paging and declaration options for [`holders_page`].
+#[derive(Debug, Clone, Default)]
+pub struct HoldersQuery {
+    limit: Option<usize>,
+    cursor: Option<String>,
+    declarations: Vec<NegativeDeclaration>,
+}
+
+impl HoldersQuery {
+    /// First page, [`DEFAULT_HOLDERS_PAGE`] records, no declarations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Examine at most `limit` records (at least one) per page.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit.max(1));
+        self
+    }
+
+    /// Continue after the page that returned this `next_cursor`.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Check holders against these declarations. Only declarations that
+    /// verify against the holder's own key count.
+    pub fn declarations(mut self, declarations: Vec<NegativeDeclaration>) -> Self {
+        self.declarations = declarations;
+        self
+    }
+}
+
+/// One page of [`holders_page`] results.
+#[derive(Debug, Clone, Default)]
+pub struct HoldersPage {
+    /// Holders found on this page, in record order.
+    pub holders: Vec<(IdentityId, AuthoritySource)>,
+    /// Holders on this page whose declaration negates the capability.
+    pub negated: Vec<NegatedHolder>,
+    /// Records examined on this page.
+    pub scanned: usize,
+    /// Cursor for the next page, if any records remain.
+    pub next_cursor: Option<String>,
+}
+
+/// A record to examine, keyed so grants and spawns share one ordering.
+enum Candidate {
+    Grant(TrustId),
+    Spawn(SpawnId),
+}
+
+impl Candidate {
+    fn key(&self) -> String {
+        match self {
+            Self::Grant(id) => format!("grant:{}", id.0),
+            Self::Spawn(id) => format!("spawn:{}", id.0),
+        }
+    }
+}
+
+/// Every identity holding `capability` at `now` (microseconds), across all
+/// pages and without declarations.
+///
+/// # Errors
+///
+/// Returns `IdentityError::Io` if a store directory cannot be read.
+pub fn holders_of(
+    capability: &str,
+    base_dir: &Path,
+    now: u64,
+) -> Result<Vec<(IdentityId, AuthoritySource)>> {
+    let mut holders = Vec::new();
+    let mut query = HoldersQuery::new();
+    loop {
+        let page = holders_page(capability, base_dir, now, &query)?;
+        holders.extend(page.holders);
+        match page.next_cursor {
+            Some(cursor) => query = query.cursor(cursor),
+            None => return Ok(holders),
+        }
+    }
+}
+
+/// One page of the identities holding `capability` at `now` (microseconds).
+///
+/// Only file names are listed up front; at most the page's records are
+/// loaded. Unreadable records are skipped. Missing store directories are
+/// treated as empty and are never created.
+///
+/// # Errors
+///
+/// Returns `IdentityError::Io` if a store directory cannot be read.
+pub fn holders_page(
+    capability: &str,
+    base_dir: &Path,
+    now: u64,
+    query: &HoldersQuery,
+) -> Result<HoldersPage> {
+    let trust_dir = base_dir.join("trust");
+    let spawn_dir = base_dir.join("spawn");
+    let trust = if trust_dir.is_dir() {
+        Some(TrustStore::new(&trust_dir)?)
+    } else {
+        None
+    };
+    let spawns = if spawn_dir.is_dir() {
+        Some(SpawnStore::new(&spawn_dir)?)
+    } else {
+        None
+    };
+
+    let mut candidates = Vec::new();
+    if let Some(store) = &trust {
+        let mut ids = store.list_received()?;
+        ids.extend(store.list_granted()?);
+        candidates.extend(ids.into_iter().map(Candidate::Grant));
+    }
+    if let Some(store) = &spawns {
+        candidates.extend(store.list()?.into_iter().map(Candidate::Spawn));
+    }
+    let mut candidates: Vec<(String, Candidate)> =
+        candidates.into_iter().map(|c| (c.key(), c)).collect();
+    candidates.sort_by(|a, b| a.0.cmp(&b.0));
+    // A grant held on both sides of one machine is listed twice.
+    candidates.dedup_by(|a, b| a.0 == b.0);
+    if let Some(cursor) = &query.cursor {
+        candidates.retain(|(key, _)| key.as_str() > cursor.as_str());
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_HOLDERS_PAGE);
+    let next_cursor = (candidates.len() > limit).then(|| candidates[limit - 1].0.clone());
+    candidates.truncate(limit);
+
+    let mut lineage: Option<Vec<SpawnRecord>> = None;
+    let mut page = HoldersPage {
+        scanned: candidates.len(),
+        next_cursor,
+        ..HoldersPage::default()
+    };
+    for (_, candidate) in candidates {
+        let found = match candidate {
+            Candidate::Grant(id) => trust
+                .as_ref()
+                .and_then(|store| grant_holder(store, &id, capability, now)),
+            Candidate::Spawn(id) => spawns.as_ref().and_then(|store| {
+                let all = lineage.get_or_insert_with(|| store.load_all().unwrap_or_default());
+                spawn_holder(store, &id, all, capability)
+            }),
+        };
+        let Some((identity, key, source)) = found else {
+            continue;
+        };
+        match negating_declaration(&query.declarations, &identity, &key, capability) {
+            Some(declaration) => page.negated.push(NegatedHolder {
+                identity,
+                source,
+                declaration,
+            }),
+            None => page.holders.push((identity, source)),
+        }
+    }
+    Ok(page)
+}
+
+/// The grantee, its key and the grant, if the grant confers `capability`.
+fn grant_holder(
+    store: &TrustStore,
+    id: &TrustId,
+    capability: &str,
+    now: u64,
+) -> Option<(IdentityId, String, AuthoritySource)> {
+    let grant = store.load_grant(id).ok()?;
+    // An unreadable revocation still revokes.
+    let revocations = if store.is_revoked(id) {
+        vec![store.load_revocation(id).ok()?]
+    } else {
+        vec![]
+    };
+    let verification = verify_trust_grant_with(
+        &grant,
+        capability,
+        0,
+        &revocations,
+        &VerifyOptions::new().time(TimeSource::Trusted(now)),
+    )
+    .ok()?;
+    let holds = verification.signature_valid
+        && verification.time_valid
+        && verification.not_revoked
+        && verification.capability_granted;
+    holds.then(|| {
+        let source = AuthoritySource::Grant {
+            trust_id: grant.id,
+            grantor: grant.grantor,
+        };
+        (grant.grantee, grant.grantee_key, source)
+    })
+}
+
+/// The child, its key and the spawn, if the child's effective authority
+/// covers `capability`.
+fn spawn_holder(
+    store: &SpawnStore,
+    id: &SpawnId,
+    all: &[SpawnRecord],
+    capability: &str,
+) -> Option<(IdentityId, String, AuthoritySource)> {
+    let record = store.load(id).ok()?;
+    let authority = authority_for(&record.child_id, all).ok()?;
+    let holds = authority.active
+        && authority.spawn_id.as_ref() == Some(&record.id)
+        && capabilities_cover(&authority.effective_authority, capability);
+    holds.then(|| {
+        let source = AuthoritySource::Spawn {
+            spawn_id: record.id,
+            parent: record.parent_id,
+        };
+        (record.child_id, record.child_key, source)
+    })
+}
+
+fn negating_declaration(
+    declarations: &[NegativeDeclaration],
+    identity: &IdentityId,
+    public_key: &str,
+    capability: &str,
+) -> Option<DeclarationId> {
+    declarations
+        .iter()
+        .filter(|d| &d.identity == identity)
+        .filter(|d| {
+            d.cannot_do
+                .iter()
+                .any(|c| capability_uri_covers(c, capability))
+        })
+        .find(|d| verify_declaration(d, public_key).is_ok())
+        .map(|d| d.declaration_id.clone())
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::negative::declare_cannot;
+    use crate::trust::constraint::TrustConstraints;
+    use crate::trust::grant::TrustGrantBuilder;
+    use crate::trust::revocation::{Revocation, RevocationReason};
+    use crate::trust::Capability;
+
+    fn grant(store: &TrustStore, grantor: &IdentityAnchor, grantee: &IdentityAnchor, cap: &str) {
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capability(Capability::new(cap))
+            .constraints(TrustConstraints::open())
+            .sign(grantor.signing_key())
+            .unwrap();
+        store.save_granted(&grant).unwrap();
+    }
+
+    #[test]
+    fn test_holders_via_wildcard_and_exact_grants() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(tmp.path().join("trust")).unwrap();
+        let admin = IdentityAnchor::new(None);
+        let (ops, deployer, reader) = (
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+        );
+        grant(&store, &admin, &ops, "deploy:*");
+        grant(&store, &admin, &deployer, "deploy:prod");
+        grant(&store, &admin, &reader, "read:*");
+
+        let now = crate::time::now_micros();
+        let mut holders: Vec<IdentityId> = holders_of("deploy:prod", tmp.path(), now)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        holders.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected = vec![ops.id(), deployer.id()];
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(holders, expected);
+
+        // A revoked grant confers nothing.
+        let ids = store.list_granted().unwrap();
+        for id in ids {
+            if store.load_grant(&id).unwrap().grantee == ops.id() {
+                let revocation = Revocation::create(
+                    id,
+                    admin.id(),
+                    RevocationReason::ManualRevocation,
+                    admin.signing_key(),
+                );
+                store.save_revocation(&revocation).unwrap();
+            }
+        }
+        let holders = holders_of("deploy:prod", tmp.path(), crate::time::now_micros()).unwrap();
+        assert_eq!(holders.len(), 1);
+        assert_eq!(holders[0].0, deployer.id());
+    }
+
+    #[test]
+    fn test_holders_paged_and_negated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(tmp.path().join("trust")).unwrap();
+        let admin = IdentityAnchor::new(None);
+        let agents: Vec<IdentityAnchor> = (0..3).map(|_| IdentityAnchor::new(None)).collect();
+        for agent in &agents {
+            grant(&store, &admin, agent, "*");
+        }
+        let declaration = declare_cannot(
+            &agents[0],
+            vec!["deploy:*".to_string()],
+            "read-only agent",
+            false,
+            vec![],
+        )
+        .unwrap();
+        // A declaration signed by someone else for agents[1] is ignored.
+        let mut forged = declare_cannot(&admin, vec!["*".into()], "x", false, vec![]).unwrap();
+        forged.identity = agents[1].id();
+
+        let now = crate::time::now_micros();
+        let mut query = HoldersQuery::new()
+            .limit(2)
+            .declarations(vec![declaration.clone(), forged]);
+        let mut holders = Vec::new();
+        let mut negated = Vec::new();
+        let mut pages = 0;
+        loop {
+            let page = holders_page("deploy:prod", tmp.path(), now, &query).unwrap();
+            assert!(page.scanned <= 2);
+            pages += 1;
+            holders.extend(page.holders);
+            negated.extend(page.negated);
+            match page.next_cursor {
+                Some(cursor) => query = query.cursor(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(pages, 2);
+        assert_eq!(holders.len(), 2);
+        assert_eq!(negated.len(), 1);
+        assert_eq!(negated[0].identity, agents[0].id());
+        assert_eq!(negated[0].declaration, declaration.declaration_id);
+
+        // An empty data directory has no holders and is left untouched.
+        let empty = tempfile::tempdir().unwrap();
+        assert!(holders_of("*", empty.path(), now).unwrap().is_empty());
+        assert!(!empty.path().join("trust").exists());
+    }
+}
@@ -17,6 +17,24 @@
 //! 2. Applies every specified filter in turn to narrow the set.
 //! 3. Sorts the results according to [`SortOrder`].
 //! 4. Applies an optional result limit.
+//!
+//! [`ReceiptFilter`] is the structured, serializable form of a receipt
+//! query. It adds filters on action `data` and on whole chains, and plans
+//! its execution by sizing each usable index first (see [`QueryPlan`]).
+//!
+//! [`holders_of`] / [`holders_page`] answer the reverse question — which
+//! identities hold a capability — directly from the stores on disk.
+
+mod filter;
+mod holders;
+
+pub use filter::{IndexSeed, QueryPlan, ReceiptFilter};
+pub use holders::{
+    holders_of, holders_page, AuthoritySource, HoldersPage, HoldersQuery, NegatedHolder,
+    DEFAULT_HOLDERS_PAGE,
+};
+
+use serde::{Deserialize, Serialize};
 
 use crate::identity::IdentityId;
 use crate::index::{ReceiptIndex, TrustIndex};
@@ -26,7 +44,8 @@ use crate::trust::TrustGrant;
 // ── SortOrder ─────────────────────────────────────────────────────────────────
 
 /// Sort direction for query results.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SortOrder {
     /// Most recently recorded receipt first (descending timestamp).
     #[default]
@@ -152,10 +171,10 @@ pub fn query_receipts<'a>(index: &'a ReceiptIndex, query: &ReceiptQuery) -> Vec<
 
     match query.sort {
         SortOrder::NewestFirst => {
-            candidates.sort_unstable_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            candidates.sort_unstable_by_key(|r| std::cmp::Reverse(r.timestamp));
         }
         SortOrder::OldestFirst => {
-            candidates.sort_unstable_by(|a, b| a.timestamp.cmp(&b.timestamp));
+            candidates.sort_unstable_by_key(|r| r.timestamp);
         }
     }
 
@@ -278,7 +297,7 @@ fn collect_all_grants(index: &TrustIndex) -> Vec<&TrustGrant> {
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -1,6 +1,12 @@
 //! Action types and content for receipts.
 
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::disclosure::CommittedData;
+#[cfg(feature = "signing")]
+use super::disclosure::FieldOpenings;
+use super::encrypted::EncryptedData;
 
 /// Type of action being recorded.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -38,6 +44,46 @@ pub struct ActionContent {
     pub data: Option<serde_json::Value>,
     /// References to related resources.
     pub references: Vec<String>,
+    /// A payload kept out of the receipt, in a blob store, by its hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_ref: Option<DataRef>,
+    /// Structured data readable only by named recipients, in place of `data`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_data: Option<EncryptedData>,
+    /// Hash commitments to `data` fields withheld for selective disclosure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub committed_data: Option<CommittedData>,
+}
+
+/// A reference to a payload stored outside the receipt.
+///
+/// The receipt signs the hash, so the payload it names cannot be swapped
+/// for another without the receipt failing to verify. See
+/// [`BlobStore`](crate::storage::BlobStore).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataRef {
+    /// Hex-encoded SHA-256 of the payload.
+    pub hash: String,
+    /// Payload size in bytes.
+    pub size: u64,
+    /// MIME type of the payload, e.g. `application/json`.
+    pub media_type: String,
+}
+
+impl DataRef {
+    /// The reference to `bytes`.
+    pub fn for_bytes(bytes: &[u8], media_type: impl Into<String>) -> Self {
+        Self {
+            hash: hex::encode(Sha256::digest(bytes)),
+            size: bytes.len() as u64,
+            media_type: media_type.into(),
+        }
+    }
+
+    /// Whether `bytes` is the payload this reference names.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        bytes.len() as u64 == self.size && hex::encode(Sha256::digest(bytes)) == self.hash
+    }
 }
 
 impl ActionContent {
@@ -47,6 +93,9 @@ impl ActionContent {
             description: description.into(),
             data: None,
             references: Vec::new(),
+            data_ref: None,
+            encrypted_data: None,
+            committed_data: None,
         }
     }
 
@@ -56,6 +105,66 @@ impl ActionContent {
             description: description.into(),
             data: Some(data),
             references: Vec::new(),
+            data_ref: None,
+            encrypted_data: None,
+            committed_data: None,
+        }
+    }
+
+    /// Create an action with structured data whose `committed` fields are
+    /// withheld behind hash commitments; see [`super::disclosure`].
+    ///
+    /// Returns the content and the openings the actor must keep to disclose
+    /// those fields later. Data schemas see only the fields left in `data`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if `data` is not a JSON object
+    /// or lacks one of the fields.
+    #[cfg(feature = "signing")]
+    pub fn with_committed_data(
+        description: impl Into<String>,
+        data: serde_json::Value,
+        committed: &[&str],
+    ) -> crate::error::Result<(Self, FieldOpenings)> {
+        let (data, committed_data, openings) = super::disclosure::commit_fields(data, committed)?;
+        let mut content = Self::with_data(description, data);
+        content.committed_data = Some(committed_data);
+        Ok((content, openings))
+    }
+
+    /// Attach a reference to a payload kept in a blob store, by its hex
+    /// SHA-256 `hash`, `size` in bytes and `media_type`.
+    pub fn with_data_ref(
+        mut self,
+        hash: impl Into<String>,
+        size: u64,
+        media_type: impl Into<String>,
+    ) -> Self {
+        self.data_ref = Some(DataRef {
+            hash: hash.into(),
+            size,
+            media_type: media_type.into(),
+        });
+        self
+    }
+
+    /// The structured data, decrypted as `recipient` if it is encrypted.
+    ///
+    /// Returns `Ok(None)` for an action without data.
+    ///
+    /// # Errors
+    ///
+    /// As for [`EncryptedData::open`].
+    #[cfg(feature = "signing")]
+    pub fn decrypt_data(
+        &self,
+        recipient: &crate::identity::IdentityId,
+        signing_key: &ed25519_dalek::SigningKey,
+    ) -> crate::error::Result<Option<serde_json::Value>> {
+        match &self.encrypted_data {
+            Some(encrypted) => encrypted.open(recipient, signing_key).map(Some),
+            None => Ok(self.data.clone()),
         }
     }
 }
@@ -0,0 +1,194 @@
+//! Receipt bundles — an identity document plus the receipts it issued.
+//!
+//! A [`ReceiptBundle`] is the unit a host hands over for offline checking:
+//! the actor's public document and a run of its receipts, oldest first.
+//! [`verify_bundle`] checks the document's self-signature, then every
+//! receipt's signature, actor, and link to the receipt before it, and
+//! reports each receipt individually instead of stopping at the first
+//! failure. A document that fails its own signature check cannot vouch for
+//! anything, so in that case no receipt is examined.
+
+use serde::{Deserialize, Serialize};
+
+use crate::identity::IdentityDocument;
+
+use super::receipt::{ActionReceipt, ReceiptId};
+use super::verify::verify_receipt;
+
+/// An identity document and a run of its receipts, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptBundle {
+    pub document: IdentityDocument,
+    pub receipts: Vec<ActionReceipt>,
+}
+
+/// Result of [`verify_bundle`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleVerification {
+    /// Whether the document's self-signature verified.
+    pub document_valid: bool,
+    /// Why the document failed, when it did.
+    pub document_error: Option<String>,
+    /// Per-receipt results, in bundle order. Empty if the document failed.
+    pub receipts: Vec<BundleReceiptStatus>,
+    /// Document and every receipt valid.
+    pub is_valid: bool,
+    pub verified_at: u64,
+}
+
+/// How one receipt in a bundle fared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleReceiptStatus {
+    pub id: ReceiptId,
+    /// Signature and receipt hash verified.
+    pub signature_valid: bool,
+    /// The receipt was issued by the bundle's identity.
+    pub actor_matches: bool,
+    /// The receipt links to the one before it and is not older than it.
+    /// Always true for the first receipt, which may continue an older chain.
+    pub chain_valid: bool,
+    /// Why the receipt failed, when it did.
+    pub error: Option<String>,
+    pub is_valid: bool,
+}
+
+/// Verify a bundle's document and every receipt in it.
+pub fn verify_bundle(bundle: &ReceiptBundle) -> BundleVerification {
+    let verified_at = crate::time::now_micros();
+
+    if let Err(e) = bundle.document.verify_signature() {
+        return BundleVerification {
+            document_valid: false,
+            document_error: Some(e.to_string()),
+            receipts: Vec::new(),
+            is_valid: false,
+            verified_at,
+        };
+    }
+
+    let receipts: Vec<BundleReceiptStatus> = bundle
+        .receipts
+        .iter()
+        .enumerate()
+        .map(|(i, receipt)| {
+            let previous = i.checked_sub(1).map(|p| &bundle.receipts[p]);
+            receipt_status(bundle, receipt, previous)
+        })
+        .collect();
+
+    BundleVerification {
+        document_valid: true,
+        document_error: None,
+        is_valid: receipts.iter().all(|r| r.is_valid),
+        receipts,
+        verified_at,
+    }
+}
+
+fn receipt_status(
+    bundle: &ReceiptBundle,
+    receipt: &ActionReceipt,
+    previous: Option<&ActionReceipt>,
+) -> BundleReceiptStatus {
+    let (signature_valid, mut error) = match verify_receipt(receipt) {
+        Ok(v) if v.signature_valid => (true, None),
+        Ok(_) => (false, Some("signature does not verify".to_string())),
+        Err(e) => (false, Some(e.to_string())),
+    };
+
+    let actor_matches = receipt.actor == bundle.document.id;
+    if !actor_matches && error.is_none() {
+        error = Some(format!(
+            "issued by {} instead of {}",
+            receipt.actor, bundle.document.id
+        ));
+    }
+
+    let chain_valid = match previous {
+        None => true,
+        Some(prev) => {
+            receipt.previous_receipt.as_ref() == Some(&prev.id)
+                && receipt.timestamp >= prev.timestamp
+        }
+    };
+    if !chain_valid && error.is_none() {
+        error = Some("does not chain to the preceding receipt".to_string());
+    }
+
+    BundleReceiptStatus {
+        id: receipt.id.clone(),
+        signature_valid,
+        actor_matches,
+        chain_valid,
+        error,
+        is_valid: signature_valid && actor_matches && chain_valid,
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::action::{ActionContent, ActionType};
+    use crate::receipt::receipt::ReceiptBuilder;
+
+    fn chained_bundle(anchor: &IdentityAnchor, count: usize) -> ReceiptBundle {
+        let mut receipts: Vec<ActionReceipt> = Vec::new();
+        for i in 0..count {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Observation,
+                ActionContent::new(format!("step {i}")),
+            );
+            if let Some(prev) = receipts.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            receipts.push(builder.sign(anchor.signing_key()).unwrap());
+        }
+        ReceiptBundle {
+            document: anchor.to_document(),
+            receipts,
+        }
+    }
+
+    #[test]
+    fn test_bundle_valid() {
+        let anchor = IdentityAnchor::new(None);
+        let result = verify_bundle(&chained_bundle(&anchor, 3));
+        assert!(result.document_valid);
+        assert!(result.is_valid);
+        assert_eq!(result.receipts.len(), 3);
+    }
+
+    #[test]
+    fn test_bundle_reports_each_receipt() {
+        let anchor = IdentityAnchor::new(None);
+        let mut bundle = chained_bundle(&anchor, 3);
+        bundle.receipts[1].action = ActionContent::new("rewritten");
+        bundle
+            .receipts
+            .push(chained_bundle(&IdentityAnchor::new(None), 1).receipts[0].clone());
+
+        let result = verify_bundle(&bundle);
+        assert!(result.document_valid);
+        assert!(!result.is_valid);
+        let valid: Vec<bool> = result.receipts.iter().map(|r| r.is_valid).collect();
+        assert_eq!(valid, [true, false, true, false]);
+        assert!(!result.receipts[1].signature_valid);
+        assert!(!result.receipts[3].actor_matches);
+        assert!(!result.receipts[3].chain_valid);
+    }
+
+    #[test]
+    fn test_bundle_bad_document_short_circuits() {
+        let anchor = IdentityAnchor::new(None);
+        let mut bundle = chained_bundle(&anchor, 2);
+        bundle.document.name = Some("impostor".to_string());
+
+        let result = verify_bundle(&bundle);
+        assert!(!result.document_valid);
+        assert!(result.document_error.is_some());
+        assert!(result.receipts.is_empty());
+        assert!(!result.is_valid);
+    }
+}
@@ -2,19 +2,93 @@
 //!
 //! Verifies the integrity of a sequence of chained receipts
 //! by walking the `previous_receipt` links.
+//!
+//! Receipts chained across machines carry each machine's clock, so a
+//! receipt can be stamped slightly before the one it follows. A
+//! [`ChainPolicy`] with a backward skew tolerance accepts such receipts but
+//! lists them in the [`ChainVerification`], so clock drift stays visible.
+//!
+//! [`verify_chain_stream`] checks the same properties one receipt at a
+//! time, walking back from the tip, so a chain can be verified straight
+//! from a store without holding it in memory. It reports where the chain
+//! first breaks rather than only that it is broken.
 
-use super::receipt::ActionReceipt;
-use super::verify::verify_receipt;
+use serde::{Deserialize, Serialize};
+
+use super::receipt::{ActionReceipt, ReceiptId};
+use super::verify::verify_receipt_at;
 use crate::error::{IdentityError, Result};
+use crate::trust::verify::{IssuerAllowlist, TimeSource};
+
+/// Options for [`verify_chain_with_policy`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ChainPolicy {
+    /// How far a receipt's timestamp may fall behind its predecessor's, in
+    /// seconds, and still be accepted. Zero (the default) requires
+    /// timestamps never to decrease.
+    pub max_backward_skew_secs: u64,
+}
+
+/// A receipt accepted despite being stamped before its predecessor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainSkew {
+    pub receipt: ReceiptId,
+    /// How far behind its predecessor it was, in microseconds.
+    pub behind_micros: u64,
+}
+
+/// Result of a chain that verified.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainVerification {
+    /// Receipts stamped before their predecessor but within tolerance,
+    /// oldest first.
+    pub skewed: Vec<ChainSkew>,
+}
+
+impl ChainVerification {
+    /// Timestamps never decreased along the chain.
+    pub fn is_monotonic(&self) -> bool {
+        self.skewed.is_empty()
+    }
+}
 
 /// Verify a chain of receipts (ordered from oldest to newest).
 ///
 /// Checks that each receipt's `previous_receipt` correctly references
-/// the preceding receipt, and that every signature is valid.
+/// the preceding receipt, that every signature is valid, and that
+/// timestamps never decrease and are not in the future.
 pub fn verify_chain(chain: &[ActionReceipt]) -> Result<bool> {
+    verify_chain_at(chain, &TimeSource::HostClock)
+}
+
+/// Verify a chain of receipts, judging "the future" against `time`.
+///
+/// Fails with [`IdentityError::UntrustedTime`] if `time` does not verify.
+pub fn verify_chain_at(chain: &[ActionReceipt], time: &TimeSource) -> Result<bool> {
+    verify_chain_with_policy(chain, time, &ChainPolicy::default())?;
+    Ok(true)
+}
+
+/// Verify a chain of receipts, tolerating backward timestamp skew up to
+/// `policy.max_backward_skew_secs`.
+///
+/// Receipts within the tolerance are accepted and reported in
+/// [`ChainVerification::skewed`]; a larger regression fails with
+/// [`IdentityError::InvalidChain`]. The tolerance never applies forward: a
+/// receipt stamped after `time` fails regardless.
+pub fn verify_chain_with_policy(
+    chain: &[ActionReceipt],
+    time: &TimeSource,
+    policy: &ChainPolicy,
+) -> Result<ChainVerification> {
+    let mut result = ChainVerification::default();
     if chain.is_empty() {
-        return Ok(true);
+        return Ok(result);
     }
+    let tolerance = policy.max_backward_skew_secs.saturating_mul(1_000_000);
+
+    // Resolve once so every receipt is judged against the same instant.
+    let now = TimeSource::Trusted(time.now()?);
 
     // First receipt should have no previous
     if chain[0].previous_receipt.is_some() {
@@ -23,8 +97,8 @@ pub fn verify_chain(chain: &[ActionReceipt]) -> Result<bool> {
 
     for i in 0..chain.len() {
         // Verify each receipt's signature
-        let verification = verify_receipt(&chain[i])?;
-        if !verification.signature_valid {
+        let verification = verify_receipt_at(&chain[i], &IssuerAllowlist::new(), &now)?;
+        if !verification.signature_valid || chain[i].timestamp > verification.verified_at {
             return Err(IdentityError::InvalidChain);
         }
 
@@ -35,18 +109,174 @@ pub fn verify_chain(chain: &[ActionReceipt]) -> Result<bool> {
                 Some(prev) if prev == expected_prev => {}
                 _ => return Err(IdentityError::InvalidChain),
             }
+            let behind = chain[i - 1].timestamp.saturating_sub(chain[i].timestamp);
+            if behind > tolerance {
+                return Err(IdentityError::InvalidChain);
+            }
+            if behind > 0 {
+                result.skewed.push(ChainSkew {
+                    receipt: chain[i].id.clone(),
+                    behind_micros: behind,
+                });
+            }
         }
     }
 
-    Ok(true)
+    Ok(result)
+}
+
+// ── Streaming verification ────────────────────────────────────────────────────
+
+/// Why a streamed chain stopped verifying.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainBreakReason {
+    /// The receipt could not be read from its source.
+    Unreadable { error: String },
+    /// The signature, receipt hash, or actor key did not verify.
+    BadSignature,
+    /// The receipt is stamped after the verification time.
+    FutureTimestamp,
+    /// The newer receipt's `previous_receipt` does not name this receipt.
+    /// `expected` is `None` when the newer receipt claimed to start the chain.
+    BrokenLink { expected: Option<ReceiptId> },
+    /// The newer receipt is stamped further before this one than the policy
+    /// tolerates.
+    TimestampRegression { behind_micros: u64 },
+}
+
+/// The first point at which a streamed chain failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainBreak {
+    /// Zero-based position in the stream; the tip is 0.
+    pub position: usize,
+    /// The receipt at that position, if it could be read.
+    pub receipt: Option<ReceiptId>,
+    pub reason: ChainBreakReason,
+}
+
+/// Result of [`verify_chain_stream`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamVerification {
+    /// Receipts verified before the first break (or all of them).
+    pub verified: usize,
+    /// The first receipt in the stream.
+    pub tip: Option<ReceiptId>,
+    /// The last receipt that verified; the chain root if there was no break.
+    pub last_verified: Option<ReceiptId>,
+    /// Receipts accepted despite backward skew, oldest first.
+    pub skewed: Vec<ChainSkew>,
+    /// Where the chain first failed, if it did.
+    pub first_break: Option<ChainBreak>,
+}
+
+impl StreamVerification {
+    /// Every streamed receipt verified and linked.
+    pub fn is_valid(&self) -> bool {
+        self.first_break.is_none()
+    }
+}
+
+/// Verify a chain streamed newest first, from the tip back along
+/// `previous_receipt` links (the order [`ReceiptStore::walk_chain`] yields).
+///
+/// Each receipt is checked as it arrives and only the previous receipt's
+/// link and timestamp are kept, so memory use does not grow with the chain.
+/// Verification stops at the first break, which is reported rather than
+/// returned as an error; the rest of the stream is not consumed. Stream
+/// items that are `Err` count as unreadable receipts.
+///
+/// Fails only if `time` does not verify ([`IdentityError::UntrustedTime`]).
+///
+/// [`ReceiptStore::walk_chain`]: crate::storage::ReceiptStore::walk_chain
+pub fn verify_chain_stream<I>(
+    receipts: I,
+    time: &TimeSource,
+    policy: &ChainPolicy,
+) -> Result<StreamVerification>
+where
+    I: IntoIterator<Item = Result<ActionReceipt>>,
+{
+    let tolerance = policy.max_backward_skew_secs.saturating_mul(1_000_000);
+    let now = TimeSource::Trusted(time.now()?);
+    let mut result = StreamVerification::default();
+    // The newer neighbour's (id, previous_receipt, timestamp).
+    let mut newer: Option<(ReceiptId, Option<ReceiptId>, u64)> = None;
+
+    for (position, item) in receipts.into_iter().enumerate() {
+        let receipt = match item {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                result.first_break = Some(ChainBreak {
+                    position,
+                    receipt: None,
+                    reason: ChainBreakReason::Unreadable {
+                        error: e.to_string(),
+                    },
+                });
+                break;
+            }
+        };
+        if let Some(reason) = check_streamed(&receipt, newer.as_ref(), &now, tolerance) {
+            result.first_break = Some(ChainBreak {
+                position,
+                receipt: Some(receipt.id),
+                reason,
+            });
+            break;
+        }
+
+        if let Some((newer_id, _, newer_ts)) = &newer {
+            let behind = receipt.timestamp.saturating_sub(*newer_ts);
+            if behind > 0 {
+                result.skewed.push(ChainSkew {
+                    receipt: newer_id.clone(),
+                    behind_micros: behind,
+                });
+            }
+        }
+        if result.tip.is_none() {
+            result.tip = Some(receipt.id.clone());
+        }
+        result.verified += 1;
+        result.last_verified = Some(receipt.id.clone());
+        newer = Some((receipt.id, receipt.previous_receipt, receipt.timestamp));
+    }
+
+    result.skewed.reverse();
+    Ok(result)
+}
+
+fn check_streamed(
+    receipt: &ActionReceipt,
+    newer: Option<&(ReceiptId, Option<ReceiptId>, u64)>,
+    now: &TimeSource,
+    tolerance: u64,
+) -> Option<ChainBreakReason> {
+    let verification = match verify_receipt_at(receipt, &IssuerAllowlist::new(), now) {
+        Ok(v) if v.signature_valid => v,
+        _ => return Some(ChainBreakReason::BadSignature),
+    };
+    if receipt.timestamp > verification.verified_at {
+        return Some(ChainBreakReason::FutureTimestamp);
+    }
+    let (_, expected, newer_ts) = newer?;
+    if expected.as_ref() != Some(&receipt.id) {
+        return Some(ChainBreakReason::BrokenLink {
+            expected: expected.clone(),
+        });
+    }
+    let behind_micros = receipt.timestamp.saturating_sub(*newer_ts);
+    (behind_micros > tolerance).then_some(ChainBreakReason::TimestampRegression { behind_micros })
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
     use crate::receipt::action::{ActionContent, ActionType};
     use crate::receipt::receipt::ReceiptBuilder;
+    use crate::trust::verify::TimeToken;
 
     #[test]
     fn test_verify_chain_valid() {
@@ -144,4 +374,199 @@ mod tests {
 
         assert!(verify_chain(&chain).is_ok());
     }
+
+    #[test]
+    fn test_verify_chain_at_rejects_receipts_after_trusted_time() {
+        let anchor = IdentityAnchor::new(None);
+        let authority = IdentityAnchor::new(None);
+        let before = crate::time::now_micros() - 1;
+
+        let r1 = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("step 1"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+        let chain = [r1];
+
+        assert!(verify_chain_at(&chain, &TimeSource::HostClock).is_ok());
+        assert!(matches!(
+            verify_chain_at(&chain, &TimeSource::Trusted(before)),
+            Err(IdentityError::InvalidChain)
+        ));
+
+        let mut token = TimeToken::issue(crate::time::now_micros(), authority.signing_key());
+        let source = TimeSource::Token {
+            authority_key: token.authority_key.clone(),
+            token: token.clone(),
+        };
+        assert!(verify_chain_at(&chain, &source).is_ok());
+
+        token.time += 1;
+        let tampered = TimeSource::Token {
+            authority_key: token.authority_key.clone(),
+            token,
+        };
+        assert!(matches!(
+            verify_chain_at(&chain, &tampered),
+            Err(IdentityError::UntrustedTime(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_chain_rejects_decreasing_timestamps() {
+        let anchor = IdentityAnchor::new(None);
+        let r1 = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("step 1"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+        let r2 = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("step 2"),
+        )
+        .chain_to(r1.id.clone())
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        // Backdate the later receipt and re-sign it so only ordering is wrong.
+        let mut backdated = r2;
+        backdated.timestamp = r1.timestamp - 1;
+        backdated.receipt_hash = backdated.compute_hash();
+        backdated.signature = crate::crypto::signing::sign_in_domain(
+            anchor.signing_key(),
+            crate::crypto::signing::SignatureDomain::Receipt,
+            backdated.receipt_hash.as_bytes(),
+        );
+        assert!(verify_chain(&[r1, backdated]).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_backward_skew_tolerance() {
+        let anchor = IdentityAnchor::new(None);
+        let r1 = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("step 1"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+        let backdated_by = |micros: u64| {
+            let mut r2 = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Decision,
+                ActionContent::new("step 2"),
+            )
+            .chain_to(r1.id.clone())
+            .sign(anchor.signing_key())
+            .unwrap();
+            r2.timestamp = r1.timestamp - micros;
+            r2.receipt_hash = r2.compute_hash();
+            r2.signature = crate::crypto::signing::sign_in_domain(
+                anchor.signing_key(),
+                crate::crypto::signing::SignatureDomain::Receipt,
+                r2.receipt_hash.as_bytes(),
+            );
+            r2
+        };
+        let two_secs = ChainPolicy {
+            max_backward_skew_secs: 2,
+        };
+        let check = |r2: &ActionReceipt, policy: &ChainPolicy| {
+            verify_chain_with_policy(&[r1.clone(), r2.clone()], &TimeSource::HostClock, policy)
+        };
+
+        let slightly = backdated_by(500_000);
+        let result = check(&slightly, &two_secs).unwrap();
+        assert!(!result.is_monotonic());
+        assert_eq!(result.skewed[0].receipt, slightly.id);
+        assert_eq!(result.skewed[0].behind_micros, 500_000);
+
+        assert!(check(&backdated_by(3_000_000), &two_secs).is_err());
+        assert!(check(&slightly, &ChainPolicy::default()).is_err());
+    }
+
+    #[test]
+    fn test_verify_chain_stream_reports_first_break() {
+        let anchor = IdentityAnchor::new(None);
+        let mut chain: Vec<ActionReceipt> = Vec::new();
+        for step in 0..4 {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Decision,
+                ActionContent::new(format!("step {step}")),
+            );
+            if let Some(prev) = chain.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            chain.push(builder.sign(anchor.signing_key()).unwrap());
+        }
+        let newest_first = |c: &[ActionReceipt]| -> Vec<Result<ActionReceipt>> {
+            c.iter().rev().cloned().map(Ok).collect()
+        };
+        let stream = |c: &[ActionReceipt]| {
+            verify_chain_stream(
+                newest_first(c),
+                &TimeSource::HostClock,
+                &ChainPolicy::default(),
+            )
+            .unwrap()
+        };
+
+        let ok = stream(&chain);
+        assert!(ok.is_valid());
+        assert_eq!(ok.verified, 4);
+        assert_eq!(ok.tip.as_ref(), Some(&chain[3].id));
+        assert_eq!(ok.last_verified.as_ref(), Some(&chain[0].id));
+
+        // Drop step 1: step 2 links to a receipt the stream does not yield.
+        let gapped = [chain[0].clone(), chain[2].clone(), chain[3].clone()];
+        let broken = stream(&gapped);
+        assert_eq!(broken.verified, 2);
+        let at = broken.first_break.unwrap();
+        assert_eq!(at.position, 2);
+        assert_eq!(at.receipt.as_ref(), Some(&chain[0].id));
+        assert_eq!(
+            at.reason,
+            ChainBreakReason::BrokenLink {
+                expected: Some(chain[1].id.clone())
+            }
+        );
+
+        // A tampered receipt breaks at its own position.
+        let mut tampered = chain.clone();
+        tampered[2].action = ActionContent::new("rewritten");
+        let bad = stream(&tampered).first_break.unwrap();
+        assert_eq!(
+            (bad.position, bad.reason),
+            (1, ChainBreakReason::BadSignature)
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_stream_stops_at_unreadable_item() {
+        let anchor = IdentityAnchor::new(None);
+        let r1 = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("only"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+        let items = vec![
+            Ok(r1),
+            Err(IdentityError::NotFound("receipt not found: arec_x".into())),
+            Err(IdentityError::InvalidChain),
+        ];
+        let result =
+            verify_chain_stream(items, &TimeSource::HostClock, &ChainPolicy::default()).unwrap();
+        assert_eq!(result.verified, 1);
+        let at = result.first_break.unwrap();
+        assert_eq!(at.position, 1);
+        assert!(matches!(at.reason, ChainBreakReason::Unreadable { .. }));
+    }
 }
@@ -0,0 +1,218 @@
+//! Context snapshots — what a receipt's `context_hash` commits to.
+//!
+//! A [`ContextSnapshot`] records the circumstances an action was taken in:
+//! the runtime environment, the model, a hash of the prompt and the inputs
+//! passed to tools. [`ContextSnapshot::hash`] digests its canonical form,
+//! and [`ReceiptBuilder::with_context`](super::receipt::ReceiptBuilder::with_context)
+//! stores that digest in the receipt, so anyone later shown the snapshot can
+//! check it is the one the receipt was signed over.
+//!
+//! # Canonical form
+//!
+//! The snapshot is written as compact JSON with every object's keys sorted
+//! by byte order, at any depth, and unset fields omitted. The hash is the hex
+//! SHA-256 of `aid:context:v1:` followed by that JSON, so the same snapshot
+//! hashes the same in any implementation regardless of field or map order.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::receipt::ActionReceipt;
+
+/// Prefix hashed ahead of the canonical JSON. A new canonical form gets a
+/// new version.
+const CONTEXT_HASH_PREFIX: &str = "aid:context:v1:";
+
+/// One input passed to a tool while producing the action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolInput {
+    pub tool: String,
+    pub input: Value,
+}
+
+/// The context an action was produced in.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextSnapshot {
+    /// Runtime environment, e.g. host, region or deployment.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<String, String>,
+    /// Model identifier, e.g. `provider/model@version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Hex SHA-256 of the prompt; the prompt itself is never recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_hash: Option<String>,
+    /// Tool inputs, in call order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_inputs: Vec<ToolInput>,
+}
+
+impl ContextSnapshot {
+    /// Create an empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record an environment variable or setting.
+    pub fn environment(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.environment.insert(key.into(), value.into());
+        self
+    }
+
+    /// Record the model that produced the action.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Record the hash of `prompt`.
+    pub fn prompt(mut self, prompt: &str) -> Self {
+        self.prompt_hash = Some(hex::encode(Sha256::digest(prompt.as_bytes())));
+        self
+    }
+
+    /// Record an input passed to `tool`.
+    pub fn tool_input(mut self, tool: impl Into<String>, input: Value) -> Self {
+        self.tool_inputs.push(ToolInput {
+            tool: tool.into(),
+            input,
+        });
+        self
+    }
+
+    /// The canonical JSON form described in the [module docs](self).
+    pub fn canonical_json(&self) -> String {
+        let mut fields = serde_json::Map::new();
+        if !self.environment.is_empty() {
+            fields.insert(
+                "environment".into(),
+                Value::Object(
+                    self.environment
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+                        .collect(),
+                ),
+            );
+        }
+        if let Some(model) = &self.model {
+            fields.insert("model".into(), Value::String(model.clone()));
+        }
+        if let Some(prompt_hash) = &self.prompt_hash {
+            fields.insert("prompt_hash".into(), Value::String(prompt_hash.clone()));
+        }
+        if !self.tool_inputs.is_empty() {
+            fields.insert(
+                "tool_inputs".into(),
+                Value::Array(
+                    self.tool_inputs
+                        .iter()
+                        .map(|t| {
+                            let mut entry = serde_json::Map::new();
+                            entry.insert("input".into(), t.input.clone());
+                            entry.insert("tool".into(), Value::String(t.tool.clone()));
+                            Value::Object(entry)
+                        })
+                        .collect(),
+                ),
+            );
+        }
+        let mut out = String::new();
+        write_canonical(&Value::Object(fields), &mut out);
+        out
+    }
+
+    /// Hex SHA-256 of the canonical form, as stored in `context_hash`.
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(CONTEXT_HASH_PREFIX.as_bytes());
+        hasher.update(self.canonical_json().as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// Is this the snapshot `receipt` was bound to?
+    pub fn matches(&self, receipt: &ActionReceipt) -> bool {
+        receipt.context_hash.as_deref() == Some(self.hash().as_str())
+    }
+}
+
+/// Append `value` to `out` as compact JSON with object keys sorted.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_json_sorts_keys_at_every_depth() {
+        let a = ContextSnapshot::new()
+            .model("acme/model@1")
+            .environment("region", "eu")
+            .environment("host", "worker-1")
+            .tool_input("search", serde_json::json!({"q": "x", "limit": 5}));
+        let b = ContextSnapshot::new()
+            .environment("host", "worker-1")
+            .environment("region", "eu")
+            .tool_input("search", serde_json::json!({"limit": 5, "q": "x"}))
+            .model("acme/model@1");
+        assert_eq!(
+            a.canonical_json(),
+            r#"{"environment":{"host":"worker-1","region":"eu"},"model":"acme/model@1","tool_inputs":[{"input":{"limit":5,"q":"x"},"tool":"search"}]}"#
+        );
+        assert_eq!(a.hash(), b.hash());
+        assert_eq!(a.hash().len(), 64);
+    }
+
+    #[test]
+    fn test_hash_covers_every_field() {
+        let base = ContextSnapshot::new().model("m").prompt("hello");
+        assert_ne!(base.hash(), base.clone().prompt("hello!").hash());
+        assert_ne!(base.hash(), base.clone().model("n").hash());
+        assert_ne!(base.hash(), base.clone().environment("k", "v").hash());
+        assert_ne!(
+            base.hash(),
+            base.clone().tool_input("t", Value::Null).hash()
+        );
+        assert_ne!(base.hash(), ContextSnapshot::new().hash());
+    }
+
+    #[test]
+    fn test_snapshot_round_trips_through_json() {
+        let snapshot = ContextSnapshot::new()
+            .prompt("p")
+            .tool_input("shell", serde_json::json!(["ls", "-l"]));
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let back: ContextSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.hash(), snapshot.hash());
+    }
+}
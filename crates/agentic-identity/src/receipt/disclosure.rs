@@ -0,0 +1,272 @@
+//! Selective disclosure — committing to `data` fields without revealing them.
+//!
+//! [`ActionContent::with_committed_data`](super::ActionContent::with_committed_data)
+//! replaces chosen top-level fields of an action's `data` with salted hash
+//! commitments in [`CommittedData`]; the rest stay in `data` as usual. The
+//! receipt signs the commitments, and the actor keeps the
+//! [`FieldOpenings`] (each field's salt and value) to itself.
+//!
+//! Later the actor hands a verifier a [`DisclosureProof`] opening only some
+//! of the fields, and [`verify_disclosure`] checks them against the signed
+//! receipt: the verifier learns that the amount was logged, and what it
+//! was, without learning the other committed fields. Field names are
+//! visible to everyone; only values are hidden.
+//!
+//! A field's commitment is the hex SHA-256 of the canonical JSON (RFC 8785)
+//! of `[salt, name, value]`. The random salt keeps guessable values, such as
+//! a yes/no flag, from being recovered by hashing candidates.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::canonical::canonicalize;
+use crate::error::{IdentityError, Result};
+
+use super::verify::verify_receipt;
+use super::{ActionReceipt, ReceiptId};
+
+/// Scheme tag of [`CommittedData`] written by this version.
+pub const COMMITMENT_SCHEME: &str = "sha256-salted-jcs";
+
+/// Salted hash commitments to withheld `data` fields, signed in the receipt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommittedData {
+    /// Always [`COMMITMENT_SCHEME`] for now.
+    pub scheme: String,
+    /// Hex commitment of each withheld field, by field name.
+    pub commitments: BTreeMap<String, String>,
+}
+
+/// The salt and value behind one field's commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldOpening {
+    /// Hex-encoded random salt.
+    pub salt: String,
+    pub value: serde_json::Value,
+}
+
+impl FieldOpening {
+    /// The commitment to this opening as field `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if the value cannot be
+    /// canonicalized.
+    pub fn commitment(&self, name: &str) -> Result<String> {
+        let json = canonicalize(&serde_json::json!([self.salt, name, self.value]))?;
+        Ok(hex::encode(Sha256::digest(json.as_bytes())))
+    }
+}
+
+/// Every opening of a receipt's committed fields, kept by the actor.
+///
+/// Anyone holding these can disclose any committed field, so they are as
+/// sensitive as the values themselves.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldOpenings {
+    pub fields: BTreeMap<String, FieldOpening>,
+}
+
+impl FieldOpenings {
+    /// Prove the values of `fields` of `receipt` and nothing else.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if a field has no opening, or
+    /// `IdentityError::InvalidInput` if the receipt does not commit to it
+    /// with this opening.
+    pub fn disclose(&self, receipt: &ActionReceipt, fields: &[&str]) -> Result<DisclosureProof> {
+        let mut disclosed = BTreeMap::new();
+        for &name in fields {
+            let opening = self
+                .fields
+                .get(name)
+                .ok_or_else(|| IdentityError::NotFound(format!("no opening for field '{name}'")))?;
+            check_opening(receipt, name, opening)?;
+            disclosed.insert(name.to_string(), opening.clone());
+        }
+        Ok(DisclosureProof {
+            receipt_id: receipt.id.clone(),
+            disclosed,
+        })
+    }
+}
+
+/// Openings of some of a receipt's committed fields, for a verifier.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisclosureProof {
+    pub receipt_id: ReceiptId,
+    pub disclosed: BTreeMap<String, FieldOpening>,
+}
+
+/// Commit to the `fields` of a JSON object `data`, returning what stays
+/// public, the commitments, and the openings.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidInput` if `data` is not an object, or a
+/// field is missing from it.
+#[cfg(feature = "signing")]
+pub(crate) fn commit_fields(
+    mut data: serde_json::Value,
+    fields: &[&str],
+) -> Result<(serde_json::Value, CommittedData, FieldOpenings)> {
+    let object = data.as_object_mut().ok_or_else(|| {
+        IdentityError::InvalidInput("only fields of a JSON object can be committed".into())
+    })?;
+    let mut commitments = BTreeMap::new();
+    let mut openings = FieldOpenings::default();
+    for &name in fields {
+        let value = object.remove(name).ok_or_else(|| {
+            IdentityError::InvalidInput(format!("data has no field '{name}' to commit"))
+        })?;
+        let opening = FieldOpening {
+            salt: hex::encode(crate::crypto::random::random_bytes::<16>()),
+            value,
+        };
+        commitments.insert(name.to_string(), opening.commitment(name)?);
+        openings.fields.insert(name.to_string(), opening);
+    }
+    let committed = CommittedData {
+        scheme: COMMITMENT_SCHEME.into(),
+        commitments,
+    };
+    Ok((data, committed, openings))
+}
+
+/// Verify `proof` against `receipt` and return the disclosed values.
+///
+/// Checks the receipt's signature, which covers the commitments, and that
+/// every disclosed value opens its field's commitment.
+///
+/// # Errors
+///
+/// Returns `IdentityError::SignatureInvalid` if the receipt does not
+/// verify, and `IdentityError::InvalidInput` if the proof is for another
+/// receipt or a value does not match its commitment.
+pub fn verify_disclosure(
+    receipt: &ActionReceipt,
+    proof: &DisclosureProof,
+) -> Result<BTreeMap<String, serde_json::Value>> {
+    if !verify_receipt(receipt)?.is_valid {
+        return Err(IdentityError::SignatureInvalid);
+    }
+    if proof.receipt_id != receipt.id {
+        return Err(IdentityError::InvalidInput(format!(
+            "disclosure is for {}, not {}",
+            proof.receipt_id, receipt.id
+        )));
+    }
+    proof
+        .disclosed
+        .iter()
+        .map(|(name, opening)| {
+            check_opening(receipt, name, opening)?;
+            Ok((name.clone(), opening.value.clone()))
+        })
+        .collect()
+}
+
+/// Check that `opening` opens the commitment `receipt` signs for `name`.
+fn check_opening(receipt: &ActionReceipt, name: &str, opening: &FieldOpening) -> Result<()> {
+    let committed = receipt
+        .action
+        .committed_data
+        .as_ref()
+        .filter(|c| c.scheme == COMMITMENT_SCHEME)
+        .and_then(|c| c.commitments.get(name));
+    match committed {
+        Some(commitment) if *commitment == opening.commitment(name)? => Ok(()),
+        Some(_) => Err(IdentityError::InvalidInput(format!(
+            "disclosed value of '{name}' does not match its commitment"
+        ))),
+        None => Err(IdentityError::InvalidInput(format!(
+            "receipt {} commits to no field '{name}'",
+            receipt.id
+        ))),
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::{ActionContent, ActionType};
+
+    fn payment(anchor: &IdentityAnchor) -> (ActionReceipt, FieldOpenings) {
+        let data = serde_json::json!({
+            "invoice": "inv-42",
+            "amount": 1250,
+            "payee": "acme",
+            "approved": true,
+        });
+        let (content, openings) = ActionContent::with_committed_data(
+            "Paid invoice",
+            data,
+            &["amount", "payee", "approved"],
+        )
+        .unwrap();
+        let receipt = ReceiptBuilder::new(anchor.id(), ActionType::Mutation, content)
+            .sign(anchor.signing_key())
+            .unwrap();
+        (receipt, openings)
+    }
+
+    #[test]
+    fn test_disclose_subset_of_fields() {
+        let anchor = IdentityAnchor::new(None);
+        let (receipt, openings) = payment(&anchor);
+
+        // Uncommitted fields stay public; committed values appear nowhere.
+        assert_eq!(
+            receipt.action.data,
+            Some(serde_json::json!({"invoice": "inv-42"}))
+        );
+        let json = serde_json::to_string(&receipt).unwrap();
+        assert!(!json.contains("acme") && !json.contains("1250"));
+
+        let proof = openings.disclose(&receipt, &["amount"]).unwrap();
+        let disclosed = verify_disclosure(&receipt, &proof).unwrap();
+        assert_eq!(disclosed.len(), 1);
+        assert_eq!(disclosed["amount"], serde_json::json!(1250));
+    }
+
+    #[test]
+    fn test_forged_disclosures_fail() {
+        let anchor = IdentityAnchor::new(None);
+        let (receipt, openings) = payment(&anchor);
+
+        let mut proof = openings.disclose(&receipt, &["amount"]).unwrap();
+        proof.disclosed.get_mut("amount").unwrap().value = serde_json::json!(12);
+        assert!(matches!(
+            verify_disclosure(&receipt, &proof),
+            Err(IdentityError::InvalidInput(_))
+        ));
+
+        // Same opening, different receipt.
+        let (other, _) = payment(&anchor);
+        let proof = openings.disclose(&receipt, &["payee"]).unwrap();
+        assert!(verify_disclosure(&other, &proof).is_err());
+
+        // Re-committing the receipt's fields breaks its signature.
+        let forged = FieldOpening {
+            salt: "00".into(),
+            value: serde_json::json!(12),
+        };
+        let mut altered = receipt.clone();
+        let committed = altered.action.committed_data.as_mut().unwrap();
+        committed
+            .commitments
+            .insert("amount".into(), forged.commitment("amount").unwrap());
+        assert!(matches!(
+            verify_disclosure(&altered, &proof),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        assert!(openings.disclose(&receipt, &["invoice"]).is_err());
+        assert!(ActionContent::with_committed_data("x", serde_json::json!([1]), &["0"]).is_err());
+    }
+}
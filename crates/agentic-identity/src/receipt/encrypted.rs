@@ -0,0 +1,247 @@
+//! Encrypted action data — payloads only named recipients can read.
+//!
+//! A receipt's `data` is normally readable by anyone who can read the
+//! receipt. [`ReceiptBuilder::encrypt_data_for`](super::receipt::ReceiptBuilder::encrypt_data_for)
+//! replaces it with an [`EncryptedData`] envelope before the receipt is
+//! hashed, so the signature covers the ciphertext: everyone can still
+//! verify the receipt, but only the recipients can decrypt its data.
+//!
+//! The data is encrypted once under a random content key with
+//! ChaCha20-Poly1305. The content key is wrapped for each recipient with a
+//! key derived (HKDF-SHA256) from an X25519 exchange between a fresh
+//! ephemeral key and the recipient's Ed25519 key, converted to X25519 (see
+//! [`crate::crypto::keys::x25519_public_from_ed25519`]). Recipients need no
+//! key besides the one they sign with.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "signing")]
+use ed25519_dalek::{SigningKey, VerifyingKey};
+#[cfg(feature = "signing")]
+use zeroize::Zeroize;
+
+#[cfg(feature = "signing")]
+use crate::crypto::derivation::derive_key;
+#[cfg(feature = "signing")]
+use crate::crypto::encryption::{decrypt, encrypt};
+#[cfg(feature = "signing")]
+use crate::crypto::keys::{x25519_public_from_ed25519, X25519KeyPair};
+#[cfg(feature = "signing")]
+use crate::crypto::random::random_bytes;
+#[cfg(feature = "signing")]
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+
+/// Scheme tag of [`EncryptedData`] envelopes written by this version.
+pub const ENCRYPTION_SCHEME: &str = "x25519-hkdf-sha256-chacha20poly1305";
+
+/// Action data encrypted to a set of recipient identities.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedData {
+    /// Always [`ENCRYPTION_SCHEME`] for now.
+    pub scheme: String,
+    /// Ephemeral X25519 public key of the sender (base64).
+    pub ephemeral_key: String,
+    /// Nonce of the data ciphertext (base64).
+    pub nonce: String,
+    /// The JSON data, encrypted under the content key (base64).
+    pub ciphertext: String,
+    /// The content key, wrapped for each recipient.
+    pub recipients: Vec<WrappedKey>,
+}
+
+/// The content key of an [`EncryptedData`], wrapped for one recipient.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub recipient: IdentityId,
+    /// Nonce of the wrapped key (base64).
+    pub nonce: String,
+    /// The content key, encrypted under the recipient's key (base64).
+    pub wrapped_key: String,
+}
+
+impl EncryptedData {
+    /// Encrypt `data` so each of `recipients`, given as identity ID and
+    /// Ed25519 public key, can decrypt it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` for no recipients, or
+    /// `IdentityError::EncryptionFailed` if encryption fails.
+    #[cfg(feature = "signing")]
+    pub fn seal(
+        data: &serde_json::Value,
+        recipients: &[(IdentityId, VerifyingKey)],
+    ) -> Result<Self> {
+        if recipients.is_empty() {
+            return Err(IdentityError::InvalidInput(
+                "encrypted data needs at least one recipient".into(),
+            ));
+        }
+        let plaintext = serde_json::to_vec(data)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        let mut content_key: [u8; 32] = random_bytes();
+        let sealed = encrypt(&content_key, &plaintext);
+
+        let ephemeral = X25519KeyPair::generate();
+        let ephemeral_key = b64(ephemeral.public_key_bytes());
+        let wrapped: Result<Vec<WrappedKey>> = recipients
+            .iter()
+            .map(|(recipient, key)| {
+                let mut shared = ephemeral.diffie_hellman(&x25519_public_from_ed25519(key));
+                let wrapping_key = derive_key(&shared, &wrap_context(&ephemeral_key, recipient));
+                shared.zeroize();
+                let mut wrapping_key = wrapping_key?;
+                let (nonce, wrapped_key) = encrypt(&wrapping_key, &content_key)?;
+                wrapping_key.zeroize();
+                Ok(WrappedKey {
+                    recipient: recipient.clone(),
+                    nonce: b64(nonce),
+                    wrapped_key: b64(wrapped_key),
+                })
+            })
+            .collect();
+        content_key.zeroize();
+
+        let (nonce, ciphertext) = sealed?;
+        Ok(Self {
+            scheme: ENCRYPTION_SCHEME.into(),
+            ephemeral_key,
+            nonce: b64(nonce),
+            ciphertext: b64(ciphertext),
+            recipients: wrapped?,
+        })
+    }
+
+    /// Decrypt the data as `recipient`, holding its Ed25519 `signing_key`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if `recipient` is not a recipient,
+    /// `IdentityError::InvalidInput` for an unknown scheme or malformed
+    /// envelope, and `IdentityError::DecryptionFailed` if the key is not
+    /// the recipient's or the envelope was altered.
+    #[cfg(feature = "signing")]
+    pub fn open(
+        &self,
+        recipient: &IdentityId,
+        signing_key: &SigningKey,
+    ) -> Result<serde_json::Value> {
+        if self.scheme != ENCRYPTION_SCHEME {
+            return Err(IdentityError::InvalidInput(format!(
+                "unsupported data encryption scheme: {}",
+                self.scheme
+            )));
+        }
+        let wrapped = self
+            .recipients
+            .iter()
+            .find(|w| &w.recipient == recipient)
+            .ok_or_else(|| {
+                IdentityError::NotFound(format!("{recipient} is not a recipient of this data"))
+            })?;
+
+        let ephemeral: [u8; 32] = unb64(&self.ephemeral_key)?
+            .try_into()
+            .map_err(|_| IdentityError::InvalidInput("ephemeral key must be 32 bytes".into()))?;
+        let mut shared = X25519KeyPair::from_ed25519(signing_key)
+            .diffie_hellman(&x25519_dalek::PublicKey::from(ephemeral));
+        let wrapping_key = derive_key(&shared, &wrap_context(&self.ephemeral_key, recipient));
+        shared.zeroize();
+        let mut wrapping_key = wrapping_key?;
+        let content_key = decrypt(
+            &wrapping_key,
+            &unb64(&wrapped.nonce)?,
+            &unb64(&wrapped.wrapped_key)?,
+        );
+        wrapping_key.zeroize();
+        let mut content_key: [u8; 32] = content_key
+            .map_err(|_| not_decryptable(recipient))?
+            .try_into()
+            .map_err(|_| not_decryptable(recipient))?;
+
+        let plaintext = decrypt(
+            &content_key,
+            &unb64(&self.nonce)?,
+            &unb64(&self.ciphertext)?,
+        );
+        content_key.zeroize();
+        let plaintext = plaintext.map_err(|_| not_decryptable(recipient))?;
+        serde_json::from_slice(&plaintext).map_err(|e| {
+            IdentityError::DecryptionFailed(format!("decrypted data is not JSON: {e}"))
+        })
+    }
+
+    /// The identities that can decrypt the data.
+    pub fn recipient_ids(&self) -> impl Iterator<Item = &IdentityId> {
+        self.recipients.iter().map(|w| &w.recipient)
+    }
+}
+
+/// HKDF info binding a wrapping key to the envelope and the recipient.
+#[cfg(feature = "signing")]
+fn wrap_context(ephemeral_key: &str, recipient: &IdentityId) -> String {
+    format!("agentic-identity/receipt-data/{ephemeral_key}/{recipient}")
+}
+
+#[cfg(feature = "signing")]
+fn not_decryptable(recipient: &IdentityId) -> IdentityError {
+    IdentityError::DecryptionFailed(format!("data cannot be decrypted as {recipient}"))
+}
+
+#[cfg(feature = "signing")]
+fn b64(bytes: impl AsRef<[u8]>) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+}
+
+#[cfg(feature = "signing")]
+fn unb64(value: &str) -> Result<Vec<u8>> {
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, value)
+        .map_err(|e| IdentityError::InvalidInput(format!("invalid base64 in encrypted data: {e}")))
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+
+    fn recipient(anchor: &IdentityAnchor) -> (IdentityId, VerifyingKey) {
+        (anchor.id(), *anchor.verifying_key())
+    }
+
+    #[test]
+    fn test_each_recipient_can_open() {
+        let alice = IdentityAnchor::new(None);
+        let bob = IdentityAnchor::new(None);
+        let data = serde_json::json!({"patient": "p-17", "dose_mg": 40});
+
+        let sealed = EncryptedData::seal(&data, &[recipient(&alice), recipient(&bob)]).unwrap();
+        assert!(!sealed.ciphertext.contains("p-17"));
+        assert_eq!(sealed.recipient_ids().count(), 2);
+        assert_eq!(sealed.open(&alice.id(), alice.signing_key()).unwrap(), data);
+        assert_eq!(sealed.open(&bob.id(), bob.signing_key()).unwrap(), data);
+    }
+
+    #[test]
+    fn test_outsiders_and_tampering_fail() {
+        let alice = IdentityAnchor::new(None);
+        let mallory = IdentityAnchor::new(None);
+        let sealed =
+            EncryptedData::seal(&serde_json::json!("secret"), &[recipient(&alice)]).unwrap();
+
+        assert!(matches!(
+            sealed.open(&mallory.id(), mallory.signing_key()),
+            Err(IdentityError::NotFound(_))
+        ));
+        // Claiming to be alice without her key gets nowhere.
+        assert!(matches!(
+            sealed.open(&alice.id(), mallory.signing_key()),
+            Err(IdentityError::DecryptionFailed(_))
+        ));
+
+        let mut altered = sealed.clone();
+        altered.ciphertext = sealed.recipients[0].wrapped_key.clone();
+        assert!(altered.open(&alice.id(), alice.signing_key()).is_err());
+        assert!(EncryptedData::seal(&serde_json::json!(1), &[]).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! Receipt graphs — how an agent's receipts link together, drawn for people.
+//!
+//! A [`ReceiptGraph`] has a node per receipt and an edge per link between
+//! them: a receipt follows the one it chains to, a chain starts at the
+//! identity that acted, a spawn receipt leads to the child identity it
+//! created, and a grant exercise is led to by the grantor whose grant it
+//! used. Identities appear only where such a link names them. A receipt that
+//! is chained to but not in the graph shows up as a missing node, so gaps
+//! left by retention or a partial export stay visible.
+//!
+//! [`ReceiptGraph::to_dot`] renders Graphviz DOT and
+//! [`ReceiptGraph::to_mermaid`] a Mermaid flowchart.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::action::ActionType;
+use super::receipt::ActionReceipt;
+use crate::error::Result;
+use crate::storage::ReceiptStore;
+
+/// Characters of a description kept in a node label.
+const MAX_LABEL_CHARS: usize = 40;
+
+/// Characters of an ID kept in a node label.
+const SHORT_ID_CHARS: usize = 14;
+
+/// What a node stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Receipt,
+    Identity,
+    /// A receipt chained to but not in the graph.
+    Missing,
+}
+
+/// A receipt or identity in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// The receipt or identity ID.
+    pub id: String,
+    pub kind: NodeKind,
+    /// Short human-readable text; may span lines.
+    pub label: String,
+}
+
+/// How two nodes are linked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// From a receipt to the receipt chained to it.
+    Chain,
+    /// From an identity to the first receipt of one of its chains.
+    Acted,
+    /// From a spawn receipt to the child identity it created.
+    Spawned,
+    /// From a grantor to a receipt exercising its grant.
+    Granted,
+}
+
+impl EdgeKind {
+    /// Edge label in rendered output; chain edges are left unlabeled.
+    fn label(self) -> Option<&'static str> {
+        match self {
+            Self::Chain => None,
+            Self::Acted => Some("acted"),
+            Self::Spawned => Some("spawned"),
+            Self::Granted => Some("granted"),
+        }
+    }
+}
+
+/// A directed link between two nodes, by node ID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+/// The chain and delegation links among a set of receipts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptGraph {
+    /// Receipts oldest first, then identities and missing receipts in the
+    /// order they were first linked.
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl ReceiptGraph {
+    /// The graph of `receipts`. Order does not matter, and a receipt listed
+    /// twice is drawn once.
+    pub fn from_receipts(receipts: &[ActionReceipt]) -> Self {
+        let mut sorted: Vec<&ActionReceipt> = receipts.iter().collect();
+        sorted.sort_by(|a, b| (a.timestamp, &a.id.0).cmp(&(b.timestamp, &b.id.0)));
+        sorted.dedup_by(|a, b| a.id == b.id);
+
+        let mut graph = Self {
+            nodes: sorted
+                .iter()
+                .map(|receipt| GraphNode {
+                    id: receipt.id.0.clone(),
+                    kind: NodeKind::Receipt,
+                    label: receipt_label(receipt),
+                })
+                .collect(),
+            edges: Vec::new(),
+        };
+        let mut known: HashSet<String> = graph.nodes.iter().map(|n| n.id.clone()).collect();
+        let mut extra = Vec::new();
+        let mut node = |id: &str, kind: NodeKind| {
+            if known.insert(id.to_string()) {
+                let label = match kind {
+                    NodeKind::Missing => format!("missing\n{}", short_id(id)),
+                    _ => short_id(id),
+                };
+                extra.push(GraphNode {
+                    id: id.to_string(),
+                    kind,
+                    label,
+                });
+            }
+        };
+
+        for receipt in &sorted {
+            let id = &receipt.id.0;
+            match &receipt.previous_receipt {
+                Some(previous) => {
+                    node(&previous.0, NodeKind::Missing);
+                    graph.edge(&previous.0, id, EdgeKind::Chain);
+                }
+                None => {
+                    node(&receipt.actor.0, NodeKind::Identity);
+                    graph.edge(&receipt.actor.0, id, EdgeKind::Acted);
+                }
+            }
+            if receipt.action_type != ActionType::Delegation {
+                continue;
+            }
+            let data = receipt.action.data.as_ref();
+            if let Some(child) = data.and_then(|d| d["child_id"].as_str()) {
+                node(child, NodeKind::Identity);
+                graph.edge(id, child, EdgeKind::Spawned);
+            }
+            if let Some(grantor) = data.and_then(|d| d["grantor"].as_str()) {
+                node(grantor, NodeKind::Identity);
+                graph.edge(grantor, id, EdgeKind::Granted);
+            }
+        }
+        graph.nodes.extend(extra);
+        graph
+    }
+
+    /// The graph of every receipt in `store`. Unreadable receipts are
+    /// skipped, as in [`ReceiptStore::load_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the store directory cannot be read.
+    pub fn from_store(store: &ReceiptStore) -> Result<Self> {
+        Ok(Self::from_receipts(&store.load_all()?))
+    }
+
+    fn edge(&mut self, from: &str, to: &str, kind: EdgeKind) {
+        self.edges.push(GraphEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            kind,
+        });
+    }
+
+    /// Graphviz DOT, drawn left to right. Receipts are boxes, identities
+    /// ellipses and missing receipts dashed boxes.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph receipts {\n    rankdir=LR;\n    node [shape=box];\n");
+        for node in &self.nodes {
+            let style = match node.kind {
+                NodeKind::Receipt => "",
+                NodeKind::Identity => ", shape=ellipse",
+                NodeKind::Missing => ", style=dashed",
+            };
+            out.push_str(&format!(
+                "    \"{}\" [label=\"{}\"{style}];\n",
+                dot_escape(&node.id),
+                dot_escape(&node.label)
+            ));
+        }
+        for edge in &self.edges {
+            let label = edge
+                .kind
+                .label()
+                .map(|l| format!(" [label=\"{l}\"]"))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\"{label};\n",
+                dot_escape(&edge.from),
+                dot_escape(&edge.to)
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// A Mermaid flowchart, drawn left to right. Nodes are named `n0`,
+    /// `n1`, … in [`nodes`](Self::nodes) order; receipts are rectangles,
+    /// identities stadiums and missing receipts dashed rectangles.
+    pub fn to_mermaid(&self) -> String {
+        let names: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (node.id.as_str(), format!("n{i}")))
+            .collect();
+        let mut out = String::from("flowchart LR\n");
+        let mut missing = Vec::new();
+        for node in &self.nodes {
+            let name = &names[node.id.as_str()];
+            let label = mermaid_escape(&node.label);
+            match node.kind {
+                NodeKind::Receipt => out.push_str(&format!("    {name}[\"{label}\"]\n")),
+                NodeKind::Identity => out.push_str(&format!("    {name}([\"{label}\"])\n")),
+                NodeKind::Missing => {
+                    out.push_str(&format!("    {name}[\"{label}\"]\n"));
+                    missing.push(name.as_str());
+                }
+            }
+        }
+        for edge in &self.edges {
+            let (Some(from), Some(to)) =
+                (names.get(edge.from.as_str()), names.get(edge.to.as_str()))
+            else {
+                continue;
+            };
+            match edge.kind.label() {
+                Some(label) => out.push_str(&format!("    {from} -->|{label}| {to}\n")),
+                None => out.push_str(&format!("    {from} --> {to}\n")),
+            }
+        }
+        if !missing.is_empty() {
+            out.push_str("    classDef missing stroke-dasharray: 5 5\n");
+            out.push_str(&format!("    class {} missing\n", missing.join(",")));
+        }
+        out
+    }
+}
+
+/// Action type and description on one line, short ID on the next.
+fn receipt_label(receipt: &ActionReceipt) -> String {
+    let description: String = receipt
+        .action
+        .description
+        .chars()
+        .take(MAX_LABEL_CHARS)
+        .collect();
+    let ellipsis = if receipt.action.description.chars().count() > MAX_LABEL_CHARS {
+        "…"
+    } else {
+        ""
+    };
+    format!(
+        "{}: {description}{ellipsis}\n{}",
+        receipt.action_type.as_tag(),
+        short_id(&receipt.id.0)
+    )
+}
+
+pub(crate) fn short_id(id: &str) -> String {
+    if id.chars().count() > SHORT_ID_CHARS {
+        format!("{}…", id.chars().take(SHORT_ID_CHARS).collect::<String>())
+    } else {
+        id.to_string()
+    }
+}
+
+/// Escape text for a double-quoted DOT string; newlines become line breaks.
+fn dot_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escape text for a double-quoted Mermaid label; newlines become line
+/// breaks.
+pub(crate) fn mermaid_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("#quot;"),
+            '#' => out.push_str("#35;"),
+            '<' => out.push_str("#lt;"),
+            '>' => out.push_str("#gt;"),
+            '\n' => out.push_str("<br/>"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::action::ActionContent;
+    use crate::receipt::receipt::ReceiptBuilder;
+
+    fn chain(anchor: &IdentityAnchor, descriptions: &[&str]) -> Vec<ActionReceipt> {
+        let mut receipts: Vec<ActionReceipt> = Vec::new();
+        for description in descriptions {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Observation,
+                ActionContent::new(*description),
+            );
+            if let Some(prev) = receipts.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            receipts.push(builder.sign(anchor.signing_key()).unwrap());
+        }
+        receipts
+    }
+
+    #[test]
+    fn test_chain_hangs_from_its_actor() {
+        let anchor = IdentityAnchor::new(None);
+        let receipts = chain(&anchor, &["first", "second", "third"]);
+        let graph = ReceiptGraph::from_receipts(&receipts);
+
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes[3].kind, NodeKind::Identity);
+        assert_eq!(graph.nodes[3].id, anchor.id().0);
+        // Receipts signed within the same microsecond may come out in
+        // either order, so edges are compared as a set.
+        let expected = [
+            (anchor.id().0, &receipts[0], EdgeKind::Acted),
+            (receipts[0].id.0.clone(), &receipts[1], EdgeKind::Chain),
+            (receipts[1].id.0.clone(), &receipts[2], EdgeKind::Chain),
+        ];
+        assert_eq!(graph.edges.len(), expected.len());
+        for (from, to, kind) in expected {
+            assert!(graph.edges.contains(&GraphEdge {
+                from,
+                to: to.id.0.clone(),
+                kind,
+            }));
+        }
+    }
+
+    #[test]
+    fn test_missing_predecessor_is_drawn() {
+        let anchor = IdentityAnchor::new(None);
+        let receipts = chain(&anchor, &["pruned", "kept"]);
+        let graph = ReceiptGraph::from_receipts(&receipts[1..]);
+
+        let missing = graph
+            .nodes
+            .iter()
+            .find(|n| n.kind == NodeKind::Missing)
+            .unwrap();
+        assert_eq!(missing.id, receipts[0].id.0);
+        assert!(graph.to_dot().contains("style=dashed"));
+        assert!(graph.to_mermaid().contains("class n1 missing"));
+    }
+
+    #[test]
+    fn test_delegation_links_identities() {
+        let parent = IdentityAnchor::new(None);
+        let child = IdentityAnchor::new(None);
+        let spawn = ReceiptBuilder::new(
+            parent.id(),
+            ActionType::Delegation,
+            ActionContent::with_data(
+                "Spawned worker child: build",
+                serde_json::json!({ "child_id": child.id().0 }),
+            ),
+        )
+        .sign(parent.signing_key())
+        .unwrap();
+        let exercise = ReceiptBuilder::new(
+            child.id(),
+            ActionType::Delegation,
+            ActionContent::with_data(
+                "Exercised deploy:prod",
+                serde_json::json!({ "grantor": parent.id().0 }),
+            ),
+        )
+        .sign(child.signing_key())
+        .unwrap();
+        let graph = ReceiptGraph::from_receipts(&[exercise.clone(), spawn.clone()]);
+
+        let has = |from: &str, to: &str, kind| {
+            graph
+                .edges
+                .iter()
+                .any(|e| e.from == from && e.to == to && e.kind == kind)
+        };
+        assert!(has(&spawn.id.0, &child.id().0, EdgeKind::Spawned));
+        assert!(has(&parent.id().0, &exercise.id.0, EdgeKind::Granted));
+        assert!(has(&child.id().0, &exercise.id.0, EdgeKind::Acted));
+        // Each identity is drawn once however many links name it.
+        let identities = graph
+            .nodes
+            .iter()
+            .filter(|n| n.kind == NodeKind::Identity)
+            .count();
+        assert_eq!(identities, 2);
+    }
+
+    #[test]
+    fn test_renderers_escape_labels() {
+        let anchor = IdentityAnchor::new(None);
+        let receipts = chain(&anchor, &["say \"hi\" <now> #1 \\ done"]);
+        let graph = ReceiptGraph::from_receipts(&receipts);
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph receipts {"));
+        assert!(dot.contains("say \\\"hi\\\" <now> #1 \\\\ done\\n"));
+        assert!(dot.contains("[label=\"acted\"]"));
+
+        let mermaid = graph.to_mermaid();
+        assert!(mermaid.starts_with("flowchart LR\n"));
+        assert!(mermaid.contains("say #quot;hi#quot; #lt;now#gt; #35;1 \\ done<br/>"));
+        assert!(mermaid.contains("n1 -->|acted| n0"));
+    }
+
+    #[test]
+    fn test_from_store_matches_from_receipts() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let receipts = chain(&anchor, &["one", "two"]);
+        for receipt in &receipts {
+            store.save(receipt).unwrap();
+        }
+
+        let graph = ReceiptGraph::from_store(&store).unwrap();
+        assert_eq!(graph, ReceiptGraph::from_receipts(&receipts));
+    }
+}
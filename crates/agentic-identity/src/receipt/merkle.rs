@@ -0,0 +1,386 @@
+//! Merkle trees over receipts, and signed receipt batches.
+//!
+//! An agent issuing receipts at high volume can batch them: a
+//! [`ReceiptBatch`] builds N receipts under the agent's key, puts their
+//! hashes in a Merkle tree, and signs only the root. Each receipt then
+//! carries no signature of its own; instead the batch hands out a compact
+//! [`InclusionProof`] per receipt (one sibling hash per tree level), and
+//! [`verify_batch_inclusion`] checks a single receipt against the signed
+//! [`BatchRoot`] without the rest of the batch.
+//!
+//! Tree shape: leaves are `SHA-256(0x00 || receipt_hash)`, interior nodes are
+//! `SHA-256(0x01 || left || right)`, and an unpaired node is promoted to the
+//! next level unchanged. The same tree summarizes receipt chains for
+//! [`notary`](super::notary) anchors.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityAnchor;
+use crate::identity::IdentityId;
+
+#[cfg(feature = "signing")]
+use super::receipt::ReceiptBuilder;
+use super::receipt::{ActionReceipt, ReceiptId};
+
+// ── Tree ─────────────────────────────────────────────────────────────────────
+
+/// One step of a Merkle inclusion proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleStep {
+    /// Hex-encoded sibling hash.
+    pub sibling: String,
+    /// Is the sibling the left operand?
+    pub sibling_is_left: bool,
+}
+
+fn leaf_hash(receipt: &ActionReceipt) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(receipt.receipt_hash.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks(2) yields one or two items"),
+        })
+        .collect()
+}
+
+/// Merkle root over `chain` (all zeros for an empty chain).
+pub fn merkle_root(chain: &[ActionReceipt]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = chain.iter().map(leaf_hash).collect();
+    if level.is_empty() {
+        return [0u8; 32];
+    }
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// Inclusion proof for `chain[index]`, or `None` if out of range.
+pub fn merkle_proof(chain: &[ActionReceipt], index: usize) -> Option<Vec<MerkleStep>> {
+    if index >= chain.len() {
+        return None;
+    }
+    let mut level: Vec<[u8; 32]> = chain.iter().map(leaf_hash).collect();
+    let mut position = index;
+    let mut steps = Vec::new();
+
+    while level.len() > 1 {
+        let sibling = position ^ 1;
+        if sibling < level.len() {
+            steps.push(MerkleStep {
+                sibling: hex::encode(level[sibling]),
+                sibling_is_left: sibling < position,
+            });
+        }
+        level = next_level(&level);
+        position /= 2;
+    }
+    Some(steps)
+}
+
+/// Does `proof` show that `receipt` is included under the hex `root`?
+pub fn verify_inclusion(receipt: &ActionReceipt, proof: &[MerkleStep], root: &str) -> bool {
+    let mut current = leaf_hash(receipt);
+    for step in proof {
+        let Some(sibling) = hex::decode(&step.sibling)
+            .ok()
+            .and_then(|b| <[u8; 32]>::try_from(b).ok())
+        else {
+            return false;
+        };
+        current = if step.sibling_is_left {
+            node_hash(&sibling, &current)
+        } else {
+            node_hash(&current, &sibling)
+        };
+    }
+    hex::encode(current) == root
+}
+
+// ── Signed batches ───────────────────────────────────────────────────────────
+
+/// The signed root of a receipt batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchRoot {
+    pub signer: IdentityId,
+    /// Base64 public key the root was signed with.
+    pub signer_key: String,
+    /// Hex-encoded Merkle root of the batch's receipts.
+    pub root: String,
+    /// Number of receipts in the batch.
+    pub leaf_count: u64,
+    /// When the root was signed (microseconds since epoch).
+    pub signed_at: u64,
+    pub signature: String,
+    /// Absent on roots signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// Everything in a [`BatchRoot`] except its signature.
+#[derive(Serialize)]
+struct BatchRootPayload<'a> {
+    signer: &'a IdentityId,
+    signer_key: &'a str,
+    root: &'a str,
+    leaf_count: u64,
+    signed_at: u64,
+}
+
+impl BatchRoot {
+    fn signing_input(&self) -> Result<String> {
+        serde_json::to_string(&BatchRootPayload {
+            signer: &self.signer,
+            signer_key: &self.signer_key,
+            root: &self.root,
+            leaf_count: self.leaf_count,
+            signed_at: self.signed_at,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+    }
+}
+
+/// Proof that one receipt is in a batch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub receipt_id: ReceiptId,
+    /// Position of the receipt in the batch.
+    pub index: u64,
+    /// Sibling hashes from the leaf up to the root.
+    pub steps: Vec<MerkleStep>,
+}
+
+/// Receipts whose hashes are covered by one signed Merkle root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptBatch {
+    pub root: BatchRoot,
+    /// The batch's receipts, in tree order. Their own `signature` is empty.
+    pub receipts: Vec<ActionReceipt>,
+}
+
+impl ReceiptBatch {
+    /// Build each receipt under `anchor`'s key and sign the batch root.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` for an empty batch or a receipt
+    /// whose actor is not `anchor`, and any error from building a receipt.
+    #[cfg(feature = "signing")]
+    pub fn sign(anchor: &IdentityAnchor, builders: Vec<ReceiptBuilder>) -> Result<Self> {
+        let signer = anchor.id();
+        let signer_key = anchor.public_key_base64();
+        let receipts = builders
+            .into_iter()
+            .map(|builder| {
+                let receipt = builder.build(signer_key.clone())?;
+                if receipt.actor != signer {
+                    return Err(IdentityError::InvalidInput(format!(
+                        "receipt actor {} is not the batch signer {signer}",
+                        receipt.actor
+                    )));
+                }
+                Ok(receipt)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if receipts.is_empty() {
+            return Err(IdentityError::InvalidInput(
+                "a receipt batch needs at least one receipt".into(),
+            ));
+        }
+
+        let mut root = BatchRoot {
+            signer,
+            signer_key,
+            root: hex::encode(merkle_root(&receipts)),
+            leaf_count: receipts.len() as u64,
+            signed_at: crate::time::now_micros(),
+            signature: String::new(),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        let input = root.signing_input()?;
+        root.signature = signing::sign_in_domain(
+            anchor.signing_key(),
+            SignatureDomain::ReceiptBatch,
+            input.as_bytes(),
+        );
+        Ok(Self { root, receipts })
+    }
+
+    /// Inclusion proof for the receipt at `index`, or `None` if out of range.
+    pub fn proof(&self, index: usize) -> Option<InclusionProof> {
+        Some(InclusionProof {
+            receipt_id: self.receipts.get(index)?.id.clone(),
+            index: index as u64,
+            steps: merkle_proof(&self.receipts, index)?,
+        })
+    }
+
+    /// Inclusion proof for the receipt with ID `id`, if it is in the batch.
+    pub fn proof_for(&self, id: &ReceiptId) -> Option<InclusionProof> {
+        self.proof(self.receipts.iter().position(|r| &r.id == id)?)
+    }
+}
+
+/// Verify that `root` was signed with `public_key` (base64) and not altered.
+///
+/// The key is taken from the caller, never from the root, and must be the
+/// one the root names and the one its signer ID derives from.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidKey` if `public_key` does not decode or is
+/// not the root's signer key, and `IdentityError::SignatureInvalid` if the
+/// root was altered after signing.
+pub fn verify_batch_root(root: &BatchRoot, public_key: &str) -> Result<()> {
+    let key = crate::identity::anchor::decode_public_key(public_key)?;
+    if root.signer_key != public_key || IdentityId::from_verifying_key(&key) != root.signer {
+        return Err(IdentityError::InvalidKey(format!(
+            "batch root was not signed by the key of {}",
+            root.signer
+        )));
+    }
+    signing::verify_versioned(
+        &key,
+        SignatureDomain::ReceiptBatch,
+        root.signature_version,
+        root.signing_input()?.as_bytes(),
+        &root.signature,
+    )
+}
+
+/// Verify a single receipt from a batch against the batch's signed root.
+///
+/// Checks the root's signature against `public_key`, that the receipt was
+/// issued under the signer's key and its hash covers its content, and that
+/// `proof` leads from the receipt to the root.
+///
+/// # Errors
+///
+/// As for [`verify_batch_root`]; in addition `IdentityError::InvalidInput`
+/// if the receipt is not the signer's or not the one the proof is for,
+/// `IdentityError::SignatureInvalid` if its content does not match its hash,
+/// and `IdentityError::InvalidChain` if the proof does not reach the root.
+pub fn verify_batch_inclusion(
+    receipt: &ActionReceipt,
+    proof: &InclusionProof,
+    root: &BatchRoot,
+    public_key: &str,
+) -> Result<()> {
+    verify_batch_root(root, public_key)?;
+    if receipt.actor != root.signer || receipt.actor_key != root.signer_key {
+        return Err(IdentityError::InvalidInput(format!(
+            "receipt {} was not issued by batch signer {}",
+            receipt.id, root.signer
+        )));
+    }
+    if proof.receipt_id != receipt.id {
+        return Err(IdentityError::InvalidInput(format!(
+            "inclusion proof is for receipt {}, not {}",
+            proof.receipt_id, receipt.id
+        )));
+    }
+    if receipt.compute_hash() != receipt.receipt_hash {
+        return Err(IdentityError::SignatureInvalid);
+    }
+    if proof.index >= root.leaf_count || !verify_inclusion(receipt, &proof.steps, &root.root) {
+        return Err(IdentityError::InvalidChain);
+    }
+    Ok(())
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::receipt::{ActionContent, ActionType};
+
+    fn builders(anchor: &IdentityAnchor, count: usize) -> Vec<ReceiptBuilder> {
+        (0..count)
+            .map(|i| {
+                ReceiptBuilder::new(
+                    anchor.id(),
+                    ActionType::Observation,
+                    ActionContent::new(format!("reading {i}")),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_receipts_verify_individually() {
+        let anchor = IdentityAnchor::new(None);
+        let key = anchor.public_key_base64();
+        let batch = ReceiptBatch::sign(&anchor, builders(&anchor, 5)).unwrap();
+        assert_eq!(batch.root.leaf_count, 5);
+        assert!(batch.receipts.iter().all(|r| r.signature.is_empty()));
+
+        // A verifier holding only the root, one receipt and its proof.
+        let root: BatchRoot =
+            serde_json::from_str(&serde_json::to_string(&batch.root).unwrap()).unwrap();
+        for (i, receipt) in batch.receipts.iter().enumerate() {
+            let proof = batch.proof_for(&receipt.id).unwrap();
+            assert_eq!(proof.index, i as u64);
+            assert!(verify_batch_inclusion(receipt, &proof, &root, &key).is_ok());
+        }
+        assert!(batch.proof(5).is_none());
+
+        let other = IdentityAnchor::new(None);
+        assert!(matches!(
+            verify_batch_root(&root, &other.public_key_base64()),
+            Err(IdentityError::InvalidKey(_))
+        ));
+        assert!(ReceiptBatch::sign(&anchor, Vec::new()).is_err());
+        assert!(ReceiptBatch::sign(&anchor, builders(&other, 1)).is_err());
+    }
+
+    #[test]
+    fn test_batch_inclusion_detects_tampering() {
+        let anchor = IdentityAnchor::new(None);
+        let key = anchor.public_key_base64();
+        let batch = ReceiptBatch::sign(&anchor, builders(&anchor, 4)).unwrap();
+        let proof = batch.proof(2).unwrap();
+
+        let mut edited = batch.receipts[2].clone();
+        edited.action.description = "reading 9".into();
+        assert!(matches!(
+            verify_batch_inclusion(&edited, &proof, &batch.root, &key),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        let wrong_proof = batch.proof(1).unwrap();
+        assert!(
+            verify_batch_inclusion(&batch.receipts[2], &wrong_proof, &batch.root, &key).is_err()
+        );
+
+        let mut forged = batch.root.clone();
+        forged.leaf_count = 3;
+        assert!(matches!(
+            verify_batch_inclusion(&batch.receipts[2], &proof, &forged, &key),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+}
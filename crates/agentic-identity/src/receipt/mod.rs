@@ -1,13 +1,44 @@
 //! Action receipts — signed proofs that an agent took an action.
 
 pub mod action;
+pub mod bundle;
 pub mod chain;
+pub mod context;
+pub mod disclosure;
+pub mod encrypted;
+pub mod graph;
+pub mod merkle;
+pub mod notary;
+pub mod policy;
 #[allow(clippy::module_inception)]
 pub mod receipt;
+pub mod schema;
+pub mod session_log;
+pub mod vc;
 pub mod verify;
 pub mod witness;
 
-pub use action::{ActionContent, ActionType};
+pub use action::{ActionContent, ActionType, DataRef};
+pub use bundle::{verify_bundle, BundleReceiptStatus, BundleVerification, ReceiptBundle};
+pub use chain::{
+    verify_chain_stream, verify_chain_with_policy, ChainBreak, ChainBreakReason, ChainPolicy,
+    ChainSkew, ChainVerification, StreamVerification,
+};
+pub use context::{ContextSnapshot, ToolInput};
+pub use disclosure::{
+    verify_disclosure, CommittedData, DisclosureProof, FieldOpening, FieldOpenings,
+    COMMITMENT_SCHEME,
+};
+pub use encrypted::{EncryptedData, WrappedKey, ENCRYPTION_SCHEME};
+pub use graph::{EdgeKind, GraphEdge, GraphNode, NodeKind, ReceiptGraph};
+pub use merkle::{
+    verify_batch_inclusion, verify_batch_root, BatchRoot, InclusionProof, ReceiptBatch,
+};
+pub use notary::{NotaryAnchor, NotaryHook, NotaryReceipt};
+pub use policy::RequirementPolicy;
 pub use receipt::{ActionReceipt, ReceiptId};
+pub use schema::DataSchemas;
+pub use session_log::{verify_session_log, SessionLog, SessionLogEntry};
+pub use vc::{from_vc_jwt, receipt_credential, to_vc_jwt};
 pub use verify::ReceiptVerification;
-pub use witness::WitnessSignature;
+pub use witness::{witness_signing_input, WitnessSignature};
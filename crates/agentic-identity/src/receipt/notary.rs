@@ -0,0 +1,157 @@
+//! Notarization — publishing receipt-chain roots to an external notary.
+//!
+//! A chain of receipts is summarized by a Merkle root over the receipts'
+//! hashes. The root can be published to an external notary (a transparency
+//! log, a blockchain, ...) through a [`NotaryHook`]; the resulting
+//! [`NotaryAnchor`] binds the notary's proof to that exact root and to the
+//! receipts it covers, and [`merkle_proof`] / [`verify_inclusion`] later show
+//! that a single receipt was among them.
+//!
+//! The tree is built as described in [`merkle`](super::merkle).
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+
+pub use super::merkle::{merkle_proof, merkle_root, verify_inclusion, MerkleStep};
+use super::receipt::{ActionReceipt, ReceiptId};
+
+/// An external notary that timestamps/publishes a 32-byte root.
+pub trait NotaryHook {
+    /// Publish `root` and return the notary's proof of publication.
+    fn publish(&self, root: [u8; 32]) -> Result<NotaryReceipt>;
+}
+
+/// Proof of publication returned by a [`NotaryHook`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotaryReceipt {
+    /// Name of the notary (e.g. a log URL or chain name).
+    pub notary: String,
+    /// Hex-encoded root the notary attests to.
+    pub root: String,
+    /// Notary-specific proof (transaction ID, signed tree head, ...).
+    pub proof: String,
+    /// When the notary accepted the root (microseconds since epoch).
+    pub published_at: u64,
+}
+
+/// A receipt-chain root, the receipts it covers, and its notarization.
+///
+/// Stored before the notary is contacted; `notary_receipt` stays `None`
+/// until publication succeeds, so a failed publish can be retried without
+/// recomputing or losing the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotaryAnchor {
+    /// Hex-encoded Merkle root of the covered receipts.
+    pub root: String,
+    /// Newest receipt covered.
+    pub tip: ReceiptId,
+    /// Covered receipts, oldest first (ending with `tip`).
+    pub covered: Vec<ReceiptId>,
+    /// When the root was computed (microseconds since epoch).
+    pub computed_at: u64,
+    /// The notary's proof, once published.
+    pub notary_receipt: Option<NotaryReceipt>,
+}
+
+impl NotaryAnchor {
+    /// Compute an unpublished anchor for `chain` (oldest first).
+    pub fn for_chain(chain: &[ActionReceipt]) -> Result<Self> {
+        let tip = chain.last().ok_or(IdentityError::InvalidChain)?;
+        Ok(Self {
+            root: hex::encode(merkle_root(chain)),
+            tip: tip.id.clone(),
+            covered: chain.iter().map(|r| r.id.clone()).collect(),
+            computed_at: crate::time::now_micros(),
+            notary_receipt: None,
+        })
+    }
+
+    /// Has the root been published?
+    pub fn is_published(&self) -> bool {
+        self.notary_receipt.is_some()
+    }
+
+    /// Check that this anchor covers exactly `chain` (oldest first) and that
+    /// any notary receipt attests to the same root.
+    pub fn verify(&self, chain: &[ActionReceipt]) -> Result<()> {
+        let ids: Vec<&ReceiptId> = chain.iter().map(|r| &r.id).collect();
+        if ids != self.covered.iter().collect::<Vec<_>>()
+            || chain.last().map(|r| &r.id) != Some(&self.tip)
+            || hex::encode(merkle_root(chain)) != self.root
+        {
+            return Err(IdentityError::InvalidChain);
+        }
+        match &self.notary_receipt {
+            Some(receipt) if receipt.root != self.root => Err(IdentityError::InvalidChain),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::{ActionContent, ActionType};
+
+    fn make_chain(len: usize) -> Vec<ActionReceipt> {
+        let anchor = IdentityAnchor::new(None);
+        let mut chain: Vec<ActionReceipt> = Vec::new();
+        for i in 0..len {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Observation,
+                ActionContent::new(format!("step {i}")),
+            );
+            if let Some(prev) = chain.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            chain.push(builder.sign(anchor.signing_key()).unwrap());
+        }
+        chain
+    }
+
+    #[test]
+    fn test_inclusion_proofs_verify_for_every_leaf() {
+        for len in 1..=7 {
+            let chain = make_chain(len);
+            let root = hex::encode(merkle_root(&chain));
+            for (i, receipt) in chain.iter().enumerate() {
+                let proof = merkle_proof(&chain, i).unwrap();
+                assert!(
+                    verify_inclusion(receipt, &proof, &root),
+                    "len {len} index {i}"
+                );
+            }
+            assert!(merkle_proof(&chain, len).is_none());
+        }
+    }
+
+    #[test]
+    fn test_inclusion_rejects_foreign_receipt() {
+        let chain = make_chain(4);
+        let other = make_chain(1);
+        let root = hex::encode(merkle_root(&chain));
+        let proof = merkle_proof(&chain, 2).unwrap();
+        assert!(!verify_inclusion(&other[0], &proof, &root));
+    }
+
+    #[test]
+    fn test_anchor_binds_root_and_tip() {
+        let chain = make_chain(3);
+        let mut anchor = NotaryAnchor::for_chain(&chain).unwrap();
+        assert_eq!(anchor.tip, chain[2].id);
+        assert!(anchor.verify(&chain).is_ok());
+        assert!(anchor.verify(&chain[..2]).is_err());
+
+        anchor.notary_receipt = Some(NotaryReceipt {
+            notary: "test".into(),
+            root: hex::encode([0u8; 32]),
+            proof: "p".into(),
+            published_at: 0,
+        });
+        assert!(anchor.verify(&chain).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! Capability requirements — which capabilities an action needs before signing.
+//!
+//! Plain signing never checks authority: a receipt proves an agent *did*
+//! something, not that it was *allowed* to. A [`RequirementPolicy`] lets a
+//! deployment declare what an action implies (e.g. any action mentioning
+//! "deploy to prod" requires `deploy:prod`) so an agent can compare those
+//! requirements against its effective authority as a pre-flight check, or
+//! have them enforced with
+//! [`ReceiptBuilder::sign_guarded`](super::receipt::ReceiptBuilder::sign_guarded).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::action::{ActionContent, ActionType};
+use crate::trust::capability::Capability;
+
+/// A mapping from actions to the capabilities they require.
+///
+/// Rules are keyed either by action type tag (`mutation`, `delegation`, a
+/// custom tag, ...) or by a keyword matched case-insensitively against the
+/// action description. An empty policy requires nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequirementPolicy {
+    #[serde(default)]
+    by_type: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    by_keyword: BTreeMap<String, Vec<String>>,
+}
+
+impl RequirementPolicy {
+    /// Create an empty policy (no requirements).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `capability` for every action of type `action_type` (a tag).
+    pub fn for_type(
+        mut self,
+        action_type: impl Into<String>,
+        capability: impl Into<String>,
+    ) -> Self {
+        push_unique(
+            self.by_type
+                .entry(action_type.into().to_lowercase())
+                .or_default(),
+            capability.into(),
+        );
+        self
+    }
+
+    /// Require `capability` for every action whose description contains
+    /// `keyword` (case-insensitive).
+    pub fn for_keyword(
+        mut self,
+        keyword: impl Into<String>,
+        capability: impl Into<String>,
+    ) -> Self {
+        push_unique(
+            self.by_keyword
+                .entry(keyword.into().to_lowercase())
+                .or_default(),
+            capability.into(),
+        );
+        self
+    }
+
+    /// Is this policy empty (no requirements)?
+    pub fn is_empty(&self) -> bool {
+        self.by_type.is_empty() && self.by_keyword.is_empty()
+    }
+
+    /// Capabilities the action requires, sorted and deduplicated.
+    ///
+    /// Returns an empty list when no rule matches; that means "no capability
+    /// required", not an error.
+    pub fn required_capabilities(
+        &self,
+        action: &ActionContent,
+        action_type: &ActionType,
+    ) -> Vec<Capability> {
+        let description = action.description.to_lowercase();
+        let mut required: BTreeSet<&str> = BTreeSet::new();
+
+        if let Some(caps) = self.by_type.get(&action_type.as_tag().to_lowercase()) {
+            required.extend(caps.iter().map(String::as_str));
+        }
+        for (keyword, caps) in &self.by_keyword {
+            if description.contains(keyword.as_str()) {
+                required.extend(caps.iter().map(String::as_str));
+            }
+        }
+
+        required.into_iter().map(Capability::new).collect()
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uris(caps: &[Capability]) -> Vec<&str> {
+        caps.iter().map(|c| c.uri.as_str()).collect()
+    }
+
+    #[test]
+    fn test_empty_policy_requires_nothing() {
+        let policy = RequirementPolicy::new();
+        assert!(policy.is_empty());
+        let required = policy
+            .required_capabilities(&ActionContent::new("deploy to prod"), &ActionType::Mutation);
+        assert!(required.is_empty());
+    }
+
+    #[test]
+    fn test_type_and_keyword_rules_combine() {
+        let policy = RequirementPolicy::new()
+            .for_type("mutation", "write:*")
+            .for_keyword("Deploy to Prod", "deploy:prod")
+            .for_keyword("prod", "deploy:prod");
+
+        let required = policy.required_capabilities(
+            &ActionContent::new("Deploy to prod now"),
+            &ActionType::Mutation,
+        );
+        assert_eq!(uris(&required), vec!["deploy:prod", "write:*"]);
+
+        let required = policy.required_capabilities(
+            &ActionContent::new("read the docs"),
+            &ActionType::Observation,
+        );
+        assert!(required.is_empty());
+    }
+
+    #[test]
+    fn test_custom_type_tag_matches() {
+        let policy = RequirementPolicy::new().for_type("billing", "billing:charge");
+        let required = policy.required_capabilities(
+            &ActionContent::new("charge card"),
+            &ActionType::Custom("billing".into()),
+        );
+        assert_eq!(uris(&required), vec!["billing:charge"]);
+    }
+
+    #[test]
+    fn test_policy_deserializes_with_missing_sections() {
+        let policy: RequirementPolicy =
+            serde_json::from_str(r#"{"by_keyword":{"prod":["deploy:prod"]}}"#).unwrap();
+        assert!(!policy.is_empty());
+        let required =
+            policy.required_capabilities(&ActionContent::new("to prod"), &ActionType::Decision);
+        assert_eq!(uris(&required), vec!["deploy:prod"]);
+    }
+}
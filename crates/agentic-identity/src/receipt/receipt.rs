@@ -1,27 +1,84 @@
 //! Action receipt — signed proof of an action.
 
-use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::crypto::signing;
-use crate::error::Result;
+#[cfg(feature = "signing")]
+use crate::crypto::signer::{sign_in_domain_with, Signer};
+#[cfg(feature = "signing")]
+use crate::crypto::signing::SignatureDomain;
+use crate::crypto::{canonical, signing};
+use crate::error::{IdentityError, Result};
+use crate::identity::multisig::Cosignature;
 use crate::identity::IdentityId;
+#[cfg(feature = "signing")]
+use crate::trust::capability::{capabilities_cover, Capability};
 
 use super::action::{ActionContent, ActionType};
+#[cfg(feature = "signing")]
+use super::context::ContextSnapshot;
+#[cfg(feature = "signing")]
+use super::policy::RequirementPolicy;
+#[cfg(feature = "signing")]
+use super::schema::DataSchemas;
 use super::witness::WitnessSignature;
 
 /// Unique identifier for a receipt.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ReceiptId(pub String);
 
+impl ReceiptId {
+    /// Parse a receipt ID from untrusted input, checking its prefix and charset.
+    pub fn parse(s: &str) -> Result<Self> {
+        crate::identity::anchor::validate_prefixed_id(s, "arec_")?;
+        Ok(Self(s.to_string()))
+    }
+}
+
 impl std::fmt::Display for ReceiptId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Top-level field names understood by this version of [`ActionReceipt`].
+#[cfg(feature = "signing")]
+const KNOWN_FIELDS: &[&str] = &[
+    "id",
+    "actor",
+    "actor_key",
+    "action_type",
+    "action",
+    "timestamp",
+    "context_hash",
+    "previous_receipt",
+    "receipt_hash",
+    "signature",
+    "witnesses",
+    "schema_version",
+];
+
+/// Optional top-level fields, omitted from JSON when unset.
+#[cfg(feature = "signing")]
+const OPTIONAL_FIELDS: &[&str] = &["intent", "cosignatures", "signature_version"];
+
 /// An action receipt proving an agent took an action.
+///
+/// Top-level fields this version does not know about (added by a newer
+/// writer) are preserved in `extra` and are covered by the receipt hash, so
+/// a receipt round-trips and verifies across versions.
+///
+/// `intent` records why the action was taken. It is signed and hashes like
+/// an extra field, so readers predating it still verify receipts carrying it.
+///
+/// A receipt from a multi-signature identity has an empty `signature` and
+/// carries its members' [`Cosignature`]s instead; see
+/// [`MultisigAnchor`](crate::identity::MultisigAnchor).
+///
+/// `signature_version` is absent on receipts signed before domain
+/// separation, which verify against the bare receipt hash; see
+/// [`signing`](crate::crypto::signing). It also selects how the receipt
+/// hash is computed: receipts signed now hash a JCS-canonical payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionReceipt {
     pub id: ReceiptId,
@@ -35,17 +92,38 @@ pub struct ActionReceipt {
     pub receipt_hash: String,
     pub signature: String,
     pub witnesses: Vec<WitnessSignature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub intent: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<Cosignature>,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    /// Layout version; see [`migrate`](crate::storage::migrate). Not signed.
+    #[serde(default)]
+    pub schema_version: u32,
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Builder for creating action receipts.
+#[cfg(feature = "signing")]
 pub struct ReceiptBuilder {
     actor: IdentityId,
     action_type: ActionType,
     action: ActionContent,
     context_hash: Option<String>,
     previous_receipt: Option<ReceiptId>,
+    intent: Option<String>,
+    extra: serde_json::Map<String, serde_json::Value>,
+    requirements: RequirementPolicy,
+    data_schemas: DataSchemas,
+    data_recipients: Vec<(IdentityId, ed25519_dalek::VerifyingKey)>,
 }
 
+#[cfg(feature = "signing")]
 impl ReceiptBuilder {
     /// Start building a receipt for an action.
     pub fn new(actor: IdentityId, action_type: ActionType, action: ActionContent) -> Self {
@@ -55,6 +133,11 @@ impl ReceiptBuilder {
             action,
             context_hash: None,
             previous_receipt: None,
+            intent: None,
+            extra: serde_json::Map::new(),
+            requirements: RequirementPolicy::new(),
+            data_schemas: DataSchemas::new(),
+            data_recipients: Vec::new(),
         }
     }
 
@@ -64,56 +147,170 @@ impl ReceiptBuilder {
         self
     }
 
+    /// Bind the receipt to `snapshot` by setting the context hash to its
+    /// [`hash`](ContextSnapshot::hash).
+    pub fn with_context(self, snapshot: &ContextSnapshot) -> Self {
+        self.context_hash(snapshot.hash())
+    }
+
     /// Chain this receipt to a previous one.
     pub fn chain_to(mut self, previous: ReceiptId) -> Self {
         self.previous_receipt = Some(previous);
         self
     }
 
+    /// Record why the action was taken.
+    pub fn intent(mut self, intent: impl Into<String>) -> Self {
+        self.intent = Some(intent.into());
+        self
+    }
+
+    /// Attach an additional top-level field, covered by the receipt hash.
+    ///
+    /// Signing fails if `key` collides with a field this version knows.
+    pub fn extra_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    /// Set the capability requirements enforced by
+    /// [`sign_guarded`](Self::sign_guarded). Plain [`sign`](Self::sign)
+    /// ignores them.
+    pub fn requirements(mut self, policy: RequirementPolicy) -> Self {
+        self.requirements = policy;
+        self
+    }
+
+    /// Check the action's `data` against `schemas` when signing.
+    ///
+    /// Data that does not match the schema registered for this action type
+    /// fails signing with [`IdentityError::SchemaViolation`] before the
+    /// receipt is hashed, so it never gets an ID or a place in a chain.
+    pub fn data_schemas(mut self, schemas: DataSchemas) -> Self {
+        self.data_schemas = schemas;
+        self
+    }
+
+    /// Encrypt the action's `data` so only `recipients`, given as identity
+    /// ID and Ed25519 public key, can read it; see [`super::encrypted`].
+    ///
+    /// The data is checked against the data schemas first and encrypted
+    /// before hashing, so the signature covers the ciphertext. Include the
+    /// actor among the recipients if it should read the data back. Signing
+    /// fails with [`IdentityError::InvalidInput`] if there is no data.
+    pub fn encrypt_data_for(
+        mut self,
+        recipients: &[(IdentityId, ed25519_dalek::VerifyingKey)],
+    ) -> Self {
+        self.data_recipients.extend_from_slice(recipients);
+        self
+    }
+
+    /// Sign the receipt only if `effective_authority` covers every
+    /// capability the requirement policy maps this action to.
+    ///
+    /// Matching is wildcard-aware, so an authority of `*` passes every
+    /// guard, and an action with no mapped requirement signs freely. Fails
+    /// with [`IdentityError::AuthorityEscalation`] naming the missing
+    /// capabilities otherwise.
+    pub fn sign_guarded<S: Signer + ?Sized>(
+        self,
+        signer: &S,
+        effective_authority: &[Capability],
+    ) -> Result<ActionReceipt> {
+        let missing: Vec<String> = self
+            .requirements
+            .required_capabilities(&self.action, &self.action_type)
+            .into_iter()
+            .filter(|cap| !capabilities_cover(effective_authority, &cap.uri))
+            .map(|cap| cap.uri)
+            .collect();
+        if !missing.is_empty() {
+            return Err(IdentityError::AuthorityEscalation(format!(
+                "{} action requires {}",
+                self.action_type.as_tag(),
+                missing.join(", ")
+            )));
+        }
+        self.sign(signer)
+    }
+
     /// Sign and finalize the receipt.
-    pub fn sign(self, signing_key: &SigningKey) -> Result<ActionReceipt> {
+    ///
+    /// `signer` is usually the actor's signing key or anchor, but may be any
+    /// [`Signer`], such as a hardware-backed key.
+    pub fn sign<S: Signer + ?Sized>(self, signer: &S) -> Result<ActionReceipt> {
+        let mut receipt = self.build(signer.public_key_base64())?;
+        receipt.signature = sign_in_domain_with(
+            signer,
+            SignatureDomain::Receipt,
+            receipt.receipt_hash.as_bytes(),
+        )?;
+        Ok(receipt)
+    }
+
+    /// Finalize the receipt under `actor_key`, leaving `signature` empty.
+    pub(crate) fn build(self, actor_key: String) -> Result<ActionReceipt> {
+        if let Some(key) = self
+            .extra
+            .keys()
+            .find(|k| KNOWN_FIELDS.contains(&k.as_str()) || OPTIONAL_FIELDS.contains(&k.as_str()))
+        {
+            return Err(IdentityError::SerializationError(format!(
+                "extra field '{key}' collides with a receipt field"
+            )));
+        }
+        self.data_schemas
+            .validate(&self.action_type, self.action.data.as_ref())?;
+        let mut action = self.action;
+        if !self.data_recipients.is_empty() {
+            let data = action
+                .data
+                .take()
+                .ok_or_else(|| IdentityError::InvalidInput("no action data to encrypt".into()))?;
+            action.encrypted_data = Some(super::encrypted::EncryptedData::seal(
+                &data,
+                &self.data_recipients,
+            )?);
+        }
+
         let now = crate::time::now_micros();
-        let actor_key = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            signing_key.verifying_key().to_bytes(),
-        );
 
         // Compute the receipt hash over all content fields
-        let hash_input = format!(
-            "{}:{}:{}:{}:{}:{}:{}",
-            self.actor.0,
-            actor_key,
-            self.action_type.as_tag(),
-            serde_json::to_string(&self.action).unwrap_or_default(),
+        let receipt_hash = compute_receipt_hash(
+            signing::CANONICAL_SIGNATURE_VERSION,
+            &self.actor,
+            &actor_key,
+            &self.action_type,
+            &action,
             now,
-            self.context_hash.as_deref().unwrap_or(""),
-            self.previous_receipt
-                .as_ref()
-                .map(|r| r.0.as_str())
-                .unwrap_or(""),
-        );
-        let receipt_hash = hex::encode(Sha256::digest(hash_input.as_bytes()));
+            self.context_hash.as_deref(),
+            self.previous_receipt.as_ref(),
+            &signed_extras(&self.extra, self.intent.as_deref()),
+        )?;
 
         // Generate receipt ID from the hash
         let id_hash = Sha256::digest(receipt_hash.as_bytes());
         let id_encoded = bs58::encode(&id_hash[..16]).into_string();
         let id = ReceiptId(format!("arec_{id_encoded}"));
 
-        // Sign the receipt hash
-        let signature = signing::sign_to_base64(signing_key, receipt_hash.as_bytes());
-
         Ok(ActionReceipt {
             id,
             actor: self.actor,
             actor_key,
             action_type: self.action_type,
-            action: self.action,
+            action,
             timestamp: now,
             context_hash: self.context_hash,
             previous_receipt: self.previous_receipt,
             receipt_hash,
-            signature,
+            signature: String::new(),
             witnesses: Vec::new(),
+            intent: self.intent,
+            cosignatures: Vec::new(),
+            signature_version: signing::CANONICAL_SIGNATURE_VERSION,
+            schema_version: crate::storage::migrate::SCHEMA_VERSION,
+            extra: self.extra,
         })
     }
 }
@@ -123,9 +320,140 @@ impl ActionReceipt {
     pub fn add_witness(&mut self, witness: WitnessSignature) {
         self.witnesses.push(witness);
     }
+
+    /// Co-sign this receipt as `witness` and attach the witness signature.
+    ///
+    /// Only a receipt whose actor signature verifies can be witnessed, and
+    /// only by an identity other than its actor that has not already
+    /// witnessed it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SignatureInvalid` if the receipt does not
+    /// verify, and `IdentityError::InvalidInput` if `witness` is the actor or
+    /// an existing witness.
+    #[cfg(feature = "signing")]
+    pub fn sign_as_witness(
+        &mut self,
+        witness: &crate::identity::IdentityAnchor,
+    ) -> Result<&WitnessSignature> {
+        if !super::verify::verify_receipt(self)?.signature_valid {
+            return Err(IdentityError::SignatureInvalid);
+        }
+        let witness_id = witness.id();
+        if witness_id == self.actor {
+            return Err(IdentityError::InvalidInput(format!(
+                "{witness_id} is the actor of receipt {} and cannot witness it",
+                self.id
+            )));
+        }
+        if self.witnesses.iter().any(|w| w.witness == witness_id) {
+            return Err(IdentityError::InvalidInput(format!(
+                "receipt {} is already witnessed by {witness_id}",
+                self.id
+            )));
+        }
+        self.witnesses.push(WitnessSignature::create(
+            witness_id,
+            witness.signing_key(),
+            &self.receipt_hash,
+        ));
+        Ok(&self.witnesses[self.witnesses.len() - 1])
+    }
+
+    /// Recompute the receipt hash from the receipt's fields, including any
+    /// preserved unknown fields, in the format of its `signature_version`.
+    ///
+    /// Returns an empty string if the content cannot be canonicalized, which
+    /// never matches a stored hash.
+    pub fn compute_hash(&self) -> String {
+        compute_receipt_hash(
+            self.signature_version,
+            &self.actor,
+            &self.actor_key,
+            &self.action_type,
+            &self.action,
+            self.timestamp,
+            self.context_hash.as_deref(),
+            self.previous_receipt.as_ref(),
+            &signed_extras(&self.extra, self.intent.as_deref()),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Names of signed top-level fields this version does not understand.
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
 }
 
-#[cfg(test)]
+/// Merge the optional `intent` field into the extras map for hashing.
+fn signed_extras(
+    extra: &serde_json::Map<String, serde_json::Value>,
+    intent: Option<&str>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut merged = extra.clone();
+    if let Some(intent) = intent {
+        merged.insert("intent".into(), intent.into());
+    }
+    merged
+}
+
+/// Hash the receipt content in the format of `version`.
+///
+/// From [`CANONICAL_SIGNATURE_VERSION`](signing::CANONICAL_SIGNATURE_VERSION)
+/// the hash is over the JCS form of an object holding the content fields
+/// and extras side by side. Earlier receipts hash a `:`-joined string;
+/// unknown fields are appended only when present, so receipts without them
+/// hash exactly as they did before extras were supported.
+#[allow(clippy::too_many_arguments)]
+fn compute_receipt_hash(
+    version: u32,
+    actor: &IdentityId,
+    actor_key: &str,
+    action_type: &ActionType,
+    action: &ActionContent,
+    timestamp: u64,
+    context_hash: Option<&str>,
+    previous_receipt: Option<&ReceiptId>,
+    extra: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String> {
+    if version >= signing::CANONICAL_SIGNATURE_VERSION {
+        let action = serde_json::to_value(action)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        let mut payload = extra.clone();
+        payload.insert("actor".into(), actor.0.clone().into());
+        payload.insert("actor_key".into(), actor_key.into());
+        payload.insert("action_type".into(), action_type.as_tag().into());
+        payload.insert("action".into(), action);
+        payload.insert("timestamp".into(), timestamp.into());
+        payload.insert("context_hash".into(), context_hash.into());
+        payload.insert(
+            "previous_receipt".into(),
+            previous_receipt.map(|r| r.0.as_str()).into(),
+        );
+        let json = canonical::canonicalize(&serde_json::Value::Object(payload))?;
+        return Ok(hex::encode(Sha256::digest(json.as_bytes())));
+    }
+
+    let mut hash_input = format!(
+        "{}:{}:{}:{}:{}:{}:{}",
+        actor.0,
+        actor_key,
+        action_type.as_tag(),
+        serde_json::to_string(action).unwrap_or_default(),
+        timestamp,
+        context_hash.unwrap_or(""),
+        previous_receipt.map(|r| r.0.as_str()).unwrap_or(""),
+    );
+    if !extra.is_empty() {
+        hash_input.push(':');
+        hash_input.push_str(&serde_json::to_string(extra).unwrap_or_default());
+    }
+    Ok(hex::encode(Sha256::digest(hash_input.as_bytes())))
+}
+
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -188,6 +516,27 @@ mod tests {
         assert_eq!(receipt.context_hash.as_deref(), Some("abc123def456"));
     }
 
+    #[test]
+    fn test_receipt_with_context_snapshot() {
+        let anchor = IdentityAnchor::new(None);
+        let snapshot = ContextSnapshot::new()
+            .model("acme/model@1")
+            .prompt("rotate the logs")
+            .tool_input("shell", serde_json::json!({"cmd": "logrotate"}));
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Mutation,
+            ActionContent::new("Rotated logs"),
+        )
+        .with_context(&snapshot)
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        assert!(snapshot.matches(&receipt));
+        assert!(!snapshot.clone().prompt("delete the logs").matches(&receipt));
+        crate::receipt::verify::verify_receipt(&receipt).unwrap();
+    }
+
     #[test]
     fn test_receipt_types() {
         let anchor = IdentityAnchor::new(None);
@@ -211,4 +560,304 @@ mod tests {
             assert_eq!(receipt.action_type, action_type);
         }
     }
+
+    #[test]
+    fn test_receipt_intent_is_signed() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .intent("unblock release")
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        assert_eq!(receipt.compute_hash(), receipt.receipt_hash);
+        assert!(receipt.unknown_fields().is_empty());
+
+        let mut tampered = receipt.clone();
+        tampered.intent = Some("something else".into());
+        assert_ne!(tampered.compute_hash(), receipt.receipt_hash);
+    }
+
+    #[test]
+    fn test_receipt_hash_unchanged_without_extras() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        assert!(receipt.extra.is_empty());
+        assert_eq!(receipt.compute_hash(), receipt.receipt_hash);
+        let json = serde_json::to_value(&receipt).unwrap();
+        // Every known field, plus the signature version.
+        assert_eq!(json.as_object().unwrap().len(), KNOWN_FIELDS.len() + 1);
+    }
+
+    #[test]
+    fn test_receipt_extra_fields_round_trip() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .extra_field("policy_version", serde_json::json!(2))
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        let json = serde_json::to_string(&receipt).unwrap();
+        let loaded: ActionReceipt = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.unknown_fields(), vec!["policy_version".to_string()]);
+        assert_eq!(loaded.compute_hash(), receipt.receipt_hash);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+
+        // Dropping a signed extra field changes the canonical hash.
+        let mut stripped = loaded.clone();
+        stripped.extra.clear();
+        assert_ne!(stripped.compute_hash(), receipt.receipt_hash);
+    }
+
+    #[test]
+    fn test_receipt_extra_field_collision_rejected() {
+        let anchor = IdentityAnchor::new(None);
+        let result = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .extra_field("timestamp", serde_json::json!(0))
+        .sign(anchor.signing_key());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_receipt_id_parse() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        assert_eq!(ReceiptId::parse(&receipt.id.0).unwrap(), receipt.id);
+        assert!(ReceiptId::parse("atrust_3yZe7d").is_err());
+        assert!(ReceiptId::parse("arec_not valid").is_err());
+    }
+
+    #[test]
+    fn test_sign_guarded_enforces_requirements() {
+        let anchor = IdentityAnchor::new(None);
+        let policy = RequirementPolicy::new().for_keyword("deploy", "deploy:prod");
+        let deploy = || {
+            ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Mutation,
+                ActionContent::new("Deploy v2"),
+            )
+            .requirements(policy.clone())
+        };
+
+        let err = deploy()
+            .sign_guarded(anchor.signing_key(), &[Capability::new("read:*")])
+            .unwrap_err();
+        assert!(
+            matches!(err, IdentityError::AuthorityEscalation(ref m) if m.contains("deploy:prod"))
+        );
+
+        for held in ["deploy:*", "*"] {
+            assert!(deploy()
+                .sign_guarded(anchor.signing_key(), &[Capability::new(held)])
+                .is_ok());
+        }
+
+        // No mapped requirement: signs with no authority at all.
+        let observed = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("Read the logs"),
+        )
+        .requirements(policy.clone())
+        .sign_guarded(anchor.signing_key(), &[]);
+        assert!(observed.is_ok());
+
+        // Plain signing stays unrestricted.
+        assert!(deploy().sign(anchor.signing_key()).is_ok());
+    }
+
+    #[test]
+    fn test_data_schema_checked_before_signing() {
+        let anchor = IdentityAnchor::new(None);
+        let schemas = DataSchemas::new()
+            .for_type(
+                "mutation",
+                serde_json::json!({"type": "object", "required": ["table"]}),
+            )
+            .unwrap();
+        let mutation = |data| {
+            ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Mutation,
+                ActionContent::with_data("Update rows", data),
+            )
+            .data_schemas(schemas.clone())
+        };
+
+        let err = mutation(serde_json::json!({"rows": 3}))
+            .sign(anchor.signing_key())
+            .unwrap_err();
+        assert!(matches!(err, IdentityError::SchemaViolation(ref m) if m.contains("data.table")));
+        assert!(mutation(serde_json::json!({"table": "users"}))
+            .sign(anchor.signing_key())
+            .is_ok());
+
+        // No schema for decisions: any data signs.
+        let decision = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::with_data("Approve", serde_json::json!([1, 2])),
+        )
+        .data_schemas(schemas)
+        .sign(anchor.signing_key());
+        assert!(decision.is_ok());
+    }
+
+    #[test]
+    fn test_sign_as_witness() {
+        let actor = IdentityAnchor::new(None);
+        let witness = IdentityAnchor::new(None);
+        let mut receipt = ReceiptBuilder::new(
+            actor.id(),
+            ActionType::Mutation,
+            ActionContent::new("Deployed v2"),
+        )
+        .sign(actor.signing_key())
+        .unwrap();
+
+        let ws = receipt.sign_as_witness(&witness).unwrap();
+        assert_eq!(ws.witness, witness.id());
+        let verification = crate::receipt::verify::verify_receipt(&receipt).unwrap();
+        assert_eq!(verification.witnesses_valid, vec![true]);
+
+        assert!(matches!(
+            receipt.sign_as_witness(&witness),
+            Err(IdentityError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            receipt.sign_as_witness(&actor),
+            Err(IdentityError::InvalidInput(_))
+        ));
+
+        receipt.action.description = "Deployed v3".into();
+        assert!(matches!(
+            receipt.sign_as_witness(&IdentityAnchor::new(None)),
+            Err(IdentityError::SignatureInvalid)
+        ));
+        assert_eq!(receipt.witnesses.len(), 1);
+    }
+
+    #[test]
+    fn test_receipt_hash_is_over_canonical_payload() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .intent("release window")
+        .extra_field("ticket", serde_json::json!({"id": 7, "board": "ops"}))
+        .sign(anchor.signing_key())
+        .unwrap();
+        assert_eq!(
+            receipt.signature_version,
+            signing::CANONICAL_SIGNATURE_VERSION
+        );
+
+        let payload = serde_json::json!({
+            "ticket": {"board": "ops", "id": 7},
+            "timestamp": receipt.timestamp,
+            "previous_receipt": null,
+            "intent": "release window",
+            "context_hash": null,
+            "actor_key": receipt.actor_key,
+            "actor": receipt.actor.0,
+            "action_type": "decision",
+            "action": serde_json::to_value(&receipt.action).unwrap(),
+        });
+        let json = canonical::canonicalize(&payload).unwrap();
+        assert_eq!(
+            receipt.receipt_hash,
+            hex::encode(Sha256::digest(json.as_bytes()))
+        );
+        assert_eq!(receipt.compute_hash(), receipt.receipt_hash);
+
+        // The same content under an older version hashes differently.
+        let mut older = receipt.clone();
+        older.signature_version = signing::SIGNATURE_VERSION;
+        assert_ne!(older.compute_hash(), receipt.receipt_hash);
+    }
+
+    #[test]
+    fn test_encrypted_data_is_signed_and_readable_by_recipients() {
+        let actor = IdentityAnchor::new(None);
+        let auditor = IdentityAnchor::new(None);
+        let outsider = IdentityAnchor::new(None);
+        let data = serde_json::json!({"account": "acct-991", "amount": 1200});
+
+        let receipt = ReceiptBuilder::new(
+            actor.id(),
+            ActionType::Mutation,
+            ActionContent::with_data("Refunded customer", data.clone()),
+        )
+        .encrypt_data_for(&[
+            (actor.id(), *actor.verifying_key()),
+            (auditor.id(), *auditor.verifying_key()),
+        ])
+        .sign(actor.signing_key())
+        .unwrap();
+
+        assert!(receipt.action.data.is_none());
+        assert!(!serde_json::to_string(&receipt)
+            .unwrap()
+            .contains("acct-991"));
+        assert_eq!(receipt.compute_hash(), receipt.receipt_hash);
+        for reader in [&actor, &auditor] {
+            let opened = receipt
+                .action
+                .decrypt_data(&reader.id(), reader.signing_key())
+                .unwrap();
+            assert_eq!(opened, Some(data.clone()));
+        }
+        assert!(receipt
+            .action
+            .decrypt_data(&outsider.id(), outsider.signing_key())
+            .is_err());
+
+        // The signature covers the ciphertext.
+        let mut altered = receipt.clone();
+        altered
+            .action
+            .encrypted_data
+            .as_mut()
+            .unwrap()
+            .recipients
+            .pop();
+        assert_ne!(altered.compute_hash(), receipt.receipt_hash);
+
+        let no_data = ReceiptBuilder::new(
+            actor.id(),
+            ActionType::Mutation,
+            ActionContent::new("Nothing to hide"),
+        )
+        .encrypt_data_for(&[(auditor.id(), *auditor.verifying_key())])
+        .sign(actor.signing_key());
+        assert!(matches!(no_data, Err(IdentityError::InvalidInput(_))));
+    }
 }
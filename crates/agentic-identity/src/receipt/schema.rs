@@ -0,0 +1,386 @@
+//! Data schemas — opt-in structure checks for a receipt's `data` field.
+//!
+//! `ActionContent::data` is free-form, so nothing stops a `mutation`
+//! receipt from leaving out the fields an auditor relies on. A
+//! [`DataSchemas`] registry maps action type tags to JSON Schemas, and
+//! [`ReceiptBuilder::data_schemas`](super::receipt::ReceiptBuilder::data_schemas)
+//! makes signing refuse data that does not match, before anything is hashed
+//! or signed. Action types without a schema sign freely.
+//!
+//! Only the structural core of JSON Schema is supported: `type`, `enum`,
+//! `const`, `required`, `properties`, `additionalProperties`, `items`,
+//! `minItems`/`maxItems`, `minLength`/`maxLength` and `minimum`/`maximum`.
+//! A schema using any other keyword is rejected when registered rather
+//! than having that keyword silently ignored.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use super::action::ActionType;
+use crate::error::{IdentityError, Result};
+
+const KEYWORDS: &[&str] = &[
+    "$schema",
+    "title",
+    "description",
+    "type",
+    "enum",
+    "const",
+    "required",
+    "properties",
+    "additionalProperties",
+    "items",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "minimum",
+    "maximum",
+];
+
+const TYPES: &[&str] = &[
+    "null", "boolean", "object", "array", "number", "integer", "string",
+];
+
+/// JSON Schemas for receipt data, keyed by action type tag.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataSchemas {
+    by_type: BTreeMap<String, Value>,
+}
+
+impl DataSchemas {
+    /// Create an empty registry (every action signs freely).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require the data of every `action_type` action (a tag such as
+    /// `mutation` or a custom tag) to match `schema`, replacing any schema
+    /// already registered for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if `schema` uses an
+    /// unsupported keyword or a keyword with a malformed value.
+    pub fn for_type(mut self, action_type: impl Into<String>, schema: Value) -> Result<Self> {
+        check_schema(&schema, "schema")?;
+        self.by_type
+            .insert(action_type.into().to_lowercase(), schema);
+        Ok(self)
+    }
+
+    /// Is this registry empty (no schemas)?
+    pub fn is_empty(&self) -> bool {
+        self.by_type.is_empty()
+    }
+
+    /// The schema registered for `action_type`, if any.
+    pub fn schema_for(&self, action_type: &ActionType) -> Option<&Value> {
+        self.by_type.get(&action_type.as_tag().to_lowercase())
+    }
+
+    /// Check `data` against the schema for `action_type`.
+    ///
+    /// Missing data is checked as `null`, so a schema of `"type": "object"`
+    /// also makes data mandatory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SchemaViolation` naming the path of the first
+    /// offending field, e.g. `data.target.id`.
+    pub fn validate(&self, action_type: &ActionType, data: Option<&Value>) -> Result<()> {
+        let Some(schema) = self.schema_for(action_type) else {
+            return Ok(());
+        };
+        validate(schema, data.unwrap_or(&Value::Null), "data").map_err(|e| {
+            IdentityError::SchemaViolation(format!("{} action: {e}", action_type.as_tag()))
+        })
+    }
+}
+
+fn invalid_schema(path: &str, keyword: &str, expected: &str) -> IdentityError {
+    IdentityError::InvalidInput(format!("{path}: '{keyword}' must be {expected}"))
+}
+
+/// Reject schemas that use keywords this validator does not enforce.
+fn check_schema(schema: &Value, path: &str) -> Result<()> {
+    let Some(schema) = schema.as_object() else {
+        return Err(IdentityError::InvalidInput(format!(
+            "{path} must be a JSON object"
+        )));
+    };
+    for (keyword, value) in schema {
+        if !KEYWORDS.contains(&keyword.as_str()) {
+            return Err(IdentityError::InvalidInput(format!(
+                "{path}: unsupported schema keyword '{keyword}'"
+            )));
+        }
+        match keyword.as_str() {
+            "type" => {
+                let known = |t: &Value| t.as_str().is_some_and(|t| TYPES.contains(&t));
+                let ok = match value {
+                    Value::Array(types) => !types.is_empty() && types.iter().all(known),
+                    other => known(other),
+                };
+                if !ok {
+                    return Err(invalid_schema(path, keyword, "a JSON type or list of them"));
+                }
+            }
+            "enum" if !value.is_array() => {
+                return Err(invalid_schema(path, keyword, "an array"));
+            }
+            "required" => {
+                let ok = value
+                    .as_array()
+                    .is_some_and(|fields| fields.iter().all(Value::is_string));
+                if !ok {
+                    return Err(invalid_schema(path, keyword, "an array of field names"));
+                }
+            }
+            "properties" => {
+                let Some(properties) = value.as_object() else {
+                    return Err(invalid_schema(path, keyword, "an object"));
+                };
+                for (name, property) in properties {
+                    check_schema(property, &format!("{path}.properties.{name}"))?;
+                }
+            }
+            "additionalProperties" if !value.is_boolean() => {
+                check_schema(value, &format!("{path}.additionalProperties"))?;
+            }
+            "items" => check_schema(value, &format!("{path}.items"))?,
+            "minItems" | "maxItems" | "minLength" | "maxLength" if !value.is_u64() => {
+                return Err(invalid_schema(path, keyword, "a non-negative integer"));
+            }
+            "minimum" | "maximum" if !value.is_number() => {
+                return Err(invalid_schema(path, keyword, "a number"));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => type_name(value) == ty,
+    }
+}
+
+/// Validate `value` (at `path`) against an already-checked schema.
+fn validate(schema: &Value, value: &Value, path: &str) -> std::result::Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(ty) = schema.get("type") {
+        let allowed: Vec<&str> = match ty {
+            Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        if !allowed.iter().any(|t| has_type(value, t)) {
+            return Err(format!(
+                "{path}: expected {}, got {}",
+                allowed.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+    if let Some(options) = schema.get("enum").and_then(Value::as_array) {
+        if !options.contains(value) {
+            return Err(format!(
+                "{path}: {value} is not one of {}",
+                Value::Array(options.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            return Err(format!("{path}: expected {expected}, got {value}"));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            for name in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !fields.contains_key(name) {
+                    return Err(format!("{path}.{name}: required field is missing"));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in fields {
+                let field_path = format!("{path}.{name}");
+                match properties.and_then(|p| p.get(name)) {
+                    Some(property) => validate(property, field, &field_path)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{field_path}: field is not allowed"));
+                        }
+                        Some(extra @ Value::Object(_)) => validate(extra, field, &field_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            let len = items.len() as u64;
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if len < min {
+                    return Err(format!("{path}: expected at least {min} items, got {len}"));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if len > max {
+                    return Err(format!("{path}: expected at most {max} items, got {len}"));
+                }
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate(item_schema, item, &format!("{path}[{i}]"))?;
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if len < min {
+                    return Err(format!(
+                        "{path}: expected at least {min} characters, got {len}"
+                    ));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if len > max {
+                    return Err(format!(
+                        "{path}: expected at most {max} characters, got {len}"
+                    ));
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+                if n < min {
+                    return Err(format!("{path}: {n} is below the minimum {min}"));
+                }
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+                if n > max {
+                    return Err(format!("{path}: {n} is above the maximum {max}"));
+                }
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mutation_schemas() -> DataSchemas {
+        DataSchemas::new()
+            .for_type(
+                "mutation",
+                json!({
+                    "type": "object",
+                    "required": ["target", "change"],
+                    "properties": {
+                        "target": {
+                            "type": "object",
+                            "required": ["id"],
+                            "properties": {"id": {"type": "string", "minLength": 1}}
+                        },
+                        "change": {"enum": ["create", "update", "delete"]},
+                        "rows": {"type": "integer", "minimum": 0}
+                    }
+                }),
+            )
+            .unwrap()
+    }
+
+    fn violation(result: Result<()>) -> String {
+        match result {
+            Err(IdentityError::SchemaViolation(msg)) => msg,
+            other => panic!("expected a schema violation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unregistered_types_pass_and_valid_data_passes() {
+        let schemas = mutation_schemas();
+        assert!(schemas.validate(&ActionType::Decision, None).is_ok());
+        let data = json!({"target": {"id": "users"}, "change": "update", "rows": 3});
+        assert!(schemas.validate(&ActionType::Mutation, Some(&data)).is_ok());
+    }
+
+    #[test]
+    fn test_violations_name_the_field_path() {
+        let schemas = mutation_schemas();
+
+        let msg = violation(schemas.validate(
+            &ActionType::Mutation,
+            Some(&json!({"target": {}, "change": "update"})),
+        ));
+        assert!(msg.contains("data.target.id"), "{msg}");
+
+        let msg = violation(schemas.validate(
+            &ActionType::Mutation,
+            Some(&json!({"target": {"id": "t"}, "change": "drop"})),
+        ));
+        assert!(msg.contains("data.change"), "{msg}");
+
+        let msg = violation(schemas.validate(
+            &ActionType::Mutation,
+            Some(&json!({"target": {"id": "t"}, "change": "delete", "rows": -1})),
+        ));
+        assert!(msg.contains("data.rows"), "{msg}");
+
+        // Missing data is checked as null.
+        let msg = violation(schemas.validate(&ActionType::Mutation, None));
+        assert!(msg.contains("expected object, got null"), "{msg}");
+    }
+
+    #[test]
+    fn test_custom_tags_and_unsupported_keywords() {
+        let schemas = DataSchemas::new()
+            .for_type(
+                "Billing",
+                json!({"type": "array", "items": {"type": "number"}, "maxItems": 2}),
+            )
+            .unwrap();
+        let billing = ActionType::Custom("billing".into());
+        assert!(schemas.validate(&billing, Some(&json!([1, 2.5]))).is_ok());
+        let msg = violation(schemas.validate(&billing, Some(&json!([1, "x"]))));
+        assert!(msg.contains("data[1]"), "{msg}");
+
+        let err = DataSchemas::new().for_type("mutation", json!({"pattern": "^a"}));
+        assert!(matches!(err, Err(IdentityError::InvalidInput(_))));
+        let err = DataSchemas::new()
+            .for_type("mutation", json!({"properties": {"id": {"type": "uuid"}}}));
+        assert!(matches!(err, Err(IdentityError::InvalidInput(_))));
+    }
+}
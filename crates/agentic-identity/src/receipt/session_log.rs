@@ -0,0 +1,213 @@
+//! Session logs — a host's operation log exported as a signed artifact.
+//!
+//! A [`SessionLog`] records what one session did: when it started, every
+//! logged operation with its intent, and when it was exported. The
+//! exporting identity signs all of it, so [`verify_session_log`] detects an
+//! edited, reordered, added or removed entry given only the signer's public
+//! key.
+//!
+//! Argument summaries can carry secrets, so an exporter normally leaves
+//! them out; [`SessionLog::redacted`] records whether it did, and is itself
+//! covered by the signature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityAnchor;
+use crate::identity::IdentityId;
+
+/// One logged operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionLogEntry {
+    /// The tool or operation that ran.
+    pub tool_name: String,
+    /// The intent recorded with it, if any.
+    pub intent: Option<String>,
+    /// Summary of its arguments; `None` when redacted.
+    pub summary: Option<String>,
+    /// When it ran, as recorded by the host (seconds since epoch).
+    pub timestamp: u64,
+    /// `ok`, `error` or `rpc_error`, when the host recorded it.
+    pub outcome: Option<String>,
+}
+
+/// A signed export of one session's operation log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub signer: IdentityId,
+    /// Base64 public key the log was signed with.
+    pub signer_key: String,
+    /// When the session started (seconds since epoch), if known.
+    pub session_started_at: Option<u64>,
+    /// When the log was exported (microseconds since epoch).
+    pub exported_at: u64,
+    /// Argument summaries were left out of every entry.
+    pub redacted: bool,
+    /// Logged operations, oldest first.
+    pub entries: Vec<SessionLogEntry>,
+    pub signature: String,
+    /// Absent on logs signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// Everything in a [`SessionLog`] except its signature.
+#[derive(Serialize)]
+struct SessionLogPayload<'a> {
+    signer: &'a IdentityId,
+    signer_key: &'a str,
+    session_started_at: Option<u64>,
+    exported_at: u64,
+    redacted: bool,
+    entries: &'a [SessionLogEntry],
+}
+
+impl SessionLog {
+    /// Sign `entries` (oldest first) as `anchor`'s log of a session.
+    ///
+    /// With `redact` set, every entry's summary is dropped before signing.
+    #[cfg(feature = "signing")]
+    pub fn sign(
+        anchor: &IdentityAnchor,
+        session_started_at: Option<u64>,
+        mut entries: Vec<SessionLogEntry>,
+        redact: bool,
+    ) -> Result<Self> {
+        if redact {
+            for entry in &mut entries {
+                entry.summary = None;
+            }
+        }
+        let mut log = Self {
+            signer: anchor.id(),
+            signer_key: anchor.public_key_base64(),
+            session_started_at,
+            exported_at: crate::time::now_micros(),
+            redacted: redact,
+            entries,
+            signature: String::new(),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        let input = log.signing_input()?;
+        log.signature = signing::sign_in_domain(
+            anchor.signing_key(),
+            SignatureDomain::SessionLog,
+            input.as_bytes(),
+        );
+        Ok(log)
+    }
+
+    fn signing_input(&self) -> Result<String> {
+        serde_json::to_string(&SessionLogPayload {
+            signer: &self.signer,
+            signer_key: &self.signer_key,
+            session_started_at: self.session_started_at,
+            exported_at: self.exported_at,
+            redacted: self.redacted,
+            entries: &self.entries,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+    }
+}
+
+/// Verify that `log` was signed with `public_key` (base64) and not altered.
+///
+/// The key is taken from the caller, never from the log, and must be the
+/// one the log names and the one its signer ID derives from.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidKey` if `public_key` does not decode or is
+/// not the log's signer key, and `IdentityError::SignatureInvalid` if the
+/// log was altered after signing.
+pub fn verify_session_log(log: &SessionLog, public_key: &str) -> Result<()> {
+    let key = crate::identity::anchor::decode_public_key(public_key)?;
+    if log.signer_key != public_key || IdentityId::from_verifying_key(&key) != log.signer {
+        return Err(IdentityError::InvalidKey(format!(
+            "session log was not signed by the key of {}",
+            log.signer
+        )));
+    }
+    signing::verify_versioned(
+        &key,
+        SignatureDomain::SessionLog,
+        log.signature_version,
+        log.signing_input()?.as_bytes(),
+        &log.signature,
+    )
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<SessionLogEntry> {
+        vec![
+            SessionLogEntry {
+                tool_name: "action_context".into(),
+                intent: Some("rotate the deploy key".into()),
+                summary: Some("intent: rotate the deploy key".into()),
+                timestamp: 1_700_000_000,
+                outcome: None,
+            },
+            SessionLogEntry {
+                tool_name: "trust_grant".into(),
+                intent: None,
+                summary: Some(r#"{"token":"secret"}"#.into()),
+                timestamp: 1_700_000_005,
+                outcome: Some("ok".into()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_session_log_round_trip_and_redaction() {
+        let anchor = IdentityAnchor::new(None);
+        let log = SessionLog::sign(&anchor, Some(1_700_000_000), entries(), true).unwrap();
+        assert!(log.redacted);
+        assert!(log.entries.iter().all(|e| e.summary.is_none()));
+        assert_eq!(
+            log.entries[0].intent.as_deref(),
+            Some("rotate the deploy key")
+        );
+
+        let json = serde_json::to_string(&log).unwrap();
+        let parsed: SessionLog = serde_json::from_str(&json).unwrap();
+        assert!(verify_session_log(&parsed, &anchor.public_key_base64()).is_ok());
+
+        let full = SessionLog::sign(&anchor, None, entries(), false).unwrap();
+        assert!(full.entries[1].summary.is_some());
+        assert!(verify_session_log(&full, &anchor.public_key_base64()).is_ok());
+    }
+
+    #[test]
+    fn test_session_log_detects_tampering() {
+        let anchor = IdentityAnchor::new(None);
+        let key = anchor.public_key_base64();
+        let log = SessionLog::sign(&anchor, None, entries(), true).unwrap();
+
+        let mut dropped = log.clone();
+        dropped.entries.remove(1);
+        assert!(matches!(
+            verify_session_log(&dropped, &key),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        let mut unredacted = log.clone();
+        unredacted.redacted = false;
+        assert!(verify_session_log(&unredacted, &key).is_err());
+
+        // A log re-signed by someone else fails against the real key.
+        let other = IdentityAnchor::new(None);
+        let forged = SessionLog::sign(&other, None, entries(), true).unwrap();
+        assert!(matches!(
+            verify_session_log(&forged, &key),
+            Err(IdentityError::InvalidKey(_))
+        ));
+    }
+}
@@ -0,0 +1,342 @@
+//! W3C Verifiable Credential encoding for action receipts.
+//!
+//! [`to_vc_jwt`] presents a receipt to systems that only understand
+//! Verifiable Credentials. The receipt becomes the `credentialSubject` of a
+//! VC (data model v1.1), which is signed as a compact JWS (`alg: EdDSA`) in
+//! the VC-JWT encoding. The issuer is the actor's `did:key`, so a VC-JWT
+//! verifier can check the credential without knowing anything about AID.
+//!
+//! [`from_vc_jwt`] is the inverse. It checks the JWS, then rebuilds the
+//! receipt and verifies the receipt's own signature, so an imported receipt
+//! is exactly as trustworthy as the original.
+//!
+//! The JWS signs the standard `header.payload` input with no domain tag, as
+//! VC verifiers expect. That input always starts with the base64url of
+//! `{"`, so it cannot collide with any domain-tagged or hex AID message.
+
+use ed25519_dalek::Signature;
+use serde_json::{json, Value};
+
+use crate::crypto::signer::Signer;
+use crate::crypto::signing;
+use crate::error::{IdentityError, Result};
+use crate::identity::anchor::decode_public_key;
+use crate::identity::did::{did_key, verifying_key_from_did_key};
+
+use super::receipt::{ActionReceipt, ReceiptId};
+use super::verify::verify_receipt;
+
+/// JSON-LD context of the VC data model v1.1.
+pub const CREDENTIALS_V1_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+
+/// Credential type naming a credential whose subject is an action receipt.
+pub const RECEIPT_CREDENTIAL_TYPE: &str = "ActionReceiptCredential";
+
+/// The JWS algorithm for Ed25519 signatures.
+const JWS_ALGORITHM: &str = "EdDSA";
+
+// ── Export ───────────────────────────────────────────────────────────────────
+
+/// The unsigned VC for `receipt`, as a JSON-LD document.
+///
+/// Both the issuer and the subject are the actor's `did:key`. The whole
+/// receipt, signature included, is carried as `credentialSubject.receipt`.
+pub fn receipt_credential(receipt: &ActionReceipt) -> Result<Value> {
+    let did = did_key(&decode_public_key(&receipt.actor_key)?);
+    let receipt_json = serde_json::to_value(receipt)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+
+    Ok(json!({
+        "@context": [CREDENTIALS_V1_CONTEXT],
+        "id": credential_id(&receipt.id),
+        "type": ["VerifiableCredential", RECEIPT_CREDENTIAL_TYPE],
+        "issuer": did,
+        "issuanceDate": crate::time::micros_to_rfc3339(receipt.timestamp),
+        "credentialSubject": {
+            "id": did,
+            "receipt": receipt_json,
+        },
+    }))
+}
+
+/// Encode `receipt` as a VC-JWT signed by `signer`.
+///
+/// `signer` must hold the receipt's actor key: the credential asserts that
+/// the actor took the action, so no one else can issue it.
+pub fn to_vc_jwt<S: Signer + ?Sized>(receipt: &ActionReceipt, signer: &S) -> Result<String> {
+    if signer.public_key_base64() != receipt.actor_key {
+        return Err(IdentityError::InvalidKey(format!(
+            "signer key is not the actor key of receipt {}",
+            receipt.id
+        )));
+    }
+
+    let credential = receipt_credential(receipt)?;
+    let key = signer.verifying_key();
+    let did = did_key(&key);
+    let header = json!({
+        "alg": JWS_ALGORITHM,
+        "typ": "JWT",
+        "kid": format!("{did}#{}", &did["did:key:".len()..]),
+    });
+    let payload = json!({
+        "iss": did,
+        "sub": did,
+        "jti": credential_id(&receipt.id),
+        "nbf": receipt.timestamp / 1_000_000,
+        "vc": credential,
+    });
+
+    let signing_input = format!("{}.{}", encode_segment(&header)?, encode_segment(&payload)?);
+    let signature = signer.sign_message(signing_input.as_bytes())?;
+    signing::verify(&key, signing_input.as_bytes(), &signature)?;
+    Ok(format!(
+        "{signing_input}.{}",
+        base64::Engine::encode(
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            signature.to_bytes()
+        )
+    ))
+}
+
+// ── Import ───────────────────────────────────────────────────────────────────
+
+/// Decode and verify a VC-JWT produced by [`to_vc_jwt`], returning the
+/// receipt it carries.
+///
+/// Checks, in order: the JWS signature against the `did:key` in the header,
+/// that the issuer is that DID, that the receipt's actor key is the same
+/// key, that the credential ID names the receipt, and finally the receipt's
+/// own signature and witnesses.
+///
+/// # Errors
+///
+/// [`IdentityError::InvalidFileFormat`] for a token that is not a receipt
+/// VC-JWT, [`IdentityError::SignatureInvalid`] if either signature fails,
+/// and [`IdentityError::InvalidKey`] if the keys do not agree.
+pub fn from_vc_jwt(token: &str) -> Result<ActionReceipt> {
+    let mut parts = token.trim().split('.');
+    let (Some(header_b64), Some(payload_b64), Some(signature_b64), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(malformed("expected three dot-separated parts"));
+    };
+
+    let header = decode_segment(header_b64)?;
+    if header["alg"] != JWS_ALGORITHM {
+        return Err(malformed(&format!(
+            "unsupported JWS algorithm {}",
+            header["alg"]
+        )));
+    }
+    let kid = header["kid"]
+        .as_str()
+        .ok_or_else(|| malformed("header has no kid"))?;
+    let key = verifying_key_from_did_key(kid)?;
+
+    let signature: [u8; 64] = base64::Engine::decode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        signature_b64,
+    )
+    .ok()
+    .and_then(|bytes| bytes.try_into().ok())
+    .ok_or(IdentityError::SignatureInvalid)?;
+    signing::verify(
+        &key,
+        format!("{header_b64}.{payload_b64}").as_bytes(),
+        &Signature::from_bytes(&signature),
+    )?;
+
+    let payload = decode_segment(payload_b64)?;
+    if payload["iss"] != did_key(&key) {
+        return Err(IdentityError::InvalidKey(
+            "VC-JWT issuer is not the signing key".into(),
+        ));
+    }
+    let credential = &payload["vc"];
+    let is_receipt_credential = credential["type"]
+        .as_array()
+        .is_some_and(|types| types.iter().any(|t| t == RECEIPT_CREDENTIAL_TYPE));
+    if !is_receipt_credential {
+        return Err(malformed(&format!(
+            "credential is not an {RECEIPT_CREDENTIAL_TYPE}"
+        )));
+    }
+    let receipt: ActionReceipt =
+        serde_json::from_value(credential["credentialSubject"]["receipt"].clone())
+            .map_err(|e| malformed(&format!("invalid receipt in credential: {e}")))?;
+
+    if decode_public_key(&receipt.actor_key)? != key {
+        return Err(IdentityError::InvalidKey(format!(
+            "VC-JWT was not signed by the actor of receipt {}",
+            receipt.id
+        )));
+    }
+    if payload["jti"] != credential_id(&receipt.id) {
+        return Err(malformed("credential ID does not name its receipt"));
+    }
+    if !verify_receipt(&receipt)?.is_valid {
+        return Err(IdentityError::SignatureInvalid);
+    }
+
+    Ok(receipt)
+}
+
+// ── Helpers ──────────────────────────────────────────────────────────────────
+
+/// The credential ID for a receipt.
+fn credential_id(id: &ReceiptId) -> String {
+    format!("urn:aid:receipt:{id}")
+}
+
+/// base64url (unpadded) of a JSON value's compact serialization.
+fn encode_segment(value: &Value) -> Result<String> {
+    let json =
+        serde_json::to_vec(value).map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    Ok(base64::Engine::encode(
+        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+        json,
+    ))
+}
+
+/// Decode a base64url JSON segment of a JWS.
+fn decode_segment(segment: &str) -> Result<Value> {
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, segment)
+        .map_err(|e| malformed(&format!("invalid base64url segment: {e}")))?;
+    serde_json::from_slice(&bytes).map_err(|e| malformed(&format!("invalid JSON segment: {e}")))
+}
+
+fn malformed(reason: &str) -> IdentityError {
+    IdentityError::InvalidFileFormat(format!("not a receipt VC-JWT: {reason}"))
+}
+
+// ── Tests ────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::action::{ActionContent, ActionType};
+    use crate::receipt::receipt::ReceiptBuilder;
+
+    fn signed_receipt(anchor: &IdentityAnchor) -> ActionReceipt {
+        ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved deployment"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap()
+    }
+
+    /// Re-sign `token` after `edit` changes its payload.
+    fn resign(token: &str, anchor: &IdentityAnchor, edit: impl FnOnce(&mut Value)) -> String {
+        let mut parts = token.split('.');
+        let header = parts.next().unwrap();
+        let mut payload = decode_segment(parts.next().unwrap()).unwrap();
+        edit(&mut payload);
+        let input = format!("{header}.{}", encode_segment(&payload).unwrap());
+        let signature = anchor.sign_message(input.as_bytes()).unwrap();
+        format!(
+            "{input}.{}",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+                signature.to_bytes()
+            )
+        )
+    }
+
+    #[test]
+    fn test_vc_jwt_round_trip() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = signed_receipt(&anchor);
+
+        let token = to_vc_jwt(&receipt, &anchor).unwrap();
+        let header = decode_segment(token.split('.').next().unwrap()).unwrap();
+        assert_eq!(header["alg"], "EdDSA");
+        assert!(header["kid"]
+            .as_str()
+            .unwrap()
+            .starts_with(&did_key(anchor.verifying_key())));
+
+        let imported = from_vc_jwt(&token).unwrap();
+        assert_eq!(imported.id, receipt.id);
+        assert_eq!(imported.receipt_hash, receipt.receipt_hash);
+        assert_eq!(imported.signature, receipt.signature);
+    }
+
+    #[test]
+    fn test_credential_document_shape() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = signed_receipt(&anchor);
+        let vc = receipt_credential(&receipt).unwrap();
+
+        assert_eq!(vc["@context"][0], CREDENTIALS_V1_CONTEXT);
+        assert_eq!(vc["type"][1], RECEIPT_CREDENTIAL_TYPE);
+        assert_eq!(vc["id"], format!("urn:aid:receipt:{}", receipt.id));
+        assert_eq!(vc["issuer"], vc["credentialSubject"]["id"]);
+        assert_eq!(vc["credentialSubject"]["receipt"]["id"], receipt.id.0);
+    }
+
+    #[test]
+    fn test_only_the_actor_can_issue() {
+        let anchor = IdentityAnchor::new(None);
+        let other = IdentityAnchor::new(None);
+        let receipt = signed_receipt(&anchor);
+
+        assert!(matches!(
+            to_vc_jwt(&receipt, &other),
+            Err(IdentityError::InvalidKey(_))
+        ));
+
+        // A validly signed JWS from another key carrying the receipt.
+        let token = to_vc_jwt(&receipt, &anchor).unwrap();
+        let other_did = did_key(other.verifying_key());
+        let header = encode_segment(&json!({
+            "alg": "EdDSA",
+            "typ": "JWT",
+            "kid": format!("{other_did}#{}", &other_did["did:key:".len()..]),
+        }))
+        .unwrap();
+        let unsigned = format!("{header}.{}", token.split('.').nth(1).unwrap());
+        let forged = resign(&unsigned, &other, |payload| {
+            payload["iss"] = other_did.clone().into();
+        });
+        assert!(matches!(
+            from_vc_jwt(&forged),
+            Err(IdentityError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = signed_receipt(&anchor);
+        let token = to_vc_jwt(&receipt, &anchor).unwrap();
+
+        // Payload edited without re-signing the JWS.
+        let mut parts: Vec<String> = token.split('.').map(str::to_string).collect();
+        let mut payload = decode_segment(&parts[1]).unwrap();
+        payload["nbf"] = 0.into();
+        parts[1] = encode_segment(&payload).unwrap();
+        assert!(matches!(
+            from_vc_jwt(&parts.join(".")),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        // Receipt edited and the JWS re-signed: the receipt's own signature fails.
+        let edited = resign(&token, &anchor, |payload| {
+            payload["vc"]["credentialSubject"]["receipt"]["action"]["description"] =
+                "Rejected deployment".into();
+        });
+        assert!(matches!(
+            from_vc_jwt(&edited),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        assert!(matches!(
+            from_vc_jwt("not-a-jwt"),
+            Err(IdentityError::InvalidFileFormat(_))
+        ));
+    }
+}
@@ -1,8 +1,10 @@
 //! Receipt verification.
 
 use crate::crypto::keys::Ed25519KeyPair;
-use crate::crypto::signing;
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::error::{IdentityError, Result};
+use crate::identity::MultisigDocument;
+use crate::trust::verify::{IssuerAllowlist, TimeSource};
 
 use super::receipt::ActionReceipt;
 
@@ -12,13 +14,45 @@ pub struct ReceiptVerification {
     pub signature_valid: bool,
     pub chain_valid: Option<bool>,
     pub witnesses_valid: Vec<bool>,
+    pub issuer_trusted: bool,
+    /// Signed top-level fields this version does not understand.
+    pub unknown_fields: Vec<String>,
     pub is_valid: bool,
     pub verified_at: u64,
 }
 
+impl ReceiptVerification {
+    /// Strict profile: valid, and every signed field was understood.
+    pub fn is_valid_strict(&self) -> bool {
+        self.is_valid && self.unknown_fields.is_empty()
+    }
+}
+
 /// Verify that a receipt's signature is valid.
 pub fn verify_receipt(receipt: &ActionReceipt) -> Result<ReceiptVerification> {
-    let now = crate::time::now_micros();
+    verify_receipt_with_issuers(receipt, &IssuerAllowlist::new())
+}
+
+/// Verify a receipt's signature and require that the actor key is in
+/// `issuers` as of the receipt's timestamp.
+///
+/// An empty allowlist accepts any valid self-signature.
+pub fn verify_receipt_with_issuers(
+    receipt: &ActionReceipt,
+    issuers: &IssuerAllowlist,
+) -> Result<ReceiptVerification> {
+    verify_receipt_at(receipt, issuers, &TimeSource::HostClock)
+}
+
+/// Verify a receipt, stamping the result with the time from `time`.
+///
+/// Fails with [`IdentityError::UntrustedTime`] if `time` does not verify.
+pub fn verify_receipt_at(
+    receipt: &ActionReceipt,
+    issuers: &IssuerAllowlist,
+    time: &TimeSource,
+) -> Result<ReceiptVerification> {
+    let now = time.now()?;
 
     // Decode the actor's public key
     let pub_bytes = base64::Engine::decode(
@@ -33,51 +67,75 @@ pub fn verify_receipt(receipt: &ActionReceipt) -> Result<ReceiptVerification> {
 
     let verifying_key = Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)?;
 
-    // Verify the main signature
-    let sig_valid = signing::verify_from_base64(
-        &verifying_key,
-        receipt.receipt_hash.as_bytes(),
-        &receipt.signature,
-    )
-    .is_ok();
+    // Verify the main signature, and that the hash covers the receipt's
+    // actual content (including any preserved unknown fields)
+    let sig_valid = receipt.compute_hash() == receipt.receipt_hash
+        && signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::Receipt,
+            receipt.signature_version,
+            receipt.receipt_hash.as_bytes(),
+            &receipt.signature,
+        )
+        .is_ok();
 
     // Verify witness signatures
     let witnesses_valid: Vec<bool> = receipt
         .witnesses
         .iter()
-        .map(|w| {
-            let Ok(wb) =
-                base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &w.witness_key)
-            else {
-                return false;
-            };
-            let Ok(wk): std::result::Result<[u8; 32], _> = wb.try_into() else {
-                return false;
-            };
-            let Ok(wvk) = Ed25519KeyPair::verifying_key_from_bytes(&wk) else {
-                return false;
-            };
-            let to_verify = format!(
-                "witness:{}:{}:{}",
-                w.witness.0, receipt.receipt_hash, w.witnessed_at
-            );
-            signing::verify_from_base64(&wvk, to_verify.as_bytes(), &w.signature).is_ok()
-        })
+        .map(|w| w.verify(&receipt.receipt_hash).is_ok())
         .collect();
 
     let all_witnesses_ok = witnesses_valid.iter().all(|&v| v);
-    let is_valid = sig_valid && all_witnesses_ok;
+    let issuer_trusted = issuers.permits(&receipt.actor_key, receipt.timestamp);
+    let is_valid = sig_valid && all_witnesses_ok && issuer_trusted;
 
     Ok(ReceiptVerification {
         signature_valid: sig_valid,
         chain_valid: None, // Chain verification requires access to the receipt store
         witnesses_valid,
+        issuer_trusted,
+        unknown_fields: receipt.unknown_fields(),
         is_valid,
         verified_at: now,
     })
 }
 
-#[cfg(test)]
+/// Verify a receipt issued by the multi-signature identity in `document`.
+///
+/// The receipt must name the document's identity and configuration, and at
+/// least `threshold` distinct members must have validly cosigned it. Fails
+/// with [`IdentityError::InvalidId`] if `document` does not match its own
+/// keys and threshold.
+pub fn verify_multisig_receipt(
+    receipt: &ActionReceipt,
+    document: &MultisigDocument,
+) -> Result<ReceiptVerification> {
+    let multisig = document.anchor()?;
+
+    let sig_valid = multisig.issued(&receipt.actor, &receipt.actor_key)
+        && receipt.compute_hash() == receipt.receipt_hash
+        && multisig.is_satisfied(&receipt.receipt_hash, &receipt.cosignatures);
+
+    let witnesses_valid: Vec<bool> = receipt
+        .witnesses
+        .iter()
+        .map(|w| w.verify(&receipt.receipt_hash).is_ok())
+        .collect();
+    let is_valid = sig_valid && witnesses_valid.iter().all(|&v| v);
+
+    Ok(ReceiptVerification {
+        signature_valid: sig_valid,
+        chain_valid: None,
+        witnesses_valid,
+        issuer_trusted: true,
+        unknown_fields: receipt.unknown_fields(),
+        is_valid,
+        verified_at: crate::time::now_micros(),
+    })
+}
+
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -101,6 +159,34 @@ mod tests {
         assert!(result.is_valid);
     }
 
+    #[test]
+    fn test_receipt_verify_legacy_signature() {
+        let anchor = IdentityAnchor::new(None);
+        let mut receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        // A receipt from before domain separation: no version, and a
+        // signature over the bare receipt hash.
+        receipt.signature_version = signing::LEGACY_SIGNATURE_VERSION;
+        receipt.receipt_hash = receipt.compute_hash();
+        receipt.signature =
+            signing::sign_to_base64(anchor.signing_key(), receipt.receipt_hash.as_bytes());
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert!(json.get("signature_version").is_none());
+        let loaded: ActionReceipt = serde_json::from_value(json).unwrap();
+        assert!(verify_receipt(&loaded).unwrap().is_valid);
+
+        // The same bare signature claiming the current version is rejected.
+        let mut upgraded = loaded;
+        upgraded.signature_version = signing::SIGNATURE_VERSION;
+        assert!(!verify_receipt(&upgraded).unwrap().signature_valid);
+    }
+
     #[test]
     fn test_receipt_verify_wrong_actor() {
         let anchor_a = IdentityAnchor::new(None);
@@ -152,4 +238,122 @@ mod tests {
         assert!(result.witnesses_valid[0]);
         assert!(result.is_valid);
     }
+
+    #[test]
+    fn test_receipt_verify_issuer_allowlist() {
+        let anchor = IdentityAnchor::new(None);
+        let other = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        let other_key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            other.verifying_key_bytes(),
+        );
+        let rejecting = IssuerAllowlist::new().allow(other_key);
+        let result = verify_receipt_with_issuers(&receipt, &rejecting).unwrap();
+        assert!(result.signature_valid);
+        assert!(!result.issuer_trusted);
+        assert!(!result.is_valid);
+
+        let accepting = IssuerAllowlist::new().allow(receipt.actor_key.clone());
+        let result = verify_receipt_with_issuers(&receipt, &accepting).unwrap();
+        assert!(result.issuer_trusted);
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_receipt_verify_unknown_fields() {
+        let anchor = IdentityAnchor::new(None);
+        let receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .extra_field("policy_version", serde_json::json!(2))
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        // Simulate an older verifier loading a newer artifact.
+        let json = serde_json::to_string(&receipt).unwrap();
+        let loaded: ActionReceipt = serde_json::from_str(&json).unwrap();
+
+        let result = verify_receipt(&loaded).unwrap();
+        assert!(result.signature_valid);
+        assert!(result.is_valid);
+        assert_eq!(result.unknown_fields, vec!["policy_version".to_string()]);
+        assert!(!result.is_valid_strict());
+    }
+
+    #[test]
+    fn test_receipt_verify_tampered_extra_field() {
+        let anchor = IdentityAnchor::new(None);
+        let mut receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .extra_field("policy_version", serde_json::json!(2))
+        .sign(anchor.signing_key())
+        .unwrap();
+
+        receipt
+            .extra
+            .insert("policy_version".into(), serde_json::json!(3));
+        let result = verify_receipt(&receipt).unwrap();
+        assert!(!result.signature_valid);
+        assert!(!result.is_valid);
+    }
+
+    #[test]
+    fn test_multisig_receipt_requires_threshold() {
+        use crate::identity::MultisigAnchor;
+
+        let members: Vec<IdentityAnchor> = (0..3).map(|_| IdentityAnchor::new(None)).collect();
+        let multisig =
+            MultisigAnchor::new(members.iter().map(|m| *m.verifying_key()).collect(), 2).unwrap();
+        let doc = multisig.to_document();
+
+        let builder = ReceiptBuilder::new(
+            multisig.id(),
+            ActionType::Decision,
+            ActionContent::new("Release funds"),
+        );
+        let mut receipt = multisig.prepare_receipt(builder).unwrap();
+        // The single-key verifier cannot accept a multisig receipt.
+        assert!(!matches!(verify_receipt(&receipt), Ok(v) if v.is_valid));
+
+        multisig
+            .cosign_receipt(&mut receipt, members[0].signing_key())
+            .unwrap();
+        // A repeat signature from the same member counts once.
+        receipt.cosignatures.push(receipt.cosignatures[0].clone());
+        assert!(!verify_multisig_receipt(&receipt, &doc).unwrap().is_valid);
+
+        multisig
+            .cosign_receipt(&mut receipt, members[2].signing_key())
+            .unwrap();
+        let result = verify_multisig_receipt(&receipt, &doc).unwrap();
+        assert!(result.signature_valid);
+        assert!(result.is_valid);
+
+        let outsider = IdentityAnchor::new(None);
+        assert!(multisig
+            .cosign_receipt(&mut receipt, outsider.signing_key())
+            .is_err());
+
+        // A document claiming a lower threshold is rejected outright.
+        let mut lowered = doc.clone();
+        lowered.threshold = 1;
+        assert!(verify_multisig_receipt(&receipt, &lowered).is_err());
+
+        let mut tampered = receipt;
+        tampered.action = ActionContent::new("Release more funds");
+        assert!(!verify_multisig_receipt(&tampered, &doc).unwrap().is_valid);
+    }
 }
@@ -1,11 +1,37 @@
 //! Witness signatures on action receipts.
 
-use ed25519_dalek::SigningKey;
+use ed25519_dalek::{SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
-use crate::crypto::signing;
+use crate::crypto::keys::Ed25519KeyPair;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
 use crate::identity::IdentityId;
 
+/// The message a witness signs:
+/// `aid:witness:v1:witness:{witness_id}:{receipt_hash}:{witnessed_at}`.
+///
+/// Exposed so a remote witness can produce a signature without this crate's
+/// key handling; the result is verified by [`WitnessSignature::verify`]
+/// with the current `signature_version`.
+pub fn witness_signing_input(
+    witness: &IdentityId,
+    receipt_hash: &str,
+    witnessed_at: u64,
+) -> String {
+    format!(
+        "{}:{}",
+        SignatureDomain::Witness.tag(),
+        witness_message(witness, receipt_hash, witnessed_at)
+    )
+}
+
+/// The witness message without its domain tag, as signed before domain
+/// separation.
+fn witness_message(witness: &IdentityId, receipt_hash: &str, witnessed_at: u64) -> String {
+    format!("witness:{}:{receipt_hash}:{witnessed_at}", witness.0)
+}
+
 /// A witness signature on a receipt.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WitnessSignature {
@@ -13,6 +39,11 @@ pub struct WitnessSignature {
     pub witness_key: String,
     pub witnessed_at: u64,
     pub signature: String,
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 impl WitnessSignature {
@@ -23,14 +54,105 @@ impl WitnessSignature {
             &base64::engine::general_purpose::STANDARD,
             signing_key.verifying_key().to_bytes(),
         );
-        let to_sign = format!("witness:{}:{receipt_hash}:{now}", witness_id.0);
-        let signature = signing::sign_to_base64(signing_key, to_sign.as_bytes());
+        let to_sign = witness_message(&witness_id, receipt_hash, now);
+        let signature =
+            signing::sign_in_domain(signing_key, SignatureDomain::Witness, to_sign.as_bytes());
 
         Self {
             witness: witness_id,
             witness_key,
             witnessed_at: now,
             signature,
+            signature_version: signing::SIGNATURE_VERSION,
         }
     }
+    /// Decode `witness_key` into an Ed25519 public key.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if the key is not valid base64 or
+    /// not a 32-byte Ed25519 public key.
+    pub fn verifying_key(&self) -> Result<VerifyingKey> {
+        let key_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &self.witness_key,
+        )
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid witness key: {e}")))?;
+        let key_bytes: [u8; 32] = key_bytes
+            .try_into()
+            .map_err(|_| IdentityError::InvalidKey("witness key must be 32 bytes".into()))?;
+        Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)
+    }
+
+    /// Verify this witness signature over `receipt_hash`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if `witness_key` is not a valid
+    /// Ed25519 public key, or `IdentityError::SignatureInvalid` if the
+    /// signature does not verify.
+    pub fn verify(&self, receipt_hash: &str) -> Result<()> {
+        let verifying_key = self.verifying_key()?;
+        let to_verify = witness_message(&self.witness, receipt_hash, self.witnessed_at);
+        signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::Witness,
+            self.signature_version,
+            to_verify.as_bytes(),
+            &self.signature,
+        )
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+
+    #[test]
+    fn test_witness_verify_roundtrip() {
+        let witness = IdentityAnchor::new(None);
+        let ws = WitnessSignature::create(witness.id(), witness.signing_key(), "abc123");
+        assert!(ws.verify("abc123").is_ok());
+        assert!(matches!(
+            ws.verify("other"),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_witness_verify_external_signature() {
+        let witness = IdentityAnchor::new(None);
+        let input = witness_signing_input(&witness.id(), "abc123", 42);
+        let ws = WitnessSignature {
+            witness: witness.id(),
+            witness_key: witness.public_key_base64(),
+            witnessed_at: 42,
+            signature: signing::sign_to_base64(witness.signing_key(), input.as_bytes()),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        assert!(ws.verify("abc123").is_ok());
+
+        // Signed without the domain tag, it only passes as a legacy witness.
+        let bare = witness_message(&witness.id(), "abc123", 42);
+        let legacy = WitnessSignature {
+            signature: signing::sign_to_base64(witness.signing_key(), bare.as_bytes()),
+            ..ws.clone()
+        };
+        assert!(legacy.verify("abc123").is_err());
+        let legacy = WitnessSignature {
+            signature_version: signing::LEGACY_SIGNATURE_VERSION,
+            ..legacy
+        };
+        assert!(legacy.verify("abc123").is_ok());
+
+        let bad_key = WitnessSignature {
+            witness_key: "not-a-key".into(),
+            ..ws
+        };
+        assert!(matches!(
+            bad_key.verify("abc123"),
+            Err(IdentityError::InvalidKey(_))
+        ));
+    }
 }
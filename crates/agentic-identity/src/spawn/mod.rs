@@ -4,19 +4,29 @@
 //! - Child identity creation with authority bounding
 //! - Five spawn types (Worker, Delegate, Clone, Specialist, Custom)
 //! - Lineage tracking and verification
+//! - Portable lineage proofs for third parties
 //! - Spawn lifetime management
 //! - Authority decay and depth limits
 //! - Termination with optional cascade
+//! - Metered resource budgets (receipts, sub-spawns, cost)
+//! - Lineage trees with per-node authority, rendered as text or Mermaid
 
 pub mod engine;
+pub mod proof;
+pub mod tree;
 pub mod types;
 
 pub use types::{
-    Lineage, LineageVerification, SpawnConstraints, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord,
-    SpawnType,
+    BudgetResource, Lineage, LineageVerification, SpawnAuthority, SpawnConstraints,
+    SpawnConsumption, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord, SpawnType,
 };
 
 pub use engine::{
-    can_spawn, get_ancestors, get_children, get_descendants, get_effective_authority, spawn_child,
-    terminate_spawn, verify_lineage,
+    authority_for, can_spawn, consume_budget, get_ancestors, get_children, get_descendants,
+    get_effective_authority, verify_lineage,
 };
+#[cfg(feature = "signing")]
+pub use engine::{spawn_child, spawn_child_with_policy, terminate_spawn};
+
+pub use proof::{prove_descendant, verify_lineage_proof, LineageProof};
+pub use tree::{SpawnTree, SpawnTreeNode};
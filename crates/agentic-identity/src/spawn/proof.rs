@@ -0,0 +1,390 @@
+//! Lineage proofs — a child proving it descends from a given ancestor.
+//!
+//! A [`LineageProof`] carries only the spawn records on the path from the
+//! ancestor down to the child (never siblings or other branches), plus the
+//! child's signature over that path. A third party holding the ancestor's
+//! public [`IdentityDocument`] checks every hop with
+//! [`verify_lineage_proof`]: the parent's signature and the child's
+//! acknowledgment on each record, that consecutive hops link up, and that
+//! the path starts at the ancestor's key. The ancestor document itself must
+//! pass [`verify_genesis`], so a forged document cannot claim a root's ID.
+//!
+//! The spawn signatures cover who spawned whom, when, and as what type. The
+//! authority and termination fields of the records are as the prover
+//! presented them, so [`LineageVerification::effective_authority`] and the
+//! activity flags describe the snapshot in the proof, not a live check.
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::keys::Ed25519KeyPair;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+use crate::identity::{verify_genesis, IdentityAnchor, IdentityDocument, IdentityId};
+use crate::trust::capabilities_cover;
+
+use super::engine::{spawn_ack_input, spawn_signing_input};
+use super::types::{LineageVerification, SpawnRecord};
+
+/// Proof that `child` was spawned, directly or transitively, by `ancestor`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageProof {
+    pub child: IdentityId,
+    pub ancestor: IdentityId,
+    /// Spawn records from the ancestor's spawn (first) to the child's (last).
+    pub hops: Vec<SpawnRecord>,
+    pub created_at: u64,
+    /// Child's signature over the ancestor, the spawn IDs, and `created_at`.
+    pub child_signature: String,
+    /// Signature scheme of `child_signature`; absent on proofs made before
+    /// domain separation. Each hop carries its own.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// The message the child signs: the claimed ancestor, the spawn IDs in
+/// order, and the proof time.
+fn proof_signing_input(
+    child: &IdentityId,
+    ancestor: &IdentityId,
+    hops: &[SpawnRecord],
+    created_at: u64,
+) -> String {
+    let path: Vec<&str> = hops.iter().map(|h| h.id.0.as_str()).collect();
+    format!(
+        "lineage-proof:{}:{}:{}:{created_at}",
+        child.0,
+        ancestor.0,
+        path.join(",")
+    )
+}
+
+/// Build a proof that `child` descends from `ancestor`.
+///
+/// Only the records on the direct path are included.
+///
+/// # Errors
+///
+/// Returns `IdentityError::NotFound` if `child` has no spawn record or its
+/// lineage does not reach `ancestor`.
+pub fn prove_descendant(
+    child: &IdentityAnchor,
+    ancestor: &IdentityId,
+    records: &[SpawnRecord],
+) -> Result<LineageProof> {
+    let child_id = child.id();
+    let mut hops = Vec::new();
+    let mut current = child_id.clone();
+
+    // Bounded by the record count to guard against cycles.
+    for _ in 0..records.len() {
+        let Some(record) = records.iter().find(|r| r.child_id == current) else {
+            break;
+        };
+        hops.push(record.clone());
+        if record.parent_id == *ancestor {
+            hops.reverse();
+            let created_at = crate::time::now_micros();
+            let input = proof_signing_input(&child_id, ancestor, &hops, created_at);
+            return Ok(LineageProof {
+                child: child_id,
+                ancestor: ancestor.clone(),
+                hops,
+                created_at,
+                child_signature: signing::sign_in_domain(
+                    child.signing_key(),
+                    SignatureDomain::LineageProof,
+                    input.as_bytes(),
+                ),
+                signature_version: signing::SIGNATURE_VERSION,
+            });
+        }
+        current = record.parent_id.clone();
+    }
+
+    Err(IdentityError::NotFound(format!(
+        "{child_id} does not descend from {ancestor}"
+    )))
+}
+
+/// Verify a lineage proof against the ancestor's public document.
+///
+/// Problems are reported in [`LineageVerification::errors`]; the proof is
+/// valid only if the ancestor document passes [`verify_genesis`], the path
+/// is intact, every signature verifies, and every spawn on the path is
+/// still active.
+pub fn verify_lineage_proof(
+    proof: &LineageProof,
+    ancestor_doc: &IdentityDocument,
+) -> LineageVerification {
+    let mut errors = Vec::new();
+
+    if let Err(e) = ancestor_doc.verify_signature() {
+        errors.push(format!("ancestor document does not verify: {e}"));
+    }
+    if !verify_genesis(ancestor_doc) {
+        errors.push(format!(
+            "ancestor document's key history does not derive {}",
+            ancestor_doc.id
+        ));
+    }
+    if ancestor_doc.id != proof.ancestor {
+        errors.push(format!(
+            "ancestor document is for {}, proof claims {}",
+            ancestor_doc.id, proof.ancestor
+        ));
+    }
+    let ancestor_keys: Vec<&str> = std::iter::once(ancestor_doc.public_key.as_str())
+        .chain(
+            ancestor_doc
+                .rotation_history
+                .iter()
+                .flat_map(|r| [r.previous_key.as_str(), r.new_key.as_str()]),
+        )
+        .collect();
+
+    match (proof.hops.first(), proof.hops.last()) {
+        (Some(first), Some(last)) => {
+            if first.parent_id != proof.ancestor
+                || !ancestor_keys.contains(&first.parent_key.as_str())
+            {
+                errors.push(format!("path does not start at {}", proof.ancestor));
+            }
+            if last.child_id != proof.child {
+                errors.push(format!("path does not end at {}", proof.child));
+            }
+        }
+        _ => errors.push("proof contains no spawn records".to_string()),
+    }
+
+    for (i, hop) in proof.hops.iter().enumerate() {
+        if let Some(prev) = i.checked_sub(1).map(|p| &proof.hops[p]) {
+            if hop.parent_id != prev.child_id || hop.parent_key != prev.child_key {
+                errors.push(format!(
+                    "spawn {} does not continue from {}",
+                    hop.id, prev.id
+                ));
+            }
+        }
+        if let Err(e) = verify_hop(hop) {
+            errors.push(format!("spawn {}: {e}", hop.id));
+        }
+    }
+
+    if let Some(last) = proof.hops.last() {
+        let input =
+            proof_signing_input(&proof.child, &proof.ancestor, &proof.hops, proof.created_at);
+        let signed = decode_key(&last.child_key).and_then(|key| {
+            signing::verify_versioned(
+                &key,
+                SignatureDomain::LineageProof,
+                proof.signature_version,
+                input.as_bytes(),
+                &proof.child_signature,
+            )
+        });
+        if signed.is_err() {
+            errors.push("child signature over the proof does not verify".to_string());
+        }
+    }
+
+    let lineage_valid = errors.is_empty();
+    let mut all_active = true;
+    let mut revoked_ancestor = None;
+    for hop in &proof.hops {
+        if hop.terminated {
+            all_active = false;
+            revoked_ancestor.get_or_insert_with(|| hop.child_id.clone());
+            errors.push(format!("spawn {} is terminated", hop.id));
+        } else if hop.lifetime.is_expired(hop.spawn_timestamp) {
+            all_active = false;
+            errors.push(format!("spawn {} has expired", hop.id));
+        }
+    }
+    let is_valid = lineage_valid && all_active;
+
+    // Child's grant, narrowed by every ancestor spawn's grant.
+    let effective_authority = match proof.hops.split_last() {
+        Some((last, ancestors)) if is_valid => last
+            .authority_granted
+            .iter()
+            .filter(|cap| {
+                ancestors
+                    .iter()
+                    .all(|hop| capabilities_cover(&hop.authority_granted, &cap.uri))
+            })
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    LineageVerification {
+        identity: proof.child.clone(),
+        lineage_valid,
+        all_ancestors_active: all_active,
+        effective_authority,
+        spawn_depth: proof.hops.len() as u32,
+        revoked_ancestor,
+        is_valid,
+        verified_at: crate::time::now_micros(),
+        errors,
+    }
+}
+
+/// Check one spawn record's signatures and that its keys match its IDs.
+fn verify_hop(hop: &SpawnRecord) -> Result<()> {
+    let parent_key = decode_key(&hop.parent_key)?;
+    let child_key = decode_key(&hop.child_key)?;
+    if IdentityId::from_verifying_key(&child_key) != hop.child_id {
+        return Err(IdentityError::InvalidKey(
+            "child key does not match child ID".into(),
+        ));
+    }
+
+    let input = spawn_signing_input(hop)?;
+    signing::verify_versioned(
+        &parent_key,
+        SignatureDomain::Spawn,
+        hop.signature_version,
+        input.as_bytes(),
+        &hop.parent_signature,
+    )?;
+
+    let ack = hop
+        .child_acknowledgment
+        .as_deref()
+        .ok_or(IdentityError::SignatureInvalid)?;
+    let ack_input = spawn_ack_input(hop)?;
+    signing::verify_versioned(
+        &child_key,
+        SignatureDomain::SpawnAcknowledgment,
+        hop.signature_version,
+        ack_input.as_bytes(),
+        ack,
+    )
+}
+
+fn decode_key(key_b64: &str) -> Result<VerifyingKey> {
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, key_b64)
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid base64 key: {e}")))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| IdentityError::InvalidKey("key must be 32 bytes".into()))?;
+    Ed25519KeyPair::verifying_key_from_bytes(&bytes)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::spawn::engine::spawn_child;
+    use crate::spawn::types::{SpawnConstraints, SpawnId, SpawnInfo, SpawnLifetime, SpawnType};
+    use crate::trust::Capability;
+
+    fn spawn(
+        parent: &IdentityAnchor,
+        parent_record: Option<&SpawnRecord>,
+        caps: &[&str],
+    ) -> (IdentityAnchor, SpawnRecord) {
+        let info = parent_record.map(|r| SpawnInfo {
+            spawn_id: r.id.clone(),
+            parent_id: r.parent_id.clone(),
+            spawn_type: r.spawn_type.clone(),
+            spawn_timestamp: r.spawn_timestamp,
+            authority_ceiling: r.authority_ceiling.clone(),
+            lifetime: r.lifetime.clone(),
+            constraints: r.constraints.clone(),
+        });
+        let caps: Vec<Capability> = caps.iter().map(|c| Capability::new(*c)).collect();
+        let (child, record, _) = spawn_child(
+            parent,
+            SpawnType::Worker,
+            "task",
+            caps.clone(),
+            caps,
+            SpawnLifetime::Indefinite,
+            SpawnConstraints::default(),
+            info.as_ref(),
+            &[],
+        )
+        .unwrap();
+        (child, record)
+    }
+
+    /// root -> a -> b, plus a sibling of b.
+    fn family() -> (IdentityAnchor, IdentityAnchor, Vec<SpawnRecord>) {
+        let root = IdentityAnchor::new(None);
+        let (a, rec_a) = spawn(&root, None, &["read:*"]);
+        let (b, rec_b) = spawn(&a, Some(&rec_a), &["read:docs"]);
+        let (_sibling, rec_sibling) = spawn(&a, Some(&rec_a), &["read:mail"]);
+        (root, b, vec![rec_a, rec_sibling, rec_b])
+    }
+
+    #[test]
+    fn test_lineage_proof_roundtrip_reveals_only_path() {
+        let (root, b, records) = family();
+        let proof = prove_descendant(&b, &root.id(), &records).unwrap();
+
+        assert_eq!(proof.hops.len(), 2);
+        assert_eq!(proof.hops[0].parent_id, root.id());
+        assert_eq!(proof.hops[1].child_id, b.id());
+        assert!(!proof.hops.iter().any(|h| h.id == records[1].id));
+
+        let result = verify_lineage_proof(&proof, &root.to_document());
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert_eq!(result.spawn_depth, 2);
+        assert_eq!(result.effective_authority[0].uri, "read:docs");
+    }
+
+    #[test]
+    fn test_lineage_proof_wrong_ancestor_fails() {
+        let (root, b, records) = family();
+        let stranger = IdentityAnchor::new(None);
+        assert!(matches!(
+            prove_descendant(&b, &stranger.id(), &records),
+            Err(IdentityError::NotFound(_))
+        ));
+
+        // A valid proof presented against a different ancestor document.
+        let proof = prove_descendant(&b, &root.id(), &records).unwrap();
+        let result = verify_lineage_proof(&proof, &stranger.to_document());
+        assert!(!result.is_valid);
+
+        // A proof truncated so it no longer reaches the claimed ancestor.
+        let mut truncated = proof.clone();
+        truncated.hops.remove(0);
+        assert!(!verify_lineage_proof(&truncated, &root.to_document()).is_valid);
+    }
+
+    #[test]
+    fn test_lineage_proof_detects_tampering() {
+        let (root, b, records) = family();
+        let proof = prove_descendant(&b, &root.id(), &records).unwrap();
+
+        let mut forged = proof.clone();
+        forged.hops[1].id = SpawnId("aspawn_forged".into());
+        assert!(!verify_lineage_proof(&forged, &root.to_document()).lineage_valid);
+
+        // Authority is covered by the parent's signature.
+        let mut widened = proof.clone();
+        widened.hops[1].authority_granted = vec![Capability::new("*")];
+        assert!(!verify_lineage_proof(&widened, &root.to_document()).lineage_valid);
+
+        let mut unsigned = proof;
+        unsigned.hops[0].child_acknowledgment = None;
+        assert!(!verify_lineage_proof(&unsigned, &root.to_document()).lineage_valid);
+    }
+
+    #[test]
+    fn test_lineage_proof_reports_terminated_hop() {
+        let (root, b, mut records) = family();
+        records[0].terminated = true;
+        let proof = prove_descendant(&b, &root.id(), &records).unwrap();
+        let result = verify_lineage_proof(&proof, &root.to_document());
+        assert!(result.lineage_valid);
+        assert!(!result.is_valid);
+        assert!(result.effective_authority.is_empty());
+    }
+}
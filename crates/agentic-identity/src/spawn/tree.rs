@@ -0,0 +1,378 @@
+//! Spawn trees — the whole lineage under one or more identities at once.
+//!
+//! `spawn_list` shows records one at a time and [`authority_for`] answers for
+//! one identity. A [`SpawnTree`] puts every spawned identity under its
+//! parent, each node carrying its own effective authority, so a multi-level
+//! agent hierarchy can be read at a glance. Render it as indented text with
+//! [`SpawnTree::render_text`], as a Mermaid flowchart with
+//! [`SpawnTree::to_mermaid`], or serialize it as JSON.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::engine::authority_for;
+use super::types::{SpawnId, SpawnRecord, SpawnType};
+use crate::error::Result;
+use crate::identity::IdentityId;
+use crate::receipt::graph::{mermaid_escape, short_id};
+use crate::storage::SpawnStore;
+use crate::trust::Capability;
+
+/// One identity in a spawn tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpawnTreeNode {
+    pub identity: IdentityId,
+    /// Spawn record that created this identity (`None` for a root).
+    pub spawn_id: Option<SpawnId>,
+    pub spawn_type: Option<SpawnType>,
+    pub purpose: Option<String>,
+    pub spawned_at: Option<u64>,
+    /// Spawns between this identity and its root; 0 for a root.
+    pub depth: u32,
+    pub terminated: bool,
+    /// Is this spawn and every ancestor spawn still active?
+    pub active: bool,
+    /// Granted authority bounded by every ancestor, as [`authority_for`]
+    /// computes it.
+    pub effective_authority: Vec<Capability>,
+    /// Children in the order they were spawned.
+    pub children: Vec<SpawnTreeNode>,
+}
+
+/// Spawned identities arranged under their parents.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpawnTree {
+    /// Top-level identities, in the order of their first spawn.
+    pub roots: Vec<SpawnTreeNode>,
+}
+
+impl SpawnTree {
+    /// Every lineage in `records`. The roots are the parents that were not
+    /// themselves spawned by anyone in `records`.
+    pub fn build(records: &[SpawnRecord]) -> Self {
+        let children: HashSet<&IdentityId> = records.iter().map(|r| &r.child_id).collect();
+        let mut by_age: Vec<&SpawnRecord> = records.iter().collect();
+        by_age.sort_by_key(|r| r.spawn_timestamp);
+
+        let mut seen = HashSet::new();
+        let mut roots = Vec::new();
+        for record in by_age {
+            if !children.contains(&record.parent_id) && !seen.contains(&record.parent_id) {
+                roots.push(node(&record.parent_id, records, &mut seen));
+            }
+        }
+        Self { roots }
+    }
+
+    /// The lineage below `identity`, which need not be a root.
+    pub fn rooted_at(identity: &IdentityId, records: &[SpawnRecord]) -> Self {
+        Self {
+            roots: vec![node(identity, records, &mut HashSet::new())],
+        }
+    }
+
+    /// Every lineage in `store`. Unreadable records are skipped, as in
+    /// [`SpawnStore::load_all`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the store directory cannot be read.
+    pub fn from_store(store: &SpawnStore) -> Result<Self> {
+        Ok(Self::build(&store.load_all()?))
+    }
+
+    /// Number of identities in the tree.
+    pub fn len(&self) -> usize {
+        fn count(node: &SpawnTreeNode) -> usize {
+            1 + node.children.iter().map(count).sum::<usize>()
+        }
+        self.roots.iter().map(count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// The node for `identity`, searching depth-first.
+    pub fn find(&self, identity: &IdentityId) -> Option<&SpawnTreeNode> {
+        fn search<'a>(node: &'a SpawnTreeNode, identity: &IdentityId) -> Option<&'a SpawnTreeNode> {
+            if node.identity == *identity {
+                return Some(node);
+            }
+            node.children.iter().find_map(|c| search(c, identity))
+        }
+        self.roots.iter().find_map(|root| search(root, identity))
+    }
+
+    /// An indented outline, one identity per line:
+    ///
+    /// ```text
+    /// aid_root (root) — *
+    /// ├── aid_worker [worker] index docs — docs:read
+    /// │   └── aid_helper [delegate] fetch — none (terminated)
+    /// └── aid_other [clone] backup — docs:*
+    /// ```
+    pub fn render_text(&self) -> String {
+        fn walk(node: &SpawnTreeNode, prefix: &str, out: &mut String) {
+            let count = node.children.len();
+            for (i, child) in node.children.iter().enumerate() {
+                let last = i + 1 == count;
+                out.push_str(prefix);
+                out.push_str(if last { "└── " } else { "├── " });
+                out.push_str(&line(child));
+                out.push('\n');
+                let deeper = format!("{prefix}{}", if last { "    " } else { "│   " });
+                walk(child, &deeper, out);
+            }
+        }
+        let mut out = String::new();
+        for root in &self.roots {
+            out.push_str(&line(root));
+            out.push('\n');
+            walk(root, "", &mut out);
+        }
+        out
+    }
+
+    /// A Mermaid flowchart, drawn top down. Nodes are named `n0`, `n1`, …
+    /// depth-first; edges are labeled with the spawn type, and terminated
+    /// or inactive identities are dashed.
+    pub fn to_mermaid(&self) -> String {
+        fn walk(
+            node: &SpawnTreeNode,
+            name: String,
+            next: &mut usize,
+            out: &mut String,
+            inactive: &mut Vec<String>,
+        ) {
+            let mut label = short_id(&node.identity.0);
+            if let Some(purpose) = &node.purpose {
+                label.push_str(&format!("\n{purpose}"));
+            }
+            label.push_str(&format!("\n{}", authority_label(node)));
+            out.push_str(&format!("    {name}[\"{}\"]\n", mermaid_escape(&label)));
+            if !node.active {
+                inactive.push(name.clone());
+            }
+            for child in &node.children {
+                let child_name = format!("n{next}");
+                *next += 1;
+                let tag = child.spawn_type.as_ref().map_or("spawn", |t| t.as_tag());
+                out.push_str(&format!(
+                    "    {name} -->|{}| {child_name}\n",
+                    mermaid_escape(tag)
+                ));
+                walk(child, child_name, next, out, inactive);
+            }
+        }
+        let mut out = String::from("flowchart TD\n");
+        let mut next = 0;
+        let mut inactive = Vec::new();
+        for root in &self.roots {
+            let name = format!("n{next}");
+            next += 1;
+            walk(root, name, &mut next, &mut out, &mut inactive);
+        }
+        if !inactive.is_empty() {
+            out.push_str("    classDef inactive stroke-dasharray: 5 5\n");
+            out.push_str(&format!("    class {} inactive\n", inactive.join(",")));
+        }
+        out
+    }
+}
+
+/// The node for `identity` and everything spawned below it. `seen` stops a
+/// cycle in corrupt records from recursing forever: an identity already in
+/// the tree gets no children the second time.
+fn node(
+    identity: &IdentityId,
+    records: &[SpawnRecord],
+    seen: &mut HashSet<IdentityId>,
+) -> SpawnTreeNode {
+    let first_visit = seen.insert(identity.clone());
+    // authority_for never fails; it returns a root's authority when
+    // `identity` has no spawn record.
+    let authority = authority_for(identity, records).ok();
+    let record = records.iter().find(|r| r.child_id == *identity);
+
+    let mut spawned: Vec<&SpawnRecord> = if first_visit {
+        records
+            .iter()
+            .filter(|r| r.parent_id == *identity)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    spawned.sort_by_key(|r| r.spawn_timestamp);
+
+    SpawnTreeNode {
+        identity: identity.clone(),
+        spawn_id: record.map(|r| r.id.clone()),
+        spawn_type: record.map(|r| r.spawn_type.clone()),
+        purpose: record.map(|r| r.spawn_purpose.clone()),
+        spawned_at: record.map(|r| r.spawn_timestamp),
+        depth: authority.as_ref().map_or(0, |a| a.spawn_depth),
+        terminated: record.is_some_and(|r| r.terminated),
+        active: authority.as_ref().is_some_and(|a| a.active),
+        effective_authority: authority.map(|a| a.effective_authority).unwrap_or_default(),
+        children: spawned
+            .into_iter()
+            .map(|r| node(&r.child_id, records, seen))
+            .collect(),
+    }
+}
+
+/// One outline line: identity, spawn type and purpose, authority, status.
+fn line(node: &SpawnTreeNode) -> String {
+    let mut out = node.identity.0.clone();
+    match (&node.spawn_type, &node.purpose) {
+        (Some(spawn_type), Some(purpose)) => {
+            out.push_str(&format!(" [{}] {purpose}", spawn_type.as_tag()));
+        }
+        _ => out.push_str(" (root)"),
+    }
+    out.push_str(&format!(" — {}", authority_label(node)));
+    if node.terminated {
+        out.push_str(" (terminated)");
+    } else if !node.active {
+        out.push_str(" (inactive)");
+    }
+    out
+}
+
+fn authority_label(node: &SpawnTreeNode) -> String {
+    if node.effective_authority.is_empty() {
+        return "none".to_string();
+    }
+    node.effective_authority
+        .iter()
+        .map(|c| c.uri.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::spawn::engine::spawn_child;
+    use crate::spawn::types::{SpawnConstraints, SpawnLifetime};
+
+    fn spawn(
+        parent: &IdentityAnchor,
+        spawn_type: SpawnType,
+        purpose: &str,
+        granted: &str,
+    ) -> (IdentityAnchor, SpawnRecord) {
+        let (child, record, _) = spawn_child(
+            parent,
+            spawn_type,
+            purpose,
+            vec![Capability::new(granted)],
+            vec![Capability::new(granted)],
+            SpawnLifetime::Indefinite,
+            SpawnConstraints::default(),
+            None,
+            &[],
+        )
+        .unwrap();
+        (child, record)
+    }
+
+    /// root → worker → helper, and root → clone.
+    fn hierarchy() -> (IdentityAnchor, Vec<IdentityAnchor>, Vec<SpawnRecord>) {
+        let root = IdentityAnchor::new(Some("root".into()));
+        let (worker, r1) = spawn(&root, SpawnType::Worker, "index docs", "docs:*");
+        let (helper, r2) = spawn(&worker, SpawnType::Delegate, "fetch", "docs:read");
+        let (clone, r3) = spawn(&root, SpawnType::Clone, "backup", "backup:*");
+        (root, vec![worker, helper, clone], vec![r1, r2, r3])
+    }
+
+    #[test]
+    fn test_build_nests_lineages() {
+        let (root, ids, records) = hierarchy();
+        let tree = SpawnTree::build(&records);
+
+        assert_eq!(tree.roots.len(), 1);
+        assert_eq!(tree.len(), 4);
+        let top = &tree.roots[0];
+        assert_eq!(top.identity, root.id());
+        assert_eq!(top.depth, 0);
+        assert!(top.spawn_id.is_none());
+        assert_eq!(top.children.len(), 2);
+
+        let helper = tree.find(&ids[1].id()).unwrap();
+        assert_eq!(helper.depth, 2);
+        assert_eq!(helper.spawn_type, Some(SpawnType::Delegate));
+        assert_eq!(helper.effective_authority[0].uri, "docs:read");
+    }
+
+    #[test]
+    fn test_authority_follows_termination() {
+        let (_, ids, mut records) = hierarchy();
+        records[0].terminated = true;
+        let tree = SpawnTree::build(&records);
+
+        let worker = tree.find(&ids[0].id()).unwrap();
+        assert!(worker.terminated);
+        assert!(!worker.active);
+        // The helper's own record is live, but its parent's is not.
+        let helper = tree.find(&ids[1].id()).unwrap();
+        assert!(!helper.terminated);
+        assert!(!helper.active);
+        assert!(helper.effective_authority.is_empty());
+        assert!(tree.find(&ids[2].id()).unwrap().active);
+    }
+
+    #[test]
+    fn test_rooted_at_child() {
+        let (_, ids, records) = hierarchy();
+        let tree = SpawnTree::rooted_at(&ids[0].id(), &records);
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.roots[0].depth, 1);
+        assert_eq!(tree.roots[0].purpose.as_deref(), Some("index docs"));
+    }
+
+    #[test]
+    fn test_render_text_and_mermaid() {
+        let (root, ids, mut records) = hierarchy();
+        records[1].terminated = true;
+        let tree = SpawnTree::build(&records);
+
+        let text = tree.render_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], format!("{} (root) — *", root.id()));
+        assert!(lines[1].starts_with(&format!("├── {} [worker] index docs", ids[0].id())));
+        assert!(lines[2].starts_with("│   └── "));
+        assert!(lines[2].ends_with("— none (terminated)"));
+        assert!(lines[3].starts_with(&format!("└── {} [clone] backup", ids[2].id())));
+
+        let mermaid = tree.to_mermaid();
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("n0 -->|worker| n1"));
+        assert!(mermaid.contains("n1 -->|delegate| n2"));
+        assert!(mermaid.contains("n0 -->|clone| n3"));
+        assert!(mermaid.contains("class n2 inactive"));
+    }
+
+    #[test]
+    fn test_cycle_terminates() {
+        let (_, _, mut records) = hierarchy();
+        // Corrupt: the helper claims to have spawned its own grandparent.
+        records[0].parent_id = records[1].child_id.clone();
+        let tree = SpawnTree::rooted_at(&records[0].child_id, &records);
+        assert!(tree.len() <= records.len() + 1);
+    }
+
+    #[test]
+    fn test_empty_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let tree = SpawnTree::from_store(&store).unwrap();
+        assert!(tree.is_empty());
+        assert_eq!(tree.render_text(), "");
+    }
+}
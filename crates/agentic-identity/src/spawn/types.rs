@@ -14,6 +14,14 @@ use crate::trust::Capability;
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct SpawnId(pub String);
 
+impl SpawnId {
+    /// Parse a spawn ID from untrusted input, checking its prefix and charset.
+    pub fn parse(s: &str) -> crate::error::Result<Self> {
+        crate::identity::anchor::validate_prefixed_id(s, "aspawn_")?;
+        Ok(Self(s.to_string()))
+    }
+}
+
 impl std::fmt::Display for SpawnId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
@@ -38,9 +46,27 @@ pub struct SpawnRecord {
     pub constraints: SpawnConstraints,
     pub parent_signature: String,
     pub child_acknowledgment: Option<String>,
+    /// Signature scheme of `parent_signature` and `child_acknowledgment`;
+    /// absent on records signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    /// Layout version; see [`migrate`](crate::storage::migrate). Not signed.
+    #[serde(default)]
+    pub schema_version: u32,
     pub terminated: bool,
     pub terminated_at: Option<u64>,
     pub termination_reason: Option<String>,
+    /// File the host saved the child identity to, relative to its identity
+    /// directory. Bookkeeping only: not covered by the parent's signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub child_file: Option<String>,
+    /// Resources metered against the budgets in `constraints`. Bookkeeping
+    /// only: not covered by the parent's signature.
+    #[serde(default, skip_serializing_if = "SpawnConsumption::is_zero")]
+    pub consumption: SpawnConsumption,
 }
 
 // ---------------------------------------------------------------------------
@@ -108,7 +134,11 @@ impl SpawnLifetime {
 
     /// Check if the lifetime has expired.
     pub fn is_expired(&self, spawn_timestamp: u64) -> bool {
-        let now = crate::time::now_micros();
+        self.is_expired_at(spawn_timestamp, crate::time::now_micros())
+    }
+
+    /// Check if the lifetime had expired at `now` (microseconds since epoch).
+    pub fn is_expired_at(&self, spawn_timestamp: u64, now: u64) -> bool {
         match self {
             Self::Indefinite => false,
             Self::Duration { seconds } => now > spawn_timestamp + (seconds * 1_000_000),
@@ -137,6 +167,35 @@ pub struct SpawnConstraints {
     /// Authority decay factor per generation (None = no decay).
     /// Value between 0.0 and 1.0 — multiplied against parent authority.
     pub authority_decay: Option<f32>,
+    /// Maximum number of receipts the child may sign (None = unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_receipts: Option<u64>,
+    /// Maximum number of spawns the child may make over its lifetime,
+    /// terminated ones included (None = unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_sub_spawns: Option<u64>,
+    /// Maximum cost the child may report, in whatever unit the host meters
+    /// (tokens, cents, …) (None = unlimited).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<u64>,
+}
+
+impl SpawnConstraints {
+    /// The budget for `resource`, if any.
+    pub fn budget(&self, resource: BudgetResource) -> Option<u64> {
+        match resource {
+            BudgetResource::Receipts => self.max_receipts,
+            BudgetResource::SubSpawns => self.max_sub_spawns,
+            BudgetResource::Cost => self.max_cost,
+        }
+    }
+
+    /// Does any resource have a budget?
+    pub fn has_budget(&self) -> bool {
+        BudgetResource::ALL
+            .iter()
+            .any(|r| self.budget(*r).is_some())
+    }
 }
 
 impl Default for SpawnConstraints {
@@ -147,10 +206,74 @@ impl Default for SpawnConstraints {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Spawn Budgets
+// ---------------------------------------------------------------------------
+
+/// A resource metered against a spawn's budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetResource {
+    /// Receipts signed by the child.
+    Receipts,
+    /// Spawns made by the child.
+    SubSpawns,
+    /// Host-defined cost, such as tokens.
+    Cost,
+}
+
+impl BudgetResource {
+    pub const ALL: [BudgetResource; 3] = [Self::Receipts, Self::SubSpawns, Self::Cost];
+
+    /// Return a stable string tag.
+    pub fn as_tag(&self) -> &str {
+        match self {
+            Self::Receipts => "receipts",
+            Self::SubSpawns => "sub_spawns",
+            Self::Cost => "cost",
         }
     }
 }
 
+/// What a spawned identity has used so far, per budgeted resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpawnConsumption {
+    pub receipts: u64,
+    pub sub_spawns: u64,
+    pub cost: u64,
+}
+
+impl SpawnConsumption {
+    /// Amount of `resource` used.
+    pub fn used(&self, resource: BudgetResource) -> u64 {
+        match resource {
+            BudgetResource::Receipts => self.receipts,
+            BudgetResource::SubSpawns => self.sub_spawns,
+            BudgetResource::Cost => self.cost,
+        }
+    }
+
+    pub(crate) fn set(&mut self, resource: BudgetResource, used: u64) {
+        match resource {
+            BudgetResource::Receipts => self.receipts = used,
+            BudgetResource::SubSpawns => self.sub_spawns = used,
+            BudgetResource::Cost => self.cost = used,
+        }
+    }
+
+    /// Nothing used yet.
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Spawn Info (attached to spawned identity)
 // ---------------------------------------------------------------------------
@@ -195,3 +318,23 @@ pub struct LineageVerification {
     pub verified_at: u64,
     pub errors: Vec<String>,
 }
+
+/// Authority of an identity derived purely from spawn records.
+///
+/// Computed without loading any identity file, so it works for children
+/// whose `.aid` file is absent.
+#[derive(Debug, Clone)]
+pub struct SpawnAuthority {
+    pub identity: IdentityId,
+    /// Spawn record that created this identity (`None` for a root).
+    pub spawn_id: Option<SpawnId>,
+    pub parent_id: Option<IdentityId>,
+    pub is_root: bool,
+    pub spawn_depth: u32,
+    /// Is this spawn and every ancestor spawn still active (not terminated
+    /// or expired)?
+    pub active: bool,
+    /// Granted authority, bounded by every ancestor's grant. Empty if inactive.
+    pub effective_authority: Vec<Capability>,
+    pub authority_ceiling: Vec<Capability>,
+}
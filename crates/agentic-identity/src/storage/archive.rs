@@ -0,0 +1,412 @@
+//! Receipt compaction — folding old receipts into signed archive bundles.
+//!
+//! [`ReceiptStore::compact`](super::ReceiptStore::compact) moves every
+//! receipt older than a cutoff into one [`ReceiptArchive`] and frees their
+//! individual files. The archive is committed to by a root receipt: an
+//! ordinary receipt of type [`ARCHIVE_ACTION`], signed by the compacting
+//! identity, whose data ([`ArchiveSummary`]) carries the Merkle root (see
+//! [`crate::receipt::merkle`]) of the archived receipts. The root receipt
+//! is also saved in the store, so the proof outlives the bundle file.
+//!
+//! Archived receipts keep their own signatures. A single one is verified
+//! against the root receipt with [`verify_archived`] and an inclusion proof,
+//! without the rest of the archive; [`ReceiptArchive::verify`] checks a
+//! whole bundle. [`ReceiptStore::load`](super::ReceiptStore::load) still
+//! finds an archived receipt, through `archives/index.json`, so chains
+//! through archived receipts keep resolving.
+//!
+//! ```text
+//! {receipt_dir}/archives/
+//! ├── index.json              (receipt ID → archive ID)
+//! └── {root_receipt_id}.json  ({ "version": 1, "archive": { ... } })
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "signing")]
+use crate::crypto::canonical::MAX_SAFE_INTEGER;
+#[cfg(feature = "signing")]
+use crate::crypto::signer::Signer;
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityId;
+#[cfg(feature = "signing")]
+use crate::receipt::action::ActionContent;
+use crate::receipt::action::ActionType;
+use crate::receipt::merkle::{merkle_proof, merkle_root, verify_inclusion};
+#[cfg(feature = "signing")]
+use crate::receipt::receipt::ReceiptBuilder;
+use crate::receipt::verify::verify_receipt;
+use crate::receipt::{ActionReceipt, InclusionProof, ReceiptId};
+
+#[cfg(feature = "signing")]
+use super::atomic::write_atomic;
+
+/// Action type of the root receipt committing to an archive.
+pub const ARCHIVE_ACTION: &str = "receipt_archive";
+
+const ARCHIVE_FILE_VERSION: u32 = 1;
+
+/// Sub-directory of the receipt store holding archives.
+pub(super) const ARCHIVES_DIR: &str = "archives";
+
+const INDEX_FILE: &str = "index.json";
+
+/// What a root receipt attests about its archive, stored as its data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    /// Hex-encoded Merkle root over the archived receipts, in archive order.
+    pub merkle_root: String,
+    /// Number of archived receipts.
+    pub count: u64,
+    /// Receipts older than this were archived (microseconds since epoch).
+    pub cutoff: u64,
+    /// Timestamp of the oldest archived receipt.
+    pub oldest: u64,
+    /// Timestamp of the newest archived receipt.
+    pub newest: u64,
+}
+
+/// Receipts compacted into one bundle, with the receipt that commits to them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptArchive {
+    /// The signed [`ARCHIVE_ACTION`] receipt; its ID is the archive's ID.
+    pub root_receipt: ActionReceipt,
+    /// The archived receipts, oldest first. This is Merkle tree order.
+    pub receipts: Vec<ActionReceipt>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveFile {
+    version: u32,
+    archive: ReceiptArchive,
+}
+
+impl ReceiptArchive {
+    /// Bundle `receipts` (at least one) and sign a root receipt over them
+    /// with `signer`. Receipts are put in timestamp order first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` for no receipts, or for a
+    /// `cutoff` or receipt timestamp above 2^53, which the signed summary
+    /// cannot hold exactly; and any error from signing the root receipt.
+    #[cfg(feature = "signing")]
+    pub fn seal<S: Signer + ?Sized>(
+        signer: &S,
+        mut receipts: Vec<ActionReceipt>,
+        cutoff: u64,
+    ) -> Result<Self> {
+        if cutoff > MAX_SAFE_INTEGER {
+            return Err(IdentityError::InvalidInput(format!(
+                "archive cutoff {cutoff} exceeds 2^53 and cannot be signed exactly"
+            )));
+        }
+        receipts.sort_by(|a, b| {
+            a.timestamp
+                .cmp(&b.timestamp)
+                .then_with(|| a.id.0.cmp(&b.id.0))
+        });
+        let (Some(oldest), Some(newest)) = (receipts.first(), receipts.last()) else {
+            return Err(IdentityError::InvalidInput(
+                "an archive needs at least one receipt".into(),
+            ));
+        };
+        if newest.timestamp > MAX_SAFE_INTEGER {
+            return Err(IdentityError::InvalidInput(format!(
+                "receipt {} has timestamp {} beyond 2^53 and cannot be archived",
+                newest.id, newest.timestamp
+            )));
+        }
+        let summary = ArchiveSummary {
+            merkle_root: hex::encode(merkle_root(&receipts)),
+            count: receipts.len() as u64,
+            cutoff,
+            oldest: oldest.timestamp,
+            newest: newest.timestamp,
+        };
+        let data = serde_json::to_value(&summary)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        let root_receipt = ReceiptBuilder::new(
+            IdentityId::from_verifying_key(&signer.verifying_key()),
+            ActionType::Custom(ARCHIVE_ACTION.into()),
+            ActionContent::with_data(
+                format!("Archived {} receipts older than {cutoff}", receipts.len()),
+                data,
+            ),
+        )
+        .sign(signer)?;
+        Ok(Self {
+            root_receipt,
+            receipts,
+        })
+    }
+
+    /// The archive's ID: its root receipt's.
+    pub fn id(&self) -> &ReceiptId {
+        &self.root_receipt.id
+    }
+
+    /// What the root receipt attests.
+    ///
+    /// # Errors
+    ///
+    /// As for [`archive_summary`].
+    pub fn summary(&self) -> Result<ArchiveSummary> {
+        archive_summary(&self.root_receipt)
+    }
+
+    /// The archived receipt with ID `id`, if any.
+    pub fn get(&self, id: &ReceiptId) -> Option<&ActionReceipt> {
+        self.receipts.iter().find(|r| &r.id == id)
+    }
+
+    /// Proof that the receipt with ID `id` is in this archive.
+    pub fn proof_for(&self, id: &ReceiptId) -> Option<InclusionProof> {
+        let index = self.receipts.iter().position(|r| &r.id == id)?;
+        Some(InclusionProof {
+            receipt_id: id.clone(),
+            index: index as u64,
+            steps: merkle_proof(&self.receipts, index)?,
+        })
+    }
+
+    /// Verify the whole archive: the root receipt's signature, that its
+    /// summary matches the archived receipts, and every archived receipt's
+    /// own signature.
+    ///
+    /// # Errors
+    ///
+    /// As for [`archive_summary`]; `IdentityError::SignatureInvalid` if the
+    /// root receipt or an archived receipt does not verify, and
+    /// `IdentityError::InvalidChain` if the receipts are not the ones the
+    /// root receipt commits to.
+    pub fn verify(&self) -> Result<()> {
+        let summary = verified_summary(&self.root_receipt)?;
+        if summary.count != self.receipts.len() as u64
+            || summary.merkle_root != hex::encode(merkle_root(&self.receipts))
+        {
+            return Err(IdentityError::InvalidChain);
+        }
+        for receipt in &self.receipts {
+            if !verify_receipt(receipt)?.is_valid {
+                return Err(IdentityError::SignatureInvalid);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The [`ArchiveSummary`] carried by a root receipt.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidInput` if `root_receipt` is not an
+/// [`ARCHIVE_ACTION`] receipt, or its data is not a summary.
+pub fn archive_summary(root_receipt: &ActionReceipt) -> Result<ArchiveSummary> {
+    if root_receipt.action_type != ActionType::Custom(ARCHIVE_ACTION.into()) {
+        return Err(IdentityError::InvalidInput(format!(
+            "receipt {} is not an archive root",
+            root_receipt.id
+        )));
+    }
+    let data = root_receipt.action.data.clone().unwrap_or_default();
+    serde_json::from_value(data).map_err(|e| {
+        IdentityError::InvalidInput(format!(
+            "archive root {} has no summary: {e}",
+            root_receipt.id
+        ))
+    })
+}
+
+/// Verify one archived receipt against its archive's root receipt, without
+/// the rest of the archive.
+///
+/// Checks both receipts' signatures and that `proof` leads from `receipt`
+/// to the root receipt's Merkle root.
+///
+/// # Errors
+///
+/// As for [`archive_summary`]; `IdentityError::SignatureInvalid` if either
+/// receipt does not verify, `IdentityError::InvalidInput` if the proof is
+/// for another receipt, and `IdentityError::InvalidChain` if the proof does
+/// not reach the root.
+pub fn verify_archived(
+    receipt: &ActionReceipt,
+    proof: &InclusionProof,
+    root_receipt: &ActionReceipt,
+) -> Result<()> {
+    let summary = verified_summary(root_receipt)?;
+    if !verify_receipt(receipt)?.is_valid {
+        return Err(IdentityError::SignatureInvalid);
+    }
+    if proof.receipt_id != receipt.id {
+        return Err(IdentityError::InvalidInput(format!(
+            "proof is for {}, not {}",
+            proof.receipt_id, receipt.id
+        )));
+    }
+    if !verify_inclusion(receipt, &proof.steps, &summary.merkle_root) {
+        return Err(IdentityError::InvalidChain);
+    }
+    Ok(())
+}
+
+fn verified_summary(root_receipt: &ActionReceipt) -> Result<ArchiveSummary> {
+    let summary = archive_summary(root_receipt)?;
+    if !verify_receipt(root_receipt)?.is_valid {
+        return Err(IdentityError::SignatureInvalid);
+    }
+    Ok(summary)
+}
+
+// ── Files ─────────────────────────────────────────────────────────────────────
+
+/// `{dir}/{archive_id}.json`.
+pub(super) fn archive_path(dir: &Path, id: &ReceiptId) -> PathBuf {
+    dir.join(format!("{}.json", id.0))
+}
+
+/// Write `archive` into `dir`; the caller holds the store lock.
+#[cfg(feature = "signing")]
+pub(super) fn write_archive(dir: &Path, archive: &ReceiptArchive) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let file = ArchiveFile {
+        version: ARCHIVE_FILE_VERSION,
+        archive: archive.clone(),
+    };
+    let json = serde_json::to_string_pretty(&file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    write_atomic(&archive_path(dir, archive.id()), json.as_bytes())
+}
+
+/// Read the archive with ID `id` from `dir`, if there is one.
+pub(super) fn read_archive(dir: &Path, id: &ReceiptId) -> Result<Option<ReceiptArchive>> {
+    let path = archive_path(dir, id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path)?;
+    let file: ArchiveFile = serde_json::from_slice(&bytes).map_err(|e| {
+        IdentityError::InvalidFileFormat(format!(
+            "failed to parse receipt archive {}: {e}",
+            path.display()
+        ))
+    })?;
+    if file.version > ARCHIVE_FILE_VERSION {
+        return Err(IdentityError::UnsupportedVersion {
+            kind: "receipt archive".into(),
+            found: file.version,
+            supported: ARCHIVE_FILE_VERSION,
+        });
+    }
+    Ok(Some(file.archive))
+}
+
+/// Which archive holds each archived receipt, by ID.
+pub(super) fn read_index(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let path = dir.join(INDEX_FILE);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let bytes = std::fs::read(&path)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        IdentityError::InvalidFileFormat(format!(
+            "failed to parse archive index {}: {e}",
+            path.display()
+        ))
+    })
+}
+
+/// Replace the archive index; the caller holds the store lock.
+#[cfg(feature = "signing")]
+pub(super) fn write_index(dir: &Path, index: &BTreeMap<String, String>) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let json = serde_json::to_string_pretty(index)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    write_atomic(&dir.join(INDEX_FILE), json.as_bytes())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::ActionContent;
+
+    fn sealed(anchor: &IdentityAnchor, len: usize) -> ReceiptArchive {
+        let receipts = (0..len)
+            .map(|i| {
+                ReceiptBuilder::new(
+                    anchor.id(),
+                    ActionType::Decision,
+                    ActionContent::new(format!("decision {i}")),
+                )
+                .sign(anchor.signing_key())
+                .unwrap()
+            })
+            .collect();
+        ReceiptArchive::seal(anchor.signing_key(), receipts, crate::time::now_micros()).unwrap()
+    }
+
+    #[test]
+    fn test_sealed_archive_verifies_and_proves_each_receipt() {
+        let anchor = IdentityAnchor::new(None);
+        let archive = sealed(&anchor, 5);
+        archive.verify().unwrap();
+
+        let summary = archive.summary().unwrap();
+        assert_eq!(summary.count, 5);
+        assert!(summary.oldest <= summary.newest);
+        for receipt in &archive.receipts {
+            let proof = archive.proof_for(&receipt.id).unwrap();
+            verify_archived(receipt, &proof, &archive.root_receipt).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_tampered_archive_fails_verification() {
+        let anchor = IdentityAnchor::new(None);
+        let mut archive = sealed(&anchor, 3);
+        archive.receipts.pop();
+        assert!(matches!(archive.verify(), Err(IdentityError::InvalidChain)));
+
+        let other = sealed(&anchor, 2);
+        let proof = other.proof_for(&other.receipts[0].id).unwrap();
+        assert!(matches!(
+            verify_archived(&other.receipts[0], &proof, &archive.root_receipt),
+            Err(IdentityError::InvalidChain)
+        ));
+        assert!(archive_summary(&other.receipts[0]).is_err());
+    }
+
+    #[test]
+    fn test_archive_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let archive = sealed(&anchor, 2);
+        write_archive(dir.path(), &archive).unwrap();
+
+        let loaded = read_archive(dir.path(), archive.id()).unwrap().unwrap();
+        loaded.verify().unwrap();
+        assert_eq!(loaded.receipts.len(), 2);
+        assert!(read_archive(dir.path(), &archive.receipts[0].id)
+            .unwrap()
+            .is_none());
+        assert!(ReceiptArchive::seal(anchor.signing_key(), Vec::new(), 0).is_err());
+    }
+
+    #[test]
+    fn test_seal_rejects_cutoff_beyond_2_pow_53() {
+        let anchor = IdentityAnchor::new(None);
+        let receipts = sealed(&anchor, 1).receipts;
+        match ReceiptArchive::seal(anchor.signing_key(), receipts, u64::MAX) {
+            Err(IdentityError::InvalidInput(msg)) => assert!(msg.contains("cutoff")),
+            other => panic!("expected InvalidInput, got {other:?}"),
+        }
+    }
+}
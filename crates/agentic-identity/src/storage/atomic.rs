@@ -0,0 +1,267 @@
+//! Crash-safe writes, and recovery from writes a crash interrupted.
+//!
+//! [`write_atomic`] writes a file's new contents to a sibling
+//! `{name}.{ext}.tmp`, flushes it to disk, then renames it over the file
+//! and flushes the directory. A crash at any point leaves either the old
+//! contents or the new, never a truncated mix.
+//!
+//! Files written before this existed, or by another tool, can still be
+//! partial. [`recover_dir`] scans a store directory at startup and moves
+//! every partial file into `{dir}/quarantine/`: a leftover `.tmp` whose
+//! rename never happened, or a record that is not even well-formed JSON. A
+//! quarantined file is out of the store's listings but kept for inspection.
+//! Records that parse but fail to load for other reasons are left alone;
+//! they are the stores' and [`super::migrate`]'s business.
+//!
+//! ```text
+//! {dir}/
+//! ├── {id}.json
+//! └── quarantine/
+//!     └── {id}.json       (or {id}.json.tmp, with a numeric suffix on a clash)
+//! ```
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::Result;
+
+use super::lock::StoreLock;
+
+/// Sub-directory partial files are moved into.
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// What a recovery scan found.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QuarantineReport {
+    /// Files examined, temporary files included.
+    pub checked: usize,
+    /// Each partial file's original path, with why it was quarantined.
+    pub quarantined: Vec<(PathBuf, String)>,
+}
+
+impl QuarantineReport {
+    /// Fold another scan's findings into this one.
+    pub fn merge(&mut self, other: QuarantineReport) {
+        self.checked += other.checked;
+        self.quarantined.extend(other.quarantined);
+    }
+
+    /// Whether nothing was quarantined.
+    pub fn is_clean(&self) -> bool {
+        self.quarantined.is_empty()
+    }
+}
+
+/// Replace `path` with `data` so that a crash never leaves it partial.
+///
+/// # Errors
+///
+/// Returns `IdentityError::Io` for filesystem errors, in which case `path`
+/// is unchanged.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let tmp_path = tmp_path(path);
+    let mut file = std::fs::File::create(&tmp_path)?;
+    let written = file.write_all(data).and_then(|()| file.sync_all());
+    drop(file);
+    if let Err(e) = written.and_then(|()| std::fs::rename(&tmp_path, path)) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+    sync_parent(path)
+}
+
+/// Flush the directory holding `path`, so a rename or removal of `path`
+/// survives a crash.
+///
+/// # Errors
+///
+/// Returns `IdentityError::Io` if the directory cannot be flushed.
+pub fn sync_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => sync_dir(dir),
+        _ => sync_dir(Path::new(".")),
+    }
+}
+
+/// Flush the directory `dir` itself. Only Unix can open a directory for
+/// this; elsewhere a rename is durable once it returns.
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<()> {
+    std::fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Quarantine the partial `*.{extension}` files in `dir`, and the leftover
+/// temporary files of [`write_atomic`].
+///
+/// The scan holds the directory's [`StoreLock`], and each file's own lock
+/// while it is examined, so no write in progress is mistaken for a partial
+/// one. A missing `dir` has nothing to recover.
+///
+/// # Errors
+///
+/// Returns `IdentityError::StoreLocked` if a writer keeps the directory
+/// locked, or `IdentityError::Io` for filesystem errors.
+pub fn recover_dir(dir: &Path, extension: &str) -> Result<QuarantineReport> {
+    if !dir.is_dir() {
+        return Ok(QuarantineReport::default());
+    }
+    let _lock = StoreLock::dir(dir)?;
+    recover_locked(dir, extension)
+}
+
+/// [`recover_dir`] for a caller already holding the lock of the store
+/// `dir` belongs to.
+pub(super) fn recover_locked(dir: &Path, extension: &str) -> Result<QuarantineReport> {
+    scan(dir, extension, true)
+}
+
+/// Quarantine only the leftover temporary files in `dir`, for records
+/// whose mere presence means something and which must stay put even when
+/// partial. The caller holds the store lock.
+pub(super) fn recover_interrupted_locked(dir: &Path, extension: &str) -> Result<QuarantineReport> {
+    scan(dir, extension, false)
+}
+
+/// Quarantine the temporary files in `dir`, and with `check_records` the
+/// malformed records too.
+fn scan(dir: &Path, extension: &str, check_records: bool) -> Result<QuarantineReport> {
+    let mut report = QuarantineReport::default();
+    if !dir.is_dir() {
+        return Ok(report);
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|p| p.is_file());
+    paths.sort();
+
+    let suffix = format!(".{extension}");
+    for path in paths {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let interrupted = match name.strip_suffix(".tmp") {
+            Some(target) if target.ends_with(&suffix) => Some(dir.join(target)),
+            Some(_) => continue,
+            None if check_records && name.ends_with(&suffix) => None,
+            None => continue,
+        };
+        report.checked += 1;
+
+        // Identity files are written under their own lock, not the
+        // directory's.
+        let _lock = StoreLock::file(interrupted.as_deref().unwrap_or(&path))?;
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let reason = if interrupted.is_some() {
+            "interrupted write (temporary file never renamed)".to_string()
+        } else {
+            match serde_json::from_slice::<serde_json::Value>(&bytes) {
+                Ok(_) => continue,
+                Err(_) if bytes.is_empty() => "empty file".to_string(),
+                Err(e) => format!("not valid JSON: {e}"),
+            }
+        };
+
+        quarantine(dir, &path)?;
+        report.quarantined.push((path, reason));
+    }
+    Ok(report)
+}
+
+/// The temporary sibling [`write_atomic`] writes `path` through.
+pub(super) fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// Move `path` into `{dir}/quarantine/`, never overwriting an earlier
+/// quarantined file.
+fn quarantine(dir: &Path, path: &Path) -> Result<()> {
+    let quarantine_dir = dir.join(QUARANTINE_DIR);
+    std::fs::create_dir_all(&quarantine_dir)?;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut destination = quarantine_dir.join(name.as_ref());
+    let mut n = 1;
+    while destination.exists() {
+        destination = quarantine_dir.join(format!("{name}.{n}"));
+        n += 1;
+    }
+    std::fs::rename(path, destination)?;
+    Ok(())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_and_leaves_no_tmp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("record.json");
+        write_atomic(&path, b"{\"v\":1}").unwrap();
+        write_atomic(&path, b"{\"v\":2}").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"{\"v\":2}");
+        assert!(!dir.path().join("record.json.tmp").exists());
+    }
+
+    #[test]
+    fn test_recover_dir_quarantines_partial_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("good.json"), b"{\"ok\":true}").unwrap();
+        std::fs::write(dir.path().join("truncated.json"), b"{\"ok\":tr").unwrap();
+        std::fs::write(dir.path().join("empty.json"), b"").unwrap();
+        std::fs::write(dir.path().join("good.json.tmp"), b"{\"ok\":fal").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), b"not a record").unwrap();
+
+        let report = recover_dir(dir.path(), "json").unwrap();
+        assert_eq!(report.checked, 4);
+        let mut moved: Vec<_> = report
+            .quarantined
+            .iter()
+            .map(|(p, _)| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        moved.sort();
+        assert_eq!(moved, ["empty.json", "good.json.tmp", "truncated.json"]);
+
+        let quarantine = dir.path().join(QUARANTINE_DIR);
+        assert!(quarantine.join("truncated.json").exists());
+        assert!(dir.path().join("good.json").exists());
+        assert!(dir.path().join("notes.txt").exists());
+        assert!(recover_dir(dir.path(), "json").unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_recover_dir_never_overwrites_quarantine() {
+        let dir = tempfile::tempdir().unwrap();
+        for _ in 0..2 {
+            std::fs::write(dir.path().join("bad.json"), b"{").unwrap();
+            recover_dir(dir.path(), "json").unwrap();
+        }
+        let quarantine = dir.path().join(QUARANTINE_DIR);
+        assert!(quarantine.join("bad.json").exists());
+        assert!(quarantine.join("bad.json.1").exists());
+    }
+
+    #[test]
+    fn test_recover_missing_dir_is_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = recover_dir(&dir.path().join("absent"), "json").unwrap();
+        assert_eq!(report, QuarantineReport::default());
+        assert!(!dir.path().join("absent").exists());
+    }
+}
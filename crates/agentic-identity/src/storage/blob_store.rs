@@ -0,0 +1,249 @@
+//! Blob store — large action payloads, addressed by their hash.
+//!
+//! A receipt that embeds a large payload in `ActionContent::data` grows by
+//! the payload's size, and so does every copy, export and bundle of it.
+//! Instead the payload goes in the blob store and the receipt carries a
+//! [`DataRef`] (hash, size and media type) from
+//! [`ActionContent::with_data_ref`](crate::receipt::ActionContent::with_data_ref).
+//! The receipt's signature covers the reference, and a blob is checked
+//! against its hash whenever it is read, so the payload stays as verifiable
+//! as embedded data.
+//!
+//! Each blob is stored once, however many receipts refer to it:
+//! ```text
+//! {base_dir}/{sha256_hex}.blob
+//! ```
+
+use std::path::PathBuf;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{IdentityError, Result};
+use crate::receipt::DataRef;
+
+use super::atomic::{self, write_atomic, QuarantineReport};
+use super::lock::StoreLock;
+
+const BLOB_EXTENSION: &str = "blob";
+
+/// Filesystem-backed, content-addressed store of payloads.
+pub struct BlobStore {
+    base_dir: PathBuf,
+}
+
+impl BlobStore {
+    /// Create a new `BlobStore` rooted at `base_dir`.
+    ///
+    /// The directory and any missing parents are created if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the directory cannot be created.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        std::fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    /// Store `bytes` and return the reference a receipt should carry.
+    ///
+    /// Storing a payload that is already present writes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process keeps the
+    /// store locked, or `IdentityError::Io` if the file cannot be written.
+    pub fn put(&self, bytes: &[u8], media_type: impl Into<String>) -> Result<DataRef> {
+        let data_ref = DataRef::for_bytes(bytes, media_type);
+        let path = self.blob_path(&data_ref.hash)?;
+        if !path.exists() {
+            let _lock = StoreLock::dir(&self.base_dir)?;
+            write_atomic(&path, bytes)?;
+        }
+        Ok(data_ref)
+    }
+
+    /// Load the blob with hex SHA-256 `hash`, checking it against the hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` for a malformed hash,
+    /// `IdentityError::NotFound` if no such blob is stored, and
+    /// `IdentityError::StorageError` if the stored bytes no longer match
+    /// their hash.
+    pub fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.blob_path(hash)?;
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(IdentityError::NotFound(format!("blob not found: {hash}")));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if hex::encode(Sha256::digest(&bytes)) != hash {
+            return Err(IdentityError::StorageError(format!(
+                "blob {hash} does not match its hash"
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Load the payload a receipt's `data_ref` names.
+    ///
+    /// # Errors
+    ///
+    /// As for [`get`](Self::get); `IdentityError::StorageError` too if the
+    /// blob's size is not the one the reference records.
+    pub fn load(&self, data_ref: &DataRef) -> Result<Vec<u8>> {
+        let bytes = self.get(&data_ref.hash)?;
+        if !data_ref.matches(&bytes) {
+            return Err(IdentityError::StorageError(format!(
+                "blob {} is {} bytes, not {}",
+                data_ref.hash,
+                bytes.len(),
+                data_ref.size
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Check whether a blob with hex SHA-256 `hash` is stored.
+    pub fn contains(&self, hash: &str) -> bool {
+        self.blob_path(hash).is_ok_and(|path| path.exists())
+    }
+
+    /// List the hashes of all stored blobs.
+    ///
+    /// The returned list is not sorted in any particular order.
+    pub fn list(&self) -> Result<Vec<String>> {
+        let suffix = format!(".{BLOB_EXTENSION}");
+        let mut hashes = Vec::new();
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let name = entry?.file_name();
+            if let Some(stem) = name.to_string_lossy().strip_suffix(&suffix) {
+                hashes.push(stem.to_string());
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Delete the blob with hex SHA-256 `hash`. Receipts referring to it
+    /// still verify, but their payload can no longer be loaded.
+    ///
+    /// If no such blob exists, this is a no-op (returns `Ok`).
+    pub fn delete(&self, hash: &str) -> Result<()> {
+        let path = self.blob_path(hash)?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    /// Quarantine the temporary files of blob writes a crash interrupted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if a writer keeps the store
+    /// locked, or `IdentityError::Io` for filesystem errors.
+    pub fn recover(&self) -> Result<QuarantineReport> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        atomic::recover_interrupted_locked(&self.base_dir, BLOB_EXTENSION)
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    /// Build the filesystem path for a blob, rejecting anything but a hex
+    /// SHA-256 so a hash can never name a path outside the store.
+    fn blob_path(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            return Err(IdentityError::InvalidInput(format!(
+                "not a hex SHA-256 blob hash: {hash}"
+            )));
+        }
+        Ok(self.base_dir.join(format!("{hash}.{BLOB_EXTENSION}")))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::verify::verify_receipt;
+    use crate::receipt::{ActionContent, ActionType};
+
+    #[test]
+    fn test_blob_put_get_dedupes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path()).unwrap();
+        let payload = vec![7u8; 100_000];
+
+        let first = store.put(&payload, "application/octet-stream").unwrap();
+        let second = store.put(&payload, "application/octet-stream").unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.size, 100_000);
+        assert_eq!(store.list().unwrap(), vec![first.hash.clone()]);
+        assert_eq!(store.load(&first).unwrap(), payload);
+
+        store.delete(&first.hash).unwrap();
+        assert!(!store.contains(&first.hash));
+        assert!(matches!(
+            store.get(&first.hash),
+            Err(IdentityError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_blob_tampering_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path()).unwrap();
+        let data_ref = store
+            .put(b"{\"rows\":[1,2,3]}", "application/json")
+            .unwrap();
+        std::fs::write(
+            dir.path().join(format!("{}.blob", data_ref.hash)),
+            b"{\"rows\":[1,2,4]}",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            store.load(&data_ref),
+            Err(IdentityError::StorageError(_))
+        ));
+        assert!(matches!(
+            store.get("../../etc/passwd"),
+            Err(IdentityError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_receipt_signs_its_data_ref() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let payload = vec![1u8; 4096];
+        let data_ref = store.put(&payload, "application/octet-stream").unwrap();
+
+        let mut receipt = ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Observation,
+            ActionContent::new("captured sensor dump").with_data_ref(
+                data_ref.hash.clone(),
+                data_ref.size,
+                data_ref.media_type.clone(),
+            ),
+        )
+        .sign(anchor.signing_key())
+        .unwrap();
+        assert!(verify_receipt(&receipt).unwrap().is_valid);
+        let referenced = receipt.action.data_ref.clone().unwrap();
+        assert_eq!(store.load(&referenced).unwrap(), payload);
+
+        receipt.action.data_ref = Some(DataRef::for_bytes(b"other", "text/plain"));
+        assert!(!verify_receipt(&receipt).unwrap().is_valid);
+    }
+}
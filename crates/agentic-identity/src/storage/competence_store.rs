@@ -0,0 +1,410 @@
+//! Competence persistence — store and retrieve `CompetenceAttempt` records.
+//!
+//! Attempts are grouped by domain: each is stored as a single JSON file
+//! named `{attempt_id}.json` inside a directory for its domain under the
+//! configured base directory. Domain names are used as directory names with
+//! every byte outside `[A-Za-z0-9._-]` percent-encoded, so `code_review`
+//! stays readable and `deploy:prod` becomes `deploy%3Aprod`. Writes hold the
+//! base directory's [`StoreLock`](super::StoreLock).
+//!
+//! File format:
+//! ```json
+//! {
+//!     "version": 1,
+//!     "attempt": { ... CompetenceAttempt ... }
+//! }
+//! ```
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::competence::{
+    get_competence, AttemptId, CompetenceAttempt, CompetenceDomain, CompetenceRecord,
+};
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+
+use super::lock::StoreLock;
+use super::scan;
+use super::transaction::Transaction;
+
+// ── File format constants ─────────────────────────────────────────────────────
+
+const COMPETENCE_FILE_VERSION: u32 = 1;
+
+// ── On-disk structure ─────────────────────────────────────────────────────────
+
+/// Wrapper written to disk for each attempt.
+#[derive(Debug, Serialize, Deserialize)]
+struct CompetenceFile {
+    /// Format version number.
+    version: u32,
+    /// The stored attempt.
+    attempt: CompetenceAttempt,
+}
+
+// ── CompetenceStore ───────────────────────────────────────────────────────────
+
+/// Filesystem-backed store for `CompetenceAttempt` records, grouped by
+/// domain.
+///
+/// Each attempt is written to a dedicated JSON file named by its ID.
+/// The store is safe for single-process use; concurrent writes from
+/// multiple processes are not coordinated.
+pub struct CompetenceStore {
+    base_dir: PathBuf,
+}
+
+impl CompetenceStore {
+    /// Create a new `CompetenceStore` rooted at `base_dir`.
+    ///
+    /// The directory and any missing parents are created if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the directory cannot be created.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        std::fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    /// Persist an attempt to disk.
+    ///
+    /// Writes `{base_dir}/{domain}/{attempt_id}.json`, creating the domain
+    /// directory if needed. Any existing file with the same ID is
+    /// overwritten.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if JSON serialization fails,
+    /// or `IdentityError::Io` for filesystem errors.
+    pub fn save(&self, attempt: &CompetenceAttempt) -> Result<()> {
+        let json = encode_attempt(attempt)?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        std::fs::create_dir_all(self.domain_dir(&attempt.domain))?;
+        std::fs::write(
+            self.attempt_path(&attempt.domain, &attempt.attempt_id),
+            json,
+        )?;
+        Ok(())
+    }
+
+    /// Stage `attempt` in `txn` to be saved when it commits.
+    pub fn stage(&self, txn: &mut Transaction, attempt: &CompetenceAttempt) -> Result<()> {
+        std::fs::create_dir_all(self.domain_dir(&attempt.domain))?;
+        txn.stage_in_store(
+            &self.base_dir,
+            &self.attempt_path(&attempt.domain, &attempt.attempt_id),
+            encode_attempt(attempt)?.as_bytes(),
+        )
+    }
+
+    /// Load an attempt by domain and ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no such attempt is stored, or
+    /// `IdentityError::InvalidFileFormat` if its file cannot be parsed.
+    pub fn load(&self, domain: &CompetenceDomain, id: &AttemptId) -> Result<CompetenceAttempt> {
+        let path = self.attempt_path(domain, id);
+
+        if !path.exists() {
+            return Err(IdentityError::NotFound(format!(
+                "competence attempt not found: {domain}/{id}"
+            )));
+        }
+
+        let bytes = std::fs::read(&path)?;
+        let file: CompetenceFile = serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::InvalidFileFormat(format!(
+                "failed to parse competence file {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        Ok(file.attempt)
+    }
+
+    /// List every domain with at least one stored attempt, sorted by name.
+    pub fn list_domains(&self) -> Result<Vec<CompetenceDomain>> {
+        let mut domains = Vec::new();
+
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let Some(domain) = entry.file_name().to_str().and_then(decode_domain) else {
+                continue;
+            };
+            let domain = CompetenceDomain(domain);
+            if !self.list(&domain)?.is_empty() {
+                domains.push(domain);
+            }
+        }
+
+        domains.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(domains)
+    }
+
+    /// List the IDs of all attempts stored for `domain`.
+    ///
+    /// A domain with no attempts yields an empty list.
+    pub fn list(&self, domain: &CompetenceDomain) -> Result<Vec<AttemptId>> {
+        let dir = self.domain_dir(domain);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+
+            if let Some(stem) = name_str.strip_suffix(".json") {
+                ids.push(AttemptId(stem.to_string()));
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Load every attempt stored for `domain`, oldest first.
+    ///
+    /// Corrupt files are skipped. Ties in timestamp are broken by ID so the
+    /// order is stable.
+    pub fn load_domain(&self, domain: &CompetenceDomain) -> Result<Vec<CompetenceAttempt>> {
+        let ids = self.list(domain)?;
+        let mut attempts = scan::load_each(&ids, |id| self.load(domain, id));
+        attempts
+            .sort_by(|a, b| (a.timestamp, &a.attempt_id.0).cmp(&(b.timestamp, &b.attempt_id.0)));
+        Ok(attempts)
+    }
+
+    /// Load every stored attempt across all domains, oldest first.
+    pub fn load_all(&self) -> Result<Vec<CompetenceAttempt>> {
+        let mut attempts = Vec::new();
+        for domain in self.list_domains()? {
+            attempts.extend(self.load_domain(&domain)?);
+        }
+        attempts
+            .sort_by(|a, b| (a.timestamp, &a.attempt_id.0).cmp(&(b.timestamp, &b.attempt_id.0)));
+        Ok(attempts)
+    }
+
+    /// Aggregate `identity`'s stored history in `domain`.
+    ///
+    /// Returns `None` if the identity has no attempts in the domain. See
+    /// [`get_competence`].
+    pub fn competence(
+        &self,
+        identity: &IdentityId,
+        domain: &CompetenceDomain,
+    ) -> Result<Option<CompetenceRecord>> {
+        Ok(get_competence(identity, domain, &self.load_domain(domain)?))
+    }
+
+    /// Delete the file for an attempt.
+    ///
+    /// If no file exists for `id`, this is a no-op (returns `Ok`).
+    pub fn delete(&self, domain: &CompetenceDomain, id: &AttemptId) -> Result<()> {
+        let path = self.attempt_path(domain, id);
+
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    /// Build the directory path for a domain.
+    fn domain_dir(&self, domain: &CompetenceDomain) -> PathBuf {
+        self.base_dir.join(encode_domain(&domain.0))
+    }
+
+    /// Build the filesystem path for an attempt.
+    fn attempt_path(&self, domain: &CompetenceDomain, id: &AttemptId) -> PathBuf {
+        self.domain_dir(domain).join(format!("{}.json", id.0))
+    }
+}
+
+/// Serialize `attempt` into the contents of its competence file.
+fn encode_attempt(attempt: &CompetenceAttempt) -> Result<String> {
+    let file = CompetenceFile {
+        version: COMPETENCE_FILE_VERSION,
+        attempt: attempt.clone(),
+    };
+    serde_json::to_string_pretty(&file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
+/// Turn a domain name into a directory name that is safe on every platform.
+///
+/// The empty domain and the names `.` and `..` are encoded in full so they
+/// cannot alias the base directory or its parent.
+fn encode_domain(domain: &str) -> String {
+    let keep = |b: u8| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-');
+    let whole = domain.is_empty() || domain.bytes().all(|b| b == b'.');
+    let mut out = String::with_capacity(domain.len());
+    for b in domain.bytes() {
+        if keep(b) && !(whole && b == b'.') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    if out.is_empty() {
+        out.push('%');
+    }
+    out
+}
+
+/// Inverse of [`encode_domain`]; `None` for names it never produces.
+fn decode_domain(name: &str) -> Option<String> {
+    if name == "%" {
+        return Some(String::new());
+    }
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = name.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::competence::{record_attempt, AttemptOutcome};
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::ReceiptId;
+
+    fn attempt(
+        anchor: &IdentityAnchor,
+        domain: &str,
+        outcome: AttemptOutcome,
+    ) -> CompetenceAttempt {
+        record_attempt(
+            anchor,
+            CompetenceDomain::new(domain),
+            outcome,
+            ReceiptId("arec_test".into()),
+            None,
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_competence_store_save_load_per_domain() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CompetenceStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let deploy = attempt(&anchor, "deploy:prod", AttemptOutcome::Success);
+        let review = attempt(&anchor, "code_review", AttemptOutcome::Success);
+        store.save(&deploy).unwrap();
+        store.save(&review).unwrap();
+
+        assert!(dir.path().join("deploy%3Aprod").is_dir());
+        assert_eq!(
+            store
+                .load(&deploy.domain, &deploy.attempt_id)
+                .unwrap()
+                .attempt_id,
+            deploy.attempt_id
+        );
+        assert!(store.load(&review.domain, &deploy.attempt_id).is_err());
+        assert_eq!(
+            store.list_domains().unwrap(),
+            vec![
+                CompetenceDomain::new("code_review"),
+                CompetenceDomain::new("deploy:prod")
+            ]
+        );
+        assert_eq!(
+            store.list(&deploy.domain).unwrap(),
+            vec![deploy.attempt_id.clone()]
+        );
+        assert!(store
+            .list(&CompetenceDomain::new("unused"))
+            .unwrap()
+            .is_empty());
+        assert_eq!(store.load_all().unwrap().len(), 2);
+
+        store.delete(&deploy.domain, &deploy.attempt_id).unwrap();
+        store.delete(&deploy.domain, &deploy.attempt_id).unwrap();
+        assert_eq!(
+            store.list_domains().unwrap(),
+            vec![CompetenceDomain::new("code_review")]
+        );
+    }
+
+    #[test]
+    fn test_competence_store_aggregates_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = CompetenceStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let other = IdentityAnchor::new(None);
+
+        for outcome in [
+            AttemptOutcome::Success,
+            AttemptOutcome::Success,
+            AttemptOutcome::Failure {
+                reason: "timeout".into(),
+            },
+            AttemptOutcome::Success,
+        ] {
+            store.save(&attempt(&anchor, "deploy", outcome)).unwrap();
+        }
+        store
+            .save(&attempt(&other, "deploy", AttemptOutcome::Success))
+            .unwrap();
+
+        let domain = CompetenceDomain::new("deploy");
+        let record = store.competence(&anchor.id(), &domain).unwrap().unwrap();
+        assert_eq!(record.total_attempts, 4);
+        assert_eq!(record.successes, 3);
+        assert!((record.success_rate - 0.75).abs() < f32::EPSILON);
+        assert!(store
+            .competence(&anchor.id(), &CompetenceDomain::new("planning"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_domain_names_round_trip() {
+        for domain in [
+            "deploy",
+            "deploy:prod",
+            "a/b",
+            "..",
+            ".",
+            "",
+            "会議",
+            "100%",
+        ] {
+            let encoded = encode_domain(domain);
+            assert!(!encoded.contains('/') && encoded != "." && encoded != "..");
+            assert_eq!(decode_domain(&encoded).as_deref(), Some(domain));
+        }
+        assert_eq!(encode_domain("code_review"), "code_review");
+        assert_eq!(decode_domain("%zz"), None);
+    }
+}
@@ -0,0 +1,762 @@
+//! Continuity chain persistence — store, export, and import experience chains.
+//!
+//! Each identity's chain (ordered experiences, anchors and heartbeats) is stored as a
+//! single JSON file named `{identity_id}.json` inside the configured base
+//! directory, so a chain is always replaced as one unit. Each
+//! read-modify-write of a chain holds the directory's
+//! [`StoreLock`](super::StoreLock), so concurrent appends are never lost.
+//!
+//! File format:
+//! ```json
+//! {
+//!     "version": 1,
+//!     "identity": "aid_...",
+//!     "experiences": [ ... ExperienceEvent ... ],
+//!     "anchors": [ ... ContinuityAnchor ... ],
+//!     "heartbeats": [ ... HeartbeatRecord ... ]
+//! }
+//! ```
+//!
+//! A [`ContinuityExport`] is the signed, portable form of a chain used to
+//! migrate an agent between machines.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::continuity::engine::{
+    anchor_signing_input, classify_gaps, compute_cumulative_hash, detect_gaps, get_continuity_state,
+};
+use crate::continuity::{ContinuityAnchor, ContinuityState, ExperienceEvent, Gap, HeartbeatRecord};
+use crate::crypto::keys::Ed25519KeyPair;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+use crate::identity::{IdentityAnchor, IdentityId};
+
+use super::atomic::write_atomic;
+use super::lock::StoreLock;
+
+// ── File format constants ─────────────────────────────────────────────────────
+
+const CONTINUITY_FILE_VERSION: u32 = 1;
+/// Version 2 exports are signed in the continuity-export domain; version 1
+/// exports predate domain separation.
+const CONTINUITY_EXPORT_VERSION: u32 = 2;
+
+// ── On-disk structure ─────────────────────────────────────────────────────────
+
+/// Wrapper written to disk for each identity's chain.
+#[derive(Debug, Serialize, Deserialize)]
+struct ContinuityFile {
+    /// Format version number.
+    version: u32,
+    /// Identity the chain belongs to.
+    identity: IdentityId,
+    /// Experiences in sequence order, starting at genesis.
+    experiences: Vec<ExperienceEvent>,
+    /// Anchors over the chain, in creation order.
+    anchors: Vec<ContinuityAnchor>,
+    /// Heartbeats, in the order they were recorded.
+    #[serde(default)]
+    heartbeats: Vec<HeartbeatRecord>,
+}
+
+// ── Export format ─────────────────────────────────────────────────────────────
+
+/// A signed, self-contained copy of an identity's continuity chain.
+///
+/// The signature covers every experience's cumulative hash and every
+/// anchor, so dropping or reordering any part of the chain is detected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContinuityExport {
+    /// Export format version.
+    pub version: u32,
+    /// Identity the chain belongs to.
+    pub identity: IdentityId,
+    /// Base64 public key of the identity; must hash to `identity`.
+    pub identity_key: String,
+    /// Experiences in sequence order, starting at genesis.
+    pub experiences: Vec<ExperienceEvent>,
+    /// Anchors over the chain.
+    pub anchors: Vec<ContinuityAnchor>,
+    /// Export timestamp (microseconds since epoch).
+    pub exported_at: u64,
+    /// Hash over the whole export.
+    pub export_hash: String,
+    /// Identity's signature over `export_hash`.
+    pub signature: String,
+}
+
+impl ContinuityExport {
+    /// Recompute the export hash from the export's content.
+    pub fn compute_hash(&self) -> String {
+        let chain: Vec<&str> = self
+            .experiences
+            .iter()
+            .map(|e| e.cumulative_hash.as_str())
+            .collect();
+        let anchors: Vec<String> = self
+            .anchors
+            .iter()
+            .map(|a| format!("{}={}", a.id.0, a.signature))
+            .collect();
+        let hash_input = format!(
+            "continuity-export:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.version,
+            self.identity.0,
+            self.identity_key,
+            self.exported_at,
+            self.experiences.len(),
+            chain.join(","),
+            self.anchors.len(),
+            anchors.join(","),
+        );
+        hex::encode(Sha256::digest(hash_input.as_bytes()))
+    }
+
+    /// Signature scheme of the export signature, from the export version.
+    fn signature_version(&self) -> u32 {
+        if self.version < 2 {
+            signing::LEGACY_SIGNATURE_VERSION
+        } else {
+            signing::SIGNATURE_VERSION
+        }
+    }
+
+    /// Verify the export end to end: key binding, the export signature, the
+    /// cumulative-hash chain, each experience signature, and each anchor.
+    pub fn verify(&self) -> Result<()> {
+        let pub_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &self.identity_key,
+        )
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid identity key: {e}")))?;
+        let key_bytes: [u8; 32] = pub_bytes
+            .try_into()
+            .map_err(|_| IdentityError::InvalidKey("identity key must be 32 bytes".into()))?;
+        let verifying_key = Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)?;
+
+        if IdentityId::from_verifying_key(&verifying_key) != self.identity {
+            return Err(IdentityError::InvalidKey(format!(
+                "export key does not belong to {}",
+                self.identity
+            )));
+        }
+
+        if self.compute_hash() != self.export_hash {
+            return Err(IdentityError::SignatureInvalid);
+        }
+        signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::ContinuityExport,
+            self.signature_version(),
+            self.export_hash.as_bytes(),
+            &self.signature,
+        )?;
+
+        if first_break(&self.identity, &self.experiences).is_some() {
+            return Err(IdentityError::InvalidChain);
+        }
+        for exp in &self.experiences {
+            signing::verify_versioned(
+                &verifying_key,
+                SignatureDomain::Experience,
+                exp.signature_version,
+                exp.cumulative_hash.as_bytes(),
+                &exp.signature,
+            )?;
+        }
+
+        for anchor in &self.anchors {
+            if !anchor_matches(&self.identity, anchor, &self.experiences) {
+                return Err(IdentityError::InvalidChain);
+            }
+            let sign_input = anchor_signing_input(
+                &anchor.id,
+                &anchor.anchor_type,
+                &anchor.cumulative_hash,
+                anchor.experience_count,
+                anchor.timestamp,
+            );
+            signing::verify_versioned(
+                &verifying_key,
+                SignatureDomain::ContinuityAnchor,
+                anchor.signature_version,
+                sign_input.as_bytes(),
+                &anchor.signature,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// ── Chain replay ──────────────────────────────────────────────────────────────
+
+/// Result of replaying a stored chain from genesis with
+/// [`ContinuityStore::replay`].
+///
+/// Replay recomputes every link and cumulative hash but does not check
+/// signatures, which needs the identity's key; [`ContinuityStore::export`]
+/// and [`ContinuityExport::verify`] cover those.
+#[derive(Debug, Clone)]
+pub struct ChainReplay {
+    /// Summary of the stored chain, or `None` if nothing is stored.
+    pub state: Option<ContinuityState>,
+    /// Experiences, counted from genesis, that extend the chain correctly.
+    pub verified: u64,
+    /// Sequence position of the first experience that does not, if any.
+    pub broken_at: Option<u64>,
+    /// Number of stored anchors.
+    pub anchors: usize,
+    /// Anchors over an experience in the verified prefix whose hash and
+    /// count match it.
+    pub anchors_verified: usize,
+}
+
+impl ChainReplay {
+    /// Whether every experience and every anchor replayed correctly.
+    pub fn is_intact(&self) -> bool {
+        self.broken_at.is_none() && self.anchors_verified == self.anchors
+    }
+}
+
+/// Index of the first experience that does not extend the chain before it —
+/// wrong identity or sequence number, a broken link, or a cumulative hash
+/// that does not recompute — or `None` if the whole chain is intact.
+fn first_break(identity: &IdentityId, experiences: &[ExperienceEvent]) -> Option<usize> {
+    let mut previous: Option<&ExperienceEvent> = None;
+    for (seq, exp) in experiences.iter().enumerate() {
+        let expected_prev_hash = previous.map(|p| p.cumulative_hash.as_str());
+        let extends = &exp.identity == identity
+            && exp.sequence_number == seq as u64
+            && exp.previous_experience_id.as_ref() == previous.map(|p| &p.id)
+            && exp.previous_experience_hash.as_deref() == expected_prev_hash
+            && compute_cumulative_hash(
+                expected_prev_hash,
+                &exp.content_hash,
+                exp.sequence_number,
+                exp.timestamp,
+            ) == exp.cumulative_hash;
+        if !extends {
+            return Some(seq);
+        }
+        previous = Some(exp);
+    }
+    None
+}
+
+/// Whether `anchor` belongs to `identity` and matches the hash and
+/// experience count of the experience it anchors.
+fn anchor_matches(
+    identity: &IdentityId,
+    anchor: &ContinuityAnchor,
+    experiences: &[ExperienceEvent],
+) -> bool {
+    experiences
+        .iter()
+        .find(|e| e.id == anchor.experience_id)
+        .is_some_and(|anchored| {
+            &anchor.identity == identity
+                && anchor.cumulative_hash == anchored.cumulative_hash
+                && anchor.experience_count == anchored.sequence_number + 1
+        })
+}
+
+// ── ContinuityStore ───────────────────────────────────────────────────────────
+
+/// Filesystem-backed store for continuity chains, one file per identity.
+///
+/// The store is safe for single-process use; concurrent writes from
+/// multiple processes are not coordinated.
+pub struct ContinuityStore {
+    base_dir: PathBuf,
+}
+
+impl ContinuityStore {
+    /// Create a new `ContinuityStore` rooted at `base_dir`.
+    ///
+    /// The directory and any missing parents are created if they do not exist.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        std::fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    /// Append an experience to its identity's chain.
+    ///
+    /// The experience must directly extend the stored chain (next sequence
+    /// number, linked to the current head); otherwise
+    /// [`IdentityError::InvalidChain`] is returned and nothing is written.
+    pub fn append_experience(&self, experience: &ExperienceEvent) -> Result<()> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut file = self.read_or_empty(&experience.identity)?;
+
+        let head = file.experiences.last();
+        let extends = experience.sequence_number == file.experiences.len() as u64
+            && experience.previous_experience_hash.as_deref()
+                == head.map(|h| h.cumulative_hash.as_str());
+        if !extends {
+            return Err(IdentityError::InvalidChain);
+        }
+
+        file.experiences.push(experience.clone());
+        self.write(&file)
+    }
+
+    /// Persist an anchor over an experience already in the chain.
+    ///
+    /// An anchor with the same ID is replaced.
+    pub fn save_anchor(&self, anchor: &ContinuityAnchor) -> Result<()> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut file = self.read_or_empty(&anchor.identity)?;
+
+        let anchored = file
+            .experiences
+            .iter()
+            .any(|e| e.id == anchor.experience_id && e.cumulative_hash == anchor.cumulative_hash);
+        if !anchored {
+            return Err(IdentityError::InvalidChain);
+        }
+
+        file.anchors.retain(|a| a.id != anchor.id);
+        file.anchors.push(anchor.clone());
+        self.write(&file)
+    }
+
+    /// Append a heartbeat to its identity's history.
+    pub fn append_heartbeat(&self, heartbeat: &HeartbeatRecord) -> Result<()> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut file = self.read_or_empty(&heartbeat.identity)?;
+        file.heartbeats.push(heartbeat.clone());
+        self.write(&file)
+    }
+
+    /// Load an identity's experiences in sequence order.
+    ///
+    /// Returns an empty list if nothing has been stored for the identity.
+    pub fn load_experiences(&self, identity: &IdentityId) -> Result<Vec<ExperienceEvent>> {
+        Ok(self.read_or_empty(identity)?.experiences)
+    }
+
+    /// Load an identity's anchors.
+    pub fn load_anchors(&self, identity: &IdentityId) -> Result<Vec<ContinuityAnchor>> {
+        Ok(self.read_or_empty(identity)?.anchors)
+    }
+
+    /// Load an identity's heartbeats in the order they were recorded.
+    pub fn load_heartbeats(&self, identity: &IdentityId) -> Result<Vec<HeartbeatRecord>> {
+        Ok(self.read_or_empty(identity)?.heartbeats)
+    }
+
+    /// Replay an identity's stored chain from genesis, recomputing each
+    /// cumulative hash and checking each anchor against it.
+    ///
+    /// A chain that fails to replay is reported, not returned as an error:
+    /// `broken_at` names the first experience that does not extend the
+    /// chain, and only the prefix before it counts as verified.
+    pub fn replay(&self, identity: &IdentityId) -> Result<ChainReplay> {
+        let file = self.read_or_empty(identity)?;
+        let broken_at = first_break(identity, &file.experiences);
+        let verified = &file.experiences[..broken_at.unwrap_or(file.experiences.len())];
+        let anchors_verified = file
+            .anchors
+            .iter()
+            .filter(|a| anchor_matches(identity, a, verified))
+            .count();
+
+        Ok(ChainReplay {
+            state: get_continuity_state(identity, &file.experiences).ok(),
+            verified: verified.len() as u64,
+            broken_at: broken_at.map(|seq| seq as u64),
+            anchors: file.anchors.len(),
+            anchors_verified,
+        })
+    }
+
+    /// Detect gaps in an identity's stored chain and classify each with the
+    /// stored heartbeats. See [`detect_gaps`] and [`classify_gaps`].
+    ///
+    /// Temporal gaps shorter than `grace_period_seconds` are ignored.
+    pub fn gaps(&self, identity: &IdentityId, grace_period_seconds: u64) -> Result<Vec<Gap>> {
+        let file = self.read_or_empty(identity)?;
+        let mut gaps = detect_gaps(&file.experiences, grace_period_seconds);
+        classify_gaps(&mut gaps, &file.experiences, &file.heartbeats);
+        Ok(gaps)
+    }
+
+    /// List the identities that have a stored chain.
+    pub fn list(&self) -> Result<Vec<IdentityId>> {
+        let mut ids = Vec::new();
+
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+
+            if let Some(stem) = name_str.strip_suffix(".json") {
+                ids.push(IdentityId(stem.to_string()));
+            }
+        }
+
+        Ok(ids)
+    }
+
+    /// Delete an identity's stored chain.
+    ///
+    /// If no chain exists for `identity`, this is a no-op (returns `Ok`).
+    pub fn delete(&self, identity: &IdentityId) -> Result<()> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        match std::fs::remove_file(self.chain_path(identity)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    /// Export an identity's full chain and anchors, signed by the identity.
+    pub fn export(&self, identity: &IdentityAnchor) -> Result<ContinuityExport> {
+        let file = self.read_or_empty(&identity.id())?;
+
+        let mut export = ContinuityExport {
+            version: CONTINUITY_EXPORT_VERSION,
+            identity: identity.id(),
+            identity_key: identity.public_key_base64(),
+            experiences: file.experiences,
+            anchors: file.anchors,
+            exported_at: crate::time::now_micros(),
+            export_hash: String::new(),
+            signature: String::new(),
+        };
+        export.export_hash = export.compute_hash();
+        export.signature = signing::sign_in_domain(
+            identity.signing_key(),
+            SignatureDomain::ContinuityExport,
+            export.export_hash.as_bytes(),
+        );
+
+        Ok(export)
+    }
+
+    /// Verify an export and persist it, returning the number of experiences
+    /// added to the store.
+    ///
+    /// The import is all-or-nothing: the export is fully verified and
+    /// reconciled before anything is written, and the chain file is replaced
+    /// atomically. If the store already holds a chain for the identity, one
+    /// chain must be a prefix of the other; anything else is refused with
+    /// [`IdentityError::ContinuityConflict`].
+    pub fn import(&self, export: &ContinuityExport) -> Result<usize> {
+        export.verify()?;
+
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let existing = self.read_or_empty(&export.identity)?;
+        let shared = existing.experiences.len().min(export.experiences.len());
+        let diverged = existing.experiences[..shared]
+            .iter()
+            .zip(&export.experiences[..shared])
+            .position(|(ours, theirs)| ours.cumulative_hash != theirs.cumulative_hash);
+        if let Some(seq) = diverged {
+            return Err(IdentityError::ContinuityConflict(format!(
+                "stored chain for {} diverges from the import at sequence {seq}",
+                export.identity
+            )));
+        }
+
+        let added = export.experiences.len().saturating_sub(shared);
+        let mut merged = existing;
+        merged
+            .experiences
+            .extend(export.experiences[shared..].iter().cloned());
+        for anchor in &export.anchors {
+            if !merged.anchors.iter().any(|a| a.id == anchor.id) {
+                merged.anchors.push(anchor.clone());
+            }
+        }
+
+        self.write(&merged)?;
+        Ok(added)
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    /// Build the filesystem path for an identity's chain.
+    fn chain_path(&self, identity: &IdentityId) -> PathBuf {
+        self.base_dir.join(format!("{}.json", identity.0))
+    }
+
+    /// Read an identity's chain file, or an empty chain if none exists.
+    fn read_or_empty(&self, identity: &IdentityId) -> Result<ContinuityFile> {
+        let path = self.chain_path(identity);
+
+        if !path.exists() {
+            return Ok(ContinuityFile {
+                version: CONTINUITY_FILE_VERSION,
+                identity: identity.clone(),
+                experiences: Vec::new(),
+                anchors: Vec::new(),
+                heartbeats: Vec::new(),
+            });
+        }
+
+        let bytes = std::fs::read(&path)?;
+        serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::InvalidFileFormat(format!(
+                "failed to parse continuity file {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// Write a chain file atomically (temp file + rename); the caller holds
+    /// the store lock.
+    fn write(&self, file: &ContinuityFile) -> Result<()> {
+        let json = serde_json::to_string_pretty(file)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        write_atomic(&self.chain_path(&file.identity), json.as_bytes())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::continuity::{create_anchor, record_experience, AnchorType, ExperienceType};
+    use crate::continuity::{CognitionType, SystemEvent};
+
+    /// Record `count` chained experiences for `anchor` into `store`.
+    fn record_chain(store: &ContinuityStore, anchor: &IdentityAnchor, count: usize) {
+        let mut previous = store.load_experiences(&anchor.id()).unwrap().pop();
+        for i in 0..count {
+            let exp = record_experience(
+                anchor,
+                ExperienceType::Cognition {
+                    cognition_type: CognitionType::Thought,
+                },
+                &format!("content-{i}"),
+                0.5,
+                previous.as_ref(),
+            )
+            .unwrap();
+            store.append_experience(&exp).unwrap();
+            previous = Some(exp);
+        }
+    }
+
+    #[test]
+    fn test_continuity_store_append_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ContinuityStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        record_chain(&store, &anchor, 3);
+        let exps = store.load_experiences(&anchor.id()).unwrap();
+        assert_eq!(exps.len(), 3);
+        assert_eq!(exps[2].sequence_number, 2);
+        assert_eq!(store.list().unwrap(), vec![anchor.id()]);
+
+        // An experience that does not extend the head is refused.
+        let stray = record_experience(
+            &anchor,
+            ExperienceType::System {
+                event: SystemEvent::Checkpoint,
+            },
+            "stray",
+            0.1,
+            None,
+        )
+        .unwrap();
+        assert!(store.append_experience(&stray).is_err());
+        assert_eq!(store.load_experiences(&anchor.id()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_continuity_store_heartbeats() {
+        use crate::continuity::{create_heartbeat, HealthMetrics, HeartbeatStatus};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = ContinuityStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        record_chain(&store, &anchor, 2);
+
+        for (seq, status) in [HeartbeatStatus::Active, HeartbeatStatus::Suspended]
+            .into_iter()
+            .enumerate()
+        {
+            let health = HealthMetrics {
+                memory_usage_bytes: 0,
+                experience_rate_per_hour: 0.0,
+                error_count: 0,
+                latency_ms: 0,
+            };
+            let hb = create_heartbeat(&anchor, seq as u64, "h", 2, 0, status, health).unwrap();
+            store.append_heartbeat(&hb).unwrap();
+        }
+
+        let heartbeats = store.load_heartbeats(&anchor.id()).unwrap();
+        assert_eq!(heartbeats.len(), 2);
+        assert_eq!(heartbeats[1].status, HeartbeatStatus::Suspended);
+        // Heartbeats live alongside the chain without disturbing it.
+        assert_eq!(store.load_experiences(&anchor.id()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_continuity_store_replay_finds_first_break() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ContinuityStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let empty = store.replay(&anchor.id()).unwrap();
+        assert!(empty.state.is_none() && empty.is_intact());
+
+        record_chain(&store, &anchor, 4);
+        let exps = store.load_experiences(&anchor.id()).unwrap();
+        let ca = create_anchor(&anchor, AnchorType::Manual, &exps[3], None, None).unwrap();
+        store.save_anchor(&ca).unwrap();
+
+        let replay = store.replay(&anchor.id()).unwrap();
+        assert!(replay.is_intact());
+        assert_eq!(replay.verified, 4);
+        assert_eq!(replay.anchors_verified, 1);
+        let state = replay.state.unwrap();
+        assert_eq!(state.total_experiences, 4);
+        assert_eq!(state.latest_hash, exps[3].cumulative_hash);
+        assert!(store.gaps(&anchor.id(), 300).unwrap().is_empty());
+
+        // Edit an experience in place, as a tampered file would.
+        let path = dir.path().join(format!("{}.json", anchor.id().0));
+        let mut file: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        file["experiences"][2]["content_hash"] = "tampered".into();
+        std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+        let replay = store.replay(&anchor.id()).unwrap();
+        assert!(!replay.is_intact());
+        assert_eq!(replay.broken_at, Some(2));
+        assert_eq!(replay.verified, 2);
+        assert_eq!(replay.anchors_verified, 0);
+    }
+
+    #[test]
+    fn test_continuity_export_import_round_trip() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let src = ContinuityStore::new(src_dir.path()).unwrap();
+        let dst = ContinuityStore::new(dst_dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        record_chain(&src, &anchor, 4);
+        let head = src.load_experiences(&anchor.id()).unwrap().pop().unwrap();
+        let ca = create_anchor(&anchor, AnchorType::Manual, &head, None, None).unwrap();
+        src.save_anchor(&ca).unwrap();
+
+        let export = src.export(&anchor).unwrap();
+        assert!(export.verify().is_ok());
+
+        // Serialize across the "migration" boundary.
+        let json = serde_json::to_string(&export).unwrap();
+        let received: ContinuityExport = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(dst.import(&received).unwrap(), 4);
+        assert_eq!(dst.load_experiences(&anchor.id()).unwrap().len(), 4);
+        assert_eq!(dst.load_anchors(&anchor.id()).unwrap().len(), 1);
+
+        // Re-importing the same export adds nothing.
+        assert_eq!(dst.import(&received).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_continuity_export_v1_verifies_legacy_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ContinuityStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        record_chain(&store, &anchor, 2);
+
+        // A version 1 export signed its hash without a domain tag.
+        let mut export = store.export(&anchor).unwrap();
+        export.version = 1;
+        export.export_hash = export.compute_hash();
+        export.signature =
+            signing::sign_to_base64(anchor.signing_key(), export.export_hash.as_bytes());
+        assert!(export.verify().is_ok());
+
+        // The same bare signature on a version 2 export does not verify.
+        export.version = CONTINUITY_EXPORT_VERSION;
+        export.export_hash = export.compute_hash();
+        export.signature =
+            signing::sign_to_base64(anchor.signing_key(), export.export_hash.as_bytes());
+        assert!(matches!(
+            export.verify(),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_continuity_import_broken_chain_refused() {
+        let src_dir = tempfile::tempdir().unwrap();
+        let dst_dir = tempfile::tempdir().unwrap();
+        let src = ContinuityStore::new(src_dir.path()).unwrap();
+        let dst = ContinuityStore::new(dst_dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        record_chain(&src, &anchor, 3);
+        let mut export = src.export(&anchor).unwrap();
+        export.experiences[1].content_hash = "tampered".into();
+
+        assert!(matches!(
+            dst.import(&export),
+            Err(IdentityError::InvalidChain)
+        ));
+        assert!(dst.load_experiences(&anchor.id()).unwrap().is_empty());
+        assert!(dst.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_continuity_import_truncation_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ContinuityStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        record_chain(&store, &anchor, 3);
+        let mut export = store.export(&anchor).unwrap();
+        export.experiences.pop();
+
+        assert!(export.verify().is_err());
+    }
+
+    #[test]
+    fn test_continuity_import_merges_prefix() {
+        let dir_a = tempfile::tempdir().unwrap();
+        let dir_b = tempfile::tempdir().unwrap();
+        let a = ContinuityStore::new(dir_a.path()).unwrap();
+        let b = ContinuityStore::new(dir_b.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        record_chain(&a, &anchor, 2);
+        b.import(&a.export(&anchor).unwrap()).unwrap();
+
+        // The source keeps going; the destination catches up.
+        record_chain(&a, &anchor, 2);
+        assert_eq!(b.import(&a.export(&anchor).unwrap()).unwrap(), 2);
+        assert_eq!(b.load_experiences(&anchor.id()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_continuity_import_conflict_refused() {
+        let dir_a = tempfile::tempdir().unwrap();
+        let dir_b = tempfile::tempdir().unwrap();
+        let a = ContinuityStore::new(dir_a.path()).unwrap();
+        let b = ContinuityStore::new(dir_b.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        // Two independent chains for the same identity.
+        record_chain(&a, &anchor, 2);
+        record_chain(&b, &anchor, 3);
+
+        let result = b.import(&a.export(&anchor).unwrap());
+        assert!(matches!(result, Err(IdentityError::ContinuityConflict(_))));
+        assert_eq!(b.load_experiences(&anchor.id()).unwrap().len(), 3);
+    }
+}
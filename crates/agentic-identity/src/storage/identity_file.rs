@@ -24,21 +24,43 @@
 use std::path::Path;
 
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "signing")]
 use zeroize::Zeroize;
 
+#[cfg(feature = "signing")]
 use crate::crypto::{derivation, encryption};
-use crate::error::{IdentityError, Result};
-use crate::identity::{IdentityAnchor, IdentityDocument, KeyRotation};
+#[cfg(feature = "signing")]
+use crate::error::IdentityError;
+use crate::error::Result;
+use crate::identity::IdentityDocument;
+
+#[cfg(feature = "signing")]
+use super::atomic::write_atomic;
+use super::atomic::QuarantineReport;
+#[cfg(feature = "signing")]
+use super::lock::StoreLock;
+#[cfg(feature = "signing")]
+use super::transaction::Transaction;
+#[cfg(feature = "signing")]
+use crate::identity::{IdentityAnchor, KeyRotation};
 
 // ── File format constants ─────────────────────────────────────────────────────
+//
+// Reading and writing the encrypted anchor needs the `signing` feature;
+// without it only the plaintext public document can be read.
 
+#[cfg(feature = "signing")]
 const AID_VERSION: u32 = 1;
+#[cfg(feature = "signing")]
 const AID_FORMAT: &str = "aid-v1";
+#[cfg(feature = "signing")]
 const AID_ALGORITHM: &str = "chacha20-poly1305";
+#[cfg(feature = "signing")]
 const AID_KDF: &str = "argon2id";
 
 /// HKDF context string for deriving the identity encryption key from the
 /// Argon2id master key. Must remain stable across versions.
+#[cfg(feature = "signing")]
 const IDENTITY_ENCRYPTION_CONTEXT: &str = "identity-encryption";
 
 // ── On-disk structures ────────────────────────────────────────────────────────
@@ -76,8 +98,9 @@ pub struct EncryptionMetadata {
 /// This struct is serialized to JSON and then encrypted. It contains
 /// everything required to reconstruct an `IdentityAnchor` via
 /// `IdentityAnchor::from_parts`.
+#[cfg(feature = "signing")]
 #[derive(Debug, Serialize, Deserialize, Zeroize)]
-struct AnchorPrivateData {
+pub(super) struct AnchorPrivateData {
     /// Ed25519 signing key bytes encoded as base64.
     signing_key_b64: String,
     /// Creation timestamp (microseconds since Unix epoch).
@@ -88,6 +111,46 @@ struct AnchorPrivateData {
     rotation_history: Vec<KeyRotation>,
 }
 
+#[cfg(feature = "signing")]
+impl AnchorPrivateData {
+    /// Copy the private parts of `anchor`.
+    pub(super) fn from_anchor(anchor: &IdentityAnchor) -> Self {
+        let mut signing_bytes = anchor.signing_key_bytes();
+        let signing_key_b64 =
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, signing_bytes);
+        signing_bytes.zeroize();
+
+        Self {
+            signing_key_b64,
+            created_at: anchor.created_at,
+            name: anchor.name.clone(),
+            rotation_history: anchor.rotation_history.clone(),
+        }
+    }
+
+    /// Rebuild the anchor these parts were taken from.
+    pub(super) fn to_anchor(&self) -> Result<IdentityAnchor> {
+        let key_bytes_vec = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &self.signing_key_b64,
+        )
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid signing key base64: {e}")))?;
+
+        let mut key_bytes: [u8; 32] = key_bytes_vec
+            .try_into()
+            .map_err(|_| IdentityError::InvalidKey("signing key must be 32 bytes".to_string()))?;
+
+        let anchor = IdentityAnchor::from_parts(
+            &key_bytes,
+            self.created_at,
+            self.name.clone(),
+            self.rotation_history.clone(),
+        );
+        key_bytes.zeroize();
+        anchor
+    }
+}
+
 // ── Public API ────────────────────────────────────────────────────────────────
 
 /// Save an `IdentityAnchor` to a `.aid` file, encrypting private key material
@@ -95,30 +158,53 @@ struct AnchorPrivateData {
 ///
 /// The file is written atomically: the serialized JSON is written to a
 /// temporary file in the same directory and then renamed, so a concurrent
-/// reader never sees a partial write.
+/// reader never sees a partial write. The write holds the file's
+/// [`StoreLock`](super::StoreLock), so concurrent writers take turns.
 ///
 /// # Errors
 ///
 /// Returns `IdentityError::DerivationFailed` if key derivation fails,
-/// `IdentityError::EncryptionFailed` if encryption fails, or
+/// `IdentityError::EncryptionFailed` if encryption fails,
+/// `IdentityError::StoreLocked` if another process keeps the file locked, or
 /// `IdentityError::Io` for filesystem errors.
+#[cfg(feature = "signing")]
 pub fn save_identity(anchor: &IdentityAnchor, path: &Path, passphrase: &str) -> Result<()> {
+    write_locked(path, &encode_identity(anchor, passphrase)?)
+}
+
+/// Stage `anchor` in `txn` to be saved to `path` when it commits, encrypted
+/// exactly as [`save_identity`] would.
+///
+/// # Errors
+///
+/// As for [`save_identity`].
+#[cfg(feature = "signing")]
+pub fn stage_identity(
+    txn: &mut Transaction,
+    anchor: &IdentityAnchor,
+    path: &Path,
+    passphrase: &str,
+) -> Result<()> {
+    txn.stage(path, &encode_identity(anchor, passphrase)?)
+}
+
+/// Encrypt `anchor` into the JSON contents of a `.aid` file.
+///
+/// For hosts without a filesystem, such as browsers, that keep the bytes
+/// themselves; [`decode_identity`] reverses it.
+///
+/// # Errors
+///
+/// As for [`save_identity`], without the filesystem errors.
+#[cfg(feature = "signing")]
+pub fn encode_identity(anchor: &IdentityAnchor, passphrase: &str) -> Result<Vec<u8>> {
     // 1. Collect private data.
-    let mut signing_bytes = anchor.signing_key_bytes();
-    let signing_key_b64 =
-        base64::Engine::encode(&base64::engine::general_purpose::STANDARD, signing_bytes);
-    signing_bytes.zeroize();
-
-    let private_data = AnchorPrivateData {
-        signing_key_b64,
-        created_at: anchor.created_at,
-        name: anchor.name.clone(),
-        rotation_history: anchor.rotation_history.clone(),
-    };
+    let mut private_data = AnchorPrivateData::from_anchor(anchor);
 
     // 2. Serialize private data to JSON bytes.
     let mut plaintext = serde_json::to_vec(&private_data)
         .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    private_data.zeroize();
 
     // 3. Derive encryption key from passphrase.
     //    passphrase → Argon2id(passphrase, salt) → master_key
@@ -151,13 +237,9 @@ pub fn save_identity(anchor: &IdentityAnchor, path: &Path, passphrase: &str) ->
         public_document: anchor.to_document(),
     };
 
-    // 6. Write JSON to disk (atomic via temp-file-then-rename).
-    let json = serde_json::to_string_pretty(&aid_file)
-        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
-
-    write_atomic(path, json.as_bytes())?;
-
-    Ok(())
+    // 6. Serialize to JSON.
+    serde_json::to_vec_pretty(&aid_file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
 }
 
 /// Load an `IdentityAnchor` from a `.aid` file, decrypting with the given
@@ -167,11 +249,24 @@ pub fn save_identity(anchor: &IdentityAnchor, path: &Path, passphrase: &str) ->
 ///
 /// Returns `IdentityError::InvalidPassphrase` if the passphrase is wrong
 /// (ChaCha20-Poly1305 authentication will fail), `IdentityError::InvalidFileFormat`
-/// for malformed files, or `IdentityError::Io` for filesystem errors.
+/// for malformed files, `IdentityError::UnsupportedVersion` if the public
+/// document was written by a newer version, or `IdentityError::Io` for
+/// filesystem errors.
+#[cfg(feature = "signing")]
 pub fn load_identity(path: &Path, passphrase: &str) -> Result<IdentityAnchor> {
-    // 1. Read and parse the file.
-    let bytes = std::fs::read(path)?;
-    let aid_file: AidFile = serde_json::from_slice(&bytes)
+    decode_identity(&std::fs::read(path)?, passphrase)
+}
+
+/// Decrypt the contents of a `.aid` file, as produced by
+/// [`encode_identity`] or read from disk.
+///
+/// # Errors
+///
+/// As for [`load_identity`], without the filesystem errors.
+#[cfg(feature = "signing")]
+pub fn decode_identity(bytes: &[u8], passphrase: &str) -> Result<IdentityAnchor> {
+    // 1. Parse the file contents.
+    let aid_file: AidFile = serde_json::from_slice(bytes)
         .map_err(|e| IdentityError::InvalidFileFormat(format!("failed to parse .aid file: {e}")))?;
 
     // 2. Validate version and format.
@@ -181,6 +276,7 @@ pub fn load_identity(path: &Path, passphrase: &str) -> Result<IdentityAnchor> {
             aid_file.version, aid_file.format,
         )));
     }
+    super::migrate::check(&aid_file.public_document)?;
 
     // 3. Decode salt, nonce, and ciphertext from base64.
     let salt_bytes = base64::Engine::decode(
@@ -215,31 +311,51 @@ pub fn load_identity(path: &Path, passphrase: &str) -> Result<IdentityAnchor> {
     encryption_key.zeroize();
 
     // 6. Deserialize the private data.
-    let private_data: AnchorPrivateData = serde_json::from_slice(&plaintext)
+    let mut private_data: AnchorPrivateData = serde_json::from_slice(&plaintext)
         .map_err(|e| IdentityError::SerializationError(format!("anchor data: {e}")))?;
     plaintext.zeroize();
 
-    // 7. Decode the signing key bytes from base64.
-    let key_bytes_vec = base64::Engine::decode(
-        &base64::engine::general_purpose::STANDARD,
-        &private_data.signing_key_b64,
-    )
-    .map_err(|e| IdentityError::InvalidKey(format!("invalid signing key base64: {e}")))?;
+    // 7. Reconstruct the anchor.
+    let anchor = private_data.to_anchor();
+    private_data.zeroize();
+    anchor
+}
 
-    let mut key_bytes: [u8; 32] = key_bytes_vec
-        .try_into()
-        .map_err(|_| IdentityError::InvalidKey("signing key must be 32 bytes".to_string()))?;
-
-    // 8. Reconstruct the anchor.
-    let anchor = IdentityAnchor::from_parts(
-        &key_bytes,
-        private_data.created_at,
-        private_data.name,
-        private_data.rotation_history,
-    )?;
-    key_bytes.zeroize();
-
-    Ok(anchor)
+/// Re-encrypt a `.aid` file in place under `new_passphrase`.
+///
+/// The re-encrypted copy is written next to the original and loaded back
+/// with `new_passphrase` before it replaces the original, so the file is
+/// never left unreadable under either passphrase. The original stays locked
+/// throughout, so no other writer's save is lost in between.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidPassphrase` if `old_passphrase` does not
+/// decrypt the file, plus any error of [`load_identity`] or
+/// [`save_identity`].
+#[cfg(feature = "signing")]
+pub fn change_passphrase(path: &Path, old_passphrase: &str, new_passphrase: &str) -> Result<()> {
+    let _lock = StoreLock::file(path)?;
+    let anchor = load_identity(path, old_passphrase)?;
+
+    let staged = path.with_extension("aid.new");
+    save_identity(&anchor, &staged, new_passphrase)?;
+    match load_identity(&staged, new_passphrase) {
+        Ok(reloaded) if reloaded.id() == anchor.id() => {}
+        Ok(_) => {
+            let _ = std::fs::remove_file(&staged);
+            return Err(IdentityError::StorageError(
+                "re-encrypted identity does not match the original".to_string(),
+            ));
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&staged);
+            return Err(e);
+        }
+    }
+
+    std::fs::rename(&staged, path)?;
+    Ok(())
 }
 
 /// Read only the public identity document from a `.aid` file.
@@ -249,41 +365,44 @@ pub fn load_identity(path: &Path, passphrase: &str) -> Result<IdentityAnchor> {
 ///
 /// # Errors
 ///
-/// Returns `IdentityError::InvalidFileFormat` for malformed files or
-/// `IdentityError::Io` for filesystem errors.
+/// Returns `IdentityError::InvalidFileFormat` for malformed files,
+/// `IdentityError::UnsupportedVersion` for a document written by a newer
+/// version, or `IdentityError::Io` for filesystem errors.
 pub fn read_public_document(path: &Path) -> Result<IdentityDocument> {
     let bytes = std::fs::read(path)?;
-    let aid_file: AidFile = serde_json::from_slice(&bytes)
-        .map_err(|e| IdentityError::InvalidFileFormat(format!("failed to parse .aid file: {e}")))?;
-    Ok(aid_file.public_document)
+    super::migrate::read_wrapped(path, &bytes, "public_document")
+}
+
+/// Quarantine the `.aid` files in `dir` that a crash left partial, and the
+/// temporary files of interrupted saves; see [`super::atomic`].
+///
+/// # Errors
+///
+/// Returns `IdentityError::StoreLocked` if a save keeps a file locked, or
+/// `IdentityError::Io` for filesystem errors.
+pub fn recover_identity_files(dir: &Path) -> Result<QuarantineReport> {
+    super::atomic::recover_dir(dir, "aid")
 }
 
 // ── Internal helpers ──────────────────────────────────────────────────────────
 
-/// Write `data` to `path` atomically using a sibling temporary file.
+/// Write `data` to `path` atomically (see [`super::atomic`]) under the
+/// file's lock.
 ///
-/// Creates the parent directory if it does not exist. The write uses a
-/// sibling temp file and `std::fs::rename` so that a crash during the write
-/// cannot leave a partially-written file visible to readers.
-fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+/// Creates the parent directory if it does not exist.
+#[cfg(feature = "signing")]
+pub(super) fn write_locked(path: &Path, data: &[u8]) -> Result<()> {
     // Ensure parent directory exists.
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
-
-    // Write to a sibling temp file first.
-    let tmp_path = path.with_extension("aid.tmp");
-    std::fs::write(&tmp_path, data)?;
-
-    // Rename into place.
-    std::fs::rename(&tmp_path, path)?;
-
-    Ok(())
+    let _lock = StoreLock::file(path)?;
+    write_atomic(path, data)
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -377,6 +496,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_identity_bytes_round_trip() {
+        let anchor = make_anchor("in-memory");
+        let bytes = encode_identity(&anchor, "pass").expect("encode failed");
+
+        let loaded = decode_identity(&bytes, "pass").expect("decode failed");
+        assert_eq!(loaded.id(), anchor.id());
+        assert_eq!(loaded.name.as_deref(), Some("in-memory"));
+        assert!(matches!(
+            decode_identity(&bytes, "wrong"),
+            Err(IdentityError::InvalidPassphrase)
+        ));
+        assert!(matches!(
+            decode_identity(b"not json", "pass"),
+            Err(IdentityError::InvalidFileFormat(_))
+        ));
+    }
+
     #[test]
     fn test_identity_file_read_public_document() {
         let dir = tempfile::tempdir().unwrap();
@@ -415,6 +552,64 @@ mod tests {
         assert_eq!(loaded.rotation_history[1].reason, RotationReason::Scheduled);
     }
 
+    #[test]
+    fn test_identity_file_change_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("change.aid");
+
+        let anchor = make_anchor("change-test");
+        save_identity(&anchor, &path, "old").unwrap();
+
+        assert!(matches!(
+            change_passphrase(&path, "wrong", "new"),
+            Err(IdentityError::InvalidPassphrase)
+        ));
+        assert!(load_identity(&path, "old").is_ok());
+
+        change_passphrase(&path, "old", "new").unwrap();
+        assert_eq!(load_identity(&path, "new").unwrap().id(), anchor.id());
+        assert!(load_identity(&path, "old").is_err());
+        assert!(!path.with_extension("aid.new").exists());
+    }
+
+    #[test]
+    fn test_recover_identity_files_quarantines_truncated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.aid");
+        save_identity(&make_anchor("kept"), &kept, "pass").unwrap();
+        let full = std::fs::read(&kept).unwrap();
+        std::fs::write(dir.path().join("cut.aid"), &full[..full.len() - 10]).unwrap();
+        std::fs::write(dir.path().join("kept.aid.tmp"), &full[..20]).unwrap();
+
+        let report = recover_identity_files(dir.path()).unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.quarantined.len(), 2);
+        assert!(load_identity(&kept, "pass").is_ok());
+        assert!(dir.path().join("quarantine").join("cut.aid").exists());
+        assert!(!dir.path().join("kept.aid.tmp").exists());
+    }
+
+    #[test]
+    fn test_identity_file_save_waits_for_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("locked.aid");
+        let held = StoreLock::file(&path).unwrap();
+
+        let anchor = make_anchor("locked");
+        let id = anchor.id();
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || save_identity(&anchor, &path, "pass"))
+        };
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!path.exists());
+        drop(held);
+
+        writer.join().unwrap().unwrap();
+        assert_eq!(load_identity(&path, "pass").unwrap().id(), id);
+        assert!(!dir.path().join("locked.aid.lock").exists());
+    }
+
     #[test]
     fn test_identity_file_creates_parent_dir() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,267 @@
+//! Key directory — the known public keys of other identities.
+//!
+//! Trust grants name their grantee by `IdentityId` but must carry the
+//! grantee's public key, which an ID alone does not reveal. The directory
+//! maps IDs to keys. Every entry is checked on the way in: a bare key is
+//! filed under the ID derived from it, and a key taken from an identity
+//! document is accepted only if the document is self-signed and its
+//! rotation lineage leads from the ID to that key.
+//!
+//! Each entry is a JSON file named `{identity_id}.json`:
+//! ```json
+//! {
+//!     "version": 1,
+//!     "entry": { ... KeyEntry ... }
+//! }
+//! ```
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+use crate::identity::{verify_genesis, IdentityDocument, IdentityId};
+
+use super::lock::StoreLock;
+use super::scan;
+
+// ── File format constants ─────────────────────────────────────────────────────
+
+const KEY_DIRECTORY_FILE_VERSION: u32 = 1;
+
+// ── Records ───────────────────────────────────────────────────────────────────
+
+/// A registered public key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyEntry {
+    pub identity: IdentityId,
+    /// Current Ed25519 public key (base64).
+    pub public_key: String,
+    /// When the key was registered (microseconds since epoch).
+    pub registered_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+/// Wrapper written to disk for each entry.
+#[derive(Debug, Serialize, Deserialize)]
+struct KeyDirectoryFile {
+    /// Format version number.
+    version: u32,
+    /// The stored entry.
+    entry: KeyEntry,
+}
+
+// ── KeyDirectory ──────────────────────────────────────────────────────────────
+
+/// Filesystem-backed map from `IdentityId` to public key.
+pub struct KeyDirectory {
+    base_dir: PathBuf,
+}
+
+impl KeyDirectory {
+    /// Create a new `KeyDirectory` rooted at `base_dir`.
+    ///
+    /// The directory and any missing parents are created if they do not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the directory cannot be created.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        std::fs::create_dir_all(&base_dir)?;
+        Ok(Self { base_dir })
+    }
+
+    /// Register a base64 public key under the identity ID derived from it.
+    ///
+    /// This covers identities that have never rotated. For a rotated
+    /// identity, whose ID no longer derives from its key, use
+    /// [`register_document`](Self::register_document).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidKey` if the key does not decode.
+    pub fn register_key(&self, public_key: &str, label: Option<String>) -> Result<KeyEntry> {
+        let identity = IdentityId::from_public_key_base64(public_key)?;
+        self.save(KeyEntry {
+            identity,
+            public_key: public_key.to_string(),
+            registered_at: crate::time::now_micros(),
+            label,
+        })
+    }
+
+    /// Register the current key of an identity document, replacing any key
+    /// registered for its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SignatureInvalid` (or another verification
+    /// error) if the document's self-signature does not verify, and
+    /// `IdentityError::InvalidKey` if its ID does not lead to its key.
+    pub fn register_document(
+        &self,
+        doc: &IdentityDocument,
+        label: Option<String>,
+    ) -> Result<KeyEntry> {
+        doc.verify_signature()?;
+        if !verify_genesis(doc) {
+            return Err(IdentityError::InvalidKey(format!(
+                "identity document {} does not derive its ID from its keys",
+                doc.id
+            )));
+        }
+        self.save(KeyEntry {
+            identity: doc.id.clone(),
+            public_key: doc.public_key.clone(),
+            registered_at: crate::time::now_micros(),
+            label: label.or_else(|| doc.name.clone()),
+        })
+    }
+
+    /// Load the entry for `identity`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no key is registered,
+    /// `IdentityError::InvalidId` for a malformed ID, or
+    /// `IdentityError::InvalidFileFormat` for a malformed file.
+    pub fn load(&self, identity: &IdentityId) -> Result<KeyEntry> {
+        let path = self.path(identity)?;
+        if !path.exists() {
+            return Err(IdentityError::NotFound(format!(
+                "no key registered for identity {identity}"
+            )));
+        }
+        let bytes = std::fs::read(&path)?;
+        let file: KeyDirectoryFile = serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::InvalidFileFormat(format!(
+                "failed to parse key directory file {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(file.entry)
+    }
+
+    /// The registered public key (base64) of `identity`.
+    ///
+    /// # Errors
+    ///
+    /// As for [`load`](Self::load).
+    pub fn resolve(&self, identity: &IdentityId) -> Result<String> {
+        Ok(self.load(identity)?.public_key)
+    }
+
+    /// List the IDs of all registered identities.
+    ///
+    /// The returned list is not sorted in any particular order.
+    pub fn list(&self) -> Result<Vec<IdentityId>> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.base_dir)? {
+            let name = entry?.file_name();
+            if let Some(stem) = name.to_string_lossy().strip_suffix(".json") {
+                ids.push(IdentityId(stem.to_string()));
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Load every entry, sorted by identity ID. Corrupt files are skipped.
+    pub fn entries(&self) -> Result<Vec<KeyEntry>> {
+        let mut entries = scan::load_each(&self.list()?, |id| self.load(id));
+        entries.sort_by(|a, b| a.identity.0.cmp(&b.identity.0));
+        Ok(entries)
+    }
+
+    /// Remove the key registered for `identity`.
+    ///
+    /// If none is registered, this is a no-op (returns `Ok`).
+    pub fn remove(&self, identity: &IdentityId) -> Result<()> {
+        let path = self.path(identity)?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    fn save(&self, entry: KeyEntry) -> Result<KeyEntry> {
+        let file = KeyDirectoryFile {
+            version: KEY_DIRECTORY_FILE_VERSION,
+            entry,
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        let path = self.path(&file.entry.identity)?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        std::fs::write(path, json)?;
+        Ok(file.entry)
+    }
+
+    /// `{base_dir}/{identity_id}.json`, after checking the ID cannot escape
+    /// the directory.
+    fn path(&self, identity: &IdentityId) -> Result<PathBuf> {
+        IdentityId::parse(&identity.0)?;
+        Ok(self.base_dir.join(format!("{}.json", identity.0)))
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::{IdentityAnchor, RotationReason};
+
+    #[test]
+    fn test_key_directory_register_and_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory = KeyDirectory::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let entry = directory
+            .register_key(&anchor.public_key_base64(), Some("bob".into()))
+            .unwrap();
+        assert_eq!(entry.identity, anchor.id());
+        assert_eq!(
+            directory.resolve(&anchor.id()).unwrap(),
+            anchor.public_key_base64()
+        );
+        assert_eq!(directory.entries().unwrap(), vec![entry]);
+
+        let unknown = IdentityAnchor::new(None).id();
+        assert!(matches!(
+            directory.resolve(&unknown),
+            Err(IdentityError::NotFound(_))
+        ));
+        assert!(directory.resolve(&IdentityId("../x".into())).is_err());
+        assert!(directory.register_key("not-a-key", None).is_err());
+
+        directory.remove(&anchor.id()).unwrap();
+        directory.remove(&anchor.id()).unwrap();
+        assert!(directory.list().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_key_directory_checks_documents() {
+        let dir = tempfile::tempdir().unwrap();
+        let directory = KeyDirectory::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(Some("carol".into()));
+        let rotated = anchor.rotate(RotationReason::Scheduled).unwrap();
+
+        let entry = directory
+            .register_document(&rotated.to_document(), None)
+            .unwrap();
+        assert_eq!(entry.public_key, rotated.public_key_base64());
+        assert_eq!(entry.label.as_deref(), Some("carol"));
+
+        // Claiming another identity's ID breaks the self-signature.
+        let mut forged = IdentityAnchor::new(None).to_document();
+        forged.id = anchor.id();
+        assert!(directory.register_document(&forged, None).is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Advisory locks shared by every process writing the same stores.
+//!
+//! Two servers, or the CLI and a server, pointed at one `~/.agentic/` would
+//! otherwise interleave their writes: one process's read-modify-write of a
+//! spawn record or an identity file silently undoes the other's. Every
+//! store takes a [`StoreLock`] on its directory around each write, and
+//! identity files are locked individually, so writers take turns.
+//!
+//! A lock is an OS advisory lock (`flock` on Unix, `LockFileEx` on
+//! Windows) on an open lock file, held for as long as the [`StoreLock`]
+//! lives. The OS releases it when the holder closes the file or dies, so a
+//! crashed process never leaves a lock behind and a live holder keeps its
+//! lock however long it runs. The file records the holder's process ID for
+//! error messages. Readers never lock.
+//!
+//! On Unix the holder deletes the lock file just before releasing it. A
+//! waiter that then gets the lock on the deleted file sees that the path no
+//! longer names the file it locked, and starts over on the new one. On
+//! other platforms lock files are left in place.
+//!
+//! ```text
+//! {store_dir}/.lock       (while a store write is in progress)
+//! {name}.aid.lock         (while an identity file is being written)
+//! ```
+
+use std::fs::{File, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::{IdentityError, Result};
+
+/// How long a store write waits for another process's lock before failing
+/// with `IdentityError::StoreLocked`.
+pub const STORE_LOCK_TIMEOUT_SECS: u64 = 5;
+
+/// Lock file taken on a store directory.
+const DIR_LOCK_FILE: &str = ".lock";
+
+/// An exclusive, advisory lock, released when dropped.
+#[derive(Debug)]
+pub struct StoreLock {
+    path: PathBuf,
+    /// The locked file, never read; closing it releases the lock.
+    _file: File,
+}
+
+impl StoreLock {
+    /// Lock the store directory `dir`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process holds the
+    /// lock for longer than [`STORE_LOCK_TIMEOUT_SECS`], and
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn dir(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        Self::acquire(
+            dir.join(DIR_LOCK_FILE),
+            Duration::from_secs(STORE_LOCK_TIMEOUT_SECS),
+        )
+    }
+
+    /// Lock the single file `path` (which need not exist yet) through a
+    /// sibling `{path}.lock`.
+    ///
+    /// # Errors
+    ///
+    /// As for [`StoreLock::dir`].
+    pub fn file(path: &Path) -> Result<Self> {
+        let mut lock_path = path.as_os_str().to_owned();
+        lock_path.push(".lock");
+        Self::acquire(
+            PathBuf::from(lock_path),
+            Duration::from_secs(STORE_LOCK_TIMEOUT_SECS),
+        )
+    }
+
+    /// Lock the file `path`, creating it if needed and waiting up to
+    /// `timeout` for its holder to release it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if the lock is still held when
+    /// `timeout` runs out, and `IdentityError::Io` for filesystem errors.
+    pub fn acquire(path: PathBuf, timeout: Duration) -> Result<Self> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+            match file.try_lock() {
+                // The previous holder may have deleted the file between our
+                // open and our lock; then lock whatever the path names now.
+                Ok(()) if !is_same_file(&file, &path) => continue,
+                Ok(()) => {
+                    // The holder's PID is only for diagnostics.
+                    let _ = file
+                        .set_len(0)
+                        .and_then(|()| write!(&file, "{}", std::process::id()));
+                    return Ok(Self { path, _file: file });
+                }
+                Err(TryLockError::WouldBlock) => {}
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+            drop(file);
+
+            if Instant::now() >= deadline {
+                let holder = std::fs::read_to_string(&path)
+                    .ok()
+                    .filter(|pid| !pid.trim().is_empty())
+                    .map(|pid| format!(" (held by process {})", pid.trim()))
+                    .unwrap_or_default();
+                return Err(IdentityError::StoreLocked(format!(
+                    "{}{holder}",
+                    path.display()
+                )));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// The lock file this lock holds.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for StoreLock {
+    /// Delete the lock file while still holding the lock (Unix), then
+    /// release the lock by closing the file.
+    fn drop(&mut self) {
+        if cfg!(unix) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Whether `path` still names the open `file`.
+#[cfg(unix)]
+fn is_same_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(open), Ok(named)) => open.dev() == named.dev() && open.ino() == named.ino(),
+        _ => false,
+    }
+}
+
+/// Lock files are never deleted here, so the path always names the file.
+#[cfg(not(unix))]
+fn is_same_file(_file: &File, _path: &Path) -> bool {
+    true
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_released_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = StoreLock::dir(dir.path()).unwrap();
+        let path = lock.path().to_path_buf();
+        assert!(path.exists());
+        drop(lock);
+        if cfg!(unix) {
+            assert!(!path.exists());
+        }
+        StoreLock::dir(dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_contention_reports_store_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+        let _held = StoreLock::acquire(path.clone(), Duration::ZERO).unwrap();
+
+        let err = StoreLock::acquire(path, Duration::from_millis(30)).unwrap_err();
+        match err {
+            IdentityError::StoreLocked(msg) => {
+                assert!(msg.contains(&format!("held by process {}", std::process::id())))
+            }
+            other => panic!("expected StoreLocked, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_waiter_gets_lock_once_released() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+        let held = StoreLock::acquire(path.clone(), Duration::ZERO).unwrap();
+
+        let waiter = std::thread::spawn(move || StoreLock::acquire(path, Duration::from_secs(5)));
+        std::thread::sleep(Duration::from_millis(50));
+        drop(held);
+        assert!(waiter.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_old_lock_is_kept_while_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+        let _held = StoreLock::acquire(path.clone(), Duration::ZERO).unwrap();
+        let old = std::time::SystemTime::now() - Duration::from_secs(3_600);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        assert!(matches!(
+            StoreLock::acquire(path, Duration::from_millis(30)),
+            Err(IdentityError::StoreLocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_racing_for_an_abandoned_lock_is_exclusive() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        // A lock file left by a holder that died: present, but unlocked.
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lock");
+        std::fs::write(&path, "999999").unwrap();
+
+        let holders = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let (path, holders) = (path.clone(), Arc::clone(&holders));
+                std::thread::spawn(move || {
+                    let _lock = StoreLock::acquire(path, Duration::from_secs(10)).unwrap();
+                    assert_eq!(holders.fetch_add(1, Ordering::SeqCst), 0);
+                    std::thread::sleep(Duration::from_millis(5));
+                    holders.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_file_lock_is_a_sibling() {
+        let dir = tempfile::tempdir().unwrap();
+        let aid = dir.path().join("default.aid");
+        let lock = StoreLock::file(&aid).unwrap();
+        assert_eq!(lock.path(), dir.path().join("default.aid.lock"));
+    }
+}
@@ -0,0 +1,410 @@
+//! Schema versioning and migration for stored signed objects.
+//!
+//! [`ActionReceipt`], [`TrustGrant`], [`SpawnRecord`] and
+//! [`IdentityDocument`] carry a `schema_version`: the layout they were
+//! written in. Objects written before the field existed load as version 0.
+//!
+//! [`migrate`] reads an object as raw JSON, upgrades it one version at a
+//! time to [`SCHEMA_VERSION`], then deserializes it. An object written by a
+//! newer version fails with [`IdentityError::UnsupportedVersion`] rather
+//! than loading with fields dropped or misread. The stores read through
+//! [`migrate`], and [`migrate_dir`] rewrites old files under a storage root
+//! in place, holding each store's [`StoreLock`](super::StoreLock) and
+//! each identity file's own lock while it does.
+//!
+//! The schema version is not signed, and migrations only move unsigned
+//! structure, so an upgraded object verifies exactly as it did before.
+//!
+//! # Versions
+//!
+//! | Version | Change |
+//! |:---|:---|
+//! | 0 | Objects written before `schema_version` existed |
+//! | 1 | `schema_version` added; no other change |
+
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityDocument;
+use crate::receipt::ActionReceipt;
+use crate::spawn::SpawnRecord;
+use crate::trust::TrustGrant;
+
+use super::atomic::write_atomic;
+use super::lock::StoreLock;
+
+/// Schema version written on every object created by this version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An object stored with a `schema_version`.
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Name used in errors, e.g. `receipt`.
+    const KIND: &'static str;
+
+    /// The object's schema version.
+    fn schema_version(&self) -> u32;
+}
+
+impl Versioned for ActionReceipt {
+    const KIND: &'static str = "receipt";
+
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+}
+
+impl Versioned for TrustGrant {
+    const KIND: &'static str = "trust grant";
+
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+}
+
+impl Versioned for SpawnRecord {
+    const KIND: &'static str = "spawn record";
+
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+}
+
+impl Versioned for IdentityDocument {
+    const KIND: &'static str = "identity document";
+
+    fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+}
+
+/// Fail with [`IdentityError::UnsupportedVersion`] if `object` was written
+/// by a newer schema than this version understands.
+pub fn check<T: Versioned>(object: &T) -> Result<()> {
+    check_version::<T>(object.schema_version())
+}
+
+fn check_version<T: Versioned>(found: u32) -> Result<()> {
+    if found > SCHEMA_VERSION {
+        return Err(IdentityError::UnsupportedVersion {
+            kind: T::KIND.to_string(),
+            found,
+            supported: SCHEMA_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// The `schema_version` of a stored object, 0 if it predates the field.
+pub fn schema_version_of(value: &Value) -> Result<u32> {
+    match value.get("schema_version") {
+        None | Some(Value::Null) => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| {
+                IdentityError::SerializationError(format!("invalid schema_version: {v}"))
+            }),
+    }
+}
+
+/// Upgrade `value`, a stored `T`, to [`SCHEMA_VERSION`] in place.
+///
+/// Returns whether anything changed.
+///
+/// # Errors
+///
+/// Returns `IdentityError::UnsupportedVersion` for an object from a newer
+/// schema, and `IdentityError::SerializationError` if `value` is not an
+/// object or its version is malformed.
+pub fn upgrade<T: Versioned>(value: &mut Value) -> Result<bool> {
+    let found = schema_version_of(value)?;
+    check_version::<T>(found)?;
+    if found == SCHEMA_VERSION {
+        return Ok(false);
+    }
+    let object = value.as_object_mut().ok_or_else(|| {
+        IdentityError::SerializationError(format!("stored {} is not an object", T::KIND))
+    })?;
+    // Version 1 only introduced `schema_version`. A later version that moves
+    // fields adds its step here, applied when `found` is below it.
+    object.insert("schema_version".into(), SCHEMA_VERSION.into());
+    Ok(true)
+}
+
+/// Upgrade a stored `T` and deserialize it.
+///
+/// # Errors
+///
+/// As for [`upgrade`], or `IdentityError::SerializationError` if the
+/// upgraded JSON is not a valid `T`.
+pub fn migrate<T: Versioned>(mut value: Value) -> Result<T> {
+    upgrade::<T>(&mut value)?;
+    serde_json::from_value(value).map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
+/// Read a store file at `path` holding a `T` under `field`, migrating it.
+///
+/// Malformed files fail with `IdentityError::InvalidFileFormat`; files from
+/// a newer schema with `IdentityError::UnsupportedVersion`.
+pub(crate) fn read_wrapped<T: Versioned>(path: &Path, bytes: &[u8], field: &str) -> Result<T> {
+    let malformed = |e: &dyn std::fmt::Display| {
+        IdentityError::InvalidFileFormat(format!(
+            "failed to parse {} file {}: {e}",
+            T::KIND,
+            path.display()
+        ))
+    };
+    let mut file: Value = serde_json::from_slice(bytes).map_err(|e| malformed(&e))?;
+    let object = file
+        .get_mut(field)
+        .map(Value::take)
+        .ok_or_else(|| malformed(&format!("missing field `{field}`")))?;
+    migrate_or(object, |e| malformed(&e))
+}
+
+/// [`migrate`], passing any failure other than an unsupported version
+/// through `malformed`.
+pub(crate) fn migrate_or<T: Versioned>(
+    value: Value,
+    malformed: impl FnOnce(IdentityError) -> IdentityError,
+) -> Result<T> {
+    migrate(value).map_err(|e| match e {
+        IdentityError::UnsupportedVersion { .. } => e,
+        other => malformed(other),
+    })
+}
+
+// ── Migrating a storage root ──────────────────────────────────────────────────
+
+/// Result of [`migrate_dir`].
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Files rewritten at the current schema version.
+    pub upgraded: Vec<PathBuf>,
+    /// Files already at the current schema version.
+    pub current: usize,
+    /// Files left untouched because they could not be migrated, with why:
+    /// malformed, or written by a newer version.
+    pub failed: Vec<(PathBuf, String)>,
+}
+
+/// Upgrade every receipt, trust grant, spawn record and identity document
+/// under `root`, laid out as described in the [storage docs](super).
+///
+/// Each file is rewritten only if it changed, keeping its wrapper fields.
+/// A file that cannot be migrated is reported in
+/// [`MigrationReport::failed`] and left as it was; it does not stop the
+/// others.
+///
+/// # Errors
+///
+/// Returns `IdentityError::StoreLocked` if another process keeps a store
+/// locked, or `IdentityError::Io` if a directory cannot be read or an
+/// upgraded file cannot be written.
+pub fn migrate_dir(root: &Path) -> Result<MigrationReport> {
+    let mut report = MigrationReport::default();
+    let receipts = root.join("receipts");
+    {
+        let _lock = lock_existing(&receipts)?;
+        migrate_files::<ActionReceipt>(&receipts, "json", "receipt", &mut report)?;
+        migrate_files::<ActionReceipt>(&receipts.join("archive"), "json", "receipt", &mut report)?;
+    }
+    {
+        let trust = root.join("trust");
+        let _lock = lock_existing(&trust)?;
+        for sub in ["granted", "received"] {
+            migrate_files::<TrustGrant>(&trust.join(sub), "json", "grant", &mut report)?;
+        }
+    }
+    {
+        let spawn = root.join("spawn");
+        let _lock = lock_existing(&spawn)?;
+        migrate_files::<SpawnRecord>(&spawn, "json", "record", &mut report)?;
+    }
+    migrate_files::<IdentityDocument>(
+        &root.join("identity"),
+        "aid",
+        "public_document",
+        &mut report,
+    )?;
+    Ok(report)
+}
+
+/// Migrate the `T` under `field` in each `*.{extension}` file in `dir`.
+fn migrate_files<T: Versioned>(
+    dir: &Path,
+    extension: &str,
+    field: &str,
+    report: &mut MigrationReport,
+) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.retain(|p| p.is_file() && p.extension().is_some_and(|e| e == extension));
+    paths.sort();
+
+    for path in paths {
+        // Identity files are locked one by one rather than as a store.
+        let _lock = (extension == "aid")
+            .then(|| StoreLock::file(&path))
+            .transpose()?;
+        let bytes = std::fs::read(&path)?;
+        match upgrade_wrapped::<T>(&bytes, field) {
+            Ok(Some(json)) => {
+                write_atomic(&path, json.as_bytes())?;
+                report.upgraded.push(path);
+            }
+            Ok(None) => report.current += 1,
+            Err(e) => report.failed.push((path, e.to_string())),
+        }
+    }
+    Ok(())
+}
+
+/// Lock the store directory `dir` if it exists, without creating it.
+fn lock_existing(dir: &Path) -> Result<Option<StoreLock>> {
+    if dir.is_dir() {
+        StoreLock::dir(dir).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The upgraded contents of a file holding a `T` under `field`, or `None`
+/// if it is already current.
+fn upgrade_wrapped<T: Versioned>(bytes: &[u8], field: &str) -> Result<Option<String>> {
+    let mut file: Value = serde_json::from_slice(bytes)
+        .map_err(|e| IdentityError::InvalidFileFormat(e.to_string()))?;
+    let object = file
+        .get_mut(field)
+        .ok_or_else(|| IdentityError::InvalidFileFormat(format!("missing field `{field}`")))?;
+    if !upgrade::<T>(object)? {
+        return Ok(None);
+    }
+    // The upgraded object must still load before the old file is replaced.
+    serde_json::from_value::<T>(object.clone())
+        .map_err(|e| IdentityError::InvalidFileFormat(e.to_string()))?;
+    serde_json::to_string_pretty(&file)
+        .map(Some)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::verify::verify_receipt;
+    use crate::receipt::{ActionContent, ActionType};
+    use crate::storage::ReceiptStore;
+
+    fn receipt(anchor: &IdentityAnchor) -> ActionReceipt {
+        ReceiptBuilder::new(
+            anchor.id(),
+            ActionType::Decision,
+            ActionContent::new("Approved"),
+        )
+        .sign(anchor.signing_key())
+        .unwrap()
+    }
+
+    /// Rewrite the stored receipt file for `receipt` with `edit` applied.
+    fn edit_stored(dir: &Path, receipt: &ActionReceipt, edit: impl FnOnce(&mut Value)) {
+        let path = dir.join(format!("{}.json", receipt.id));
+        let mut file: Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        edit(&mut file["receipt"]);
+        std::fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_pre_versioning_objects_upgrade_and_still_verify() {
+        let anchor = IdentityAnchor::new(None);
+        let original = receipt(&anchor);
+        assert_eq!(original.schema_version, SCHEMA_VERSION);
+
+        let mut value = serde_json::to_value(&original).unwrap();
+        value.as_object_mut().unwrap().remove("schema_version");
+        assert_eq!(schema_version_of(&value).unwrap(), 0);
+
+        let migrated: ActionReceipt = migrate(value).unwrap();
+        assert_eq!(migrated.schema_version, SCHEMA_VERSION);
+        assert!(migrated.extra.is_empty());
+        assert!(verify_receipt(&migrated).unwrap().is_valid);
+
+        let doc = anchor.to_document();
+        let mut value = serde_json::to_value(&doc).unwrap();
+        value.as_object_mut().unwrap().remove("schema_version");
+        let migrated: IdentityDocument = migrate(value).unwrap();
+        assert!(migrated.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn test_future_version_rejected() {
+        let anchor = IdentityAnchor::new(None);
+        let mut value = serde_json::to_value(receipt(&anchor)).unwrap();
+        value["schema_version"] = (SCHEMA_VERSION + 1).into();
+        assert!(matches!(
+            migrate::<ActionReceipt>(value),
+            Err(IdentityError::UnsupportedVersion { found, supported, .. })
+                if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION
+        ));
+    }
+
+    #[test]
+    fn test_store_loads_old_and_rejects_future_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let old = receipt(&anchor);
+        let future = receipt(&anchor);
+        store.save(&old).unwrap();
+        store.save(&future).unwrap();
+        edit_stored(dir.path(), &old, |r| {
+            r.as_object_mut().unwrap().remove("schema_version");
+        });
+        edit_stored(dir.path(), &future, |r| r["schema_version"] = 99.into());
+
+        assert_eq!(store.load(&old.id).unwrap().schema_version, SCHEMA_VERSION);
+        assert!(matches!(
+            store.load(&future.id),
+            Err(IdentityError::UnsupportedVersion { found: 99, .. })
+        ));
+    }
+
+    #[test]
+    fn test_migrate_dir_rewrites_old_files_only() {
+        let root = tempfile::tempdir().unwrap();
+        let receipts = root.path().join("receipts");
+        let store = ReceiptStore::new(&receipts).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let (old, current, future) = (receipt(&anchor), receipt(&anchor), receipt(&anchor));
+        for r in [&old, &current, &future] {
+            store.save(r).unwrap();
+        }
+        edit_stored(&receipts, &old, |r| {
+            r.as_object_mut().unwrap().remove("schema_version");
+        });
+        edit_stored(&receipts, &future, |r| r["schema_version"] = 99.into());
+
+        let report = migrate_dir(root.path()).unwrap();
+        assert_eq!(
+            report.upgraded,
+            vec![receipts.join(format!("{}.json", old.id))]
+        );
+        assert_eq!(report.current, 1);
+        assert_eq!(report.failed.len(), 1);
+
+        let stored: Value =
+            serde_json::from_slice(&std::fs::read(&report.upgraded[0]).unwrap()).unwrap();
+        assert_eq!(stored["version"], 1);
+        assert_eq!(stored["receipt"]["schema_version"], SCHEMA_VERSION);
+        assert!(migrate_dir(root.path()).unwrap().upgraded.is_empty());
+    }
+}
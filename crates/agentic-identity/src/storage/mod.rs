@@ -1,4 +1,5 @@
-//! Storage layer for identity files, receipts, and trust grants.
+//! Storage layer for identity files, receipts, trust grants, continuity,
+//! competence history, negative capability records, and known keys.
 //!
 //! Handles `.aid` file format, encrypted private key storage, and
 //! persistence for receipts and trust grants.
@@ -10,39 +11,130 @@
 //!
 //! ```text
 //! ~/.agentic/
+//! ├── blobs/
+//! │   └── {sha256}.blob      (action payloads kept out of receipts)
+//! ├── competence/
+//! │   └── {domain}/{attempt_id}.json
+//! ├── continuity/
+//! │   └── {identity_id}.json
 //! ├── identity/
 //! │   ├── default.aid
-//! │   └── {name}.aid
+//! │   ├── {name}.aid
+//! │   └── .rekey/            (only while a rekey is in progress)
+//! ├── keys/
+//! │   └── {identity_id}.json
+//! ├── negative/
+//! │   ├── declarations/{declaration_id}.json
+//! │   └── proofs/{proof_id}.json
 //! ├── receipts/
-//! │   └── {receipt_id}.json
+//! │   ├── {receipt_id}.json
+//! │   ├── archive/{receipt_id}.json
+//! │   ├── archives/          (compacted bundles and index.json)
+//! │   ├── index/text.ndjson  (text index journal)
+//! │   ├── notary/{tip_receipt_id}.json
+//! │   └── stubs/{receipt_id}.json
 //! ├── spawn/
 //! │   └── {spawn_id}.json
+//! ├── transactions/          (journal for multi-store writes)
+//! │   ├── lock
+//! │   └── {txn_id}.json
 //! └── trust/
 //!     ├── granted/
 //!     │   └── {trust_id}.json
+//!     ├── index/text.ndjson  (text index journal)
 //!     ├── received/
 //!     │   └── {trust_id}.json
 //!     └── revocations/
 //!         └── {trust_id}.json
 //! ```
 //!
+//! While a store is being written, its directory also holds a `.lock` file,
+//! and an identity file being saved has a `{name}.aid.lock` sibling; see
+//! [`lock`]. Files a crash left partial are moved into a `quarantine/`
+//! sub-directory of the directory they were found in; see [`atomic`].
+//!
 //! # Modules
 //!
+//! - [`archive`] — compacting old receipts into signed, Merkle-rooted archive bundles.
+//! - [`atomic`] — crash-safe writes, and quarantining files a crash left partial.
+//! - [`backend`] — async `StorageBackend` trait and its Tokio implementation (`async` feature).
+//! - [`blob_store`] — content-addressed store for large action payloads.
+//! - [`competence_store`] — `CompetenceAttempt` history, grouped by domain.
+//! - [`continuity_store`] — experience chains, with replay, gap detection, and signed export/import.
 //! - [`identity_file`] — `.aid` file save/load with passphrase encryption.
+//! - [`key_directory`] — public keys of other identities, keyed by `IdentityId`.
+//! - [`lock`] — advisory lock files serializing writers across processes.
+//! - [`migrate`] — schema versions of stored signed objects, and upgrading old files.
+//! - [`negative_store`] — CRUD for `NegativeDeclaration` and `NegativeCapabilityProof` records.
+//! - [`receipt_merge`] — merging two receipt stores, reporting conflicts and forks.
 //! - [`receipt_store`] — CRUD for `ActionReceipt` records.
-//! - [`spawn_store`] — CRUD for `SpawnRecord` records.
+//! - [`receipt_stream`] — NDJSON bulk export/import of receipts.
+//! - [`rekey`] — re-encrypting every `.aid` file under a new passphrase.
+//! - [`retention`] — age/count retention policies for the receipt store.
+//! - [`sqlite_store`] — single-file SQLite store with indexed queries (`sqlite` feature).
+//! - [`spawn_store`] — CRUD and paginated queries for `SpawnRecord` records.
+//! - [`transaction`] — staging writes across stores and applying them atomically.
 //! - [`trust_store`] — CRUD for `TrustGrant` and `Revocation` records.
+//! - [`vault`] — many identities in one encrypted file under one passphrase.
 
+pub mod archive;
+pub mod atomic;
+#[cfg(feature = "async")]
+pub mod backend;
+pub mod blob_store;
+pub mod competence_store;
+pub mod continuity_store;
 pub mod identity_file;
+pub mod key_directory;
+pub mod lock;
+pub mod migrate;
+pub mod negative_store;
+pub mod receipt_merge;
 pub mod receipt_store;
+pub mod receipt_stream;
+#[cfg(feature = "signing")]
+pub mod rekey;
+pub mod retention;
+mod scan;
 pub mod spawn_store;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
+pub mod transaction;
 pub mod trust_store;
+#[cfg(feature = "signing")]
+pub mod vault;
 
 // Re-export the primary types so callers can write `storage::ReceiptStore`
 // without reaching into sub-modules.
+pub use archive::{archive_summary, verify_archived, ArchiveSummary, ReceiptArchive};
+pub use atomic::QuarantineReport;
+#[cfg(feature = "async")]
+pub use backend::{StorageBackend, TokioFsBackend};
+pub use blob_store::BlobStore;
+pub use competence_store::CompetenceStore;
+pub use continuity_store::{ChainReplay, ContinuityExport, ContinuityStore};
+#[cfg(feature = "signing")]
+pub use identity_file::{
+    change_passphrase, decode_identity, encode_identity, load_identity, save_identity,
+    stage_identity,
+};
 pub use identity_file::{
-    load_identity, read_public_document, save_identity, AidFile, EncryptionMetadata,
+    read_public_document, recover_identity_files, AidFile, EncryptionMetadata,
 };
-pub use receipt_store::ReceiptStore;
-pub use spawn_store::SpawnStore;
+pub use key_directory::{KeyDirectory, KeyEntry};
+pub use lock::StoreLock;
+pub use migrate::{migrate_dir, MigrationReport};
+pub use negative_store::NegativeStore;
+pub use receipt_merge::{MergeReport, ReceiptFork};
+pub use receipt_store::{ChainWalk, NotaryOutcome, ReceiptStore};
+pub use receipt_stream::{read_ndjson, ReceiptExportFilter, StreamSummary};
+#[cfg(feature = "signing")]
+pub use rekey::{pending_rekey, rekey_identities, RekeyReport};
+pub use retention::{ReceiptStub, RetentionPolicy, RetentionReport};
+pub use spawn_store::{SpawnPage, SpawnQuery, SpawnStore};
+#[cfg(feature = "sqlite")]
+pub use sqlite_store::{ReceiptQuery, SqliteStore};
+pub use transaction::{RecoveryReport, Transaction};
 pub use trust_store::TrustStore;
+#[cfg(feature = "signing")]
+pub use vault::{read_vault_documents, IdentityVault, VaultFile};
@@ -0,0 +1,323 @@
+//! Negative declaration and proof persistence.
+//!
+//! Stores `NegativeDeclaration` and `NegativeCapabilityProof` records as JSON
+//! files under a directory tree:
+//!
+//! ```text
+//! {base_dir}/
+//! ├── declarations/     — voluntary self-imposed restrictions
+//! │   └── {declaration_id}.json
+//! └── proofs/           — generated impossibility proofs
+//!     └── {proof_id}.json
+//! ```
+//!
+//! File format for declarations:
+//! ```json
+//! { "version": 1, "declaration": { ... NegativeDeclaration ... } }
+//! ```
+//!
+//! File format for proofs:
+//! ```json
+//! { "version": 1, "proof": { ... NegativeCapabilityProof ... } }
+//! ```
+//!
+//! Declarations cannot be deleted through the store: a restriction an
+//! identity has declared keeps binding every later check. Writes hold the
+//! base directory's [`StoreLock`](super::StoreLock).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+use crate::negative::{
+    DeclarationId, NegativeCapabilityProof, NegativeDeclaration, NegativeProofId,
+};
+
+use super::lock::StoreLock;
+use super::scan;
+
+// ── File format constants ─────────────────────────────────────────────────────
+
+const NEGATIVE_FILE_VERSION: u32 = 1;
+
+// ── On-disk structures ────────────────────────────────────────────────────────
+
+/// Wrapper written to disk for each declaration.
+#[derive(Debug, Serialize, Deserialize)]
+struct DeclarationFile {
+    /// Format version number.
+    version: u32,
+    /// The stored declaration.
+    declaration: NegativeDeclaration,
+}
+
+/// Wrapper written to disk for each proof.
+#[derive(Debug, Serialize, Deserialize)]
+struct ProofFile {
+    /// Format version number.
+    version: u32,
+    /// The stored proof.
+    proof: NegativeCapabilityProof,
+}
+
+// ── Sub-directory names ───────────────────────────────────────────────────────
+
+const DECLARATIONS_DIR: &str = "declarations";
+const PROOFS_DIR: &str = "proofs";
+
+// ── NegativeStore ─────────────────────────────────────────────────────────────
+
+/// Filesystem-backed store for `NegativeDeclaration` and
+/// `NegativeCapabilityProof` records.
+pub struct NegativeStore {
+    base_dir: PathBuf,
+}
+
+impl NegativeStore {
+    /// Create a new `NegativeStore` rooted at `base_dir`.
+    ///
+    /// Creates `declarations/` and `proofs/` sub-directories if they do not
+    /// already exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if any directory cannot be created.
+    pub fn new(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        std::fs::create_dir_all(base_dir.join(DECLARATIONS_DIR))?;
+        std::fs::create_dir_all(base_dir.join(PROOFS_DIR))?;
+        Ok(Self { base_dir })
+    }
+
+    // ── Declaration persistence ───────────────────────────────────────────────
+
+    /// Persist a declaration to `declarations/`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if serialization fails, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn save_declaration(&self, declaration: &NegativeDeclaration) -> Result<()> {
+        let file = DeclarationFile {
+            version: NEGATIVE_FILE_VERSION,
+            declaration: declaration.clone(),
+        };
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        write_file(
+            &self.path(DECLARATIONS_DIR, &declaration.declaration_id.0),
+            &file,
+        )
+    }
+
+    /// Load a declaration by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no such declaration is stored,
+    /// `IdentityError::InvalidFileFormat` for malformed files, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn load_declaration(&self, id: &DeclarationId) -> Result<NegativeDeclaration> {
+        let path = self.path(DECLARATIONS_DIR, &id.0);
+        if !path.exists() {
+            return Err(IdentityError::NotFound(format!(
+                "negative declaration not found: {id}"
+            )));
+        }
+        let file: DeclarationFile = read_file(&path, "negative declaration")?;
+        Ok(file.declaration)
+    }
+
+    /// List the IDs of all stored declarations.
+    ///
+    /// The returned list is not sorted in any particular order.
+    pub fn list_declarations(&self) -> Result<Vec<DeclarationId>> {
+        Ok(self
+            .list_ids(DECLARATIONS_DIR)?
+            .into_iter()
+            .map(DeclarationId)
+            .collect())
+    }
+
+    /// Load every declaration made by `identity`, oldest first.
+    ///
+    /// Corrupt files are skipped.
+    pub fn declarations_for(&self, identity: &IdentityId) -> Result<Vec<NegativeDeclaration>> {
+        let ids = self.list_declarations()?;
+        let mut declarations: Vec<_> = scan::load_each(&ids, |id| self.load_declaration(id))
+            .into_iter()
+            .filter(|d| &d.identity == identity)
+            .collect();
+        declarations.sort_by(|a, b| {
+            (a.declared_at, &a.declaration_id.0).cmp(&(b.declared_at, &b.declaration_id.0))
+        });
+        Ok(declarations)
+    }
+
+    // ── Proof persistence ─────────────────────────────────────────────────────
+
+    /// Persist a proof to `proofs/`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::SerializationError` if serialization fails, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn save_proof(&self, proof: &NegativeCapabilityProof) -> Result<()> {
+        let file = ProofFile {
+            version: NEGATIVE_FILE_VERSION,
+            proof: proof.clone(),
+        };
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        write_file(&self.path(PROOFS_DIR, &proof.proof_id.0), &file)
+    }
+
+    /// Load a proof by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no such proof is stored,
+    /// `IdentityError::InvalidFileFormat` for malformed files, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn load_proof(&self, id: &NegativeProofId) -> Result<NegativeCapabilityProof> {
+        let path = self.path(PROOFS_DIR, &id.0);
+        if !path.exists() {
+            return Err(IdentityError::NotFound(format!(
+                "negative proof not found: {id}"
+            )));
+        }
+        let file: ProofFile = read_file(&path, "negative proof")?;
+        Ok(file.proof)
+    }
+
+    /// List the IDs of all stored proofs.
+    ///
+    /// The returned list is not sorted in any particular order.
+    pub fn list_proofs(&self) -> Result<Vec<NegativeProofId>> {
+        Ok(self
+            .list_ids(PROOFS_DIR)?
+            .into_iter()
+            .map(NegativeProofId)
+            .collect())
+    }
+
+    /// Delete the file for a proof.
+    ///
+    /// If no file exists for `id`, this is a no-op (returns `Ok`).
+    pub fn delete_proof(&self, id: &NegativeProofId) -> Result<()> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        match std::fs::remove_file(self.path(PROOFS_DIR, &id.0)) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    /// Build the filesystem path for a record: `{base_dir}/{sub}/{id}.json`.
+    fn path(&self, sub_dir: &str, id: &str) -> PathBuf {
+        self.base_dir.join(sub_dir).join(format!("{id}.json"))
+    }
+
+    /// Read a directory listing and extract IDs from `{id}.json` filenames.
+    fn list_ids(&self, sub_dir: &str) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+
+        for entry in std::fs::read_dir(self.base_dir.join(sub_dir))? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+
+            if let Some(stem) = name_str.strip_suffix(".json") {
+                ids.push(stem.to_string());
+            }
+        }
+
+        Ok(ids)
+    }
+}
+
+/// Serialize `file` as pretty JSON and write it to `path`.
+fn write_file<T: Serialize>(path: &Path, file: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Read and deserialize a `kind` file from `path`.
+fn read_file<T: for<'de> Deserialize<'de>>(path: &Path, kind: &str) -> Result<T> {
+    let bytes = std::fs::read(path)?;
+    serde_json::from_slice(&bytes).map_err(|e| {
+        IdentityError::InvalidFileFormat(format!(
+            "failed to parse {kind} file {}: {e}",
+            path.display()
+        ))
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::negative::{declare_cannot, prove_declared};
+
+    #[test]
+    fn test_negative_store_declarations_by_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = NegativeStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let other = IdentityAnchor::new(None);
+
+        let first =
+            declare_cannot(&anchor, vec!["deploy:prod".into()], "policy", true, vec![]).unwrap();
+        let second =
+            declare_cannot(&anchor, vec!["net:*".into()], "sandboxed", false, vec![]).unwrap();
+        let foreign =
+            declare_cannot(&other, vec!["deploy:prod".into()], "policy", false, vec![]).unwrap();
+        for d in [&second, &first, &foreign] {
+            store.save_declaration(d).unwrap();
+        }
+
+        assert_eq!(store.list_declarations().unwrap().len(), 3);
+        let mine = store.declarations_for(&anchor.id()).unwrap();
+        assert_eq!(mine.len(), 2);
+        assert!(mine.iter().all(|d| d.identity == anchor.id()));
+        assert!(mine[0].declared_at <= mine[1].declared_at);
+        assert_eq!(
+            store
+                .load_declaration(&first.declaration_id)
+                .unwrap()
+                .cannot_do,
+            vec!["deploy:prod".to_string()]
+        );
+        assert!(matches!(
+            store.load_declaration(&DeclarationId("adecl_missing".into())),
+            Err(IdentityError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_negative_store_proofs_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = NegativeStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let declaration =
+            declare_cannot(&anchor, vec!["deploy:*".into()], "policy", true, vec![]).unwrap();
+        let proof = prove_declared(&anchor, "deploy:prod", &[declaration]).unwrap();
+        store.save_proof(&proof).unwrap();
+
+        assert_eq!(store.list_proofs().unwrap(), vec![proof.proof_id.clone()]);
+        let loaded = store.load_proof(&proof.proof_id).unwrap();
+        assert_eq!(loaded.proof_hash, proof.proof_hash);
+        assert_eq!(loaded.reason, proof.reason);
+
+        store.delete_proof(&proof.proof_id).unwrap();
+        store.delete_proof(&proof.proof_id).unwrap();
+        assert!(store.load_proof(&proof.proof_id).is_err());
+    }
+}
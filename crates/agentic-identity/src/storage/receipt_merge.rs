@@ -0,0 +1,189 @@
+//! Merging receipt stores — combining the receipts an agent produced on
+//! two machines.
+//!
+//! [`ReceiptStore::merge_from`] copies in every receipt the target lacks
+//! and never overwrites one it has. Because a receipt's ID is derived from
+//! its content, two different receipts under one ID mean one of them was
+//! altered; the merge keeps the target's copy and reports the conflict.
+//!
+//! Two machines continuing the same chain produce a fork: one predecessor
+//! with several successors. The merge stores every branch and lists each
+//! fork in the report, leaving the choice of a canonical branch to the
+//! operator.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+use crate::receipt::{ActionReceipt, ReceiptId};
+
+use super::receipt_store::ReceiptStore;
+
+/// A receipt with more than one successor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptFork {
+    /// The receipt each branch chains to.
+    pub predecessor: ReceiptId,
+    /// The first receipt of each branch, sorted by ID.
+    pub successors: Vec<ReceiptId>,
+}
+
+/// Result of [`ReceiptStore::merge_from`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// Receipts copied into the target, sorted by ID.
+    pub imported: Vec<ReceiptId>,
+    /// Receipts the target already held with identical content.
+    pub duplicates: usize,
+    /// IDs held by both stores with different content. The target's copy
+    /// was kept.
+    pub conflicts: Vec<ReceiptId>,
+    /// Every fork in the target after the merge, sorted by predecessor.
+    pub forks: Vec<ReceiptFork>,
+}
+
+impl MergeReport {
+    /// Nothing needs an operator's attention: no conflicts and no forks.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty() && self.forks.is_empty()
+    }
+}
+
+impl ReceiptStore {
+    /// Copy every receipt in `other` that this store lacks into this store.
+    ///
+    /// Receipts already present with identical content are counted as
+    /// duplicates, so merging the same store twice changes nothing. An ID
+    /// present in both with different content is a conflict: this store's
+    /// copy is kept and the ID reported. Forks are reported, never
+    /// resolved. Corrupt files in `other` are skipped, as in
+    /// [`load_all`](Self::load_all).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if either store cannot be read or a
+    /// receipt cannot be written.
+    pub fn merge_from(&self, other: &ReceiptStore) -> Result<MergeReport> {
+        let mut report = MergeReport::default();
+
+        for receipt in other.load_all()? {
+            match self.load(&receipt.id) {
+                Ok(existing) => {
+                    if same_content(&existing, &receipt)? {
+                        report.duplicates += 1;
+                    } else {
+                        report.conflicts.push(receipt.id);
+                    }
+                }
+                Err(IdentityError::NotFound(_)) => {
+                    self.save(&receipt)?;
+                    report.imported.push(receipt.id);
+                }
+                // An unreadable local copy is still a receipt we must not
+                // overwrite.
+                Err(_) => report.conflicts.push(receipt.id),
+            }
+        }
+
+        report.forks = self.forks()?;
+        Ok(report)
+    }
+
+    /// Every receipt in the store with more than one successor.
+    fn forks(&self) -> Result<Vec<ReceiptFork>> {
+        let mut successors: BTreeMap<String, Vec<ReceiptId>> = BTreeMap::new();
+        for receipt in self.load_all()? {
+            if let Some(prev) = receipt.previous_receipt {
+                successors.entry(prev.0).or_default().push(receipt.id);
+            }
+        }
+        Ok(successors
+            .into_iter()
+            .filter(|(_, next)| next.len() > 1)
+            .map(|(prev, next)| ReceiptFork {
+                predecessor: ReceiptId(prev),
+                successors: next,
+            })
+            .collect())
+    }
+}
+
+fn same_content(a: &ActionReceipt, b: &ActionReceipt) -> Result<bool> {
+    let encode = |r: &ActionReceipt| {
+        serde_json::to_vec(r).map_err(|e| IdentityError::SerializationError(e.to_string()))
+    };
+    Ok(encode(a)? == encode(b)?)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::{ActionContent, ActionType};
+
+    fn sign(anchor: &IdentityAnchor, text: &str, prev: Option<&ActionReceipt>) -> ActionReceipt {
+        let mut builder =
+            ReceiptBuilder::new(anchor.id(), ActionType::Decision, ActionContent::new(text));
+        if let Some(prev) = prev {
+            builder = builder.chain_to(prev.id.clone());
+        }
+        builder.sign(anchor.signing_key()).unwrap()
+    }
+
+    #[test]
+    fn test_merge_imports_and_reports_fork() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ours = ReceiptStore::new(tmp.path().join("ours")).unwrap();
+        let theirs = ReceiptStore::new(tmp.path().join("theirs")).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let root = sign(&anchor, "root", None);
+        let local = sign(&anchor, "on laptop", Some(&root));
+        let remote = sign(&anchor, "on server", Some(&root));
+        ours.save(&root).unwrap();
+        ours.save(&local).unwrap();
+        theirs.save(&root).unwrap();
+        theirs.save(&remote).unwrap();
+
+        let report = ours.merge_from(&theirs).unwrap();
+        assert_eq!(report.imported, vec![remote.id.clone()]);
+        assert_eq!(report.duplicates, 1);
+        assert!(report.conflicts.is_empty());
+        assert_eq!(report.forks.len(), 1);
+        assert_eq!(report.forks[0].predecessor, root.id);
+        let mut branches = vec![local.id.clone(), remote.id.clone()];
+        branches.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(report.forks[0].successors, branches);
+        assert!(!report.is_clean());
+
+        // Merging again imports nothing.
+        let again = ours.merge_from(&theirs).unwrap();
+        assert!(again.imported.is_empty());
+        assert_eq!(again.duplicates, 2);
+        assert_eq!(ours.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_merge_never_overwrites_conflicting_receipt() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ours = ReceiptStore::new(tmp.path().join("ours")).unwrap();
+        let theirs = ReceiptStore::new(tmp.path().join("theirs")).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let genuine = sign(&anchor, "approve", None);
+        let mut altered = genuine.clone();
+        altered.action = ActionContent::new("reject");
+        ours.save(&genuine).unwrap();
+        theirs.save(&altered).unwrap();
+
+        let report = ours.merge_from(&theirs).unwrap();
+        assert_eq!(report.conflicts, vec![genuine.id.clone()]);
+        assert!(report.imported.is_empty());
+        assert_eq!(
+            ours.load(&genuine.id).unwrap().action.description,
+            "approve"
+        );
+    }
+}
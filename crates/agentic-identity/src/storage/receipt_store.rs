@@ -10,14 +10,46 @@
 //!     "receipt": { ... ActionReceipt ... }
 //! }
 //! ```
+//!
+//! Notary anchors live under `{base_dir}/notary/{tip_id}.json`, one per
+//! chain tip, in the same `{version, anchor}` wrapper.
+//!
+//! Retention (see [`super::retention`]) moves pruned receipts to
+//! `{base_dir}/archive/`, where they are no longer listed but still load
+//! by ID, and writes stubs to `{base_dir}/stubs/{id}.json` as
+//! `{version, stub}`.
+//!
+//! `{base_dir}/index/text.ndjson` is the [`TextIndex`] journal over receipt
+//! descriptions and data, appended to on every save and delete.
+//!
+//! Compaction (see [`super::archive`]) folds old receipts into signed
+//! bundles under `{base_dir}/archives/`; they are no longer listed, but
+//! still load by ID.
+//!
+//! Every write, including retention and notary anchors, holds the
+//! directory's [`StoreLock`](super::StoreLock) and replaces its file
+//! atomically. [`ReceiptStore::recover`] quarantines files a crash left
+//! partial.
 
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::{IdentityError, Result};
+use crate::index::{receipt_terms, TextIndex};
+use crate::receipt::chain::verify_chain;
+use crate::receipt::notary::{NotaryAnchor, NotaryHook};
 use crate::receipt::{ActionReceipt, ReceiptId};
 
+use super::archive::{self, ReceiptArchive, ARCHIVES_DIR};
+use super::atomic::{self, write_atomic, QuarantineReport};
+use super::lock::StoreLock;
+use super::migrate;
+use super::retention::{ReceiptStub, RetentionPolicy, RetentionReport};
+use super::scan;
+use super::transaction::Transaction;
+
 // ── File format constants ─────────────────────────────────────────────────────
 
 const RECEIPT_FILE_VERSION: u32 = 1;
@@ -33,13 +65,51 @@ struct ReceiptFile {
     receipt: ActionReceipt,
 }
 
+/// Wrapper written to disk for each notary anchor.
+#[derive(Debug, Serialize, Deserialize)]
+struct NotaryAnchorFile {
+    /// Format version number.
+    version: u32,
+    /// The stored anchor.
+    anchor: NotaryAnchor,
+}
+
+/// Wrapper written to disk for each receipt stub.
+#[derive(Debug, Serialize, Deserialize)]
+struct ReceiptStubFile {
+    /// Format version number.
+    version: u32,
+    /// The stored stub.
+    stub: ReceiptStub,
+}
+
+/// Result of [`ReceiptStore::anchor_to_notary`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotaryOutcome {
+    /// The root was published by this call.
+    Published(NotaryAnchor),
+    /// The same root and tip were already published; nothing was sent.
+    AlreadyAnchored(NotaryAnchor),
+}
+
+impl NotaryOutcome {
+    /// The anchor, whether newly published or pre-existing.
+    pub fn anchor(&self) -> &NotaryAnchor {
+        match self {
+            Self::Published(anchor) | Self::AlreadyAnchored(anchor) => anchor,
+        }
+    }
+}
+
 // ── ReceiptStore ──────────────────────────────────────────────────────────────
 
 /// Filesystem-backed store for `ActionReceipt` records.
 ///
 /// Each receipt is written to a dedicated JSON file named by its ID.
-/// The store is safe for single-process use; concurrent writes from
-/// multiple processes are not coordinated.
+/// Writes from several processes are serialized by the directory's
+/// [`StoreLock`]; a writer that cannot take it within
+/// [`STORE_LOCK_TIMEOUT_SECS`](super::lock::STORE_LOCK_TIMEOUT_SECS) seconds fails
+/// with `IdentityError::StoreLocked`.
 pub struct ReceiptStore {
     base_dir: PathBuf,
 }
@@ -61,38 +131,52 @@ impl ReceiptStore {
     /// Persist a receipt to disk.
     ///
     /// Writes `{base_dir}/{receipt_id}.json`. Any existing file with the same
-    /// ID is overwritten.
+    /// ID is overwritten. The receipt's terms are appended to the text index.
     ///
     /// # Errors
     ///
     /// Returns `IdentityError::SerializationError` if JSON serialization fails,
     /// or `IdentityError::Io` for filesystem errors.
     pub fn save(&self, receipt: &ActionReceipt) -> Result<()> {
-        let file = ReceiptFile {
-            version: RECEIPT_FILE_VERSION,
-            receipt: receipt.clone(),
-        };
-
-        let json = serde_json::to_string_pretty(&file)
-            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
-
-        let path = self.receipt_path(&receipt.id);
-        std::fs::write(&path, json.as_bytes())?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        self.write_receipt(receipt)
+    }
 
-        Ok(())
+    /// Stage `receipt` in `txn` to be saved when it commits.
+    ///
+    /// # Errors
+    ///
+    /// As for [`ReceiptStore::save`].
+    pub fn stage(&self, txn: &mut Transaction, receipt: &ActionReceipt) -> Result<()> {
+        txn.stage_in_store(
+            &self.base_dir,
+            &self.receipt_path(&receipt.id),
+            encode_receipt(receipt)?.as_bytes(),
+        )
     }
 
-    /// Load a receipt by its ID.
+    /// Load a receipt by its ID, from its own file, the file retention moved
+    /// it to, or else the archive it was compacted into.
     ///
     /// # Errors
     ///
-    /// Returns `IdentityError::NotFound` if no file exists for `id`,
-    /// `IdentityError::InvalidFileFormat` if the file cannot be parsed, or
-    /// `IdentityError::Io` for other filesystem errors.
+    /// Returns `IdentityError::NotFound` if the store has no such receipt,
+    /// `IdentityError::InvalidFileFormat` if the file cannot be parsed,
+    /// `IdentityError::UnsupportedVersion` if a newer version wrote it, or
+    /// `IdentityError::Io` for other filesystem errors. Receipts in an older
+    /// schema are upgraded as they load; see [`super::migrate`].
     pub fn load(&self, id: &ReceiptId) -> Result<ActionReceipt> {
-        let path = self.receipt_path(id);
+        let mut path = self.receipt_path(id);
+        if !path.exists() {
+            path = self.retired_path(id);
+        }
 
         if !path.exists() {
+            if let Some(archive) = self.find_archive(id)? {
+                if let Some(receipt) = archive.get(id) {
+                    return Ok(receipt.clone());
+                }
+            }
             return Err(IdentityError::NotFound(format!(
                 "receipt not found: {}",
                 id
@@ -100,14 +184,7 @@ impl ReceiptStore {
         }
 
         let bytes = std::fs::read(&path)?;
-        let file: ReceiptFile = serde_json::from_slice(&bytes).map_err(|e| {
-            IdentityError::InvalidFileFormat(format!(
-                "failed to parse receipt file {}: {e}",
-                path.display()
-            ))
-        })?;
-
-        Ok(file.receipt)
+        migrate::read_wrapped(&path, &bytes, "receipt")
     }
 
     /// List the IDs of all receipts stored in this store.
@@ -133,6 +210,20 @@ impl ReceiptStore {
         Ok(ids)
     }
 
+    /// Load all receipts from the store, ordered by ID.
+    ///
+    /// Corrupt files are skipped. With the `parallel` feature the files are
+    /// read on a bounded worker pool; the result is the same either way.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the directory cannot be read.
+    pub fn load_all(&self) -> Result<Vec<ActionReceipt>> {
+        let mut ids = self.list()?;
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(scan::load_each(&ids, |id| self.load(id)))
+    }
+
     /// Delete the file for a receipt by its ID.
     ///
     /// If no file exists for `id`, this is a no-op (returns `Ok`).
@@ -142,26 +233,528 @@ impl ReceiptStore {
     /// Returns `IdentityError::Io` for filesystem errors other than
     /// "not found".
     pub fn delete(&self, id: &ReceiptId) -> Result<()> {
-        let path = self.receipt_path(id);
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        self.remove_receipt(id)
+    }
 
-        match std::fs::remove_file(&path) {
-            Ok(()) => Ok(()),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
-            Err(e) => Err(IdentityError::Io(e)),
+    /// Quarantine the receipts, archived receipts, stubs and notary anchors
+    /// a crash left partial; see [`super::atomic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process keeps the
+    /// store locked, or `IdentityError::Io` for filesystem errors.
+    pub fn recover(&self) -> Result<QuarantineReport> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut report = atomic::recover_locked(&self.base_dir, "json")?;
+        for sub_dir in ["archive", ARCHIVES_DIR, "stubs", "notary"] {
+            report.merge(atomic::recover_locked(
+                &self.base_dir.join(sub_dir),
+                "json",
+            )?);
+        }
+        Ok(report)
+    }
+
+    /// Open the text index over stored receipts.
+    ///
+    /// The journal is reconciled with the directory first, so receipts
+    /// written without [`save`](Self::save) (staged in a transaction, or
+    /// copied in) are indexed and deleted ones dropped. Only those receipts
+    /// are loaded; the rest of the index comes from the journal.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors.
+    pub fn text_index(&self) -> Result<TextIndex> {
+        let stored = self.list()?.into_iter().map(|id| id.0).collect();
+        TextIndex::open_synced(&self.text_index_path(), stored, |id| {
+            self.load(&ReceiptId(id.to_string()))
+                .ok()
+                .map(|r| receipt_terms(&r))
+        })
+    }
+
+    // ── Notarization ──────────────────────────────────────────────────────────
+
+    /// Load the chain ending at `tip`, oldest first, by following
+    /// `previous_receipt` links back to the first receipt.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if any linked receipt is missing, or
+    /// `IdentityError::InvalidChain` if the links loop.
+    pub fn load_chain(&self, tip: &ReceiptId) -> Result<Vec<ActionReceipt>> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(tip.clone());
+
+        while let Some(id) = next {
+            if !seen.insert(id.clone()) {
+                return Err(IdentityError::InvalidChain);
+            }
+            let receipt = self.load(&id)?;
+            next = receipt.previous_receipt.clone();
+            chain.push(receipt);
+        }
+
+        chain.reverse();
+        Ok(chain)
+    }
+
+    /// Walk the chain ending at `tip`, newest first, loading one receipt at a
+    /// time.
+    ///
+    /// Unlike [`load_chain`](Self::load_chain) this holds only the receipt
+    /// IDs seen so far, so it suits [`verify_chain_stream`] on long chains. A
+    /// missing or unreadable receipt is yielded as an error and ends the
+    /// walk, as does a link back to an already-visited receipt
+    /// (`IdentityError::InvalidChain`).
+    ///
+    /// [`verify_chain_stream`]: crate::receipt::verify_chain_stream
+    pub fn walk_chain(&self, tip: &ReceiptId) -> ChainWalk<'_> {
+        ChainWalk {
+            store: self,
+            next: Some(tip.clone()),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Compute the Merkle root of the chain ending at `up_to` and publish it
+    /// through `hook`, storing the notary's proof alongside the root.
+    ///
+    /// The anchor (root, tip, covered receipt IDs) is persisted *before* the
+    /// hook is called, so if publishing fails the computed root is kept and a
+    /// later call retries it. Re-anchoring a tip whose root is already
+    /// published returns [`NotaryOutcome::AlreadyAnchored`] without calling
+    /// the hook.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidChain` if the chain does not verify, if
+    /// a stored anchor for `up_to` has a different root (the receipts changed
+    /// after anchoring), or if the notary attests to a different root. Hook
+    /// errors are returned unchanged.
+    pub fn anchor_to_notary(
+        &self,
+        hook: &dyn NotaryHook,
+        up_to: &ReceiptId,
+    ) -> Result<NotaryOutcome> {
+        let chain = self.load_chain(up_to)?;
+        verify_chain(&chain)?;
+        let computed = NotaryAnchor::for_chain(&chain)?;
+
+        let mut anchor = match self.load_notary_anchor(up_to)? {
+            Some(existing) if existing.root != computed.root => {
+                return Err(IdentityError::InvalidChain);
+            }
+            Some(existing) if existing.is_published() => {
+                return Ok(NotaryOutcome::AlreadyAnchored(existing));
+            }
+            Some(pending) => pending,
+            None => {
+                self.save_notary_anchor(&computed)?;
+                computed
+            }
+        };
+
+        let mut root = [0u8; 32];
+        hex::decode_to_slice(&anchor.root, &mut root)
+            .map_err(|e| IdentityError::InvalidFileFormat(format!("bad anchor root: {e}")))?;
+
+        let receipt = hook.publish(root)?;
+        if receipt.root != anchor.root {
+            return Err(IdentityError::InvalidChain);
+        }
+
+        anchor.notary_receipt = Some(receipt);
+        self.save_notary_anchor(&anchor)?;
+        Ok(NotaryOutcome::Published(anchor))
+    }
+
+    /// Load the notary anchor stored for chain tip `tip`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if the file cannot be
+    /// parsed, or `IdentityError::Io` for filesystem errors.
+    pub fn load_notary_anchor(&self, tip: &ReceiptId) -> Result<Option<NotaryAnchor>> {
+        let path = self.notary_path(tip);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let bytes = std::fs::read(&path)?;
+        let file: NotaryAnchorFile = serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::InvalidFileFormat(format!(
+                "failed to parse notary anchor {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Some(file.anchor))
+    }
+
+    /// Load every stored notary anchor.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if an anchor file cannot be
+    /// parsed, or `IdentityError::Io` for filesystem errors.
+    pub fn list_notary_anchors(&self) -> Result<Vec<NotaryAnchor>> {
+        let dir = self.base_dir.join("notary");
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut anchors = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            if let Some(stem) = name.to_string_lossy().strip_suffix(".json") {
+                if let Some(anchor) = self.load_notary_anchor(&ReceiptId(stem.to_string()))? {
+                    anchors.push(anchor);
+                }
+            }
+        }
+        Ok(anchors)
+    }
+
+    // ── Retention ─────────────────────────────────────────────────────────────
+
+    /// Prune receipts outside `policy`, judging age against `now`
+    /// (microseconds since epoch).
+    ///
+    /// Receipts bracketing a notary anchor and receipts in
+    /// `policy.protected` are kept even when outside the policy. With
+    /// `policy.keep_chain_tips`, every pruned receipt that a retained receipt
+    /// names as `previous_receipt` is replaced by a [`ReceiptStub`]; the stub
+    /// is written before the receipt is removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if a receipt or notary
+    /// anchor cannot be parsed (nothing is pruned in that case), or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn apply_retention(&self, policy: &RetentionPolicy, now: u64) -> Result<RetentionReport> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut receipts = self
+            .list()?
+            .iter()
+            .map(|id| self.load(id))
+            .collect::<Result<Vec<_>>>()?;
+        receipts.sort_by(|a, b| {
+            b.timestamp
+                .cmp(&a.timestamp)
+                .then_with(|| b.id.0.cmp(&a.id.0))
+        });
+
+        let mut protected: HashSet<ReceiptId> = policy.protected.iter().cloned().collect();
+        for anchor in self.list_notary_anchors()? {
+            protected.extend(anchor.covered.first().cloned());
+            protected.insert(anchor.tip);
+        }
+
+        let cutoff = policy
+            .max_age_secs
+            .map(|secs| now.saturating_sub(secs.saturating_mul(1_000_000)));
+        let mut report = RetentionReport {
+            examined: receipts.len(),
+            ..RetentionReport::default()
+        };
+        let mut retained = Vec::new();
+        let mut pruned = Vec::new();
+
+        for (rank, receipt) in receipts.into_iter().enumerate() {
+            let too_old = cutoff.is_some_and(|cutoff| receipt.timestamp < cutoff);
+            let over_count = policy.max_count.is_some_and(|max| rank >= max);
+            if !(too_old || over_count) {
+                retained.push(receipt);
+            } else if protected.contains(&receipt.id) {
+                report.protected.push(receipt.id.clone());
+                retained.push(receipt);
+            } else {
+                pruned.push(receipt);
+            }
+        }
+
+        let linked: HashSet<&ReceiptId> = retained
+            .iter()
+            .filter_map(|r| r.previous_receipt.as_ref())
+            .collect();
+
+        for receipt in &pruned {
+            if policy.keep_chain_tips && linked.contains(&receipt.id) {
+                self.save_stub(&ReceiptStub::of(receipt, now))?;
+                report.stubbed.push(receipt.id.clone());
+            }
+            if policy.archive {
+                let retired = self.retired_path(&receipt.id);
+                if let Some(parent) = retired.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(self.receipt_path(&receipt.id), retired)?;
+                TextIndex::append_remove(&self.text_index_path(), &receipt.id.0)?;
+            } else {
+                self.remove_receipt(&receipt.id)?;
+            }
+            report.pruned.push(receipt.id.clone());
+        }
+
+        report.retained = retained.len();
+        Ok(report)
+    }
+
+    // ── Compaction ────────────────────────────────────────────────────────────
+
+    /// Compact every receipt older than `cutoff` (microseconds since epoch)
+    /// into one [`ReceiptArchive`] signed by `signer`, and free their files.
+    ///
+    /// The archive's root receipt is saved in the store too. Root receipts of
+    /// earlier archives are never compacted, so every archive's proof stays
+    /// in the store. Returns `None` if nothing is old enough. The archive and
+    /// its index entries are written before any receipt file is removed, so
+    /// a crash part-way leaves receipts duplicated, never lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process keeps the
+    /// store locked, `IdentityError::InvalidInput` for a `cutoff` above 2^53,
+    /// any error from signing the root receipt, or `IdentityError::Io` for
+    /// filesystem errors.
+    #[cfg(feature = "signing")]
+    pub fn compact<S: crate::crypto::signer::Signer + ?Sized>(
+        &self,
+        signer: &S,
+        cutoff: u64,
+    ) -> Result<Option<ReceiptArchive>> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let archive_root = crate::receipt::ActionType::Custom(archive::ARCHIVE_ACTION.into());
+        let old: Vec<ActionReceipt> = scan::load_each(&self.list()?, |id| self.load(id))
+            .into_iter()
+            .filter(|r| r.timestamp < cutoff && r.action_type != archive_root)
+            .collect();
+        if old.is_empty() {
+            return Ok(None);
+        }
+
+        let sealed = ReceiptArchive::seal(signer, old, cutoff)?;
+        let dir = self.archives_dir();
+        archive::write_archive(&dir, &sealed)?;
+        let mut index = archive::read_index(&dir)?;
+        for receipt in &sealed.receipts {
+            index.insert(receipt.id.0.clone(), sealed.id().0.clone());
+        }
+        archive::write_index(&dir, &index)?;
+        self.write_receipt(&sealed.root_receipt)?;
+
+        for receipt in &sealed.receipts {
+            self.remove_receipt(&receipt.id)?;
+        }
+        Ok(Some(sealed))
+    }
+
+    /// List the IDs of every archive, which are their root receipts' IDs.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the directory cannot be read.
+    pub fn list_archives(&self) -> Result<Vec<ReceiptId>> {
+        let dir = self.archives_dir();
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&dir)? {
+            let name = entry?.file_name();
+            if let Some(stem) = name.to_string_lossy().strip_suffix(".json") {
+                if stem != "index" {
+                    ids.push(ReceiptId(stem.to_string()));
+                }
+            }
+        }
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(ids)
+    }
+
+    /// Load the archive with ID `id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if there is no such archive,
+    /// `IdentityError::InvalidFileFormat` if it cannot be parsed, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn load_archive(&self, id: &ReceiptId) -> Result<ReceiptArchive> {
+        archive::read_archive(&self.archives_dir(), id)?
+            .ok_or_else(|| IdentityError::NotFound(format!("receipt archive not found: {id}")))
+    }
+
+    /// The archive the receipt with ID `id` was compacted into, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if the index or the
+    /// archive cannot be parsed, or `IdentityError::Io` for filesystem
+    /// errors.
+    pub fn find_archive(&self, id: &ReceiptId) -> Result<Option<ReceiptArchive>> {
+        let dir = self.archives_dir();
+        if !dir.exists() {
+            return Ok(None);
+        }
+        match archive::read_index(&dir)?.get(&id.0) {
+            Some(archive_id) => archive::read_archive(&dir, &ReceiptId(archive_id.clone())),
+            None => Ok(None),
         }
     }
 
+    /// Load the stub left for a pruned receipt, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidFileFormat` if the file cannot be
+    /// parsed, or `IdentityError::Io` for filesystem errors.
+    pub fn load_stub(&self, id: &ReceiptId) -> Result<Option<ReceiptStub>> {
+        let path = self.stub_path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let bytes = std::fs::read(&path)?;
+        let file: ReceiptStubFile = serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::InvalidFileFormat(format!(
+                "failed to parse receipt stub {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        Ok(Some(file.stub))
+    }
+
     // ── Internal helpers ──────────────────────────────────────────────────────
 
     /// Build the filesystem path for a receipt ID.
     fn receipt_path(&self, id: &ReceiptId) -> PathBuf {
         self.base_dir.join(format!("{}.json", id.0))
     }
+
+    /// Build the filesystem path retention moves a pruned receipt to.
+    fn retired_path(&self, id: &ReceiptId) -> PathBuf {
+        self.base_dir.join("archive").join(format!("{}.json", id.0))
+    }
+
+    /// Build the filesystem path for the archives directory.
+    fn archives_dir(&self) -> PathBuf {
+        self.base_dir.join(ARCHIVES_DIR)
+    }
+
+    /// Build the filesystem path for the text index journal.
+    fn text_index_path(&self) -> PathBuf {
+        self.base_dir.join("index").join("text.ndjson")
+    }
+
+    /// Build the filesystem path for the notary anchor of a chain tip.
+    fn notary_path(&self, tip: &ReceiptId) -> PathBuf {
+        self.base_dir.join("notary").join(format!("{}.json", tip.0))
+    }
+
+    /// Build the filesystem path for a receipt stub.
+    fn stub_path(&self, id: &ReceiptId) -> PathBuf {
+        self.base_dir.join("stubs").join(format!("{}.json", id.0))
+    }
+
+    /// Write a receipt's file and index it; the caller holds the store lock.
+    fn write_receipt(&self, receipt: &ActionReceipt) -> Result<()> {
+        let json = encode_receipt(receipt)?;
+        write_atomic(&self.receipt_path(&receipt.id), json.as_bytes())?;
+        TextIndex::append_put(
+            &self.text_index_path(),
+            &receipt.id.0,
+            &receipt_terms(receipt),
+        )
+    }
+
+    /// Remove a receipt's file and index entry; the caller holds the store
+    /// lock.
+    fn remove_receipt(&self, id: &ReceiptId) -> Result<()> {
+        match std::fs::remove_file(self.receipt_path(id)) {
+            Ok(()) => TextIndex::append_remove(&self.text_index_path(), &id.0),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    /// Write a stub atomically (temporary file, then rename); the caller
+    /// holds the store lock.
+    fn save_stub(&self, stub: &ReceiptStub) -> Result<()> {
+        let file = ReceiptStubFile {
+            version: RECEIPT_FILE_VERSION,
+            stub: stub.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+
+        let path = self.stub_path(&stub.id);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_atomic(&path, json.as_bytes())
+    }
+
+    /// Write an anchor atomically (temporary file, then rename).
+    fn save_notary_anchor(&self, anchor: &NotaryAnchor) -> Result<()> {
+        let file = NotaryAnchorFile {
+            version: RECEIPT_FILE_VERSION,
+            anchor: anchor.clone(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+
+        let path = self.notary_path(&anchor.tip);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        write_atomic(&path, json.as_bytes())
+    }
+}
+
+/// Serialize `receipt` into the contents of its receipt file.
+fn encode_receipt(receipt: &ActionReceipt) -> Result<String> {
+    let file = ReceiptFile {
+        version: RECEIPT_FILE_VERSION,
+        receipt: receipt.clone(),
+    };
+    serde_json::to_string_pretty(&file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
+// ── ChainWalk ─────────────────────────────────────────────────────────────────
+
+/// Iterator returned by [`ReceiptStore::walk_chain`].
+pub struct ChainWalk<'a> {
+    store: &'a ReceiptStore,
+    next: Option<ReceiptId>,
+    seen: HashSet<ReceiptId>,
+}
+
+impl Iterator for ChainWalk<'_> {
+    type Item = Result<ActionReceipt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.next.take()?;
+        if !self.seen.insert(id.clone()) {
+            return Some(Err(IdentityError::InvalidChain));
+        }
+        let receipt = self.store.load(&id);
+        if let Ok(r) = &receipt {
+            self.next = r.previous_receipt.clone();
+        }
+        Some(receipt)
+    }
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -242,6 +835,51 @@ mod tests {
         assert!(ids.contains(&r3.id));
     }
 
+    #[test]
+    fn test_receipt_store_load_all_skips_corrupt_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let mut saved: Vec<ReceiptId> = (0..20)
+            .map(|i| {
+                let r = make_receipt(&anchor, &format!("action {i}"));
+                store.save(&r).unwrap();
+                r.id
+            })
+            .collect();
+        std::fs::write(dir.path().join("arec_corrupt.json"), b"{not json").unwrap();
+
+        let loaded: Vec<ReceiptId> = store
+            .load_all()
+            .unwrap()
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        saved.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(loaded, saved);
+    }
+
+    #[test]
+    fn test_receipt_store_recover_quarantines_partial_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let receipt = make_receipt(&anchor, "kept");
+        store.save(&receipt).unwrap();
+
+        let full = std::fs::read(dir.path().join(format!("{}.json", receipt.id.0))).unwrap();
+        std::fs::write(dir.path().join("arec_cut.json"), &full[..full.len() / 2]).unwrap();
+        std::fs::create_dir_all(dir.path().join("stubs")).unwrap();
+        std::fs::write(dir.path().join("stubs").join("arec_old.json.tmp"), b"{").unwrap();
+
+        let report = store.recover().unwrap();
+        assert_eq!(report.quarantined.len(), 2);
+        assert_eq!(store.list().unwrap(), vec![receipt.id.clone()]);
+        assert!(dir.path().join("quarantine").join("arec_cut.json").exists());
+        assert!(store.recover().unwrap().is_clean());
+    }
+
     #[test]
     fn test_receipt_store_delete() {
         let dir = tempfile::tempdir().unwrap();
@@ -258,6 +896,38 @@ mod tests {
         assert!(store.load(&id).is_err());
     }
 
+    #[test]
+    fn test_receipt_store_text_index_tracks_saves_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+
+        let deploy = make_receipt(&anchor, "deployed to staging");
+        let rollback = make_receipt(&anchor, "rolled back staging");
+        store.save(&deploy).unwrap();
+        store.save(&rollback).unwrap();
+
+        // Written behind the store's back, as a committed transaction would.
+        let copied = make_receipt(&anchor, "copied from backup");
+        std::fs::write(
+            dir.path().join(format!("{}.json", copied.id.0)),
+            encode_receipt(&copied).unwrap(),
+        )
+        .unwrap();
+
+        let index = store.text_index().unwrap();
+        assert_eq!(index.search("staging").len(), 2);
+        assert!(index.search("backup").contains(copied.id.0.as_str()));
+
+        store.delete(&deploy.id).unwrap();
+        let index = store.text_index().unwrap();
+        assert_eq!(
+            index.search("staging").into_iter().collect::<Vec<_>>(),
+            vec![rollback.id.0.as_str()]
+        );
+        assert_eq!(store.list().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_receipt_store_delete_nonexistent_is_ok() {
         let dir = tempfile::tempdir().unwrap();
@@ -329,4 +999,256 @@ mod tests {
         assert!(value["receipt"].is_object());
         assert_eq!(value["receipt"]["id"].as_str().unwrap(), receipt.id.0);
     }
+
+    /// Notary that fails until `fail` is cleared and counts publications.
+    struct TestNotary {
+        fail: std::cell::Cell<bool>,
+        calls: std::cell::Cell<usize>,
+    }
+
+    impl NotaryHook for TestNotary {
+        fn publish(&self, root: [u8; 32]) -> Result<crate::receipt::notary::NotaryReceipt> {
+            self.calls.set(self.calls.get() + 1);
+            if self.fail.get() {
+                return Err(IdentityError::StorageError("notary offline".into()));
+            }
+            Ok(crate::receipt::notary::NotaryReceipt {
+                notary: "test-log".into(),
+                root: hex::encode(root),
+                proof: format!("entry-{}", self.calls.get()),
+                published_at: 1,
+            })
+        }
+    }
+
+    fn save_chain(store: &ReceiptStore, anchor: &IdentityAnchor, len: usize) -> Vec<ActionReceipt> {
+        let mut chain: Vec<ActionReceipt> = Vec::new();
+        for i in 0..len {
+            let mut builder = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Decision,
+                ActionContent::new(format!("step {i}")),
+            );
+            if let Some(prev) = chain.last() {
+                builder = builder.chain_to(prev.id.clone());
+            }
+            let receipt = builder.sign(anchor.signing_key()).unwrap();
+            store.save(&receipt).unwrap();
+            chain.push(receipt);
+        }
+        chain
+    }
+
+    #[test]
+    fn test_walk_chain_streams_into_verifier() {
+        use crate::receipt::chain::{verify_chain_stream, ChainBreakReason, ChainPolicy};
+        use crate::trust::verify::TimeSource;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 5);
+        let tip = chain[4].id.clone();
+
+        let ids: Vec<ReceiptId> = store.walk_chain(&tip).map(|r| r.unwrap().id).collect();
+        assert_eq!(ids.first(), Some(&tip));
+        assert_eq!(ids.len(), 5);
+
+        let policy = ChainPolicy::default();
+        let ok = verify_chain_stream(store.walk_chain(&tip), &TimeSource::HostClock, &policy);
+        assert!(ok.unwrap().is_valid());
+
+        // A receipt missing from the middle of the chain is the break point.
+        store.delete(&chain[2].id).unwrap();
+        let result =
+            verify_chain_stream(store.walk_chain(&tip), &TimeSource::HostClock, &policy).unwrap();
+        assert_eq!(result.verified, 2);
+        let at = result.first_break.unwrap();
+        assert_eq!(at.position, 2);
+        assert!(matches!(at.reason, ChainBreakReason::Unreadable { .. }));
+    }
+
+    #[test]
+    fn test_anchor_to_notary_retries_after_hook_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 3);
+        let tip = chain[2].id.clone();
+        let notary = TestNotary {
+            fail: std::cell::Cell::new(true),
+            calls: std::cell::Cell::new(0),
+        };
+
+        assert!(store.anchor_to_notary(&notary, &tip).is_err());
+        let pending = store.load_notary_anchor(&tip).unwrap().unwrap();
+        assert!(!pending.is_published());
+        assert_eq!(pending.covered.len(), 3);
+
+        notary.fail.set(false);
+        let outcome = store.anchor_to_notary(&notary, &tip).unwrap();
+        let published = outcome.anchor();
+        assert!(matches!(outcome, NotaryOutcome::Published(_)));
+        assert_eq!(published.root, pending.root);
+        assert_eq!(published.computed_at, pending.computed_at);
+        assert!(published.verify(&chain).is_ok());
+
+        // Anchor files do not show up as receipts.
+        assert_eq!(store.list().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_anchor_to_notary_unchanged_chain_is_duplicate() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 2);
+        let tip = chain[1].id.clone();
+        let notary = TestNotary {
+            fail: std::cell::Cell::new(false),
+            calls: std::cell::Cell::new(0),
+        };
+
+        let first = store.anchor_to_notary(&notary, &tip).unwrap();
+        let second = store.anchor_to_notary(&notary, &tip).unwrap();
+        assert!(matches!(second, NotaryOutcome::AlreadyAnchored(_)));
+        assert_eq!(first.anchor(), second.anchor());
+        assert_eq!(notary.calls.get(), 1);
+
+        // An earlier tip is a different root with its own anchor.
+        let earlier = store.anchor_to_notary(&notary, &chain[0].id).unwrap();
+        assert!(matches!(earlier, NotaryOutcome::Published(_)));
+        assert_ne!(earlier.anchor().root, first.anchor().root);
+    }
+    #[test]
+    fn test_retention_max_count_stubs_linked_receipts() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 4);
+
+        let policy = RetentionPolicy {
+            max_count: Some(2),
+            keep_chain_tips: true,
+            ..RetentionPolicy::default()
+        };
+        let report = store
+            .apply_retention(&policy, crate::time::now_micros())
+            .unwrap();
+        assert_eq!(report.examined, 4);
+        assert_eq!(report.retained, 2);
+        assert_eq!(report.pruned.len(), 2);
+        // Only the receipt the oldest retained one links to needs a stub.
+        assert_eq!(report.stubbed, vec![chain[1].id.clone()]);
+
+        let stub = store.load_stub(&chain[1].id).unwrap().unwrap();
+        assert_eq!(stub.receipt_hash, chain[1].receipt_hash);
+        assert_eq!(
+            store.load(&chain[2].id).unwrap().previous_receipt,
+            Some(stub.id)
+        );
+        assert!(store.load_stub(&chain[0].id).unwrap().is_none());
+        assert_eq!(store.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_retention_keeps_protected_and_anchored_receipts() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 3);
+        let notary = TestNotary {
+            fail: std::cell::Cell::new(true),
+            calls: std::cell::Cell::new(0),
+        };
+        // A pending anchor still protects its bracket.
+        assert!(store.anchor_to_notary(&notary, &chain[1].id).is_err());
+
+        let policy = RetentionPolicy {
+            max_age_secs: Some(1),
+            archive: true,
+            protected: vec![chain[2].id.clone()],
+            ..RetentionPolicy::default()
+        };
+        let later = crate::time::now_micros() + 100_000_000;
+        let report = store.apply_retention(&policy, later).unwrap();
+        assert!(report.pruned.is_empty());
+        assert_eq!(report.protected.len(), 3);
+
+        let report = store
+            .apply_retention(
+                &RetentionPolicy {
+                    max_age_secs: Some(1),
+                    archive: true,
+                    ..RetentionPolicy::default()
+                },
+                later,
+            )
+            .unwrap();
+        assert_eq!(report.pruned, vec![chain[2].id.clone()]);
+        assert!(dir
+            .path()
+            .join("archive")
+            .join(format!("{}.json", chain[2].id.0))
+            .exists());
+
+        // A receipt retention archived is no longer listed but still loads.
+        assert!(!store.list().unwrap().contains(&chain[2].id));
+        assert_eq!(store.load(&chain[2].id).unwrap().id, chain[2].id);
+    }
+
+    #[test]
+    fn test_compact_archives_old_receipts_and_frees_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 3);
+
+        let later = crate::time::now_micros() + 100_000_000;
+        let archive = store
+            .compact(anchor.signing_key(), later)
+            .unwrap()
+            .expect("old receipts to archive");
+        assert_eq!(archive.receipts.len(), 3);
+        archive.verify().unwrap();
+
+        // Only the root receipt is left as a file.
+        assert_eq!(store.list().unwrap(), vec![archive.id().clone()]);
+        for receipt in &chain {
+            assert!(!dir.path().join(format!("{}.json", receipt.id.0)).exists());
+        }
+        assert_eq!(store.list_archives().unwrap(), vec![archive.id().clone()]);
+
+        // Archived receipts still load, and chains through them resolve.
+        assert_eq!(store.load(&chain[0].id).unwrap().id, chain[0].id);
+        assert_eq!(store.load_chain(&chain[2].id).unwrap().len(), 3);
+
+        let found = store.find_archive(&chain[1].id).unwrap().unwrap();
+        let proof = found.proof_for(&chain[1].id).unwrap();
+        let root = store.load(archive.id()).unwrap();
+        super::archive::verify_archived(&chain[1], &proof, &root).unwrap();
+
+        // The root receipt is never compacted into a later archive.
+        assert!(store
+            .compact(anchor.signing_key(), later)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_compact_keeps_receipts_newer_than_cutoff() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(dir.path()).unwrap();
+        let anchor = IdentityAnchor::new(None);
+        let chain = save_chain(&store, &anchor, 2);
+
+        assert!(store.compact(anchor.signing_key(), 0).unwrap().is_none());
+        assert_eq!(store.list().unwrap().len(), 2);
+        assert!(store.list_archives().unwrap().is_empty());
+        assert!(store.find_archive(&chain[0].id).unwrap().is_none());
+        assert!(matches!(
+            store.load_archive(&chain[0].id),
+            Err(IdentityError::NotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,280 @@
+//! NDJSON receipt streams — bulk export and import of receipts.
+//!
+//! A stream is one receipt per line, oldest first, followed by exactly one
+//! summary line:
+//!
+//! ```text
+//! {"id":"arec_...","actor":"aid_...", ... }
+//! {"id":"arec_...","actor":"aid_...", ... }
+//! {"summary":{"count":2,"chain_tip":"arec_...","complete":true}}
+//! ```
+//!
+//! The summary is what makes a transfer checkable: a reader that reaches
+//! end of input without it, or whose receipt count disagrees with it, knows
+//! the stream was cut short. [`ReceiptStore::export_ndjson`] writes one
+//! receipt at a time, so memory use does not grow with the store, and
+//! [`read_ndjson`] consumes the same format line by line.
+
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+use crate::receipt::{ActionReceipt, ReceiptId};
+
+use super::migrate;
+use super::receipt_store::ReceiptStore;
+
+/// Which receipts an export includes. The default includes all of them.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptExportFilter {
+    /// Only receipts signed by this identity.
+    pub actor: Option<IdentityId>,
+    /// Only receipts at or after this time (microseconds since epoch).
+    pub since: Option<u64>,
+    /// Only receipts at or before this time (microseconds since epoch).
+    pub until: Option<u64>,
+}
+
+impl ReceiptExportFilter {
+    fn matches(&self, receipt: &ActionReceipt) -> bool {
+        self.actor.as_ref().is_none_or(|a| receipt.actor == *a)
+            && self.since.is_none_or(|t| receipt.timestamp >= t)
+            && self.until.is_none_or(|t| receipt.timestamp <= t)
+    }
+}
+
+/// The trailing line of a receipt stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamSummary {
+    /// Number of receipt lines before the summary.
+    pub count: u64,
+    /// The newest receipt in the stream, if any.
+    pub chain_tip: Option<ReceiptId>,
+    /// Always true when written by an exporter that finished.
+    pub complete: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SummaryLine {
+    summary: StreamSummary,
+}
+
+impl ReceiptStore {
+    /// Write every receipt matching `filter` to `out` as an NDJSON stream,
+    /// oldest first, ending with the summary line.
+    ///
+    /// Receipts are loaded and written one at a time; corrupt files are
+    /// skipped, as in [`load_all`](Self::load_all).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the store cannot be listed or `out`
+    /// cannot be written.
+    pub fn export_ndjson<W: Write>(
+        &self,
+        filter: &ReceiptExportFilter,
+        mut out: W,
+    ) -> Result<StreamSummary> {
+        // Only the (timestamp, id) index is held in memory, to order the stream.
+        let mut index: Vec<(u64, ReceiptId)> = Vec::new();
+        for id in self.list()? {
+            if let Ok(receipt) = self.load(&id) {
+                if filter.matches(&receipt) {
+                    index.push((receipt.timestamp, id));
+                }
+            }
+        }
+        index.sort_by(|a, b| (a.0, &(a.1).0).cmp(&(b.0, &(b.1).0)));
+
+        let mut count = 0u64;
+        let mut chain_tip = None;
+        for (_, id) in index {
+            let Ok(receipt) = self.load(&id) else {
+                continue; // removed since it was indexed
+            };
+            write_line(&mut out, &receipt)?;
+            count += 1;
+            chain_tip = Some(receipt.id);
+        }
+
+        let summary = StreamSummary {
+            count,
+            chain_tip,
+            complete: true,
+        };
+        write_line(
+            &mut out,
+            &SummaryLine {
+                summary: summary.clone(),
+            },
+        )?;
+        out.flush()?;
+        Ok(summary)
+    }
+}
+
+fn write_line<W: Write, T: Serialize>(out: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *out, value)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    out.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Read an NDJSON receipt stream, passing each receipt to `on_receipt`.
+///
+/// Blank lines are ignored. Returns the stream's summary once it has been
+/// checked against what was read.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidFileFormat` if a line is neither a
+/// receipt nor a summary, if anything follows the summary, or if the
+/// stream is truncated: no summary, or a count or chain tip that does not
+/// match the receipts read. Errors from `on_receipt` are returned
+/// unchanged; receipts already passed to it are not rolled back.
+pub fn read_ndjson<R: BufRead>(
+    input: R,
+    mut on_receipt: impl FnMut(ActionReceipt) -> Result<()>,
+) -> Result<StreamSummary> {
+    let mut count = 0u64;
+    let mut last = None;
+    let mut summary: Option<StreamSummary> = None;
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let bad_line = |e: serde_json::Error| {
+            IdentityError::InvalidFileFormat(format!("line {}: {e}", number + 1))
+        };
+        if summary.is_some() {
+            return Err(IdentityError::InvalidFileFormat(format!(
+                "line {}: data after the summary line",
+                number + 1
+            )));
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&line).map_err(bad_line)?;
+        if value.get("summary").is_some() {
+            let parsed: SummaryLine = serde_json::from_value(value).map_err(bad_line)?;
+            summary = Some(parsed.summary);
+            continue;
+        }
+
+        let receipt: ActionReceipt = migrate::migrate_or(value, |e| {
+            IdentityError::InvalidFileFormat(format!("line {}: {e}", number + 1))
+        })?;
+        count += 1;
+        last = Some(receipt.id.clone());
+        on_receipt(receipt)?;
+    }
+
+    let summary = summary.ok_or_else(|| {
+        IdentityError::InvalidFileFormat(format!(
+            "stream ended after {count} receipts without a summary line"
+        ))
+    })?;
+    if !summary.complete || summary.count != count || summary.chain_tip != last {
+        return Err(IdentityError::InvalidFileFormat(format!(
+            "summary promises {} receipts ending at {:?}, stream had {count} ending at {:?}",
+            summary.count,
+            summary.chain_tip.as_ref().map(|id| id.0.as_str()),
+            last.as_ref().map(|id| id.0.as_str()),
+        )));
+    }
+    Ok(summary)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::{ActionContent, ActionType};
+
+    fn store_with_receipts(
+        dir: &std::path::Path,
+        anchor: &IdentityAnchor,
+        n: usize,
+    ) -> ReceiptStore {
+        let store = ReceiptStore::new(dir).unwrap();
+        for i in 0..n {
+            let receipt = ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Observation,
+                ActionContent::new(format!("step {i}")),
+            )
+            .sign(anchor.signing_key())
+            .unwrap();
+            store.save(&receipt).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_export_then_import_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let alice = IdentityAnchor::new(None);
+        let bob = IdentityAnchor::new(None);
+        let store = store_with_receipts(&tmp.path().join("src"), &alice, 3);
+        let bob_receipt = ReceiptBuilder::new(
+            bob.id(),
+            ActionType::Decision,
+            ActionContent::new("unrelated"),
+        )
+        .sign(bob.signing_key())
+        .unwrap();
+        store.save(&bob_receipt).unwrap();
+
+        let filter = ReceiptExportFilter {
+            actor: Some(alice.id()),
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        let exported = store.export_ndjson(&filter, &mut out).unwrap();
+        assert_eq!(exported.count, 3);
+        assert_eq!(String::from_utf8_lossy(&out).lines().count(), 4);
+
+        let target = ReceiptStore::new(tmp.path().join("dst")).unwrap();
+        let imported = read_ndjson(out.as_slice(), |r| target.save(&r)).unwrap();
+        assert_eq!(imported, exported);
+        assert_eq!(target.list().unwrap().len(), 3);
+        assert!(target.load(&bob_receipt.id).is_err());
+    }
+
+    #[test]
+    fn test_empty_export_still_has_summary() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = ReceiptStore::new(tmp.path()).unwrap();
+        let mut out = Vec::new();
+        let summary = store
+            .export_ndjson(&ReceiptExportFilter::default(), &mut out)
+            .unwrap();
+        assert_eq!(summary.count, 0);
+        assert_eq!(summary.chain_tip, None);
+        assert!(read_ndjson(out.as_slice(), |_| Ok(())).is_ok());
+    }
+
+    #[test]
+    fn test_truncated_stream_is_detected() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = store_with_receipts(tmp.path(), &IdentityAnchor::new(None), 3);
+        let mut out = Vec::new();
+        store
+            .export_ndjson(&ReceiptExportFilter::default(), &mut out)
+            .unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        // Cut before the summary.
+        let cut = lines[..2].join("\n");
+        assert!(read_ndjson(cut.as_bytes(), |_| Ok(())).is_err());
+
+        // A receipt dropped from the middle.
+        let gapped = [lines[0], lines[2], lines[3]].join("\n");
+        assert!(read_ndjson(gapped.as_bytes(), |_| Ok(())).is_err());
+    }
+}
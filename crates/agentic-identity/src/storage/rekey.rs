@@ -0,0 +1,373 @@
+//! Re-encrypting every `.aid` file in an identity directory under a new passphrase.
+//!
+//! [`rekey_identities`] runs in two phases so a crash never leaves the
+//! directory in an unknown mixture of old and new keys:
+//!
+//! 1. **Staging.** Each identity is decrypted with the old passphrase,
+//!    re-encrypted with the new one into `.rekey/{name}.aid`, and loaded
+//!    back with the new passphrase to confirm it decrypts to the same
+//!    identity. Originals are untouched; any failure discards the staging
+//!    directory and leaves every file under the old key.
+//! 2. **Committing.** Once everything is staged, the manifest is switched to
+//!    `committing` and each staged file is renamed over its original.
+//!
+//! The manifest (`.rekey/manifest.json`) records the phase and the files
+//! involved. A run that finds a manifest left behind resumes: an interrupted
+//! staging phase is discarded and started over, and an interrupted commit is
+//! finished — files still in `.rekey/` are the ones under the new key that
+//! have not been moved yet, everything else has already been committed.
+//!
+//! ```text
+//! identity/
+//! ├── default.aid
+//! └── .rekey/
+//!     ├── manifest.json
+//!     └── default.aid
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+
+use super::atomic::write_atomic;
+use super::identity_file::{load_identity, save_identity};
+use super::lock::StoreLock;
+
+/// Staging directory created inside the identity directory.
+const REKEY_DIR: &str = ".rekey";
+
+/// Manifest file inside [`REKEY_DIR`].
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// How far a rekey got before it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RekeyPhase {
+    /// Staged copies are being written; originals are all under the old key.
+    Staging,
+    /// Staged copies are verified and being moved over the originals.
+    Committing,
+}
+
+/// Record of an in-progress rekey, persisted as `.rekey/manifest.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RekeyManifest {
+    pub phase: RekeyPhase,
+    /// When this rekey started (microseconds since epoch).
+    pub started_at: u64,
+    /// Every `.aid` file being rekeyed, by file name.
+    pub files: Vec<RekeyEntry>,
+}
+
+/// One identity file covered by a rekey.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RekeyEntry {
+    pub file: String,
+    pub identity: IdentityId,
+}
+
+/// What [`rekey_identities`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RekeyReport {
+    /// Identity files found (or recorded, when finishing a commit).
+    pub examined: usize,
+    /// Files moved under the new key by this call.
+    pub rekeyed: usize,
+    /// Files an interrupted commit had already moved before this call.
+    pub already_rekeyed: usize,
+    /// Whether a manifest from an interrupted run was found.
+    pub resumed: bool,
+}
+
+/// Re-encrypt every `.aid` file directly inside `dir` from `old_passphrase`
+/// to `new_passphrase`.
+///
+/// Either every file ends up under the new passphrase or, on error, every
+/// file stays under the old one — except when an interrupted commit is being
+/// finished, in which case the manifest is kept so a later call can complete
+/// it. See the [module docs](self) for the recovery rules.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidPassphrase` if any identity does not
+/// decrypt with `old_passphrase`, or if a staged file left by an interrupted
+/// commit does not decrypt with `new_passphrase`. Returns
+/// `IdentityError::StorageError` if a re-encrypted file does not load back as
+/// the same identity, and `IdentityError::Io` for filesystem errors.
+pub fn rekey_identities(
+    dir: &Path,
+    old_passphrase: &str,
+    new_passphrase: &str,
+) -> Result<RekeyReport> {
+    if !dir.exists() {
+        return Ok(RekeyReport::default());
+    }
+    let staging = dir.join(REKEY_DIR);
+    let mut resumed = false;
+
+    if let Some(manifest) = read_manifest(&staging)? {
+        resumed = true;
+        match manifest.phase {
+            RekeyPhase::Committing => {
+                let mut report = commit(dir, &staging, &manifest, new_passphrase)?;
+                report.resumed = true;
+                return Ok(report);
+            }
+            // Nothing was moved yet: start the staging over from scratch.
+            RekeyPhase::Staging => std::fs::remove_dir_all(&staging)?,
+        }
+    } else if staging.exists() {
+        // A staging directory without a manifest never got past creation.
+        std::fs::remove_dir_all(&staging)?;
+    }
+
+    let mut manifest = RekeyManifest {
+        phase: RekeyPhase::Staging,
+        started_at: crate::time::now_micros(),
+        files: Vec::new(),
+    };
+    std::fs::create_dir_all(&staging)?;
+    write_manifest(&staging, &manifest)?;
+
+    match stage(dir, &staging, old_passphrase, new_passphrase) {
+        Ok(files) => manifest.files = files,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    }
+
+    manifest.phase = RekeyPhase::Committing;
+    write_manifest(&staging, &manifest)?;
+
+    let mut report = commit(dir, &staging, &manifest, new_passphrase)?;
+    report.resumed = resumed;
+    Ok(report)
+}
+
+/// Read the manifest of an interrupted rekey in `dir`, if there is one.
+pub fn pending_rekey(dir: &Path) -> Result<Option<RekeyManifest>> {
+    read_manifest(&dir.join(REKEY_DIR))
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+/// Write a verified copy of every identity in `dir` to `staging`.
+fn stage(
+    dir: &Path,
+    staging: &Path,
+    old_passphrase: &str,
+    new_passphrase: &str,
+) -> Result<Vec<RekeyEntry>> {
+    let mut files = Vec::new();
+    for path in list_aid_files(dir)? {
+        let file = file_name(&path)?;
+        let anchor = load_identity(&path, old_passphrase)?;
+        let staged = staging.join(&file);
+        save_identity(&anchor, &staged, new_passphrase)?;
+
+        let reloaded = load_identity(&staged, new_passphrase)?;
+        if reloaded.id() != anchor.id() {
+            return Err(IdentityError::StorageError(format!(
+                "re-encrypted {file} loads as {} instead of {}",
+                reloaded.id(),
+                anchor.id()
+            )));
+        }
+        files.push(RekeyEntry {
+            file,
+            identity: anchor.id(),
+        });
+    }
+    Ok(files)
+}
+
+/// Move every staged file over its original, then remove the staging directory.
+fn commit(
+    dir: &Path,
+    staging: &Path,
+    manifest: &RekeyManifest,
+    new_passphrase: &str,
+) -> Result<RekeyReport> {
+    let mut report = RekeyReport {
+        examined: manifest.files.len(),
+        ..RekeyReport::default()
+    };
+    for entry in &manifest.files {
+        let staged = staging.join(&entry.file);
+        if !staged.exists() {
+            report.already_rekeyed += 1;
+            continue;
+        }
+        // Re-check before the old version is replaced: on resume the caller
+        // may hold a different passphrase than the run that staged this file.
+        if load_identity(&staged, new_passphrase)?.id() != entry.identity {
+            return Err(IdentityError::StorageError(format!(
+                "staged {} does not hold identity {}",
+                entry.file, entry.identity
+            )));
+        }
+        let target = dir.join(&entry.file);
+        let _lock = StoreLock::file(&target)?;
+        std::fs::rename(&staged, &target)?;
+        report.rekeyed += 1;
+    }
+    std::fs::remove_dir_all(staging)?;
+    Ok(report)
+}
+
+fn list_aid_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "aid"))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            IdentityError::StorageError(format!("unreadable file name: {}", path.display()))
+        })
+}
+
+fn read_manifest(staging: &Path) -> Result<Option<RekeyManifest>> {
+    let path = staging.join(MANIFEST_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let bytes = std::fs::read(&path)?;
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| IdentityError::SerializationError(format!("rekey manifest: {e}")))
+}
+
+/// Replace the manifest atomically, so a crash leaves either phase readable.
+fn write_manifest(staging: &Path, manifest: &RekeyManifest) -> Result<()> {
+    let json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    write_atomic(&staging.join(MANIFEST_FILE), &json)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+
+    fn make_store(names: &[&str], passphrase: &str) -> (tempfile::TempDir, Vec<IdentityAnchor>) {
+        let dir = tempfile::tempdir().unwrap();
+        let anchors: Vec<IdentityAnchor> = names
+            .iter()
+            .map(|name| {
+                let anchor = IdentityAnchor::new(Some(name.to_string()));
+                save_identity(&anchor, &dir.path().join(format!("{name}.aid")), passphrase)
+                    .unwrap();
+                anchor
+            })
+            .collect();
+        (dir, anchors)
+    }
+
+    #[test]
+    fn test_rekey_moves_every_file_to_new_passphrase() {
+        let (dir, anchors) = make_store(&["alpha", "beta"], "old");
+
+        let report = rekey_identities(dir.path(), "old", "new").unwrap();
+        assert_eq!(report.examined, 2);
+        assert_eq!(report.rekeyed, 2);
+        assert!(!report.resumed);
+
+        for anchor in &anchors {
+            let path = dir
+                .path()
+                .join(format!("{}.aid", anchor.name.as_deref().unwrap()));
+            assert_eq!(load_identity(&path, "new").unwrap().id(), anchor.id());
+            assert!(matches!(
+                load_identity(&path, "old"),
+                Err(IdentityError::InvalidPassphrase)
+            ));
+        }
+        assert!(!dir.path().join(REKEY_DIR).exists());
+        assert!(pending_rekey(dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rekey_wrong_old_passphrase_changes_nothing() {
+        let (dir, _) = make_store(&["alpha"], "old");
+        save_identity(
+            &IdentityAnchor::new(None),
+            &dir.path().join("other.aid"),
+            "x",
+        )
+        .unwrap();
+
+        let result = rekey_identities(dir.path(), "old", "new");
+        assert!(matches!(result, Err(IdentityError::InvalidPassphrase)));
+        assert!(load_identity(&dir.path().join("alpha.aid"), "old").is_ok());
+        assert!(!dir.path().join(REKEY_DIR).exists());
+    }
+
+    #[test]
+    fn test_rekey_finishes_interrupted_commit() {
+        let (dir, anchors) = make_store(&["alpha", "beta"], "old");
+        let staging = dir.path().join(REKEY_DIR);
+
+        // Simulate a crash after "alpha" was committed but before "beta" was.
+        let files = stage(dir.path(), &staging, "old", "new").unwrap();
+        write_manifest(
+            &staging,
+            &RekeyManifest {
+                phase: RekeyPhase::Committing,
+                started_at: 0,
+                files,
+            },
+        )
+        .unwrap();
+        std::fs::rename(staging.join("alpha.aid"), dir.path().join("alpha.aid")).unwrap();
+
+        // The wrong new passphrase must not replace the remaining original.
+        assert!(rekey_identities(dir.path(), "old", "other").is_err());
+        assert!(load_identity(&dir.path().join("beta.aid"), "old").is_ok());
+
+        let report = rekey_identities(dir.path(), "old", "new").unwrap();
+        assert!(report.resumed);
+        assert_eq!(report.rekeyed, 1);
+        assert_eq!(report.already_rekeyed, 1);
+        for (anchor, name) in anchors.iter().zip(["alpha", "beta"]) {
+            let path = dir.path().join(format!("{name}.aid"));
+            assert_eq!(load_identity(&path, "new").unwrap().id(), anchor.id());
+        }
+        assert!(!staging.exists());
+    }
+
+    #[test]
+    fn test_rekey_restarts_interrupted_staging() {
+        let (dir, _) = make_store(&["alpha"], "old");
+        let staging = dir.path().join(REKEY_DIR);
+        std::fs::create_dir_all(&staging).unwrap();
+        write_manifest(
+            &staging,
+            &RekeyManifest {
+                phase: RekeyPhase::Staging,
+                started_at: 0,
+                files: Vec::new(),
+            },
+        )
+        .unwrap();
+        std::fs::write(staging.join("alpha.aid"), b"partial").unwrap();
+
+        let report = rekey_identities(dir.path(), "old", "new").unwrap();
+        assert!(report.resumed);
+        assert_eq!(report.rekeyed, 1);
+        assert!(load_identity(&dir.path().join("alpha.aid"), "new").is_ok());
+    }
+}
@@ -0,0 +1,92 @@
+//! Receipt retention — bounding how many receipts a store keeps, and for how long.
+//!
+//! A [`RetentionPolicy`] is applied with
+//! [`ReceiptStore::apply_retention`](super::ReceiptStore::apply_retention).
+//! Receipts outside the policy are deleted, or moved to `archive/` when
+//! [`RetentionPolicy::archive`] is set. Some receipts are never pruned:
+//!
+//! - the first and last receipt (the bracket) of every notary anchor, so
+//!   the anchor keeps identifying what it covered;
+//! - any receipt listed in [`RetentionPolicy::protected`], for brackets of
+//!   proofs managed outside the store.
+//!
+//! With [`RetentionPolicy::keep_chain_tips`], a pruned receipt that a
+//! retained receipt links to via `previous_receipt` leaves a
+//! [`ReceiptStub`] behind, so the retained chain still resolves its links.
+
+use serde::{Deserialize, Serialize};
+
+use crate::identity::IdentityId;
+use crate::receipt::{ActionReceipt, ReceiptId};
+
+/// Which receipts a store keeps.
+///
+/// Receipts are ranked newest first. A receipt is pruned if it is older than
+/// `max_age_secs` or falls beyond the newest `max_count`. With neither limit
+/// set, nothing is pruned.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Prune receipts older than this many seconds.
+    pub max_age_secs: Option<u64>,
+    /// Keep at most this many receipts.
+    pub max_count: Option<usize>,
+    /// Leave a stub for pruned receipts that retained receipts link to.
+    pub keep_chain_tips: bool,
+    /// Move pruned receipts to `archive/` instead of deleting them.
+    #[serde(default)]
+    pub archive: bool,
+    /// Receipts that must never be pruned.
+    #[serde(default)]
+    pub protected: Vec<ReceiptId>,
+}
+
+impl RetentionPolicy {
+    /// Does this policy never prune anything?
+    pub fn is_unbounded(&self) -> bool {
+        self.max_age_secs.is_none() && self.max_count.is_none()
+    }
+}
+
+/// What [`ReceiptStore::apply_retention`](super::ReceiptStore::apply_retention) did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Receipts examined.
+    pub examined: usize,
+    /// Receipts still in the store afterwards.
+    pub retained: usize,
+    /// Receipts removed (deleted or archived).
+    pub pruned: Vec<ReceiptId>,
+    /// Pruned receipts that left a stub behind.
+    pub stubbed: Vec<ReceiptId>,
+    /// Receipts outside the policy that were kept because they are protected.
+    pub protected: Vec<ReceiptId>,
+}
+
+/// The linkable summary of a pruned receipt.
+///
+/// Carries enough to resolve a retained receipt's `previous_receipt` and to
+/// confirm the hash it was chained over, but not the action itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptStub {
+    pub id: ReceiptId,
+    pub actor: IdentityId,
+    pub timestamp: u64,
+    pub receipt_hash: String,
+    pub previous_receipt: Option<ReceiptId>,
+    /// When the receipt was pruned (microseconds since epoch).
+    pub pruned_at: u64,
+}
+
+impl ReceiptStub {
+    /// Summarize `receipt`, pruned at `now` (microseconds since epoch).
+    pub fn of(receipt: &ActionReceipt, now: u64) -> Self {
+        Self {
+            id: receipt.id.clone(),
+            actor: receipt.actor.clone(),
+            timestamp: receipt.timestamp,
+            receipt_hash: receipt.receipt_hash.clone(),
+            previous_receipt: receipt.previous_receipt.clone(),
+            pruned_at: now,
+        }
+    }
+}
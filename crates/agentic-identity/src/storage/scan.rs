@@ -0,0 +1,105 @@
+//! Store enumeration — load many records, serially or on a bounded pool.
+//!
+//! With the `parallel` feature, [`load_each`] reads and parses files on up
+//! to [`MAX_SCAN_WORKERS`] scoped threads. Workers pull the next index from
+//! a shared counter, so at most that many files are open at once however
+//! large the store is. Results are put back in input order, and records that
+//! fail to load are skipped individually, exactly as on the serial path.
+
+use crate::error::Result;
+
+/// Upper bound on concurrent loads (and therefore open files).
+#[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+pub(crate) const MAX_SCAN_WORKERS: usize = 8;
+
+/// Load every item with `load`, skipping failures, preserving input order.
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn load_each<I, T, F>(items: &[I], load: F) -> Vec<T>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<T> + Sync,
+{
+    items.iter().filter_map(|item| load(item).ok()).collect()
+}
+
+/// Load every item with `load`, skipping failures, preserving input order.
+#[cfg(feature = "parallel")]
+pub(crate) fn load_each<I, T, F>(items: &[I], load: F) -> Vec<T>
+where
+    I: Sync,
+    T: Send,
+    F: Fn(&I) -> Result<T> + Sync,
+{
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(MAX_SCAN_WORKERS)
+        .min(items.len());
+    if workers <= 1 {
+        return items.iter().filter_map(|item| load(item).ok()).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut loaded: Vec<(usize, T)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            return done;
+                        };
+                        if let Ok(value) = load(item) {
+                            done.push((index, value));
+                        }
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    loaded.sort_unstable_by_key(|(index, _)| *index);
+    loaded.into_iter().map(|(_, value)| value).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::IdentityError;
+
+    #[test]
+    fn test_load_each_skips_failures_and_keeps_order() {
+        let items: Vec<u32> = (0..200).collect();
+        let loaded = load_each(&items, |&n| {
+            if n % 7 == 0 {
+                Err(IdentityError::InvalidFileFormat(format!("corrupt {n}")))
+            } else {
+                Ok(n * 2)
+            }
+        });
+        let expected: Vec<u32> = items
+            .iter()
+            .filter(|n| *n % 7 != 0)
+            .map(|n| n * 2)
+            .collect();
+        assert_eq!(loaded, expected);
+    }
+
+    #[test]
+    fn test_load_each_empty() {
+        let loaded: Vec<u32> = load_each(&[] as &[u32], |&n| Ok(n));
+        assert!(loaded.is_empty());
+    }
+}
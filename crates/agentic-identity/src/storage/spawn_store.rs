@@ -1,7 +1,9 @@
 //! Spawn record persistence — store and retrieve `SpawnRecord` records.
 //!
 //! Each spawn record is stored as a single JSON file named `{spawn_id}.json`
-//! inside the configured base directory.
+//! inside the configured base directory. Writes hold the directory's
+//! [`StoreLock`](super::StoreLock) and replace their file atomically;
+//! [`SpawnStore::recover`] quarantines files a crash left partial.
 //!
 //! File format:
 //! ```json
@@ -16,7 +18,14 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{IdentityError, Result};
-use crate::spawn::{SpawnId, SpawnRecord};
+use crate::identity::IdentityId;
+use crate::spawn::{authority_for, consume_budget, BudgetResource, SpawnId, SpawnRecord};
+
+use super::atomic::{self, write_atomic, QuarantineReport};
+use super::lock::StoreLock;
+use super::migrate;
+use super::scan;
+use super::transaction::Transaction;
 
 // ── File format constants ─────────────────────────────────────────────────────
 
@@ -33,6 +42,82 @@ struct SpawnFile {
     record: SpawnRecord,
 }
 
+// ── Queries ───────────────────────────────────────────────────────────────────
+
+/// Filter and pagination options for [`SpawnStore::query`].
+///
+/// Results are ordered newest first. Cursors are keyed on
+/// `(spawn_timestamp, spawn_id)`, so spawns created after a page was fetched
+/// never shift later pages.
+#[derive(Debug, Clone, Default)]
+pub struct SpawnQuery {
+    parent: Option<IdentityId>,
+    spawn_type: Option<String>,
+    active_only: bool,
+    limit: Option<usize>,
+    cursor: Option<String>,
+}
+
+impl SpawnQuery {
+    /// Match every record, unpaginated.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only direct children of `parent`.
+    pub fn parent(mut self, parent: IdentityId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Only spawns whose type tag (e.g. `worker`) equals `spawn_type`.
+    pub fn spawn_type(mut self, spawn_type: impl Into<String>) -> Self {
+        self.spawn_type = Some(spawn_type.into());
+        self
+    }
+
+    /// Only spawns that are active: not terminated, not expired, and with
+    /// every ancestor spawn active too.
+    pub fn active_only(mut self, active_only: bool) -> Self {
+        self.active_only = active_only;
+        self
+    }
+
+    /// Return at most `limit` records per page.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Continue after the page that returned this `next_cursor`.
+    pub fn cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+}
+
+/// One page of [`SpawnStore::query`] results.
+#[derive(Debug, Clone)]
+pub struct SpawnPage {
+    /// Records on this page, newest first.
+    pub records: Vec<SpawnRecord>,
+    /// Number of records matching the filters across all pages.
+    pub total: usize,
+    /// Cursor for the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+fn page_cursor(record: &SpawnRecord) -> String {
+    format!("{}:{}", record.spawn_timestamp, record.id.0)
+}
+
+fn parse_cursor(cursor: &str) -> Result<(u64, String)> {
+    cursor
+        .split_once(':')
+        .and_then(|(ts, id)| Some((ts.parse().ok()?, id.to_string())))
+        .ok_or_else(|| IdentityError::SerializationError(format!("invalid cursor '{cursor}'")))
+}
+
 // ── SpawnStore ────────────────────────────────────────────────────────────────
 
 /// Filesystem-backed store for `SpawnRecord` records.
@@ -59,18 +144,17 @@ impl SpawnStore {
     /// Writes `{base_dir}/{spawn_id}.json`. Any existing file with the same
     /// ID is overwritten.
     pub fn save(&self, record: &SpawnRecord) -> Result<()> {
-        let file = SpawnFile {
-            version: SPAWN_FILE_VERSION,
-            record: record.clone(),
-        };
-
-        let json = serde_json::to_string_pretty(&file)
-            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
-
-        let path = self.record_path(&record.id);
-        std::fs::write(&path, json.as_bytes())?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        self.write_record(record)
+    }
 
-        Ok(())
+    /// Stage `record` in `txn` to be saved when it commits.
+    pub fn stage(&self, txn: &mut Transaction, record: &SpawnRecord) -> Result<()> {
+        txn.stage_in_store(
+            &self.base_dir,
+            &self.record_path(&record.id),
+            encode_record(record)?.as_bytes(),
+        )
     }
 
     /// Load a spawn record by its ID.
@@ -85,14 +169,7 @@ impl SpawnStore {
         }
 
         let bytes = std::fs::read(&path)?;
-        let file: SpawnFile = serde_json::from_slice(&bytes).map_err(|e| {
-            IdentityError::InvalidFileFormat(format!(
-                "failed to parse spawn file {}: {e}",
-                path.display()
-            ))
-        })?;
-
-        Ok(file.record)
+        migrate::read_wrapped(&path, &bytes, "record")
     }
 
     /// List the IDs of all spawn records stored in this store.
@@ -112,19 +189,89 @@ impl SpawnStore {
         Ok(ids)
     }
 
-    /// Load all spawn records from the store.
+    /// Load all spawn records from the store, ordered by ID.
+    ///
+    /// Corrupt files are skipped. With the `parallel` feature the files are
+    /// read on a bounded worker pool; the result is the same either way.
     pub fn load_all(&self) -> Result<Vec<SpawnRecord>> {
-        let ids = self.list()?;
-        let mut records = Vec::with_capacity(ids.len());
+        let mut ids = self.list()?;
+        ids.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(scan::load_each(&ids, |id| self.load(id)))
+    }
 
-        for id in &ids {
-            match self.load(id) {
-                Ok(record) => records.push(record),
-                Err(_) => continue, // Skip corrupt files
+    /// Load one filtered page of spawn records.
+    ///
+    /// Activity is judged against the whole store, so a child whose parent
+    /// spawn was terminated is inactive even with an indefinite lifetime.
+    pub fn query(&self, query: &SpawnQuery) -> Result<SpawnPage> {
+        let all = self.load_all()?;
+        let after = query.cursor.as_deref().map(parse_cursor).transpose()?;
+
+        let mut matched: Vec<&SpawnRecord> = all
+            .iter()
+            .filter(|r| query.parent.as_ref().is_none_or(|p| r.parent_id == *p))
+            .filter(|r| {
+                query
+                    .spawn_type
+                    .as_deref()
+                    .is_none_or(|t| r.spawn_type.as_tag() == t)
+            })
+            .filter(|r| {
+                !query.active_only || authority_for(&r.child_id, &all).is_ok_and(|a| a.active)
+            })
+            .collect();
+        matched.sort_by(|a, b| (b.spawn_timestamp, &b.id.0).cmp(&(a.spawn_timestamp, &a.id.0)));
+        let total = matched.len();
+
+        let mut page: Vec<SpawnRecord> = matched
+            .into_iter()
+            .filter(|r| {
+                after
+                    .as_ref()
+                    .is_none_or(|(ts, id)| (r.spawn_timestamp, &r.id.0) < (*ts, id))
+            })
+            .take(query.limit.map_or(usize::MAX, |l| l.saturating_add(1)))
+            .cloned()
+            .collect();
+
+        let next_cursor = match query.limit {
+            Some(limit) if page.len() > limit => {
+                page.truncate(limit);
+                page.last().map(page_cursor)
             }
-        }
+            _ => None,
+        };
 
-        Ok(records)
+        Ok(SpawnPage {
+            records: page,
+            total,
+            next_cursor,
+        })
+    }
+
+    /// Meter `amount` of `resource` against the budget of spawn `id` and
+    /// save the updated consumption, returning what remains of the budget
+    /// (`None` if the resource has no budget).
+    ///
+    /// The record is read, updated and rewritten under the store lock, so
+    /// concurrent callers, in this process or another, never lose a charge.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::BudgetExhausted` if the budget does not cover
+    /// `amount` more, in which case the record is left unchanged, or
+    /// `IdentityError::NotFound` if there is no such record.
+    pub fn consume_budget(
+        &self,
+        id: &SpawnId,
+        resource: BudgetResource,
+        amount: u64,
+    ) -> Result<Option<u64>> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut record = self.load(id)?;
+        let remaining = consume_budget(&mut record, resource, amount)?;
+        self.write_record(&record)?;
+        Ok(remaining)
     }
 
     /// Delete the file for a spawn record by its ID.
@@ -133,6 +280,7 @@ impl SpawnStore {
     pub fn delete(&self, id: &SpawnId) -> Result<()> {
         let path = self.record_path(id);
 
+        let _lock = StoreLock::dir(&self.base_dir)?;
         match std::fs::remove_file(&path) {
             Ok(()) => Ok(()),
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
@@ -140,17 +288,44 @@ impl SpawnStore {
         }
     }
 
+    /// Quarantine the spawn records a crash left partial; see
+    /// [`super::atomic`].
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process keeps the
+    /// store locked, or `IdentityError::Io` for filesystem errors.
+    pub fn recover(&self) -> Result<QuarantineReport> {
+        atomic::recover_dir(&self.base_dir, "json")
+    }
+
     // ── Internal helpers ──────────────────────────────────────────────────────
 
+    /// Write `record`'s file; the caller holds the store lock.
+    fn write_record(&self, record: &SpawnRecord) -> Result<()> {
+        let json = encode_record(record)?;
+        write_atomic(&self.record_path(&record.id), json.as_bytes())
+    }
+
     /// Build the filesystem path for a spawn ID.
     fn record_path(&self, id: &SpawnId) -> PathBuf {
         self.base_dir.join(format!("{}.json", id.0))
     }
 }
 
+/// Serialize `record` into the contents of its spawn file.
+fn encode_record(record: &SpawnRecord) -> Result<String> {
+    let file = SpawnFile {
+        version: SPAWN_FILE_VERSION,
+        record: record.clone(),
+    };
+    serde_json::to_string_pretty(&file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -273,4 +448,221 @@ mod tests {
         assert!(loaded.terminated);
         assert_eq!(loaded.termination_reason.as_deref(), Some("test"));
     }
+
+    #[test]
+    fn test_spawn_store_consume_budget_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let parent = IdentityAnchor::new(Some("parent".to_string()));
+        let (_, record, _) = spawn_child(
+            &parent,
+            SpawnType::Worker,
+            "budgeted",
+            vec![Capability::new("read:*")],
+            vec![Capability::new("read:*")],
+            SpawnLifetime::Indefinite,
+            SpawnConstraints {
+                max_receipts: Some(2),
+                ..SpawnConstraints::default()
+            },
+            None,
+            &[],
+        )
+        .unwrap();
+        store.save(&record).unwrap();
+
+        let receipts = BudgetResource::Receipts;
+        assert_eq!(
+            store.consume_budget(&record.id, receipts, 1).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            store.consume_budget(&record.id, receipts, 1).unwrap(),
+            Some(0)
+        );
+        assert!(matches!(
+            store.consume_budget(&record.id, receipts, 1),
+            Err(IdentityError::BudgetExhausted { limit: 2, .. })
+        ));
+        // Unbudgeted resources are metered but never refused.
+        assert_eq!(
+            store
+                .consume_budget(&record.id, BudgetResource::Cost, 500)
+                .unwrap(),
+            None
+        );
+
+        let loaded = store.load(&record.id).unwrap();
+        assert_eq!(loaded.consumption.receipts, 2);
+        assert_eq!(loaded.consumption.cost, 500);
+    }
+
+    #[test]
+    fn test_spawn_store_concurrent_metering_loses_no_charge() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut record = make_record();
+        record.constraints.max_receipts = Some(8);
+        SpawnStore::new(dir.path()).unwrap().save(&record).unwrap();
+
+        // Separate store handles stand in for separate processes.
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let store = SpawnStore::new(dir.path()).unwrap();
+                let id = record.id.clone();
+                std::thread::spawn(move || store.consume_budget(&id, BudgetResource::Receipts, 1))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let store = SpawnStore::new(dir.path()).unwrap();
+        assert_eq!(store.load(&record.id).unwrap().consumption.receipts, 8);
+        assert!(!dir.path().join(".lock").exists());
+    }
+
+    #[test]
+    fn test_spawn_store_recover_quarantines_partial_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let record = make_record();
+        store.save(&record).unwrap();
+        std::fs::write(dir.path().join("aspawn_cut.json"), b"").unwrap();
+        std::fs::write(
+            dir.path().join(format!("{}.json.tmp", record.id.0)),
+            b"{\"version\"",
+        )
+        .unwrap();
+
+        let report = store.recover().unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.quarantined.len(), 2);
+        assert_eq!(store.load_all().unwrap().len(), 1);
+        assert_eq!(store.load(&record.id).unwrap().id, record.id);
+    }
+
+    #[test]
+    fn test_spawn_store_save_reports_store_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let _held = StoreLock::dir(dir.path()).unwrap();
+
+        assert!(matches!(
+            store.save(&make_record()),
+            Err(IdentityError::StoreLocked(_))
+        ));
+    }
+
+    #[test]
+    fn test_spawn_store_query_filters_and_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let parent = IdentityAnchor::new(Some("parent".to_string()));
+        let spawn = |anchor: &IdentityAnchor, spawn_type: SpawnType| {
+            let (child, record, _) = spawn_child(
+                anchor,
+                spawn_type,
+                "fleet",
+                vec![Capability::new("read:*")],
+                vec![Capability::new("read:*")],
+                SpawnLifetime::Indefinite,
+                SpawnConstraints::default(),
+                None,
+                &[],
+            )
+            .unwrap();
+            store.save(&record).unwrap();
+            (child, record)
+        };
+
+        let (child, _) = spawn(&parent, SpawnType::Worker);
+        for _ in 0..3 {
+            spawn(&parent, SpawnType::Worker);
+        }
+        spawn(&parent, SpawnType::Specialist);
+
+        let specialists = store
+            .query(&SpawnQuery::new().spawn_type("specialist"))
+            .unwrap();
+        assert_eq!(specialists.total, 1);
+
+        // A parent with no children yields an empty page, not everything.
+        let childless = store
+            .query(&SpawnQuery::new().parent(IdentityAnchor::new(None).id()))
+            .unwrap();
+        assert_eq!(childless.total, 0);
+        assert!(childless.records.is_empty());
+
+        let first = store
+            .query(&SpawnQuery::new().parent(parent.id()).limit(2))
+            .unwrap();
+        assert_eq!(first.total, 5);
+        assert_eq!(first.records.len(), 2);
+        let cursor = first.next_cursor.clone().unwrap();
+
+        // New spawns land before the cursor and do not shift later pages.
+        spawn(&parent, SpawnType::Worker);
+        let second = store
+            .query(
+                &SpawnQuery::new()
+                    .parent(parent.id())
+                    .limit(2)
+                    .cursor(&cursor),
+            )
+            .unwrap();
+        let third = store
+            .query(
+                &SpawnQuery::new()
+                    .parent(parent.id())
+                    .limit(2)
+                    .cursor(second.next_cursor.clone().unwrap()),
+            )
+            .unwrap();
+        assert_eq!(second.records.len(), 2);
+        assert_eq!(third.records.len(), 1);
+        assert!(third.next_cursor.is_none());
+        assert_eq!(third.records[0].child_id, child.id());
+
+        assert!(store.query(&SpawnQuery::new().cursor("bogus")).is_err());
+    }
+
+    #[test]
+    fn test_spawn_store_query_active_follows_lineage() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = SpawnStore::new(dir.path()).unwrap();
+        let root = IdentityAnchor::new(None);
+
+        let (child, mut child_record, _) = spawn_child(
+            &root,
+            SpawnType::Delegate,
+            "middle",
+            vec![Capability::new("read:*")],
+            vec![Capability::new("read:*")],
+            SpawnLifetime::Indefinite,
+            SpawnConstraints::default(),
+            None,
+            &[],
+        )
+        .unwrap();
+        let (_, grandchild_record, _) = spawn_child(
+            &child,
+            SpawnType::Worker,
+            "leaf",
+            vec![Capability::new("read:*")],
+            vec![Capability::new("read:*")],
+            SpawnLifetime::Indefinite,
+            SpawnConstraints::default(),
+            None,
+            &[],
+        )
+        .unwrap();
+        child_record.terminated = true;
+        store.save(&child_record).unwrap();
+        store.save(&grandchild_record).unwrap();
+
+        let all = store.query(&SpawnQuery::new()).unwrap();
+        assert_eq!(all.total, 2);
+        let active = store.query(&SpawnQuery::new().active_only(true)).unwrap();
+        assert_eq!(active.total, 0);
+    }
 }
@@ -0,0 +1,671 @@
+//! Single-file SQLite store for receipts, trust grants, revocations, and
+//! spawn records.
+//!
+//! The directory stores write one JSON file per object, which is simple and
+//! diff-friendly but slow to list and filter once a store holds tens of
+//! thousands of receipts. [`SqliteStore`] keeps the same records in one
+//! database file. Each row holds the record's JSON (the same serde
+//! representation the directory stores use) alongside indexed columns for
+//! the fields callers filter on:
+//!
+//! ```text
+//! receipts     (id, actor, action_type, timestamp, body)
+//! trust_grants (id, direction, grantor, grantee, granted_at, body)
+//! revocations  (trust_id, revoked_at, body)
+//! spawns       (id, parent_id, child_id, spawn_type, spawn_timestamp, body)
+//! ```
+//!
+//! Only built with the `sqlite` feature.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{IdentityError, Result};
+use crate::identity::IdentityId;
+use crate::receipt::{ActionReceipt, ReceiptId};
+use crate::spawn::{SpawnId, SpawnRecord};
+use crate::trust::{Revocation, TrustGrant, TrustId};
+
+use super::migrate::{self, Versioned};
+use super::receipt_store::ReceiptStore;
+
+// ── Schema ────────────────────────────────────────────────────────────────────
+
+/// Stored in `PRAGMA user_version`; bump when the schema changes.
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS receipts (
+    id          TEXT PRIMARY KEY,
+    actor       TEXT NOT NULL,
+    action_type TEXT NOT NULL,
+    timestamp   INTEGER NOT NULL,
+    body        TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS receipts_actor ON receipts (actor, timestamp);
+CREATE INDEX IF NOT EXISTS receipts_action_type ON receipts (action_type, timestamp);
+CREATE INDEX IF NOT EXISTS receipts_timestamp ON receipts (timestamp);
+
+CREATE TABLE IF NOT EXISTS trust_grants (
+    id          TEXT NOT NULL,
+    direction   TEXT NOT NULL CHECK (direction IN ('granted', 'received')),
+    grantor     TEXT NOT NULL,
+    grantee     TEXT NOT NULL,
+    granted_at  INTEGER NOT NULL,
+    body        TEXT NOT NULL,
+    PRIMARY KEY (id, direction)
+);
+CREATE INDEX IF NOT EXISTS trust_grants_grantee ON trust_grants (grantee);
+CREATE INDEX IF NOT EXISTS trust_grants_grantor ON trust_grants (grantor);
+
+CREATE TABLE IF NOT EXISTS revocations (
+    trust_id    TEXT PRIMARY KEY,
+    revoked_at  INTEGER NOT NULL,
+    body        TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS spawns (
+    id              TEXT PRIMARY KEY,
+    parent_id       TEXT NOT NULL,
+    child_id        TEXT NOT NULL,
+    spawn_type      TEXT NOT NULL,
+    spawn_timestamp INTEGER NOT NULL,
+    body            TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS spawns_parent ON spawns (parent_id, spawn_timestamp);
+";
+
+/// Which side of a grant this store's identity is on.
+const GRANTED: &str = "granted";
+const RECEIVED: &str = "received";
+
+fn db_err(e: rusqlite::Error) -> IdentityError {
+    IdentityError::StorageError(format!("sqlite: {e}"))
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value).map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
+fn decode<T: DeserializeOwned>(kind: &str, id: &str, body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|e| {
+        IdentityError::InvalidFileFormat(format!("failed to parse stored {kind} {id}: {e}"))
+    })
+}
+
+/// [`decode`] a schema-versioned object, upgrading it if it is older.
+fn decode_versioned<T: Versioned>(id: &str, body: &str) -> Result<T> {
+    let value: serde_json::Value = decode(T::KIND, id, body)?;
+    migrate::migrate_or(value, |e| {
+        IdentityError::InvalidFileFormat(format!("failed to parse stored {} {id}: {e}", T::KIND))
+    })
+}
+
+/// Timestamps are microseconds since the epoch and fit comfortably in i64.
+fn ts(micros: u64) -> i64 {
+    micros.min(i64::MAX as u64) as i64
+}
+
+// ── Queries ───────────────────────────────────────────────────────────────────
+
+/// Filter options for [`SqliteStore::query_receipts`].
+///
+/// Every filter is answered from an index. Results are ordered newest first.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiptQuery {
+    actor: Option<IdentityId>,
+    action_type: Option<String>,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: Option<usize>,
+}
+
+impl ReceiptQuery {
+    /// Match every receipt, unlimited.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only receipts signed by `actor`.
+    pub fn actor(mut self, actor: IdentityId) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    /// Only receipts whose action type tag (e.g. `decision`) equals `action_type`.
+    pub fn action_type(mut self, action_type: impl Into<String>) -> Self {
+        self.action_type = Some(action_type.into());
+        self
+    }
+
+    /// Only receipts with `timestamp >= since`.
+    pub fn since(mut self, since: u64) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// Only receipts with `timestamp < until`.
+    pub fn until(mut self, until: u64) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Return at most `limit` receipts.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+// ── SqliteStore ───────────────────────────────────────────────────────────────
+
+/// SQLite-backed store for receipts, trust grants, revocations, and spawn
+/// records.
+///
+/// Saving a record whose ID already exists replaces it, as the directory
+/// stores do. SQLite serialises writers, so several processes may share one
+/// database file.
+pub struct SqliteStore {
+    conn: Connection,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database at `path` and bring its schema up to date.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StorageError` if the database cannot be opened,
+    /// or if it was written by a newer schema version.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::init(Connection::open(path).map_err(db_err)?)
+    }
+
+    /// Open a private in-memory database, mainly for tests.
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory().map_err(db_err)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        let version: i64 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(db_err)?;
+        if version > SCHEMA_VERSION {
+            return Err(IdentityError::StorageError(format!(
+                "database schema version {version} is newer than supported version {SCHEMA_VERSION}"
+            )));
+        }
+        // journal_mode reports the resulting mode as a row; in-memory
+        // databases stay in "memory" mode.
+        conn.pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+            .map_err(db_err)?;
+        conn.execute_batch(SCHEMA).map_err(db_err)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)
+            .map_err(db_err)?;
+        Ok(Self { conn })
+    }
+
+    // ── Receipts ──────────────────────────────────────────────────────────
+
+    /// Persist a receipt, replacing any receipt with the same ID.
+    pub fn save_receipt(&self, receipt: &ActionReceipt) -> Result<()> {
+        insert_receipt(&self.conn, receipt)
+    }
+
+    /// Persist many receipts in one SQL transaction.
+    ///
+    /// Either every receipt is saved or none is.
+    pub fn save_receipts(&mut self, receipts: &[ActionReceipt]) -> Result<()> {
+        let tx = self.conn.transaction().map_err(db_err)?;
+        for receipt in receipts {
+            insert_receipt(&tx, receipt)?;
+        }
+        tx.commit().map_err(db_err)
+    }
+
+    /// Load a receipt by its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no receipt has that ID.
+    pub fn load_receipt(&self, id: &ReceiptId) -> Result<ActionReceipt> {
+        let body: Option<String> = self
+            .conn
+            .query_row("SELECT body FROM receipts WHERE id = ?1", [&id.0], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(db_err)?;
+        match body {
+            Some(body) => decode_versioned(&id.0, &body),
+            None => Err(IdentityError::NotFound(format!("receipt not found: {id}"))),
+        }
+    }
+
+    /// List the IDs of all stored receipts, ordered by ID.
+    pub fn list_receipts(&self) -> Result<Vec<ReceiptId>> {
+        self.ids("SELECT id FROM receipts ORDER BY id")
+            .map(|ids| ids.into_iter().map(ReceiptId).collect())
+    }
+
+    /// Number of stored receipts.
+    pub fn count_receipts(&self) -> Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM receipts", [], |row| {
+                row.get::<_, i64>(0)
+            })
+            .map(|n| n as usize)
+            .map_err(db_err)
+    }
+
+    /// Load the receipts matching `query`, newest first.
+    pub fn query_receipts(&self, query: &ReceiptQuery) -> Result<Vec<ActionReceipt>> {
+        let mut sql = String::from("SELECT id, body FROM receipts WHERE 1 = 1");
+        let mut args: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(actor) = &query.actor {
+            sql.push_str(" AND actor = ?");
+            args.push(actor.0.clone().into());
+        }
+        if let Some(action_type) = &query.action_type {
+            sql.push_str(" AND action_type = ?");
+            args.push(action_type.clone().into());
+        }
+        if let Some(since) = query.since {
+            sql.push_str(" AND timestamp >= ?");
+            args.push(ts(since).into());
+        }
+        if let Some(until) = query.until {
+            sql.push_str(" AND timestamp < ?");
+            args.push(ts(until).into());
+        }
+        sql.push_str(" ORDER BY timestamp DESC, id DESC");
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ?");
+            args.push(ts(limit as u64).into());
+        }
+
+        let mut stmt = self.conn.prepare(&sql).map_err(db_err)?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(args), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(db_err)?;
+        let mut receipts = Vec::new();
+        for row in rows {
+            let (id, body) = row.map_err(db_err)?;
+            receipts.push(decode_versioned(&id, &body)?);
+        }
+        Ok(receipts)
+    }
+
+    /// Delete a receipt. Deleting an unknown ID is a no-op.
+    pub fn delete_receipt(&self, id: &ReceiptId) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM receipts WHERE id = ?1", [&id.0])
+            .map(|_| ())
+            .map_err(db_err)
+    }
+
+    /// Copy every receipt from a directory store into this database in one
+    /// SQL transaction, returning how many were copied.
+    ///
+    /// Corrupt receipt files are skipped, as in [`ReceiptStore::load_all`].
+    pub fn import_receipts(&mut self, from: &ReceiptStore) -> Result<usize> {
+        let receipts = from.load_all()?;
+        self.save_receipts(&receipts)?;
+        Ok(receipts.len())
+    }
+
+    // ── Trust grants ──────────────────────────────────────────────────────
+
+    /// Persist a grant issued by this identity.
+    pub fn save_granted(&self, grant: &TrustGrant) -> Result<()> {
+        self.save_grant(grant, GRANTED)
+    }
+
+    /// Persist a grant issued to this identity.
+    pub fn save_received(&self, grant: &TrustGrant) -> Result<()> {
+        self.save_grant(grant, RECEIVED)
+    }
+
+    fn save_grant(&self, grant: &TrustGrant, direction: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO trust_grants
+                     (id, direction, grantor, grantee, granted_at, body)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    grant.id.0,
+                    direction,
+                    grant.grantor.0,
+                    grant.grantee.0,
+                    ts(grant.granted_at),
+                    encode(grant)?,
+                ],
+            )
+            .map(|_| ())
+            .map_err(db_err)
+    }
+
+    /// Load a grant by its ID, from either direction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no grant has that ID.
+    pub fn load_grant(&self, id: &TrustId) -> Result<TrustGrant> {
+        let body: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT body FROM trust_grants WHERE id = ?1 LIMIT 1",
+                [&id.0],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        match body {
+            Some(body) => decode_versioned(&id.0, &body),
+            None => Err(IdentityError::NotFound(format!(
+                "trust grant not found: {id}"
+            ))),
+        }
+    }
+
+    /// List the IDs of grants issued by this identity.
+    pub fn list_granted(&self) -> Result<Vec<TrustId>> {
+        self.grant_ids(GRANTED)
+    }
+
+    /// List the IDs of grants issued to this identity.
+    pub fn list_received(&self) -> Result<Vec<TrustId>> {
+        self.grant_ids(RECEIVED)
+    }
+
+    fn grant_ids(&self, direction: &str) -> Result<Vec<TrustId>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM trust_grants WHERE direction = ?1 ORDER BY id")
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([direction], |row| row.get::<_, String>(0))
+            .map_err(db_err)?;
+        rows.map(|r| r.map(TrustId).map_err(db_err)).collect()
+    }
+
+    /// Load every grant naming `grantee`, in either direction.
+    pub fn grants_to(&self, grantee: &IdentityId) -> Result<Vec<TrustGrant>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT DISTINCT id, body FROM trust_grants
+                 WHERE grantee = ?1 ORDER BY granted_at, id",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([&grantee.0], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(db_err)?;
+        let mut grants = Vec::new();
+        for row in rows {
+            let (id, body) = row.map_err(db_err)?;
+            grants.push(decode_versioned(&id, &body)?);
+        }
+        Ok(grants)
+    }
+
+    // ── Revocations ───────────────────────────────────────────────────────
+
+    /// Persist a revocation, replacing any earlier one for the same grant.
+    pub fn save_revocation(&self, revocation: &Revocation) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO revocations (trust_id, revoked_at, body)
+                 VALUES (?1, ?2, ?3)",
+                params![
+                    revocation.trust_id.0,
+                    ts(revocation.revoked_at),
+                    encode(revocation)?,
+                ],
+            )
+            .map(|_| ())
+            .map_err(db_err)
+    }
+
+    /// Load the revocation for a grant.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if the grant has not been revoked.
+    pub fn load_revocation(&self, id: &TrustId) -> Result<Revocation> {
+        let body: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT body FROM revocations WHERE trust_id = ?1",
+                [&id.0],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(db_err)?;
+        match body {
+            Some(body) => decode("revocation", &id.0, &body),
+            None => Err(IdentityError::NotFound(format!(
+                "revocation not found: {id}"
+            ))),
+        }
+    }
+
+    /// List the IDs of all revoked grants, ordered by ID.
+    pub fn list_revocations(&self) -> Result<Vec<TrustId>> {
+        self.ids("SELECT trust_id FROM revocations ORDER BY trust_id")
+            .map(|ids| ids.into_iter().map(TrustId).collect())
+    }
+
+    /// Whether a revocation exists for the given grant.
+    pub fn is_revoked(&self, id: &TrustId) -> Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS (SELECT 1 FROM revocations WHERE trust_id = ?1)",
+                [&id.0],
+                |row| row.get(0),
+            )
+            .map_err(db_err)
+    }
+
+    // ── Spawn records ─────────────────────────────────────────────────────
+
+    /// Persist a spawn record, replacing any record with the same ID.
+    pub fn save_spawn(&self, record: &SpawnRecord) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO spawns
+                     (id, parent_id, child_id, spawn_type, spawn_timestamp, body)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    record.id.0,
+                    record.parent_id.0,
+                    record.child_id.0,
+                    record.spawn_type.as_tag(),
+                    ts(record.spawn_timestamp),
+                    encode(record)?,
+                ],
+            )
+            .map(|_| ())
+            .map_err(db_err)
+    }
+
+    /// Load a spawn record by its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if no record has that ID.
+    pub fn load_spawn(&self, id: &SpawnId) -> Result<SpawnRecord> {
+        let body: Option<String> = self
+            .conn
+            .query_row("SELECT body FROM spawns WHERE id = ?1", [&id.0], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(db_err)?;
+        match body {
+            Some(body) => decode_versioned(&id.0, &body),
+            None => Err(IdentityError::NotFound(format!(
+                "spawn record not found: {id}"
+            ))),
+        }
+    }
+
+    /// List the IDs of all stored spawn records, ordered by ID.
+    pub fn list_spawns(&self) -> Result<Vec<SpawnId>> {
+        self.ids("SELECT id FROM spawns ORDER BY id")
+            .map(|ids| ids.into_iter().map(SpawnId).collect())
+    }
+
+    /// Load the direct children of `parent`, oldest first.
+    pub fn children_of(&self, parent: &IdentityId) -> Result<Vec<SpawnRecord>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, body FROM spawns WHERE parent_id = ?1
+                 ORDER BY spawn_timestamp, id",
+            )
+            .map_err(db_err)?;
+        let rows = stmt
+            .query_map([&parent.0], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(db_err)?;
+        let mut records = Vec::new();
+        for row in rows {
+            let (id, body) = row.map_err(db_err)?;
+            records.push(decode_versioned(&id, &body)?);
+        }
+        Ok(records)
+    }
+
+    fn ids(&self, sql: &str) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(sql).map_err(db_err)?;
+        let rows = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(db_err)?;
+        rows.map(|r| r.map_err(db_err)).collect()
+    }
+}
+
+fn insert_receipt(conn: &Connection, receipt: &ActionReceipt) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO receipts (id, actor, action_type, timestamp, body)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            receipt.id.0,
+            receipt.actor.0,
+            receipt.action_type.as_tag(),
+            ts(receipt.timestamp),
+            encode(receipt)?,
+        ],
+    )
+    .map(|_| ())
+    .map_err(db_err)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::receipt::receipt::ReceiptBuilder;
+    use crate::receipt::{ActionContent, ActionType};
+    use crate::trust::{Capability, RevocationReason, TrustGrantBuilder};
+
+    fn make_receipt(anchor: &IdentityAnchor, action_type: ActionType) -> ActionReceipt {
+        ReceiptBuilder::new(anchor.id(), action_type, ActionContent::new("test"))
+            .sign(anchor.signing_key())
+            .expect("signing receipt failed")
+    }
+
+    #[test]
+    fn test_sqlite_receipt_roundtrip_and_query() {
+        let mut store = SqliteStore::open_in_memory().unwrap();
+        let alice = IdentityAnchor::new(None);
+        let bob = IdentityAnchor::new(None);
+        let receipts = vec![
+            make_receipt(&alice, ActionType::Decision),
+            make_receipt(&alice, ActionType::Observation),
+            make_receipt(&bob, ActionType::Decision),
+        ];
+        store.save_receipts(&receipts).unwrap();
+        assert_eq!(store.count_receipts().unwrap(), 3);
+
+        let loaded = store.load_receipt(&receipts[0].id).unwrap();
+        assert_eq!(loaded.receipt_hash, receipts[0].receipt_hash);
+
+        let by_alice = store
+            .query_receipts(&ReceiptQuery::new().actor(alice.id()))
+            .unwrap();
+        assert_eq!(by_alice.len(), 2);
+        let decisions = store
+            .query_receipts(&ReceiptQuery::new().action_type("decision").limit(10))
+            .unwrap();
+        assert_eq!(decisions.len(), 2);
+        let later = store
+            .query_receipts(&ReceiptQuery::new().since(u64::MAX / 2))
+            .unwrap();
+        assert!(later.is_empty());
+
+        store.delete_receipt(&receipts[0].id).unwrap();
+        assert!(matches!(
+            store.load_receipt(&receipts[0].id),
+            Err(IdentityError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_sqlite_grants_and_revocations() {
+        let store = SqliteStore::open_in_memory().unwrap();
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        // The same grant may be stored in both directions.
+        store.save_granted(&grant).unwrap();
+        store.save_received(&grant).unwrap();
+        assert_eq!(store.list_granted().unwrap(), vec![grant.id.clone()]);
+        assert_eq!(store.list_received().unwrap(), vec![grant.id.clone()]);
+        assert_eq!(store.grants_to(&grantee.id()).unwrap().len(), 1);
+        assert_eq!(
+            store.load_grant(&grant.id).unwrap().grant_hash,
+            grant.grant_hash
+        );
+
+        assert!(!store.is_revoked(&grant.id).unwrap());
+        let revocation = Revocation::create(
+            grant.id.clone(),
+            grantor.id(),
+            RevocationReason::ManualRevocation,
+            grantor.signing_key(),
+        );
+        store.save_revocation(&revocation).unwrap();
+        assert!(store.is_revoked(&grant.id).unwrap());
+        assert_eq!(store.list_revocations().unwrap(), vec![grant.id.clone()]);
+    }
+
+    #[test]
+    fn test_sqlite_reopen_persists() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("identity.db");
+        let anchor = IdentityAnchor::new(None);
+        let receipt = make_receipt(&anchor, ActionType::Decision);
+
+        SqliteStore::open(&path)
+            .unwrap()
+            .save_receipt(&receipt)
+            .unwrap();
+        let reopened = SqliteStore::open(&path).unwrap();
+        assert_eq!(reopened.list_receipts().unwrap(), vec![receipt.id]);
+    }
+}
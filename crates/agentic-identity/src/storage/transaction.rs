@@ -0,0 +1,577 @@
+//! Transactions — several store writes applied together or not at all.
+//!
+//! An operation like spawning a child writes an identity file, a spawn
+//! record and a receipt, usually in different store directories. A
+//! [`Transaction`] stages each write and applies them as one unit:
+//!
+//! 1. **Staging.** [`Transaction::stage`] writes the new contents to a
+//!    sibling of the target (`{file}.txn-{id}`, so the final rename never
+//!    crosses filesystems) and records it in the manifest. Targets are
+//!    untouched; a failure, [`Transaction::rollback`], or dropping the
+//!    transaction deletes every staged file.
+//! 2. **Committing.** [`Transaction::commit`] switches the manifest to
+//!    `committing` with one atomic rename — the commit point — then renames
+//!    each staged file over its target and deletes the manifest.
+//!
+//! Staged files and manifests are flushed to disk before they are renamed
+//! into place, and each directory is flushed after its renames, so the
+//! commit point and every applied write survive a power loss. While the
+//! staged files are moved into place the commit holds the lock of every
+//! store it writes to, the same lock that store's own writes take.
+//!
+//! Manifests live in a journal directory shared by every transaction over
+//! the same stores. A manifest left behind by a crash is resolved by
+//! [`recover`], which [`Transaction::begin`] also runs: an interrupted
+//! staging phase is discarded, and an interrupted commit is finished — staged
+//! files still present are the writes not yet applied.
+//!
+//! Only one transaction per journal is open at a time. [`Transaction::begin`]
+//! takes the journal's lock file and holds it until the transaction commits
+//! or rolls back, so two transactions never interleave their writes, and
+//! recovery never mistakes a live transaction for a crashed one. The lock is
+//! a [`StoreLock`], so a crashed process releases it as it dies. Writes made
+//! outside a transaction are serialized by each store's own lock instead.
+//! A transaction that reads a record and writes back a change to it takes
+//! that store's lock early with [`Transaction::lock_store`], so no other
+//! writer can change the record between the read and the commit.
+//!
+//! ```text
+//! {journal}/
+//! ├── lock
+//! └── {txn_id}.json
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::atomic::{sync_parent, tmp_path, write_atomic};
+use super::lock::StoreLock;
+use crate::error::{IdentityError, Result};
+
+/// Lock file held by the open transaction.
+const LOCK_FILE: &str = "lock";
+
+/// How long [`Transaction::begin`] waits for the lock before giving up.
+/// Longer than a store's [`STORE_LOCK_TIMEOUT_SECS`], since a transaction
+/// holds its lock while it stages as well as while it commits.
+///
+/// [`STORE_LOCK_TIMEOUT_SECS`]: super::lock::STORE_LOCK_TIMEOUT_SECS
+pub const LOCK_TIMEOUT_SECS: u64 = 10;
+
+/// How far a transaction got before it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionPhase {
+    /// Writes are being staged; no target has been touched.
+    Staging,
+    /// Every write is staged and they are being moved over their targets.
+    Committing,
+}
+
+/// Record of an open transaction, persisted as `{journal}/{id}.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionManifest {
+    pub id: String,
+    pub phase: TransactionPhase,
+    /// When the transaction began (microseconds since epoch).
+    pub started_at: u64,
+    /// Staged writes, in staging order.
+    pub writes: Vec<StagedWrite>,
+}
+
+/// One staged write.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StagedWrite {
+    /// The file the write replaces or creates.
+    pub target: PathBuf,
+    /// The staged contents, renamed over `target` on commit.
+    pub staged: PathBuf,
+    /// The store directory whose [`StoreLock`] covers `target`, or `None`
+    /// for a file locked on its own, like an identity file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<PathBuf>,
+}
+
+/// What [`recover`] did with transactions left behind by a crash.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// Interrupted commits that were finished.
+    pub applied: usize,
+    /// Interrupted stagings that were discarded.
+    pub discarded: usize,
+}
+
+/// A set of writes applied together or not at all. See the
+/// [module docs](self).
+#[derive(Debug)]
+pub struct Transaction {
+    journal: PathBuf,
+    manifest: TransactionManifest,
+    /// Set once the transaction has committed or been rolled back.
+    finished: bool,
+    /// The journal lock, released once the transaction is finished.
+    lock: Option<StoreLock>,
+    /// Store locks taken by [`Transaction::lock_store`], by store directory.
+    held: Vec<(PathBuf, StoreLock)>,
+}
+
+impl Transaction {
+    /// Open a transaction journaled in `journal`, first resolving any
+    /// transaction a crash left behind.
+    ///
+    /// Blocks while another transaction on the same journal is open.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if the lock is not released
+    /// within [`LOCK_TIMEOUT_SECS`], and `IdentityError::Io` for filesystem
+    /// errors.
+    pub fn begin(journal: &Path) -> Result<Self> {
+        std::fs::create_dir_all(journal)?;
+        let lock = acquire_lock(journal)?;
+
+        recover_locked(journal)?;
+        let started_at = crate::time::now_micros();
+        let manifest = TransactionManifest {
+            id: format!("txn_{started_at:x}_{:x}", std::process::id()),
+            phase: TransactionPhase::Staging,
+            started_at,
+            writes: Vec::new(),
+        };
+        write_manifest(journal, &manifest)?;
+        Ok(Self {
+            journal: journal.to_path_buf(),
+            manifest,
+            finished: false,
+            lock: Some(lock),
+            held: Vec::new(),
+        })
+    }
+
+    /// This transaction's ID.
+    pub fn id(&self) -> &str {
+        &self.manifest.id
+    }
+
+    /// Take the lock of the store rooted at `store` now and hold it until
+    /// the transaction finishes, instead of only while committing.
+    ///
+    /// Reads of that store made after this call stay current through the
+    /// commit, so a read-modify-write staged here cannot lose a concurrent
+    /// write. Locking a store already held does nothing.
+    ///
+    /// # Errors
+    ///
+    /// As for [`StoreLock::dir`].
+    pub fn lock_store(&mut self, store: &Path) -> Result<()> {
+        if !self.held.iter().any(|(dir, _)| dir == store) {
+            let lock = StoreLock::dir(store)?;
+            self.held.push((store.to_path_buf(), lock));
+        }
+        Ok(())
+    }
+
+    /// Stage `data` to be written to the file `target` on commit, under the
+    /// file's own lock ([`StoreLock::file`]).
+    ///
+    /// Staging the same target again replaces the earlier contents. The
+    /// target's parent directory is created if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StorageError` if `target` has no file name,
+    /// and `IdentityError::Io` for filesystem errors.
+    pub fn stage(&mut self, target: &Path, data: &[u8]) -> Result<()> {
+        self.stage_write(target, None, data)
+    }
+
+    /// Stage `data` to be written to `target`, a record of the store rooted
+    /// at `store`, on commit, under the store's lock ([`StoreLock::dir`]).
+    ///
+    /// # Errors
+    ///
+    /// As for [`Transaction::stage`].
+    pub fn stage_in_store(&mut self, store: &Path, target: &Path, data: &[u8]) -> Result<()> {
+        self.stage_write(target, Some(store), data)
+    }
+
+    fn stage_write(&mut self, target: &Path, store: Option<&Path>, data: &[u8]) -> Result<()> {
+        let file_name = target
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| {
+                IdentityError::StorageError(format!("cannot stage a write to {}", target.display()))
+            })?;
+        let staged = target.with_file_name(format!("{file_name}.txn-{}", self.manifest.id));
+
+        // Record the write before creating the file, so a crash in between
+        // never leaves a staged file that recovery does not know about.
+        if !self.manifest.writes.iter().any(|w| w.target == target) {
+            self.manifest.writes.push(StagedWrite {
+                target: target.to_path_buf(),
+                staged: staged.clone(),
+                store: store.map(Path::to_path_buf),
+            });
+            write_manifest(&self.journal, &self.manifest)?;
+        }
+        if let Some(parent) = staged.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        write_atomic(&staged, data)
+    }
+
+    /// Apply every staged write.
+    ///
+    /// Once the manifest reaches the committing phase the writes are
+    /// durable: if moving them into place fails, the manifest is kept and
+    /// the next [`Transaction::begin`] or [`recover`] on this journal
+    /// finishes the commit.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors. An error before
+    /// the commit point rolls every staged write back.
+    pub fn commit(mut self) -> Result<()> {
+        self.manifest.phase = TransactionPhase::Committing;
+        if let Err(e) = write_manifest(&self.journal, &self.manifest) {
+            self.manifest.phase = TransactionPhase::Staging;
+            return Err(e);
+        }
+        self.finished = true;
+        let held: Vec<&Path> = self.held.iter().map(|(dir, _)| dir.as_path()).collect();
+        let applied = apply(&self.journal, &self.manifest, &held);
+        self.held.clear();
+        self.lock = None;
+        applied
+    }
+
+    /// Discard every staged write, leaving all targets as they were.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if a staged file or the manifest cannot
+    /// be removed.
+    pub fn rollback(mut self) -> Result<()> {
+        self.finished = true;
+        let discarded = discard(&self.journal, &self.manifest);
+        self.held.clear();
+        self.lock = None;
+        discarded
+    }
+}
+
+impl Drop for Transaction {
+    /// An abandoned transaction rolls back, then releases the lock.
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = discard(&self.journal, &self.manifest);
+        }
+    }
+}
+
+/// Resolve every transaction a crash left in `journal`: finish interrupted
+/// commits and discard interrupted stagings.
+///
+/// Waits for an open transaction on the journal to finish first.
+///
+/// # Errors
+///
+/// Returns `IdentityError::StoreLocked` if the lock is not released within
+/// [`LOCK_TIMEOUT_SECS`], `IdentityError::SerializationError` for an
+/// unreadable manifest, and `IdentityError::Io` for filesystem errors.
+pub fn recover(journal: &Path) -> Result<RecoveryReport> {
+    if !journal.exists() {
+        return Ok(RecoveryReport::default());
+    }
+    let _lock = acquire_lock(journal)?;
+    recover_locked(journal)
+}
+
+// ── Internal helpers ──────────────────────────────────────────────────────────
+
+fn recover_locked(journal: &Path) -> Result<RecoveryReport> {
+    let mut report = RecoveryReport::default();
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(journal)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+    for path in paths {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => {}
+            // A manifest replacement that never reached its rename.
+            Some("tmp") => {
+                std::fs::remove_file(&path)?;
+                continue;
+            }
+            _ => continue,
+        }
+        let bytes = std::fs::read(&path)?;
+        let manifest: TransactionManifest = serde_json::from_slice(&bytes).map_err(|e| {
+            IdentityError::SerializationError(format!(
+                "transaction manifest {}: {e}",
+                path.display()
+            ))
+        })?;
+        match manifest.phase {
+            TransactionPhase::Committing => {
+                apply(journal, &manifest, &[])?;
+                report.applied += 1;
+            }
+            TransactionPhase::Staging => {
+                discard(journal, &manifest)?;
+                report.discarded += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Move every staged file still present over its target, holding the
+/// targets' locks, then remove the manifest. A staged file already gone was
+/// applied before a crash. Stores in `held` are already locked by the caller.
+fn apply(journal: &Path, manifest: &TransactionManifest, held: &[&Path]) -> Result<()> {
+    let _locks = lock_targets(manifest, held)?;
+    let mut synced: Vec<&Path> = Vec::new();
+    for write in &manifest.writes {
+        if write.staged.exists() {
+            std::fs::rename(&write.staged, &write.target)?;
+        }
+        let dir = write.target.parent().unwrap_or(Path::new(""));
+        if !synced.contains(&dir) {
+            sync_parent(&write.target)?;
+            synced.push(dir);
+        }
+    }
+    std::fs::remove_file(manifest_path(journal, &manifest.id))?;
+    Ok(())
+}
+
+/// Take the lock covering each target of `manifest` that is not in `held`,
+/// in a fixed order.
+fn lock_targets(manifest: &TransactionManifest, held: &[&Path]) -> Result<Vec<StoreLock>> {
+    let mut stores: Vec<&Path> = Vec::new();
+    let mut files: Vec<&Path> = Vec::new();
+    for write in &manifest.writes {
+        match &write.store {
+            Some(store) if held.contains(&store.as_path()) => {}
+            Some(store) => stores.push(store),
+            None => files.push(&write.target),
+        }
+    }
+    stores.sort();
+    stores.dedup();
+    files.sort();
+    files.dedup();
+    let stores = stores.into_iter().map(StoreLock::dir);
+    files
+        .into_iter()
+        .map(StoreLock::file)
+        .chain(stores)
+        .collect()
+}
+
+/// Remove every staged file, then the manifest.
+fn discard(journal: &Path, manifest: &TransactionManifest) -> Result<()> {
+    for write in &manifest.writes {
+        // A staged file whose own atomic write was cut short.
+        for path in [write.staged.clone(), tmp_path(&write.staged)] {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+    }
+    let path = manifest_path(journal, &manifest.id);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn manifest_path(journal: &Path, id: &str) -> PathBuf {
+    journal.join(format!("{id}.json"))
+}
+
+/// Replace the manifest atomically, so a crash leaves either phase readable.
+fn write_manifest(journal: &Path, manifest: &TransactionManifest) -> Result<()> {
+    let json = serde_json::to_vec_pretty(manifest)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    write_atomic(&manifest_path(journal, &manifest.id), &json)
+}
+
+/// Take the journal's lock file, waiting for the current holder.
+fn acquire_lock(journal: &Path) -> Result<StoreLock> {
+    StoreLock::acquire(
+        journal.join(LOCK_FILE),
+        Duration::from_secs(LOCK_TIMEOUT_SECS),
+    )
+}
+
+/// Abandon `txn` as a crashed process would: its lock is released and
+/// nothing else is cleaned up.
+#[cfg(test)]
+fn crash(mut txn: Transaction) {
+    txn.lock = None;
+    std::mem::forget(txn);
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("transactions");
+        (dir, journal)
+    }
+
+    #[test]
+    fn test_commit_applies_every_write() {
+        let (dir, journal) = setup();
+        let a = dir.path().join("identity/child.aid");
+        let b = dir.path().join("spawn/record.json");
+        std::fs::create_dir_all(dir.path().join("spawn")).unwrap();
+        std::fs::write(&b, b"old").unwrap();
+
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.stage(&a, b"child").unwrap();
+        txn.stage(&b, b"first").unwrap();
+        txn.stage(&b, b"record").unwrap();
+        assert_eq!(std::fs::read(&b).unwrap(), b"old");
+        assert!(!a.exists());
+        txn.commit().unwrap();
+
+        assert_eq!(std::fs::read(&a).unwrap(), b"child");
+        assert_eq!(std::fs::read(&b).unwrap(), b"record");
+        let left: Vec<_> = std::fs::read_dir(&journal).unwrap().collect();
+        assert!(left.is_empty());
+        let spawn_files = std::fs::read_dir(dir.path().join("spawn")).unwrap().count();
+        assert_eq!(spawn_files, 1);
+    }
+
+    #[test]
+    fn test_rollback_and_drop_leave_targets_untouched() {
+        let (dir, journal) = setup();
+        let target = dir.path().join("child.aid");
+
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.stage(&target, b"child").unwrap();
+        txn.rollback().unwrap();
+        assert!(!target.exists());
+
+        {
+            let mut txn = Transaction::begin(&journal).unwrap();
+            txn.stage(&target, b"child").unwrap();
+        }
+        assert!(!target.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(recover(&journal).unwrap(), RecoveryReport::default());
+    }
+
+    #[test]
+    fn test_recover_resolves_crashed_transactions() {
+        let (dir, journal) = setup();
+        let a = dir.path().join("a.json");
+        let b = dir.path().join("b.json");
+
+        // A crash mid-commit: "a" was moved into place, "b" was not.
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.stage(&a, b"a").unwrap();
+        txn.stage(&b, b"b").unwrap();
+        let mut manifest = txn.manifest.clone();
+        manifest.phase = TransactionPhase::Committing;
+        write_manifest(&journal, &manifest).unwrap();
+        std::fs::rename(&manifest.writes[0].staged, &a).unwrap();
+        crash(txn);
+
+        let report = recover(&journal).unwrap();
+        assert_eq!((report.applied, report.discarded), (1, 0));
+        assert_eq!(std::fs::read(&a).unwrap(), b"a");
+        assert_eq!(std::fs::read(&b).unwrap(), b"b");
+
+        // A crash mid-staging, with a staged file left behind.
+        let c = dir.path().join("c.json");
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.stage(&c, b"c").unwrap();
+        crash(txn);
+
+        let report = recover(&journal).unwrap();
+        assert_eq!((report.applied, report.discarded), (0, 1));
+        assert!(!c.exists());
+        let mut left: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect();
+        left.sort();
+        assert_eq!(left, ["a.json", "b.json", "transactions"]);
+    }
+
+    #[test]
+    fn test_commit_waits_for_the_target_store_lock() {
+        let (dir, journal) = setup();
+        let store = dir.path().join("spawn");
+        let target = store.join("record.json");
+        let held = StoreLock::dir(&store).unwrap();
+
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.stage_in_store(&store, &target, b"record").unwrap();
+        let committer = std::thread::spawn(move || txn.commit());
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!target.exists());
+
+        drop(held);
+        committer.join().unwrap().unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"record");
+    }
+
+    #[test]
+    fn test_lock_store_holds_the_store_until_commit() {
+        let (dir, journal) = setup();
+        let store = dir.path().join("spawn");
+        let target = store.join("record.json");
+
+        let mut txn = Transaction::begin(&journal).unwrap();
+        txn.lock_store(&store).unwrap();
+        txn.lock_store(&store).unwrap();
+        assert!(matches!(
+            StoreLock::acquire(store.join(".lock"), Duration::ZERO),
+            Err(IdentityError::StoreLocked(_))
+        ));
+
+        // Committing does not wait on the lock the transaction already holds.
+        txn.stage_in_store(&store, &target, b"record").unwrap();
+        txn.commit().unwrap();
+        assert_eq!(std::fs::read(&target).unwrap(), b"record");
+        StoreLock::acquire(store.join(".lock"), Duration::ZERO).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_transactions_do_not_interleave() {
+        let (dir, journal) = setup();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+
+        let handles: Vec<_> = (0..8u8)
+            .map(|n| {
+                let (journal, a, b) = (journal.clone(), a.clone(), b.clone());
+                std::thread::spawn(move || {
+                    let mut txn = Transaction::begin(&journal).unwrap();
+                    txn.stage(&a, &[n]).unwrap();
+                    std::thread::sleep(Duration::from_millis(2));
+                    txn.stage(&b, &[n]).unwrap();
+                    txn.commit().unwrap();
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Whichever committed last wrote both files.
+        assert_eq!(std::fs::read(&a).unwrap(), std::fs::read(&b).unwrap());
+    }
+}
@@ -9,8 +9,12 @@
 //! │   └── {trust_id}.json
 //! ├── received/         — grants received by this identity
 //! │   └── {trust_id}.json
-//! └── revocations/      — revoked grants (either direction)
-//!     └── {trust_id}.json
+//! ├── revocations/      — revoked grants (either direction)
+//! │   └── {trust_id}.json
+//! ├── uses/             — usage ledger, one file per recorded use
+//! │   └── {trust_id}/{n}
+//! └── index/
+//!     └── text.ndjson   — text index journal over capability URIs
 //! ```
 //!
 //! File format for grants:
@@ -22,13 +26,29 @@
 //! ```json
 //! { "version": 1, "revocation": { ... Revocation ... } }
 //! ```
+//!
+//! The `n`th use of a grant is recorded by creating `uses/{trust_id}/{n}`
+//! (holding the time of use in microseconds) with an exclusive create. Two
+//! processes can never claim the same slot, so a grant's `max_uses` holds
+//! even when it is used concurrently. Grant and revocation writes hold the
+//! directory's [`StoreLock`](super::StoreLock) instead, and replace their
+//! file atomically; [`TrustStore::recover`] quarantines files a crash left
+//! partial.
 
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 
 use crate::error::{IdentityError, Result};
-use crate::trust::{Revocation, TrustGrant, TrustId};
+use crate::identity::IdentityId;
+use crate::index::{grant_terms, TextIndex};
+use crate::trust::{verify_revocation_list, Revocation, RevocationList, TrustGrant, TrustId};
+
+use super::atomic::{self, write_atomic, QuarantineReport};
+use super::lock::StoreLock;
+use super::migrate;
+use super::scan;
+use super::transaction::Transaction;
 
 // ── File format constants ─────────────────────────────────────────────────────
 
@@ -59,6 +79,7 @@ struct RevocationFile {
 const GRANTED_DIR: &str = "granted";
 const RECEIVED_DIR: &str = "received";
 const REVOCATIONS_DIR: &str = "revocations";
+const USES_DIR: &str = "uses";
 
 // ── TrustStore ────────────────────────────────────────────────────────────────
 
@@ -110,13 +131,28 @@ impl TrustStore {
         self.write_grant(grant, RECEIVED_DIR)
     }
 
+    /// Stage a trust grant issued by this identity in `txn`, to be saved to
+    /// `granted/` when it commits.
+    ///
+    /// # Errors
+    ///
+    /// As for [`TrustStore::save_granted`].
+    pub fn stage_granted(&self, txn: &mut Transaction, grant: &TrustGrant) -> Result<()> {
+        txn.stage_in_store(
+            &self.base_dir,
+            &self.grant_path(&grant.id, GRANTED_DIR),
+            encode_grant(grant)?.as_bytes(),
+        )
+    }
+
     /// Load a trust grant by ID, checking `granted/` first then `received/`.
     ///
     /// # Errors
     ///
     /// Returns `IdentityError::NotFound` if the grant is not in either
-    /// directory, `IdentityError::InvalidFileFormat` for malformed files, or
-    /// `IdentityError::Io` for filesystem errors.
+    /// directory, `IdentityError::InvalidFileFormat` for malformed files,
+    /// `IdentityError::UnsupportedVersion` for grants written by a newer
+    /// version, or `IdentityError::Io` for filesystem errors.
     pub fn load_grant(&self, id: &TrustId) -> Result<TrustGrant> {
         // Check granted/ first.
         let granted_path = self.grant_path(id, GRANTED_DIR);
@@ -158,6 +194,29 @@ impl TrustStore {
         self.list_ids(RECEIVED_DIR)
     }
 
+    /// Open the text index over the capability URIs of stored grants, in
+    /// either direction. A grant stored in both directories is indexed once.
+    ///
+    /// The journal is reconciled with `granted/` and `received/` first, as
+    /// for [`ReceiptStore::text_index`](super::ReceiptStore::text_index).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` for filesystem errors.
+    pub fn text_index(&self) -> Result<TextIndex> {
+        let stored = self
+            .list_granted()?
+            .into_iter()
+            .chain(self.list_received()?)
+            .map(|id| id.0)
+            .collect();
+        TextIndex::open_synced(&self.text_index_path(), stored, |id| {
+            self.load_grant(&TrustId(id.to_string()))
+                .ok()
+                .map(|g| grant_terms(&g))
+        })
+    }
+
     // ── Revocation persistence ────────────────────────────────────────────────
 
     /// Persist a revocation record to `revocations/`.
@@ -179,7 +238,8 @@ impl TrustStore {
             .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
 
         let path = self.revocation_path(&revocation.trust_id);
-        std::fs::write(&path, json.as_bytes())?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        write_atomic(&path, json.as_bytes())?;
 
         Ok(())
     }
@@ -231,34 +291,181 @@ impl TrustStore {
         self.revocation_path(id).exists()
     }
 
-    // ── Internal helpers ──────────────────────────────────────────────────────
+    /// Load every stored revocation issued by `revoker`, sorted by trust ID.
+    /// Corrupt files are skipped.
+    ///
+    /// This is the content of `revoker`'s [`RevocationList`].
+    pub fn revocations_by(&self, revoker: &IdentityId) -> Result<Vec<Revocation>> {
+        let mut revocations: Vec<Revocation> =
+            scan::load_each(&self.list_revocations()?, |id| self.load_revocation(id))
+                .into_iter()
+                .filter(|r| &r.revoker == revoker)
+                .collect();
+        revocations.sort_by(|a, b| a.trust_id.0.cmp(&b.trust_id.0));
+        Ok(revocations)
+    }
 
-    /// Serialize and write a grant to `{base_dir}/{sub_dir}/{id}.json`.
-    fn write_grant(&self, grant: &TrustGrant, sub_dir: &str) -> Result<()> {
-        let file = TrustGrantFile {
-            version: TRUST_FILE_VERSION,
-            grant: grant.clone(),
+    /// Import the revocations of a remote identity's signed list.
+    ///
+    /// The list is verified against `public_key` first; nothing is stored
+    /// unless it verifies. Revocations already stored are left as they are.
+    /// Returns the number of newly stored revocations.
+    ///
+    /// # Errors
+    ///
+    /// Returns the verification error of [`verify_revocation_list`], or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn import_revocation_list(&self, list: &RevocationList, public_key: &str) -> Result<usize> {
+        verify_revocation_list(list, public_key)?;
+        let mut imported = 0;
+        for revocation in &list.revocations {
+            if !self.is_revoked(&revocation.trust_id) {
+                self.save_revocation(revocation)?;
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+
+    /// Quarantine the grants a crash left partial, and the temporary files
+    /// of interrupted grant and revocation writes; see [`super::atomic`].
+    ///
+    /// A partial revocation file is kept: its presence alone revokes the
+    /// grant, and moving it aside would quietly un-revoke it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::StoreLocked` if another process keeps the
+    /// store locked, or `IdentityError::Io` for filesystem errors.
+    pub fn recover(&self) -> Result<QuarantineReport> {
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        let mut report = QuarantineReport::default();
+        for sub_dir in [GRANTED_DIR, RECEIVED_DIR] {
+            report.merge(atomic::recover_locked(
+                &self.base_dir.join(sub_dir),
+                "json",
+            )?);
+        }
+        report.merge(atomic::recover_interrupted_locked(
+            &self.base_dir.join(REVOCATIONS_DIR),
+            "json",
+        )?);
+        Ok(report)
+    }
+
+    // ── Usage ledger ──────────────────────────────────────────────────────────
+
+    /// The number of recorded uses of a grant (0 if it was never used).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the ledger cannot be read.
+    pub fn use_count(&self, id: &TrustId) -> Result<u64> {
+        match std::fs::read_dir(self.uses_dir(id)) {
+            Ok(entries) => Ok(entries.filter(|e| e.is_ok()).count() as u64),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(IdentityError::Io(e)),
+        }
+    }
+
+    /// When each recorded use of a grant happened (microseconds since
+    /// epoch), oldest first, for
+    /// [`UsageContext::use_times`](crate::trust::UsageContext::use_times).
+    ///
+    /// A use whose slot was claimed but never written, as after a crash,
+    /// counts as happening when its slot file was last modified.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the ledger cannot be read.
+    pub fn use_times(&self, id: &TrustId) -> Result<Vec<u64>> {
+        let entries = match std::fs::read_dir(self.uses_dir(id)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(IdentityError::Io(e)),
         };
+        let mut times = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            let recorded = std::fs::read_to_string(&path)?.trim().parse::<u64>();
+            let time = match recorded {
+                Ok(time) => time,
+                Err(_) => std::fs::metadata(&path)?
+                    .modified()?
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |d| d.as_micros() as u64),
+            };
+            times.push(time);
+        }
+        times.sort_unstable();
+        Ok(times)
+    }
 
-        let json = serde_json::to_string_pretty(&file)
-            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+    /// Record one use of a grant, returning which use it was (1 for the
+    /// first).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if the ledger cannot be written.
+    pub fn record_use(&self, id: &TrustId) -> Result<u64> {
+        self.record_use_within(id, None)
+    }
 
-        let path = self.grant_path(&grant.id, sub_dir);
-        std::fs::write(&path, json.as_bytes())?;
+    /// Record one use of a grant unless `max_uses` uses are already
+    /// recorded, returning which use it was.
+    ///
+    /// Concurrent callers each claim a distinct use, so no more than
+    /// `max_uses` calls ever succeed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::MaxUsesExceeded` if every allowed use has been
+    /// recorded, or `IdentityError::Io` if the ledger cannot be written.
+    pub fn record_use_within(&self, id: &TrustId, max_uses: Option<u64>) -> Result<u64> {
+        let dir = self.uses_dir(id);
+        std::fs::create_dir_all(&dir)?;
+
+        let mut slot = self.use_count(id)? + 1;
+        loop {
+            if max_uses.is_some_and(|max| slot > max) {
+                return Err(IdentityError::MaxUsesExceeded);
+            }
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(dir.join(slot.to_string()))
+            {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    file.write_all(crate::time::now_micros().to_string().as_bytes())?;
+                    return Ok(slot);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => slot += 1,
+                Err(e) => return Err(IdentityError::Io(e)),
+            }
+        }
+    }
 
-        Ok(())
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    /// Serialize and write a grant to `{base_dir}/{sub_dir}/{id}.json`, and
+    /// append its terms to the text index.
+    fn write_grant(&self, grant: &TrustGrant, sub_dir: &str) -> Result<()> {
+        let json = encode_grant(grant)?;
+        let _lock = StoreLock::dir(&self.base_dir)?;
+        write_atomic(&self.grant_path(&grant.id, sub_dir), json.as_bytes())?;
+        TextIndex::append_put(&self.text_index_path(), &grant.id.0, &grant_terms(grant))
+    }
+
+    /// Build the filesystem path for the text index journal.
+    fn text_index_path(&self) -> PathBuf {
+        self.base_dir.join("index").join("text.ndjson")
     }
 
     /// Read and deserialize a grant from an absolute path.
     fn read_grant(&self, path: &std::path::Path) -> Result<TrustGrant> {
         let bytes = std::fs::read(path)?;
-        let file: TrustGrantFile = serde_json::from_slice(&bytes).map_err(|e| {
-            IdentityError::InvalidFileFormat(format!(
-                "failed to parse trust grant file {}: {e}",
-                path.display()
-            ))
-        })?;
-        Ok(file.grant)
+        migrate::read_wrapped(path, &bytes, "grant")
     }
 
     /// Build the filesystem path for a trust grant: `{base_dir}/{sub}/{id}.json`.
@@ -273,6 +480,11 @@ impl TrustStore {
             .join(format!("{}.json", id.0))
     }
 
+    /// Build the ledger directory of a grant: `{base_dir}/uses/{id}/`.
+    fn uses_dir(&self, id: &TrustId) -> PathBuf {
+        self.base_dir.join(USES_DIR).join(&id.0)
+    }
+
     /// Read a directory listing and extract IDs from `{id}.json` filenames.
     fn list_ids(&self, sub_dir: &str) -> Result<Vec<TrustId>> {
         let dir = self.base_dir.join(sub_dir);
@@ -292,9 +504,19 @@ impl TrustStore {
     }
 }
 
+/// Serialize `grant` into the contents of its grant file.
+fn encode_grant(grant: &TrustGrant) -> Result<String> {
+    let file = TrustGrantFile {
+        version: TRUST_FILE_VERSION,
+        grant: grant.clone(),
+    };
+    serde_json::to_string_pretty(&file)
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -368,6 +590,33 @@ mod tests {
         assert_eq!(loaded.id, id);
     }
 
+    #[test]
+    fn test_trust_store_text_index_covers_both_directions() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(dir.path()).unwrap();
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let granted = make_grant(&grantor, &grantee);
+        let received = make_grant(&grantee, &grantor);
+        store.save_granted(&granted).unwrap();
+        store.save_granted(&received).unwrap();
+        store.save_received(&received).unwrap();
+
+        let index = store.text_index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.search("calendar").len(), 2);
+        assert!(index.search_prefix("cal").contains(granted.id.0.as_str()));
+
+        std::fs::remove_file(
+            dir.path()
+                .join("granted")
+                .join(format!("{}.json", granted.id.0)),
+        )
+        .unwrap();
+        assert!(!store.text_index().unwrap().contains(&granted.id.0));
+    }
+
     #[test]
     fn test_trust_store_load_grant_checks_both_dirs() {
         let dir = tempfile::tempdir().unwrap();
@@ -488,6 +737,28 @@ mod tests {
         assert_eq!(loaded.reason, RevocationReason::ManualRevocation);
     }
 
+    #[test]
+    fn test_trust_store_recover_keeps_partial_revocations() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(dir.path()).unwrap();
+        let grantor = IdentityAnchor::new(None);
+        let grant = make_grant(&grantor, &IdentityAnchor::new(None));
+        store.save_granted(&grant).unwrap();
+
+        let granted = dir.path().join(GRANTED_DIR);
+        std::fs::write(granted.join("atrust_cut.json"), b"{\"version\": 1, \"gr").unwrap();
+        let revocations = dir.path().join(REVOCATIONS_DIR);
+        std::fs::write(revocations.join(format!("{}.json", grant.id.0)), b"{\"ver").unwrap();
+        std::fs::write(revocations.join("atrust_other.json.tmp"), b"{").unwrap();
+
+        let report = store.recover().unwrap();
+        assert_eq!(report.quarantined.len(), 2);
+        assert_eq!(store.list_granted().unwrap(), vec![grant.id.clone()]);
+        // The partial revocation still revokes the grant.
+        assert!(store.is_revoked(&grant.id));
+        assert!(!revocations.join("atrust_other.json.tmp").exists());
+    }
+
     #[test]
     fn test_revocation_store_list_revocations() {
         let dir = tempfile::tempdir().unwrap();
@@ -569,6 +840,71 @@ mod tests {
         assert_eq!(value["grant"]["id"].as_str().unwrap(), grant.id.0);
     }
 
+    #[test]
+    fn test_record_use_enforces_max_uses() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrustStore::new(dir.path()).unwrap();
+
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = make_grant(&grantor, &grantee);
+
+        assert_eq!(store.use_count(&grant.id).unwrap(), 0);
+        assert_eq!(store.record_use_within(&grant.id, Some(2)).unwrap(), 1);
+        assert_eq!(store.record_use_within(&grant.id, Some(2)).unwrap(), 2);
+        assert!(matches!(
+            store.record_use_within(&grant.id, Some(2)),
+            Err(IdentityError::MaxUsesExceeded)
+        ));
+        assert_eq!(store.use_count(&grant.id).unwrap(), 2);
+
+        // Without a limit, uses keep counting.
+        assert_eq!(store.record_use(&grant.id).unwrap(), 3);
+        assert_eq!(store.use_count(&grant.id).unwrap(), 3);
+
+        let times = store.use_times(&grant.id).unwrap();
+        assert_eq!(times.len(), 3);
+        assert!(times.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*times.last().unwrap() <= crate::time::now_micros());
+        assert!(store
+            .use_times(&TrustId("never-used".into()))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_revocation_list_export_and_import() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let issuer_dir = tempfile::tempdir().unwrap();
+        let issuer = TrustStore::new(issuer_dir.path()).unwrap();
+
+        let grant = make_grant(&grantor, &grantee);
+        issuer
+            .save_revocation(&make_revocation(&grantor, &grant))
+            .unwrap();
+        let other = make_grant(&grantee, &grantor);
+        issuer
+            .save_revocation(&make_revocation(&grantee, &other))
+            .unwrap();
+
+        let revocations = issuer.revocations_by(&grantor.id()).unwrap();
+        assert_eq!(revocations.len(), 1);
+        let list = RevocationList::sign(&grantor, revocations).unwrap();
+
+        let verifier_dir = tempfile::tempdir().unwrap();
+        let verifier = TrustStore::new(verifier_dir.path()).unwrap();
+        assert!(verifier
+            .import_revocation_list(&list, &grantee.public_key_base64())
+            .is_err());
+        assert!(!verifier.is_revoked(&grant.id));
+
+        let key = grantor.public_key_base64();
+        assert_eq!(verifier.import_revocation_list(&list, &key).unwrap(), 1);
+        assert!(verifier.is_revoked(&grant.id));
+        assert_eq!(verifier.import_revocation_list(&list, &key).unwrap(), 0);
+    }
+
     #[test]
     fn test_revocation_file_format() {
         let dir = tempfile::tempdir().unwrap();
@@ -0,0 +1,424 @@
+//! Identity vaults — many identities in one encrypted file.
+//!
+//! A `.aid` file holds one identity. An [`IdentityVault`] holds any number
+//! of them, keyed by name, under a single passphrase: the private parts of
+//! every identity are serialized together and encrypted with
+//! ChaCha20-Poly1305 under a key derived from the passphrase via Argon2id,
+//! exactly as for a `.aid` file. The public documents are kept in plaintext
+//! so [`read_vault_documents`] can list a vault without the passphrase.
+//!
+//! File format (JSON):
+//! ```json
+//! {
+//!     "version": 1,
+//!     "format": "aid-vault-v1",
+//!     "encryption": {
+//!         "algorithm": "chacha20-poly1305",
+//!         "kdf": "argon2id",
+//!         "salt": "<base64-16-bytes>",
+//!         "nonce": "<base64-12-bytes>"
+//!     },
+//!     "encrypted_identities": "<base64-ciphertext>",
+//!     "public_documents": { "<name>": { ... IdentityDocument ... } }
+//! }
+//! ```
+//!
+//! Changes made through [`IdentityVault::add`] and [`IdentityVault::remove`]
+//! stay in memory until [`IdentityVault::save`] rewrites the file. The
+//! derived key is kept for the life of the value, so saving does not repeat
+//! the key derivation; each save encrypts under a fresh nonce.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::crypto::{derivation, encryption};
+use crate::error::{IdentityError, Result};
+use crate::identity::{IdentityAnchor, IdentityDocument};
+
+use super::identity_file::{write_locked, AnchorPrivateData, EncryptionMetadata};
+
+const VAULT_VERSION: u32 = 1;
+const VAULT_FORMAT: &str = "aid-vault-v1";
+const VAULT_ALGORITHM: &str = "chacha20-poly1305";
+const VAULT_KDF: &str = "argon2id";
+
+/// HKDF context string for deriving the vault encryption key from the
+/// Argon2id master key. Distinct from the `.aid` context so a vault key
+/// never decrypts an identity file. Must remain stable across versions.
+const VAULT_ENCRYPTION_CONTEXT: &str = "identity-vault-encryption";
+
+/// Top-level structure written to disk as a vault file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VaultFile {
+    /// Format version number.
+    pub version: u32,
+    /// Format identifier string.
+    pub format: String,
+    /// Encryption parameters needed for decryption.
+    pub encryption: EncryptionMetadata,
+    /// Base64-encoded ciphertext of every identity's private data.
+    pub encrypted_identities: String,
+    /// Public identity documents, by identity name.
+    pub public_documents: BTreeMap<String, IdentityDocument>,
+}
+
+/// An opened vault: its identities, decrypted, and the key to save them with.
+pub struct IdentityVault {
+    path: PathBuf,
+    salt: [u8; 16],
+    key: [u8; 32],
+    identities: BTreeMap<String, IdentityAnchor>,
+}
+
+impl IdentityVault {
+    /// Create an empty vault at `path`, encrypted under `passphrase`.
+    ///
+    /// Nothing is written until [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if `path` already exists, or
+    /// `IdentityError::DerivationFailed` if key derivation fails.
+    pub fn create(path: &Path, passphrase: &str) -> Result<Self> {
+        if path.exists() {
+            return Err(IdentityError::InvalidInput(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        let salt = crate::crypto::random::random_salt_16();
+        Ok(Self {
+            path: path.to_path_buf(),
+            key: derive_vault_key(passphrase, &salt)?,
+            salt,
+            identities: BTreeMap::new(),
+        })
+    }
+
+    /// Open the vault at `path`, decrypting it with `passphrase`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidPassphrase` if the passphrase is wrong,
+    /// `IdentityError::InvalidFileFormat` for a malformed vault, or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self> {
+        let vault = read_vault_file(path)?;
+
+        let salt: [u8; 16] = decode_b64(&vault.encryption.salt, "salt")?
+            .try_into()
+            .map_err(|_| IdentityError::InvalidFileFormat("salt must be 16 bytes".to_string()))?;
+        let nonce = decode_b64(&vault.encryption.nonce, "nonce")?;
+        if nonce.len() != 12 {
+            return Err(IdentityError::InvalidFileFormat(
+                "nonce must be 12 bytes".to_string(),
+            ));
+        }
+        let ciphertext = decode_b64(&vault.encrypted_identities, "ciphertext")?;
+
+        let mut key = derive_vault_key(passphrase, &salt)?;
+        let mut plaintext = match encryption::decrypt(&key, &nonce, &ciphertext) {
+            Ok(plaintext) => plaintext,
+            Err(e) => {
+                key.zeroize();
+                return Err(e);
+            }
+        };
+        let parsed: serde_json::Result<BTreeMap<String, AnchorPrivateData>> =
+            serde_json::from_slice(&plaintext);
+        plaintext.zeroize();
+        let mut private = parsed
+            .map_err(|e| IdentityError::SerializationError(format!("vault identities: {e}")))?;
+
+        let mut identities = BTreeMap::new();
+        let mut failure = None;
+        for (name, data) in &mut private {
+            match data.to_anchor() {
+                Ok(anchor) => {
+                    identities.insert(name.clone(), anchor);
+                }
+                Err(e) => failure = failure.or(Some(e)),
+            }
+            data.zeroize();
+        }
+        if let Some(e) = failure {
+            key.zeroize();
+            return Err(e);
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            salt,
+            key,
+            identities,
+        })
+    }
+
+    /// Open the vault at `path`, or create an empty one if there is none.
+    ///
+    /// # Errors
+    ///
+    /// As for [`open`](Self::open) and [`create`](Self::create).
+    pub fn open_or_create(path: &Path, passphrase: &str) -> Result<Self> {
+        if path.exists() {
+            Self::open(path, passphrase)
+        } else {
+            Self::create(path, passphrase)
+        }
+    }
+
+    /// The file this vault is saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Names of the identities in the vault, in order.
+    pub fn list(&self) -> Vec<&str> {
+        self.identities.keys().map(String::as_str).collect()
+    }
+
+    /// Whether the vault holds an identity named `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.identities.contains_key(name)
+    }
+
+    /// The identity named `name`, if the vault holds one.
+    pub fn get(&self, name: &str) -> Option<&IdentityAnchor> {
+        self.identities.get(name)
+    }
+
+    /// An owned copy of the identity named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if the vault has no such identity.
+    pub fn load(&self, name: &str) -> Result<IdentityAnchor> {
+        let anchor = self
+            .get(name)
+            .ok_or_else(|| IdentityError::NotFound(format!("identity '{name}' in vault")))?;
+        let mut data = AnchorPrivateData::from_anchor(anchor);
+        let copy = data.to_anchor();
+        data.zeroize();
+        copy
+    }
+
+    /// Add `anchor` under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if `name` is empty or already
+    /// taken; an existing identity is never replaced.
+    pub fn add(&mut self, name: &str, anchor: IdentityAnchor) -> Result<()> {
+        if name.is_empty() {
+            return Err(IdentityError::InvalidInput(
+                "identity name must not be empty".to_string(),
+            ));
+        }
+        if self.contains(name) {
+            return Err(IdentityError::InvalidInput(format!(
+                "identity '{name}' is already in the vault"
+            )));
+        }
+        self.identities.insert(name.to_string(), anchor);
+        Ok(())
+    }
+
+    /// Remove the identity named `name` and return it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::NotFound` if the vault has no such identity.
+    pub fn remove(&mut self, name: &str) -> Result<IdentityAnchor> {
+        self.identities
+            .remove(name)
+            .ok_or_else(|| IdentityError::NotFound(format!("identity '{name}' in vault")))
+    }
+
+    /// Re-key the vault under `new_passphrase`, with a fresh salt. Takes
+    /// effect on the next [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::DerivationFailed` if key derivation fails.
+    pub fn change_passphrase(&mut self, new_passphrase: &str) -> Result<()> {
+        let salt = crate::crypto::random::random_salt_16();
+        let key = derive_vault_key(new_passphrase, &salt)?;
+        self.key.zeroize();
+        self.key = key;
+        self.salt = salt;
+        Ok(())
+    }
+
+    /// Write the vault to its file, atomically.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::EncryptionFailed` if encryption fails or
+    /// `IdentityError::Io` for filesystem errors.
+    pub fn save(&self) -> Result<()> {
+        let mut private: BTreeMap<&str, AnchorPrivateData> = self
+            .identities
+            .iter()
+            .map(|(name, anchor)| (name.as_str(), AnchorPrivateData::from_anchor(anchor)))
+            .collect();
+        let serialized = serde_json::to_vec(&private)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()));
+        private.values_mut().for_each(Zeroize::zeroize);
+        let mut plaintext = serialized?;
+
+        let encrypted = encryption::encrypt(&self.key, &plaintext);
+        plaintext.zeroize();
+        let (nonce, ciphertext) = encrypted?;
+
+        let vault = VaultFile {
+            version: VAULT_VERSION,
+            format: VAULT_FORMAT.to_string(),
+            encryption: EncryptionMetadata {
+                algorithm: VAULT_ALGORITHM.to_string(),
+                kdf: VAULT_KDF.to_string(),
+                salt: encode_b64(&self.salt),
+                nonce: encode_b64(&nonce),
+            },
+            encrypted_identities: encode_b64(&ciphertext),
+            public_documents: self
+                .identities
+                .iter()
+                .map(|(name, anchor)| (name.clone(), anchor.to_document()))
+                .collect(),
+        };
+        let json = serde_json::to_vec_pretty(&vault)
+            .map_err(|e| IdentityError::SerializationError(e.to_string()))?;
+        write_locked(&self.path, &json)
+    }
+}
+
+impl Drop for IdentityVault {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl std::fmt::Debug for IdentityVault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IdentityVault")
+            .field("path", &self.path)
+            .field("identities", &self.list())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Read the public documents of every identity in the vault at `path`,
+/// by name, without the passphrase.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidFileFormat` for a malformed vault or
+/// `IdentityError::Io` for filesystem errors.
+pub fn read_vault_documents(path: &Path) -> Result<BTreeMap<String, IdentityDocument>> {
+    Ok(read_vault_file(path)?.public_documents)
+}
+
+fn read_vault_file(path: &Path) -> Result<VaultFile> {
+    let bytes = std::fs::read(path)?;
+    let vault: VaultFile = serde_json::from_slice(&bytes)
+        .map_err(|e| IdentityError::InvalidFileFormat(format!("failed to parse vault: {e}")))?;
+    if vault.version != VAULT_VERSION || vault.format != VAULT_FORMAT {
+        return Err(IdentityError::InvalidFileFormat(format!(
+            "unsupported vault version={} format={}",
+            vault.version, vault.format,
+        )));
+    }
+    Ok(vault)
+}
+
+/// passphrase → Argon2id(passphrase, salt) → master_key
+/// HKDF-SHA256(master_key, "identity-vault-encryption") → vault key
+fn derive_vault_key(passphrase: &str, salt: &[u8; 16]) -> Result<[u8; 32]> {
+    let mut master_key = encryption::derive_passphrase_key(passphrase.as_bytes(), salt)?;
+    let key = derivation::derive_key(&master_key, VAULT_ENCRYPTION_CONTEXT);
+    master_key.zeroize();
+    key
+}
+
+fn encode_b64(bytes: &[u8]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, bytes)
+}
+
+fn decode_b64(value: &str, what: &str) -> Result<Vec<u8>> {
+    base64::Engine::decode(&base64::engine::general_purpose::STANDARD, value)
+        .map_err(|e| IdentityError::InvalidFileFormat(format!("invalid {what} base64: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_add_save_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identities.vault");
+
+        let alice = IdentityAnchor::new(Some("alice".into()));
+        let bob = IdentityAnchor::new(Some("bob".into()));
+        let (alice_id, bob_id) = (alice.id(), bob.id());
+
+        let mut vault = IdentityVault::create(&path, "pass").unwrap();
+        vault.add("alice", alice).unwrap();
+        vault.add("bob", bob).unwrap();
+        assert!(matches!(
+            vault.add("alice", IdentityAnchor::new(None)),
+            Err(IdentityError::InvalidInput(_))
+        ));
+        vault.save().unwrap();
+        assert!(IdentityVault::create(&path, "pass").is_err());
+
+        let documents = read_vault_documents(&path).unwrap();
+        assert_eq!(documents["alice"].id, alice_id);
+        assert_eq!(documents["bob"].id, bob_id);
+
+        let mut reopened = IdentityVault::open(&path, "pass").unwrap();
+        assert_eq!(reopened.list(), vec!["alice", "bob"]);
+        let loaded = reopened.load("bob").unwrap();
+        assert_eq!(loaded.id(), bob_id);
+        assert_eq!(loaded.name.as_deref(), Some("bob"));
+
+        assert_eq!(reopened.remove("alice").unwrap().id(), alice_id);
+        assert!(matches!(
+            reopened.load("alice"),
+            Err(IdentityError::NotFound(_))
+        ));
+        reopened.save().unwrap();
+        assert_eq!(
+            IdentityVault::open(&path, "pass").unwrap().list(),
+            vec!["bob"]
+        );
+    }
+
+    #[test]
+    fn test_vault_wrong_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("identities.vault");
+
+        let mut vault = IdentityVault::create(&path, "right").unwrap();
+        vault.add("default", IdentityAnchor::new(None)).unwrap();
+        vault.save().unwrap();
+
+        assert!(matches!(
+            IdentityVault::open(&path, "wrong"),
+            Err(IdentityError::InvalidPassphrase)
+        ));
+        // A vault is not an identity file, under either format's reader.
+        assert!(super::super::identity_file::load_identity(&path, "right").is_err());
+
+        vault.change_passphrase("new").unwrap();
+        vault.save().unwrap();
+        assert!(IdentityVault::open(&path, "right").is_err());
+        assert_eq!(
+            IdentityVault::open(&path, "new").unwrap().list(),
+            vec!["default"]
+        );
+    }
+}
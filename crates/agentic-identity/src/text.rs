@@ -0,0 +1,42 @@
+//! Validation for text that callers supply: identity names and capability
+//! URIs.
+//!
+//! These strings are printed to terminals, embedded in JSON and handed
+//! across the FFI boundary as C strings, so control characters are refused
+//! where the value is created. NUL is one of them: a `CString` cannot hold
+//! it, and rejecting it up front gives a clear error instead of an opaque
+//! failure at serialization time. Any other Unicode is accepted.
+
+use crate::error::{IdentityError, Result};
+
+/// Check that `value` contains no control characters.
+///
+/// `what` names the value in the error, e.g. `"identity name"`.
+pub fn validate_text(what: &str, value: &str) -> Result<()> {
+    match value.char_indices().find(|(_, c)| c.is_control()) {
+        None => Ok(()),
+        Some((at, c)) => Err(IdentityError::InvalidInput(format!(
+            "{what} contains control character U+{:04X} at byte {at}",
+            c as u32
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_text() {
+        assert!(validate_text("identity name", "agent-7").is_ok());
+        assert!(validate_text("identity name", "エージェント ✓").is_ok());
+
+        let err = validate_text("identity name", "bad\0name").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid input: identity name contains control character U+0000 at byte 3"
+        );
+        assert!(validate_text("capability", "read:\ncalendar").is_err());
+        assert!(validate_text("capability", "read:\u{7f}").is_err());
+    }
+}
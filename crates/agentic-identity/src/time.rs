@@ -2,6 +2,8 @@
 //!
 //! All timestamps are Unix epoch microseconds (u64).
 
+use crate::error::{IdentityError, Result};
+
 /// Return the current time as microseconds since Unix epoch.
 pub fn now_micros() -> u64 {
     std::time::SystemTime::now()
@@ -17,3 +19,173 @@ pub fn micros_to_rfc3339(micros: u64) -> String {
     let dt = chrono::DateTime::from_timestamp(secs, nsecs).unwrap_or(chrono::DateTime::UNIX_EPOCH);
     dt.to_rfc3339()
 }
+
+/// Longest duration string [`parse_duration_micros`] accepts.
+pub const MAX_DURATION_LEN: usize = 32;
+
+/// Parse a duration like `"24h"`, `"7d"` or `"1h30m"` into microseconds.
+///
+/// A duration is one or more terms, each a number followed by a unit: `d`,
+/// `h`, `m` or `s`. Terms may come in any order (`"30m1h"` equals
+/// `"1h30m"`), but each unit may appear only once, so `"1h1h"` is an error
+/// rather than two hours. A bare number is hours.
+///
+/// Input longer than [`MAX_DURATION_LEN`] characters is rejected, and
+/// parsing stops at the first problem, so no input costs more than that
+/// many characters of work.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidInput` naming the problem: the input is
+/// too long, a unit is unknown or repeated, a unit has no number or a
+/// number no unit, the total is zero, or it does not fit in a `u64`.
+pub fn parse_duration_micros(s: &str) -> Result<u64> {
+    let s = s.trim();
+    let invalid = |why: &str| {
+        let shown = match s.char_indices().nth(MAX_DURATION_LEN) {
+            Some((end, _)) => format!("{}...", &s[..end]),
+            None => s.to_string(),
+        };
+        IdentityError::InvalidInput(format!("duration '{shown}' {why}"))
+    };
+
+    let mut total: u64 = 0;
+    let mut number: Option<u64> = None;
+    let mut seen_units = String::new();
+    for (i, ch) in s.chars().enumerate() {
+        if i == MAX_DURATION_LEN {
+            return Err(invalid(&format!(
+                "is longer than {MAX_DURATION_LEN} characters"
+            )));
+        }
+        if let Some(digit) = ch.to_digit(10) {
+            let value = number
+                .unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(u64::from(digit)))
+                .ok_or_else(|| invalid("is too large"))?;
+            number = Some(value);
+            continue;
+        }
+
+        let unit_micros: u64 = match ch {
+            'd' => 86_400_000_000,
+            'h' => 3_600_000_000,
+            'm' => 60_000_000,
+            's' => 1_000_000,
+            _ => {
+                return Err(invalid(&format!(
+                    "has unknown unit '{ch}' (use d, h, m or s)"
+                )))
+            }
+        };
+        if seen_units.contains(ch) {
+            return Err(invalid(&format!("repeats unit '{ch}'")));
+        }
+        seen_units.push(ch);
+        let value = number
+            .take()
+            .ok_or_else(|| invalid(&format!("has no number before unit '{ch}'")))?;
+        total = value
+            .checked_mul(unit_micros)
+            .and_then(|micros| total.checked_add(micros))
+            .ok_or_else(|| invalid("is too large"))?;
+    }
+
+    match number {
+        Some(hours) if seen_units.is_empty() => {
+            total = hours
+                .checked_mul(3_600_000_000)
+                .ok_or_else(|| invalid("is too large"))?;
+        }
+        Some(_) => return Err(invalid("ends with a number but no unit (use d, h, m or s)")),
+        None => {}
+    }
+    if total == 0 {
+        return Err(invalid("must be greater than zero"));
+    }
+    Ok(total)
+}
+
+/// Format a duration in microseconds the way [`parse_duration_micros`]
+/// reads it, e.g. `"1d2h"` or `"45m30s"`.
+///
+/// Terms run from days down to seconds and zero terms are left out. Time
+/// under a second is dropped, so anything shorter than one second is
+/// `"0s"`.
+pub fn format_duration_micros(micros: u64) -> String {
+    let mut secs = micros / 1_000_000;
+    if secs == 0 {
+        return "0s".to_string();
+    }
+    let mut out = String::new();
+    for (unit, unit_secs) in [('d', 86_400), ('h', 3_600), ('m', 60), ('s', 1)] {
+        let count = secs / unit_secs;
+        if count > 0 {
+            out.push_str(&format!("{count}{unit}"));
+            secs %= unit_secs;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_terms_in_any_order() {
+        assert_eq!(parse_duration_micros("1h30m").unwrap(), 5_400_000_000);
+        assert_eq!(parse_duration_micros("30m1h").unwrap(), 5_400_000_000);
+        assert_eq!(parse_duration_micros(" 2 ").unwrap(), 7_200_000_000);
+        assert_eq!(parse_duration_micros("1d2h3m4s").unwrap(), 93_784_000_000);
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_repeats_and_runaway_input() {
+        let err = parse_duration_micros("1h1h").unwrap_err().to_string();
+        assert!(err.contains("repeats unit 'h'"), "{err}");
+
+        let err = parse_duration_micros(&"1h".repeat(5_000))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("repeats unit 'h'"), "{err}");
+        assert!(err.len() < 200, "{err}");
+
+        let err = parse_duration_micros(&"9".repeat(10_000))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("too large"), "{err}");
+
+        // Leading zeros never overflow, so only the length limit stops them.
+        let err = parse_duration_micros(&"0".repeat(10_000))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("longer than"), "{err}");
+        assert!(err.len() < 200, "{err}");
+    }
+
+    #[test]
+    fn test_format_duration_round_trips() {
+        assert_eq!(format_duration_micros(93_784_000_000), "1d2h3m4s");
+        assert_eq!(format_duration_micros(7_200_999_999), "2h");
+        assert_eq!(format_duration_micros(999_999), "0s");
+        for micros in [1_000_000, 5_400_000_000, 90_061_000_000] {
+            let text = format_duration_micros(micros);
+            assert_eq!(parse_duration_micros(&text).unwrap(), micros, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        for input in ["", "0h", "h", "5x", "1h30", "99999999999999999999d"] {
+            assert!(
+                matches!(
+                    parse_duration_micros(input),
+                    Err(IdentityError::InvalidInput(_))
+                ),
+                "{input}"
+            );
+        }
+    }
+}
@@ -0,0 +1,383 @@
+//! Effective authority over time — what an identity could do at a given moment.
+//!
+//! [`authority_as_of`] is a pure function of a timestamp and the grant,
+//! revocation and spawn records, so it can be evaluated for any point in
+//! the past. [`authority_diff`] compares two such points, which is how an
+//! operator sees grants expire, revocations take effect, and spawned
+//! identities lapse. [`expiry_sweep`] looks ahead instead, listing grants
+//! that will stop verifying soon or already have.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::identity::IdentityId;
+use crate::spawn::SpawnRecord;
+
+use super::capability::{capabilities_cover, Capability};
+use super::grant::TrustGrant;
+use super::revocation::Revocation;
+
+/// How an identity's effective authority changed between two timestamps.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthorityDiff {
+    /// Held at the later timestamp but not the earlier one.
+    pub added: Vec<Capability>,
+    /// Held at the earlier timestamp but not the later one.
+    pub removed: Vec<Capability>,
+}
+
+impl AuthorityDiff {
+    /// No capability was added or removed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The capabilities `identity` held at `now` (microseconds since epoch).
+///
+/// A grant counts if it names `identity` as grantee, its signature
+/// verifies, it had been issued by `now`, `now` lies within its time
+/// constraints, and no revocation of it was issued at or before `now`. Use
+/// limits are not time-based and are ignored.
+///
+/// A spawned identity also holds its spawn's granted authority, narrowed by
+/// every ancestor's. If any spawn in its lineage had not yet happened, had
+/// been terminated, or had outlived its lifetime at `now`, the identity
+/// holds nothing at all. A root identity holds only what it was granted.
+///
+/// The result is sorted by URI with duplicates removed.
+pub fn authority_as_of(
+    identity: &IdentityId,
+    now: u64,
+    grants: &[TrustGrant],
+    revocations: &[Revocation],
+    spawn_records: &[SpawnRecord],
+) -> Vec<Capability> {
+    let mut held: BTreeMap<String, Capability> = BTreeMap::new();
+
+    if let Some(record) = spawn_records.iter().find(|r| r.child_id == *identity) {
+        let Some(spawned) = spawn_authority_at(record, now, spawn_records) else {
+            return Vec::new();
+        };
+        for cap in spawned {
+            held.entry(cap.uri.clone()).or_insert(cap);
+        }
+    }
+
+    for grant in grants {
+        if grant_held_at(grant, identity, now, revocations) {
+            for cap in &grant.capabilities {
+                held.entry(cap.uri.clone()).or_insert_with(|| cap.clone());
+            }
+        }
+    }
+
+    held.into_values().collect()
+}
+
+/// Compare `identity`'s authority at `from` with its authority at `to`.
+///
+/// Comparing a timestamp with itself always yields an empty diff.
+pub fn authority_diff(
+    identity: &IdentityId,
+    from: u64,
+    to: u64,
+    grants: &[TrustGrant],
+    revocations: &[Revocation],
+    spawn_records: &[SpawnRecord],
+) -> AuthorityDiff {
+    let before = authority_as_of(identity, from, grants, revocations, spawn_records);
+    let after = authority_as_of(identity, to, grants, revocations, spawn_records);
+
+    let missing_from =
+        |caps: &[Capability], cap: &Capability| caps.iter().all(|c| c.uri != cap.uri);
+    AuthorityDiff {
+        added: after
+            .iter()
+            .filter(|cap| missing_from(&before, cap))
+            .cloned()
+            .collect(),
+        removed: before
+            .iter()
+            .filter(|cap| missing_from(&after, cap))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Grants whose time window ends within a horizon, or has already ended.
+#[derive(Debug, Clone, Default)]
+pub struct ExpirySweep {
+    /// Still inside their window but past it within the horizon, soonest
+    /// expiry first.
+    pub expiring: Vec<TrustGrant>,
+    /// Already past their window, most recently expired first.
+    pub expired: Vec<TrustGrant>,
+}
+
+/// Sort `grants` with a `not_after` into those expiring within `horizon`
+/// microseconds of `now` and those already expired at `now`.
+///
+/// Expiry uses the same rule as verification: a grant verifies while `now`
+/// is at or before its `not_after`, so it stops verifying `not_after − now`
+/// microseconds from now. Pass the `now` a verifier would use and the two
+/// agree. Indefinite grants, and grants revoked at or before `now`, are in
+/// neither list.
+pub fn expiry_sweep(
+    now: u64,
+    horizon: u64,
+    grants: &[TrustGrant],
+    revocations: &[Revocation],
+) -> ExpirySweep {
+    let mut sweep = ExpirySweep::default();
+    for grant in grants {
+        let Some(not_after) = grant.constraints.not_after else {
+            continue;
+        };
+        if revocations
+            .iter()
+            .any(|r| r.trust_id == grant.id && r.revoked_at <= now)
+        {
+            continue;
+        }
+        if now > not_after {
+            sweep.expired.push(grant.clone());
+        } else if not_after - now <= horizon {
+            sweep.expiring.push(grant.clone());
+        }
+    }
+    let expiry = |g: &TrustGrant| g.constraints.not_after.unwrap_or(0);
+    sweep.expiring.sort_by_key(expiry);
+    sweep.expired.sort_by_key(|g| std::cmp::Reverse(expiry(g)));
+    sweep
+}
+
+fn grant_held_at(
+    grant: &TrustGrant,
+    identity: &IdentityId,
+    now: u64,
+    revocations: &[Revocation],
+) -> bool {
+    grant.grantee == *identity
+        && grant.granted_at <= now
+        && grant.constraints.is_time_valid(now)
+        && !revocations
+            .iter()
+            .any(|r| r.trust_id == grant.id && r.revoked_at <= now)
+        && grant.verify_signature().is_ok()
+}
+
+fn spawn_active_at(record: &SpawnRecord, now: u64) -> bool {
+    let terminated = record.terminated && record.terminated_at.is_none_or(|t| t <= now);
+    record.spawn_timestamp <= now
+        && !terminated
+        && !record.lifetime.is_expired_at(record.spawn_timestamp, now)
+}
+
+/// The spawn's granted authority narrowed by its ancestors', or `None` if
+/// any spawn in the lineage was inactive at `now`.
+fn spawn_authority_at(
+    record: &SpawnRecord,
+    now: u64,
+    spawn_records: &[SpawnRecord],
+) -> Option<Vec<Capability>> {
+    if !spawn_active_at(record, now) {
+        return None;
+    }
+    let mut authority = record.authority_granted.clone();
+    let mut current_id = record.parent_id.clone();
+
+    // Bounded by the record count to guard against cycles.
+    for _ in 0..spawn_records.len() {
+        let Some(parent) = spawn_records.iter().find(|r| r.child_id == current_id) else {
+            break; // Reached root
+        };
+        if !spawn_active_at(parent, now) {
+            return None;
+        }
+        authority.retain(|cap| capabilities_cover(&parent.authority_granted, &cap.uri));
+        current_id = parent.parent_id.clone();
+    }
+    Some(authority)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::spawn::{spawn_child, SpawnConstraints, SpawnLifetime, SpawnType};
+    use crate::trust::constraint::TrustConstraints;
+    use crate::trust::grant::TrustGrantBuilder;
+    use crate::trust::revocation::RevocationReason;
+
+    fn grant(
+        grantor: &IdentityAnchor,
+        grantee: &IdentityAnchor,
+        cap: &str,
+        constraints: TrustConstraints,
+    ) -> TrustGrant {
+        TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capability(Capability::new(cap))
+            .constraints(constraints)
+            .sign(grantor.signing_key())
+            .unwrap()
+    }
+
+    fn uris(caps: &[Capability]) -> Vec<&str> {
+        caps.iter().map(|c| c.uri.as_str()).collect()
+    }
+
+    #[test]
+    fn test_authority_as_of_applies_expiry_and_revocation() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        // A window starting after both grants are issued.
+        let t0 = crate::time::now_micros() + 1_000_000;
+
+        let expiring = grant(
+            &grantor,
+            &grantee,
+            "read:calendar",
+            TrustConstraints::time_bounded(t0, t0 + 1_000),
+        );
+        let revoked = grant(
+            &grantor,
+            &grantee,
+            "write:notes",
+            TrustConstraints::time_bounded(t0, t0 + 10_000_000),
+        );
+        let mut revocation = Revocation::create(
+            revoked.id.clone(),
+            grantor.id(),
+            RevocationReason::ManualRevocation,
+            grantor.signing_key(),
+        );
+        revocation.revoked_at = t0 + 500;
+        let grants = [expiring, revoked];
+        let revocations = [revocation];
+
+        let at = |t| authority_as_of(&grantee.id(), t, &grants, &revocations, &[]);
+        assert_eq!(uris(&at(t0 + 100)), ["read:calendar", "write:notes"]);
+        assert_eq!(uris(&at(t0 + 600)), ["read:calendar"]);
+        assert!(at(t0 + 2_000).is_empty());
+        assert!(at(t0 - 1).is_empty());
+
+        let diff = authority_diff(
+            &grantee.id(),
+            t0 + 100,
+            t0 + 600,
+            &grants,
+            &revocations,
+            &[],
+        );
+        assert!(diff.added.is_empty());
+        assert_eq!(uris(&diff.removed), ["write:notes"]);
+    }
+
+    #[test]
+    fn test_authority_as_of_folds_in_spawn_lifetime() {
+        let parent = IdentityAnchor::new(None);
+        let (child, record, _) = spawn_child(
+            &parent,
+            SpawnType::Worker,
+            "index docs",
+            vec![Capability::new("read:docs")],
+            vec![Capability::new("read:*")],
+            SpawnLifetime::Duration { seconds: 60 },
+            SpawnConstraints::default(),
+            None,
+            &[],
+        )
+        .unwrap();
+        let spawned_at = record.spawn_timestamp;
+        let records = [record];
+
+        let at = |t| authority_as_of(&child.id(), t, &[], &[], &records);
+        assert_eq!(uris(&at(spawned_at)), ["read:docs"]);
+        assert!(at(spawned_at + 61_000_000).is_empty());
+
+        let mut terminated = records[0].clone();
+        terminated.terminated = true;
+        terminated.terminated_at = Some(spawned_at + 1_000);
+        let records = [terminated];
+        let diff = authority_diff(
+            &child.id(),
+            spawned_at,
+            spawned_at + 2_000,
+            &[],
+            &[],
+            &records,
+        );
+        assert_eq!(uris(&diff.removed), ["read:docs"]);
+    }
+
+    #[test]
+    fn test_authority_diff_same_timestamp_is_empty() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grants = [grant(
+            &grantor,
+            &grantee,
+            "read:calendar",
+            TrustConstraints::open(),
+        )];
+        let now = crate::time::now_micros();
+        let diff = authority_diff(&grantee.id(), now, now, &grants, &[], &[]);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_expiry_sweep_buckets_and_order() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let hour = 3_600_000_000;
+        let now = 100 * hour;
+        let bounded = |cap: &str, not_after: u64| {
+            grant(
+                &grantor,
+                &grantee,
+                cap,
+                TrustConstraints::time_bounded(0, not_after),
+            )
+        };
+
+        let revoked = bounded("write:notes", now + 60_000_000);
+        let mut revocation = Revocation::create(
+            revoked.id.clone(),
+            grantor.id(),
+            RevocationReason::ManualRevocation,
+            grantor.signing_key(),
+        );
+        revocation.revoked_at = now;
+        let grants = [
+            bounded("read:late", now + 20 * hour),
+            bounded("read:soon", now + hour),
+            bounded("read:at-now", now),
+            bounded("read:beyond", now + 48 * hour),
+            bounded("read:old", now - 2 * hour),
+            bounded("read:recent", now - 1),
+            grant(&grantor, &grantee, "read:forever", TrustConstraints::open()),
+            revoked,
+        ];
+
+        let sweep = expiry_sweep(now, 24 * hour, &grants, &[revocation]);
+        let names = |gs: &[TrustGrant]| {
+            gs.iter()
+                .map(|g| g.capabilities[0].uri.clone())
+                .collect::<Vec<_>>()
+        };
+        // A grant expiring exactly now still verifies, so it is expiring.
+        assert_eq!(
+            names(&sweep.expiring),
+            ["read:at-now", "read:soon", "read:late"]
+        );
+        assert_eq!(names(&sweep.expired), ["read:recent", "read:old"]);
+        for g in &sweep.expiring {
+            assert!(g.constraints.is_time_valid(now));
+        }
+        for g in &sweep.expired {
+            assert!(!g.constraints.is_time_valid(now));
+        }
+    }
+}
@@ -6,10 +6,39 @@
 //!   - `read:*` — read anything
 //!   - `execute:deploy:production` — execute deploy to production
 //!   - `execute:deploy:*` — execute deploy to any environment
+//!   - `read:*:events` — read events of any one resource
+//!   - `fs:read:/home/*/notes.txt` — one file in every home directory
+//!   - `fs:read:/home/**` — anything under `/home`, at any depth
 //!   - `*` — all capabilities (root trust)
+//!
+//! # Grammar
+//!
+//! A URI is a sequence of segments separated by `:` or `/`. In a granted
+//! URI:
+//!
+//! - `*` matches any run of characters within one segment, so it never
+//!   crosses a `:` or `/` (`deploy-*` matches `deploy-eu` but not
+//!   `deploy:eu`).
+//! - `**` matches any run of characters, separators included.
+//! - A trailing `:*`, `/*`, `:**`, or `/**` matches the prefix itself or
+//!   anything beneath it, at any depth (`read:*` matches `read:calendar`
+//!   and `read:calendar:events`).
+//! - `*` on its own matches everything.
+//!
+//! Every other character matches itself. The same rules apply wherever a
+//! capability is checked: trust grant verification, spawn authority, and
+//! impossibility proofs.
+//!
+//! A requested URI may itself contain wildcards, as when a delegated grant
+//! is checked against its parent. A `*` in the granted URI does not match
+//! a `**` in the requested one, so a grant can never cover a request wider
+//! than itself.
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::Result;
+use crate::text::validate_text;
+
 /// A capability being granted.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Capability {
@@ -31,6 +60,16 @@ impl Capability {
         }
     }
 
+    /// Create a capability, rejecting URIs that contain control characters.
+    ///
+    /// Use this for URIs that come from outside the program; see
+    /// [`validate_text`](crate::text::validate_text).
+    pub fn try_new(uri: impl Into<String>) -> Result<Self> {
+        let uri = uri.into();
+        validate_text("capability URI", &uri)?;
+        Ok(Self::new(uri))
+    }
+
     /// Create a capability with a description.
     pub fn with_description(uri: impl Into<String>, description: impl Into<String>) -> Self {
         Self {
@@ -42,11 +81,14 @@ impl Capability {
 
     /// Check whether this capability's URI covers (grants) a requested URI.
     ///
-    /// Matching rules:
+    /// Matching rules (see the [module documentation](self) for the full
+    /// grammar):
     /// - `*` matches everything
     /// - `action:*` matches any resource under `action:`
     /// - `action:resource` matches exactly
     /// - `action:resource:*` matches anything under `action:resource:`
+    /// - `action:*:sub` matches `sub` of any one resource
+    /// - `fs:/home/**` matches anything under `/home`
     pub fn covers(&self, requested: &str) -> bool {
         capability_uri_covers(&self.uri, requested)
     }
@@ -62,7 +104,8 @@ impl Eq for Capability {}
 
 /// Check whether a granted URI covers a requested URI.
 ///
-/// This is the core wildcard matching logic for capability URIs.
+/// This is the core wildcard matching logic for capability URIs; see the
+/// [module documentation](self) for the grammar.
 pub fn capability_uri_covers(granted: &str, requested: &str) -> bool {
     // Universal wildcard
     if granted == "*" {
@@ -74,28 +117,66 @@ pub fn capability_uri_covers(granted: &str, requested: &str) -> bool {
         return true;
     }
 
-    // Wildcard suffix matching: "read:*" covers "read:calendar"
-    if let Some(prefix) = granted.strip_suffix(":*") {
-        // requested must start with the prefix followed by ':'
-        if requested == prefix {
-            return true;
-        }
-        if requested.starts_with(prefix) && requested.as_bytes().get(prefix.len()) == Some(&b':') {
-            return true;
+    // Trailing wildcard segment: "read:*" covers "read" and anything under
+    // "read:", "storage/**" anything under "storage/".
+    for (suffix, separator) in [(":**", b':'), ("/**", b'/'), (":*", b':'), ("/*", b'/')] {
+        if let Some(prefix) = granted.strip_suffix(suffix) {
+            let (prefix, requested) = (prefix.as_bytes(), requested.as_bytes());
+            return glob_matches(prefix, requested)
+                || requested
+                    .iter()
+                    .enumerate()
+                    .any(|(i, &b)| b == separator && glob_matches(prefix, &requested[..i]));
         }
     }
 
-    // Wildcard suffix with path-like matching: "storage/*" covers "storage/files/readme.md"
-    if let Some(prefix) = granted.strip_suffix("/*") {
-        if requested == prefix {
-            return true;
-        }
-        if requested.starts_with(prefix) && requested.as_bytes().get(prefix.len()) == Some(&b'/') {
-            return true;
+    glob_matches(granted.as_bytes(), requested.as_bytes())
+}
+
+/// Whether `pattern` matches the whole of `uri`, with `*` confined to one
+/// segment and `**` unconfined.
+///
+/// Runs in O(pattern × uri) time, so no pattern can make it backtrack.
+fn glob_matches(pattern: &[u8], uri: &[u8]) -> bool {
+    // matched[j]: the pattern consumed so far matches uri[..j].
+    let mut matched = vec![false; uri.len() + 1];
+    matched[0] = true;
+
+    let mut i = 0;
+    while i < pattern.len() {
+        let mut next = vec![false; uri.len() + 1];
+        if pattern[i] == b'*' {
+            let globstar = pattern.get(i + 1) == Some(&b'*');
+            for j in 0..=uri.len() {
+                next[j] =
+                    matched[j] || (j > 0 && next[j - 1] && star_consumes(globstar, uri, j - 1));
+            }
+            i += if globstar { 2 } else { 1 };
+        } else {
+            for j in 0..uri.len() {
+                next[j + 1] = matched[j] && uri[j] == pattern[i];
+            }
+            i += 1;
         }
+        matched = next;
     }
 
-    false
+    matched[uri.len()]
+}
+
+/// Whether a `*` (or `**`) in a pattern may consume `uri[k]`.
+///
+/// A single `*` stops at separators, and at a `**` in the requested URI,
+/// which is wider than it.
+fn star_consumes(globstar: bool, uri: &[u8], k: usize) -> bool {
+    if globstar {
+        return true;
+    }
+    match uri[k] {
+        b':' | b'/' => false,
+        b'*' => uri.get(k + 1) != Some(&b'*') && (k == 0 || uri[k - 1] != b'*'),
+        _ => true,
+    }
 }
 
 /// Check if a set of granted capabilities covers a single requested capability URI.
@@ -112,6 +193,16 @@ pub fn capabilities_cover_all(granted: &[Capability], requested: &[&str]) -> boo
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_capability_try_new() {
+        assert_eq!(
+            Capability::try_new("read:calendar:会議").unwrap().uri,
+            "read:calendar:会議"
+        );
+        assert!(Capability::try_new("read:\0calendar").is_err());
+        assert!(Capability::try_new("read:calendar\r\n").is_err());
+    }
+
     #[test]
     fn test_exact_match() {
         assert!(capability_uri_covers("read:calendar", "read:calendar"));
@@ -160,6 +251,74 @@ mod tests {
         assert!(!capability_uri_covers("storage/*", "other/files"));
     }
 
+    #[test]
+    fn test_segment_wildcard_stays_in_its_segment() {
+        assert!(capability_uri_covers(
+            "read:*:events",
+            "read:calendar:events"
+        ));
+        assert!(!capability_uri_covers("read:*:events", "read:a:b:events"));
+        assert!(!capability_uri_covers(
+            "read:*:events",
+            "read:calendar:tasks"
+        ));
+        assert!(capability_uri_covers(
+            "execute:deploy-*",
+            "execute:deploy-eu"
+        ));
+        assert!(!capability_uri_covers(
+            "execute:deploy-*",
+            "execute:deploy-eu:prod"
+        ));
+        assert!(capability_uri_covers(
+            "fs:read:/home/*/notes.txt",
+            "fs:read:/home/alice/notes.txt"
+        ));
+        assert!(!capability_uri_covers(
+            "fs:read:/home/*/notes.txt",
+            "fs:read:/home/alice/work/notes.txt"
+        ));
+    }
+
+    #[test]
+    fn test_globstar_crosses_segments() {
+        assert!(capability_uri_covers("fs:read:/home/**", "fs:read:/home"));
+        assert!(capability_uri_covers(
+            "fs:read:/home/**",
+            "fs:read:/home/alice/work/notes.txt"
+        ));
+        assert!(!capability_uri_covers(
+            "fs:read:/home/**",
+            "fs:read:/homeless"
+        ));
+        assert!(!capability_uri_covers(
+            "fs:read:/home/**",
+            "fs:write:/home/alice"
+        ));
+        assert!(capability_uri_covers(
+            "fs:read:/home/**/notes.txt",
+            "fs:read:/home/alice/work/notes.txt"
+        ));
+        assert!(!capability_uri_covers(
+            "fs:read:/home/**/notes.txt",
+            "fs:read:/home/alice/todo.txt"
+        ));
+        assert!(capability_uri_covers("**", "anything:at/all"));
+    }
+
+    #[test]
+    fn test_wildcard_requests_never_widen() {
+        // A request that is itself a pattern is covered only by a pattern
+        // at least as wide.
+        assert!(capability_uri_covers("read:*", "read:*"));
+        assert!(capability_uri_covers("read:*", "read:calendar:*"));
+        assert!(!capability_uri_covers("read:calendar:*", "read:*"));
+        assert!(capability_uri_covers("fs:/home/*/x", "fs:/home/*/x"));
+        assert!(!capability_uri_covers("fs:/home/*/x", "fs:/home/**/x"));
+        assert!(capability_uri_covers("fs:/home/**/x", "fs:/home/*/x"));
+        assert!(!capability_uri_covers("read:*:events", "read:**:events"));
+    }
+
     #[test]
     fn test_no_partial_prefix_match() {
         // "read:*" should NOT match "reading:calendar"
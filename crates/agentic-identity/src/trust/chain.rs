@@ -10,7 +10,7 @@ use crate::error::{IdentityError, Result};
 use super::capability::capabilities_cover;
 use super::grant::{TrustGrant, TrustId};
 use super::revocation::Revocation;
-use super::verify::TrustVerification;
+use super::verify::{TrustVerification, VerifyOptions};
 
 /// Verify a trust chain for a specific capability.
 ///
@@ -29,7 +29,27 @@ pub fn verify_trust_chain(
     requested_capability: &str,
     revocations: &[Revocation],
 ) -> Result<TrustVerification> {
-    let now = crate::time::now_micros();
+    verify_trust_chain_with(
+        chain,
+        requested_capability,
+        revocations,
+        &VerifyOptions::default(),
+    )
+}
+
+/// Verify a trust chain as `options` direct.
+///
+/// Every link's grantor key must be in `options.issuers`, every link must
+/// cover the capability directly or through `options.implications`, and
+/// every link's predicates are checked against `options.context`. Fails
+/// with [`IdentityError::UntrustedTime`] if `options.time` does not verify.
+pub fn verify_trust_chain_with(
+    chain: &[TrustGrant],
+    requested_capability: &str,
+    revocations: &[Revocation],
+    options: &VerifyOptions,
+) -> Result<TrustVerification> {
+    let now = options.time.now()?;
 
     if chain.is_empty() {
         return Err(IdentityError::InvalidChain);
@@ -41,9 +61,14 @@ pub fn verify_trust_chain(
     let mut time_valid = true;
     let mut not_revoked = true;
     let mut cap_granted = true;
+    let mut cap_implied = false;
+    let mut issuer_trusted = true;
+    let mut unknown_fields: Vec<String> = Vec::new();
+    let mut context_violations: Vec<String> = Vec::new();
 
     for (i, grant) in chain.iter().enumerate() {
         trust_chain_ids.push(grant.id.clone());
+        unknown_fields.extend(grant.unknown_fields());
 
         // 1. Signature check
         if grant.verify_signature().is_err() {
@@ -58,18 +83,49 @@ pub fn verify_trust_chain(
         }
 
         // 3. Revocation check — if ANY link is revoked, chain is invalid
-        if revocations.iter().any(|r| r.trust_id == grant.id) {
+        if revocations
+            .iter()
+            .any(|r| r.trust_id == grant.id && r.revoked_at <= now)
+        {
             not_revoked = false;
             all_valid = false;
         }
 
-        // 4. Capability coverage — every link must cover the requested capability
+        // 4. Capability coverage — every link must cover the requested
+        //    capability, directly or through an implication rule
         if !capabilities_cover(&grant.capabilities, requested_capability) {
-            cap_granted = false;
+            if !options.implications.is_empty()
+                && options
+                    .implications
+                    .covers(&grant.capabilities, requested_capability)
+            {
+                cap_implied = true;
+            } else {
+                cap_granted = false;
+                all_valid = false;
+            }
+        }
+
+        // 5. Issuer allowlist — every link's signer must be trusted
+        if !options
+            .issuers
+            .permits(&grant.grantor_key, grant.granted_at)
+        {
+            issuer_trusted = false;
+            all_valid = false;
+        }
+
+        // 6. Predicates, against the use being checked
+        let violations =
+            grant
+                .constraints
+                .predicate_violations(requested_capability, &options.context, now);
+        if !violations.is_empty() {
+            context_violations.extend(violations);
             all_valid = false;
         }
 
-        // 5. Delegation checks (for links after the root)
+        // 7. Delegation checks (for links after the root)
         if i > 0 {
             let parent = &chain[i - 1];
 
@@ -98,7 +154,13 @@ pub fn verify_trust_chain(
         not_revoked,
         uses_valid: true, // Use counting is per-grant, handled externally
         capability_granted: cap_granted,
+        capability_implied: cap_granted && cap_implied,
+        issuer_trusted,
+        grantee_bound: true,
+        context_valid: context_violations.is_empty(),
+        context_violations,
         trust_chain: trust_chain_ids,
+        unknown_fields,
         is_valid: all_valid,
         verified_at: now,
     })
@@ -138,7 +200,7 @@ pub fn validate_delegation(
     Ok(())
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -193,6 +255,55 @@ mod tests {
         assert_eq!(result.trust_chain.len(), 2);
     }
 
+    #[test]
+    fn test_chain_options_apply_to_every_link() {
+        use crate::trust::constraint::{Predicate, TrustConstraints, UsageContext};
+        use crate::trust::policy::ImplicationPolicy;
+        use crate::trust::verify::IssuerAllowlist;
+
+        let a = IdentityAnchor::new(None);
+        let b = IdentityAnchor::new(None);
+        let c = IdentityAnchor::new(None);
+
+        let ab = TrustGrantBuilder::new(a.id(), b.id(), make_key_b64(&b))
+            .capability(Capability::new("admin:*"))
+            .allow_delegation(2)
+            .sign(a.signing_key())
+            .unwrap();
+        let bc = TrustGrantBuilder::new(b.id(), c.id(), make_key_b64(&c))
+            .capability(Capability::new("read:calendar"))
+            .constraints(TrustConstraints::open().with_predicate(
+                "read:*",
+                Predicate::RateLimit {
+                    max: 1,
+                    window_secs: 60,
+                },
+            ))
+            .delegated_from(ab.id.clone(), 1)
+            .sign(b.signing_key())
+            .unwrap();
+        let chain = [ab, bc];
+
+        // The root only covers read:calendar through an implication, and
+        // the leaf's rate limit needs a use history.
+        let result = verify_trust_chain(&chain, "read:calendar", &[]).unwrap();
+        assert!(!result.capability_granted);
+        assert!(!result.context_valid);
+
+        let options = VerifyOptions::new()
+            .implications(ImplicationPolicy::new().imply("admin:*", "read:*"))
+            .context(UsageContext::new().use_times(Vec::new()));
+        let result = verify_trust_chain_with(&chain, "read:calendar", &[], &options).unwrap();
+        assert!(result.is_valid);
+        assert!(result.capability_implied);
+
+        // An allowlist must admit every link's signer.
+        let options = options.issuers(IssuerAllowlist::new().allow(make_key_b64(&a)));
+        let result = verify_trust_chain_with(&chain, "read:calendar", &[], &options).unwrap();
+        assert!(!result.issuer_trusted);
+        assert!(!result.is_valid);
+    }
+
     #[test]
     fn test_delegation_not_allowed() {
         let a = IdentityAnchor::new(None);
@@ -3,11 +3,117 @@
 //! Constraints define the boundaries of a trust grant: when it becomes
 //! valid, when it expires, how many times it can be used, and any
 //! additional custom restrictions.
+//!
+//! Per-capability [`Predicate`]s narrow individual capabilities further —
+//! to resource path prefixes, a use rate, or a value ceiling — and are
+//! checked against the [`UsageContext`] of each use.
+
+use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::capability::capability_uri_covers;
 use crate::error::{IdentityError, Result};
 
+/// A restriction on how one capability of a grant may be used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Predicate {
+    /// The used resource must lie under one of `prefixes`, compared by path
+    /// segment (`/data` admits `/data/x` but not `/database`).
+    ResourcePrefix { prefixes: Vec<String> },
+    /// At most `max` uses within any `window_secs` seconds.
+    RateLimit { max: u64, window_secs: u64 },
+    /// The used value (e.g. an amount spent) must not exceed `max`.
+    MaxValue { max: f64 },
+}
+
+impl Predicate {
+    /// Check one use described by `context` at `now`, returning why it is
+    /// refused, if it is.
+    ///
+    /// A predicate the context carries no data for refuses the use.
+    pub fn violation(&self, context: &UsageContext, now: u64) -> Option<String> {
+        match self {
+            Self::ResourcePrefix { prefixes } => match context.resource.as_deref() {
+                None => Some("no resource given for a resource-scoped capability".into()),
+                Some(resource) if resource.split('/').any(|seg| seg == "..") => {
+                    Some(format!("resource '{resource}' contains '..'"))
+                }
+                Some(resource) if prefixes.iter().any(|p| path_under(resource, p)) => None,
+                Some(resource) => Some(format!(
+                    "resource '{resource}' is outside {}",
+                    prefixes.join(", ")
+                )),
+            },
+            Self::RateLimit { max, window_secs } => {
+                let Some(use_times) = &context.use_times else {
+                    return Some("no use history given for a rate-limited capability".into());
+                };
+                let since = now.saturating_sub(window_secs.saturating_mul(1_000_000));
+                let recent = use_times.iter().filter(|&&t| t > since && t <= now).count() as u64;
+                (recent >= *max).then(|| {
+                    format!("{recent} uses in the last {window_secs}s; at most {max} allowed")
+                })
+            }
+            Self::MaxValue { max } => match context.value {
+                None => Some("no value given for a value-limited capability".into()),
+                Some(value) if value.is_finite() && value <= *max => None,
+                Some(value) => Some(format!("value {value} exceeds {max}")),
+            },
+        }
+    }
+}
+
+/// Is `resource` equal to `prefix` or a path beneath it?
+fn path_under(resource: &str, prefix: &str) -> bool {
+    let Some(rest) = resource.strip_prefix(prefix) else {
+        return false;
+    };
+    rest.is_empty() || prefix.ends_with('/') || rest.starts_with('/')
+}
+
+/// What a single use of a grant touches, for checking [`Predicate`]s.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageContext {
+    /// The resource acted on, e.g. a file path or URL path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+    /// The value involved, e.g. an amount spent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    /// When the grant was used before (microseconds since epoch), for rate
+    /// limits; see [`TrustStore::use_times`](crate::storage::TrustStore::use_times).
+    /// An empty list means it never was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_times: Option<Vec<u64>>,
+}
+
+impl UsageContext {
+    /// A context with no details; any predicate needing one refuses it.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the resource acted on.
+    pub fn resource(mut self, resource: impl Into<String>) -> Self {
+        self.resource = Some(resource.into());
+        self
+    }
+
+    /// Set the value involved.
+    pub fn value(mut self, value: f64) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Set the times of earlier uses.
+    pub fn use_times(mut self, use_times: Vec<u64>) -> Self {
+        self.use_times = Some(use_times);
+        self
+    }
+}
+
 /// Constraints on a trust grant.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrustConstraints {
@@ -23,6 +129,10 @@ pub struct TrustConstraints {
     pub ip_allowlist: Option<Vec<String>>,
     /// Custom constraints (arbitrary JSON).
     pub custom: Option<serde_json::Value>,
+    /// Predicates keyed by the capability pattern they restrict. Omitted
+    /// when empty, so grants without predicates hash as before.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub predicates: BTreeMap<String, Vec<Predicate>>,
 }
 
 impl TrustConstraints {
@@ -35,6 +145,7 @@ impl TrustConstraints {
             geographic: None,
             ip_allowlist: None,
             custom: None,
+            predicates: BTreeMap::new(),
         }
     }
 
@@ -47,6 +158,7 @@ impl TrustConstraints {
             geographic: None,
             ip_allowlist: None,
             custom: None,
+            predicates: BTreeMap::new(),
         }
     }
 
@@ -56,6 +168,37 @@ impl TrustConstraints {
         self
     }
 
+    /// Restrict uses of capabilities matching `capability` with `predicate`.
+    pub fn with_predicate(mut self, capability: impl Into<String>, predicate: Predicate) -> Self {
+        self.predicates
+            .entry(capability.into())
+            .or_default()
+            .push(predicate);
+        self
+    }
+
+    /// Why the use of `capability` described by `context` at `now` breaks
+    /// the predicates on it, if it does.
+    ///
+    /// Predicates apply when their pattern covers `capability`.
+    pub fn predicate_violations(
+        &self,
+        capability: &str,
+        context: &UsageContext,
+        now: u64,
+    ) -> Vec<String> {
+        self.predicates
+            .iter()
+            .filter(|(pattern, _)| capability_uri_covers(pattern, capability))
+            .flat_map(|(pattern, predicates)| {
+                predicates
+                    .iter()
+                    .filter_map(move |p| p.violation(context, now))
+                    .map(move |reason| format!("{pattern}: {reason}"))
+            })
+            .collect()
+    }
+
     /// Check if the constraints are satisfied at the given time with the given use count.
     pub fn validate(&self, now: u64, current_uses: u64) -> Result<()> {
         // Time: not before
@@ -178,4 +321,72 @@ mod tests {
         let c = TrustConstraints::open();
         assert!(c.is_within_uses(u64::MAX));
     }
+
+    #[test]
+    fn test_predicates() {
+        let now = crate::time::now_micros();
+        let c = TrustConstraints::open()
+            .with_predicate(
+                "read:files",
+                Predicate::ResourcePrefix {
+                    prefixes: vec!["/data/reports".into()],
+                },
+            )
+            .with_predicate("spend:usd", Predicate::MaxValue { max: 100.0 })
+            .with_predicate(
+                "spend:*",
+                Predicate::RateLimit {
+                    max: 2,
+                    window_secs: 60,
+                },
+            );
+
+        let ok = |cap: &str, ctx: UsageContext| c.predicate_violations(cap, &ctx, now).is_empty();
+        assert!(ok(
+            "read:files",
+            UsageContext::new().resource("/data/reports/q1")
+        ));
+        assert!(ok(
+            "read:files",
+            UsageContext::new().resource("/data/reports")
+        ));
+        assert!(!ok(
+            "read:files",
+            UsageContext::new().resource("/data/reports-old")
+        ));
+        assert!(!ok(
+            "read:files",
+            UsageContext::new().resource("/data/reports/../keys")
+        ));
+        assert!(!ok("read:files", UsageContext::new()));
+
+        // spend:usd is also rate-limited, so its uses need a history.
+        let unused = || UsageContext::new().use_times(Vec::new());
+        assert!(ok("spend:usd", unused().value(100.0)));
+        assert!(!ok("spend:usd", unused().value(100.5)));
+        assert!(!ok("spend:usd", unused().value(f64::NAN)));
+        assert!(!ok("spend:usd", unused()));
+
+        // Only the rate limit applies to other spend capabilities.
+        let recent = vec![now - 10_000_000, now - 5_000_000];
+        assert!(!ok("spend:eur", UsageContext::new()));
+        assert!(ok("spend:eur", unused()));
+        assert!(!ok("spend:eur", UsageContext::new().use_times(recent)));
+        assert!(ok(
+            "spend:eur",
+            UsageContext::new().use_times(vec![now - 120_000_000, now - 5_000_000])
+        ));
+
+        // Unrestricted capabilities need no context.
+        assert!(ok("write:calendar", UsageContext::new()));
+    }
+
+    #[test]
+    fn test_predicates_keep_legacy_serialization() {
+        let c = TrustConstraints::time_bounded(1, 2);
+        let json = serde_json::to_string(&c).unwrap();
+        assert!(!json.contains("predicates"));
+        let back: TrustConstraints = serde_json::from_str(&json).unwrap();
+        assert!(back.predicates.is_empty());
+    }
 }
@@ -0,0 +1,441 @@
+//! Delegated trust grants — passing on part of a grant's authority.
+//!
+//! When A trusts B with a grant that allows delegation, B can issue C a
+//! grant of its own under A's. That delegated grant names its parent, sits
+//! one level deeper, and may only narrow what the parent conveys.
+//!
+//! [`DelegatedTrustGrant`] is a [`TrustGrant`] known to have a parent. It
+//! serializes exactly as the grant it wraps, so it is stored and exchanged
+//! like any other grant. [`verify_delegation_chain`] checks a root grant and
+//! its delegations link by link, then checks signatures, time bounds, and
+//! revocations as [`verify_trust_chain`](super::chain::verify_trust_chain)
+//! does.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IdentityError, Result};
+
+use super::capability::capabilities_cover;
+use super::chain::verify_trust_chain_with;
+use super::grant::{TrustGrant, TrustId};
+use super::revocation::Revocation;
+use super::verify::{TimeSource, TrustVerification, VerifyOptions};
+
+#[cfg(feature = "signing")]
+use crate::crypto::signer::Signer;
+#[cfg(feature = "signing")]
+use crate::identity::IdentityId;
+
+#[cfg(feature = "signing")]
+use super::capability::Capability;
+#[cfg(feature = "signing")]
+use super::chain::validate_delegation;
+#[cfg(feature = "signing")]
+use super::constraint::TrustConstraints;
+#[cfg(feature = "signing")]
+use super::grant::TrustGrantBuilder;
+
+/// A trust grant issued under another grant.
+///
+/// `parent_grant` always equals `grant.parent_grant`; a value whose two
+/// disagree (built by hand) fails [`verify_delegation_chain`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "TrustGrant", into = "TrustGrant")]
+pub struct DelegatedTrustGrant {
+    /// The grant this one was delegated under.
+    pub parent_grant: TrustId,
+    /// The delegated grant itself.
+    pub grant: TrustGrant,
+}
+
+impl DelegatedTrustGrant {
+    /// Wrap a grant that names a parent grant.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidChain` if `grant` has no parent or
+    /// claims depth 0.
+    pub fn from_grant(grant: TrustGrant) -> Result<Self> {
+        match &grant.parent_grant {
+            Some(parent) if grant.delegation_depth > 0 => Ok(Self {
+                parent_grant: parent.clone(),
+                grant,
+            }),
+            _ => Err(IdentityError::InvalidChain),
+        }
+    }
+
+    /// Unwrap the underlying grant.
+    pub fn into_grant(self) -> TrustGrant {
+        self.grant
+    }
+
+    /// How far below the root grant this grant sits (1 for a grant
+    /// delegated directly under a root grant).
+    pub fn depth(&self) -> u32 {
+        self.grant.delegation_depth
+    }
+
+    /// Start a delegation under `parent` to `grantee`.
+    ///
+    /// The grantor is the parent's grantee; the result must be signed by
+    /// that identity's key.
+    #[cfg(feature = "signing")]
+    pub fn builder(
+        parent: &TrustGrant,
+        grantee: IdentityId,
+        grantee_key: String,
+    ) -> DelegationBuilder<'_> {
+        DelegationBuilder {
+            parent,
+            inner: TrustGrantBuilder::new(parent.grantee.clone(), grantee, grantee_key),
+            capabilities: Vec::new(),
+            max_delegation_depth: None,
+        }
+    }
+}
+
+impl TryFrom<TrustGrant> for DelegatedTrustGrant {
+    type Error = IdentityError;
+
+    fn try_from(grant: TrustGrant) -> Result<Self> {
+        Self::from_grant(grant)
+    }
+}
+
+impl From<DelegatedTrustGrant> for TrustGrant {
+    fn from(delegated: DelegatedTrustGrant) -> Self {
+        delegated.grant
+    }
+}
+
+/// Builder for a [`DelegatedTrustGrant`].
+///
+/// Checks the delegation against the parent when signing, so a grant that
+/// could never verify is never issued.
+#[cfg(feature = "signing")]
+pub struct DelegationBuilder<'a> {
+    parent: &'a TrustGrant,
+    inner: TrustGrantBuilder,
+    capabilities: Vec<Capability>,
+    max_delegation_depth: Option<u32>,
+}
+
+#[cfg(feature = "signing")]
+impl DelegationBuilder<'_> {
+    /// Add a capability; it must be covered by the parent grant.
+    pub fn capability(mut self, cap: Capability) -> Self {
+        self.capabilities.push(cap);
+        self
+    }
+
+    /// Add multiple capabilities; each must be covered by the parent grant.
+    pub fn capabilities(mut self, caps: Vec<Capability>) -> Self {
+        self.capabilities.extend(caps);
+        self
+    }
+
+    /// Set the constraints.
+    pub fn constraints(mut self, constraints: TrustConstraints) -> Self {
+        self.inner = self.inner.constraints(constraints);
+        self
+    }
+
+    /// Allow the grantee to delegate further, down to absolute depth
+    /// `max_depth`. It may not exceed the parent's own limit.
+    pub fn allow_delegation(mut self, max_depth: u32) -> Self {
+        self.max_delegation_depth = Some(max_depth);
+        self
+    }
+
+    /// Record why this grant is being issued.
+    pub fn purpose(mut self, purpose: impl Into<String>) -> Self {
+        self.inner = self.inner.purpose(purpose);
+        self
+    }
+
+    /// Sign the delegated grant as the parent's grantee.
+    ///
+    /// # Errors
+    ///
+    /// - `IdentityError::InvalidKey` if `delegator` is not the parent
+    ///   grant's grantee key.
+    /// - `IdentityError::DelegationNotAllowed` or
+    ///   `IdentityError::DelegationDepthExceeded` if the parent does not
+    ///   permit this delegation, or `allow_delegation` asks for more depth
+    ///   than the parent allows.
+    /// - `IdentityError::TrustNotGranted` if a capability is not covered by
+    ///   the parent.
+    pub fn sign<S: Signer + ?Sized>(self, delegator: &S) -> Result<DelegatedTrustGrant> {
+        let parent = self.parent;
+        if delegator.public_key_base64() != parent.grantee_key {
+            return Err(IdentityError::InvalidKey(format!(
+                "delegating key is not the grantee key of {}",
+                parent.id
+            )));
+        }
+        validate_delegation(parent, &self.capabilities)?;
+
+        let mut inner = self.inner;
+        if let Some(max_depth) = self.max_delegation_depth {
+            if parent
+                .max_delegation_depth
+                .is_some_and(|limit| max_depth > limit)
+            {
+                return Err(IdentityError::DelegationDepthExceeded);
+            }
+            inner = inner.allow_delegation(max_depth);
+        }
+
+        let grant = inner
+            .capabilities(self.capabilities)
+            .delegated_from(parent.id.clone(), parent.delegation_depth + 1)
+            .sign(delegator)?;
+        DelegatedTrustGrant::from_grant(grant)
+    }
+}
+
+/// Verify `delegations` under `root` for a capability.
+///
+/// `delegations` is ordered from the grant delegated under `root` to the
+/// leaf. On top of the checks made by
+/// [`verify_trust_chain`](super::chain::verify_trust_chain), every link must:
+///
+/// 1. Name the previous grant as its parent, one level deeper
+/// 2. Be issued by the previous grant's grantee
+/// 3. Sit no deeper than the limit of any grant above it
+/// 4. Carry only capabilities the previous grant covers
+///
+/// An empty `delegations` verifies `root` on its own.
+///
+/// # Errors
+///
+/// - `IdentityError::InvalidChain` if `root` is itself a delegation or a
+///   link does not follow from the one before it.
+/// - `IdentityError::DelegationNotAllowed` or
+///   `IdentityError::DelegationDepthExceeded` if a link breaks a delegation
+///   limit.
+/// - `IdentityError::AuthorityEscalation` if a link widens its parent.
+pub fn verify_delegation_chain(
+    root: &TrustGrant,
+    delegations: &[DelegatedTrustGrant],
+    requested_capability: &str,
+    revocations: &[Revocation],
+) -> Result<TrustVerification> {
+    verify_delegation_chain_at(
+        root,
+        delegations,
+        requested_capability,
+        revocations,
+        &TimeSource::HostClock,
+    )
+}
+
+/// Verify a delegation chain with every time check made against `time`.
+///
+/// Fails with [`IdentityError::UntrustedTime`] if `time` does not verify.
+pub fn verify_delegation_chain_at(
+    root: &TrustGrant,
+    delegations: &[DelegatedTrustGrant],
+    requested_capability: &str,
+    revocations: &[Revocation],
+    time: &TimeSource,
+) -> Result<TrustVerification> {
+    if root.parent_grant.is_some() || root.delegation_depth != 0 {
+        return Err(IdentityError::InvalidChain);
+    }
+
+    let mut parent = root;
+    // The tightest depth limit set by any grant so far.
+    let mut depth_limit: Option<u32> = None;
+    for link in delegations {
+        let grant = &link.grant;
+
+        if link.parent_grant != parent.id
+            || grant.parent_grant.as_ref() != Some(&parent.id)
+            || grant.delegation_depth != parent.delegation_depth + 1
+            || grant.grantor != parent.grantee
+        {
+            return Err(IdentityError::InvalidChain);
+        }
+
+        if !parent.delegation_allowed {
+            return Err(IdentityError::DelegationNotAllowed);
+        }
+        if let Some(max_depth) = parent.max_delegation_depth {
+            depth_limit = Some(depth_limit.map_or(max_depth, |limit| limit.min(max_depth)));
+        }
+        if depth_limit.is_some_and(|limit| grant.delegation_depth > limit) {
+            return Err(IdentityError::DelegationDepthExceeded);
+        }
+
+        if let Some(cap) = grant
+            .capabilities
+            .iter()
+            .find(|cap| !capabilities_cover(&parent.capabilities, &cap.uri))
+        {
+            return Err(IdentityError::AuthorityEscalation(format!(
+                "{} delegates '{}', which {} does not cover",
+                grant.id, cap.uri, parent.id
+            )));
+        }
+
+        parent = grant;
+    }
+
+    let chain: Vec<TrustGrant> = std::iter::once(root.clone())
+        .chain(delegations.iter().map(|link| link.grant.clone()))
+        .collect();
+    verify_trust_chain_with(
+        &chain,
+        requested_capability,
+        revocations,
+        &VerifyOptions::new().time(time.clone()),
+    )
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::trust::capability::Capability;
+
+    fn root_grant(a: &IdentityAnchor, b: &IdentityAnchor, cap: &str, depth: u32) -> TrustGrant {
+        TrustGrantBuilder::new(a.id(), b.id(), b.public_key_base64())
+            .capability(Capability::new(cap))
+            .allow_delegation(depth)
+            .sign(a.signing_key())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_delegation_chain_narrows_and_verifies() {
+        let (a, b, c, d) = (
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+        );
+        let ab = root_grant(&a, &b, "read:*", 2);
+
+        let bc = DelegatedTrustGrant::builder(&ab, c.id(), c.public_key_base64())
+            .capability(Capability::new("read:calendar:*"))
+            .allow_delegation(2)
+            .sign(b.signing_key())
+            .unwrap();
+        assert_eq!(bc.parent_grant, ab.id);
+        assert_eq!(bc.depth(), 1);
+
+        let cd = DelegatedTrustGrant::builder(&bc.grant, d.id(), d.public_key_base64())
+            .capability(Capability::new("read:calendar:work"))
+            .sign(c.signing_key())
+            .unwrap();
+        assert_eq!(cd.depth(), 2);
+
+        let chain = [bc, cd];
+        let result = verify_delegation_chain(&ab, &chain, "read:calendar:work", &[]).unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.trust_chain.len(), 3);
+
+        // The leaf narrowed away everything outside its own capability.
+        let result = verify_delegation_chain(&ab, &chain, "read:email", &[]).unwrap();
+        assert!(!result.capability_granted);
+
+        // A delegated grant round-trips as a plain grant.
+        let json = serde_json::to_string(&chain[1]).unwrap();
+        let plain: TrustGrant = serde_json::from_str(&json).unwrap();
+        assert_eq!(plain.parent_grant.as_ref(), Some(&chain[0].grant.id));
+        let back: DelegatedTrustGrant = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.grant.id, chain[1].grant.id);
+        assert!(
+            serde_json::from_str::<DelegatedTrustGrant>(&serde_json::to_string(&ab).unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_builder_refuses_invalid_delegations() {
+        let (a, b, c) = (
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+        );
+        let ab = root_grant(&a, &b, "read:calendar", 1);
+
+        // Only B can delegate A's grant to B.
+        let result = DelegatedTrustGrant::builder(&ab, c.id(), c.public_key_base64())
+            .capability(Capability::new("read:calendar"))
+            .sign(c.signing_key());
+        assert!(matches!(result, Err(IdentityError::InvalidKey(_))));
+
+        // Capabilities cannot widen.
+        let result = DelegatedTrustGrant::builder(&ab, c.id(), c.public_key_base64())
+            .capability(Capability::new("write:calendar"))
+            .sign(b.signing_key());
+        assert!(matches!(result, Err(IdentityError::TrustNotGranted(_))));
+
+        // Nor can the depth limit.
+        let result = DelegatedTrustGrant::builder(&ab, c.id(), c.public_key_base64())
+            .capability(Capability::new("read:calendar"))
+            .allow_delegation(3)
+            .sign(b.signing_key());
+        assert!(matches!(
+            result,
+            Err(IdentityError::DelegationDepthExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_verifier_rejects_escalation_depth_and_broken_links() {
+        let (a, b, c, d) = (
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+            IdentityAnchor::new(None),
+        );
+        let ab = root_grant(&a, &b, "read:calendar", 1);
+
+        // Hand-built grants bypass the builder's checks; the verifier
+        // catches them.
+        let widened = TrustGrantBuilder::new(b.id(), c.id(), c.public_key_base64())
+            .capability(Capability::new("read:*"))
+            .delegated_from(ab.id.clone(), 1)
+            .sign(b.signing_key())
+            .unwrap();
+        let widened = DelegatedTrustGrant::from_grant(widened).unwrap();
+        assert!(matches!(
+            verify_delegation_chain(&ab, &[widened], "read:calendar", &[]),
+            Err(IdentityError::AuthorityEscalation(_))
+        ));
+
+        // C's own grant allows depth 5, but A's root limit of 1 still binds.
+        let bc = TrustGrantBuilder::new(b.id(), c.id(), c.public_key_base64())
+            .capability(Capability::new("read:calendar"))
+            .allow_delegation(5)
+            .delegated_from(ab.id.clone(), 1)
+            .sign(b.signing_key())
+            .unwrap();
+        let bc = DelegatedTrustGrant::from_grant(bc).unwrap();
+        let cd = DelegatedTrustGrant::builder(&bc.grant, d.id(), d.public_key_base64())
+            .capability(Capability::new("read:calendar"))
+            .sign(c.signing_key())
+            .unwrap();
+        assert!(matches!(
+            verify_delegation_chain(&ab, &[bc.clone(), cd], "read:calendar", &[]),
+            Err(IdentityError::DelegationDepthExceeded)
+        ));
+
+        // A link under some other parent does not chain.
+        let other = root_grant(&a, &b, "read:calendar", 2);
+        assert!(matches!(
+            verify_delegation_chain(&other, std::slice::from_ref(&bc), "read:calendar", &[]),
+            Err(IdentityError::InvalidChain)
+        ));
+        assert!(DelegatedTrustGrant::from_grant(ab.clone()).is_err());
+        assert!(
+            verify_delegation_chain(&ab, &[bc], "read:calendar", &[])
+                .unwrap()
+                .is_valid
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Exercise receipts — signed records that a grantee used a capability.
+//!
+//! A grant records what was authorized; [`exercise`] records what was done
+//! with it. The grantee signs a `Delegation` receipt naming the grant, the
+//! capability and the use number, so an audit can reconstruct every use.
+//! [`verify_exercise`] checks that receipt and then checks the grant as it
+//! stood at the receipt's timestamp, not as it stands now: a use made
+//! before a revocation or expiry stays valid, one made after does not.
+
+#[cfg(feature = "signing")]
+use serde_json::{json, Value};
+
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityAnchor;
+#[cfg(feature = "signing")]
+use crate::receipt::receipt::ReceiptBuilder;
+#[cfg(feature = "signing")]
+use crate::receipt::ActionContent;
+use crate::receipt::{ActionReceipt, ActionType};
+
+#[cfg(feature = "signing")]
+use super::capability::capabilities_cover;
+use super::grant::{TrustGrant, TrustId};
+use super::revocation::Revocation;
+use super::verify::{verify_trust_grant_with, TimeSource, TrustVerification, VerifyOptions};
+
+/// Record that `grantee_anchor` exercised `capability` under `grant`.
+///
+/// `uses` is the grant's usage counter: it must hold the number of earlier
+/// uses, and is incremented once the receipt is signed. Nothing is signed
+/// and the counter is untouched if the grant does not cover `capability`
+/// (wildcards included), if `grantee_anchor` is not the grantee, or if the
+/// grant has no uses left.
+#[cfg(feature = "signing")]
+pub fn exercise(
+    grant: &TrustGrant,
+    grantee_anchor: &IdentityAnchor,
+    capability: &str,
+    context: Option<Value>,
+    uses: &mut u64,
+) -> Result<ActionReceipt> {
+    if !capabilities_cover(&grant.capabilities, capability) {
+        return Err(IdentityError::TrustNotGranted(capability.to_string()));
+    }
+    if grantee_anchor.id() != grant.grantee
+        || grantee_anchor.public_key_base64() != grant.grantee_key
+    {
+        return Err(IdentityError::InvalidKey(format!(
+            "only the grantee {} can exercise grant {}",
+            grant.grantee, grant.id
+        )));
+    }
+    if !grant.constraints.is_within_uses(*uses) {
+        return Err(IdentityError::MaxUsesExceeded);
+    }
+
+    let use_number = *uses + 1;
+    let mut data = json!({
+        "grant_id": grant.id.0,
+        "grantor": grant.grantor.0,
+        "capability": capability,
+        "use": use_number,
+    });
+    if let Some(context) = context {
+        data["context"] = context;
+    }
+    let mut action =
+        ActionContent::with_data(format!("Exercised {capability} under {}", grant.id), data);
+    action.references.push(grant.id.0.clone());
+
+    let receipt = ReceiptBuilder::new(grant.grantee.clone(), ActionType::Delegation, action)
+        .sign(grantee_anchor.signing_key())?;
+    *uses = use_number;
+    Ok(receipt)
+}
+
+/// The grant, capability and use number an exercise receipt claims.
+fn exercise_claim(receipt: &ActionReceipt) -> Option<(TrustId, String, u64)> {
+    let data = receipt.action.data.as_ref()?;
+    Some((
+        TrustId(data.get("grant_id")?.as_str()?.to_string()),
+        data.get("capability")?.as_str()?.to_string(),
+        data.get("use")?.as_u64()?,
+    ))
+}
+
+/// Verify an exercise receipt against the grant it names.
+///
+/// The receipt must verify, be a `Delegation` receipt naming `grant`, and
+/// be signed by the grantee's key. The grant is then verified for the
+/// exercised capability and use number with every time check made at the
+/// receipt's timestamp, so only revocations issued by then count. Fails
+/// with [`IdentityError::InvalidFileFormat`] if the receipt is not an
+/// exercise receipt at all.
+pub fn verify_exercise(
+    receipt: &ActionReceipt,
+    grant: &TrustGrant,
+    revocations: &[Revocation],
+) -> Result<TrustVerification> {
+    let (grant_id, capability, use_number) = match exercise_claim(receipt) {
+        Some(claim) if receipt.action_type == ActionType::Delegation => claim,
+        _ => {
+            return Err(IdentityError::InvalidFileFormat(format!(
+                "{} is not an exercise receipt",
+                receipt.id
+            )))
+        }
+    };
+    if grant_id != grant.id {
+        return Err(IdentityError::NotFound(format!(
+            "receipt {} exercises {grant_id}, not {}",
+            receipt.id, grant.id
+        )));
+    }
+
+    let signed_by_grantee = receipt.actor == grant.grantee
+        && receipt.actor_key == grant.grantee_key
+        && crate::receipt::verify::verify_receipt(receipt)?.is_valid;
+
+    let mut verification = verify_trust_grant_with(
+        grant,
+        &capability,
+        use_number.saturating_sub(1),
+        revocations,
+        &VerifyOptions::new().time(TimeSource::Trusted(receipt.timestamp)),
+    )?;
+    verification.is_valid &= signed_by_grantee && use_number > 0;
+    Ok(verification)
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::trust::capability::Capability;
+    use crate::trust::constraint::TrustConstraints;
+    use crate::trust::grant::TrustGrantBuilder;
+    use crate::trust::revocation::RevocationReason;
+
+    fn grant_between(grantor: &IdentityAnchor, grantee: &IdentityAnchor, cap: &str) -> TrustGrant {
+        TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+            .capability(Capability::new(cap))
+            .constraints(TrustConstraints::open().with_max_uses(2))
+            .sign(grantor.signing_key())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_exercise_signed_by_grantee() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = grant_between(&grantor, &grantee, "read:calendar:*");
+        let mut uses = 0;
+
+        let receipt = exercise(
+            &grant,
+            &grantee,
+            "read:calendar:work",
+            Some(json!({"event": "standup"})),
+            &mut uses,
+        )
+        .unwrap();
+        assert_eq!(uses, 1);
+        assert_eq!(receipt.action_type, ActionType::Delegation);
+        assert_eq!(receipt.actor, grantee.id());
+        assert_eq!(receipt.actor_key, grantee.public_key_base64());
+        let data = receipt.action.data.as_ref().unwrap();
+        assert_eq!(data["grant_id"], grant.id.0);
+        assert_eq!(data["capability"], "read:calendar:work");
+        assert_eq!(data["context"]["event"], "standup");
+
+        let verification = verify_exercise(&receipt, &grant, &[]).unwrap();
+        assert!(verification.is_valid);
+    }
+
+    #[test]
+    fn test_exercise_rejects_uncovered_capability_and_wrong_signer() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = grant_between(&grantor, &grantee, "read:calendar:*");
+        let mut uses = 0;
+
+        let err = exercise(&grant, &grantee, "write:calendar:work", None, &mut uses);
+        assert!(matches!(err, Err(IdentityError::TrustNotGranted(_))));
+        assert!(exercise(&grant, &grantor, "read:calendar:work", None, &mut uses).is_err());
+        assert_eq!(uses, 0);
+
+        exercise(&grant, &grantee, "read:calendar:a", None, &mut uses).unwrap();
+        exercise(&grant, &grantee, "read:calendar:b", None, &mut uses).unwrap();
+        let err = exercise(&grant, &grantee, "read:calendar:c", None, &mut uses);
+        assert!(matches!(err, Err(IdentityError::MaxUsesExceeded)));
+        assert_eq!(uses, 2);
+    }
+
+    #[test]
+    fn test_verify_exercise_as_of_receipt_time() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = grant_between(&grantor, &grantee, "read:calendar:*");
+        let mut uses = 0;
+        let receipt = exercise(&grant, &grantee, "read:calendar:work", None, &mut uses).unwrap();
+
+        // A revocation issued after the use does not invalidate it.
+        let mut revocation = Revocation::create(
+            grant.id.clone(),
+            grantor.id(),
+            RevocationReason::ManualRevocation,
+            grantor.signing_key(),
+        );
+        revocation.revoked_at = receipt.timestamp + 1;
+        assert!(
+            verify_exercise(&receipt, &grant, &[revocation.clone()])
+                .unwrap()
+                .is_valid
+        );
+
+        // One issued before it does.
+        revocation.revoked_at = receipt.timestamp - 1;
+        let verification = verify_exercise(&receipt, &grant, &[revocation]).unwrap();
+        assert!(!verification.not_revoked);
+        assert!(!verification.is_valid);
+    }
+
+    #[test]
+    fn test_verify_exercise_rejects_grantor_signed_receipt() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = grant_between(&grantor, &grantee, "read:calendar:*");
+        let mut uses = 0;
+        let genuine = exercise(&grant, &grantee, "read:calendar:work", None, &mut uses).unwrap();
+
+        let forged = ReceiptBuilder::new(grantor.id(), ActionType::Delegation, genuine.action)
+            .sign(grantor.signing_key())
+            .unwrap();
+        assert!(!verify_exercise(&forged, &grant, &[]).unwrap().is_valid);
+    }
+}
@@ -3,17 +3,24 @@
 //! A trust grant is a cryptographic object where identity A says
 //! "I trust identity B to do {capabilities} under {constraints}."
 
-use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::crypto::signing;
+#[cfg(feature = "signing")]
+use crate::contracts::policy::PolicySet;
+use crate::crypto::canonical;
+use crate::crypto::signer::{sign_in_domain_with, sign_with, Signer};
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::error::{IdentityError, Result};
+use crate::identity::multisig::Cosignature;
 use crate::identity::IdentityId;
+use crate::receipt::ReceiptId;
 
 use super::capability::Capability;
 use super::constraint::TrustConstraints;
-use super::revocation::{RevocationChannel, RevocationConfig};
+#[cfg(feature = "signing")]
+use super::revocation::RevocationChannel;
+use super::revocation::RevocationConfig;
 
 /// Unique identifier for a trust grant.
 ///
@@ -21,13 +28,59 @@ use super::revocation::{RevocationChannel, RevocationConfig};
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TrustId(pub String);
 
+impl TrustId {
+    /// Parse a trust ID from untrusted input, checking its prefix and charset.
+    pub fn parse(s: &str) -> Result<Self> {
+        crate::identity::anchor::validate_prefixed_id(s, "atrust_")?;
+        Ok(Self(s.to_string()))
+    }
+}
+
 impl std::fmt::Display for TrustId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Top-level field names understood by this version of [`TrustGrant`].
+#[cfg(feature = "signing")]
+const KNOWN_FIELDS: &[&str] = &[
+    "id",
+    "grantor",
+    "grantor_key",
+    "grantee",
+    "grantee_key",
+    "capabilities",
+    "constraints",
+    "delegation_allowed",
+    "max_delegation_depth",
+    "parent_grant",
+    "delegation_depth",
+    "revocation",
+    "granted_at",
+    "grant_hash",
+    "grantor_signature",
+    "grantee_acknowledgment",
+    "schema_version",
+];
+
+/// Optional top-level fields, omitted from JSON when unset.
+#[cfg(feature = "signing")]
+const OPTIONAL_FIELDS: &[&str] = &[
+    "purpose",
+    "justification_receipt",
+    "cosignatures",
+    "signature_version",
+];
+
 /// A signed trust relationship between two identities.
+///
+/// Top-level fields this version does not know about are preserved in
+/// `extra` and are covered by the grant hash.
+///
+/// `purpose` and `justification_receipt` record why the grant was issued.
+/// Both are signed; they hash like extras so that readers predating them
+/// still verify grants that carry them.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrustGrant {
     /// Unique trust ID.
@@ -62,11 +115,42 @@ pub struct TrustGrant {
     pub grantor_signature: String,
     /// Grantee's acknowledgment signature (optional).
     pub grantee_acknowledgment: Option<String>,
+    /// Why the grant was issued (signed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub purpose: Option<String>,
+    /// Receipt of the grantor's action that justifies this grant (signed).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub justification_receipt: Option<ReceiptId>,
+    /// Member signatures, when the grantor is a multi-signature identity
+    /// (`grantor_signature` is then empty).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<Cosignature>,
+    /// Signature scheme of the grantor signature and acknowledgment, and
+    /// the format of `grant_hash`; absent on grants signed before domain
+    /// separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+    /// Layout version; see [`migrate`](crate::storage::migrate). Not signed.
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Unknown top-level fields from a newer writer (signed).
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl TrustGrant {
     /// Verify the grantor's signature on this grant.
+    ///
+    /// Also checks that the grant hash matches the grant's content,
+    /// including any preserved unknown fields.
     pub fn verify_signature(&self) -> Result<()> {
+        if self.compute_hash() != self.grant_hash {
+            return Err(IdentityError::SignatureInvalid);
+        }
+
         let pub_bytes = base64::Engine::decode(
             &base64::engine::general_purpose::STANDARD,
             &self.grantor_key,
@@ -80,23 +164,65 @@ impl TrustGrant {
         let verifying_key =
             crate::crypto::keys::Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)?;
 
-        signing::verify_from_base64(
+        signing::verify_versioned(
             &verifying_key,
+            SignatureDomain::TrustGrant,
+            self.signature_version,
             self.grant_hash.as_bytes(),
             &self.grantor_signature,
         )
     }
 
+    /// Recompute the grant hash from the grant's signed fields, in the
+    /// format of its `signature_version`.
+    ///
+    /// Returns an empty string if the content cannot be canonicalized, which
+    /// never matches a stored hash.
+    pub fn compute_hash(&self) -> String {
+        compute_grant_hash(
+            self.signature_version,
+            &self.grantor,
+            &self.grantor_key,
+            &self.grantee,
+            &self.grantee_key,
+            &self.capabilities,
+            &self.constraints,
+            self.delegation_allowed,
+            self.max_delegation_depth,
+            self.granted_at,
+            &signed_extras(
+                &self.extra,
+                self.purpose.as_deref(),
+                self.justification_receipt.as_ref(),
+            ),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Names of signed top-level fields this version does not understand.
+    pub fn unknown_fields(&self) -> Vec<String> {
+        self.extra.keys().cloned().collect()
+    }
+
     /// Add the grantee's acknowledgment signature.
-    pub fn acknowledge(&mut self, grantee_signing_key: &SigningKey) -> Result<()> {
+    pub fn acknowledge<S: Signer + ?Sized>(&mut self, grantee: &S) -> Result<()> {
         let ack_message = format!("ack:{}:{}", self.id.0, self.grant_hash);
-        let sig = signing::sign_to_base64(grantee_signing_key, ack_message.as_bytes());
+        let sig = if self.signature_version == signing::LEGACY_SIGNATURE_VERSION {
+            sign_with(grantee, ack_message.as_bytes())?
+        } else {
+            sign_in_domain_with(
+                grantee,
+                SignatureDomain::TrustAcknowledgment,
+                ack_message.as_bytes(),
+            )?
+        };
         self.grantee_acknowledgment = Some(sig);
         Ok(())
     }
 }
 
 /// Builder for creating trust grants.
+#[cfg(feature = "signing")]
 pub struct TrustGrantBuilder {
     grantor: IdentityId,
     grantee: IdentityId,
@@ -109,8 +235,13 @@ pub struct TrustGrantBuilder {
     delegation_depth: u32,
     revocation_channel: RevocationChannel,
     required_witnesses: Vec<IdentityId>,
+    purpose: Option<String>,
+    justification_receipt: Option<ReceiptId>,
+    extra: serde_json::Map<String, serde_json::Value>,
+    policies: PolicySet,
 }
 
+#[cfg(feature = "signing")]
 impl TrustGrantBuilder {
     /// Start building a trust grant from grantor to grantee.
     pub fn new(grantor: IdentityId, grantee: IdentityId, grantee_key: String) -> Self {
@@ -126,6 +257,10 @@ impl TrustGrantBuilder {
             delegation_depth: 0,
             revocation_channel: RevocationChannel::Local,
             required_witnesses: Vec::new(),
+            purpose: None,
+            justification_receipt: None,
+            extra: serde_json::Map::new(),
+            policies: PolicySet::new(),
         }
     }
 
@@ -173,19 +308,75 @@ impl TrustGrantBuilder {
         self
     }
 
+    /// Record why this grant is being issued.
+    pub fn purpose(mut self, purpose: impl Into<String>) -> Self {
+        self.purpose = Some(purpose.into());
+        self
+    }
+
+    /// Link a receipt (signed by the grantor) that justifies this grant.
+    ///
+    /// The link is signed but not resolved here; see
+    /// [`verify_grant_justification`](super::verify::verify_grant_justification).
+    pub fn justification_receipt(mut self, receipt: ReceiptId) -> Self {
+        self.justification_receipt = Some(receipt);
+        self
+    }
+
+    /// Attach an additional top-level field, covered by the grant hash.
+    ///
+    /// Signing fails if `key` collides with a field this version knows.
+    pub fn extra_field(mut self, key: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    /// Check the grant against the grantor's installed `policies` when
+    /// signing.
+    ///
+    /// A grant that breaks one fails signing with
+    /// [`IdentityError::PolicyViolation`] before it is signed; see
+    /// [`contracts::policy`](crate::contracts::policy).
+    pub fn policies(mut self, policies: PolicySet) -> Self {
+        self.policies = policies;
+        self
+    }
+
     /// Sign and finalize the trust grant.
-    pub fn sign(self, grantor_signing_key: &SigningKey) -> Result<TrustGrant> {
+    ///
+    /// `grantor` may be any [`Signer`], such as a hardware-backed key.
+    pub fn sign<S: Signer + ?Sized>(mut self, grantor: &S) -> Result<TrustGrant> {
+        let policies = std::mem::take(&mut self.policies);
+        let mut grant = self.build(grantor.public_key_base64())?;
+        if let Some(violation) = policies.check_grant(&grant) {
+            return Err(violation.into());
+        }
+        grant.grantor_signature = sign_in_domain_with(
+            grantor,
+            SignatureDomain::TrustGrant,
+            grant.grant_hash.as_bytes(),
+        )?;
+        Ok(grant)
+    }
+
+    /// Finalize the grant under `grantor_key`, leaving `grantor_signature` empty.
+    pub(crate) fn build(self, grantor_key: String) -> Result<TrustGrant> {
         if self.capabilities.is_empty() {
             return Err(IdentityError::TrustNotGranted(
                 "no capabilities specified".into(),
             ));
         }
+        if let Some(key) = self
+            .extra
+            .keys()
+            .find(|k| KNOWN_FIELDS.contains(&k.as_str()) || OPTIONAL_FIELDS.contains(&k.as_str()))
+        {
+            return Err(IdentityError::SerializationError(format!(
+                "extra field '{key}' collides with a trust grant field"
+            )));
+        }
 
         let now = crate::time::now_micros();
-        let grantor_key = base64::Engine::encode(
-            &base64::engine::general_purpose::STANDARD,
-            grantor_signing_key.verifying_key().to_bytes(),
-        );
 
         // Derive revocation key ID
         let revocation_key_id = format!("revkey_{}", &self.grantor.0[4..]);
@@ -197,31 +388,29 @@ impl TrustGrantBuilder {
         };
 
         // Compute grant hash over all fields
-        let caps_json = serde_json::to_string(&self.capabilities).unwrap_or_default();
-        let constraints_json = serde_json::to_string(&self.constraints).unwrap_or_default();
-
-        let hash_input = format!(
-            "{}:{}:{}:{}:{}:{}:{}:{}:{}",
-            self.grantor.0,
-            grantor_key,
-            self.grantee.0,
-            self.grantee_key,
-            caps_json,
-            constraints_json,
+        let grant_hash = compute_grant_hash(
+            signing::CANONICAL_SIGNATURE_VERSION,
+            &self.grantor,
+            &grantor_key,
+            &self.grantee,
+            &self.grantee_key,
+            &self.capabilities,
+            &self.constraints,
             self.delegation_allowed,
-            self.max_delegation_depth.unwrap_or(0),
+            self.max_delegation_depth,
             now,
-        );
-        let grant_hash = hex::encode(Sha256::digest(hash_input.as_bytes()));
+            &signed_extras(
+                &self.extra,
+                self.purpose.as_deref(),
+                self.justification_receipt.as_ref(),
+            ),
+        )?;
 
         // Generate trust ID from the hash
         let id_hash = Sha256::digest(grant_hash.as_bytes());
         let id_encoded = bs58::encode(&id_hash[..16]).into_string();
         let id = TrustId(format!("atrust_{id_encoded}"));
 
-        // Sign the grant hash
-        let grantor_signature = signing::sign_to_base64(grantor_signing_key, grant_hash.as_bytes());
-
         Ok(TrustGrant {
             id,
             grantor: self.grantor,
@@ -237,13 +426,101 @@ impl TrustGrantBuilder {
             revocation,
             granted_at: now,
             grant_hash,
-            grantor_signature,
+            grantor_signature: String::new(),
             grantee_acknowledgment: None,
+            purpose: self.purpose,
+            justification_receipt: self.justification_receipt,
+            cosignatures: Vec::new(),
+            signature_version: signing::CANONICAL_SIGNATURE_VERSION,
+            schema_version: crate::storage::migrate::SCHEMA_VERSION,
+            extra: self.extra,
         })
     }
 }
 
-#[cfg(test)]
+/// Merge the optional rationale fields into the extras map for hashing.
+fn signed_extras(
+    extra: &serde_json::Map<String, serde_json::Value>,
+    purpose: Option<&str>,
+    justification_receipt: Option<&ReceiptId>,
+) -> serde_json::Map<String, serde_json::Value> {
+    let mut merged = extra.clone();
+    if let Some(purpose) = purpose {
+        merged.insert("purpose".into(), purpose.into());
+    }
+    if let Some(receipt) = justification_receipt {
+        merged.insert("justification_receipt".into(), receipt.0.clone().into());
+    }
+    merged
+}
+
+/// Hash the grant content in the format of `version`.
+///
+/// From [`CANONICAL_SIGNATURE_VERSION`](signing::CANONICAL_SIGNATURE_VERSION)
+/// the hash is over the JCS form of an object holding the signed fields and
+/// extras side by side. Earlier grants hash a `:`-joined string; unknown
+/// fields are appended only when present, so grants without them hash
+/// exactly as they did before extras were supported.
+#[allow(clippy::too_many_arguments)]
+fn compute_grant_hash(
+    version: u32,
+    grantor: &IdentityId,
+    grantor_key: &str,
+    grantee: &IdentityId,
+    grantee_key: &str,
+    capabilities: &[Capability],
+    constraints: &TrustConstraints,
+    delegation_allowed: bool,
+    max_delegation_depth: Option<u32>,
+    granted_at: u64,
+    extra: &serde_json::Map<String, serde_json::Value>,
+) -> Result<String> {
+    if version >= signing::CANONICAL_SIGNATURE_VERSION {
+        let mut payload = extra.clone();
+        payload.insert("grantor".into(), grantor.0.clone().into());
+        payload.insert("grantor_key".into(), grantor_key.into());
+        payload.insert("grantee".into(), grantee.0.clone().into());
+        payload.insert("grantee_key".into(), grantee_key.into());
+        payload.insert(
+            "capabilities".into(),
+            serde_json::to_value(capabilities)
+                .map_err(|e| IdentityError::SerializationError(e.to_string()))?,
+        );
+        payload.insert(
+            "constraints".into(),
+            serde_json::to_value(constraints)
+                .map_err(|e| IdentityError::SerializationError(e.to_string()))?,
+        );
+        payload.insert("delegation_allowed".into(), delegation_allowed.into());
+        payload.insert("max_delegation_depth".into(), max_delegation_depth.into());
+        payload.insert("granted_at".into(), granted_at.into());
+        let json = canonical::canonicalize(&serde_json::Value::Object(payload))?;
+        return Ok(hex::encode(Sha256::digest(json.as_bytes())));
+    }
+
+    let caps_json = serde_json::to_string(capabilities).unwrap_or_default();
+    let constraints_json = serde_json::to_string(constraints).unwrap_or_default();
+
+    let mut hash_input = format!(
+        "{}:{}:{}:{}:{}:{}:{}:{}:{}",
+        grantor.0,
+        grantor_key,
+        grantee.0,
+        grantee_key,
+        caps_json,
+        constraints_json,
+        delegation_allowed,
+        max_delegation_depth.unwrap_or(0),
+        granted_at,
+    );
+    if !extra.is_empty() {
+        hash_input.push(':');
+        hash_input.push_str(&serde_json::to_string(extra).unwrap_or_default());
+    }
+    Ok(hex::encode(Sha256::digest(hash_input.as_bytes())))
+}
+
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -285,6 +562,90 @@ mod tests {
         assert!(grant.verify_signature().is_ok());
     }
 
+    #[test]
+    fn test_receipt_signature_does_not_verify_as_trust_grant() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let mut grant =
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+                .capability(Capability::new("read:*"))
+                .sign(grantor.signing_key())
+                .unwrap();
+
+        // The grantor's key signing the same hash as a receipt.
+        grant.grantor_signature = signing::sign_in_domain(
+            grantor.signing_key(),
+            SignatureDomain::Receipt,
+            grant.grant_hash.as_bytes(),
+        );
+        assert!(matches!(
+            grant.verify_signature(),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_trust_grant_legacy_signature_verifies() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let mut grant =
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+                .capability(Capability::new("read:*"))
+                .sign(grantor.signing_key())
+                .unwrap();
+        grant.signature_version = signing::LEGACY_SIGNATURE_VERSION;
+        grant.grant_hash = grant.compute_hash();
+        grant.grantor_signature =
+            signing::sign_to_base64(grantor.signing_key(), grant.grant_hash.as_bytes());
+
+        let json = serde_json::to_value(&grant).unwrap();
+        assert!(json.get("signature_version").is_none());
+        let loaded: TrustGrant = serde_json::from_value(json).unwrap();
+        assert!(loaded.verify_signature().is_ok());
+    }
+
+    #[test]
+    fn test_trust_grant_hash_is_over_canonical_payload() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:*"))
+            .purpose("nightly sync")
+            .sign(grantor.signing_key())
+            .unwrap();
+        assert_eq!(
+            grant.signature_version,
+            signing::CANONICAL_SIGNATURE_VERSION
+        );
+
+        let payload = serde_json::json!({
+            "purpose": "nightly sync",
+            "max_delegation_depth": null,
+            "grantor": grant.grantor.0,
+            "grantor_key": grant.grantor_key,
+            "grantee": grant.grantee.0,
+            "grantee_key": grant.grantee_key,
+            "granted_at": grant.granted_at,
+            "delegation_allowed": false,
+            "constraints": serde_json::to_value(&grant.constraints).unwrap(),
+            "capabilities": serde_json::to_value(&grant.capabilities).unwrap(),
+        });
+        let json = canonical::canonicalize(&payload).unwrap();
+        assert_eq!(
+            grant.grant_hash,
+            hex::encode(Sha256::digest(json.as_bytes()))
+        );
+        assert!(grant.verify_signature().is_ok());
+
+        // Claiming the previous format breaks the hash check.
+        let mut older = grant;
+        older.signature_version = signing::SIGNATURE_VERSION;
+        assert!(older.verify_signature().is_err());
+    }
+
     #[test]
     fn test_trust_grant_no_capabilities_fails() {
         let grantor = IdentityAnchor::new(None);
@@ -347,4 +708,71 @@ mod tests {
 
         assert_ne!(g1.id, g2.id);
     }
+
+    #[test]
+    fn test_trust_grant_extra_fields_round_trip() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .extra_field("audience", serde_json::json!(["ops"]))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let json = serde_json::to_string(&grant).unwrap();
+        let loaded: TrustGrant = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.unknown_fields(), vec!["audience".to_string()]);
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        assert!(loaded.verify_signature().is_ok());
+
+        let mut stripped = loaded;
+        stripped.extra.clear();
+        assert!(stripped.verify_signature().is_err());
+    }
+    #[test]
+    fn test_trust_grant_purpose_is_signed() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let receipt_id = ReceiptId("arec_justification".into());
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .purpose("quarterly planning")
+            .justification_receipt(receipt_id.clone())
+            .sign(grantor.signing_key())
+            .unwrap();
+        assert!(grant.verify_signature().is_ok());
+        assert!(grant.unknown_fields().is_empty());
+
+        let json = serde_json::to_string(&grant).unwrap();
+        let loaded: TrustGrant = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.purpose.as_deref(), Some("quarterly planning"));
+        assert_eq!(loaded.justification_receipt, Some(receipt_id));
+        assert!(loaded.verify_signature().is_ok());
+
+        let mut tampered = loaded.clone();
+        tampered.purpose = Some("anything".into());
+        assert!(tampered.verify_signature().is_err());
+
+        let mut unlinked = loaded;
+        unlinked.justification_receipt = None;
+        assert!(unlinked.verify_signature().is_err());
+    }
+
+    #[test]
+    fn test_trust_grant_without_purpose_omits_fields() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let json = serde_json::to_value(&grant).unwrap();
+        assert!(json.get("purpose").is_none());
+        assert!(json.get("justification_receipt").is_none());
+        assert!(grant.verify_signature().is_ok());
+    }
 }
@@ -0,0 +1,361 @@
+//! Trust graph — delegation paths across the grants of many agents.
+//!
+//! A [`TrustStore`] only holds the grants its own identity issued or
+//! received, so in a network of agents no single store can say whether A's
+//! authority reaches B. A [`TrustGraph`] ingests grants and revocations from
+//! any number of stores (or from grants handed over directly), and
+//! [`TrustGraph::find_path`] answers "is there a delegation path from A to B
+//! granting capability X?" with the shortest such path, one [`TrustHop`] per
+//! grant and each carrying that grant's constraints.
+//!
+//! A path follows the same rules as [`verify_trust_chain`](super::verify_trust_chain):
+//! every grant is validly signed, in force and unrevoked at the given time,
+//! and covers the capability; each grant after the first is issued by the
+//! previous grantee, under a grant that allows delegation to its depth.
+//! Predicates and use limits are returned in the hops, not evaluated: they
+//! depend on a usage context and a usage ledger the graph does not have.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::capability::{capabilities_cover, Capability};
+use super::constraint::TrustConstraints;
+use super::grant::{TrustGrant, TrustId};
+use super::revocation::Revocation;
+use crate::error::Result;
+use crate::identity::IdentityId;
+use crate::storage::TrustStore;
+
+/// One grant along a [`TrustPath`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustHop {
+    pub grant_id: TrustId,
+    pub grantor: IdentityId,
+    pub grantee: IdentityId,
+    /// The grant's capabilities that cover the requested one.
+    pub capabilities: Vec<Capability>,
+    pub constraints: TrustConstraints,
+    pub delegation_allowed: bool,
+    pub max_delegation_depth: Option<u32>,
+    pub delegation_depth: u32,
+}
+
+/// A delegation path granting `capability` from `from` to `to`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustPath {
+    pub from: IdentityId,
+    pub to: IdentityId,
+    pub capability: String,
+    /// Grants in order, from the one `from` issued to the one `to` holds.
+    pub hops: Vec<TrustHop>,
+}
+
+impl TrustPath {
+    /// IDs of the grants along the path, in order.
+    pub fn grant_ids(&self) -> Vec<&TrustId> {
+        self.hops.iter().map(|h| &h.grant_id).collect()
+    }
+
+    /// Number of grants along the path.
+    pub fn len(&self) -> usize {
+        self.hops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hops.is_empty()
+    }
+}
+
+/// Grants and revocations gathered from any number of sources.
+#[derive(Debug, Clone, Default)]
+pub struct TrustGraph {
+    /// Grants by ID; the same grant found in several stores is kept once.
+    grants: HashMap<TrustId, TrustGrant>,
+    revocations: Vec<Revocation>,
+}
+
+impl TrustGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `grants`, returning how many were not already in the graph.
+    pub fn add_grants(&mut self, grants: impl IntoIterator<Item = TrustGrant>) -> usize {
+        let before = self.grants.len();
+        for grant in grants {
+            self.grants.entry(grant.id.clone()).or_insert(grant);
+        }
+        self.grants.len() - before
+    }
+
+    pub fn add_revocations(&mut self, revocations: impl IntoIterator<Item = Revocation>) {
+        self.revocations.extend(revocations);
+    }
+
+    /// Add every grant in `store`, issued or received, and its revocations.
+    /// Returns how many grants were new. Unreadable files are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::Io` if a store directory cannot be read.
+    pub fn ingest_store(&mut self, store: &TrustStore) -> Result<usize> {
+        let mut ids = store.list_granted()?;
+        ids.extend(store.list_received()?);
+        let grants: Vec<TrustGrant> = ids
+            .iter()
+            .filter(|id| !self.grants.contains_key(*id))
+            .filter_map(|id| store.load_grant(id).ok())
+            .collect();
+        let revocations: Vec<Revocation> = store
+            .list_revocations()?
+            .iter()
+            .filter_map(|id| store.load_revocation(id).ok())
+            .collect();
+        self.add_revocations(revocations);
+        Ok(self.add_grants(grants))
+    }
+
+    /// Number of distinct grants in the graph.
+    pub fn len(&self) -> usize {
+        self.grants.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grants.is_empty()
+    }
+
+    pub fn grant(&self, id: &TrustId) -> Option<&TrustGrant> {
+        self.grants.get(id)
+    }
+
+    /// The shortest delegation path through which `from` grants `to`
+    /// `capability` at `now`, or `None` if there is none.
+    ///
+    /// Among paths of equal length, the one through the earliest grants wins.
+    pub fn find_path(
+        &self,
+        from: &IdentityId,
+        to: &IdentityId,
+        capability: &str,
+        now: u64,
+    ) -> Option<TrustPath> {
+        if from == to {
+            return None;
+        }
+
+        // Usable grants, by grantor, oldest first.
+        let mut issued: HashMap<&IdentityId, Vec<&TrustGrant>> = HashMap::new();
+        for grant in self.grants.values() {
+            if self.usable_at(grant, capability, now) {
+                issued.entry(&grant.grantor).or_default().push(grant);
+            }
+        }
+        for grants in issued.values_mut() {
+            grants.sort_by(|a, b| (a.granted_at, &a.id.0).cmp(&(b.granted_at, &b.id.0)));
+        }
+
+        // Breadth-first over grants: whether a grant can extend a path
+        // depends only on the grant before it.
+        let mut previous: HashMap<&TrustId, Option<&TrustGrant>> = HashMap::new();
+        let mut queue = VecDeque::new();
+        for grant in issued.get(from).into_iter().flatten() {
+            previous.insert(&grant.id, None);
+            queue.push_back(*grant);
+        }
+        while let Some(grant) = queue.pop_front() {
+            if grant.grantee == *to {
+                return Some(self.path(from, to, capability, grant, &previous));
+            }
+            if !grant.delegation_allowed {
+                continue;
+            }
+            for next in issued.get(&grant.grantee).into_iter().flatten() {
+                let within_depth = grant
+                    .max_delegation_depth
+                    .is_none_or(|max| next.delegation_depth <= max);
+                if within_depth && !previous.contains_key(&next.id) {
+                    previous.insert(&next.id, Some(grant));
+                    queue.push_back(*next);
+                }
+            }
+        }
+        None
+    }
+
+    /// The grants along `path`, in order, for
+    /// [`verify_trust_chain`](super::verify_trust_chain).
+    pub fn chain(&self, path: &TrustPath) -> Vec<TrustGrant> {
+        path.grant_ids()
+            .into_iter()
+            .filter_map(|id| self.grants.get(id).cloned())
+            .collect()
+    }
+
+    // ── Internal helpers ──────────────────────────────────────────────────────
+
+    fn usable_at(&self, grant: &TrustGrant, capability: &str, now: u64) -> bool {
+        grant.granted_at <= now
+            && grant.constraints.is_time_valid(now)
+            && capabilities_cover(&grant.capabilities, capability)
+            && !self
+                .revocations
+                .iter()
+                .any(|r| r.trust_id == grant.id && r.revoked_at <= now)
+            && grant.verify_signature().is_ok()
+    }
+
+    fn path(
+        &self,
+        from: &IdentityId,
+        to: &IdentityId,
+        capability: &str,
+        last: &TrustGrant,
+        previous: &HashMap<&TrustId, Option<&TrustGrant>>,
+    ) -> TrustPath {
+        let mut hops = Vec::new();
+        let mut current = Some(last);
+        while let Some(grant) = current {
+            hops.push(TrustHop {
+                grant_id: grant.id.clone(),
+                grantor: grant.grantor.clone(),
+                grantee: grant.grantee.clone(),
+                capabilities: grant
+                    .capabilities
+                    .iter()
+                    .filter(|c| c.covers(capability))
+                    .cloned()
+                    .collect(),
+                constraints: grant.constraints.clone(),
+                delegation_allowed: grant.delegation_allowed,
+                max_delegation_depth: grant.max_delegation_depth,
+                delegation_depth: grant.delegation_depth,
+            });
+            current = previous.get(&grant.id).copied().flatten();
+        }
+        hops.reverse();
+        TrustPath {
+            from: from.clone(),
+            to: to.clone(),
+            capability: capability.to_string(),
+            hops,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::identity::IdentityAnchor;
+    use crate::trust::chain::verify_trust_chain;
+    use crate::trust::grant::TrustGrantBuilder;
+    use crate::trust::revocation::RevocationReason;
+
+    fn grant(
+        grantor: &IdentityAnchor,
+        grantee: &IdentityAnchor,
+        cap: &str,
+        delegation: Option<u32>,
+        depth: u32,
+    ) -> TrustGrant {
+        let mut builder =
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), grantee.public_key_base64())
+                .capability(Capability::new(cap));
+        if let Some(max_depth) = delegation {
+            builder = builder.allow_delegation(max_depth);
+        }
+        if depth > 0 {
+            builder = builder.delegated_from(TrustId("atrust_parent".into()), depth);
+        }
+        builder.sign(grantor.signing_key()).unwrap()
+    }
+
+    #[test]
+    fn test_path_across_stores() {
+        let [alice, bob, carol] = [(); 3].map(|_| IdentityAnchor::new(None));
+        let first = grant(&alice, &bob, "deploy:*", Some(2), 0);
+        let second = grant(&bob, &carol, "deploy:prod", None, 1);
+
+        // Each agent's store holds only its own grants.
+        let (dir_a, dir_b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let store_a = TrustStore::new(dir_a.path()).unwrap();
+        let store_b = TrustStore::new(dir_b.path()).unwrap();
+        store_a.save_granted(&first).unwrap();
+        store_b.save_received(&first).unwrap();
+        store_b.save_granted(&second).unwrap();
+
+        let mut graph = TrustGraph::new();
+        assert_eq!(graph.ingest_store(&store_a).unwrap(), 1);
+        assert_eq!(graph.ingest_store(&store_b).unwrap(), 1);
+        assert_eq!(graph.len(), 2);
+
+        let now = crate::time::now_micros();
+        let path = graph
+            .find_path(&alice.id(), &carol.id(), "deploy:prod", now)
+            .unwrap();
+        assert_eq!(path.grant_ids(), [&first.id, &second.id]);
+        assert_eq!(path.hops[0].capabilities[0].uri, "deploy:*");
+        assert!(
+            verify_trust_chain(&graph.chain(&path), "deploy:prod", &[])
+                .unwrap()
+                .is_valid
+        );
+
+        assert!(graph
+            .find_path(&alice.id(), &carol.id(), "deploy:staging", now)
+            .is_none());
+        let direct = graph
+            .find_path(&alice.id(), &bob.id(), "deploy:staging", now)
+            .unwrap();
+        assert_eq!(direct.len(), 1);
+        assert!(graph
+            .find_path(&carol.id(), &alice.id(), "deploy:prod", now)
+            .is_none());
+    }
+
+    #[test]
+    fn test_delegation_rules_and_revocation_cut_paths() {
+        let [alice, bob, carol] = [(); 3].map(|_| IdentityAnchor::new(None));
+        let now = || crate::time::now_micros();
+
+        // Bob may not pass on what Alice gave him.
+        let mut graph = TrustGraph::new();
+        graph.add_grants([
+            grant(&alice, &bob, "read:docs", None, 0),
+            grant(&bob, &carol, "read:docs", None, 1),
+        ]);
+        assert!(graph
+            .find_path(&alice.id(), &carol.id(), "read:docs", now())
+            .is_none());
+
+        // Delegated deeper than Alice's grant allows.
+        let mut graph = TrustGraph::new();
+        graph.add_grants([
+            grant(&alice, &bob, "read:docs", Some(1), 0),
+            grant(&bob, &carol, "read:docs", None, 2),
+        ]);
+        assert!(graph
+            .find_path(&alice.id(), &carol.id(), "read:docs", now())
+            .is_none());
+
+        // A revoked link breaks the path, but an unrevoked alternative holds.
+        let revoked = grant(&alice, &bob, "read:docs", Some(1), 0);
+        let spare = grant(&alice, &bob, "read:*", Some(1), 0);
+        let mut graph = TrustGraph::new();
+        graph.add_grants([revoked.clone(), grant(&bob, &carol, "read:docs", None, 1)]);
+        graph.add_revocations([Revocation::create(
+            revoked.id.clone(),
+            alice.id(),
+            RevocationReason::ManualRevocation,
+            alice.signing_key(),
+        )]);
+        assert!(graph
+            .find_path(&alice.id(), &carol.id(), "read:docs", now())
+            .is_none());
+        graph.add_grants([spare.clone()]);
+        let path = graph
+            .find_path(&alice.id(), &carol.id(), "read:docs", now())
+            .unwrap();
+        assert_eq!(path.hops[0].grant_id, spare.id);
+    }
+}
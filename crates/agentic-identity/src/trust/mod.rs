@@ -2,22 +2,52 @@
 //!
 //! The trust module provides:
 //! - Capability URI parsing with wildcard matching
+//! - Optional capability implication policy
 //! - Time-bounded, use-limited trust constraints
 //! - Signed trust grants between identities
+//! - Grantee-signed receipts recording each use of a grant
 //! - Revocation mechanism
+//! - Signed revocation lists for verifiers without access to the store
 //! - Trust chain verification for delegation
 //! - Delegation depth limits
+//! - Delegated grants that may only narrow their parent
+//! - Effective authority as of any point in time, and diffs between two
+//! - Sweeps for grants about to expire or already expired
+//! - Delegation paths between identities across many agents' grants
 
+pub mod authority;
 pub mod capability;
 pub mod chain;
 pub mod constraint;
+pub mod delegation;
+pub mod exercise;
 pub mod grant;
+pub mod graph;
+pub mod policy;
 pub mod revocation;
+pub mod revocation_list;
 pub mod verify;
 
+pub use authority::{authority_as_of, authority_diff, expiry_sweep, AuthorityDiff, ExpirySweep};
 pub use capability::{capabilities_cover, capabilities_cover_all, Capability};
-pub use chain::{validate_delegation, verify_trust_chain};
-pub use constraint::TrustConstraints;
-pub use grant::{TrustGrant, TrustGrantBuilder, TrustId};
+pub use chain::{validate_delegation, verify_trust_chain, verify_trust_chain_with};
+pub use constraint::{Predicate, TrustConstraints, UsageContext};
+#[cfg(feature = "signing")]
+pub use delegation::DelegationBuilder;
+pub use delegation::{verify_delegation_chain, verify_delegation_chain_at, DelegatedTrustGrant};
+#[cfg(feature = "signing")]
+pub use exercise::exercise;
+pub use exercise::verify_exercise;
+#[cfg(feature = "signing")]
+pub use grant::TrustGrantBuilder;
+pub use grant::{TrustGrant, TrustId};
+pub use graph::{TrustGraph, TrustHop, TrustPath};
+pub use policy::ImplicationPolicy;
 pub use revocation::{Revocation, RevocationChannel, RevocationConfig, RevocationReason};
-pub use verify::{is_grant_valid, verify_trust_grant, TrustVerification};
+pub use revocation_list::{verify_revocation_list, RevocationList};
+pub use verify::{
+    is_grant_valid, verify_grant_justification, verify_grantee_binding,
+    verify_grantee_binding_strict, verify_multisig_trust_grant, verify_trust_grant,
+    verify_trust_grant_with, IssuerAllowlist, TimeSource, TimeToken, TrustVerification,
+    VerifyOptions,
+};
@@ -0,0 +1,150 @@
+//! Capability implication policy — deployment-defined "holding X implies Y".
+//!
+//! By default capabilities are independent: a grant for `admin:*` does not
+//! satisfy a request for `read:calendar`. An [`ImplicationPolicy`] lets a
+//! deployment declare rules such as `admin:* -> [read:*, write:*]`, which the
+//! trust verifier consults after plain wildcard matching fails.
+//!
+//! # Security
+//!
+//! Every rule widens the effective authority of *every* grant that covers its
+//! source capability, including grants signed before the rule existed. Keep
+//! rules narrow, review them like grants, and prefer issuing explicit
+//! capabilities where possible. An empty policy implies nothing.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::capability::{capability_uri_covers, Capability};
+
+/// A set of `from -> [to]` capability implication rules.
+///
+/// A rule fires when a held capability covers `from` (so `*` and `admin:*`
+/// both trigger a rule on `admin:*`, but `admin:users` does not). Implication
+/// is transitive; cycles are harmless and terminate.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImplicationPolicy {
+    rules: BTreeMap<String, Vec<String>>,
+}
+
+impl ImplicationPolicy {
+    /// Create an empty policy (no implications).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare that holding `from` implies holding `to`.
+    pub fn imply(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        let targets = self.rules.entry(from.into()).or_default();
+        let to = to.into();
+        if !targets.contains(&to) {
+            targets.push(to);
+        }
+        self
+    }
+
+    /// Is this policy empty (no implications)?
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Expand held capability URIs to everything they transitively imply.
+    ///
+    /// The result includes the held URIs themselves.
+    pub fn expand(&self, held: &[Capability]) -> BTreeSet<String> {
+        let mut effective: BTreeSet<String> = held.iter().map(|c| c.uri.clone()).collect();
+        if self.rules.is_empty() {
+            return effective;
+        }
+
+        let mut fired: BTreeSet<&str> = BTreeSet::new();
+        loop {
+            let mut grew = false;
+            for (from, targets) in &self.rules {
+                if fired.contains(from.as_str())
+                    || !effective.iter().any(|uri| capability_uri_covers(uri, from))
+                {
+                    continue;
+                }
+                fired.insert(from.as_str());
+                for target in targets {
+                    grew |= effective.insert(target.clone());
+                }
+            }
+            if !grew {
+                return effective;
+            }
+        }
+    }
+
+    /// Is `requested` covered by `held` once implications are applied?
+    ///
+    /// Implied capabilities use the same wildcard matching as held ones, so
+    /// `admin:* -> read:*` satisfies a request for `read:calendar`.
+    pub fn covers(&self, held: &[Capability], requested: &str) -> bool {
+        self.expand(held)
+            .iter()
+            .any(|uri| capability_uri_covers(uri, requested))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn held(uris: &[&str]) -> Vec<Capability> {
+        uris.iter().map(|u| Capability::new(*u)).collect()
+    }
+
+    #[test]
+    fn test_empty_policy_implies_nothing() {
+        let policy = ImplicationPolicy::new();
+        assert!(policy.is_empty());
+        assert!(!policy.covers(&held(&["admin:*"]), "read:calendar"));
+        assert!(policy.covers(&held(&["read:*"]), "read:calendar"));
+    }
+
+    #[test]
+    fn test_implication_composes_with_wildcards() {
+        let policy = ImplicationPolicy::new()
+            .imply("admin:*", "read:*")
+            .imply("admin:*", "write:*");
+        assert!(policy.covers(&held(&["admin:*"]), "read:calendar"));
+        assert!(policy.covers(&held(&["admin:*"]), "write:notes:today"));
+        assert!(policy.covers(&held(&["*"]), "read:calendar"));
+        // A narrower admin grant does not cover the rule's source.
+        assert!(!policy.covers(&held(&["admin:users"]), "read:calendar"));
+        assert!(!policy.covers(&held(&["admin:*"]), "delete:calendar"));
+    }
+
+    #[test]
+    fn test_implication_is_transitive() {
+        let policy = ImplicationPolicy::new()
+            .imply("owner:*", "admin:*")
+            .imply("admin:*", "read:*");
+        assert!(policy.covers(&held(&["owner:*"]), "read:calendar"));
+        assert!(!policy.covers(&held(&["read:*"]), "admin:users"));
+    }
+
+    #[test]
+    fn test_implication_cycles_terminate() {
+        let policy = ImplicationPolicy::new()
+            .imply("a:*", "b:*")
+            .imply("b:*", "c:*")
+            .imply("c:*", "a:*");
+        let effective = policy.expand(&held(&["b:x", "c:*"]));
+        assert!(effective.contains("a:*"));
+        assert!(effective.contains("b:*"));
+        assert!(policy.covers(&held(&["a:*"]), "c:anything"));
+    }
+
+    #[test]
+    fn test_policy_serde_roundtrip() {
+        let policy = ImplicationPolicy::new().imply("admin:*", "read:*");
+        let json = serde_json::to_string(&policy).unwrap();
+        assert_eq!(json, r#"{"rules":{"admin:*":["read:*"]}}"#);
+        let back: ImplicationPolicy = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, policy);
+    }
+}
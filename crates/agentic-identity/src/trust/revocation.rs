@@ -7,7 +7,7 @@
 use ed25519_dalek::SigningKey;
 use serde::{Deserialize, Serialize};
 
-use crate::crypto::signing;
+use crate::crypto::signing::{self, SignatureDomain};
 use crate::identity::IdentityId;
 use crate::receipt::WitnessSignature;
 
@@ -54,6 +54,13 @@ pub struct Revocation {
     pub signature: String,
     /// Witness signatures (if required by revocation config).
     pub witnesses: Vec<WitnessSignature>,
+    /// Signature scheme; absent on revocations signed before domain
+    /// separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
 }
 
 impl Revocation {
@@ -77,7 +84,8 @@ impl Revocation {
             now,
             reason.as_str(),
         );
-        let signature = signing::sign_to_base64(signing_key, to_sign.as_bytes());
+        let signature =
+            signing::sign_in_domain(signing_key, SignatureDomain::Revocation, to_sign.as_bytes());
 
         Self {
             trust_id,
@@ -87,6 +95,7 @@ impl Revocation {
             reason,
             signature,
             witnesses: Vec::new(),
+            signature_version: signing::SIGNATURE_VERSION,
         }
     }
 
@@ -120,7 +129,13 @@ impl Revocation {
             self.reason.as_str(),
         );
 
-        signing::verify_from_base64(&verifying_key, to_verify.as_bytes(), &self.signature)
+        signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::Revocation,
+            self.signature_version,
+            to_verify.as_bytes(),
+            &self.signature,
+        )
     }
 }
 
@@ -155,7 +170,7 @@ impl RevocationReason {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -0,0 +1,211 @@
+//! Revocation lists — an identity's revocations published as one signed
+//! document.
+//!
+//! Revocations normally live in the local `TrustStore`, which a remote
+//! verifier cannot read. A [`RevocationList`] bundles every revocation an
+//! identity has issued, stamps it with the time of issue and signs the
+//! whole, in the manner of an X.509 CRL. Given only the issuer's public key,
+//! [`verify_revocation_list`] checks that the list is authentic and
+//! complete as of `issued_at`: a revocation cannot be dropped, added or
+//! altered without breaking the signature.
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+#[cfg(feature = "signing")]
+use crate::identity::IdentityAnchor;
+use crate::identity::IdentityId;
+
+use super::grant::TrustId;
+use super::revocation::Revocation;
+
+/// A signed list of the revocations issued by one identity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationList {
+    pub issuer: IdentityId,
+    /// Base64 public key the list was signed with.
+    pub issuer_key: String,
+    /// When the list was issued (microseconds since epoch).
+    pub issued_at: u64,
+    /// The issuer's revocations, ordered by trust ID.
+    pub revocations: Vec<Revocation>,
+    pub signature: String,
+    /// Absent on lists signed before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+/// Everything in a [`RevocationList`] except its signature.
+#[derive(Serialize)]
+struct RevocationListPayload<'a> {
+    issuer: &'a IdentityId,
+    issuer_key: &'a str,
+    issued_at: u64,
+    revocations: &'a [Revocation],
+}
+
+impl RevocationList {
+    /// Sign `revocations` as `anchor`'s revocation list.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IdentityError::InvalidInput` if a revocation was issued by
+    /// another identity, and the revocation's own verification error if its
+    /// signature does not verify.
+    #[cfg(feature = "signing")]
+    pub fn sign(anchor: &IdentityAnchor, mut revocations: Vec<Revocation>) -> Result<Self> {
+        let issuer = anchor.id();
+        check_revocations(&issuer, &revocations)?;
+        revocations.sort_by(|a, b| a.trust_id.0.cmp(&b.trust_id.0));
+
+        let mut list = Self {
+            issuer,
+            issuer_key: anchor.public_key_base64(),
+            issued_at: crate::time::now_micros(),
+            revocations,
+            signature: String::new(),
+            signature_version: signing::SIGNATURE_VERSION,
+        };
+        let input = list.signing_input()?;
+        list.signature = signing::sign_in_domain(
+            anchor.signing_key(),
+            SignatureDomain::RevocationList,
+            input.as_bytes(),
+        );
+        Ok(list)
+    }
+
+    /// The revocation of `trust_id`, if the list has one.
+    pub fn revocation(&self, trust_id: &TrustId) -> Option<&Revocation> {
+        self.revocations.iter().find(|r| &r.trust_id == trust_id)
+    }
+
+    /// Whether the list revokes `trust_id`.
+    pub fn is_revoked(&self, trust_id: &TrustId) -> bool {
+        self.revocation(trust_id).is_some()
+    }
+
+    fn signing_input(&self) -> Result<String> {
+        serde_json::to_string(&RevocationListPayload {
+            issuer: &self.issuer,
+            issuer_key: &self.issuer_key,
+            issued_at: self.issued_at,
+            revocations: &self.revocations,
+        })
+        .map_err(|e| IdentityError::SerializationError(e.to_string()))
+    }
+}
+
+/// Verify that `list` was signed with `public_key` (base64), was not
+/// altered, and holds only valid revocations issued by its signer.
+///
+/// The key is taken from the caller, never from the list, and must be the
+/// one the list names and the one its issuer ID derives from.
+///
+/// # Errors
+///
+/// Returns `IdentityError::InvalidKey` if `public_key` does not decode or is
+/// not the list's issuer key, `IdentityError::SignatureInvalid` if the list
+/// was altered after signing, `IdentityError::InvalidId` for a malformed
+/// trust ID, and `IdentityError::InvalidInput` (or the revocation's
+/// verification error) for a revocation the issuer could not have made.
+pub fn verify_revocation_list(list: &RevocationList, public_key: &str) -> Result<()> {
+    let key = crate::identity::anchor::decode_public_key(public_key)?;
+    if list.issuer_key != public_key || IdentityId::from_verifying_key(&key) != list.issuer {
+        return Err(IdentityError::InvalidKey(format!(
+            "revocation list was not signed by the key of {}",
+            list.issuer
+        )));
+    }
+    signing::verify_versioned(
+        &key,
+        SignatureDomain::RevocationList,
+        list.signature_version,
+        list.signing_input()?.as_bytes(),
+        &list.signature,
+    )?;
+    check_revocations(&list.issuer, &list.revocations)
+}
+
+/// Every revocation must be `issuer`'s, name a well-formed trust ID and
+/// carry a valid signature.
+fn check_revocations(issuer: &IdentityId, revocations: &[Revocation]) -> Result<()> {
+    for revocation in revocations {
+        TrustId::parse(&revocation.trust_id.0)?;
+        if &revocation.revoker != issuer {
+            return Err(IdentityError::InvalidInput(format!(
+                "revocation of {} was issued by {}, not {issuer}",
+                revocation.trust_id, revocation.revoker
+            )));
+        }
+        revocation.verify_signature()?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "signing"))]
+mod tests {
+    use super::*;
+    use crate::trust::RevocationReason;
+
+    fn revoke(anchor: &IdentityAnchor, trust_id: &str) -> Revocation {
+        Revocation::create(
+            TrustId(trust_id.into()),
+            anchor.id(),
+            RevocationReason::Compromised,
+            anchor.signing_key(),
+        )
+    }
+
+    #[test]
+    fn test_revocation_list_round_trip() {
+        let anchor = IdentityAnchor::new(None);
+        let list = RevocationList::sign(
+            &anchor,
+            vec![revoke(&anchor, "atrust_b"), revoke(&anchor, "atrust_a")],
+        )
+        .unwrap();
+        assert_eq!(list.revocations[0].trust_id.0, "atrust_a");
+        assert!(list.is_revoked(&TrustId("atrust_b".into())));
+        assert!(!list.is_revoked(&TrustId("atrust_c".into())));
+
+        let json = serde_json::to_string(&list).unwrap();
+        let parsed: RevocationList = serde_json::from_str(&json).unwrap();
+        assert!(verify_revocation_list(&parsed, &anchor.public_key_base64()).is_ok());
+
+        let other = IdentityAnchor::new(None);
+        assert!(matches!(
+            verify_revocation_list(&parsed, &other.public_key_base64()),
+            Err(IdentityError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_revocation_list_detects_tampering() {
+        let anchor = IdentityAnchor::new(None);
+        let key = anchor.public_key_base64();
+        let list = RevocationList::sign(
+            &anchor,
+            vec![revoke(&anchor, "atrust_a"), revoke(&anchor, "atrust_b")],
+        )
+        .unwrap();
+
+        let mut dropped = list.clone();
+        dropped.revocations.remove(0);
+        assert!(matches!(
+            verify_revocation_list(&dropped, &key),
+            Err(IdentityError::SignatureInvalid)
+        ));
+
+        // Another identity's revocations cannot be published as one's own.
+        let other = IdentityAnchor::new(None);
+        assert!(matches!(
+            RevocationList::sign(&anchor, vec![revoke(&other, "atrust_c")]),
+            Err(IdentityError::InvalidInput(_))
+        ));
+    }
+}
@@ -6,11 +6,30 @@
 //! 3. Revocation status (not revoked)
 //! 4. Use count (within max_uses)
 //! 5. Capability match (requested capability is covered)
+//! 6. Issuer allowlist (optional — signer key is a trusted root)
+//! 7. Grantee binding (optional — grantee key belongs to the grantee's
+//!    published document; see [`TrustVerification::check_grantee_binding`])
+//! 8. Capability predicates (resource scope, rate, value ceiling) against
+//!    the use's [`UsageContext`]
+//!
+//! The allowlist, an [`ImplicationPolicy`] for the capability check, the
+//! usage context and the [`TimeSource`] "now" comes from are set in
+//! [`VerifyOptions`] and passed to [`verify_trust_grant_with`]. A grant's
+//! `justification_receipt` link is checked separately by
+//! [`verify_grant_justification`] during deep audits.
+
+use ed25519_dalek::SigningKey;
 
-use crate::error::Result;
+use crate::crypto::signing::{self, SignatureDomain};
+use crate::error::{IdentityError, Result};
+use crate::identity::anchor::{decode_public_key, verify_genesis};
+use crate::identity::{IdentityDocument, IdentityId, MultisigDocument};
+use crate::receipt::ActionReceipt;
 
 use super::capability::capabilities_cover;
+use super::constraint::UsageContext;
 use super::grant::TrustGrant;
+use super::policy::ImplicationPolicy;
 use super::revocation::Revocation;
 
 /// Result of verifying a trust grant.
@@ -26,15 +45,310 @@ pub struct TrustVerification {
     pub uses_valid: bool,
     /// Is the requested capability specifically granted?
     pub capability_granted: bool,
+    /// Was the capability only satisfied through an implication rule?
+    pub capability_implied: bool,
+    /// Is the grantor key in the issuer allowlist (always true when no
+    /// allowlist is enforced)?
+    pub issuer_trusted: bool,
+    /// Does the grantee key belong to the grantee's document (always true
+    /// when no document is checked)?
+    pub grantee_bound: bool,
+    /// Does the use satisfy the predicates on the requested capability?
+    pub context_valid: bool,
+    /// Why the use breaks those predicates; empty when `context_valid`.
+    pub context_violations: Vec<String>,
     /// Trust chain (if delegated).
     pub trust_chain: Vec<super::grant::TrustId>,
+    /// Signed top-level fields this version does not understand.
+    pub unknown_fields: Vec<String>,
     /// Overall validity.
     pub is_valid: bool,
     /// Verification timestamp.
     pub verified_at: u64,
 }
 
-/// Verify a trust grant for a specific capability at the current time.
+impl TrustVerification {
+    /// Strict profile: valid, and every signed field was understood.
+    pub fn is_valid_strict(&self) -> bool {
+        self.is_valid && self.unknown_fields.is_empty()
+    }
+
+    /// Additionally require `grant`'s grantee key to belong to
+    /// `grantee_doc`, updating `grantee_bound` and `is_valid`.
+    ///
+    /// With `strict` the key must be the document's current key; otherwise
+    /// a key the grantee has since rotated away from also counts. See
+    /// [`verify_grantee_binding`].
+    pub fn check_grantee_binding(
+        &mut self,
+        grant: &TrustGrant,
+        grantee_doc: &IdentityDocument,
+        strict: bool,
+    ) {
+        self.grantee_bound = if strict {
+            verify_grantee_binding_strict(grant, grantee_doc)
+        } else {
+            verify_grantee_binding(grant, grantee_doc)
+        };
+        self.is_valid = self.is_valid && self.grantee_bound;
+    }
+}
+
+/// A key accepted by an [`IssuerAllowlist`], optionally only for a window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AllowedIssuer {
+    /// Base64-encoded Ed25519 public key.
+    key: String,
+    /// Earliest signing time accepted (microseconds since epoch).
+    valid_from: Option<u64>,
+    /// Latest signing time accepted (microseconds since epoch).
+    valid_until: Option<u64>,
+}
+
+/// A set of trusted issuer public keys (a trust root set).
+///
+/// Artifacts are checked against the key they were actually signed with
+/// (`grantor_key` / `actor_key`) at the time they were signed, so a key that
+/// was rotated out can still be accepted for artifacts it signed while it
+/// was current via [`IssuerAllowlist::allow_during`].
+///
+/// An empty allowlist enforces nothing: any valid self-signature is accepted.
+#[derive(Debug, Clone, Default)]
+pub struct IssuerAllowlist {
+    issuers: Vec<AllowedIssuer>,
+}
+
+impl IssuerAllowlist {
+    /// Create an empty allowlist (no enforcement).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept artifacts signed by `key` at any time.
+    pub fn allow(mut self, key: impl Into<String>) -> Self {
+        self.issuers.push(AllowedIssuer {
+            key: key.into(),
+            valid_from: None,
+            valid_until: None,
+        });
+        self
+    }
+
+    /// Accept artifacts signed by `key` only between `valid_from` and
+    /// `valid_until` (inclusive, microseconds since epoch).
+    pub fn allow_during(
+        mut self,
+        key: impl Into<String>,
+        valid_from: u64,
+        valid_until: u64,
+    ) -> Self {
+        self.issuers.push(AllowedIssuer {
+            key: key.into(),
+            valid_from: Some(valid_from),
+            valid_until: Some(valid_until),
+        });
+        self
+    }
+
+    /// Is this allowlist empty (no enforcement)?
+    pub fn is_empty(&self) -> bool {
+        self.issuers.is_empty()
+    }
+
+    /// Would an artifact signed by `key` at `signed_at` be accepted?
+    pub fn permits(&self, key: &str, signed_at: u64) -> bool {
+        if self.issuers.is_empty() {
+            return true;
+        }
+        self.issuers.iter().any(|issuer| {
+            issuer.key == key
+                && issuer.valid_from.is_none_or(|from| signed_at >= from)
+                && issuer.valid_until.is_none_or(|until| signed_at <= until)
+        })
+    }
+
+    /// Like [`permits`](Self::permits), but returns
+    /// [`IdentityError::IssuerNotTrusted`] for a rejected key.
+    pub fn check(&self, key: &str, signed_at: u64) -> Result<()> {
+        if self.permits(key, signed_at) {
+            Ok(())
+        } else {
+            Err(IdentityError::IssuerNotTrusted(key.to_string()))
+        }
+    }
+}
+
+/// A signed timestamp from a time authority (a signed time beacon).
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TimeToken {
+    /// Attested time (microseconds since epoch).
+    pub time: u64,
+    /// Base64-encoded Ed25519 public key of the time authority.
+    pub authority_key: String,
+    /// Authority's signature over `timestamp:{authority_key}:{time}`.
+    pub signature: String,
+    /// Signature scheme; absent on tokens issued before domain separation.
+    #[serde(
+        default,
+        skip_serializing_if = "crate::crypto::signing::is_legacy_signature"
+    )]
+    pub signature_version: u32,
+}
+
+impl TimeToken {
+    /// Issue a token attesting `time`, signed by a time authority.
+    pub fn issue(time: u64, authority_signing_key: &SigningKey) -> Self {
+        let authority_key = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            authority_signing_key.verifying_key().to_bytes(),
+        );
+        let signature = signing::sign_in_domain(
+            authority_signing_key,
+            SignatureDomain::TimeToken,
+            token_signing_input(&authority_key, time).as_bytes(),
+        );
+        Self {
+            time,
+            authority_key,
+            signature,
+            signature_version: signing::SIGNATURE_VERSION,
+        }
+    }
+
+    /// Verify the token's signature and return the attested time.
+    pub fn verify(&self) -> Result<u64> {
+        let pub_bytes = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &self.authority_key,
+        )
+        .map_err(|e| IdentityError::InvalidKey(format!("invalid time authority key: {e}")))?;
+
+        let key_bytes: [u8; 32] = pub_bytes
+            .try_into()
+            .map_err(|_| IdentityError::InvalidKey("time authority key must be 32 bytes".into()))?;
+
+        let verifying_key =
+            crate::crypto::keys::Ed25519KeyPair::verifying_key_from_bytes(&key_bytes)?;
+
+        signing::verify_versioned(
+            &verifying_key,
+            SignatureDomain::TimeToken,
+            self.signature_version,
+            token_signing_input(&self.authority_key, self.time).as_bytes(),
+            &self.signature,
+        )?;
+        Ok(self.time)
+    }
+}
+
+fn token_signing_input(authority_key: &str, time: u64) -> String {
+    format!("timestamp:{authority_key}:{time}")
+}
+
+/// Where verification takes "now" from.
+///
+/// Resolved once per verification call, so expiry, revocation timing and
+/// receipt monotonicity are all judged against the same instant. An
+/// external source that fails its own verification is an error — there is
+/// no fallback to the host clock.
+#[derive(Debug, Clone, Default)]
+pub enum TimeSource {
+    /// The local system clock.
+    #[default]
+    HostClock,
+    /// A time the caller has already verified externally (for example from
+    /// an RFC 3161 timestamp response).
+    Trusted(u64),
+    /// A signed [`TimeToken`] that must verify and come from `authority_key`.
+    Token {
+        token: TimeToken,
+        /// Base64-encoded public key of the expected time authority.
+        authority_key: String,
+    },
+}
+
+impl TimeSource {
+    /// Resolve the current time, failing closed with
+    /// [`IdentityError::UntrustedTime`] if the source does not verify.
+    pub fn now(&self) -> Result<u64> {
+        match self {
+            Self::HostClock => Ok(crate::time::now_micros()),
+            Self::Trusted(time) => Ok(*time),
+            Self::Token {
+                token,
+                authority_key,
+            } => {
+                if &token.authority_key != authority_key {
+                    return Err(IdentityError::UntrustedTime(format!(
+                        "token signed by unexpected authority {}",
+                        token.authority_key
+                    )));
+                }
+                token
+                    .verify()
+                    .map_err(|e| IdentityError::UntrustedTime(format!("invalid time token: {e}")))
+            }
+        }
+    }
+}
+
+/// How [`verify_trust_grant_with`] checks a grant beyond its own fields.
+///
+/// The default enforces no issuer allowlist, applies no implication rules,
+/// takes "now" from the host clock, and checks predicates against an empty
+/// [`UsageContext`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyOptions {
+    /// Require the grantor key to be in this allowlist as of the grant's
+    /// signing time. Checked alongside — never instead of — the signature.
+    pub issuers: IssuerAllowlist,
+    /// Also accept a capability implied by one the grant covers.
+    ///
+    /// **Security:** a non-empty policy widens the effective authority of
+    /// the grant beyond the capabilities its grantor signed.
+    pub implications: ImplicationPolicy,
+    /// Where "now" comes from. A revocation only counts if it was issued at
+    /// or before that time.
+    pub time: TimeSource,
+    /// The use being checked, for the predicates on the requested
+    /// capability. A grant with a predicate on it only verifies given a
+    /// context carrying that predicate's data.
+    pub context: UsageContext,
+}
+
+impl VerifyOptions {
+    /// Default options; see [`VerifyOptions`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the issuer allowlist.
+    pub fn issuers(mut self, issuers: IssuerAllowlist) -> Self {
+        self.issuers = issuers;
+        self
+    }
+
+    /// Set the capability implication policy.
+    pub fn implications(mut self, implications: ImplicationPolicy) -> Self {
+        self.implications = implications;
+        self
+    }
+
+    /// Set where "now" comes from.
+    pub fn time(mut self, time: TimeSource) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Set the use being checked.
+    pub fn context(mut self, context: UsageContext) -> Self {
+        self.context = context;
+        self
+    }
+}
+
+/// Verify a trust grant for a specific capability at the current time, with
+/// default [`VerifyOptions`].
 ///
 /// `current_uses` is the number of times this grant has been used so far.
 /// `revocations` is the list of known revocations to check against.
@@ -44,7 +358,33 @@ pub fn verify_trust_grant(
     current_uses: u64,
     revocations: &[Revocation],
 ) -> Result<TrustVerification> {
-    let now = crate::time::now_micros();
+    verify_trust_grant_with(
+        grant,
+        requested_capability,
+        current_uses,
+        revocations,
+        &VerifyOptions::default(),
+    )
+}
+
+/// Verify a trust grant for a specific capability as `options` direct.
+///
+/// Fails with [`IdentityError::UntrustedTime`] if `options.time` does not
+/// verify. Otherwise identical to [`verify_trust_grant`].
+pub fn verify_trust_grant_with(
+    grant: &TrustGrant,
+    requested_capability: &str,
+    current_uses: u64,
+    revocations: &[Revocation],
+    options: &VerifyOptions,
+) -> Result<TrustVerification> {
+    let VerifyOptions {
+        issuers,
+        implications,
+        time,
+        context,
+    } = options;
+    let now = time.now()?;
 
     // 1. Signature check
     let signature_valid = grant.verify_signature().is_ok();
@@ -53,15 +393,37 @@ pub fn verify_trust_grant(
     let time_valid = grant.constraints.is_time_valid(now);
 
     // 3. Revocation check
-    let not_revoked = !revocations.iter().any(|r| r.trust_id == grant.id);
+    let not_revoked = !revocations
+        .iter()
+        .any(|r| r.trust_id == grant.id && r.revoked_at <= now);
 
     // 4. Use count check
     let uses_valid = grant.constraints.is_within_uses(current_uses);
 
-    // 5. Capability match
-    let capability_granted = capabilities_cover(&grant.capabilities, requested_capability);
+    // 5. Capability match (directly, then through implication rules)
+    let directly_granted = capabilities_cover(&grant.capabilities, requested_capability);
+    let capability_implied = !directly_granted
+        && !implications.is_empty()
+        && implications.covers(&grant.capabilities, requested_capability);
+    let capability_granted = directly_granted || capability_implied;
+
+    // 6. Issuer allowlist
+    let issuer_trusted = issuers.permits(&grant.grantor_key, grant.granted_at);
+
+    // 7. Capability predicates
+    let context_violations =
+        grant
+            .constraints
+            .predicate_violations(requested_capability, context, now);
+    let context_valid = context_violations.is_empty();
 
-    let is_valid = signature_valid && time_valid && not_revoked && uses_valid && capability_granted;
+    let is_valid = signature_valid
+        && time_valid
+        && not_revoked
+        && uses_valid
+        && capability_granted
+        && issuer_trusted
+        && context_valid;
 
     Ok(TrustVerification {
         signature_valid,
@@ -69,12 +431,120 @@ pub fn verify_trust_grant(
         not_revoked,
         uses_valid,
         capability_granted,
+        capability_implied,
+        issuer_trusted,
+        grantee_bound: true,
+        context_valid,
+        context_violations,
         trust_chain: Vec::new(),
+        unknown_fields: grant.unknown_fields(),
         is_valid,
         verified_at: now,
     })
 }
 
+/// Verify a trust grant issued by the multi-signature identity in `document`.
+///
+/// Identical to [`verify_trust_grant_with`] except for the signature check: the
+/// grant must name the document's identity and configuration, and at least
+/// `threshold` distinct members must have validly cosigned it. Fails with
+/// [`IdentityError::InvalidId`] if `document` does not match its own keys
+/// and threshold.
+pub fn verify_multisig_trust_grant(
+    grant: &TrustGrant,
+    requested_capability: &str,
+    current_uses: u64,
+    revocations: &[Revocation],
+    document: &MultisigDocument,
+    options: &VerifyOptions,
+) -> Result<TrustVerification> {
+    let multisig = document.anchor()?;
+    let mut verification = verify_trust_grant_with(
+        grant,
+        requested_capability,
+        current_uses,
+        revocations,
+        options,
+    )?;
+
+    verification.signature_valid = multisig.issued(&grant.grantor, &grant.grantor_key)
+        && grant.compute_hash() == grant.grant_hash
+        && multisig.is_satisfied(&grant.grant_hash, &grant.cosignatures);
+    verification.is_valid = verification.signature_valid
+        && verification.time_valid
+        && verification.not_revoked
+        && verification.uses_valid
+        && verification.capability_granted
+        && verification.issuer_trusted
+        && verification.grantee_bound
+        && verification.context_valid;
+    Ok(verification)
+}
+
+/// Deep audit: check a grant's `justification_receipt` link.
+///
+/// Succeeds when the grant links no receipt. Otherwise the linked receipt
+/// must be among `receipts`, must have been signed by the grantor's key, and
+/// must itself verify. Returns [`IdentityError::NotFound`] for a dangling
+/// link and [`IdentityError::SignatureInvalid`] for a receipt signed by
+/// anyone else or failing verification.
+pub fn verify_grant_justification(grant: &TrustGrant, receipts: &[ActionReceipt]) -> Result<()> {
+    let Some(receipt_id) = &grant.justification_receipt else {
+        return Ok(());
+    };
+
+    let receipt = receipts
+        .iter()
+        .find(|r| &r.id == receipt_id)
+        .ok_or_else(|| IdentityError::NotFound(format!("justification receipt {receipt_id}")))?;
+
+    if receipt.actor != grant.grantor || receipt.actor_key != grant.grantor_key {
+        return Err(IdentityError::SignatureInvalid);
+    }
+
+    if !crate::receipt::verify::verify_receipt(receipt)?.is_valid {
+        return Err(IdentityError::SignatureInvalid);
+    }
+    Ok(())
+}
+
+/// Does `grant` name the identity of `grantee_doc`, and is its
+/// `grantee_key` one of that identity's keys, current or rotated away from?
+///
+/// A grant whose key matches none of them was directed at someone else.
+/// The document must carry a valid self-signature and an authorized
+/// rotation history back to its genesis key (see [`verify_genesis`]), so a
+/// forged document cannot claim a key. The grant may name the identity by
+/// an ID derived from any of those keys, since rotation changes the ID.
+pub fn verify_grantee_binding(grant: &TrustGrant, grantee_doc: &IdentityDocument) -> bool {
+    grantee_binding(grant, grantee_doc)
+        .is_some_and(|keys| keys.contains(&grant.grantee_key.as_str()))
+}
+
+/// Like [`verify_grantee_binding`], but the grant's key must be the
+/// document's current key: grants made out to a rotated-away key fail.
+pub fn verify_grantee_binding_strict(grant: &TrustGrant, grantee_doc: &IdentityDocument) -> bool {
+    grantee_binding(grant, grantee_doc).is_some() && grant.grantee_key == grantee_doc.public_key
+}
+
+/// Every key of a verified `doc`, oldest first, if `grant` names its
+/// identity.
+fn grantee_binding<'a>(grant: &TrustGrant, doc: &'a IdentityDocument) -> Option<Vec<&'a str>> {
+    if !verify_genesis(doc) || doc.verify_signature().is_err() {
+        return None;
+    }
+    let keys: Vec<&str> = doc
+        .rotation_history
+        .iter()
+        .map(|r| r.previous_key.as_str())
+        .chain(std::iter::once(doc.public_key.as_str()))
+        .collect();
+    let names_identity = keys.iter().any(|key| {
+        decode_public_key(key).is_ok_and(|k| IdentityId::from_verifying_key(&k) == grant.grantee)
+    });
+    names_identity.then_some(keys)
+}
+
 /// Quick check: is a grant valid for a capability right now?
 pub fn is_grant_valid(
     grant: &TrustGrant,
@@ -87,7 +557,7 @@ pub fn is_grant_valid(
         .unwrap_or(false)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "signing"))]
 mod tests {
     use super::*;
     use crate::identity::IdentityAnchor;
@@ -122,6 +592,58 @@ mod tests {
         assert!(result.is_valid);
     }
 
+    #[test]
+    fn test_verify_with_usage_context() {
+        use crate::trust::constraint::Predicate;
+
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("spend:usd"))
+            .capability(Capability::new("read:calendar"))
+            .constraints(
+                TrustConstraints::open()
+                    .with_predicate("spend:usd", Predicate::MaxValue { max: 100.0 }),
+            )
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let within = UsageContext::new().value(40.0);
+        let result = verify_trust_grant_with(
+            &grant,
+            "spend:usd",
+            0,
+            &[],
+            &VerifyOptions::new().context(within),
+        )
+        .unwrap();
+        assert!(result.context_valid);
+        assert!(result.is_valid);
+
+        let over = UsageContext::new().value(250.0);
+        let result = verify_trust_grant_with(
+            &grant,
+            "spend:usd",
+            0,
+            &[],
+            &VerifyOptions::new().context(over),
+        )
+        .unwrap();
+        assert!(!result.context_valid);
+        assert!(!result.is_valid);
+        assert!(result.context_violations[0].contains("exceeds 100"));
+
+        // Without a context the ceiling cannot be checked, so the use fails.
+        let result = verify_trust_grant(&grant, "spend:usd", 0, &[]).unwrap();
+        assert!(!result.is_valid);
+        // Capabilities without predicates are unaffected.
+        assert!(
+            verify_trust_grant(&grant, "read:calendar", 0, &[])
+                .unwrap()
+                .is_valid
+        );
+    }
+
     #[test]
     fn test_verify_expired_grant() {
         let grantor = IdentityAnchor::new(None);
@@ -228,6 +750,47 @@ mod tests {
         assert!(!result.is_valid);
     }
 
+    #[test]
+    fn test_verify_capability_through_implication_policy() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("admin:*"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        // Without a policy, admin:* does not satisfy read:calendar.
+        let result = verify_trust_grant(&grant, "read:calendar", 0, &[]).unwrap();
+        assert!(!result.capability_granted);
+        assert!(!result.capability_implied);
+
+        let policy = ImplicationPolicy::new().imply("admin:*", "read:*");
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().implications(policy.clone()),
+        )
+        .unwrap();
+        assert!(result.capability_granted);
+        assert!(result.capability_implied);
+        assert!(result.is_valid);
+
+        // Direct matches are not reported as implied.
+        let result = verify_trust_grant_with(
+            &grant,
+            "admin:users",
+            0,
+            &[],
+            &VerifyOptions::new().implications(policy.clone()),
+        )
+        .unwrap();
+        assert!(result.capability_granted);
+        assert!(!result.capability_implied);
+    }
+
     #[test]
     fn test_verify_wildcard_capability() {
         let grantor = IdentityAnchor::new(None);
@@ -268,4 +831,390 @@ mod tests {
         assert!(is_grant_valid(&grant, "read:calendar", 0, &[]));
         assert!(!is_grant_valid(&grant, "write:calendar", 0, &[]));
     }
+
+    #[test]
+    fn test_verify_empty_allowlist_accepts_any_issuer() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().issuers(IssuerAllowlist::new()),
+        )
+        .unwrap();
+        assert!(result.issuer_trusted);
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_verify_issuer_not_in_allowlist() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let trusted = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let allowlist = IssuerAllowlist::new().allow(make_grantee_key(&trusted));
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().issuers(allowlist.clone()),
+        )
+        .unwrap();
+        assert!(result.signature_valid);
+        assert!(!result.issuer_trusted);
+        assert!(!result.is_valid);
+
+        let allowlist = allowlist.allow(make_grantee_key(&grantor));
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().issuers(allowlist.clone()),
+        )
+        .unwrap();
+        assert!(result.issuer_trusted);
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_verify_allowlist_does_not_bypass_signature() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let mut grant =
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+                .capability(Capability::new("read:calendar"))
+                .sign(grantor.signing_key())
+                .unwrap();
+        grant.grant_hash = "tampered".to_string();
+
+        let allowlist = IssuerAllowlist::new().allow(make_grantee_key(&grantor));
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().issuers(allowlist.clone()),
+        )
+        .unwrap();
+        assert!(result.issuer_trusted);
+        assert!(!result.signature_valid);
+        assert!(!result.is_valid);
+    }
+
+    #[test]
+    fn test_allowlist_window_checks_signing_time() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+        let key = make_grantee_key(&grantor);
+
+        // Key retired before this grant was signed.
+        let retired = IssuerAllowlist::new().allow_during(&key, 0, grant.granted_at - 1);
+        assert!(!retired.permits(&key, grant.granted_at));
+        assert!(matches!(
+            retired.check(&key, grant.granted_at),
+            Err(IdentityError::IssuerNotTrusted(_))
+        ));
+
+        // Key was current when the grant was signed, even if rotated since.
+        let current = IssuerAllowlist::new().allow_during(&key, 0, grant.granted_at);
+        let result = verify_trust_grant_with(
+            &grant,
+            "read:calendar",
+            0,
+            &[],
+            &VerifyOptions::new().issuers(current.clone()),
+        )
+        .unwrap();
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_verify_strict_profile_rejects_unknown_fields() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .extra_field("audience", serde_json::json!("ops"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let result = verify_trust_grant(&grant, "read:calendar", 0, &[]).unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.unknown_fields, vec!["audience".to_string()]);
+        assert!(!result.is_valid_strict());
+    }
+    #[test]
+    fn test_verify_against_external_time_token() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let authority = IdentityAnchor::new(None);
+        let now = crate::time::now_micros();
+
+        // Expired by the host clock, but valid at the attested time.
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .constraints(TrustConstraints::time_bounded(
+                now - 2_000_000,
+                now - 1_000_000,
+            ))
+            .sign(grantor.signing_key())
+            .unwrap();
+        let token = TimeToken::issue(now - 1_500_000, authority.signing_key());
+        let source = TimeSource::Token {
+            authority_key: token.authority_key.clone(),
+            token,
+        };
+        let verify_at = |time: &TimeSource, revocations: &[Revocation]| {
+            verify_trust_grant_with(
+                &grant,
+                "read:calendar",
+                0,
+                revocations,
+                &VerifyOptions::new().time(time.clone()),
+            )
+        };
+
+        assert!(
+            !verify_trust_grant(&grant, "read:calendar", 0, &[])
+                .unwrap()
+                .is_valid
+        );
+        let result = verify_at(&source, &[]).unwrap();
+        assert!(result.is_valid);
+        assert_eq!(result.verified_at, now - 1_500_000);
+
+        // A revocation issued after the attested time does not apply yet.
+        let revocation = Revocation::create(
+            grant.id.clone(),
+            grantor.id(),
+            RevocationReason::ManualRevocation,
+            grantor.signing_key(),
+        );
+        assert!(
+            verify_at(&source, std::slice::from_ref(&revocation))
+                .unwrap()
+                .not_revoked
+        );
+        assert!(
+            !verify_at(&TimeSource::HostClock, std::slice::from_ref(&revocation))
+                .unwrap()
+                .not_revoked
+        );
+    }
+
+    #[test]
+    fn test_invalid_time_source_fails_closed() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let authority = IdentityAnchor::new(None);
+        let impostor = IdentityAnchor::new(None);
+
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+        let token = TimeToken::issue(crate::time::now_micros(), authority.signing_key());
+        let authority_key = token.authority_key.clone();
+
+        let mut tampered = token.clone();
+        tampered.time += 1;
+        let forged = TimeToken::issue(token.time, impostor.signing_key());
+
+        for source in [
+            TimeSource::Token {
+                token: tampered,
+                authority_key: authority_key.clone(),
+            },
+            TimeSource::Token {
+                token: forged,
+                authority_key,
+            },
+        ] {
+            let result = verify_trust_grant_with(
+                &grant,
+                "read:calendar",
+                0,
+                &[],
+                &VerifyOptions::new().time(source),
+            );
+            assert!(matches!(result, Err(IdentityError::UntrustedTime(_))));
+        }
+    }
+
+    #[test]
+    fn test_grant_justification_deep_audit() {
+        use crate::receipt::receipt::ReceiptBuilder;
+        use crate::receipt::{ActionContent, ActionType};
+
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let make_receipt = |anchor: &IdentityAnchor| {
+            ReceiptBuilder::new(
+                anchor.id(),
+                ActionType::Decision,
+                ActionContent::new("Approved access request"),
+            )
+            .sign(anchor.signing_key())
+            .unwrap()
+        };
+        let own = make_receipt(&grantor);
+        let foreign = make_receipt(&grantee);
+        let grant_for = |receipt: &ActionReceipt| {
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+                .capability(Capability::new("read:calendar"))
+                .purpose("approved access request")
+                .justification_receipt(receipt.id.clone())
+                .sign(grantor.signing_key())
+                .unwrap()
+        };
+
+        // Unlinked grants need no justification.
+        let plain = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+        assert!(verify_grant_justification(&plain, &[]).is_ok());
+
+        let linked = grant_for(&own);
+        assert!(verify_grant_justification(&linked, std::slice::from_ref(&own)).is_ok());
+        assert!(matches!(
+            verify_grant_justification(&linked, std::slice::from_ref(&foreign)),
+            Err(IdentityError::NotFound(_))
+        ));
+
+        let misattributed = grant_for(&foreign);
+        assert!(matches!(
+            verify_grant_justification(&misattributed, &[own, foreign]),
+            Err(IdentityError::SignatureInvalid)
+        ));
+    }
+
+    #[test]
+    fn test_multisig_grant_requires_threshold() {
+        use crate::identity::MultisigAnchor;
+
+        let members: Vec<IdentityAnchor> = (0..3).map(|_| IdentityAnchor::new(None)).collect();
+        let multisig =
+            MultisigAnchor::new(members.iter().map(|m| *m.verifying_key()).collect(), 3).unwrap();
+        let doc = multisig.to_document();
+        let grantee = IdentityAnchor::new(None);
+
+        let builder =
+            TrustGrantBuilder::new(multisig.id(), grantee.id(), make_grantee_key(&grantee))
+                .capability(Capability::new("deploy:prod"));
+        let mut grant = multisig.prepare_grant(builder).unwrap();
+        for member in &members[..2] {
+            multisig
+                .cosign_grant(&mut grant, member.signing_key())
+                .unwrap();
+        }
+        let result =
+            verify_multisig_trust_grant(&grant, "deploy:prod", 0, &[], &doc, &VerifyOptions::new())
+                .unwrap();
+        assert!(!result.signature_valid);
+        assert!(!result.is_valid);
+
+        multisig
+            .cosign_grant(&mut grant, members[2].signing_key())
+            .unwrap();
+        let result =
+            verify_multisig_trust_grant(&grant, "deploy:prod", 0, &[], &doc, &VerifyOptions::new())
+                .unwrap();
+        assert!(result.is_valid);
+        assert!(
+            !verify_trust_grant(&grant, "deploy:prod", 0, &[])
+                .unwrap()
+                .is_valid
+        );
+
+        let result = verify_multisig_trust_grant(
+            &grant,
+            "deploy:staging",
+            0,
+            &[],
+            &doc,
+            &VerifyOptions::new(),
+        )
+        .unwrap();
+        assert!(result.signature_valid);
+        assert!(!result.is_valid);
+    }
+
+    #[test]
+    fn test_grantee_binding_detects_misdirected_grant() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let stranger = IdentityAnchor::new(None);
+        let doc = grantee.to_document();
+        let grant_to = |key: String| {
+            TrustGrantBuilder::new(grantor.id(), grantee.id(), key)
+                .capability(Capability::new("read:calendar"))
+                .sign(grantor.signing_key())
+                .unwrap()
+        };
+
+        let bound = grant_to(make_grantee_key(&grantee));
+        assert!(verify_grantee_binding(&bound, &doc));
+        assert!(verify_grantee_binding_strict(&bound, &doc));
+
+        let misdirected = grant_to(make_grantee_key(&stranger));
+        assert!(!verify_grantee_binding(&misdirected, &doc));
+
+        // Binding is optional: without a document the grant still verifies.
+        let mut result = verify_trust_grant(&misdirected, "read:calendar", 0, &[]).unwrap();
+        assert!(result.grantee_bound);
+        assert!(result.is_valid);
+        result.check_grantee_binding(&misdirected, &doc, false);
+        assert!(!result.grantee_bound);
+        assert!(!result.is_valid);
+
+        // A document that does not verify binds nothing.
+        let mut forged = doc.clone();
+        forged.public_key = make_grantee_key(&stranger);
+        assert!(!verify_grantee_binding(&misdirected, &forged));
+    }
+
+    #[test]
+    fn test_grantee_binding_after_rotation() {
+        let grantor = IdentityAnchor::new(None);
+        let grantee = IdentityAnchor::new(None);
+        let grant = TrustGrantBuilder::new(grantor.id(), grantee.id(), make_grantee_key(&grantee))
+            .capability(Capability::new("read:calendar"))
+            .sign(grantor.signing_key())
+            .unwrap();
+
+        let rotated = grantee
+            .rotate(crate::identity::RotationReason::Scheduled)
+            .unwrap();
+        let doc = rotated.to_document();
+        assert!(verify_grantee_binding(&grant, &doc));
+        assert!(!verify_grantee_binding_strict(&grant, &doc));
+
+        let mut result = verify_trust_grant(&grant, "read:calendar", 0, &[]).unwrap();
+        result.check_grantee_binding(&grant, &doc, true);
+        assert!(!result.grantee_bound);
+        assert!(!result.is_valid);
+    }
 }
@@ -1,11 +1,104 @@
 use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use agentic_identity::{
     IdentityAnchor,
     ActionContent, ActionType, ReceiptId,
     receipt::receipt::ReceiptBuilder,
     receipt::verify::verify_receipt,
+    storage::{decode_identity, encode_identity},
 };
 
+#[wasm_bindgen(typescript_custom_section)]
+const IDENTITY_STORAGE_TS: &str = r#"
+/**
+ * Where `WasmIdentity.save` / `WasmIdentity.load` keep encrypted identities.
+ * Each method may return its result directly or as a Promise. `get` resolves
+ * to the stored string or Uint8Array, or to null/undefined if there is none.
+ */
+export interface IdentityStorage {
+    get(key: string): any;
+    set(key: string, value: string): any;
+    remove(key: string): any;
+}
+"#;
+
+#[wasm_bindgen(inline_js = r#"
+export function localStorageAdapter(prefix) {
+    return {
+        get: (key) => globalThis.localStorage.getItem(prefix + key),
+        set: (key, value) => globalThis.localStorage.setItem(prefix + key, value),
+        remove: (key) => globalThis.localStorage.removeItem(prefix + key),
+    };
+}
+
+export function indexedDbAdapter(dbName, storeName) {
+    const db = new Promise((resolve, reject) => {
+        const open = globalThis.indexedDB.open(dbName, 1);
+        open.onupgradeneeded = () => open.result.createObjectStore(storeName);
+        open.onsuccess = () => resolve(open.result);
+        open.onerror = () => reject(open.error);
+    });
+    const run = (mode, op) => db.then((d) => new Promise((resolve, reject) => {
+        const request = op(d.transaction(storeName, mode).objectStore(storeName));
+        request.onsuccess = () => resolve(request.result);
+        request.onerror = () => reject(request.error);
+    }));
+    return {
+        get: (key) => run("readonly", (store) => store.get(key)),
+        set: (key, value) => run("readwrite", (store) => store.put(value, key)),
+        remove: (key) => run("readwrite", (store) => store.delete(key)),
+    };
+}
+"#)]
+extern "C" {
+    /// A JS object implementing the `IdentityStorage` interface.
+    #[wasm_bindgen(typescript_type = "IdentityStorage")]
+    pub type IdentityStorage;
+
+    #[wasm_bindgen(method, catch)]
+    fn get(this: &IdentityStorage, key: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn set(this: &IdentityStorage, key: &str, value: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn remove(this: &IdentityStorage, key: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(js_name = localStorageAdapter)]
+    fn local_storage_adapter(prefix: &str) -> IdentityStorage;
+
+    #[wasm_bindgen(js_name = indexedDbAdapter)]
+    fn indexed_db_adapter(db_name: &str, store_name: &str) -> IdentityStorage;
+}
+
+/// Storage backed by `localStorage`, with every key prefixed by `prefix`
+/// (default `"aid:"`).
+#[wasm_bindgen(js_name = localStorageIdentityStorage)]
+pub fn local_storage_identity_storage(prefix: Option<String>) -> IdentityStorage {
+    local_storage_adapter(prefix.as_deref().unwrap_or("aid:"))
+}
+
+/// Storage backed by an IndexedDB object store, created on first use.
+/// Defaults to database `"agentic-identity"` and store `"identities"`.
+#[wasm_bindgen(js_name = indexedDbIdentityStorage)]
+pub fn indexed_db_identity_storage(
+    db_name: Option<String>,
+    store_name: Option<String>,
+) -> IdentityStorage {
+    indexed_db_adapter(
+        db_name.as_deref().unwrap_or("agentic-identity"),
+        store_name.as_deref().unwrap_or("identities"),
+    )
+}
+
+/// Wait for `value` if it is a Promise; otherwise return it as is.
+async fn settle(value: JsValue) -> Result<JsValue, JsValue> {
+    match value.dyn_into::<js_sys::Promise>() {
+        Ok(promise) => JsFuture::from(promise).await,
+        Err(value) => Ok(value),
+    }
+}
+
 #[wasm_bindgen]
 pub struct WasmIdentity {
     inner: IdentityAnchor,
@@ -14,11 +107,13 @@ pub struct WasmIdentity {
 #[wasm_bindgen]
 impl WasmIdentity {
     /// Create a new identity anchor with an optional display name.
+    ///
+    /// Throws if the name contains a control character.
     #[wasm_bindgen(constructor)]
-    pub fn new(display_name: Option<String>) -> WasmIdentity {
-        WasmIdentity {
-            inner: IdentityAnchor::new(display_name),
-        }
+    pub fn new(display_name: Option<String>) -> Result<WasmIdentity, JsValue> {
+        let inner = IdentityAnchor::try_new(display_name)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmIdentity { inner })
     }
 
     /// Get the identity ID string.
@@ -45,6 +140,18 @@ impl WasmIdentity {
         self.inner.public_key_base64()
     }
 
+    /// Get the signature algorithm of the current key (e.g. `"ed25519"`).
+    #[wasm_bindgen]
+    pub fn algorithm(&self) -> String {
+        self.inner.algorithm().as_str().to_string()
+    }
+
+    /// Get the public key length in bytes.
+    #[wasm_bindgen]
+    pub fn key_length(&self) -> usize {
+        self.inner.algorithm().public_key_len()
+    }
+
     /// Sign an action and return the receipt as JSON.
     #[wasm_bindgen]
     pub fn sign_action(
@@ -69,10 +176,12 @@ impl WasmIdentity {
         description: &str,
         previous_receipt_id: &str,
     ) -> Result<String, JsValue> {
+        let previous =
+            ReceiptId::parse(previous_receipt_id).map_err(|e| JsValue::from_str(&e.to_string()))?;
         let atype = parse_action_type(action_type);
         let content = ActionContent::new(description);
         let receipt = ReceiptBuilder::new(self.inner.id(), atype, content)
-            .chain_to(ReceiptId(previous_receipt_id.to_string()))
+            .chain_to(previous)
             .sign(self.inner.signing_key())
             .map_err(|e| JsValue::from_str(&e.to_string()))?;
         serde_json::to_string(&receipt)
@@ -80,12 +189,93 @@ impl WasmIdentity {
     }
 
     /// Export the identity document as JSON.
+    ///
+    /// The document's `algorithm` is signed; `key_length` is added alongside
+    /// it for convenience and is not part of the signed document.
     #[wasm_bindgen]
     pub fn to_document_json(&self) -> Result<String, JsValue> {
         let doc = self.inner.to_document();
-        serde_json::to_string(&doc)
+        let mut json = serde_json::to_value(&doc)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        json["key_length"] = self.key_length().into();
+        serde_json::to_string(&json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Encrypt the identity under `passphrase`, in the `.aid` file format.
+    ///
+    /// The bytes are UTF-8 JSON, so they can be stored as text as well.
+    #[wasm_bindgen]
+    pub fn to_encrypted_bytes(&self, passphrase: &str) -> Result<Vec<u8>, JsValue> {
+        encode_identity(&self.inner, passphrase)
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
+
+    /// Decrypt an identity from `.aid` bytes, such as those returned by
+    /// `to_encrypted_bytes`.
+    ///
+    /// Throws if the passphrase is wrong or the bytes are malformed.
+    #[wasm_bindgen]
+    pub fn from_encrypted_bytes(bytes: &[u8], passphrase: &str) -> Result<WasmIdentity, JsValue> {
+        let inner = decode_identity(bytes, passphrase)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(WasmIdentity { inner })
+    }
+
+    /// Encrypt the identity under `passphrase` and store it in `storage`
+    /// under `key`, replacing any identity stored there.
+    ///
+    /// Encryption happens before this returns; the Promise settles once
+    /// the storage has written the result.
+    #[wasm_bindgen(unchecked_return_type = "Promise<void>")]
+    pub fn save(
+        &self,
+        storage: &IdentityStorage,
+        key: &str,
+        passphrase: &str,
+    ) -> Result<js_sys::Promise, JsValue> {
+        let bytes = self.to_encrypted_bytes(passphrase)?;
+        let text = String::from_utf8(bytes)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let pending = storage.set(key, &text)?;
+        Ok(future_to_promise(async move {
+            settle(pending).await?;
+            Ok(JsValue::UNDEFINED)
+        }))
+    }
+
+    /// Load the identity stored in `storage` under `key`, decrypting it with
+    /// `passphrase`. Resolves to `undefined` if nothing is stored there.
+    #[wasm_bindgen]
+    pub async fn load(
+        storage: IdentityStorage,
+        key: String,
+        passphrase: String,
+    ) -> Result<Option<WasmIdentity>, JsValue> {
+        let value = settle(storage.get(&key)?).await?;
+        if value.is_null() || value.is_undefined() {
+            return Ok(None);
+        }
+        let bytes = match value.as_string() {
+            Some(text) => text.into_bytes(),
+            None => match value.dyn_into::<js_sys::Uint8Array>() {
+                Ok(array) => array.to_vec(),
+                Err(_) => {
+                    return Err(JsValue::from_str(
+                        "stored identity is neither a string nor a Uint8Array",
+                    ))
+                }
+            },
+        };
+        WasmIdentity::from_encrypted_bytes(&bytes, &passphrase).map(Some)
+    }
+}
+
+/// Remove the identity stored in `storage` under `key`, if any.
+#[wasm_bindgen(js_name = removeStoredIdentity)]
+pub async fn remove_stored_identity(storage: IdentityStorage, key: String) -> Result<(), JsValue> {
+    settle(storage.remove(&key)?).await?;
+    Ok(())
 }
 
 /// Verify a receipt JSON string. Returns true if signature is valid.
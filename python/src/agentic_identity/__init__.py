@@ -35,6 +35,7 @@ import json
 from typing import Any, Optional
 
 from ._ffi import (
+    AID_ERR_INVALID_INPUT,
     AID_OK,
     AgenticIdentityError,
     _check,
@@ -214,8 +215,13 @@ class Identity:
         Raises
         ------
         AgenticIdentityError
-            If identity creation or file I/O fails.
+            If *name* contains a control character, or if identity creation
+            or file I/O fails.
         """
+        if name and "\0" in name:
+            # A C string ends at the NUL, which would silently shorten the
+            # name; the library rejects every other control character.
+            _check(AID_ERR_INVALID_INPUT)
         id_out = ctypes.c_char_p()
         rc = _lib.aid_identity_create(
             name.encode("utf-8") if name else None,
@@ -256,6 +262,44 @@ class Identity:
         _check(rc)
         return cls(anchor_out)
 
+    @staticmethod
+    def rotate(path: str, passphrase: str, reason: str | None = None) -> str:
+        """Rotate the signing key of the identity at *path*.
+
+        The rotation is signed by the old key and recorded in the identity's
+        rotation history; the file is re-encrypted in place with the same
+        passphrase. Handles loaded before the rotation keep the old key.
+
+        Parameters
+        ----------
+        path:
+            Filesystem path to the ``.aid`` file.
+        passphrase:
+            Passphrase the file is encrypted with.
+        reason:
+            ``"scheduled"``, ``"compromised"``, ``"device_lost"``,
+            ``"policy_required"``, or ``"manual"`` (the default).
+
+        Returns
+        -------
+        str
+            The new identity ID; it changes because it is derived from the key.
+
+        Raises
+        ------
+        AgenticIdentityError
+            If *reason* is unknown, or if loading, decryption, or saving fails.
+        """
+        id_out = ctypes.c_char_p()
+        rc = _lib.aid_identity_rotate(
+            path.encode("utf-8"),
+            passphrase.encode("utf-8"),
+            reason.encode("utf-8") if reason else None,
+            ctypes.byref(id_out),
+        )
+        _check(rc)
+        return _take_string(id_out)
+
     def close(self) -> None:
         """Release the underlying native identity handle.
 
@@ -304,6 +348,24 @@ class Identity:
         _check(rc)
         return _take_string(pk_out)
 
+    @property
+    def algorithm(self) -> str:
+        """The signature algorithm of the current key (e.g. ``"ed25519"``)."""
+        handle = self._require_handle()
+        alg_out = ctypes.c_char_p()
+        rc = _lib.aid_identity_get_algorithm(handle, ctypes.byref(alg_out))
+        _check(rc)
+        return _take_string(alg_out)
+
+    @property
+    def key_length(self) -> int:
+        """The public key length in bytes."""
+        handle = self._require_handle()
+        length = ctypes.c_uint32()
+        rc = _lib.aid_identity_get_key_length(handle, ctypes.byref(length))
+        _check(rc)
+        return length.value
+
     # -- action signing -----------------------------------------------------
 
     def sign_action(
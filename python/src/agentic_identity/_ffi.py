@@ -34,6 +34,8 @@ AID_ERR_INVALID_UTF8: int = -2
 AID_ERR_CRYPTO: int = -3
 AID_ERR_IO: int = -4
 AID_ERR_SERIALIZATION: int = -5
+AID_ERR_TOO_LARGE: int = -6
+AID_ERR_INVALID_INPUT: int = -7
 
 _ERROR_MESSAGES: dict[int, str] = {
     AID_ERR_NULL_PTR: "A required pointer argument was null",
@@ -41,6 +43,8 @@ _ERROR_MESSAGES: dict[int, str] = {
     AID_ERR_CRYPTO: "A cryptographic operation failed",
     AID_ERR_IO: "A filesystem I/O operation failed",
     AID_ERR_SERIALIZATION: "JSON serialization or deserialization failed",
+    AID_ERR_TOO_LARGE: "An input was larger than the library accepts",
+    AID_ERR_INVALID_INPUT: "A name or capability URI contained a control character",
 }
 
 # ---------------------------------------------------------------------------
@@ -121,6 +125,15 @@ def _load_library() -> ctypes.CDLL:
     ]
     lib.aid_identity_load.restype = ctypes.c_int
 
+    # -- aid_identity_rotate ------------------------------------------------
+    lib.aid_identity_rotate.argtypes = [
+        ctypes.c_char_p,                        # path
+        ctypes.c_char_p,                        # passphrase
+        ctypes.c_char_p,                        # reason (nullable)
+        ctypes.POINTER(ctypes.c_char_p),        # new_identity_id_out
+    ]
+    lib.aid_identity_rotate.restype = ctypes.c_int
+
     # -- aid_identity_free --------------------------------------------------
     lib.aid_identity_free.argtypes = [ctypes.c_void_p]
     lib.aid_identity_free.restype = None
@@ -139,6 +152,20 @@ def _load_library() -> ctypes.CDLL:
     ]
     lib.aid_identity_get_public_key.restype = ctypes.c_int
 
+    # -- aid_identity_get_algorithm -----------------------------------------
+    lib.aid_identity_get_algorithm.argtypes = [
+        ctypes.c_void_p,                        # anchor
+        ctypes.POINTER(ctypes.c_char_p),        # algorithm_out
+    ]
+    lib.aid_identity_get_algorithm.restype = ctypes.c_int
+
+    # -- aid_identity_get_key_length ----------------------------------------
+    lib.aid_identity_get_key_length.argtypes = [
+        ctypes.c_void_p,                        # anchor
+        ctypes.POINTER(ctypes.c_uint32),        # key_length_out
+    ]
+    lib.aid_identity_get_key_length.restype = ctypes.c_int
+
     # -- aid_action_sign ----------------------------------------------------
     lib.aid_action_sign.argtypes = [
         ctypes.c_void_p,                        # anchor
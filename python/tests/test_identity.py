@@ -155,6 +155,10 @@ class TestIdentityLoad:
         assert isinstance(pk, str)
         assert len(pk) > 0
 
+    def test_load_algorithm(self, loaded_identity: Identity) -> None:
+        assert loaded_identity.algorithm == "ed25519"
+        assert loaded_identity.key_length == 32
+
     def test_load_wrong_passphrase(
         self,
         created_identity: tuple[str, Path],
@@ -168,6 +172,34 @@ class TestIdentityLoad:
             Identity.load(str(tmp_dir / "does-not-exist.aid"), PASSPHRASE)
 
 
+# ---------------------------------------------------------------------------
+# Key rotation
+# ---------------------------------------------------------------------------
+
+
+class TestIdentityRotate:
+    def test_rotate_changes_id(
+        self,
+        created_identity: tuple[str, Path],
+    ) -> None:
+        old_id, path = created_identity
+        new_id = Identity.rotate(str(path), PASSPHRASE, reason="scheduled")
+        assert new_id.startswith("aid_")
+        assert new_id != old_id
+        with Identity.load(str(path), PASSPHRASE) as identity:
+            assert identity.identity_id == new_id
+
+    def test_rotate_unknown_reason(
+        self,
+        created_identity: tuple[str, Path],
+    ) -> None:
+        old_id, path = created_identity
+        with pytest.raises(AgenticIdentityError):
+            Identity.rotate(str(path), PASSPHRASE, reason="bored")
+        with Identity.load(str(path), PASSPHRASE) as identity:
+            assert identity.identity_id == old_id
+
+
 # ---------------------------------------------------------------------------
 # Context manager / close
 # ---------------------------------------------------------------------------
@@ -150,6 +150,9 @@ fn edge_spawn_depth_limit_enforced() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -185,6 +188,9 @@ fn edge_spawn_max_children_enforced() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
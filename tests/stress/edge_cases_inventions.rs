@@ -17,7 +17,8 @@ use agentic_identity::receipt::action::{ActionContent, ActionType};
 use agentic_identity::receipt::receipt::ReceiptBuilder;
 use agentic_identity::receipt::verify::verify_receipt;
 use agentic_identity::spawn::{
-    self, SpawnConstraints, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord, SpawnType,
+    self, SpawnConstraints, SpawnConsumption, SpawnId, SpawnInfo, SpawnLifetime, SpawnRecord,
+    SpawnType,
 };
 use agentic_identity::trust::capability::{capability_uri_covers, Capability};
 use agentic_identity::trust::chain::{validate_delegation, verify_trust_chain};
@@ -68,12 +69,19 @@ fn make_spawn_record(
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
         parent_signature: "test_sig".to_string(),
         child_acknowledgment: None,
+        signature_version: agentic_identity::crypto::signing::SIGNATURE_VERSION,
+        schema_version: agentic_identity::storage::migrate::SCHEMA_VERSION,
         terminated: false,
         terminated_at: None,
         termination_reason: None,
+        child_file: None,
+        consumption: SpawnConsumption::default(),
     }
 }
 
@@ -1220,6 +1228,9 @@ fn inv_fork_spawn_two_children_same_parent() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1270,6 +1281,9 @@ fn inv_fork_cannot_spawn_beyond_ceiling() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1308,6 +1322,9 @@ fn inv_fork_terminate_does_not_affect_sibling() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1767,6 +1784,9 @@ fn inv_cross_spawn_with_no_spawn_permission_fails() {
             max_descendants: None,
             can_spawn: false,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1802,6 +1822,9 @@ fn inv_cross_spawn_child_cannot_exceed_parent_ceiling() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1840,6 +1863,9 @@ fn inv_cross_lineage_verification() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1878,6 +1904,9 @@ fn inv_cross_effective_authority_computation() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
@@ -1962,6 +1991,9 @@ fn inv_cross_spawn_all_types() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         },
     };
 
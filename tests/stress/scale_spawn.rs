@@ -51,6 +51,9 @@ fn stress_spawn_tree_depth_5() {
             max_descendants: None,
             can_spawn: true,
             authority_decay: None,
+            max_receipts: None,
+            max_sub_spawns: None,
+            max_cost: None,
         };
 
         let (child, record, _receipt) = spawn::spawn_child(